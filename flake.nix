@@ -61,7 +61,8 @@
           inherit buildInputs runtimeDependencies ldLibraryPath;
 
           postInstall = ''
-            wrapProgram "$out/bin/ashell" --prefix LD_LIBRARY_PATH : "${ldLibraryPath}"
+            wrapProgram "$out/bin/ashell" --prefix LD_LIBRARY_PATH : "${ldLibraryPath}" \
+              --suffix PATH : "${pkgs.lib.makeBinPath [ pkgs.curl ]}"
           '';
           meta.mainProgram = "ashell";
         };
@@ -0,0 +1,115 @@
+use crate::config::{AnimationsConfig, Easing};
+use iced::{Background, Color};
+use std::time::{Duration, Instant};
+
+/// Interval of the redraws while a transition is running.
+pub const FRAME: Duration = Duration::from_millis(16);
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Transition {
+    fn new(config: &AnimationsConfig, duration: u64) -> Option<Self> {
+        (config.enabled && duration > 0).then(|| Transition {
+            duration: Duration::from_millis(duration),
+            easing: config.easing,
+        })
+    }
+
+    /// Eased progress, between 0.0 and 1.0, of a transition started at `since`.
+    pub fn progress(&self, since: Instant, now: Instant) -> f32 {
+        let t = now.saturating_duration_since(since).as_secs_f32() / self.duration.as_secs_f32();
+
+        self.easing.apply(t.clamp(0.0, 1.0))
+    }
+
+    pub fn is_running(&self, since: Instant, now: Instant) -> bool {
+        now.saturating_duration_since(since) < self.duration
+    }
+}
+
+/// Transitions of the bar, `None` when disabled.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Animations {
+    pub menu: Option<Transition>,
+    pub workspace: Option<Transition>,
+    pub hover: Option<Transition>,
+    pub reveal: Option<Transition>,
+}
+
+impl Animations {
+    pub fn new(config: &AnimationsConfig) -> Self {
+        Self {
+            menu: Transition::new(config, config.menu_duration),
+            workspace: Transition::new(config, config.workspace_duration),
+            hover: Transition::new(config, config.hover_duration),
+            reveal: Transition::new(config, config.reveal_duration),
+        }
+    }
+}
+
+/// Progress of an optional transition, completed when it's disabled or not started.
+pub fn progress(transition: Option<Transition>, since: Option<Instant>) -> f32 {
+    match (transition, since) {
+        (Some(transition), Some(since)) => transition.progress(since, Instant::now()),
+        _ => 1.0,
+    }
+}
+
+pub fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress
+}
+
+pub fn mix_color(from: Color, to: Color, progress: f32) -> Color {
+    Color {
+        r: lerp(from.r, to.r, progress),
+        g: lerp(from.g, to.g, progress),
+        b: lerp(from.b, to.b, progress),
+        a: lerp(from.a, to.a, progress),
+    }
+}
+
+/// Blends two solid backgrounds, gradients are not interpolated.
+pub fn mix_background(
+    from: Option<Background>,
+    to: Option<Background>,
+    progress: f32,
+) -> Option<Background> {
+    match (from, to) {
+        (None, None) => None,
+        (Some(Background::Color(from)), None) => Some(Background::Color(mix_color(
+            from,
+            Color::TRANSPARENT,
+            progress,
+        ))),
+        (None, Some(Background::Color(to))) => Some(Background::Color(mix_color(
+            Color::TRANSPARENT,
+            to,
+            progress,
+        ))),
+        (Some(Background::Color(from)), Some(Background::Color(to))) => {
+            Some(Background::Color(mix_color(from, to, progress)))
+        }
+        (_, to) => to,
+    }
+}
@@ -1,93 +1,314 @@
 use crate::{
-    HEIGHT, centerbox,
-    config::{self, AppearanceStyle, Config, Modules, Position},
-    get_log_spec,
+    animation::{self, mix_background},
+    centerbox,
+    chooser::{self, Chooser},
+    color_scheme::{self, ColorScheme},
+    components::icons::{self, StaticIcon, icon},
+    config::{
+        self, AppearanceMode, AppearanceStyle, Config, HooksConfig, ModuleName, Modules, Position,
+    },
+    debug_overlay::{self, DebugOverlay},
+    get_log_spec, hooks,
+    ipc::{IpcCommand, IpcReply, menu_module},
+    keybinds::{self, Keybinds},
     menu::{MenuSize, MenuType},
     modules::{
-        self,
+        self, InjectedModule,
         clock::Clock,
         custom_module::{self, Custom},
-        keyboard_layout::KeyboardLayout,
+        feeds::Feeds,
+        git_notifications::GitNotifications,
+        keyboard_layout::{self, KeyboardLayout},
         keyboard_submap::KeyboardSubmap,
         media_player::MediaPlayer,
+        peripherals::Peripherals,
         privacy::Privacy,
+        provider::{self, Providers},
+        recording::Recording,
         settings::Settings,
         system_info::SystemInfo,
+        taskbar::Taskbar,
+        tasks::Tasks,
         tray::TrayModule,
         updates::Updates,
         window_title::WindowTitle,
         workspaces::Workspaces,
     },
-    outputs::{HasOutput, Outputs},
+    osd::{self, Osd},
+    outputs::{HasOutput, Outputs, OutputsConfig},
     position_button::ButtonUIRef,
-    services::ReadOnlyService,
+    scheduler::{self, Scheduler},
+    services::{
+        ReadOnlyService, ServiceEvent,
+        appearance::{AppearanceService, ColorSchemePreference},
+        compositor::{CompositorEvent, CompositorService},
+        registry::{Consumer, ServiceMessage, Services},
+        tray,
+    },
     theme::{AshellTheme, backdrop_color, darken_color},
+    toasts::{self, Toasts},
+    utils::{truncate_text, xkb},
+    watchdog::{self, Guarded, Watchdog},
+    window_switcher::{self, WindowSwitcher},
 };
 use flexi_logger::LoggerHandle;
 use iced::{
-    Alignment, Color, Element, Gradient, Length, Radians, Subscription, Task, Theme,
+    Alignment, Border, Color, Element, Length, Point, Subscription, Task, Theme,
     daemon::Appearance,
     event::{
         listen_with,
         wayland::{Event as WaylandEvent, OutputEvent},
     },
-    gradient::Linear,
-    keyboard,
-    widget::{Row, container, mouse_area},
+    keyboard, mouse,
+    time::every,
+    touch,
+    widget::{Row, column, container, mouse_area, row, text},
     window::Id,
 };
-use log::{debug, info, warn};
-use std::{collections::HashMap, f32::consts::PI, path::PathBuf};
+use log::{debug, info};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 use wayland_client::protocol::wl_output::WlOutput;
 
+/// Interval of the checks hiding the unused auto-hidden bars.
+const AUTO_HIDE_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct GeneralConfig {
     outputs: config::Outputs,
+    appearance: config::Appearance,
     pub modules: Modules,
-    pub layer: config::Layer,
     enable_esc_key: bool,
+    pub hooks: HooksConfig,
+    pub http_port: Option<u16>,
+    pub http_allowed_origins: Vec<String>,
+    pub bars: HashMap<String, Vec<Config>>,
 }
 
 pub struct App {
     config_path: PathBuf,
+    /// Config applied last, the reloaded ones are compared with it.
+    config: Config,
     pub theme: AshellTheme,
     logger: LoggerHandle,
     pub general_config: GeneralConfig,
+    pub config_error: Option<String>,
+    color_scheme: Option<ColorScheme>,
+    system_prefers_light: bool,
     pub outputs: Outputs,
+    pub hidden_modules: Vec<ModuleName>,
+    pub injected_modules: Vec<InjectedModule>,
     pub custom: HashMap<String, Custom>,
+    pub providers: Providers,
     pub updates: Option<Updates>,
-    pub workspaces: Workspaces,
-    pub window_title: WindowTitle,
-    pub system_info: SystemInfo,
-    pub keyboard_layout: KeyboardLayout,
-    pub keyboard_submap: KeyboardSubmap,
-    pub tray: TrayModule,
-    pub clock: Clock,
-    pub privacy: Privacy,
-    pub settings: Settings,
-    pub media_player: MediaPlayer,
+    pub feeds: Option<Feeds>,
+    pub git_notifications: Option<GitNotifications>,
+    pub tasks: Option<Tasks>,
+    pub workspaces: Option<Workspaces>,
+    pub window_title: Option<WindowTitle>,
+    pub taskbar: Option<Taskbar>,
+    pub system_info: Option<SystemInfo>,
+    pub keyboard_layout: Option<KeyboardLayout>,
+    pub keyboard_submap: Option<KeyboardSubmap>,
+    pub tray: Option<TrayModule>,
+    pub clock: Option<Clock>,
+    pub privacy: Option<Privacy>,
+    pub peripherals: Option<Peripherals>,
+    pub recording: Option<Recording>,
+    pub settings: Option<Settings>,
+    pub media_player: Option<MediaPlayer>,
+    /// Finger pressed on a bar, followed to detect the swipes opening the settings menu.
+    touch_start: Option<(Id, touch::Finger, Point)>,
+    /// Module hovered by the cursor, its tooltip is shown after the delay.
+    tooltip_hover: Option<(Id, ModuleName, ButtonUIRef, Instant)>,
+    /// Options piped by `ashell menu`, waiting for a pick.
+    pub chooser: Option<Chooser>,
+    pub window_switcher: WindowSwitcher,
+    pub toasts: Toasts,
+    pub osd: Osd,
+    pub keybinds: Keybinds,
+    /// Backends shared by the modules, the bars and the popups.
+    services: Services,
+    pub scheduler: Scheduler,
+    pub debug_overlay: Option<DebugOverlay>,
+    watchdog: Option<Watchdog>,
+    /// Modules disabled after a panic in their update or view, with the
+    /// panic message. Filled from the views, hence the cell.
+    pub crashed_modules: RefCell<Vec<(ModuleName, String)>>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     None,
     ConfigChanged(Box<Config>),
+    ConfigError(String),
+    ColorSchemeChanged(ColorScheme),
+    SystemAppearance(ServiceEvent<AppearanceService>),
+    ReloadConfig,
+    ToggleVisibility,
+    BarHovered(Id, bool),
+    Touch(Id, touch::Event),
+    AutoHide,
+    Fullscreen(ServiceEvent<CompositorService>),
     ToggleMenu(MenuType, Id, ButtonUIRef),
+    TooltipHover(Id, ModuleName, ButtonUIRef),
+    TooltipLeave(Id),
+    ShowTooltip(Id, ModuleName),
+    OpenTooltip(Id, ModuleName, String),
     CloseMenu(Id),
     Custom(String, custom_module::Message),
+    Provider(provider::Message),
     Updates(modules::updates::Message),
+    Feeds(modules::feeds::Message),
+    GitNotifications(modules::git_notifications::Message),
+    Tasks(modules::tasks::Message),
     Workspaces(modules::workspaces::Message),
     WindowTitle(modules::window_title::Message),
+    Taskbar(modules::taskbar::Message),
     SystemInfo(modules::system_info::Message),
     KeyboardLayout(modules::keyboard_layout::Message),
     KeyboardSubmap(modules::keyboard_submap::Message),
     Tray(modules::tray::Message),
     Clock(modules::clock::Message),
     Privacy(modules::privacy::Message),
+    Peripherals(modules::peripherals::Message),
+    Recording(modules::recording::Message),
     Settings(modules::settings::Message),
     MediaPlayer(modules::media_player::Message),
+    Chooser(chooser::Message),
+    WindowSwitcher(window_switcher::Message),
+    Toasts(toasts::Message),
+    Osd(osd::Message),
+    Keybinds(keybinds::Message),
+    Services(ServiceMessage),
+    Scheduler(scheduler::Message),
+    DebugOverlay(debug_overlay::Message),
+    Watchdog(watchdog::Message),
+    RestartModule(ModuleName),
     OutputEvent((OutputEvent, WlOutput)),
     CloseAllMenus,
     ResumeFromSleep,
+    Ipc(IpcCommand, IpcReply),
+}
+
+impl Message {
+    /// Part of the bar the message is meant for, counted by the debug overlay.
+    pub fn source(&self) -> &'static str {
+        match self {
+            Message::None => "none",
+            Message::ConfigChanged(_) | Message::ConfigError(_) | Message::ReloadConfig => "config",
+            Message::ColorSchemeChanged(_) | Message::SystemAppearance(_) => "appearance",
+            Message::ToggleVisibility
+            | Message::BarHovered(..)
+            | Message::Touch(..)
+            | Message::AutoHide
+            | Message::Fullscreen(_)
+            | Message::OutputEvent(_) => "outputs",
+            Message::ToggleMenu(..)
+            | Message::TooltipHover(..)
+            | Message::TooltipLeave(_)
+            | Message::ShowTooltip(..)
+            | Message::OpenTooltip(..)
+            | Message::CloseMenu(_)
+            | Message::CloseAllMenus
+            | Message::RestartModule(_) => "menus",
+            Message::Custom(..) => "custom",
+            Message::Provider(_) => "external_modules",
+            Message::Updates(_) => "updates",
+            Message::Feeds(_) => "feeds",
+            Message::GitNotifications(_) => "git_notifications",
+            Message::Tasks(_) => "tasks",
+            Message::Workspaces(_) => "workspaces",
+            Message::WindowTitle(_) => "window_title",
+            Message::Taskbar(_) => "taskbar",
+            Message::SystemInfo(_) => "system_info",
+            Message::KeyboardLayout(_) => "keyboard_layout",
+            Message::KeyboardSubmap(_) => "keyboard_submap",
+            Message::Tray(_) => "tray",
+            Message::Clock(_) => "clock",
+            Message::Privacy(_) => "privacy",
+            Message::Peripherals(_) => "peripherals",
+            Message::Recording(_) => "recording",
+            Message::Settings(_) => "settings",
+            Message::MediaPlayer(_) => "media_player",
+            Message::Chooser(_) => "chooser",
+            Message::WindowSwitcher(_) => "window_switcher",
+            Message::Toasts(_) => "notifications",
+            Message::Osd(_) => "osd",
+            Message::Keybinds(_) => "keybinds",
+            Message::Services(_) => "services",
+            Message::Scheduler(_) => "scheduler",
+            Message::DebugOverlay(_) => "debug_overlay",
+            Message::Watchdog(_) => "watchdog",
+            Message::ResumeFromSleep => "logind",
+            Message::Ipc(..) => "ipc",
+        }
+    }
+
+    /// Module the message is meant for, dropped while the module is
+    /// disabled after a panic.
+    fn module(&self) -> Option<ModuleName> {
+        Some(match self {
+            Message::Custom(name, _) => ModuleName::Custom(name.clone()),
+            Message::Updates(_) => ModuleName::Updates,
+            Message::Feeds(_) => ModuleName::Feeds,
+            Message::GitNotifications(_) => ModuleName::GitNotifications,
+            Message::Tasks(_) => ModuleName::Tasks,
+            Message::Workspaces(_) => ModuleName::Workspaces,
+            Message::WindowTitle(_) => ModuleName::WindowTitle,
+            Message::Taskbar(_) => ModuleName::Taskbar,
+            Message::SystemInfo(_) => ModuleName::SystemInfo,
+            Message::KeyboardLayout(_) => ModuleName::KeyboardLayout,
+            Message::KeyboardSubmap(_) => ModuleName::KeyboardSubmap,
+            Message::Tray(_) => ModuleName::Tray,
+            Message::Clock(_) => ModuleName::Clock,
+            Message::Privacy(_) => ModuleName::Privacy,
+            Message::Peripherals(_) => ModuleName::Peripherals,
+            Message::Recording(_) => ModuleName::Recording,
+            Message::Settings(_) => ModuleName::Settings,
+            Message::MediaPlayer(_) => ModuleName::MediaPlayer,
+            _ => return None,
+        })
+    }
+}
+
+/// Rebuilds an optional module when its config changed, or when it joined
+/// or left the layout.
+fn rebuild<C, M>(
+    module: &mut Option<M>,
+    changed: bool,
+    in_layout: bool,
+    config: Option<C>,
+    build: impl FnOnce(C) -> M,
+) {
+    let config = config.filter(|_| in_layout);
+
+    if changed || module.is_some() != config.is_some() {
+        *module = config.map(build);
+    }
+}
+
+/// Hands the changed config to a module of the layout, keeping its state,
+/// and builds or drops it when it joined or left the layout.
+fn reconfigure<C, M>(
+    module: &mut Option<M>,
+    changed: bool,
+    in_layout: bool,
+    config: C,
+    build: impl FnOnce(C) -> M,
+    reload: impl FnOnce(&mut M, C),
+) {
+    match module {
+        Some(_) if !in_layout => *module = None,
+        Some(built) if changed => reload(built, config),
+        Some(_) => {}
+        None if in_layout => *module = Some(build(config)),
+        None => {}
+    }
 }
 
 impl App {
@@ -95,12 +316,27 @@ impl App {
         (logger, config, config_path): (LoggerHandle, Config, PathBuf),
     ) -> impl FnOnce() -> (Self, Task<Message>) {
         move || {
-            let (outputs, task) = Outputs::new(
-                config.appearance.style,
-                config.position,
-                config.layer,
-                config.appearance.scale_factor,
+            let (outputs, task) = Outputs::new(OutputsConfig::new(&config));
+            let applied = config.clone();
+            // the modules outside of the layout aren't built, their
+            // services would run for nothing
+            let layout = modules::layout_modules(
+                std::iter::once(&config.modules).chain(
+                    config
+                        .bar_configs
+                        .values()
+                        .flatten()
+                        .map(|bar| &bar.modules),
+                ),
+                &[],
             );
+            // the slow indexes are built in the background, the bar doesn't
+            // wait for them to show up
+            xkb::preload();
+            tray::set_cache_limits(&config.cache);
+            if layout.contains(&ModuleName::Tray) {
+                tray::preload_icons();
+            }
 
             let custom = config
                 .custom_modules
@@ -109,83 +345,571 @@ impl App {
                 .map(|o| (o.name.clone(), Custom::new(o)))
                 .collect();
 
+            let color_scheme = color_scheme::load_source(&config.appearance.theme_source);
+            icons::set_icons(&config.appearance.icons);
+            let mut services = Services::default();
+
             (
                 App {
                     config_path,
-                    theme: AshellTheme::new(config.position, &config.appearance),
+                    config: applied,
+                    theme: AshellTheme::new(
+                        config.position,
+                        &config.appearance.with_mode(false),
+                        color_scheme.as_ref(),
+                    ),
                     logger,
                     general_config: GeneralConfig {
                         outputs: config.outputs,
+                        appearance: config.appearance,
                         modules: config.modules,
-                        layer: config.layer,
                         enable_esc_key: config.enable_esc_key,
+                        hooks: config.hooks,
+                        http_port: config.http_port,
+                        http_allowed_origins: config.http_allowed_origins,
+                        bars: config.bar_configs,
                     },
+                    config_error: None,
+                    color_scheme,
+                    system_prefers_light: false,
                     outputs,
+                    hidden_modules: modules::hidden_modules(),
+                    injected_modules: Vec::new(),
                     custom,
-                    updates: config.updates.map(Updates::new),
-                    workspaces: Workspaces::new(config.workspaces),
-                    window_title: WindowTitle::new(config.window_title),
-                    system_info: SystemInfo::new(config.system_info),
-                    keyboard_layout: KeyboardLayout::new(config.keyboard_layout),
-                    keyboard_submap: KeyboardSubmap::default(),
-                    tray: TrayModule::default(),
-                    clock: Clock::new(config.clock),
-                    privacy: Privacy::default(),
-                    settings: Settings::new(config.settings),
-                    media_player: MediaPlayer::new(config.media_player),
+                    providers: Providers::default(),
+                    updates: config
+                        .updates
+                        .filter(|_| layout.contains(&ModuleName::Updates))
+                        .map(|updates| Updates::new(updates, config.settings.reboot_cmd.clone())),
+                    feeds: config
+                        .feeds
+                        .filter(|_| layout.contains(&ModuleName::Feeds))
+                        .map(Feeds::new),
+                    git_notifications: config
+                        .git_notifications
+                        .filter(|_| layout.contains(&ModuleName::GitNotifications))
+                        .map(GitNotifications::new),
+                    tasks: config
+                        .tasks
+                        .filter(|_| layout.contains(&ModuleName::Tasks))
+                        .map(Tasks::new),
+                    workspaces: layout
+                        .contains(&ModuleName::Workspaces)
+                        .then(|| Workspaces::new(config.workspaces)),
+                    window_title: layout
+                        .contains(&ModuleName::WindowTitle)
+                        .then(|| WindowTitle::new(config.window_title)),
+                    taskbar: layout
+                        .contains(&ModuleName::Taskbar)
+                        .then(|| Taskbar::new(config.taskbar)),
+                    system_info: layout
+                        .contains(&ModuleName::SystemInfo)
+                        .then(|| SystemInfo::new(config.system_info, services.system())),
+                    keyboard_layout: layout
+                        .contains(&ModuleName::KeyboardLayout)
+                        .then(|| KeyboardLayout::new(config.keyboard_layout)),
+                    keyboard_submap: layout
+                        .contains(&ModuleName::KeyboardSubmap)
+                        .then(|| KeyboardSubmap::new(config.keyboard_submap)),
+                    tray: layout.contains(&ModuleName::Tray).then(TrayModule::default),
+                    clock: layout
+                        .contains(&ModuleName::Clock)
+                        .then(|| Clock::new(config.clock)),
+                    privacy: layout
+                        .contains(&ModuleName::Privacy)
+                        .then(|| Privacy::new(config.privacy)),
+                    peripherals: layout
+                        .contains(&ModuleName::Peripherals)
+                        .then(|| Peripherals::new(config.peripherals)),
+                    recording: layout
+                        .contains(&ModuleName::Recording)
+                        .then(|| Recording::new(config.recording, services.system())),
+                    settings: layout
+                        .contains(&ModuleName::Settings)
+                        .then(|| Settings::new(config.settings)),
+                    media_player: layout
+                        .contains(&ModuleName::MediaPlayer)
+                        .then(|| MediaPlayer::new(config.media_player)),
+                    touch_start: None,
+                    tooltip_hover: None,
+                    chooser: None,
+                    window_switcher: WindowSwitcher::default(),
+                    toasts: Toasts::new(config.notifications),
+                    osd: Osd::new(config.osd),
+                    keybinds: Keybinds::new(config.keybinds),
+                    scheduler: Scheduler::new(config.power_saving),
+                    debug_overlay: debug_overlay::set_enabled(config.debug_overlay)
+                        .then(|| DebugOverlay::new(services.system())),
+                    services,
+                    watchdog: config.watchdog.then(Watchdog::new),
+                    crashed_modules: RefCell::new(Vec::new()),
                 },
                 task,
             )
         }
     }
 
+    /// Applies a reloaded config, only the parts whose section changed are
+    /// rebuilt so that the others keep their state.
     fn refesh_config(&mut self, config: Box<Config>) {
+        let old = std::mem::replace(&mut self.config, (*config).clone());
+
+        if self.general_config.appearance.theme_source != config.appearance.theme_source {
+            self.color_scheme = color_scheme::load_source(&config.appearance.theme_source);
+        }
+        if old.position != config.position || config::changed(&old.appearance, &config.appearance) {
+            icons::set_icons(&config.appearance.icons);
+            self.theme = AshellTheme::new(
+                config.position,
+                &config.appearance.with_mode(self.system_prefers_light),
+                self.color_scheme.as_ref(),
+            );
+        }
         self.general_config = GeneralConfig {
             outputs: config.outputs,
+            appearance: config.appearance,
             modules: config.modules,
-            layer: config.layer,
             enable_esc_key: config.enable_esc_key,
+            hooks: config.hooks,
+            http_port: config.http_port,
+            http_allowed_origins: config.http_allowed_origins,
+            bars: config.bar_configs,
         };
-        self.theme = AshellTheme::new(config.position, &config.appearance);
-        let custom = config
+
+        let mut previous = std::mem::take(&mut self.custom);
+        self.custom = config
             .custom_modules
             .into_iter()
-            .map(|o| (o.name.clone(), Custom::new(o)))
+            .chain(
+                self.injected_modules
+                    .iter()
+                    .map(|module| module.config.clone()),
+            )
+            .map(|o| {
+                let name = o.name.clone();
+                let custom = match previous.remove(&name) {
+                    Some(custom) if !config::changed(custom.config(), &o) => custom,
+                    _ => Custom::new(o),
+                };
+
+                (name, custom)
+            })
             .collect();
 
-        self.custom = custom;
-        self.updates = config.updates.map(Updates::new);
-
-        // ignore task, since config change should not generate any
-        let _ = self
-            .workspaces
-            .update(modules::workspaces::Message::ConfigReloaded(
-                config.workspaces,
-            ))
-            .map(Message::Workspaces);
-
-        self.window_title
-            .update(modules::window_title::Message::ConfigReloaded(
-                config.window_title,
-            ));
-
-        self.system_info = SystemInfo::new(config.system_info);
-
-        let _ = self
-            .keyboard_layout
-            .update(modules::keyboard_layout::Message::ConfigReloaded(
-                config.keyboard_layout,
-            ))
-            .map(Message::KeyboardLayout);
-
-        self.keyboard_submap = KeyboardSubmap::default();
-        self.clock = Clock::new(config.clock);
-        self.settings
-            .update(modules::settings::Message::ConfigReloaded(config.settings));
-        self.media_player
-            .update(modules::media_player::Message::ConfigReloaded(
-                config.media_player,
-            ));
+        // the modules removed from the layout are dropped with the state of
+        // their services, the ones joining it are built
+        let layout = self.layout_modules();
+        let in_layout = |module: ModuleName| layout.contains(&module);
+
+        rebuild(
+            &mut self.updates,
+            config::changed(&old.updates, &config.updates)
+                || old.settings.reboot_cmd != config.settings.reboot_cmd,
+            in_layout(ModuleName::Updates),
+            config.updates,
+            |updates| Updates::new(updates, config.settings.reboot_cmd.clone()),
+        );
+        rebuild(
+            &mut self.feeds,
+            config::changed(&old.feeds, &config.feeds),
+            in_layout(ModuleName::Feeds),
+            config.feeds,
+            Feeds::new,
+        );
+        rebuild(
+            &mut self.git_notifications,
+            config::changed(&old.git_notifications, &config.git_notifications),
+            in_layout(ModuleName::GitNotifications),
+            config.git_notifications,
+            GitNotifications::new,
+        );
+        rebuild(
+            &mut self.tasks,
+            config::changed(&old.tasks, &config.tasks),
+            in_layout(ModuleName::Tasks),
+            config.tasks,
+            Tasks::new,
+        );
+        rebuild(
+            &mut self.system_info,
+            config::changed(&old.system_info, &config.system_info),
+            in_layout(ModuleName::SystemInfo),
+            Some(config.system_info),
+            |config| SystemInfo::new(config, self.services.system()),
+        );
+
+        reconfigure(
+            &mut self.workspaces,
+            config::changed(&old.workspaces, &config.workspaces),
+            in_layout(ModuleName::Workspaces),
+            config.workspaces,
+            Workspaces::new,
+            |workspaces, config| {
+                // ignore task, since config change should not generate any
+                let _ = workspaces.update(modules::workspaces::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.window_title,
+            config::changed(&old.window_title, &config.window_title),
+            in_layout(ModuleName::WindowTitle),
+            config.window_title,
+            WindowTitle::new,
+            |window_title, config| {
+                window_title.update(modules::window_title::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.taskbar,
+            config::changed(&old.taskbar, &config.taskbar),
+            in_layout(ModuleName::Taskbar),
+            config.taskbar,
+            Taskbar::new,
+            |taskbar, config| {
+                let _ = taskbar.update(modules::taskbar::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.keyboard_layout,
+            config::changed(&old.keyboard_layout, &config.keyboard_layout),
+            in_layout(ModuleName::KeyboardLayout),
+            config.keyboard_layout,
+            KeyboardLayout::new,
+            |keyboard_layout, config| {
+                let _ = keyboard_layout
+                    .update(modules::keyboard_layout::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.keyboard_submap,
+            config::changed(&old.keyboard_submap, &config.keyboard_submap),
+            in_layout(ModuleName::KeyboardSubmap),
+            config.keyboard_submap,
+            KeyboardSubmap::new,
+            |keyboard_submap, config| {
+                keyboard_submap.update(modules::keyboard_submap::Message::ConfigReloaded(config));
+            },
+        );
+        if self.tray.is_some() != in_layout(ModuleName::Tray) {
+            self.tray = in_layout(ModuleName::Tray).then(TrayModule::default);
+        }
+        rebuild(
+            &mut self.clock,
+            config::changed(&old.clock, &config.clock),
+            in_layout(ModuleName::Clock),
+            Some(config.clock),
+            Clock::new,
+        );
+        reconfigure(
+            &mut self.privacy,
+            config::changed(&old.privacy, &config.privacy),
+            in_layout(ModuleName::Privacy),
+            config.privacy,
+            Privacy::new,
+            |privacy, config| {
+                privacy.update(modules::privacy::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.peripherals,
+            config::changed(&old.peripherals, &config.peripherals),
+            in_layout(ModuleName::Peripherals),
+            config.peripherals,
+            Peripherals::new,
+            |peripherals, config| {
+                peripherals.update(modules::peripherals::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.recording,
+            config::changed(&old.recording, &config.recording),
+            in_layout(ModuleName::Recording),
+            config.recording,
+            |config| Recording::new(config, self.services.system()),
+            |recording, config| {
+                recording.update(modules::recording::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.settings,
+            config::changed(&old.settings, &config.settings),
+            in_layout(ModuleName::Settings),
+            config.settings,
+            Settings::new,
+            |settings, config| {
+                settings.update(modules::settings::Message::ConfigReloaded(config));
+            },
+        );
+        reconfigure(
+            &mut self.media_player,
+            config::changed(&old.media_player, &config.media_player),
+            in_layout(ModuleName::MediaPlayer),
+            config.media_player,
+            MediaPlayer::new,
+            |media_player, config| {
+                media_player.update(modules::media_player::Message::ConfigReloaded(config));
+            },
+        );
+        if config::changed(&old.notifications, &config.notifications) {
+            self.toasts
+                .update(toasts::Message::ConfigReloaded(config.notifications));
+        }
+        if config::changed(&old.osd, &config.osd) {
+            self.osd.update(osd::Message::ConfigReloaded(config.osd));
+        }
+        if config::changed(&old.keybinds, &config.keybinds) {
+            let _ = self
+                .keybinds
+                .update(keybinds::Message::ConfigReloaded(config.keybinds));
+        }
+        self.scheduler.set_enabled(config.power_saving);
+        tray::set_cache_limits(&config.cache);
+        if debug_overlay::set_enabled(config.debug_overlay) != self.debug_overlay.is_some() {
+            self.debug_overlay = self
+                .debug_overlay
+                .is_none()
+                .then(|| DebugOverlay::new(self.services.system()));
+        }
+        if config.watchdog != self.watchdog.is_some() {
+            self.watchdog = config.watchdog.then(Watchdog::new);
+        }
+    }
+
+    /// Fits the surface of the notifications to the toasts on screen.
+    fn sync_toasts(&mut self) -> Task<Message> {
+        let output = self
+            .toasts
+            .output()
+            .and_then(|name| self.outputs.wl_output(name));
+
+        self.toasts.sync_surface(output)
+    }
+
+    /// Parts of the bar fed by the shared services, each service runs while
+    /// at least one of them is there.
+    fn service_consumers(&self) -> Vec<Consumer> {
+        let mut consumers = Vec::new();
+        if self.window_switcher_open() {
+            consumers.push(Consumer::WindowSwitcher);
+        }
+
+        for module in self.layout_modules() {
+            match module {
+                ModuleName::Workspaces => consumers.push(Consumer::Workspaces),
+                ModuleName::WindowTitle => consumers.push(Consumer::WindowTitle),
+                ModuleName::Taskbar => consumers.push(Consumer::Taskbar),
+                ModuleName::KeyboardLayout => consumers.push(Consumer::KeyboardLayout),
+                ModuleName::KeyboardSubmap => consumers.push(Consumer::KeyboardSubmap),
+                ModuleName::Privacy => consumers.push(Consumer::Privacy),
+                ModuleName::Peripherals => consumers.push(Consumer::Peripherals),
+                ModuleName::MediaPlayer => consumers.push(Consumer::MediaPlayer),
+                ModuleName::Recording
+                    if self
+                        .recording
+                        .as_ref()
+                        .is_some_and(Recording::screencasts_enabled) =>
+                {
+                    consumers.push(Consumer::Recording)
+                }
+                ModuleName::Settings => consumers.extend([
+                    Consumer::SettingsAudio,
+                    Consumer::SettingsBrightness,
+                    Consumer::SettingsPower,
+                    Consumer::SettingsNetwork,
+                    Consumer::SettingsBluetooth,
+                ]),
+                _ => {}
+            }
+        }
+        if self.outputs.hides_on_fullscreen() {
+            consumers.push(Consumer::HideOnFullscreen);
+        }
+        consumers.extend(self.toasts.consumers());
+        consumers.extend(self.osd.consumers());
+        consumers.extend(self.keybinds.consumers());
+
+        consumers
+    }
+
+    /// Whether the window switcher is open on one of the bars.
+    pub fn window_switcher_open(&self) -> bool {
+        self.outputs
+            .bars()
+            .any(|(_, menu)| menu == Some(&MenuType::Windows))
+    }
+
+    /// Starts or stops feeding the window switcher once its menu has been
+    /// opened or closed.
+    pub fn sync_window_switcher(&mut self, was_open: bool) -> Task<Message> {
+        if self.window_switcher_open() == was_open {
+            return Task::none();
+        }
+
+        let mut previous = self.service_consumers();
+        if was_open {
+            previous.push(Consumer::WindowSwitcher);
+        } else {
+            previous.retain(|consumer| *consumer != Consumer::WindowSwitcher);
+        }
+
+        self.resync_services(&previous)
+    }
+
+    /// Hands an event of a shared service to one of its consumers.
+    fn deliver(&mut self, consumer: Consumer, message: ServiceMessage) -> Task<Message> {
+        let message = match (consumer, message) {
+            (Consumer::Workspaces, ServiceMessage::Compositor(event)) => {
+                Message::Workspaces(modules::workspaces::Message::ServiceEvent(event))
+            }
+            (Consumer::WindowTitle, ServiceMessage::Compositor(event)) => {
+                Message::WindowTitle(modules::window_title::Message::ServiceEvent(event))
+            }
+            (Consumer::Taskbar, ServiceMessage::Compositor(event)) => {
+                Message::Taskbar(modules::taskbar::Message::ServiceEvent(event))
+            }
+            (Consumer::KeyboardLayout, ServiceMessage::Compositor(event)) => {
+                Message::KeyboardLayout(keyboard_layout::Message::ServiceEvent(event))
+            }
+            (Consumer::KeyboardSubmap, ServiceMessage::Compositor(event)) => {
+                Message::KeyboardSubmap(modules::keyboard_submap::Message::ServiceEvent(event))
+            }
+            (Consumer::WindowSwitcher, ServiceMessage::Compositor(event)) => {
+                Message::WindowSwitcher(window_switcher::Message::Event(event))
+            }
+            (Consumer::HideOnFullscreen, ServiceMessage::Compositor(event)) => {
+                Message::Fullscreen(event)
+            }
+            (Consumer::DndFullscreen, ServiceMessage::Compositor(event)) => {
+                Message::Toasts(toasts::Message::Compositor(event))
+            }
+            (Consumer::Keybinds, ServiceMessage::Compositor(event)) => {
+                Message::Keybinds(keybinds::Message::Compositor(event))
+            }
+            (Consumer::Privacy, ServiceMessage::Privacy(event)) => {
+                Message::Privacy(modules::privacy::Message::Event(event))
+            }
+            (Consumer::Recording, ServiceMessage::Privacy(event)) => {
+                Message::Recording(modules::recording::Message::Privacy(event))
+            }
+            (Consumer::DndScreenSharing, ServiceMessage::Privacy(event)) => {
+                Message::Toasts(toasts::Message::Privacy(event))
+            }
+            (Consumer::SettingsAudio, ServiceMessage::Audio(event)) => Message::Settings(
+                modules::settings::Message::Audio(modules::settings::audio::Message::Event(event)),
+            ),
+            (Consumer::OsdVolume, ServiceMessage::Audio(event)) => {
+                Message::Osd(osd::Message::Audio(event))
+            }
+            (Consumer::SettingsBrightness, ServiceMessage::Brightness(event)) => {
+                Message::Settings(modules::settings::Message::Brightness(
+                    modules::settings::brightness::Message::Event(event),
+                ))
+            }
+            (Consumer::OsdBrightness, ServiceMessage::Brightness(event)) => {
+                Message::Osd(osd::Message::Brightness(event))
+            }
+            (Consumer::SettingsPower, ServiceMessage::UPower(event)) => Message::Settings(
+                modules::settings::Message::Power(modules::settings::power::Message::Event(event)),
+            ),
+            (Consumer::Peripherals, ServiceMessage::UPower(event)) => {
+                Message::Peripherals(modules::peripherals::Message::Event(event))
+            }
+            (Consumer::SettingsNetwork, ServiceMessage::Network(event)) => {
+                Message::Settings(modules::settings::Message::Network(
+                    modules::settings::network::Message::Event(event),
+                ))
+            }
+            (Consumer::SettingsBluetooth, ServiceMessage::Bluetooth(event)) => {
+                Message::Settings(modules::settings::Message::Bluetooth(
+                    modules::settings::bluetooth::Message::Event(event),
+                ))
+            }
+            (Consumer::MediaPlayer, ServiceMessage::Mpris(event)) => {
+                Message::MediaPlayer(modules::media_player::Message::Event(event))
+            }
+            _ => return Task::none(),
+        };
+
+        self.update(message)
+    }
+
+    /// After a config reload or a change of the open menus, forgets the
+    /// services left without consumers and brings the new consumers up to
+    /// date with the running ones.
+    fn resync_services(&mut self, previous: &[Consumer]) -> Task<Message> {
+        let consumers = self.service_consumers();
+        self.services.retain(&consumers);
+
+        let tasks = consumers
+            .into_iter()
+            .filter(|consumer| !previous.contains(consumer))
+            .filter_map(|consumer| {
+                self.services
+                    .init(consumer.backend())
+                    .map(|message| self.deliver(consumer, message))
+            })
+            .collect::<Vec<_>>();
+
+        Task::batch(tasks)
+    }
+
+    /// Shows or hides the surface of the on-screen display.
+    fn sync_osd(&mut self) -> Task<Message> {
+        let output = self
+            .osd
+            .output()
+            .and_then(|name| self.outputs.wl_output(name));
+
+        self.osd.sync_surface(output)
+    }
+
+    /// Shows or hides the surface of the bindings of the active submap.
+    fn sync_keybinds(&mut self) -> Task<Message> {
+        let output = self
+            .keybinds
+            .output()
+            .and_then(|name| self.outputs.wl_output(name));
+
+        self.keybinds.sync_surface(output)
+    }
+
+    fn refresh_theme(&mut self) {
+        self.theme = AshellTheme::new(
+            self.theme.bar_position,
+            &self
+                .general_config
+                .appearance
+                .with_mode(self.system_prefers_light),
+            self.color_scheme.as_ref(),
+        );
+    }
+
+    fn reload_config(&self) -> Task<Message> {
+        let path = self.config_path.clone();
+
+        Task::perform(async move { config::read_config(&path) }, |res| match res {
+            Ok(config) => Message::ConfigChanged(Box::new(config)),
+            Err(e) => Message::ConfigError(e.to_string()),
+        })
+    }
+
+    /// Selects the config profile and reloads the config to apply it.
+    pub fn switch_profile(&self, name: &str) -> Task<Message> {
+        config::set_active_profile(name);
+
+        self.reload_config()
+    }
+
+    /// Returns the config of the bar with the given id, when its output has overrides.
+    pub fn bar_config(&self, id: Id) -> Option<&Config> {
+        let (name, index) = self.outputs.bar_index(id)?;
+
+        self.general_config.bars.get(name)?.get(index)
+    }
+
+    /// Edge of the screen of the bar with the given id.
+    pub fn bar_position(&self, id: Id) -> Position {
+        self.outputs.position(id).unwrap_or(self.theme.bar_position)
     }
 
     pub fn title(&self, _id: Id) -> String {
@@ -204,41 +928,105 @@ impl App {
         }
     }
 
-    pub fn scale_factor(&self, _id: Id) -> f64 {
-        self.theme.scale_factor
+    pub fn scale_factor(&self, id: Id) -> f64 {
+        self.outputs
+            .scale_factor(id)
+            .unwrap_or(self.theme.scale_factor)
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        let Some(module) = message.module() else {
+            return self.handle(message);
+        };
+        if self.crash_message(&module).is_some() {
+            return Task::none();
+        }
+
+        panic::catch_unwind(AssertUnwindSafe(|| self.handle(message))).unwrap_or_else(|payload| {
+            self.record_crash(&module, payload);
+
+            Task::none()
+        })
+    }
+
+    fn handle(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::None => Task::none(),
             Message::ConfigChanged(config) => {
                 info!("New config: {config:?}");
-                let mut tasks = Vec::new();
+                self.config_error = None;
+                // a new config is a new chance for the crashed modules
+                self.crashed_modules.get_mut().clear();
                 info!(
                     "Current outputs: {:?}, new outputs: {:?}",
                     self.general_config.outputs, config.outputs
                 );
-                if self.general_config.outputs != config.outputs
-                    || self.theme.bar_position != config.position
-                    || self.theme.bar_style != config.appearance.style
-                    || self.theme.scale_factor != config.appearance.scale_factor
-                    || self.general_config.layer != config.layer
-                {
-                    warn!("Outputs changed, syncing");
-                    tasks.push(self.outputs.sync(
-                        config.appearance.style,
-                        &config.outputs,
-                        config.position,
-                        config.layer,
-                        config.appearance.scale_factor,
-                    ));
-                }
+                let task = self.outputs.set_config(OutputsConfig::new(&config));
 
                 self.logger.set_new_spec(get_log_spec(&config.log_level));
+                let consumers = self.service_consumers();
                 self.refesh_config(config);
+                hooks::config_reloaded(&self.general_config.hooks, &self.config_path, None);
 
-                Task::batch(tasks)
+                Task::batch(vec![
+                    task,
+                    self.resync_services(&consumers),
+                    self.sync_toasts(),
+                    self.sync_osd(),
+                    self.sync_keybinds(),
+                ])
+            }
+            Message::ConfigError(error) => {
+                hooks::config_reloaded(&self.general_config.hooks, &self.config_path, Some(&error));
+                self.config_error = Some(error);
+
+                Task::none()
+            }
+            Message::ColorSchemeChanged(color_scheme) => {
+                self.color_scheme = Some(color_scheme);
+                self.refresh_theme();
+
+                Task::none()
             }
+            Message::SystemAppearance(event) => {
+                let color_scheme = match event {
+                    ServiceEvent::Init(service) => service.color_scheme,
+                    ServiceEvent::Update(color_scheme) => color_scheme,
+                    ServiceEvent::Error(_) => ColorSchemePreference::NoPreference,
+                };
+                let prefers_light = color_scheme == ColorSchemePreference::Light;
+
+                if self.system_prefers_light != prefers_light {
+                    self.system_prefers_light = prefers_light;
+                    self.refresh_theme();
+                }
+
+                Task::none()
+            }
+            Message::ReloadConfig => self.reload_config(),
+            Message::ToggleVisibility => {
+                let task = self.outputs.toggle_visibility();
+                self.scheduler.set_hidden(self.outputs.is_hidden());
+
+                task
+            }
+            Message::BarHovered(id, hovered) => self.outputs.set_hovered(id, hovered),
+            Message::AutoHide => self.outputs.update_auto_hide(),
+            Message::Touch(id, event) => self.swipe(id, event),
+            Message::Fullscreen(
+                ServiceEvent::Init(CompositorService { state, .. })
+                | ServiceEvent::Update(CompositorEvent::StateChanged(state)),
+            ) => {
+                let monitors = state
+                    .monitors
+                    .iter()
+                    .filter(|monitor| monitor.fullscreen)
+                    .map(|monitor| monitor.name.as_str())
+                    .collect::<Vec<_>>();
+
+                self.outputs.set_fullscreen(&monitors)
+            }
+            Message::Fullscreen(_) => Task::none(),
             Message::ToggleMenu(menu_type, id, button_ui_ref) => {
                 let mut cmd = vec![];
                 match &menu_type {
@@ -248,47 +1036,131 @@ impl App {
                         }
                     }
                     MenuType::Tray(name) => {
-                        self.tray
-                            .update(modules::tray::Message::MenuOpened(name.clone()));
+                        if let Some(tray) = self.tray.as_mut() {
+                            tray.update(modules::tray::Message::MenuOpened(name.clone()));
+                        }
+                    }
+                    MenuType::Custom(name) => {
+                        if let Some(menu_cmd) = self.custom.get(name).and_then(Custom::menu_cmd) {
+                            let name = name.clone();
+                            cmd.push(Task::perform(
+                                custom_module::menu_output(menu_cmd.clone()),
+                                move |entries| {
+                                    Message::Custom(
+                                        name.clone(),
+                                        custom_module::Message::MenuLoaded(entries),
+                                    )
+                                },
+                            ));
+                        }
+                    }
+                    MenuType::Windows => {
+                        cmd.push(self.window_switcher.open().map(Message::WindowSwitcher));
                     }
                     MenuType::Settings => {
-                        cmd.push(
-                            match self.settings.update(modules::settings::Message::MenuOpened) {
-                                modules::settings::Action::Command(task) => {
-                                    task.map(Message::Settings)
-                                }
-                                _ => Task::none(),
-                            },
-                        );
+                        if let Some(settings) = self.settings.as_mut()
+                            && let modules::settings::Action::Command(task) =
+                                settings.update(modules::settings::Message::MenuOpened)
+                        {
+                            cmd.push(task.map(Message::Settings));
+                        }
                     }
                     _ => {}
                 };
-                cmd.push(self.outputs.toggle_menu(
-                    id,
-                    menu_type,
-                    button_ui_ref,
-                    self.general_config.enable_esc_key,
-                ));
+                // the window switcher and the notification search need the keyboard
+                let request_keyboard = self.general_config.enable_esc_key
+                    || matches!(menu_type, MenuType::Windows | MenuType::Notifications);
+                cmd.push(
+                    self.outputs
+                        .toggle_menu(id, menu_type, button_ui_ref, request_keyboard),
+                );
 
                 Task::batch(cmd)
             }
+            Message::TooltipHover(id, module_name, button_ui_ref) => {
+                // the menu surface would cover a bar not reserving its space
+                if !self.outputs.is_exclusive(id) {
+                    return Task::none();
+                }
+
+                self.tooltip_hover = Some((id, module_name.clone(), button_ui_ref, Instant::now()));
+
+                Task::perform(
+                    tokio::time::sleep(Duration::from_millis(self.theme.tooltip.delay)),
+                    move |_| Message::ShowTooltip(id, module_name.clone()),
+                )
+            }
+            Message::TooltipLeave(id) => {
+                self.tooltip_hover = None;
+
+                self.outputs.close_tooltip(id)
+            }
+            Message::ShowTooltip(id, module_name) => {
+                // the cursor left the module, or came back to it since
+                if !self.is_tooltip_hovered(id, &module_name) || self.outputs.menu_is_open() {
+                    return Task::none();
+                }
+
+                if let ModuleName::Custom(name) = &module_name
+                    && let Some(tooltip_cmd) = self.custom.get(name).and_then(Custom::tooltip_cmd)
+                {
+                    Task::perform(
+                        custom_module::tooltip_output(tooltip_cmd.clone()),
+                        move |tooltip| Message::OpenTooltip(id, module_name.clone(), tooltip),
+                    )
+                } else if let Some(tooltip) = self.module_tooltip(&module_name) {
+                    self.update(Message::OpenTooltip(id, module_name, tooltip))
+                } else {
+                    Task::none()
+                }
+            }
+            Message::OpenTooltip(id, module_name, tooltip) => {
+                let Some((_, _, button_ui_ref, _)) = self
+                    .tooltip_hover
+                    .as_ref()
+                    .filter(|_| self.is_tooltip_hovered(id, &module_name))
+                else {
+                    return Task::none();
+                };
+
+                if tooltip.is_empty() || self.outputs.menu_is_open() {
+                    Task::none()
+                } else {
+                    let button_ui_ref = *button_ui_ref;
+                    self.outputs.toggle_menu(
+                        id,
+                        MenuType::Tooltip(module_name, tooltip),
+                        button_ui_ref,
+                        false,
+                    )
+                }
+            }
             Message::CloseMenu(id) => self
                 .outputs
                 .close_menu(id, self.general_config.enable_esc_key),
             Message::Custom(name, msg) => {
+                // the menu closes once one of its entries runs
+                let close_menu = matches!(msg, custom_module::Message::MenuEntry(_));
+
                 if let Some(custom) = self.custom.get_mut(&name) {
                     custom.update(msg);
                 }
 
-                Task::none()
+                if close_menu {
+                    self.outputs.close_all_menu_if(
+                        MenuType::Custom(name),
+                        self.general_config.enable_esc_key,
+                    )
+                } else {
+                    Task::none()
+                }
             }
             Message::Updates(msg) => {
                 if let Some(updates) = self.updates.as_mut() {
                     match updates.update(msg) {
                         modules::updates::Action::None => Task::none(),
-                        modules::updates::Action::CheckForUpdates(task) => {
-                            task.map(Message::Updates)
-                        }
+                        modules::updates::Action::CheckForUpdates(task)
+                        | modules::updates::Action::Command(task) => task.map(Message::Updates),
                         modules::updates::Action::CloseMenu(id, task) => Task::batch(vec![
                             task.map(Message::Updates),
                             self.outputs.close_menu_if(
@@ -302,65 +1174,176 @@ impl App {
                     Task::none()
                 }
             }
-            Message::Workspaces(msg) => self.workspaces.update(msg).map(Message::Workspaces),
+            Message::Feeds(msg) => {
+                if let Some(feeds) = self.feeds.as_mut() {
+                    feeds.update(msg);
+                }
+
+                Task::none()
+            }
+            Message::GitNotifications(msg) => {
+                if let Some(git_notifications) = self.git_notifications.as_mut() {
+                    git_notifications.update(msg);
+                }
+
+                Task::none()
+            }
+            Message::Provider(msg) => {
+                let close_menu = matches!(msg, provider::Message::MenuEntry(_));
+                self.providers.update(msg);
+
+                if close_menu {
+                    self.outputs
+                        .close_all_menus(self.general_config.enable_esc_key)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Tasks(msg) => {
+                if let Some(tasks) = self.tasks.as_mut() {
+                    tasks.update(msg).map(Message::Tasks)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Workspaces(msg) => {
+                if let Some(workspaces) = self.workspaces.as_mut() {
+                    workspaces.update(msg).map(Message::Workspaces)
+                } else {
+                    Task::none()
+                }
+            }
             Message::WindowTitle(msg) => {
-                self.window_title.update(msg);
+                if let Some(window_title) = self.window_title.as_mut() {
+                    window_title.update(msg);
+                }
                 Task::none()
             }
+            Message::Taskbar(msg) => {
+                if let Some(taskbar) = self.taskbar.as_mut() {
+                    taskbar.update(msg).map(Message::Taskbar)
+                } else {
+                    Task::none()
+                }
+            }
             Message::SystemInfo(msg) => {
-                self.system_info.update(msg);
+                if let Some(system_info) = self.system_info.as_mut() {
+                    system_info.update(msg);
+                }
                 Task::none()
             }
-            Message::KeyboardLayout(message) => self
-                .keyboard_layout
-                .update(message)
-                .map(Message::KeyboardLayout),
+            Message::KeyboardLayout(message) => {
+                let Some(keyboard_layout) = self.keyboard_layout.as_mut() else {
+                    return Task::none();
+                };
+                // the menu closes once a layout is picked
+                let close_menu = matches!(message, keyboard_layout::Message::SelectLayout(_));
+                let task = keyboard_layout.update(message).map(Message::KeyboardLayout);
+
+                if close_menu {
+                    Task::batch(vec![
+                        task,
+                        self.outputs.close_all_menu_if(
+                            MenuType::KeyboardLayout,
+                            self.general_config.enable_esc_key,
+                        ),
+                    ])
+                } else {
+                    task
+                }
+            }
             Message::KeyboardSubmap(message) => {
-                self.keyboard_submap.update(message);
+                if let Some(keyboard_submap) = self.keyboard_submap.as_mut() {
+                    keyboard_submap.update(message);
+                }
                 Task::none()
             }
-            Message::Tray(msg) => match self.tray.update(msg) {
-                modules::tray::Action::None => Task::none(),
-                modules::tray::Action::ToggleMenu(name, id, button_ui_ref) => {
-                    self.outputs.toggle_menu(
-                        id,
+            Message::Tray(msg) => {
+                let Some(tray) = self.tray.as_mut() else {
+                    return Task::none();
+                };
+
+                match tray.update(msg) {
+                    modules::tray::Action::None => Task::none(),
+                    modules::tray::Action::ToggleMenu(name, id, button_ui_ref) => {
+                        self.outputs.toggle_menu(
+                            id,
+                            MenuType::Tray(name),
+                            button_ui_ref,
+                            self.general_config.enable_esc_key,
+                        )
+                    }
+                    modules::tray::Action::TrayMenuCommand(task) => Task::batch(vec![
+                        self.outputs
+                            .close_all_menus(self.general_config.enable_esc_key),
+                        task.map(Message::Tray),
+                    ]),
+                    modules::tray::Action::CloseTrayMenu(name) => self.outputs.close_all_menu_if(
                         MenuType::Tray(name),
-                        button_ui_ref,
                         self.general_config.enable_esc_key,
-                    )
+                    ),
                 }
-                modules::tray::Action::TrayMenuCommand(task) => Task::batch(vec![
-                    self.outputs
-                        .close_all_menus(self.general_config.enable_esc_key),
-                    task.map(Message::Tray),
-                ]),
-                modules::tray::Action::CloseTrayMenu(name) => self
-                    .outputs
-                    .close_all_menu_if(MenuType::Tray(name), self.general_config.enable_esc_key),
-            },
+            }
             Message::Clock(message) => {
-                self.clock.update(message);
+                if let Some(clock) = self.clock.as_mut() {
+                    clock.update(message);
+                }
                 Task::none()
             }
             Message::Privacy(msg) => {
-                self.privacy.update(msg);
+                if let Some(privacy) = self.privacy.as_mut() {
+                    privacy.update(msg);
+                }
                 Task::none()
             }
-            Message::Settings(message) => match self.settings.update(message) {
-                modules::settings::Action::None => Task::none(),
-                modules::settings::Action::Command(task) => task.map(Message::Settings),
-                modules::settings::Action::CloseMenu(id) => self
-                    .outputs
-                    .close_menu(id, self.general_config.enable_esc_key),
-                modules::settings::Action::RequestKeyboard(id) => self.outputs.request_keyboard(id),
-                modules::settings::Action::ReleaseKeyboard(id) => self.outputs.release_keyboard(id),
-                modules::settings::Action::ReleaseKeyboardWithCommand(id, task) => {
-                    Task::batch(vec![
-                        task.map(Message::Settings),
-                        self.outputs.release_keyboard(id),
-                    ])
+            Message::Peripherals(msg) => {
+                if let Some(peripherals) = self.peripherals.as_mut() {
+                    peripherals.update(msg);
                 }
-            },
+                Task::none()
+            }
+            Message::Recording(msg) => {
+                if let Some(recording) = self.recording.as_mut() {
+                    recording.update(msg);
+                }
+                Task::none()
+            }
+            Message::Settings(message) => {
+                let Some(settings) = self.settings.as_mut() else {
+                    return Task::none();
+                };
+
+                match settings.update(message) {
+                    modules::settings::Action::None => Task::none(),
+                    modules::settings::Action::Command(task) => task.map(Message::Settings),
+                    modules::settings::Action::CloseMenu(id) => self
+                        .outputs
+                        .close_menu(id, self.general_config.enable_esc_key),
+                    modules::settings::Action::RequestKeyboard(id) => {
+                        self.outputs.request_keyboard(id)
+                    }
+                    modules::settings::Action::ReleaseKeyboard(id) => {
+                        self.outputs.release_keyboard(id)
+                    }
+                    modules::settings::Action::ReleaseKeyboardWithCommand(id, task) => {
+                        Task::batch(vec![
+                            task.map(Message::Settings),
+                            self.outputs.release_keyboard(id),
+                        ])
+                    }
+                    modules::settings::Action::SwitchProfile(name) => self.switch_profile(&name),
+                    modules::settings::Action::ToggleModule(module) => {
+                        self.toggle_module(module);
+
+                        Task::none()
+                    }
+                    modules::settings::Action::ToggleDoNotDisturb => {
+                        self.toasts.update(toasts::Message::ToggleDoNotDisturb);
+
+                        Task::none()
+                    }
+                }
+            }
             Message::OutputEvent((event, wl_output)) => match event {
                 iced::event::wayland::OutputEvent::Created(info) => {
                     info!("Output created: {info:?}");
@@ -369,32 +1352,37 @@ impl App {
                         .and_then(|info| info.description.as_deref())
                         .unwrap_or("");
 
-                    self.outputs.add(
-                        self.theme.bar_style,
-                        &self.general_config.outputs,
-                        self.theme.bar_position,
-                        self.general_config.layer,
+                    let task = self.outputs.add(
                         name,
                         wl_output,
-                        self.theme.scale_factor,
-                    )
+                        info.as_ref().and_then(|info| info.logical_size),
+                    );
+                    hooks::output_added(&self.general_config.hooks, name);
+
+                    task
+                }
+                iced::event::wayland::OutputEvent::InfoUpdate(info) => {
+                    match info.description.as_deref() {
+                        Some(name) => self.outputs.set_logical_size(name, info.logical_size),
+                        None => Task::none(),
+                    }
                 }
                 iced::event::wayland::OutputEvent::Removed => {
                     info!("Output destroyed");
-                    self.outputs.remove(
-                        self.theme.bar_style,
-                        self.theme.bar_position,
-                        self.general_config.layer,
-                        wl_output,
-                        self.theme.scale_factor,
-                    )
+                    self.outputs.remove(wl_output)
                 }
                 _ => Task::none(),
             },
-            Message::MediaPlayer(msg) => match self.media_player.update(msg) {
-                modules::media_player::Action::None => Task::none(),
-                modules::media_player::Action::Command(task) => task.map(Message::MediaPlayer),
-            },
+            Message::MediaPlayer(msg) => {
+                let Some(media_player) = self.media_player.as_mut() else {
+                    return Task::none();
+                };
+
+                match media_player.update(msg) {
+                    modules::media_player::Action::None => Task::none(),
+                    modules::media_player::Action::Command(task) => task.map(Message::MediaPlayer),
+                }
+            }
             Message::CloseAllMenus => {
                 if self.outputs.menu_is_open() {
                     self.outputs
@@ -403,90 +1391,271 @@ impl App {
                     Task::none()
                 }
             }
-            Message::ResumeFromSleep => self.outputs.sync(
-                self.theme.bar_style,
-                &self.general_config.outputs,
-                self.theme.bar_position,
-                self.general_config.layer,
-                self.theme.scale_factor,
-            ),
+            Message::ResumeFromSleep => self.outputs.sync(),
+            Message::WindowSwitcher(message) => match self.window_switcher.update(message) {
+                window_switcher::Action::None => Task::none(),
+                window_switcher::Action::Focus(task) => Task::batch(vec![
+                    task.map(Message::WindowSwitcher),
+                    self.outputs
+                        .close_all_menu_if(MenuType::Windows, self.general_config.enable_esc_key),
+                ]),
+            },
+            Message::Toasts(message) => {
+                self.toasts.update(message);
+
+                self.sync_toasts()
+            }
+            Message::Osd(message) => {
+                self.osd.update(message);
+
+                self.sync_osd()
+            }
+            Message::Keybinds(message) => {
+                let task = self.keybinds.update(message).map(Message::Keybinds);
+
+                Task::batch(vec![task, self.sync_keybinds()])
+            }
+            Message::Scheduler(message) => {
+                self.scheduler.update(message);
+
+                Task::none()
+            }
+            Message::DebugOverlay(message) => {
+                if let Some(debug_overlay) = self.debug_overlay.as_mut() {
+                    debug_overlay.update(message);
+                }
+
+                Task::none()
+            }
+            Message::Watchdog(message) => {
+                if let Some(watchdog) = self.watchdog.as_mut() {
+                    watchdog.update(message);
+                }
+
+                Task::none()
+            }
+            Message::RestartModule(module) => {
+                self.restart_module(&module);
+
+                Task::none()
+            }
+            Message::Services(message) => {
+                self.services.update(&message);
+
+                let backend = message.backend();
+                let tasks = self
+                    .service_consumers()
+                    .into_iter()
+                    .filter(|consumer| consumer.backend() == backend)
+                    .map(|consumer| self.deliver(consumer, message.clone()))
+                    .collect::<Vec<_>>();
+
+                Task::batch(tasks)
+            }
+            Message::Chooser(message) => {
+                let picked = self
+                    .chooser
+                    .as_mut()
+                    .is_some_and(|chooser| chooser.update(message));
+
+                if picked {
+                    self.chooser = None;
+                    self.outputs
+                        .close_all_menu_if(MenuType::Chooser, self.general_config.enable_esc_key)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Ipc(
+                IpcCommand::Choose {
+                    prompt,
+                    options,
+                    output,
+                },
+                reply,
+            ) => self.choose(prompt, options, output.as_deref(), reply),
+            Message::Ipc(command, reply) => {
+                let (response, task) = self.handle_ipc(command);
+                reply.send(response);
+
+                task
+            }
         }
     }
 
+    fn config_error_view<'a>(&'a self, error: &'a str) -> Element<'a, Message> {
+        // parse errors usually have the position on the first line
+        // and the reason on the last one
+        let mut lines = error.lines().map(str::trim).filter(|line| !line.is_empty());
+        let summary = match (lines.next(), lines.last()) {
+            (Some(first), Some(last)) => format!("{first}: {last}"),
+            (Some(first), None) => first.to_owned(),
+            _ => "Invalid config".to_owned(),
+        };
+
+        container(
+            row!(
+                icon(StaticIcon::Alert),
+                text(truncate_text(&format!("Config error, {summary}"), 120))
+                    .size(self.theme.font_size.sm)
+                    .wrapping(text::Wrapping::None),
+            )
+            .align_y(Alignment::Center)
+            .spacing(self.theme.space.xxs),
+        )
+        .padding([2, self.theme.space.xs])
+        .height(Length::Fill)
+        .align_y(Alignment::Center)
+        .style(|theme: &Theme| container::Style {
+            background: Some(theme.extended_palette().danger.base.color.into()),
+            text_color: Some(theme.extended_palette().danger.base.text),
+            border: Border::default().rounded(self.theme.radius.lg),
+            ..Default::default()
+        })
+        .into()
+    }
+
     pub fn view(&'_ self, id: Id) -> Element<'_, Message> {
+        let start = Instant::now();
+        let view = self.surface_view(id);
+        debug_overlay::record_view(start.elapsed());
+
+        view
+    }
+
+    fn surface_view(&'_ self, id: Id) -> Element<'_, Message> {
+        if self.toasts.surface() == Some(id) {
+            return self.toasts.view(&self.theme).map(Message::Toasts);
+        }
+        if self.osd.surface() == Some(id) {
+            return self.osd.view(&self.theme).map(Message::Osd);
+        }
+        if self.keybinds.surface() == Some(id) {
+            return self.keybinds.view(&self.theme).map(Message::Keybinds);
+        }
+
         match self.outputs.has(id) {
+            Some(HasOutput::Main) if self.outputs.is_collapsed(id) => Row::new().into(),
             Some(HasOutput::Main) => {
+                let position = self.bar_position(id);
+                let vertical = position.is_vertical();
                 let [left, center, right] = self.modules_section(id, &self.theme);
+                let [left, center, right] = [
+                    (left, &self.theme.gradient.left),
+                    (center, &self.theme.gradient.center),
+                    (right, &self.theme.gradient.right),
+                ]
+                .map(|(section, gradient)| match gradient {
+                    Some(gradient) if self.theme.bar_style == AppearanceStyle::Gradient => {
+                        let section = container(section);
+                        let section = if vertical {
+                            section.width(Length::Fill).align_x(Alignment::Center)
+                        } else {
+                            section.height(Length::Fill).align_y(Alignment::Center)
+                        };
+
+                        section
+                            .style(move |t: &Theme| container::Style {
+                                background: Some(
+                                    self.theme
+                                        .gradient(
+                                            position,
+                                            gradient.angle,
+                                            &gradient.stops,
+                                            t.palette().background,
+                                            None,
+                                        )
+                                        .into(),
+                                ),
+                                border: Border::default().rounded(self.theme.radius.lg),
+                                ..Default::default()
+                            })
+                            .into()
+                    }
+                    _ => section,
+                });
+                let left = match self.config_error.as_deref() {
+                    Some(error) if vertical => column!(self.config_error_view(error), left)
+                        .align_x(Alignment::Center)
+                        .spacing(self.theme.space.xxs)
+                        .into(),
+                    Some(error) => row!(self.config_error_view(error), left)
+                        .align_y(Alignment::Center)
+                        .spacing(self.theme.space.xxs)
+                        .into(),
+                    None => left,
+                };
+                let right = match self.debug_overlay.as_ref() {
+                    Some(debug_overlay) if vertical => {
+                        column!(right, debug_overlay.view(&self.theme))
+                            .align_x(Alignment::Center)
+                            .spacing(self.theme.space.xxs)
+                            .into()
+                    }
+                    Some(debug_overlay) => row!(right, debug_overlay.view(&self.theme))
+                        .align_y(Alignment::Center)
+                        .spacing(self.theme.space.xxs)
+                        .into(),
+                    None => right,
+                };
 
+                let (_, thickness) = self.outputs.height(id);
                 let centerbox = centerbox::Centerbox::new([left, center, right])
+                    .vertical(vertical)
                     .spacing(self.theme.space.xxs)
-                    .width(Length::Fill)
                     .align_items(Alignment::Center)
-                    .height(if self.theme.bar_style == AppearanceStyle::Islands {
-                        HEIGHT
-                    } else {
-                        HEIGHT - 8.
-                    } as f32)
                     .padding(if self.theme.bar_style == AppearanceStyle::Islands {
                         [self.theme.space.xxs, self.theme.space.xxs]
                     } else {
                         [0, 0]
                     });
+                let centerbox = if vertical {
+                    centerbox.width(thickness).height(Length::Fill)
+                } else {
+                    centerbox.width(Length::Fill).height(thickness)
+                };
+
+                // an auto-hidden bar fades in when it's revealed
+                let progress =
+                    animation::progress(self.theme.animations.reveal, self.outputs.revealed_at(id));
 
-                let status_bar = container(centerbox).style(|t: &Theme| container::Style {
-                    background: match self.theme.bar_style {
-                        AppearanceStyle::Gradient => Some({
-                            let start_color =
-                                t.palette().background.scale_alpha(self.theme.opacity);
-
-                            let start_color = if self.outputs.menu_is_open() {
-                                darken_color(start_color, self.theme.menu.backdrop)
-                            } else {
-                                start_color
-                            };
-
-                            let end_color = if self.outputs.menu_is_open() {
-                                backdrop_color(self.theme.menu.backdrop)
-                            } else {
-                                Color::TRANSPARENT
-                            };
-
-                            Gradient::Linear(
-                                Linear::new(Radians(PI))
-                                    .add_stop(
-                                        0.0,
-                                        match self.theme.bar_position {
-                                            Position::Top => start_color,
-                                            Position::Bottom => end_color,
-                                        },
+                let status_bar = container(centerbox).style(move |t: &Theme| container::Style {
+                    text_color: (progress < 1.0).then(|| t.palette().text.scale_alpha(progress)),
+                    background: mix_background(
+                        None,
+                        match self.theme.bar_style {
+                            AppearanceStyle::Gradient => Some(
+                                self.theme
+                                    .gradient(
+                                        position,
+                                        self.theme.gradient.angle,
+                                        &self.theme.gradient.stops,
+                                        t.palette().background,
+                                        self.outputs
+                                            .menu_is_open()
+                                            .then_some(self.theme.menu.backdrop),
                                     )
-                                    .add_stop(
-                                        1.0,
-                                        match self.theme.bar_position {
-                                            Position::Top => end_color,
-                                            Position::Bottom => start_color,
-                                        },
-                                    ),
-                            )
-                            .into()
-                        }),
-                        AppearanceStyle::Solid => Some({
-                            let bg = t.palette().background.scale_alpha(self.theme.opacity);
-                            if self.outputs.menu_is_open() {
-                                darken_color(bg, self.theme.menu.backdrop)
-                            } else {
-                                bg
-                            }
-                            .into()
-                        }),
-                        AppearanceStyle::Islands => {
-                            if self.outputs.menu_is_open() {
-                                Some(backdrop_color(self.theme.menu.backdrop).into())
-                            } else {
-                                None
+                                    .into(),
+                            ),
+                            AppearanceStyle::Solid => Some({
+                                let bg = t.palette().background.scale_alpha(self.theme.opacity);
+                                if self.outputs.menu_is_open() {
+                                    darken_color(bg, self.theme.menu.backdrop)
+                                } else {
+                                    bg
+                                }
+                                .into()
+                            }),
+                            AppearanceStyle::Islands => {
+                                if self.outputs.menu_is_open() {
+                                    Some(backdrop_color(self.theme.menu.backdrop).into())
+                                } else {
+                                    None
+                                }
                             }
-                        }
-                    },
+                        },
+                        progress,
+                    ),
                     ..Default::default()
                 });
 
@@ -498,66 +1667,394 @@ impl App {
                     status_bar.into()
                 }
             }
-            Some(HasOutput::Menu(menu_info)) => match menu_info {
-                Some((MenuType::Updates, button_ui_ref)) => {
-                    if let Some(updates) = self.updates.as_ref() {
-                        self.menu_wrapper(
+            Some(HasOutput::Menu(menu_info)) => {
+                let view = || match menu_info {
+                    Some((MenuType::Updates, button_ui_ref)) => {
+                        if let Some(updates) = self.updates.as_ref() {
+                            self.menu_wrapper(
+                                id,
+                                updates.menu_view(id, &self.theme).map(Message::Updates),
+                                MenuSize::Small,
+                                *button_ui_ref,
+                            )
+                        } else {
+                            Row::new().into()
+                        }
+                    }
+                    Some((MenuType::Feeds, button_ui_ref)) => {
+                        if let Some(feeds) = self.feeds.as_ref() {
+                            self.menu_wrapper(
+                                id,
+                                feeds.menu_view(&self.theme).map(Message::Feeds),
+                                MenuSize::Medium,
+                                *button_ui_ref,
+                            )
+                        } else {
+                            Row::new().into()
+                        }
+                    }
+                    Some((MenuType::GitNotifications, button_ui_ref)) => {
+                        if let Some(git_notifications) = self.git_notifications.as_ref() {
+                            self.menu_wrapper(
+                                id,
+                                git_notifications
+                                    .menu_view(&self.theme)
+                                    .map(Message::GitNotifications),
+                                MenuSize::Large,
+                                *button_ui_ref,
+                            )
+                        } else {
+                            Row::new().into()
+                        }
+                    }
+                    Some((MenuType::Tasks, button_ui_ref)) => {
+                        if let Some(tasks) = self.tasks.as_ref() {
+                            self.menu_wrapper(
+                                id,
+                                tasks.menu_view(&self.theme).map(Message::Tasks),
+                                MenuSize::Large,
+                                *button_ui_ref,
+                            )
+                        } else {
+                            Row::new().into()
+                        }
+                    }
+                    Some((MenuType::Windows, button_ui_ref)) => self.menu_wrapper(
+                        id,
+                        self.window_switcher
+                            .menu_view(&self.theme)
+                            .map(Message::WindowSwitcher),
+                        MenuSize::Medium,
+                        *button_ui_ref,
+                    ),
+                    Some((MenuType::Chooser, button_ui_ref)) => match self.chooser.as_ref() {
+                        Some(chooser) => self.menu_wrapper(
+                            id,
+                            chooser.view(&self.theme).map(Message::Chooser),
+                            MenuSize::Medium,
+                            *button_ui_ref,
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::Provider(name), button_ui_ref)) => self.menu_wrapper(
+                        id,
+                        self.providers
+                            .menu_view(&self.theme, name)
+                            .map(Message::Provider),
+                        MenuSize::Small,
+                        *button_ui_ref,
+                    ),
+                    Some((MenuType::Custom(name), button_ui_ref)) => match self.custom.get(name) {
+                        Some(custom) => self.menu_wrapper(
+                            id,
+                            custom
+                                .menu_view(&self.theme)
+                                .map(move |msg| Message::Custom(name.clone(), msg)),
+                            MenuSize::Medium,
+                            *button_ui_ref,
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::Tooltip(_, tooltip), button_ui_ref)) => self.menu_wrapper(
+                        id,
+                        text(tooltip).size(self.theme.font_size.sm).into(),
+                        MenuSize::Small,
+                        *button_ui_ref,
+                    ),
+                    Some((MenuType::Privacy, button_ui_ref)) => match self.privacy.as_ref() {
+                        Some(privacy) => self.menu_wrapper(
                             id,
-                            updates.menu_view(id, &self.theme).map(Message::Updates),
+                            privacy.menu_view(&self.theme).map(Message::Privacy),
+                            MenuSize::Medium,
+                            *button_ui_ref,
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::Peripherals, button_ui_ref)) => match self.peripherals.as_ref()
+                    {
+                        Some(peripherals) => self.menu_wrapper(
+                            id,
+                            peripherals.menu_view(&self.theme).map(Message::Peripherals),
                             MenuSize::Small,
                             *button_ui_ref,
-                        )
-                    } else {
-                        Row::new().into()
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::Recording, button_ui_ref)) => match self.recording.as_ref() {
+                        Some(recording) => self.menu_wrapper(
+                            id,
+                            recording.menu_view(&self.theme).map(Message::Recording),
+                            MenuSize::Medium,
+                            *button_ui_ref,
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::Notifications, button_ui_ref)) => self.menu_wrapper(
+                        id,
+                        self.toasts.menu_view(&self.theme).map(Message::Toasts),
+                        MenuSize::Medium,
+                        *button_ui_ref,
+                    ),
+                    Some((MenuType::KeyboardLayout, button_ui_ref)) => {
+                        match self.keyboard_layout.as_ref() {
+                            Some(keyboard_layout) => self.menu_wrapper(
+                                id,
+                                keyboard_layout
+                                    .menu_view(&self.theme)
+                                    .map(Message::KeyboardLayout),
+                                MenuSize::Small,
+                                *button_ui_ref,
+                            ),
+                            None => Row::new().into(),
+                        }
                     }
+                    Some((MenuType::Tray(name), button_ui_ref)) => match self.tray.as_ref() {
+                        Some(tray) => self.menu_wrapper(
+                            id,
+                            tray.menu_view(&self.theme, name).map(Message::Tray),
+                            MenuSize::Medium,
+                            *button_ui_ref,
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::Settings, button_ui_ref)) => match self.settings.as_ref() {
+                        Some(settings) => self.menu_wrapper(
+                            id,
+                            settings
+                                .menu_view(
+                                    id,
+                                    &self.theme,
+                                    self.bar_position(id),
+                                    self.layout_modules()
+                                        .into_iter()
+                                        .map(|module| {
+                                            let visible = !self.hidden_modules.contains(&module);
+                                            (module, visible)
+                                        })
+                                        .collect(),
+                                    self.toasts
+                                        .is_enabled()
+                                        .then(|| self.toasts.do_not_disturb()),
+                                )
+                                .map(Message::Settings),
+                            MenuSize::Medium,
+                            *button_ui_ref,
+                        ),
+                        None => Row::new().into(),
+                    },
+                    Some((MenuType::MediaPlayer, button_ui_ref)) => {
+                        match self.media_player.as_ref() {
+                            Some(media_player) => self.menu_wrapper(
+                                id,
+                                media_player
+                                    .menu_view(&self.theme)
+                                    .map(Message::MediaPlayer),
+                                MenuSize::Large,
+                                *button_ui_ref,
+                            ),
+                            None => Row::new().into(),
+                        }
+                    }
+                    Some((MenuType::SystemInfo, button_ui_ref)) => {
+                        if let Some(system_info) = self.system_info.as_ref() {
+                            self.menu_wrapper(
+                                id,
+                                system_info.menu_view(&self.theme).map(Message::SystemInfo),
+                                MenuSize::Medium,
+                                *button_ui_ref,
+                            )
+                        } else {
+                            Row::new().into()
+                        }
+                    }
+                    None => Row::new().into(),
+                };
+
+                // a panicking menu disables its module, like its view on the bar
+                match menu_info.and_then(|(menu_type, _)| menu_module(menu_type)) {
+                    Some(module) => self
+                        .catch_module(&module, view)
+                        .unwrap_or_else(|| Row::new().into()),
+                    None => view(),
                 }
-                Some((MenuType::Tray(name), button_ui_ref)) => self.menu_wrapper(
-                    id,
-                    self.tray.menu_view(&self.theme, name).map(Message::Tray),
-                    MenuSize::Medium,
-                    *button_ui_ref,
-                ),
-                Some((MenuType::Settings, button_ui_ref)) => self.menu_wrapper(
-                    id,
-                    self.settings
-                        .menu_view(id, &self.theme, self.theme.bar_position)
-                        .map(Message::Settings),
-                    MenuSize::Medium,
-                    *button_ui_ref,
-                ),
-                Some((MenuType::MediaPlayer, button_ui_ref)) => self.menu_wrapper(
-                    id,
-                    self.media_player
-                        .menu_view(&self.theme)
-                        .map(Message::MediaPlayer),
-                    MenuSize::Large,
-                    *button_ui_ref,
-                ),
-                Some((MenuType::SystemInfo, button_ui_ref)) => self.menu_wrapper(
-                    id,
-                    self.system_info
-                        .menu_view(&self.theme)
-                        .map(Message::SystemInfo),
-                    MenuSize::Medium,
-                    *button_ui_ref,
-                ),
-                None => Row::new().into(),
-            },
+            }
             None => Row::new().into(),
         }
     }
 
+    /// Whether a menu or a workspace transition is running and needs redraws.
+    fn is_animating(&self) -> bool {
+        let now = Instant::now();
+        let animations = self.theme.animations;
+
+        animations.menu.is_some_and(|transition| {
+            self.outputs
+                .menus_opened_at()
+                .any(|since| transition.is_running(since, now))
+        }) || animations.reveal.is_some_and(|transition| {
+            self.outputs
+                .bars_revealed_at()
+                .any(|since| transition.is_running(since, now))
+        }) || animations
+            .workspace
+            .zip(self.workspaces.as_ref().and_then(Workspaces::changed_at))
+            .is_some_and(|(transition, since)| transition.is_running(since, now))
+    }
+
+    /// Whether the cursor is on the module since the tooltip delay.
+    fn is_tooltip_hovered(&self, id: Id, module_name: &ModuleName) -> bool {
+        self.tooltip_hover
+            .as_ref()
+            .is_some_and(|(hover_id, hovered, _, since)| {
+                *hover_id == id
+                    && hovered == module_name
+                    && since.elapsed() >= Duration::from_millis(self.theme.tooltip.delay)
+            })
+    }
+
+    /// Opens the settings menu when a finger pressed on a bar is dragged
+    /// away from the screen edge by at least the height of the bar.
+    fn swipe(&mut self, id: Id, event: touch::Event) -> Task<Message> {
+        match event {
+            touch::Event::FingerPressed {
+                id: finger,
+                position,
+            } => {
+                if matches!(self.outputs.has(id), Some(HasOutput::Main)) {
+                    self.touch_start = Some((id, finger, position));
+                }
+
+                Task::none()
+            }
+            touch::Event::FingerMoved {
+                id: finger,
+                position,
+            } => {
+                let Some((bar_id, start_finger, start)) = self.touch_start else {
+                    return Task::none();
+                };
+                if bar_id != id || start_finger != finger {
+                    return Task::none();
+                }
+
+                let distance = match self.bar_position(id) {
+                    Position::Top => position.y - start.y,
+                    Position::Bottom => start.y - position.y,
+                    Position::Left => position.x - start.x,
+                    Position::Right => start.x - position.x,
+                };
+                if distance < self.outputs.height(id).0 {
+                    return Task::none();
+                }
+
+                self.touch_start = None;
+                if self.outputs.menu_is_open() {
+                    return Task::none();
+                }
+
+                debug!("Swipe from the bar, opening the settings menu");
+                let anchor = self.menu_anchor(id, &MenuType::Settings);
+                self.update(Message::ToggleMenu(MenuType::Settings, id, anchor))
+            }
+            touch::Event::FingerLifted { .. } | touch::Event::FingerLost { .. } => {
+                self.touch_start = None;
+
+                Task::none()
+            }
+        }
+    }
+
+    /// Restarts the subscription after a panic when the watchdog is enabled.
+    pub fn guard<M: 'static>(
+        &self,
+        guarded: Guarded,
+        subscription: Subscription<M>,
+    ) -> Subscription<M> {
+        match self.watchdog.as_ref() {
+            Some(watchdog) => watchdog.guard(guarded, subscription),
+            None => subscription,
+        }
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
         Subscription::batch(vec![
-            Subscription::batch(self.modules_subscriptions(&self.general_config.modules.left)),
-            Subscription::batch(self.modules_subscriptions(&self.general_config.modules.center)),
-            Subscription::batch(self.modules_subscriptions(&self.general_config.modules.right)),
+            Subscription::batch(
+                std::iter::once(&self.general_config.modules)
+                    .chain(
+                        self.general_config
+                            .bars
+                            .values()
+                            .flatten()
+                            .map(|bar| &bar.modules),
+                    )
+                    .flat_map(|modules| {
+                        [&modules.left, &modules.center, &modules.right]
+                            .into_iter()
+                            .flat_map(|modules_def| self.modules_subscriptions(modules_def))
+                    })
+                    .chain(self.modules_subscriptions(
+                        self.injected_modules.iter().map(|module| &module.def),
+                    ))
+                    .collect::<Vec<_>>(),
+            ),
             config::subscription(&self.config_path),
+            crate::signals::subscription(),
+            crate::ipc::subscription(),
+            crate::ipc::dbus::subscription(),
+            self.general_config
+                .http_port
+                .map_or_else(Subscription::none, |port| {
+                    crate::ipc::http::subscription(
+                        port,
+                        self.general_config.http_allowed_origins.clone(),
+                    )
+                }),
+            color_scheme::subscription(&self.general_config.appearance.theme_source),
+            if self.general_config.appearance.mode == AppearanceMode::Auto {
+                AppearanceService::subscribe().map(Message::SystemAppearance)
+            } else {
+                Subscription::none()
+            },
+            if self.is_animating() {
+                every(animation::FRAME).map(|_| Message::None)
+            } else {
+                Subscription::none()
+            },
+            if self.outputs.auto_hide_pending() {
+                every(AUTO_HIDE_INTERVAL).map(|_| Message::AutoHide)
+            } else {
+                Subscription::none()
+            },
+            // the IPC servers are left alone, restarting them would race for the socket
+            Services::subscription(&self.service_consumers(), |backend, subscription| {
+                self.guard(Guarded::Backend(backend), subscription)
+            })
+            .map(Message::Services),
+            self.toasts.subscription().map(Message::Toasts),
+            self.osd.subscription().map(Message::Osd),
+            self.scheduler.subscription().map(Message::Scheduler),
+            self.debug_overlay
+                .as_ref()
+                .map_or_else(Subscription::none, |debug_overlay| {
+                    debug_overlay.subscription().map(Message::DebugOverlay)
+                }),
+            self.watchdog
+                .as_ref()
+                .map_or_else(Subscription::none, |watchdog| {
+                    watchdog.subscription().map(Message::Watchdog)
+                }),
             crate::services::logind::LogindService::subscribe().map(|event| match event {
                 crate::services::ServiceEvent::Update(_) => Message::ResumeFromSleep,
                 _ => Message::None,
             }),
-            listen_with(move |evt, _, _| match evt {
+            listen_with(move |evt, _, id| match evt {
+                iced::Event::Mouse(mouse::Event::CursorEntered) => {
+                    Some(Message::BarHovered(id, true))
+                }
+                iced::Event::Mouse(mouse::Event::CursorLeft) => {
+                    Some(Message::BarHovered(id, false))
+                }
+                iced::Event::Touch(event) => Some(Message::Touch(id, event)),
                 iced::Event::PlatformSpecific(iced::event::PlatformSpecific::Wayland(
                     WaylandEvent::Output(event, wl_output),
                 )) => {
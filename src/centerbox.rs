@@ -1,4 +1,4 @@
-//! Distribute content horizontally.
+//! Distribute content horizontally or vertically.
 use iced::advanced::layout::{self, Layout, Limits, Node};
 use iced::advanced::overlay;
 use iced::advanced::renderer;
@@ -8,9 +8,10 @@ use iced::{
     Alignment, Element, Event, Length, Padding, Pixels, Point, Rectangle, Size, Vector, event,
 };
 
-/// A container that distributes its contents horizontally.
+/// A container that distributes its contents horizontally, or vertically.
 #[allow(missing_debug_implementations)]
 pub struct Centerbox<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer> {
+    vertical: bool,
     spacing: f32,
     padding: Padding,
     width: Length,
@@ -26,6 +27,7 @@ where
     /// Creates an empty [`Centerbox`].
     pub fn new(children: [Element<'a, Message, Theme, Renderer>; 3]) -> Self {
         Centerbox {
+            vertical: false,
             spacing: 0.0,
             padding: Padding::ZERO,
             width: Length::Shrink,
@@ -35,7 +37,13 @@ where
         }
     }
 
-    /// Sets the horizontal spacing _between_ elements.
+    /// Distributes the contents from top to bottom instead of from left to right.
+    pub fn vertical(mut self, vertical: bool) -> Self {
+        self.vertical = vertical;
+        self
+    }
+
+    /// Sets the spacing _between_ elements.
     ///
     /// Custom margins per element do not exist in iced. You should use this
     /// method instead! While less flexible, it helps you keep spacing between
@@ -63,7 +71,7 @@ where
         self
     }
 
-    /// Sets the vertical alignment of the contents of the [`Centerbox`] .
+    /// Sets the cross axis alignment of the contents of the [`Centerbox`] .
     pub fn align_items(mut self, align: Alignment) -> Self {
         self.align_items = align;
         self
@@ -101,19 +109,53 @@ where
             .height(self.height)
             .shrink(self.padding);
 
+        // the layout is computed along the main axis, then mapped back to x and y
+        let vertical = self.vertical;
+        let axes = |size: Size| {
+            if vertical {
+                (size.height, size.width)
+            } else {
+                (size.width, size.height)
+            }
+        };
+        let size_of = |main: f32, cross: f32| {
+            if vertical {
+                Size::new(cross, main)
+            } else {
+                Size::new(main, cross)
+            }
+        };
+        let point_at = |main: f32, cross: f32| {
+            if vertical {
+                Point::new(cross, main)
+            } else {
+                Point::new(main, cross)
+            }
+        };
+        let (main_length, cross_length) = if vertical {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+        let (padding_start, padding_end, padding_cross) = if vertical {
+            (self.padding.top, self.padding.bottom, self.padding.left)
+        } else {
+            (self.padding.left, self.padding.right, self.padding.top)
+        };
+
         let total_spacing = self.spacing * 3_i32.saturating_sub(1) as f32;
-        let max_cross = limits.max().height;
+        let (max_main, max_cross) = axes(limits.max());
 
-        let mut cross = match self.height {
+        let mut cross = match cross_length {
             Length::Shrink => 0.0,
             _ => max_cross,
         };
 
-        let available = limits.max().width - total_spacing;
+        let available = max_main - total_spacing;
 
         let mut nodes = [Node::default(), Node::default(), Node::default()];
 
-        let mut remaining = match self.width {
+        let mut remaining = match main_length {
             Length::Shrink => 0.0,
             _ => available.max(0.0),
         };
@@ -123,10 +165,14 @@ where
                 let fill_cross_factor = {
                     let size = child.as_widget().size();
 
-                    size.height.fill_factor()
+                    if vertical {
+                        size.width.fill_factor()
+                    } else {
+                        size.height.fill_factor()
+                    }
                 };
 
-                let (max_width, max_height) = (
+                let max_size = size_of(
                     remaining,
                     if fill_cross_factor != 0 {
                         cross
@@ -135,13 +181,13 @@ where
                     },
                 );
 
-                let child_limits = Limits::new(Size::ZERO, Size::new(max_width, max_height));
+                let child_limits = Limits::new(Size::ZERO, max_size);
 
                 let layout = child.as_widget().layout(tree, renderer, &child_limits);
-                let size = layout.size();
+                let (main, child_cross) = axes(layout.size());
 
-                remaining -= size.width;
-                cross = cross.max(size.height);
+                remaining -= main;
+                cross = cross.max(child_cross);
 
                 nodes[i] = layout;
             };
@@ -150,44 +196,39 @@ where
         calculate_edge_layout(2, (&self.children[2], &mut tree.children[2]));
         calculate_edge_layout(1, (&self.children[1], &mut tree.children[1]));
 
-        nodes[0].move_to_mut(Point::new(self.padding.left, self.padding.top));
-        nodes[0].align_mut(Alignment::Start, self.align_items, Size::new(0.0, cross));
-        nodes[2].move_to_mut(Point::new(
-            limits.max().width + self.padding.right,
-            self.padding.top,
-        ));
-        nodes[2].align_mut(Alignment::End, self.align_items, Size::new(0.0, cross));
+        let align = |node: &mut Node, alignment: Alignment| {
+            if vertical {
+                node.align_mut(self.align_items, alignment, size_of(0.0, cross));
+            } else {
+                node.align_mut(alignment, self.align_items, size_of(0.0, cross));
+            }
+        };
+        let [start, center, end] = nodes.each_ref().map(|node| axes(node.size()).0);
+
+        nodes[0].move_to_mut(point_at(padding_start, padding_cross));
+        align(&mut nodes[0], Alignment::Start);
+        nodes[2].move_to_mut(point_at(max_main + padding_end, padding_cross));
+        align(&mut nodes[2], Alignment::End);
 
         let half_available = available / 2.0;
-        let half_center_width = nodes[1].size().width / 2.0;
-
-        if half_available - nodes[0].size().width < half_center_width
-            || half_available - nodes[2].size().width < half_center_width
-        {
-            nodes[1].move_to_mut(Point::new(
-                self.padding.left
-                    + self.spacing
-                    + nodes[0].size().width
-                    + (available - nodes[0].size().width - nodes[2].size().width) / 2.0,
-                self.padding.top,
+        let half_center = center / 2.0;
+
+        if half_available - start < half_center || half_available - end < half_center {
+            nodes[1].move_to_mut(point_at(
+                padding_start + self.spacing + start + (available - start - end) / 2.0,
+                padding_cross,
             ));
         } else {
-            nodes[1].move_to_mut(Point::new(
-                limits.max().width / 2. + self.padding.horizontal() / 2.0,
-                self.padding.top,
+            nodes[1].move_to_mut(point_at(
+                max_main / 2. + (padding_start + padding_end) / 2.0,
+                padding_cross,
             ));
         }
-        nodes[1].align_mut(Alignment::Center, self.align_items, Size::new(0.0, cross));
+        align(&mut nodes[1], Alignment::Center);
 
-        let main =
-            nodes[0].size().width + nodes[1].size().width + nodes[2].size().width + total_spacing;
+        let main = start + center + end + total_spacing;
 
-        let (intrinsic_width, intrinsic_height) = (main, cross);
-        let size = limits.resolve(
-            self.width,
-            self.height,
-            Size::new(intrinsic_width, intrinsic_height),
-        );
+        let size = limits.resolve(self.width, self.height, size_of(main, cross));
 
         Node::with_children(size.expand(self.padding), nodes.into())
     }
@@ -0,0 +1,110 @@
+use crate::{
+    ipc::{IpcReply, IpcResponse},
+    theme::AshellTheme,
+};
+use iced::{
+    Element, Length, Task,
+    widget::{Column, button, column, text, text_input},
+};
+
+/// Options shown at most, the others are reached by typing.
+const MAX_OPTIONS: usize = 20;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FilterChanged(String),
+    Submit,
+    Select(usize),
+}
+
+/// List of options piped by `ashell menu`, the picked one is sent
+/// as the reply of the request.
+pub struct Chooser {
+    prompt: Option<String>,
+    options: Vec<String>,
+    filter: String,
+    reply: IpcReply,
+}
+
+fn input_id() -> text_input::Id {
+    text_input::Id::new("chooser")
+}
+
+impl Chooser {
+    pub fn new(prompt: Option<String>, options: Vec<String>, reply: IpcReply) -> Self {
+        Self {
+            prompt,
+            options,
+            filter: String::new(),
+            reply,
+        }
+    }
+
+    /// Gives the keyboard focus to the filter.
+    pub fn focus<Message: 'static>() -> Task<Message> {
+        text_input::focus(input_id())
+    }
+
+    fn matches(&self) -> impl Iterator<Item = (usize, &String)> {
+        let filter = self.filter.to_lowercase();
+
+        self.options
+            .iter()
+            .enumerate()
+            .filter(move |(_, option)| option.to_lowercase().contains(&filter))
+    }
+
+    /// Returns `true` once an option has been picked.
+    pub fn update(&mut self, message: Message) -> bool {
+        let picked = match message {
+            Message::FilterChanged(filter) => {
+                self.filter = filter;
+
+                return false;
+            }
+            // like dmenu, the typed text is returned when nothing matches it
+            Message::Submit => self
+                .matches()
+                .next()
+                .map_or_else(|| self.filter.clone(), |(_, option)| option.clone()),
+            Message::Select(index) => match self.options.get(index) {
+                Some(option) => option.clone(),
+                None => return false,
+            },
+        };
+
+        self.reply.send(IpcResponse::data(picked.into()));
+
+        true
+    }
+
+    pub fn view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        column!(
+            text_input(self.prompt.as_deref().unwrap_or_default(), &self.filter)
+                .id(input_id())
+                .size(theme.font_size.md)
+                .padding([theme.space.xs, theme.space.md])
+                .style(theme.text_input_style())
+                .on_input(Message::FilterChanged)
+                .on_submit(Message::Submit),
+            Column::with_children(self.matches().take(MAX_OPTIONS).map(|(index, option)| {
+                button(text(option.as_str()))
+                    .on_press(Message::Select(index))
+                    .padding([theme.space.xxs, theme.space.sm])
+                    .width(Length::Fill)
+                    .style(theme.ghost_button_style())
+                    .into()
+            }))
+            .spacing(theme.space.xxs),
+        )
+        .spacing(theme.space.sm)
+        .into()
+    }
+}
+
+impl Drop for Chooser {
+    fn drop(&mut self) {
+        // no-op when an option has already been sent
+        self.reply.send(IpcResponse::error("no option was picked"));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::{
+    app::Message,
+    config::{Appearance, AppearanceColor, ColorSchemeFormat, ThemeSource},
+};
+use hex_color::HexColor;
+use iced::{
+    Subscription,
+    futures::{SinkExt, StreamExt},
+    stream::channel,
+};
+use inotify::{Inotify, WatchMask};
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use std::{any::TypeId, collections::HashMap, error::Error, path::Path};
+
+#[derive(Deserialize)]
+struct PywalSpecial {
+    background: HexColor,
+    foreground: HexColor,
+}
+
+/// Color scheme in the pywal `colors.json` format, also produced by wallust.
+#[derive(Deserialize)]
+struct PywalColors {
+    special: PywalSpecial,
+    colors: HashMap<String, HexColor>,
+}
+
+/// Colors generated from the wallpaper, already mapped to the appearance roles.
+#[derive(Debug, Clone)]
+pub struct ColorScheme {
+    background: AppearanceColor,
+    text: AppearanceColor,
+    primary: AppearanceColor,
+    secondary: AppearanceColor,
+    success: AppearanceColor,
+    danger: AppearanceColor,
+    workspace_colors: Vec<AppearanceColor>,
+}
+
+impl ColorScheme {
+    /// Replaces the colors of the appearance with the ones of the scheme.
+    pub fn apply(&self, appearance: &Appearance) -> Appearance {
+        Appearance {
+            background_color: self.background,
+            text_color: self.text,
+            primary_color: self.primary,
+            secondary_color: self.secondary,
+            success_color: self.success,
+            danger_color: self.danger,
+            workspace_colors: self.workspace_colors.clone(),
+            ..appearance.clone()
+        }
+    }
+}
+
+fn parse_pywal(content: &str) -> Result<ColorScheme, Box<dyn Error + Send>> {
+    let pywal: PywalColors =
+        serde_json::from_str(content).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    let color = |index: usize| {
+        pywal
+            .colors
+            .get(&format!("color{index}"))
+            .copied()
+            .unwrap_or(pywal.special.foreground)
+    };
+
+    Ok(ColorScheme {
+        background: AppearanceColor::Simple(pywal.special.background),
+        text: AppearanceColor::Simple(pywal.special.foreground),
+        primary: AppearanceColor::Complete {
+            base: color(4),
+            strong: None,
+            weak: None,
+            text: Some(pywal.special.background),
+        },
+        secondary: AppearanceColor::Simple(color(0)),
+        success: AppearanceColor::Simple(color(2)),
+        danger: AppearanceColor::Complete {
+            base: color(1),
+            weak: Some(color(3)),
+            strong: None,
+            text: None,
+        },
+        workspace_colors: [4, 5, 6]
+            .into_iter()
+            .map(|index| AppearanceColor::Simple(color(index)))
+            .collect(),
+    })
+}
+
+/// Reads a Material You palette generated by matugen.
+///
+/// Both a flat object of `role: "#hex"` pairs, as written by a template,
+/// and the `colors` object of `matugen --json hex` are accepted.
+fn parse_matugen(content: &str) -> Result<ColorScheme, Box<dyn Error + Send>> {
+    let json: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    let colors = json.get("colors").unwrap_or(&json);
+
+    let role = |name: &str| {
+        let value = colors.get(name)?;
+        let hex = value.as_str().or_else(|| {
+            ["default", "dark"]
+                .into_iter()
+                .find_map(|mode| value.get(mode))
+                .and_then(|value| value.as_str().or_else(|| value.get("hex")?.as_str()))
+        })?;
+
+        HexColor::parse(hex).ok()
+    };
+    let required = |name: &str| {
+        role(name).ok_or_else(|| {
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("missing the {name} color"),
+            )) as Box<dyn Error + Send>
+        })
+    };
+
+    let surface = required("surface")?;
+    let on_surface = required("on_surface")?;
+    let primary = required("primary")?;
+    let secondary = role("secondary").unwrap_or(primary);
+    let tertiary = role("tertiary").unwrap_or(secondary);
+
+    Ok(ColorScheme {
+        background: AppearanceColor::Complete {
+            base: surface,
+            weak: role("surface_container"),
+            strong: role("surface_container_high"),
+            text: Some(on_surface),
+        },
+        text: AppearanceColor::Simple(on_surface),
+        primary: AppearanceColor::Complete {
+            base: primary,
+            weak: None,
+            strong: None,
+            text: role("on_primary"),
+        },
+        secondary: AppearanceColor::Complete {
+            base: role("secondary_container").unwrap_or(secondary),
+            weak: None,
+            strong: role("surface_container_highest"),
+            text: role("on_secondary_container"),
+        },
+        success: AppearanceColor::Simple(tertiary),
+        danger: AppearanceColor::Complete {
+            base: required("error")?,
+            weak: role("on_error_container"),
+            strong: None,
+            text: role("on_error"),
+        },
+        workspace_colors: vec![
+            AppearanceColor::Simple(primary),
+            AppearanceColor::Simple(secondary),
+            AppearanceColor::Simple(tertiary),
+        ],
+    })
+}
+
+pub fn load(path: &Path, format: ColorSchemeFormat) -> Result<ColorScheme, Box<dyn Error + Send>> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+
+    match format {
+        ColorSchemeFormat::Pywal => parse_pywal(&content),
+        ColorSchemeFormat::Matugen => parse_matugen(&content),
+    }
+}
+
+/// Loads the color scheme of the source, if it's not the config itself.
+pub fn load_source(source: &ThemeSource) -> Option<ColorScheme> {
+    let (path, format) = source.file()?;
+
+    load(&path, format)
+        .inspect_err(|e| warn!("Failed to load the color scheme {path:?}: {e}"))
+        .ok()
+}
+
+/// Reloads the color scheme every time the file is written.
+pub fn subscription(source: &ThemeSource) -> Subscription<Message> {
+    let id = TypeId::of::<ColorScheme>();
+    let Some((path, format)) = source.file() else {
+        return Subscription::none();
+    };
+
+    Subscription::run_with_id(
+        (id, path.clone(), format),
+        channel(10, async move |mut output| {
+            let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+                error!("Invalid color scheme path {path:?}");
+                return;
+            };
+
+            let inotify = match Inotify::init() {
+                Ok(inotify) => inotify,
+                Err(e) => {
+                    error!("Failed to initialize inotify: {e}");
+                    return;
+                }
+            };
+
+            if let Err(e) = inotify.watches().add(
+                dir,
+                WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE,
+            ) {
+                error!("Failed to watch the color scheme directory {dir:?}: {e}");
+                return;
+            }
+
+            let buffer = [0; 1024];
+            let Ok(stream) = inotify.into_event_stream(buffer) else {
+                error!("Failed to create inotify event stream");
+                return;
+            };
+            let mut stream = stream.ready_chunks(10);
+
+            while let Some(events) = stream.next().await {
+                let changed = events.iter().any(|event| {
+                    event
+                        .as_ref()
+                        .is_ok_and(|event| event.name.as_deref() == Some(file_name))
+                });
+
+                if !changed {
+                    continue;
+                }
+
+                debug!("Color scheme {path:?} changed");
+                match load(&path, format) {
+                    Ok(color_scheme) => {
+                        info!("Color scheme reloaded");
+                        let _ = output.send(Message::ColorSchemeChanged(color_scheme)).await;
+                    }
+                    Err(e) => {
+                        warn!("Failed to load the color scheme {path:?}: {e}");
+                    }
+                }
+            }
+        }),
+    )
+}
@@ -1,4 +1,7 @@
-use crate::theme::AshellTheme;
+use crate::{
+    config::{IconSet, IconsConfig},
+    theme::AshellTheme,
+};
 use iced::{
     Color, Element, Font, Length, Theme,
     widget::{
@@ -7,6 +10,30 @@ use iced::{
         container, text,
     },
 };
+use std::sync::{LazyLock, OnceLock, RwLock};
+
+static ICON_FONT: OnceLock<&'static str> = OnceLock::new();
+
+static ICONS: LazyLock<RwLock<IconsConfig>> = LazyLock::new(RwLock::default);
+
+/// Replaces the icon set and the glyph overrides, applied on the next render.
+pub fn set_icons(config: &IconsConfig) {
+    *ICONS.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+}
+
+/// Overrides the font of the nerd font icons, must be called before the first render.
+pub fn set_icon_font(name: &'static str) {
+    let _ = ICON_FONT.set(name);
+}
+
+fn icon_font() -> &'static str {
+    ICON_FONT.get().copied().unwrap_or("Symbols Nerd Font")
+}
+
+fn icon_font_mono() -> &'static str {
+    // a custom icon font is used for both variants
+    ICON_FONT.get().copied().unwrap_or("Symbols Nerd Font Mono")
+}
 
 pub trait Icon {
     fn to_text<'a>(self) -> Text<'a>;
@@ -14,7 +41,7 @@ pub trait Icon {
     fn to_text_mono<'a>(self) -> Text<'a>;
 }
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Debug)]
 #[allow(dead_code)]
 pub enum StaticIcon {
     #[default]
@@ -38,6 +65,8 @@ pub enum StaticIcon {
     Mic1,
     MonitorSpeaker,
     ScreenShare,
+    Location,
+    File,
     Battery0,
     Battery1,
     Battery2,
@@ -111,6 +140,14 @@ pub enum StaticIcon {
     GamepadBatteryAlert,
     GamepadBatteryCharging,
     Remove,
+    Rss,
+    Git,
+    Tasks,
+    Alert,
+    Bell,
+    BellOff,
+    Record,
+    Monitor,
 }
 
 impl StaticIcon {
@@ -135,6 +172,8 @@ impl StaticIcon {
             StaticIcon::Mic0 => "\u{f036d}",
             StaticIcon::Mic1 => "\u{f036c}",
             StaticIcon::ScreenShare => "\u{f1483}",
+            StaticIcon::Location => "\u{f034e}",
+            StaticIcon::File => "\u{f0214}",
             StaticIcon::MonitorSpeaker => "\u{f0f5f}",
             StaticIcon::Battery0 => "\u{f0083}",
             StaticIcon::Battery1 => "\u{f007c}",
@@ -209,6 +248,122 @@ impl StaticIcon {
             StaticIcon::GamepadBatteryAlert => "\u{f074b}",
             StaticIcon::GamepadBatteryCharging => "\u{f0a22}",
             StaticIcon::Remove => "\u{f0377}",
+            StaticIcon::Rss => "\u{f046b}",
+            StaticIcon::Git => "\u{f02a2}",
+            StaticIcon::Tasks => "\u{f0756}",
+            StaticIcon::Alert => "\u{f0026}",
+            StaticIcon::Bell => "\u{f009a}",
+            StaticIcon::BellOff => "\u{f009b}",
+            StaticIcon::Record => "\u{f044a}",
+            StaticIcon::Monitor => "\u{f0379}",
+        }
+    }
+
+    fn get_emoji(&self) -> &'static str {
+        match self {
+            StaticIcon::None => "",
+            StaticIcon::Refresh => "🔄",
+            StaticIcon::NoUpdatesAvailable => "✅",
+            StaticIcon::UpdatesAvailable => "📦",
+            StaticIcon::MenuClosed => "▸",
+            StaticIcon::MenuOpen => "▾",
+            StaticIcon::Cpu => "🧠",
+            StaticIcon::Mem => "🐏",
+            StaticIcon::Temp => "🌡️",
+            StaticIcon::Speaker0 => "🔇",
+            StaticIcon::Speaker1 => "🔈",
+            StaticIcon::Speaker2 => "🔉",
+            StaticIcon::Speaker3 => "🔊",
+            StaticIcon::Headphones0 => "🎧",
+            StaticIcon::Headphones1 => "🎧",
+            StaticIcon::Headset => "🎧",
+            StaticIcon::Mic0 => "🎙️",
+            StaticIcon::Mic1 => "🎤",
+            StaticIcon::MonitorSpeaker => "🖥️",
+            StaticIcon::ScreenShare => "📺",
+            StaticIcon::Location => "📍",
+            StaticIcon::File => "📄",
+            StaticIcon::Battery0 => "🪫",
+            StaticIcon::Battery1 => "🪫",
+            StaticIcon::Battery2 => "🔋",
+            StaticIcon::Battery3 => "🔋",
+            StaticIcon::Battery4 => "🔋",
+            StaticIcon::BatteryCharging => "⚡",
+            StaticIcon::Wifi0 => "📶",
+            StaticIcon::Wifi1 => "📶",
+            StaticIcon::Wifi2 => "📶",
+            StaticIcon::Wifi3 => "📶",
+            StaticIcon::Wifi4 => "📶",
+            StaticIcon::Wifi5 => "📶",
+            StaticIcon::WifiLock1 => "🔒",
+            StaticIcon::WifiLock2 => "🔒",
+            StaticIcon::WifiLock3 => "🔒",
+            StaticIcon::WifiLock4 => "🔒",
+            StaticIcon::WifiLock5 => "🔒",
+            StaticIcon::Ethernet => "🔌",
+            StaticIcon::Vpn => "🛡️",
+            StaticIcon::Bluetooth => "🦷",
+            StaticIcon::BluetoothConnected => "🦷",
+            StaticIcon::PowerSaver => "🍃",
+            StaticIcon::Balanced => "⚖️",
+            StaticIcon::Performance => "🚀",
+            StaticIcon::EyeOpened => "👁️",
+            StaticIcon::EyeClosed => "🙈",
+            StaticIcon::Lock => "🔒",
+            StaticIcon::Power => "⏻",
+            StaticIcon::Reboot => "🔁",
+            StaticIcon::Suspend => "🌙",
+            StaticIcon::Hibernate => "💤",
+            StaticIcon::Logout => "🚪",
+            StaticIcon::RightArrow => "➡️",
+            StaticIcon::Brightness => "🔆",
+            StaticIcon::Point => "•",
+            StaticIcon::Close => "✖️",
+            StaticIcon::Airplane => "✈️",
+            StaticIcon::Webcam => "📷",
+            StaticIcon::SkipPrevious => "⏮️",
+            StaticIcon::Play => "▶️",
+            StaticIcon::Pause => "⏸️",
+            StaticIcon::SkipNext => "⏭️",
+            StaticIcon::MusicNote => "🎵",
+            StaticIcon::Drive => "💾",
+            StaticIcon::IpAddress => "🌐",
+            StaticIcon::DownloadSpeed => "⬇️",
+            StaticIcon::UploadSpeed => "⬆️",
+            StaticIcon::Copy => "📋",
+            StaticIcon::RightChevron => "›",
+            StaticIcon::Keyboard => "⌨️",
+            StaticIcon::Mouse => "🖱️",
+            StaticIcon::Gamepad => "🎮",
+            StaticIcon::KeyboardBatteryFull => "🔋",
+            StaticIcon::KeyboardBatteryMedium => "🔋",
+            StaticIcon::KeyboardBatteryLow => "🪫",
+            StaticIcon::KeyboardBatteryAlert => "🪫",
+            StaticIcon::KeyboardBatteryCharging => "⚡",
+            StaticIcon::MouseBatteryFull => "🔋",
+            StaticIcon::MouseBatteryMedium => "🔋",
+            StaticIcon::MouseBatteryLow => "🪫",
+            StaticIcon::MouseBatteryAlert => "🪫",
+            StaticIcon::MouseBatteryCharging => "⚡",
+            StaticIcon::HeadphoneBatteryFull => "🔋",
+            StaticIcon::HeadphoneBatteryMedium => "🔋",
+            StaticIcon::HeadphoneBatteryLow => "🪫",
+            StaticIcon::HeadphoneBatteryAlert => "🪫",
+            StaticIcon::HeadphoneBatteryCharging => "⚡",
+            StaticIcon::GamepadBatteryFull => "🔋",
+            StaticIcon::GamepadBatteryMedium => "🔋",
+            StaticIcon::GamepadBatteryLow => "🪫",
+            StaticIcon::GamepadBatteryAlert => "🪫",
+            StaticIcon::GamepadBatteryCharging => "⚡",
+            StaticIcon::Remove => "➖",
+            StaticIcon::Rss => "📰",
+            StaticIcon::Git => "🐙",
+            StaticIcon::Tasks => "☑️",
+            StaticIcon::Alert => "⚠️",
+            StaticIcon::Bell => "🔔",
+            StaticIcon::BellOff => "🔕",
+            StaticIcon::Record => "🔴",
+            StaticIcon::Monitor => "🖥️",
         }
     }
 
@@ -229,7 +384,7 @@ impl StaticIcon {
             | StaticIcon::HeadphoneBatteryLow
             | StaticIcon::HeadphoneBatteryAlert
             | StaticIcon::HeadphoneBatteryCharging => "Ashell Custom Icon",
-            _ => "Symbols Nerd Font",
+            _ => icon_font(),
         }
     }
 
@@ -250,18 +405,42 @@ impl StaticIcon {
             | StaticIcon::HeadphoneBatteryLow
             | StaticIcon::HeadphoneBatteryAlert
             | StaticIcon::HeadphoneBatteryCharging => "Ashell Custom Icon",
-            _ => "Symbols Nerd Font Mono",
+            _ => icon_font_mono(),
+        }
+    }
+}
+
+impl StaticIcon {
+    /// Glyph and font replacing the bundled icon, from the user overrides or the icon set.
+    fn replacement(&self) -> Option<(String, Font)> {
+        let icons = ICONS.read().unwrap_or_else(|e| e.into_inner());
+
+        if !icons.overrides.is_empty()
+            && let Some(glyph) = icons.overrides.get(&format!("{self:?}"))
+        {
+            return Some((glyph.clone(), Font::with_name(icon_font())));
+        }
+
+        match icons.set {
+            IconSet::NerdFont => None,
+            IconSet::Emoji => Some((self.get_emoji().to_owned(), Font::DEFAULT)),
         }
     }
 }
 
 impl Icon for StaticIcon {
     fn to_text<'a>(self) -> Text<'a> {
-        text(self.get_str()).font(Font::with_name(self.get_font()))
+        match self.replacement() {
+            Some((glyph, font)) => text(glyph).font(font),
+            None => text(self.get_str()).font(Font::with_name(self.get_font())),
+        }
     }
 
     fn to_text_mono<'a>(self) -> Text<'a> {
-        text(self.get_str()).font(Font::with_name(self.get_font_mono()))
+        match self.replacement() {
+            Some((glyph, font)) => text(glyph).font(font),
+            None => text(self.get_str()).font(Font::with_name(self.get_font_mono())),
+        }
     }
 }
 
@@ -270,12 +449,12 @@ pub struct DynamicIcon(pub String);
 
 impl Icon for DynamicIcon {
     fn to_text<'a>(self) -> Text<'a> {
-        text(self.0).font(Font::with_name("Symbols Nerd Font"))
+        text(self.0).font(Font::with_name(icon_font()))
     }
 
     fn to_text_mono<'a>(self) -> Text<'a> {
         text(self.0)
-            .font(Font::with_name("Symbols Nerd Font Mono"))
+            .font(Font::with_name(icon_font_mono()))
             .line_height(1.0)
     }
 }
@@ -0,0 +1,51 @@
+use crate::{config::StyleRuleColor, theme::AshellTheme};
+use iced::{
+    Border, Element, Length, Theme,
+    widget::{Row, Space, container},
+};
+
+/// Bar filled up to the level, in percent, with the primary color when
+/// no color is given.
+pub fn level_bar<'a, Message: 'a>(
+    level: u32,
+    color: Option<StyleRuleColor>,
+    height: f32,
+    theme: &AshellTheme,
+) -> Element<'a, Message> {
+    let level = level.min(100) as u16;
+    let radius = theme.radius.sm;
+
+    container(
+        Row::new()
+            .push_maybe((level > 0).then(|| {
+                container(Space::new(Length::Fill, Length::Fill))
+                    .width(Length::FillPortion(level))
+                    .style(move |iced_theme: &Theme| container::Style {
+                        background: Some(
+                            color
+                                .map_or(iced_theme.palette().primary, |color| {
+                                    color.color(iced_theme)
+                                })
+                                .into(),
+                        ),
+                        border: Border {
+                            radius: radius.into(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+            }))
+            .push_maybe((level < 100).then(|| Space::with_width(Length::FillPortion(100 - level)))),
+    )
+    .width(Length::Fill)
+    .height(Length::Fixed(height))
+    .style(move |iced_theme: &Theme| container::Style {
+        background: Some(iced_theme.extended_palette().secondary.base.color.into()),
+        border: Border {
+            radius: radius.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .into()
+}
@@ -1 +1,2 @@
 pub mod icons;
+pub mod level_bar;
@@ -1,83 +1,483 @@
 use crate::app::Message;
-use crate::services::upower::PeripheralDeviceKind;
+use crate::services::{notifications::Urgency, upower::PeripheralDeviceKind};
+use crate::utils::state::{read_state, write_state};
 use hex_color::HexColor;
 use iced::futures::StreamExt;
 use iced::{Color, Subscription, futures::SinkExt, stream::channel, theme::palette};
 use inotify::EventMask;
 use inotify::Inotify;
+use inotify::WatchDescriptor;
 use inotify::WatchMask;
 use log::{debug, error, info, warn};
 use regex::Regex;
-use serde::{Deserialize, Deserializer, de::Visitor};
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned, de::Visitor};
 use serde_with::DisplayFromStr;
 use serde_with::serde_as;
+use std::borrow::Cow;
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::{any::TypeId, collections::HashMap, error::Error, ops::Deref, path::Path};
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout};
+
+mod dump;
+
+pub use dump::{default_config, json_schema};
 
 pub const DEFAULT_CONFIG_FILE_PATH: &str = "~/.config/ashell/config.toml";
+/// Profile name used to switch back to the config without profile overrides
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILE_STATE_FILE: &str = "profile.json";
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct Config {
+    /// Other config files, or glob patterns, merged before this one
+    pub include: Vec<String>,
+    /// Log level, in the `env_logger` syntax, like `warn` or `ashell::modules=debug`
     pub log_level: String,
+    /// Edge of the screen where the bar is placed
     pub position: Position,
+    /// Layer shell layer of the bar
     pub layer: Layer,
+    /// Height of the bar in logical pixels, its width on a vertical bar
+    pub height: u32,
+    /// Length of the bar along the screen edge: `Full`, `{ Percent = 60 }` or `{ Fixed = 1200 }`
+    pub width: BarWidth,
+    /// Placement of a bar shorter than the screen edge
+    pub align: BarAlign,
+    /// Collapse the bar to a thin strip until the pointer reaches the screen edge
+    pub auto_hide: AutoHideConfig,
+    /// Hide the bar of an output while it shows a fullscreen window, not reported by Niri and River
+    pub hide_on_fullscreen: bool,
+    /// Pause the polling of the modules while the session is locked, the displays are off or the bars are hidden
+    pub power_saving: bool,
+    /// Show the frame times, the update counts and the memory usage of the bar, also enabled by `--debug-overlay`
+    pub debug_overlay: bool,
+    /// Restart the subscriptions of the modules and the backends that panicked
+    pub watchdog: bool,
+    /// Limits of the caches of the icons and pictures
+    pub cache: CacheConfig,
+    /// Gaps in pixels between the bar and the screen edges
+    pub margin: MarginConfig,
+    /// Reserve the space of the bar, when disabled the bar floats over the windows
+    pub exclusive: bool,
+    /// Outputs where the bar is shown: `All`, `Active` or `{ Targets = ["eDP-1"] }`
     pub outputs: Outputs,
+    /// Modules shown in each section of the bar
     pub modules: Modules,
+    /// Modules running user defined commands
     #[serde(rename = "CustomModule")]
     pub custom_modules: Vec<CustomModuleDef>,
+    /// Names of the modules registered at runtime by external programs through the IPC socket
+    pub external_modules: Vec<String>,
+    /// Updates module, shown only when configured
     pub updates: Option<UpdatesModuleConfig>,
+    /// RSS/Atom feeds module
+    pub feeds: Option<FeedsModuleConfig>,
+    /// GitHub and GitLab notifications module
+    pub git_notifications: Option<GitNotificationsModuleConfig>,
+    /// Taskwarrior or todo.txt tasks module
+    pub tasks: Option<TasksModuleConfig>,
+    /// Workspaces module
     pub workspaces: WorkspacesModuleConfig,
+    /// Window title module
     pub window_title: WindowTitleConfig,
+    /// Taskbar module
+    pub taskbar: TaskbarModuleConfig,
+    /// System info module
     pub system_info: SystemInfoModuleConfig,
+    /// Clock module
     pub clock: ClockModuleConfig,
+    /// Settings module
     pub settings: SettingsModuleConfig,
+    /// Fonts, colors and style of the bar
     pub appearance: Appearance,
+    /// Media player module
     pub media_player: MediaPlayerModuleConfig,
+    /// Keyboard layout module
     pub keyboard_layout: KeyboardLayoutModuleConfig,
+    /// Keyboard submap module
+    pub keyboard_submap: KeyboardSubmapModuleConfig,
+    /// Privacy module
+    pub privacy: PrivacyModuleConfig,
+    /// Battery levels of the wireless peripherals
+    pub peripherals: PeripheralsModuleConfig,
+    /// Screen recording indicator
+    pub recording: RecordingModuleConfig,
+    /// Built-in notification daemon
+    pub notifications: NotificationsConfig,
+    /// On-screen display of the volume, brightness and caps lock changes
+    pub osd: OsdConfig,
+    /// Overlay listing the bindings of the active Hyprland submap
+    pub keybinds: KeybindsConfig,
+    /// Close the open menu with the esc key, it grabs the keyboard while a menu is open
     pub enable_esc_key: bool,
+    /// Commands run on the events of the bar
+    pub hooks: HooksConfig,
+    /// Port of the read-only status endpoint on `127.0.0.1`, disabled when unset
+    pub http_port: Option<u16>,
+    /// Origins of the web pages allowed to read the status endpoint
+    pub http_allowed_origins: Vec<String>,
+    /// Overrides applied to the bar of a single output
+    #[serde(rename = "Bar")]
+    pub bars: Vec<BarConfig>,
+    /// Named overrides that can be switched at runtime
+    #[serde(rename = "Profile")]
+    pub profiles: Vec<ProfileConfig>,
+    /// Config of each bar of the outputs with bar overrides, resolved while loading
+    #[serde(skip)]
+    pub bar_configs: HashMap<String, Vec<Config>>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            include: Vec::new(),
             log_level: "warn".to_owned(),
             position: Position::default(),
             layer: Layer::default(),
+            height: 34,
+            width: BarWidth::default(),
+            align: BarAlign::default(),
+            auto_hide: AutoHideConfig::default(),
+            hide_on_fullscreen: false,
+            power_saving: true,
+            debug_overlay: false,
+            watchdog: false,
+            cache: CacheConfig::default(),
+            margin: MarginConfig::default(),
+            exclusive: true,
             outputs: Outputs::default(),
             modules: Modules::default(),
             updates: None,
+            feeds: None,
+            git_notifications: None,
+            tasks: None,
             workspaces: WorkspacesModuleConfig::default(),
             window_title: WindowTitleConfig::default(),
+            taskbar: TaskbarModuleConfig::default(),
             system_info: SystemInfoModuleConfig::default(),
             clock: ClockModuleConfig::default(),
             settings: SettingsModuleConfig::default(),
             appearance: Appearance::default(),
             media_player: MediaPlayerModuleConfig::default(),
             keyboard_layout: KeyboardLayoutModuleConfig::default(),
+            keyboard_submap: KeyboardSubmapModuleConfig::default(),
+            privacy: PrivacyModuleConfig::default(),
+            peripherals: PeripheralsModuleConfig::default(),
+            recording: RecordingModuleConfig::default(),
+            notifications: NotificationsConfig::default(),
+            osd: OsdConfig::default(),
+            keybinds: KeybindsConfig::default(),
             custom_modules: vec![],
+            external_modules: Vec::new(),
             enable_esc_key: false,
+            hooks: HooksConfig::default(),
+            http_port: None,
+            http_allowed_origins: Vec::new(),
+            bars: Vec::new(),
+            profiles: Vec::new(),
+            bar_configs: HashMap::new(),
         }
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+/// Overrides applied to the bar of a single output.
+///
+/// Every key of the main config can be overridden, the values
+/// are merged over the global config. Every entry of the same
+/// output adds a bar to it, e.g. a top bar and a bottom taskbar.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct BarConfig {
+    /// Name of the output
+    pub output: String,
+    #[serde(flatten)]
+    pub overrides: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Named set of overrides that can be switched at runtime.
+///
+/// Every key of the main config can be overridden, the values
+/// are merged over the global config.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct ProfileConfig {
+    /// Name of the profile
+    pub name: String,
+    #[serde(flatten)]
+    pub overrides: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfileState {
+    active: Option<String>,
+}
+
+/// Returns the profile selected by the user, it's kept across restarts.
+pub fn active_profile() -> Option<String> {
+    read_state::<ProfileState>(PROFILE_STATE_FILE).active
+}
+
+pub fn set_active_profile(name: &str) {
+    info!("Switching to config profile {name}");
+
+    write_state(
+        PROFILE_STATE_FILE,
+        &ProfileState {
+            active: (name != DEFAULT_PROFILE).then(|| name.to_owned()),
+        },
+    );
+}
+
+/// Commands run on the events of the bar, the details of the
+/// event are passed in `ASHELL_*` environment variables.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run when a menu is opened, with `ASHELL_MENU` and `ASHELL_OUTPUT`
+    pub on_menu_open: Option<String>,
+    /// Run when a menu is closed, with `ASHELL_MENU` and `ASHELL_OUTPUT`
+    pub on_menu_close: Option<String>,
+    /// Run when the active workspace changes, with `ASHELL_WORKSPACE` and `ASHELL_PREVIOUS_WORKSPACE`
+    pub on_workspace_change: Option<String>,
+    /// Run when a new output is connected, with `ASHELL_OUTPUT`
+    pub on_output_added: Option<String>,
+    /// Run after the config is reloaded, with `ASHELL_CONFIG_PATH` and `ASHELL_CONFIG_ERROR` when it's invalid
+    pub on_config_reload: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct UpdatesModuleConfig {
-    pub check_cmd: String,
-    pub update_cmd: String,
+    /// Command printing the available updates, one per line
+    #[serde(default)]
+    pub check_cmd: Option<String>,
+    /// Command installing the updates
+    #[serde(default)]
+    pub update_cmd: Option<String>,
+    /// Command printing the changelog of the package in `ASHELL_PACKAGE`
+    #[serde(default)]
+    pub changelog_cmd: Option<String>,
+    /// Package managers checked for updates, their counts are added up
+    #[serde(default)]
+    pub backends: Vec<UpdatesBackend>,
+    /// Shows the count of each package manager next to the total in the bar
+    #[serde(default)]
+    pub backend_counts: bool,
+    /// Terminal running the update commands, like `kitty -e {}`,
+    /// `{}` is replaced by the update command
+    #[serde(default)]
+    pub terminal_cmd: Option<String>,
+    /// Seconds between two checks
     #[serde(default = "UpdatesModuleConfig::default_interval")]
     pub interval: u64,
+    /// Times of the day of the checks, like `09:00`, replacing the `interval`
+    #[serde(default)]
+    pub at: Vec<String>,
+    /// Checks after resuming from suspend
+    #[serde(default = "UpdatesModuleConfig::default_trigger")]
+    pub check_on_resume: bool,
+    /// Checks when the network comes back online, the checks are skipped while offline
+    #[serde(default = "UpdatesModuleConfig::default_trigger")]
+    pub check_on_network: bool,
+    /// Patterns of the packages left out of the updates, like held back kernels
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub ignore: Vec<RegexCfg>,
+    /// Patterns of the important packages, highlighted at the top of the menu
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub pin: Vec<RegexCfg>,
+    /// Feeds of news and advisories shown at the top of the menu
+    #[serde(default)]
+    pub news: Vec<FeedConfig>,
+    /// Shows the Arch Linux news at the top of the menu
+    #[serde(default)]
+    pub arch_news: bool,
+    /// Desktop notification when new updates appear, disabled when missing
+    #[serde(default)]
+    pub notification: Option<UpdatesNotificationConfig>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct UpdatesNotificationConfig {
+    /// Number of updates needed to notify
+    pub threshold: usize,
+    /// Minimum seconds between two notifications
+    pub interval: u64,
+}
+
+impl Default for UpdatesNotificationConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 1,
+            interval: 21600,
+        }
+    }
 }
 
 impl UpdatesModuleConfig {
     const fn default_interval() -> u64 {
         3600
     }
+
+    const fn default_trigger() -> bool {
+        true
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PackageManager {
+    /// Repository packages of Arch Linux, with `checkupdates`
+    Pacman,
+    /// AUR packages, with `paru`
+    Paru,
+    /// AUR packages, with `yay`
+    Yay,
+    Apt,
+    Dnf,
+    Zypper,
+    Xbps,
+    Flatpak,
+    /// Inputs of a Nix flake
+    Nix,
+    /// Firmware of the devices, with the fwupd daemon
+    Fwupd,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(untagged)]
+pub enum UpdatesBackend {
+    Simple(PackageManager),
+    Complete {
+        manager: PackageManager,
+        /// Replaces the command printing the available updates
+        check_cmd: Option<String>,
+        /// Replaces the command installing the updates, it's not run in the `terminal_cmd`
+        update_cmd: Option<String>,
+        /// Replaces the command printing the changelog of the package in `ASHELL_PACKAGE`
+        changelog_cmd: Option<String>,
+        /// Directory of the flake of the `Nix` backend, defaults to `/etc/nixos`
+        flake: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum FeedsFormat {
+    #[default]
+    UnreadCount,
+    Headline,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+pub struct FeedConfig {
+    /// Url of the RSS or Atom feed
+    pub url: String,
+    /// Name shown in the menu, defaults to the feed title
+    pub name: Option<String>,
+    /// Seconds between two fetches
+    #[serde(default = "FeedConfig::default_interval")]
+    pub interval: u64,
+}
+
+impl FeedConfig {
+    const fn default_interval() -> u64 {
+        900
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct FeedsModuleConfig {
+    /// What the bar shows: `UnreadCount` or `Headline`
+    pub format: FeedsFormat,
+    /// Entries shown in the menu, the newest across all feeds
+    pub max_items: usize,
+    /// Seconds each headline is shown
+    pub headline_interval: u64,
+    /// Headlines longer than this are truncated
+    pub max_headline_length: u32,
+    /// Feeds to follow
+    #[serde(rename = "Feed")]
+    pub feeds: Vec<FeedConfig>,
+}
+
+impl Default for FeedsModuleConfig {
+    fn default() -> Self {
+        Self {
+            format: FeedsFormat::default(),
+            max_items: 30,
+            headline_interval: 10,
+            max_headline_length: 50,
+            feeds: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GitForgeConfig {
+    /// Url of a self hosted instance
+    pub url: Option<String>,
+    /// Personal access token
+    pub token: Option<String>,
+    /// Command printing the token, used when `token` is not set
+    pub token_cmd: Option<String>,
+    /// Minimum seconds between two polls
+    #[serde(default = "GitForgeConfig::default_interval")]
+    pub interval: u64,
+}
+
+impl GitForgeConfig {
+    const fn default_interval() -> u64 {
+        60
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug)]
+#[serde(default)]
+pub struct GitNotificationsModuleConfig {
+    /// GitHub account
+    pub github: Option<GitForgeConfig>,
+    /// GitLab account
+    pub gitlab: Option<GitForgeConfig>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub enum TasksBackend {
+    #[default]
+    Taskwarrior,
+    TodoTxt,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct TasksModuleConfig {
+    /// Where the tasks are read from: `Taskwarrior` or `TodoTxt`
+    pub backend: TasksBackend,
+    /// Taskwarrior command
+    pub task_cmd: String,
+    /// Path of the todo.txt file
+    pub todo_file: String,
+    /// Due date set by the defer button, in the Taskwarrior syntax
+    pub defer: String,
+    /// Seconds between two refreshes
+    pub interval: u64,
+}
+
+impl Default for TasksModuleConfig {
+    fn default() -> Self {
+        Self {
+            backend: TasksBackend::default(),
+            task_cmd: "task".to_owned(),
+            todo_file: "~/todo.txt".to_owned(),
+            defer: "tomorrow".to_owned(),
+            interval: 300,
+        }
+    }
 }
 
-#[derive(Deserialize, Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
 pub enum WorkspaceVisibilityMode {
     #[default]
     All,
@@ -85,29 +485,38 @@ pub enum WorkspaceVisibilityMode {
     MonitorSpecificExclusive,
 }
 
-#[derive(Deserialize, Clone, Default, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug)]
 #[serde(default)]
 pub struct WorkspacesModuleConfig {
+    /// Which workspaces are shown on each output
     pub visibility_mode: WorkspaceVisibilityMode,
+    /// Group the workspaces by output
     pub group_by_monitor: bool,
+    /// Show the empty workspaces up to `max_workspaces`
     pub enable_workspace_filling: bool,
+    /// Hide the special workspaces
     pub disable_special_workspaces: bool,
+    /// Number of workspaces shown with `enable_workspace_filling`
     pub max_workspaces: Option<u32>,
+    /// Names shown in place of the workspace ids
     pub workspace_names: Vec<String>,
+    /// Handle each virtual desktop of the hyprland-virtual-desktops plugin as a single workspace
     pub enable_virtual_desktops: bool,
 }
 
-#[derive(Deserialize, Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
 pub enum WindowTitleMode {
     #[default]
     Title,
     Class,
 }
 
-#[derive(Deserialize, Copy, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Debug)]
 #[serde(default)]
 pub struct WindowTitleConfig {
+    /// What is shown: the window `Title` or its `Class`
     pub mode: WindowTitleMode,
+    /// Titles longer than this are truncated
     pub truncate_title_after_length: u32,
 }
 
@@ -120,17 +529,404 @@ impl Default for WindowTitleConfig {
     }
 }
 
-#[derive(Deserialize, Clone, Default, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct TaskbarModuleConfig {
+    /// Show the windows of every monitor, not only the ones of the monitor of the bar
+    pub all_monitors: bool,
+    /// Show the windows of an application as a single icon
+    pub group_by_app: bool,
+}
+
+impl Default for TaskbarModuleConfig {
+    fn default() -> Self {
+        Self {
+            all_monitors: false,
+            group_by_app: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum KeyboardLayoutFormat {
+    /// Name of the layout, e.g. `English (US, intl., with dead keys)`
+    #[default]
+    #[serde(alias = "full")]
+    Full,
+    /// Short code of the layout, e.g. `US`
+    #[serde(alias = "code")]
+    Code,
+    /// Flag of the country of the layout, e.g. 🇺🇸
+    #[serde(alias = "flag")]
+    Flag,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct KeyboardLayoutModuleConfig {
+    /// How the layouts without a label are shown: `Full`, `Code` or `Flag`
+    pub format: KeyboardLayoutFormat,
+    /// Labels shown in place of the layout names
     pub labels: HashMap<String, String>,
+    /// Keyboard whose layout is switched on Hyprland, `all` for every keyboard
+    pub device: String,
+    /// Remember the layout of each window and restore it when the window is focused
+    pub per_window: bool,
+}
+
+impl Default for KeyboardLayoutModuleConfig {
+    fn default() -> Self {
+        Self {
+            format: KeyboardLayoutFormat::default(),
+            labels: HashMap::new(),
+            device: "all".to_owned(),
+            per_window: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct PeripheralsModuleConfig {
+    /// Battery percentage at or below which a peripheral is shown as low
+    pub low_threshold: i64,
+    /// Send a desktop notification when a peripheral battery gets low
+    pub notify: bool,
+    /// Command run when a peripheral battery gets low, with the `ASHELL_DEVICE`
+    /// and `ASHELL_CAPACITY` environment variables
+    pub low_battery_cmd: Option<String>,
+}
+
+impl Default for PeripheralsModuleConfig {
+    fn default() -> Self {
+        Self {
+            low_threshold: 20,
+            notify: false,
+            low_battery_cmd: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct RecordingModuleConfig {
+    /// Process names of the screen recorders
+    pub recorders: Vec<String>,
+    /// Show the screens and windows shared through the screencast portal
+    /// as recordings too, like the recordings of OBS Studio
+    pub screencast: bool,
+    /// Color of the badge: `primary`, `secondary`, `success`, `danger`,
+    /// `text` or a hex color, the danger color when unset
+    pub color: Option<StyleRuleColor>,
+}
+
+impl Default for RecordingModuleConfig {
+    fn default() -> Self {
+        Self {
+            recorders: vec![
+                "wf-recorder".to_owned(),
+                "wl-screenrec".to_owned(),
+                "gpu-screen-recorder".to_owned(),
+            ],
+            screencast: true,
+            color: None,
+        }
+    }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+/// Corner of the output where the notifications are shown.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotificationPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Handle the desktop notifications, keep it disabled when another
+    /// notification daemon like mako or dunst is running
+    pub enabled: bool,
+    /// Milliseconds a notification stays on screen, when the application doesn't
+    /// choose. The critical notifications stay until they are dismissed
+    pub timeout: u64,
+    /// Notifications shown at once, the newest ones first
+    pub max_visible: usize,
+    /// Stack the successive notifications of the same application
+    pub group: bool,
+    /// Notifications kept in the history menu of the `Notifications` module
+    pub history_size: usize,
+    /// Show the progress of the notifications with a `value` hint, like the
+    /// downloads, next to the bell of the `Notifications` module until they complete
+    pub progress_in_bar: bool,
+    /// Time ranges without popups, e.g. `["22:00-07:00"]`
+    pub dnd_schedule: Vec<String>,
+    /// No popups while a window is fullscreen
+    pub dnd_fullscreen: bool,
+    /// No popups while the screen is shared
+    pub dnd_screen_sharing: bool,
+    /// Rules applied to the notifications of some applications
+    pub rules: Vec<NotificationRule>,
+    /// Notifications of low urgency
+    pub low: NotificationUrgencyConfig,
+    /// Notifications of normal urgency
+    pub normal: NotificationUrgencyConfig,
+    /// Critical notifications, they stay on screen until they are dismissed by default
+    pub critical: NotificationUrgencyConfig,
+    /// Corner of the output where the notifications are shown
+    pub position: NotificationPosition,
+    /// Output showing the notifications, the focused one when unset
+    pub output: Option<String>,
+}
+
+impl NotificationsConfig {
+    pub fn urgency(&self, urgency: Urgency) -> &NotificationUrgencyConfig {
+        match urgency {
+            Urgency::Low => &self.low,
+            Urgency::Normal => &self.normal,
+            Urgency::Critical => &self.critical,
+        }
+    }
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: 5000,
+            max_visible: 5,
+            group: true,
+            history_size: 50,
+            progress_in_bar: false,
+            dnd_schedule: Vec::new(),
+            dnd_fullscreen: false,
+            dnd_screen_sharing: false,
+            rules: Vec::new(),
+            low: NotificationUrgencyConfig::default(),
+            normal: NotificationUrgencyConfig::default(),
+            critical: NotificationUrgencyConfig {
+                sound: None,
+                timeout: Some(0),
+                color: Some(StyleRuleColor::Danger),
+            },
+            position: NotificationPosition::default(),
+            output: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum OsdPosition {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct OsdConfig {
+    /// Show the on-screen display, keep it disabled when another one like
+    /// swayosd is running
+    pub enabled: bool,
+    /// Milliseconds the display stays on screen after the last change
+    pub timeout: u64,
+    /// Show the changes of the volume of the default output device
+    pub volume: bool,
+    /// Show the changes of the screen brightness
+    pub brightness: bool,
+    /// Show when the caps lock is turned on or off
+    pub caps_lock: bool,
+    /// Where the display is shown, horizontally centered on the output
+    pub position: OsdPosition,
+    /// Width of the display in pixels
+    pub width: u32,
+    /// Color of the level bar: `primary`, `secondary`, `success`, `danger`,
+    /// `text` or a hex color, the primary color when unset
+    pub color: Option<StyleRuleColor>,
+    /// Output showing the display, the focused one when unset
+    pub output: Option<String>,
+}
+
+impl Default for OsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: 1500,
+            volume: true,
+            brightness: true,
+            caps_lock: true,
+            position: OsdPosition::default(),
+            width: 300,
+            color: None,
+            output: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct KeybindsConfig {
+    /// Show the bindings of a submap while it is active
+    pub enabled: bool,
+    /// Where the overlay is shown, horizontally centered on the output
+    pub position: OsdPosition,
+    /// Width of the overlay in pixels
+    pub width: u32,
+    /// Output showing the overlay, the focused one when unset
+    pub output: Option<String>,
+}
+
+impl Default for KeybindsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            position: OsdPosition::default(),
+            width: 420,
+            output: None,
+        }
+    }
+}
+
+/// Sound, timeout and color of the notifications of an urgency level.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug)]
+#[serde(default)]
+pub struct NotificationUrgencyConfig {
+    /// Sound played with the notifications, a sound file or the name of a
+    /// sound of the theme like `message-new-instant`
+    pub sound: Option<String>,
+    /// Milliseconds the notifications stay on screen, in place of the timeout
+    /// chosen by the application. `0` keeps them until they are dismissed
+    pub timeout: Option<u64>,
+    /// Color of the border and the title: `primary`, `secondary`, `success`,
+    /// `danger`, `text` or a hex color
+    pub color: Option<StyleRuleColor>,
+}
+
+/// Rule applied to the notifications of an application.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct NotificationRule {
+    /// Name of the application, matched ignoring the case
+    pub app_name: String,
+    /// Keep the notifications out of the popups, they are still in the history
+    #[serde(default)]
+    pub silence: bool,
+    /// Urgency given to the notifications: `Low`, `Normal` or `Critical`
+    pub urgency: Option<Urgency>,
+    /// Milliseconds the notifications stay on screen
+    pub timeout: Option<u64>,
+    /// Sound played with the notifications, a sound file or the name of a
+    /// sound of the theme like `message-new-instant`
+    pub sound: Option<String>,
+}
+
+/// Look of a keyboard submap on the bar.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug)]
+#[serde(default)]
+pub struct KeyboardSubmapStyle {
+    /// Text shown in place of the submap name
+    pub label: Option<String>,
+    /// Icon shown before the label
+    pub icon: Option<String>,
+    /// Color of the icon and the label
+    pub color: Option<StyleRuleColor>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug)]
+#[serde(default)]
+pub struct KeyboardSubmapModuleConfig {
+    /// Label, icon and color of each submap by name
+    pub submaps: HashMap<String, KeyboardSubmapStyle>,
+    /// Text shown while no submap is active, the module is hidden when unset
+    pub inactive_label: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum PrivacyClickAction {
+    /// Open the menu with the applications capturing
+    #[default]
+    #[serde(alias = "menu")]
+    Menu,
+    /// Mute or unmute the default microphone
+    #[serde(alias = "toggle_mic")]
+    ToggleMic,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PrivacyIndicator {
+    Microphone,
+    Webcam,
+    ScreenShare,
+    Location,
+}
+
+/// Colors of the privacy indicators, the danger color is used when unset.
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, Debug)]
+#[serde(default)]
+pub struct PrivacyColors {
+    pub microphone: Option<StyleRuleColor>,
+    pub webcam: Option<StyleRuleColor>,
+    pub screenshare: Option<StyleRuleColor>,
+    pub location: Option<StyleRuleColor>,
+}
+
+impl PrivacyColors {
+    pub fn get(&self, indicator: PrivacyIndicator) -> Option<StyleRuleColor> {
+        match indicator {
+            PrivacyIndicator::Microphone => self.microphone,
+            PrivacyIndicator::Webcam => self.webcam,
+            PrivacyIndicator::ScreenShare => self.screenshare,
+            PrivacyIndicator::Location => self.location,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct PrivacyModuleConfig {
+    /// Action run when clicking the module: `Menu` or `ToggleMic`
+    pub click_action: PrivacyClickAction,
+    /// Indicators shown on the bar, the disabled ones are left out of the menu too
+    pub indicators: Vec<PrivacyIndicator>,
+    /// Applications, PipeWire nodes or screencasts matching one of these regexes are ignored
+    #[schemars(with = "Vec<String>")]
+    pub ignore: Vec<RegexCfg>,
+    /// Color of each indicator
+    pub colors: PrivacyColors,
+    /// Number of privacy events kept in the log of the menu
+    pub log_length: usize,
+    /// File where the privacy events are appended
+    pub log_file: Option<String>,
+}
+
+impl Default for PrivacyModuleConfig {
+    fn default() -> Self {
+        Self {
+            click_action: PrivacyClickAction::default(),
+            indicators: vec![
+                PrivacyIndicator::Microphone,
+                PrivacyIndicator::Webcam,
+                PrivacyIndicator::ScreenShare,
+                PrivacyIndicator::Location,
+            ],
+            ignore: Vec::new(),
+            colors: PrivacyColors::default(),
+            log_length: 20,
+            log_file: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct SystemInfoCpu {
+    /// Usage percentage shown as a warning
     #[serde(default)]
     pub warn_threshold: u32,
+    /// Usage percentage shown as an alert
     #[serde(default)]
     pub alert_threshold: u32,
 }
@@ -144,10 +940,12 @@ impl Default for SystemInfoCpu {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct SystemInfoMemory {
+    /// Usage percentage shown as a warning
     pub warn_threshold: u32,
+    /// Usage percentage shown as an alert
     pub alert_threshold: u32,
 }
 
@@ -160,11 +958,14 @@ impl Default for SystemInfoMemory {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct SystemInfoTemperature {
+    /// Temperature shown as a warning
     pub warn_threshold: i32,
+    /// Temperature shown as an alert
     pub alert_threshold: i32,
+    /// Label of the sensor to read, as reported by `sensors`
     pub sensor: String,
 }
 
@@ -178,10 +979,12 @@ impl Default for SystemInfoTemperature {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct SystemInfoDisk {
+    /// Usage percentage shown as a warning
     pub warn_threshold: u32,
+    /// Usage percentage shown as an alert
     pub alert_threshold: u32,
 }
 
@@ -194,15 +997,17 @@ impl Default for SystemInfoDisk {
     }
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
 pub struct SystemInfoDiskIndicatorConfig {
+    /// Mount point of the disk
     #[serde(rename = "Disk")]
     pub path: String,
+    /// Label shown in place of the path
     #[serde(rename = "Name")]
     pub name: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub enum SystemInfoIndicator {
     Cpu,
     Memory,
@@ -215,13 +1020,18 @@ pub enum SystemInfoIndicator {
     Disk(SystemInfoDiskIndicatorConfig),
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct SystemInfoModuleConfig {
+    /// Indicators shown in the bar
     pub indicators: Vec<SystemInfoIndicator>,
+    /// Cpu thresholds
     pub cpu: SystemInfoCpu,
+    /// Memory thresholds
     pub memory: SystemInfoMemory,
+    /// Temperature thresholds and sensor
     pub temperature: SystemInfoTemperature,
+    /// Disk thresholds
     pub disk: SystemInfoDisk,
 }
 
@@ -241,8 +1051,9 @@ impl Default for SystemInfoModuleConfig {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct ClockModuleConfig {
+    /// Format of the date, see the chrono strftime syntax
     pub format: String,
 }
 
@@ -254,7 +1065,7 @@ impl Default for ClockModuleConfig {
     }
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
 pub enum SettingsIndicator {
     IdleInhibitor,
     PowerProfile,
@@ -266,7 +1077,7 @@ pub enum SettingsIndicator {
     PeripheralBattery,
 }
 
-#[derive(Deserialize, Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
 pub enum BatteryFormat {
     Icon,
     Percentage,
@@ -276,35 +1087,60 @@ pub enum BatteryFormat {
     IconAndTime,
 }
 
-#[derive(Deserialize, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq, Debug)]
 pub enum PeripheralIndicators {
     #[default]
     All,
     Specific(Vec<PeripheralDeviceKind>),
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct SettingsModuleConfig {
+    /// Command locking the screen, the lock button is hidden when unset
     pub lock_cmd: Option<String>,
+    /// Command shutting down the system
     pub shutdown_cmd: String,
+    /// Command suspending the system
     pub suspend_cmd: String,
+    /// Command hibernating the system
     pub hibernate_cmd: String,
+    /// Command rebooting the system
     pub reboot_cmd: String,
+    /// Command closing the session
     pub logout_cmd: String,
+    /// How the battery is shown
     pub battery_format: BatteryFormat,
+    /// Peripherals with a battery indicator: `All` or `{ Specific = ["Mouse"] }`
     pub peripheral_indicators: PeripheralIndicators,
+    /// How the peripheral batteries are shown
     pub peripheral_battery_format: BatteryFormat,
+    /// Command run by the more button of the audio outputs
     pub audio_sinks_more_cmd: Option<String>,
+    /// Command run by the more button of the audio inputs
     pub audio_sources_more_cmd: Option<String>,
+    /// Command run by the more button of the wifi networks
     pub wifi_more_cmd: Option<String>,
+    /// Command run by the more button of the vpn connections
     pub vpn_more_cmd: Option<String>,
+    /// Command run by the more button of the bluetooth devices
     pub bluetooth_more_cmd: Option<String>,
+    /// Hide the airplane mode button
     pub remove_airplane_btn: bool,
+    /// Hide the idle inhibitor button
     pub remove_idle_btn: bool,
+    /// Hide the displays button
+    pub remove_displays_btn: bool,
+    /// Indicators shown in the bar
     pub indicators: Vec<SettingsIndicator>,
+    /// Buttons running user defined commands
     #[serde(rename = "CustomButton")]
     pub custom_buttons: Vec<SettingsCustomButton>,
+    /// Names of the config profiles, resolved while loading
+    #[serde(skip)]
+    pub profiles: Vec<String>,
+    #[serde(skip)]
+    pub active_profile: Option<String>,
 }
 
 impl Default for SettingsModuleConfig {
@@ -326,6 +1162,7 @@ impl Default for SettingsModuleConfig {
             bluetooth_more_cmd: Default::default(),
             remove_airplane_btn: Default::default(),
             remove_idle_btn: Default::default(),
+            remove_displays_btn: Default::default(),
             indicators: vec![
                 SettingsIndicator::IdleInhibitor,
                 SettingsIndicator::PowerProfile,
@@ -336,30 +1173,39 @@ impl Default for SettingsModuleConfig {
                 SettingsIndicator::Battery,
             ],
             custom_buttons: Default::default(),
+            profiles: Vec::new(),
+            active_profile: None,
         }
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct SettingsCustomButton {
+    /// Label of the button
     pub name: String,
+    /// Nerd Font icon of the button
     pub icon: String,
+    /// Command run on click
     pub command: String,
+    /// Command telling whether the button is active, exit code 0 means active
     pub status_command: Option<String>,
+    /// Text shown on hover
     pub tooltip: Option<String>,
 }
 
-#[derive(Deserialize, Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
 pub enum MediaPlayerFormat {
     Icon,
     #[default]
     IconAndTitle,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct MediaPlayerModuleConfig {
+    /// Titles longer than this are truncated
     pub max_title_length: u32,
+    /// What the bar shows: `Icon` or `IconAndTitle`
     pub indicator_format: MediaPlayerFormat,
 }
 
@@ -372,14 +1218,18 @@ impl Default for MediaPlayerModuleConfig {
     }
 }
 
-#[derive(Deserialize, Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug)]
 #[serde(untagged)]
 pub enum AppearanceColor {
-    Simple(HexColor),
+    Simple(#[schemars(with = "String")] HexColor),
     Complete {
+        #[schemars(with = "String")]
         base: HexColor,
+        #[schemars(with = "Option<String>")]
         strong: Option<HexColor>,
+        #[schemars(with = "Option<String>")]
         weak: Option<HexColor>,
+        #[schemars(with = "Option<String>")]
         text: Option<HexColor>,
     },
 }
@@ -428,7 +1278,7 @@ impl AppearanceColor {
     }
 }
 
-#[derive(Deserialize, Default, Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Default, Copy, Clone, Eq, PartialEq, Debug)]
 pub enum AppearanceStyle {
     #[default]
     Islands,
@@ -436,12 +1286,18 @@ pub enum AppearanceStyle {
     Gradient,
 }
 
-#[derive(Deserialize, Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct MenuAppearance {
+    /// Opacity of the menus, between 0.0 and 1.0
     #[serde(deserialize_with = "opacity_deserializer")]
+    #[schemars(with = "f32")]
     pub opacity: f32,
+    /// Strength of the backdrop effect behind an open menu, `0.0` disables it
     pub backdrop: f32,
+    /// Maximum height in pixels of the menus by name (e.g. `settings` or `tray:nm-applet`),
+    /// the content of a taller menu scrolls
+    pub max_height: HashMap<String, u32>,
 }
 
 impl Default for MenuAppearance {
@@ -449,31 +1305,386 @@ impl Default for MenuAppearance {
         Self {
             opacity: default_opacity(),
             backdrop: f32::default(),
+            max_height: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct TooltipAppearance {
+    /// Whether the tooltips are shown
+    pub enabled: bool,
+    /// Time in milliseconds the cursor has to stay on a module before its tooltip is shown
+    pub delay: u64,
+}
+
+impl Default for TooltipAppearance {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay: 500,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct GradientStop {
+    /// Position of the stop, between 0.0 and 1.0
+    pub offset: f32,
+    /// Color of the stop, defaults to the background color
+    #[schemars(with = "Option<String>")]
+    pub color: Option<HexColor>,
+    /// Opacity of the stop, multiplied by the opacity of the bar
+    pub opacity: f32,
+}
+
+impl Default for GradientStop {
+    fn default() -> Self {
+        Self {
+            offset: 0.0,
+            color: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Color stops supported by the renderer.
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+fn default_gradient_stops() -> Vec<GradientStop> {
+    vec![
+        GradientStop::default(),
+        GradientStop {
+            offset: 1.0,
+            color: None,
+            opacity: 0.0,
+        },
+    ]
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct SectionGradient {
+    /// Angle in degrees, `180` goes from top to bottom, defaults to moving away from the screen edge
+    pub angle: Option<f32>,
+    /// Color stops, at most 8
+    pub stops: Vec<GradientStop>,
+}
+
+impl Default for SectionGradient {
+    fn default() -> Self {
+        Self {
+            angle: None,
+            stops: default_gradient_stops(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct GradientConfig {
+    /// Angle in degrees, `180` goes from top to bottom, defaults to moving away from the screen edge
+    pub angle: Option<f32>,
+    /// Color stops, at most 8
+    pub stops: Vec<GradientStop>,
+    /// Gradient behind the left modules
+    pub left: Option<SectionGradient>,
+    /// Gradient behind the center modules
+    pub center: Option<SectionGradient>,
+    /// Gradient behind the right modules
+    pub right: Option<SectionGradient>,
+}
+
+impl Default for GradientConfig {
+    fn default() -> Self {
+        Self {
+            angle: None,
+            stops: default_gradient_stops(),
+            left: None,
+            center: None,
+            right: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    #[default]
+    EaseOut,
+    EaseInOut,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct AnimationsConfig {
+    /// Whether the transitions are enabled
+    pub enabled: bool,
+    /// Duration in milliseconds of the menu fade and slide
+    pub menu_duration: u64,
+    /// Duration in milliseconds of the workspace changes
+    pub workspace_duration: u64,
+    /// Duration in milliseconds of the hover transitions of the module buttons
+    pub hover_duration: u64,
+    /// Duration in milliseconds of the fade in of an auto-hidden bar
+    pub reveal_duration: u64,
+    /// Easing curve of the transitions
+    pub easing: Easing,
+}
+
+impl Default for AnimationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            menu_duration: 150,
+            workspace_duration: 200,
+            hover_duration: 100,
+            reveal_duration: 150,
+            easing: Easing::default(),
         }
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(default)]
 pub struct Appearance {
+    /// Font of the bar, defaults to the system font
     pub font_name: Option<String>,
+    /// Font of the icons, defaults to the bundled Symbols Nerd Font
+    pub icon_font_name: Option<String>,
+    /// Font files loaded at startup, to use fonts that are not installed
+    pub font_files: Vec<String>,
+    /// Font overrides of the modules showing text, by module name
+    pub module_fonts: HashMap<String, ModuleFontConfig>,
+    /// Icon set and glyph overrides
+    pub icons: IconsConfig,
+    /// Theme file with appearance settings, the settings of this section take precedence
+    pub theme_file: Option<String>,
+    /// Where the colors come from, a color scheme replaces the colors of this section
+    pub theme_source: ThemeSource,
+    /// Whether the dark colors of this section or the `light` ones are used
+    pub mode: AppearanceMode,
+    /// Scale of the bar and of the menus, between 0.0 and 2.0
     #[serde(deserialize_with = "scale_factor_deserializer")]
+    #[schemars(with = "f64")]
     pub scale_factor: f64,
+    /// Style of the bar: `Islands`, `Solid` or `Gradient`
     pub style: AppearanceStyle,
+    /// Opacity of the bar, between 0.0 and 1.0
     #[serde(deserialize_with = "opacity_deserializer")]
+    #[schemars(with = "f32")]
     pub opacity: f32,
+    /// Appearance of the menus
     pub menu: MenuAppearance,
+    /// Tooltips shown while hovering the modules
+    pub tooltip: TooltipAppearance,
+    /// Angle and color stops of the `Gradient` style
+    pub gradient: GradientConfig,
+    /// Transitions of the menus, the workspaces and the hover states, `false` disables them
+    #[serde(deserialize_with = "animations_deserializer")]
+    #[schemars(with = "AnimationsConfig")]
+    pub animations: AnimationsConfig,
+    /// Background color
     pub background_color: AppearanceColor,
+    /// Color of the active elements
     pub primary_color: AppearanceColor,
+    /// Color of the secondary elements
     pub secondary_color: AppearanceColor,
+    /// Color of the success states
     pub success_color: AppearanceColor,
+    /// Color of the errors and alerts, `weak` is used for the warnings
     pub danger_color: AppearanceColor,
+    /// Text color
     pub text_color: AppearanceColor,
+    /// Colors of the workspaces, used in order for each output
     pub workspace_colors: Vec<AppearanceColor>,
+    /// Colors of the special workspaces
     pub special_workspace_colors: Option<Vec<AppearanceColor>>,
+    /// Colors used in light mode
+    pub light: AppearancePalette,
 }
 
-static PRIMARY: HexColor = HexColor::rgb(250, 179, 135);
+impl Appearance {
+    /// Appearance with the colors of the active mode.
+    pub fn with_mode(&self, system_prefers_light: bool) -> Appearance {
+        let light = match self.mode {
+            AppearanceMode::Dark => false,
+            AppearanceMode::Light => true,
+            AppearanceMode::Auto => system_prefers_light,
+        };
+
+        if light {
+            Appearance {
+                background_color: self.light.background_color,
+                primary_color: self.light.primary_color,
+                secondary_color: self.light.secondary_color,
+                success_color: self.light.success_color,
+                danger_color: self.light.danger_color,
+                text_color: self.light.text_color,
+                workspace_colors: self.light.workspace_colors.clone(),
+                special_workspace_colors: self.light.special_workspace_colors.clone(),
+                ..self.clone()
+            }
+        } else {
+            self.clone()
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Default, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum AppearanceMode {
+    /// The colors of the `appearance` section
+    #[default]
+    Dark,
+    /// The colors of the `appearance.light` section
+    Light,
+    /// Follow the color scheme preference of the desktop portal
+    Auto,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(default)]
+pub struct AppearancePalette {
+    /// Background color
+    pub background_color: AppearanceColor,
+    /// Color of the active elements
+    pub primary_color: AppearanceColor,
+    /// Color of the secondary elements
+    pub secondary_color: AppearanceColor,
+    /// Color of the success states
+    pub success_color: AppearanceColor,
+    /// Color of the errors and alerts, `weak` is used for the warnings
+    pub danger_color: AppearanceColor,
+    /// Text color
+    pub text_color: AppearanceColor,
+    /// Colors of the workspaces, used in order for each output
+    pub workspace_colors: Vec<AppearanceColor>,
+    /// Colors of the special workspaces
+    pub special_workspace_colors: Option<Vec<AppearanceColor>>,
+}
+
+impl Default for AppearancePalette {
+    // Catppuccin Latte
+    fn default() -> Self {
+        Self {
+            background_color: AppearanceColor::Complete {
+                base: HexColor::rgb(239, 241, 245),
+                strong: Some(HexColor::rgb(188, 192, 204)),
+                weak: Some(HexColor::rgb(204, 208, 218)),
+                text: None,
+            },
+            primary_color: AppearanceColor::Complete {
+                base: HexColor::rgb(254, 100, 11),
+                strong: None,
+                weak: None,
+                text: Some(HexColor::rgb(239, 241, 245)),
+            },
+            secondary_color: AppearanceColor::Complete {
+                base: HexColor::rgb(220, 224, 232),
+                strong: Some(HexColor::rgb(230, 233, 239)),
+                weak: None,
+                text: None,
+            },
+            success_color: AppearanceColor::Simple(HexColor::rgb(64, 160, 43)),
+            danger_color: AppearanceColor::Complete {
+                base: HexColor::rgb(210, 15, 57),
+                weak: Some(HexColor::rgb(223, 142, 29)),
+                strong: None,
+                text: None,
+            },
+            text_color: AppearanceColor::Simple(HexColor::rgb(76, 79, 105)),
+            workspace_colors: vec![
+                AppearanceColor::Simple(HexColor::rgb(254, 100, 11)),
+                AppearanceColor::Simple(HexColor::rgb(114, 135, 253)),
+                AppearanceColor::Simple(HexColor::rgb(136, 57, 239)),
+            ],
+            special_workspace_colors: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum FontWeight {
+    Thin,
+    ExtraLight,
+    Light,
+    #[default]
+    Normal,
+    Medium,
+    Semibold,
+    Bold,
+    ExtraBold,
+    Black,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct ModuleFontConfig {
+    /// Font family, defaults to the font of the bar
+    pub family: Option<String>,
+    /// Font weight
+    pub weight: FontWeight,
+    /// Font size, defaults to the size used by the module
+    pub size: Option<u16>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum IconSet {
+    /// The bundled Symbols Nerd Font glyphs
+    #[default]
+    NerdFont,
+    /// Emoji, rendered with the system emoji font
+    Emoji,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug)]
+#[serde(default)]
+pub struct IconsConfig {
+    /// Bundled icon style
+    pub set: IconSet,
+    /// Glyphs replacing single icons, by icon name
+    pub overrides: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq, Debug)]
+pub enum ThemeSource {
+    /// The colors of the `appearance` section
+    #[default]
+    Config,
+    /// The pywal color scheme in `~/.cache/wal/colors.json`
+    Pywal,
+    /// A color scheme in the pywal format, like the output of the wallust `colors.json` template
+    File(String),
+    /// A Material You palette generated by matugen
+    Matugen(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSchemeFormat {
+    Pywal,
+    Matugen,
+}
+
+impl ThemeSource {
+    /// Path and format of the color scheme file, watched for changes.
+    pub fn file(&self) -> Option<(PathBuf, ColorSchemeFormat)> {
+        let expand = |path: &str| PathBuf::from(shellexpand::tilde(path).as_ref());
+
+        match self {
+            ThemeSource::Config => None,
+            ThemeSource::Pywal => {
+                Some((expand("~/.cache/wal/colors.json"), ColorSchemeFormat::Pywal))
+            }
+            ThemeSource::File(path) => Some((expand(path), ColorSchemeFormat::Pywal)),
+            ThemeSource::Matugen(path) => Some((expand(path), ColorSchemeFormat::Matugen)),
+        }
+    }
+}
+
+static PRIMARY: HexColor = HexColor::rgb(250, 179, 135);
 
 fn scale_factor_deserializer<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
@@ -496,6 +1707,27 @@ where
     Ok(v)
 }
 
+/// Accepts either a boolean, to only enable or disable the animations, or the full settings.
+fn animations_deserializer<'de, D>(deserializer: D) -> Result<AnimationsConfig, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Animations {
+        Enabled(bool),
+        Config(AnimationsConfig),
+    }
+
+    Ok(match Animations::deserialize(deserializer)? {
+        Animations::Enabled(enabled) => AnimationsConfig {
+            enabled,
+            ..AnimationsConfig::default()
+        },
+        Animations::Config(config) => config,
+    })
+}
+
 fn opacity_deserializer<'de, D>(deserializer: D) -> Result<f32, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -523,10 +1755,20 @@ impl Default for Appearance {
     fn default() -> Self {
         Self {
             font_name: None,
+            icon_font_name: None,
+            font_files: Vec::new(),
+            module_fonts: HashMap::new(),
+            icons: IconsConfig::default(),
+            theme_file: None,
+            theme_source: ThemeSource::default(),
+            mode: AppearanceMode::default(),
             scale_factor: 1.0,
             style: AppearanceStyle::default(),
             opacity: default_opacity(),
             menu: MenuAppearance::default(),
+            tooltip: TooltipAppearance::default(),
+            gradient: GradientConfig::default(),
+            animations: AnimationsConfig::default(),
             background_color: AppearanceColor::Complete {
                 base: HexColor::rgb(30, 30, 46),
                 strong: Some(HexColor::rgb(69, 71, 90)),
@@ -559,29 +1801,121 @@ impl Default for Appearance {
                 AppearanceColor::Simple(HexColor::rgb(203, 166, 247)),
             ],
             special_workspace_colors: None,
+            light: AppearancePalette::default(),
+        }
+    }
+}
+
+/// Auto-hide mode of the bar.
+///
+/// The bar collapses to a strip on the screen edge, without reserving
+/// space for itself, and it's revealed when the pointer reaches the
+/// strip or when one of its menus is open.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct AutoHideConfig {
+    /// Whether the bar hides itself when it's not used
+    pub enabled: bool,
+    /// Milliseconds the bar stays visible after the pointer leaves it
+    pub delay: u64,
+    /// Size in pixels of the strip left on the screen edge
+    pub strip_size: u32,
+}
+
+impl Default for AutoHideConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay: 500,
+            strip_size: 2,
         }
     }
 }
 
-#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Number of entries kept by the caches, the least recently used ones are
+/// dropped first.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Icons found by name in the icon themes
+    pub icons: usize,
+    /// Pictures sent as raw data by the tray, the notifications and the custom modules
+    pub images: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            icons: 256,
+            images: 64,
+        }
+    }
+}
+
+/// Gaps around the bar, e.g. to match the `gaps_out` of Hyprland.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct MarginConfig {
+    /// Gap between the bar and the top edge
+    pub top: i32,
+    /// Gap between the bar and the bottom edge
+    pub bottom: i32,
+    /// Gap between the bar and the left edge
+    pub left: i32,
+    /// Gap between the bar and the right edge
+    pub right: i32,
+}
+
+/// Length of the bar along the screen edge, its height on a vertical bar.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BarWidth {
+    /// The bar spans the whole edge
+    #[default]
+    Full,
+    /// Percentage of the edge of the output
+    Percent(u32),
+    /// Length in logical pixels
+    Fixed(u32),
+}
+
+/// Placement of a bar shorter than the screen edge, from the left or top edge.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BarAlign {
+    Start,
+    #[default]
+    Center,
+    End,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Position {
     #[default]
     Top,
     Bottom,
+    Left,
+    Right,
+}
+
+impl Position {
+    /// Whether the bar is anchored to a side edge, with the modules stacked from top to bottom.
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Position::Left | Position::Right)
+    }
 }
 
-#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Layer {
     #[default]
     Bottom,
     Overlay,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ModuleName {
     Updates,
     Workspaces,
     WindowTitle,
+    Taskbar,
     SystemInfo,
     KeyboardLayout,
     KeyboardSubmap,
@@ -590,9 +1924,68 @@ pub enum ModuleName {
     Privacy,
     Settings,
     MediaPlayer,
+    Feeds,
+    GitNotifications,
+    Tasks,
+    Peripherals,
+    Notifications,
+    Recording,
     Custom(String),
 }
 
+impl ModuleName {
+    /// Name used in the config, the name of the custom modules for them.
+    pub fn name(&self) -> &str {
+        match self {
+            ModuleName::Updates => "Updates",
+            ModuleName::Workspaces => "Workspaces",
+            ModuleName::WindowTitle => "WindowTitle",
+            ModuleName::Taskbar => "Taskbar",
+            ModuleName::SystemInfo => "SystemInfo",
+            ModuleName::KeyboardLayout => "KeyboardLayout",
+            ModuleName::KeyboardSubmap => "KeyboardSubmap",
+            ModuleName::Tray => "Tray",
+            ModuleName::Clock => "Clock",
+            ModuleName::Privacy => "Privacy",
+            ModuleName::Settings => "Settings",
+            ModuleName::MediaPlayer => "MediaPlayer",
+            ModuleName::Feeds => "Feeds",
+            ModuleName::GitNotifications => "GitNotifications",
+            ModuleName::Tasks => "Tasks",
+            ModuleName::Peripherals => "Peripherals",
+            ModuleName::Notifications => "Notifications",
+            ModuleName::Recording => "Recording",
+            ModuleName::Custom(name) => name,
+        }
+    }
+}
+
+impl From<&str> for ModuleName {
+    fn from(value: &str) -> Self {
+        match value {
+            "Updates" => ModuleName::Updates,
+            "Workspaces" => ModuleName::Workspaces,
+            "WindowTitle" => ModuleName::WindowTitle,
+            "Taskbar" => ModuleName::Taskbar,
+            "SystemInfo" => ModuleName::SystemInfo,
+            "KeyboardLayout" => ModuleName::KeyboardLayout,
+            "KeyboardSubmap" => ModuleName::KeyboardSubmap,
+            "Tray" => ModuleName::Tray,
+            "Clock" => ModuleName::Clock,
+            "Privacy" => ModuleName::Privacy,
+            "Settings" => ModuleName::Settings,
+            "MediaPlayer" => ModuleName::MediaPlayer,
+            "Feeds" => ModuleName::Feeds,
+            "GitNotifications" => ModuleName::GitNotifications,
+            "Tasks" => ModuleName::Tasks,
+            "Peripherals" => ModuleName::Peripherals,
+            "Notifications" => ModuleName::Notifications,
+            "Recording" => ModuleName::Recording,
+            other => ModuleName::Custom(other.to_string()),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for ModuleName {
     fn deserialize<D>(deserializer: D) -> Result<ModuleName, D::Error>
     where
@@ -608,39 +2001,49 @@ impl<'de> Deserialize<'de> for ModuleName {
             where
                 E: serde::de::Error,
             {
-                Ok(match value {
-                    "Updates" => ModuleName::Updates,
-                    "Workspaces" => ModuleName::Workspaces,
-                    "WindowTitle" => ModuleName::WindowTitle,
-                    "SystemInfo" => ModuleName::SystemInfo,
-                    "KeyboardLayout" => ModuleName::KeyboardLayout,
-                    "KeyboardSubmap" => ModuleName::KeyboardSubmap,
-                    "Tray" => ModuleName::Tray,
-                    "Clock" => ModuleName::Clock,
-                    "Privacy" => ModuleName::Privacy,
-                    "Settings" => ModuleName::Settings,
-                    "MediaPlayer" => ModuleName::MediaPlayer,
-                    other => ModuleName::Custom(other.to_string()),
-                })
+                Ok(ModuleName::from(value))
             }
         }
         deserializer.deserialize_str(ModuleNameVisitor)
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+impl Serialize for ModuleName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl JsonSchema for ModuleName {
+    fn schema_name() -> Cow<'static, str> {
+        "ModuleName".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        // any other name refers to a custom module
+        String::json_schema(generator)
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 #[serde(untagged)]
 pub enum ModuleDef {
     Single(ModuleName),
     Group(Vec<ModuleName>),
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct Modules {
+    /// Modules on the left
     #[serde(default)]
     pub left: Vec<ModuleDef>,
+    /// Modules in the center
     #[serde(default)]
     pub center: Vec<ModuleDef>,
+    /// Modules on the right
     #[serde(default)]
     pub right: Vec<ModuleDef>,
 }
@@ -659,12 +2062,13 @@ impl Default for Modules {
     }
 }
 
-#[derive(Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default, Debug, PartialEq, Eq)]
 pub enum Outputs {
     #[default]
     All,
     Active,
     #[serde(deserialize_with = "non_empty")]
+    #[schemars(with = "Vec<String>")]
     Targets(Vec<String>),
 }
 
@@ -685,7 +2089,7 @@ where
 
 /// Newtype wrapper around `Regex`to be deserializable and usable as a hashmap key
 #[serde_as]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(transparent)]
 pub struct RegexCfg(#[serde_as(as = "DisplayFromStr")] pub Regex);
 
@@ -703,6 +2107,16 @@ impl std::hash::Hash for RegexCfg {
     }
 }
 
+impl JsonSchema for RegexCfg {
+    fn schema_name() -> Cow<'static, str> {
+        "Regex".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
+
 impl Deref for RegexCfg {
     type Target = Regex;
 
@@ -711,24 +2125,125 @@ impl Deref for RegexCfg {
     }
 }
 
-#[derive(Deserialize, Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
 pub enum CustomModuleType {
     #[default]
     Button,
     Text,
 }
 
+/// Color of a custom module whose text matches the pattern.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct StyleRule {
+    /// Regex matched against the text of the module
+    pub pattern: RegexCfg,
+    /// A color of the theme or a hex color
+    pub color: StyleRuleColor,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum StyleRuleColor {
+    Primary,
+    Secondary,
+    Success,
+    Danger,
+    Text,
+    #[serde(untagged)]
+    Hex(#[schemars(with = "String")] HexColor),
+}
+
+impl StyleRuleColor {
+    pub fn color(&self, theme: &iced::Theme) -> Color {
+        match self {
+            StyleRuleColor::Primary => theme.palette().primary,
+            StyleRuleColor::Secondary => theme.extended_palette().secondary.base.color,
+            StyleRuleColor::Success => theme.palette().success,
+            StyleRuleColor::Danger => theme.palette().danger,
+            StyleRuleColor::Text => theme.palette().text,
+            StyleRuleColor::Hex(color) => Color::from_rgb8(color.r, color.g, color.b),
+        }
+    }
+}
+
+/// How the `listen_cmd` of a custom module is run.
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub enum CustomModuleMode {
+    /// Started once, the module keeps its last output after the command exits
+    #[default]
+    #[serde(alias = "once")]
+    Once,
+    /// Long-running command, restarted with an increasing delay when it exits
+    #[serde(alias = "stream")]
+    Stream,
+}
+
+/// What a custom module shows when its `listen_cmd` exits with a non-zero code.
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum CustomModuleOnError {
+    /// Keeps the last output
+    #[default]
+    #[serde(alias = "keep")]
+    Keep,
+    /// Hides the module until the next output
+    #[serde(alias = "hide")]
+    Hide,
+    /// Replaces the text with the `error_text`, in the danger color
+    #[serde(alias = "text")]
+    Text,
+    /// Shows the last output in the danger color
+    #[serde(alias = "style")]
+    Style,
+}
+
 #[serde_as]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
 pub struct CustomModuleDef {
     pub name: String,
-    #[serde(default)]
+    /// Command run on a left click (for `Button` type), also accepted as `on_click`
+    #[serde(default, alias = "on_click")]
     pub command: Option<String>,
+    /// Command run on a right click
+    #[serde(default)]
+    pub on_click_right: Option<String>,
+    /// Command run on a middle click
+    #[serde(default)]
+    pub on_click_middle: Option<String>,
+    /// Command run when scrolling up on the module
+    #[serde(default)]
+    pub on_scroll_up: Option<String>,
+    /// Command run when scrolling down on the module
+    #[serde(default)]
+    pub on_scroll_down: Option<String>,
+    /// Text shown while hovering the module, the `tooltip` of the `listen_cmd` output takes precedence
+    #[serde(default)]
+    pub tooltip: Option<String>,
+    /// Command run when the tooltip is shown, its output is used as the tooltip
+    #[serde(default)]
+    pub tooltip_cmd: Option<String>,
+    /// Command printing the entries of the menu opened by a click, replaces the `command`
+    #[serde(default)]
+    pub menu_cmd: Option<String>,
+    /// Colors of the module depending on its text, the first matching rule wins
+    #[serde(default)]
+    pub style_rules: Vec<StyleRule>,
     #[serde(default)]
     pub icon: Option<String>,
 
     /// yields json lines containing text, alt, (pot tooltip)
     pub listen_cmd: Option<String>,
+    /// How the `listen_cmd` is run: `Once` or `Stream`
+    #[serde(default)]
+    pub mode: CustomModuleMode,
+    /// What the module shows when the `listen_cmd` exits with an error
+    #[serde(default)]
+    pub on_error: CustomModuleOnError,
+    /// Text shown by the `Text` error behavior
+    #[serde(default)]
+    pub error_text: Option<String>,
+    /// Times a failing `listen_cmd` is started again in `Once` mode, with an increasing delay
+    #[serde(default)]
+    pub retries: u32,
     /// map of regex -> icon
     pub icons: Option<HashMap<RegexCfg, String>>,
     /// regex to show alert
@@ -764,11 +2279,420 @@ pub fn get_config(path: Option<PathBuf>) -> Result<(Config, PathBuf), Box<dyn Er
                     .expect("Failed to create default config parent directory");
             }
 
+            let expanded = default_config_path(expanded);
+
             (read_config(&expanded).unwrap_or_default(), expanded)
         }),
     }
 }
 
+/// Falls back to the other supported formats when there is no TOML config.
+fn default_config_path(expanded: PathBuf) -> PathBuf {
+    if expanded.exists() {
+        expanded
+    } else {
+        ["yaml", "yml", "json"]
+            .into_iter()
+            .map(|ext| expanded.with_extension(ext))
+            .find(|path| path.exists())
+            .unwrap_or(expanded)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IssueLevel {
+    Warning,
+    Error,
+}
+
+/// Step of the path to a value of the config file.
+#[derive(Debug, Clone, Copy)]
+enum ConfigKey {
+    Key(&'static str),
+    Index(usize),
+    /// Element of an array of tables counted from its end, the arrays
+    /// of the included files come before the ones of the main file
+    FromEnd(usize),
+}
+
+#[derive(Debug, Clone)]
+struct ConfigIssue {
+    level: IssueLevel,
+    message: String,
+    /// Path of the value causing the issue, empty when there is none
+    path: Vec<ConfigKey>,
+}
+
+impl ConfigIssue {
+    fn error(message: String, path: Vec<ConfigKey>) -> Self {
+        Self {
+            level: IssueLevel::Error,
+            message,
+            path,
+        }
+    }
+
+    fn warning(message: String, path: Vec<ConfigKey>) -> Self {
+        Self {
+            level: IssueLevel::Warning,
+            message,
+            path,
+        }
+    }
+}
+
+/// Span of the value at `path` in a TOML document, `None` when the value
+/// comes from another file.
+fn value_span(
+    document: &toml::Spanned<toml::de::DeValue<'_>>,
+    path: &[ConfigKey],
+) -> Option<std::ops::Range<usize>> {
+    if path.is_empty() {
+        return None;
+    }
+
+    let mut value = document;
+    for key in path {
+        value = match *key {
+            ConfigKey::Key(key) => value.get_ref().get(key)?,
+            ConfigKey::Index(index) => value.get_ref().get(index)?,
+            ConfigKey::FromEnd(count) => {
+                let len = value.get_ref().as_array()?.len();
+                value.get_ref().get(len.checked_sub(count)?)?
+            }
+        };
+    }
+
+    Some(value.span())
+}
+
+impl Config {
+    /// Looks for problems that deserialization alone doesn't catch.
+    fn validate(&self, path: &Path) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        for (index, (pattern, expanded)) in self
+            .include
+            .iter()
+            .zip(include_patterns(path, &self.include))
+            .enumerate()
+        {
+            if !glob::glob(&expanded).is_ok_and(|mut paths| paths.next().is_some()) {
+                issues.push(ConfigIssue::warning(
+                    format!("include pattern `{pattern}` doesn't match any file"),
+                    vec![ConfigKey::Key("include"), ConfigKey::Index(index)],
+                ));
+            }
+        }
+
+        for (index, custom) in self.custom_modules.iter().enumerate() {
+            let custom_path = vec![
+                ConfigKey::Key("CustomModule"),
+                ConfigKey::FromEnd(self.custom_modules.len() - index),
+            ];
+
+            if self.custom_modules[..index]
+                .iter()
+                .any(|other| other.name == custom.name)
+            {
+                issues.push(ConfigIssue::error(
+                    format!("custom module `{}` is defined more than once", custom.name),
+                    [custom_path.as_slice(), &[ConfigKey::Key("name")]].concat(),
+                ));
+            }
+
+            if custom.command.is_none() && custom.listen_cmd.is_none() {
+                issues.push(ConfigIssue::warning(
+                    format!(
+                        "custom module `{}` has neither `command` nor `listen_cmd`",
+                        custom.name
+                    ),
+                    custom_path,
+                ));
+            }
+        }
+
+        let gradient = &self.appearance.gradient;
+        for (section, stops) in [
+            (None, Some(&gradient.stops)),
+            (
+                Some("left"),
+                gradient.left.as_ref().map(|section| &section.stops),
+            ),
+            (
+                Some("center"),
+                gradient.center.as_ref().map(|section| &section.stops),
+            ),
+            (
+                Some("right"),
+                gradient.right.as_ref().map(|section| &section.stops),
+            ),
+        ] {
+            if let Some(stops) = stops
+                && stops.len() > MAX_GRADIENT_STOPS
+            {
+                issues.push(ConfigIssue::warning(
+                    format!(
+                        "`appearance.gradient{}` has more than {MAX_GRADIENT_STOPS} stops, the others are ignored",
+                        section.map(|section| format!(".{section}")).unwrap_or_default()
+                    ),
+                    [ConfigKey::Key("appearance"), ConfigKey::Key("gradient")]
+                        .into_iter()
+                        .chain(section.map(ConfigKey::Key))
+                        .chain([ConfigKey::Key("stops")])
+                        .collect(),
+                ));
+            }
+        }
+
+        self.validate_layout("", &[], &mut issues);
+
+        for (index, profile) in self.profiles.iter().enumerate() {
+            let name_path = vec![
+                ConfigKey::Key("Profile"),
+                ConfigKey::FromEnd(self.profiles.len() - index),
+                ConfigKey::Key("name"),
+            ];
+
+            if profile.name == DEFAULT_PROFILE {
+                issues.push(ConfigIssue::error(
+                    format!("`{DEFAULT_PROFILE}` is reserved and can't be used as a profile name"),
+                    name_path,
+                ));
+            } else if self.profiles[..index]
+                .iter()
+                .any(|other| other.name == profile.name)
+            {
+                issues.push(ConfigIssue::warning(
+                    format!(
+                        "Profile `{}` is defined more than once, the first one is used",
+                        profile.name
+                    ),
+                    name_path,
+                ));
+            }
+        }
+
+        for (index, bar) in self.bars.iter().enumerate() {
+            // position of the bar between the bars of its output
+            let bar_index = self.bars[..index]
+                .iter()
+                .filter(|other| other.output == bar.output)
+                .count();
+
+            if bar.overrides.contains_key("modules")
+                && let Some(bar_config) = self
+                    .bar_configs
+                    .get(&bar.output)
+                    .and_then(|bars| bars.get(bar_index))
+            {
+                let prefix = if bar_index == 0 {
+                    format!("Bar[{}].", bar.output)
+                } else {
+                    format!("Bar[{}#{}].", bar.output, bar_index + 1)
+                };
+                bar_config.validate_layout(
+                    &prefix,
+                    &[
+                        ConfigKey::Key("Bar"),
+                        ConfigKey::FromEnd(self.bars.len() - index),
+                    ],
+                    &mut issues,
+                );
+            }
+        }
+
+        if let Some(git_notifications) = &self.git_notifications {
+            for (name, forge) in [
+                ("github", &git_notifications.github),
+                ("gitlab", &git_notifications.gitlab),
+            ] {
+                if let Some(forge) = forge
+                    && forge.token.is_none()
+                    && forge.token_cmd.is_none()
+                {
+                    issues.push(ConfigIssue::error(
+                        format!(
+                            "git_notifications.{name}: either `token` or `token_cmd` is required"
+                        ),
+                        vec![ConfigKey::Key("git_notifications"), ConfigKey::Key(name)],
+                    ));
+                }
+            }
+        }
+
+        if let Some(feeds) = &self.feeds
+            && feeds.feeds.is_empty()
+        {
+            issues.push(ConfigIssue::warning(
+                "feeds: no `Feed` configured".to_owned(),
+                vec![ConfigKey::Key("feeds")],
+            ));
+        }
+
+        issues
+    }
+
+    /// Checks that every module of the layout can be shown.
+    ///
+    /// `path` leads to the table holding the `modules` of this config.
+    fn validate_layout(&self, prefix: &str, path: &[ConfigKey], issues: &mut Vec<ConfigIssue>) {
+        let used_modules = [
+            ("left", &self.modules.left),
+            ("center", &self.modules.center),
+            ("right", &self.modules.right),
+        ]
+        .into_iter()
+        .flat_map(|(section, modules)| {
+            modules
+                .iter()
+                .enumerate()
+                .flat_map(move |(index, module)| {
+                    let module_path = [
+                        ConfigKey::Key("modules"),
+                        ConfigKey::Key(section),
+                        ConfigKey::Index(index),
+                    ];
+
+                    match module {
+                        ModuleDef::Single(module) => vec![(module_path.to_vec(), module)],
+                        ModuleDef::Group(group) => group
+                            .iter()
+                            .enumerate()
+                            .map(|(index, module)| {
+                                let mut module_path = module_path.to_vec();
+                                module_path.push(ConfigKey::Index(index));
+                                (module_path, module)
+                            })
+                            .collect(),
+                    }
+                })
+                .map(move |(module_path, module)| (section, module_path, module))
+        });
+
+        for (section, module_path, module) in used_modules {
+            let module_path = [path, &module_path].concat();
+
+            let missing_section = match module {
+                ModuleName::Custom(name) => {
+                    if !self
+                        .custom_modules
+                        .iter()
+                        .any(|custom| &custom.name == name)
+                        && !self.external_modules.contains(name)
+                    {
+                        issues.push(ConfigIssue::error(
+                            format!("{prefix}modules.{section}: unknown module `{name}`"),
+                            module_path.clone(),
+                        ));
+                    }
+                    None
+                }
+                ModuleName::Updates if self.updates.is_none() => Some(("Updates", "updates")),
+                ModuleName::Feeds if self.feeds.is_none() => Some(("Feeds", "feeds")),
+                ModuleName::GitNotifications if self.git_notifications.is_none() => {
+                    Some(("GitNotifications", "git_notifications"))
+                }
+                ModuleName::Tasks if self.tasks.is_none() => Some(("Tasks", "tasks")),
+                _ => None,
+            };
+
+            if let Some((name, config_section)) = missing_section {
+                issues.push(ConfigIssue::warning(
+                    format!(
+                        "{prefix}modules.{section}: `{name}` is used but the `{config_section}` section is missing, the module will be hidden"
+                    ),
+                    module_path,
+                ));
+            }
+        }
+    }
+}
+
+/// Loads and validates the config file, printing the problems found.
+///
+/// Returns `false` if the config can't be used as is.
+pub fn check_config(path: Option<PathBuf>) -> bool {
+    let path = match path {
+        Some(path) => expand_path(path),
+        None => expand_path(PathBuf::from(DEFAULT_CONFIG_FILE_PATH)).map(default_config_path),
+    };
+    let path = match path {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("error: invalid config path: {e}");
+            return false;
+        }
+    };
+
+    if !path.exists() {
+        eprintln!("error: config file {} does not exist", path.display());
+        return false;
+    }
+
+    let config = match read_config(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {}\n{e}", path.display());
+            return false;
+        }
+    };
+
+    // only TOML files keep track of the position of their values
+    let content = match ConfigFormat::from_path(&path) {
+        ConfigFormat::Toml => std::fs::read_to_string(&path).unwrap_or_default(),
+        ConfigFormat::Yaml | ConfigFormat::Json => String::new(),
+    };
+    let document = toml::de::DeTable::parse(&content).ok().map(|document| {
+        let span = document.span();
+        toml::Spanned::new(span, toml::de::DeValue::Table(document.into_inner()))
+    });
+    let issues = config.validate(&path);
+
+    for issue in &issues {
+        let level = match issue.level {
+            IssueLevel::Warning => "warning",
+            IssueLevel::Error => "error",
+        };
+        eprintln!("{level}: {}", issue.message);
+
+        match document
+            .as_ref()
+            .and_then(|document| value_span(document, &issue.path))
+        {
+            Some(span) => {
+                let line_start = content[..span.start]
+                    .rfind('\n')
+                    .map_or(0, |index| index + 1);
+                let line = content[line_start..].lines().next().unwrap_or_default();
+                let line_number = content[..span.start].matches('\n').count() + 1;
+                let column = content[line_start..span.start].chars().count() + 1;
+
+                eprintln!("  --> {}:{line_number}:{column}", path.display());
+                eprintln!("   | {}", line.trim_end());
+            }
+            None => eprintln!("  --> {}", path.display()),
+        }
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.level == IssueLevel::Error)
+        .count();
+
+    if errors > 0 {
+        eprintln!(
+            "{} is invalid: {errors} error(s), {} warning(s)",
+            path.display(),
+            issues.len() - errors
+        );
+        false
+    } else {
+        println!("{} is valid ({} warning(s))", path.display(), issues.len());
+        true
+    }
+}
+
 fn expand_path(path: PathBuf) -> Result<PathBuf, Box<dyn Error + Send>> {
     let str_path = path.to_string_lossy();
     let expanded =
@@ -777,13 +2701,278 @@ fn expand_path(path: PathBuf) -> Result<PathBuf, Box<dyn Error + Send>> {
     Ok(PathBuf::from(expanded.to_string()))
 }
 
-fn read_config(path: &Path) -> Result<Config, Box<dyn Error + Send>> {
+/// Expands the `include` patterns, relative patterns are resolved
+/// against the directory of the main config file.
+fn include_patterns(path: &Path, include: &[String]) -> Vec<String> {
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    include
+        .iter()
+        .map(|pattern| {
+            base.join(shellexpand::tilde(pattern).as_ref())
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+/// Returns the files matched by the include patterns.
+///
+/// Files are ordered by pattern first and then by path, so the merge
+/// order doesn't depend on the file system.
+fn resolve_includes(path: &Path, include: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for pattern in include_patterns(path, include) {
+        match glob::glob(&pattern) {
+            Ok(paths) => {
+                let mut paths = paths.flatten().collect::<Vec<_>>();
+                paths.sort();
+
+                for file in paths {
+                    if file != path && !files.contains(&file) {
+                        files.push(file);
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Invalid include pattern {pattern}: {e}");
+            }
+        }
+    }
+
+    files
+}
+
+/// Config files of any format, merged before being deserialized.
+///
+/// Unlike TOML, it can hold the `null` of YAML and JSON files.
+type Table = serde_json::Map<String, serde_json::Value>;
+
+/// Merges `overlay` into `base`.
+///
+/// Tables are merged recursively, arrays of tables (like `CustomModule`)
+/// are concatenated when `concat_arrays` is set and every other value is replaced.
+fn merge_tables(base: &mut Table, overlay: Table, concat_arrays: bool) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(serde_json::Value::Object(base)), serde_json::Value::Object(overlay)) => {
+                merge_tables(base, overlay, concat_arrays);
+            }
+            (Some(serde_json::Value::Array(base)), serde_json::Value::Array(overlay))
+                if concat_arrays
+                    && base
+                        .iter()
+                        .chain(overlay.iter())
+                        .all(serde_json::Value::is_object) =>
+            {
+                base.extend(overlay);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Resolves the theme file path, relative paths are resolved
+/// against the directory of the main config file.
+fn theme_file_path(path: &Path, theme_file: &str) -> PathBuf {
+    path.parent()
+        .unwrap_or(Path::new("."))
+        .join(shellexpand::tilde(theme_file).as_ref())
+}
+
+/// Loads the theme file referenced by the `appearance` table, if any,
+/// and uses it as the base of the appearance settings.
+fn apply_theme_file(path: &Path, config: &mut Table) -> Result<(), Box<dyn Error + Send>> {
+    let Some(theme_file) = config
+        .get("appearance")
+        .and_then(|appearance| appearance.get("theme_file"))
+        .and_then(serde_json::Value::as_str)
+    else {
+        return Ok(());
+    };
+    let file = theme_file_path(path, theme_file);
+
+    info!("Loading theme file {file:?}");
+
+    let mut theme = match std::fs::read_to_string(&file) {
+        Ok(content) => ConfigFormat::from_path(&file)
+            .parse::<Table>(&content)
+            .inspect_err(|e| {
+                warn!("Failed to parse theme file {file:?}: {e}");
+            })?,
+        Err(e) => {
+            warn!("Failed to read theme file {file:?}: {e}");
+            return Ok(());
+        }
+    };
+
+    if let Some(serde_json::Value::Object(appearance)) = config.remove("appearance") {
+        merge_tables(&mut theme, appearance, false);
+    }
+    config.insert("appearance".to_owned(), serde_json::Value::Object(theme));
+
+    Ok(())
+}
+
+/// Returns the files, besides the main config file, whose changes reload the config.
+fn watched_files(path: &Path, config: &Config) -> Vec<String> {
+    let mut files = config.include.clone();
+
+    if let Some(theme_file) = &config.appearance.theme_file {
+        files.push(
+            theme_file_path(path, theme_file)
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+
+    files
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detects the format from the file extension, falling back to TOML.
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T, Box<dyn Error + Send>> {
+        match self {
+            ConfigFormat::Toml => {
+                toml::from_str(content).map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+            }
+            ConfigFormat::Yaml => {
+                serde_yaml_ng::from_str(content).map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+            }
+            ConfigFormat::Json => {
+                serde_json::from_str(content).map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+            }
+        }
+    }
+}
+
+/// Resolves the config of every bar of the outputs with a bar override
+/// by merging the overrides over the global config.
+fn resolve_bars(
+    mut base: Table,
+    bars: &[BarConfig],
+) -> Result<HashMap<String, Vec<Config>>, Box<dyn Error + Send>> {
+    base.remove("Bar");
+    base.remove("Profile");
+    base.remove("include");
+
+    let mut configs = HashMap::<String, Vec<Config>>::new();
+
+    for bar in bars {
+        let mut table = base.clone();
+        merge_tables(&mut table, bar.overrides.clone(), false);
+
+        let config =
+            serde_json::from_value::<Config>(serde_json::Value::Object(table)).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid Bar override for output {}: {e}", bar.output),
+                )) as Box<dyn Error + Send>
+            })?;
+
+        configs.entry(bar.output.clone()).or_default().push(config);
+    }
+
+    Ok(configs)
+}
+
+/// Whether a section of the config changed, compared through its serialized
+/// form since not every config type can be compared directly.
+pub fn changed<T: Serialize>(old: &T, new: &T) -> bool {
+    serde_json::to_value(old).ok() != serde_json::to_value(new).ok()
+}
+
+pub fn read_config(path: &Path) -> Result<Config, Box<dyn Error + Send>> {
     let content =
         std::fs::read_to_string(path).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    let format = ConfigFormat::from_path(path);
+
+    info!("Decoding {format:?} config file {path:?}");
+
+    let res = format.parse::<Config>(&content).and_then(|config| {
+        if config.include.is_empty()
+            && config.bars.is_empty()
+            && config.profiles.is_empty()
+            && config.appearance.theme_file.is_none()
+        {
+            return Ok(config);
+        }
+
+        let mut merged = Table::new();
+        for file in resolve_includes(path, &config.include) {
+            info!("Including config file {file:?}");
+
+            match std::fs::read_to_string(&file) {
+                Ok(content) => {
+                    let mut table = ConfigFormat::from_path(&file)
+                        .parse::<Table>(&content)
+                        .inspect_err(|e| {
+                            warn!("Failed to parse included config file {file:?}: {e}");
+                        })?;
+                    // nested includes are not supported
+                    table.remove("include");
+
+                    merge_tables(&mut merged, table, true);
+                }
+                Err(e) => {
+                    warn!("Failed to read included config file {file:?}: {e}");
+                }
+            }
+        }
+
+        // the main file always has the last word
+        merge_tables(&mut merged, format.parse(&content)?, true);
 
-    info!("Decoding config file {path:?}");
+        let config: Config = serde_json::from_value(serde_json::Value::Object(merged.clone()))
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
 
-    let res = toml::from_str(&content);
+        let profile = active_profile().and_then(|name| {
+            config
+                .profiles
+                .iter()
+                .find(|profile| profile.name == name)
+                .cloned()
+        });
+        if let Some(profile) = &profile {
+            info!("Applying config profile {}", profile.name);
+
+            // like bar overrides, profiles replace lists instead of extending them
+            merge_tables(&mut merged, profile.overrides.clone(), false);
+        }
+
+        // applied last so that profiles can switch the theme
+        apply_theme_file(path, &mut merged)?;
+
+        let mut config: Config = serde_json::from_value(serde_json::Value::Object(merged.clone()))
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        config.settings.active_profile = profile.map(|profile| profile.name);
+        config.settings.profiles = config
+            .profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect();
+        config.bar_configs = resolve_bars(merged, &config.bars)?;
+
+        Ok(config)
+    });
 
     match res {
         Ok(config) => {
@@ -792,16 +2981,99 @@ fn read_config(path: &Path) -> Result<Config, Box<dyn Error + Send>> {
         }
         Err(e) => {
             warn!("Failed to parse config file: {e}");
-            Err(Box::new(e))
+            Err(e)
         }
     }
 }
 
+/// Returns the directories to watch to pick up changes to the
+/// main config file and to the files matched by the include patterns.
+fn watched_dirs(path: &Path, include: &[String]) -> Vec<PathBuf> {
+    let mut dirs = path
+        .parent()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    for pattern in include_patterns(path, include) {
+        // the longest leading part of the pattern without wildcards
+        let dir = Path::new(&pattern)
+            .ancestors()
+            .skip(1)
+            .find(|dir| !dir.to_string_lossy().contains(['*', '?', '[']))
+            .map(Path::to_path_buf);
+
+        if let Some(dir) = dir
+            && !dirs.contains(&dir)
+        {
+            dirs.push(dir);
+        }
+    }
+
+    dirs
+}
+
+/// Delay without file events before the config is reloaded
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
 enum Event {
     Changed,
     Removed,
 }
 
+/// Change of the config files in a batch of inotify events, the last one wins.
+fn config_event(
+    path: &Path,
+    dirs: &HashMap<WatchDescriptor, PathBuf>,
+    patterns: &[glob::Pattern],
+    events: Vec<std::io::Result<inotify::Event<OsString>>>,
+) -> Option<Event> {
+    let mut file_event = None;
+
+    for event in events {
+        debug!("Event: {event:?}");
+        let Ok(inotify::Event {
+            wd,
+            name: Some(name),
+            mask,
+            ..
+        }) = event
+        else {
+            debug!("Ignoring event");
+            continue;
+        };
+
+        let Some(file) = dirs.get(&wd).map(|dir| dir.join(name)) else {
+            continue;
+        };
+        let is_main = file == path;
+        if !is_main && !patterns.iter().any(|pattern| pattern.matches_path(&file)) {
+            debug!("Ignoring event");
+            continue;
+        }
+
+        match mask {
+            EventMask::DELETE | EventMask::MOVED_FROM if is_main => {
+                debug!("File deleted or moved");
+                file_event = Some(Event::Removed);
+            }
+            EventMask::CREATE
+            | EventMask::MODIFY
+            | EventMask::MOVED_TO
+            | EventMask::DELETE
+            | EventMask::MOVED_FROM => {
+                debug!("File {file:?} changed");
+                file_event = Some(Event::Changed);
+            }
+            _ => {
+                debug!("Ignoring event");
+            }
+        }
+    }
+
+    file_event
+}
+
 pub fn subscription(path: &Path) -> Subscription<Message> {
     let id = TypeId::of::<Config>();
     let path = path.to_path_buf();
@@ -809,102 +3081,116 @@ pub fn subscription(path: &Path) -> Subscription<Message> {
     Subscription::run_with_id(
         id,
         channel(100, async move |mut output| {
-            match (path.parent(), path.file_name(), Inotify::init()) {
-                (Some(folder), Some(file_name), Ok(inotify)) => {
-                    debug!("Watching config file at {path:?}");
+            if path.parent().is_none() {
+                error!(
+                    "Config file path does not have a parent directory, cannot watch for changes"
+                );
+                return;
+            }
 
-                    let res = inotify.watches().add(
-                        folder,
+            let inotify = match Inotify::init() {
+                Ok(inotify) => inotify,
+                Err(e) => {
+                    error!("Failed to initialize inotify: {e}");
+                    return;
+                }
+            };
+
+            let buffer = [0; 1024];
+            let Ok(stream) = inotify.into_event_stream(buffer) else {
+                error!("Failed to create inotify event stream");
+                return;
+            };
+
+            let mut watches = stream.watches();
+            let mut watched = HashMap::new();
+            let mut include = read_config(&path)
+                .map(|config| watched_files(&path, &config))
+                .unwrap_or_default();
+
+            let mut watch_dirs = |include: &[String]| {
+                for dir in watched_dirs(&path, include) {
+                    if watched.values().any(|watched| watched == &dir) {
+                        continue;
+                    }
+
+                    debug!("Watching config directory {dir:?}");
+                    match watches.add(
+                        &dir,
                         WatchMask::CREATE | WatchMask::DELETE | WatchMask::MOVE | WatchMask::MODIFY,
-                    );
+                    ) {
+                        Ok(wd) => {
+                            watched.insert(wd, dir);
+                        }
+                        Err(e) => {
+                            error!("Failed to add watch for {dir:?}: {e}");
+                        }
+                    }
+                }
+
+                watched.clone()
+            };
+            let mut dirs = watch_dirs(&include);
+
+            let mut stream = stream.ready_chunks(10);
+
+            debug!("Starting config file watch loop");
+
+            loop {
+                let events = stream.next().await.unwrap_or(vec![]);
 
-                    if let Err(e) = res {
-                        error!("Failed to add watch for {folder:?}: {e}");
-                        return;
+                debug!("Received inotify events: {events:?}");
+
+                let patterns = include_patterns(&path, &include)
+                    .iter()
+                    .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+                    .collect::<Vec<_>>();
+                let mut file_event = config_event(&path, &dirs, &patterns, events);
+
+                // editors often write the file in several steps, it is read
+                // once no more events come in
+                if file_event.is_some() {
+                    while let Ok(Some(events)) = timeout(RELOAD_DEBOUNCE, stream.next()).await {
+                        debug!("Received inotify events: {events:?}");
+
+                        if let Some(event) = config_event(&path, &dirs, &patterns, events) {
+                            file_event = Some(event);
+                        }
                     }
+                }
 
-                    let buffer = [0; 1024];
-                    let stream = inotify.into_event_stream(buffer);
-
-                    if let Ok(stream) = stream {
-                        let mut stream = stream.ready_chunks(10);
-
-                        debug!("Starting config file watch loop");
-
-                        loop {
-                            let events = stream.next().await.unwrap_or(vec![]);
-
-                            debug!("Received inotify events: {events:?}");
-
-                            let mut file_event = None;
-
-                            for event in events {
-                                debug!("Event: {event:?}");
-                                match event {
-                                    Ok(inotify::Event {
-                                        name: Some(name),
-                                        mask: EventMask::DELETE | EventMask::MOVED_FROM,
-                                        ..
-                                    }) if file_name == name => {
-                                        debug!("File deleted or moved");
-                                        file_event = Some(Event::Removed);
-                                    }
-                                    Ok(inotify::Event {
-                                        name: Some(name),
-                                        mask:
-                                            EventMask::CREATE | EventMask::MODIFY | EventMask::MOVED_TO,
-                                        ..
-                                    }) if file_name == name => {
-                                        debug!("File created or moved");
-
-                                        file_event = Some(Event::Changed);
-                                    }
-                                    _ => {
-                                        debug!("Ignoring event");
-                                    }
-                                }
-                            }
+                match file_event {
+                    Some(Event::Changed) => {
+                        info!("Reload config file");
+
+                        match read_config(&path) {
+                            Ok(new_config) => {
+                                // pick up directories of new include patterns and theme files
+                                include = watched_files(&path, &new_config);
+                                dirs = watch_dirs(&include);
 
-                            match file_event {
-                                Some(Event::Changed) => {
-                                    info!("Reload config file");
-
-                                    let new_config = read_config(&path).unwrap_or_default();
-
-                                    let _ = output
-                                        .send(Message::ConfigChanged(Box::new(new_config)))
-                                        .await;
-                                }
-                                Some(Event::Removed) => {
-                                    // wait and double check if the file is really gone
-                                    sleep(Duration::from_millis(500)).await;
-
-                                    if !path.exists() {
-                                        info!("Config file removed");
-                                        let _ = output
-                                            .send(Message::ConfigChanged(Box::default()))
-                                            .await;
-                                    }
-                                }
-                                None => {
-                                    debug!("No relevant file event detected.");
-                                }
+                                let _ = output
+                                    .send(Message::ConfigChanged(Box::new(new_config)))
+                                    .await;
+                            }
+                            Err(e) => {
+                                // keep the last good config active
+                                let _ = output.send(Message::ConfigError(e.to_string())).await;
                             }
                         }
-                    } else {
-                        error!("Failed to create inotify event stream");
                     }
-                }
-                (None, _, _) => {
-                    error!(
-                        "Config file path does not have a parent directory, cannot watch for changes"
-                    );
-                }
-                (_, None, _) => {
-                    error!("Config file path does not have a file name, cannot watch for changes");
-                }
-                (_, _, Err(e)) => {
-                    error!("Failed to initialize inotify: {e}");
+                    Some(Event::Removed) => {
+                        // wait and double check if the file is really gone
+                        sleep(Duration::from_millis(500)).await;
+
+                        if !path.exists() {
+                            info!("Config file removed");
+                            let _ = output.send(Message::ConfigChanged(Box::default())).await;
+                        }
+                    }
+                    None => {
+                        debug!("No relevant file event detected.");
+                    }
                 }
             }
         }),
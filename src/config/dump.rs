@@ -0,0 +1,192 @@
+use super::Config;
+use schemars::schema_for;
+use serde_json::Value;
+use std::fmt::Write;
+
+/// JSON schema of the config, editors can use it to validate and complete the config.
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(Config)).unwrap_or_default()
+}
+
+/// Default config in TOML, with the documentation of every field as comments.
+///
+/// Both values and comments come from the config structures,
+/// the fields without a default value are commented out.
+pub fn default_config() -> String {
+    let schema = schema_for!(Config).to_value();
+    let defaults = match toml::Value::try_from(Config::default()) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    };
+
+    let mut out =
+        String::from("# Default ashell config, generated by `ashell dump-default-config`\n\n");
+    write_table(&mut out, &schema, &schema, Some(&defaults), &[]);
+
+    out
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+/// Follows the references and skips the `null` branch of optional values.
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    if let Some(definition) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/$defs/"))
+        .and_then(|name| root["$defs"].get(name))
+    {
+        return resolve(root, definition);
+    }
+
+    match schema.get("anyOf").and_then(Value::as_array) {
+        Some(variants) if variants.len() == 2 && variants.iter().any(is_null) => variants
+            .iter()
+            .find(|variant| !is_null(variant))
+            .map_or(schema, |variant| resolve(root, variant)),
+        _ => schema,
+    }
+}
+
+fn properties<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+) -> Option<&'a serde_json::Map<String, Value>> {
+    let schema = resolve(root, schema);
+
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .or_else(|| {
+            // untagged enums, like the colors, can be written as a table
+            schema
+                .get("anyOf")
+                .and_then(Value::as_array)?
+                .iter()
+                .find_map(|variant| properties(root, variant))
+        })
+}
+
+fn possible_values(schema: &Value) -> Vec<&str> {
+    let values = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+    // documented or mixed variants are listed in `oneOf`
+    let variants = schema
+        .get("oneOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .flat_map(|variant| {
+            variant
+                .get("const")
+                .and_then(Value::as_str)
+                .into_iter()
+                .chain(possible_values(variant))
+        });
+
+    values.chain(variants).collect()
+}
+
+fn write_comment(out: &mut String, root: &Value, schema: &Value) {
+    let resolved = resolve(root, schema);
+    let description = schema
+        .get("description")
+        .or_else(|| resolved.get("description"))
+        .and_then(Value::as_str);
+
+    for line in description.into_iter().flat_map(str::lines) {
+        let _ = if line.is_empty() {
+            writeln!(out, "#")
+        } else {
+            writeln!(out, "# {line}")
+        };
+    }
+
+    let values = possible_values(resolved);
+    if !values.is_empty() {
+        let _ = writeln!(out, "# Possible values: {}", values.join(", "));
+    }
+}
+
+/// Value shown for the fields without a default.
+fn placeholder(root: &Value, schema: &Value) -> String {
+    let schema = resolve(root, schema);
+
+    if let Some(default) = schema
+        .get("default")
+        .and_then(|default| toml::Value::try_from(default).ok())
+    {
+        return default.to_string();
+    }
+
+    let schema_type = match schema.get("type") {
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        Some(value) => value.as_str(),
+        None => None,
+    };
+
+    match schema_type {
+        Some("integer" | "number") => "0",
+        Some("boolean") => "false",
+        Some("array") => "[]",
+        _ => "\"\"",
+    }
+    .to_owned()
+}
+
+fn is_table(root: &Value, schema: &Value) -> bool {
+    resolve(root, schema).get("type").and_then(Value::as_str) != Some("array")
+        && properties(root, schema).is_some()
+}
+
+fn write_table(
+    out: &mut String,
+    root: &Value,
+    schema: &Value,
+    values: Option<&toml::Table>,
+    path: &[&str],
+) {
+    let Some(properties) = properties(root, schema) else {
+        return;
+    };
+
+    // TOML requires the values of a table to come before its sub tables
+    let mut tables = Vec::new();
+    for (key, property) in properties {
+        match values.and_then(|values| values.get(key)) {
+            Some(toml::Value::Table(table)) if is_table(root, property) => {
+                tables.push((key, property, Some(table)));
+            }
+            None if is_table(root, property) => {
+                tables.push((key, property, None));
+            }
+            value => {
+                write_comment(out, root, property);
+                let _ = match value {
+                    Some(value) => writeln!(out, "{key} = {value}\n"),
+                    None => writeln!(out, "# {key} = {}\n", placeholder(root, property)),
+                };
+            }
+        }
+    }
+
+    for (key, property, table) in tables {
+        let path = [path, &[key.as_str()]].concat();
+
+        write_comment(out, root, property);
+        let _ = match table {
+            Some(_) => writeln!(out, "[{}]\n", path.join(".")),
+            None => writeln!(out, "# [{}]\n", path.join(".")),
+        };
+        write_table(out, root, property, table, &path);
+    }
+}
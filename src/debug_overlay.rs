@@ -0,0 +1,261 @@
+use crate::{
+    components::icons::{StaticIcon, icon},
+    services::registry::SharedSystem,
+    theme::AshellTheme,
+};
+use iced::{
+    Alignment, Element, Length, Subscription, Theme,
+    time::every,
+    widget::{container, row, text},
+};
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate};
+
+/// Interval of the samples shown by the overlay.
+const INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether the counters are recorded, only while the overlay is shown.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Set by `--debug-overlay`, the overlay is shown whatever the config says.
+static FORCED: AtomicBool = AtomicBool::new(false);
+
+static COUNTERS: Mutex<Counters> = Mutex::new(Counters::new());
+
+/// Counted since the last sample.
+#[derive(Debug)]
+struct Counters {
+    /// Messages by the part of the bar they are meant for
+    updates: BTreeMap<&'static str, u64>,
+    /// Messages produced by the subscriptions
+    wakeups: u64,
+    views: u64,
+    view_time: Duration,
+    view_max: Duration,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            updates: BTreeMap::new(),
+            wakeups: 0,
+            views: 0,
+            view_time: Duration::ZERO,
+            view_max: Duration::ZERO,
+        }
+    }
+}
+
+pub fn force() {
+    FORCED.store(true, Ordering::Relaxed);
+}
+
+/// Whether the overlay is shown, with the given `debug_overlay` config, and
+/// starts or stops the recording of the counters accordingly.
+pub fn set_enabled(config: bool) -> bool {
+    let enabled = config || FORCED.load(Ordering::Relaxed);
+    ENABLED.store(enabled, Ordering::Relaxed);
+
+    enabled
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn record_update(source: &'static str) {
+    if enabled()
+        && let Ok(mut counters) = COUNTERS.lock()
+    {
+        *counters.updates.entry(source).or_default() += 1;
+    }
+}
+
+/// Counts the messages of the subscriptions, mapped over all of them.
+pub fn record_wakeup<T>(message: T) -> T {
+    if enabled()
+        && let Ok(mut counters) = COUNTERS.lock()
+    {
+        counters.wakeups += 1;
+    }
+
+    message
+}
+
+/// Records the time spent building the view of a surface.
+pub fn record_view(elapsed: Duration) {
+    if enabled()
+        && let Ok(mut counters) = COUNTERS.lock()
+    {
+        counters.views += 1;
+        counters.view_time += elapsed;
+        counters.view_max = counters.view_max.max(elapsed);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Sample,
+}
+
+/// Rates measured over the last interval.
+#[derive(Debug, Clone, Default)]
+struct Sample {
+    updates: Vec<(&'static str, f32)>,
+    wakeups: f32,
+    views: f32,
+    view_avg: Duration,
+    view_max: Duration,
+    /// Resident memory in bytes
+    memory: u64,
+    cpu: f32,
+}
+
+impl Sample {
+    fn updates_per_sec(&self) -> f32 {
+        self.updates.iter().map(|(_, rate)| rate).sum()
+    }
+}
+
+/// Shows what the bar is busy with, to diagnose its CPU usage on an idle
+/// desktop.
+#[derive(Debug)]
+pub struct DebugOverlay {
+    system: SharedSystem,
+    pid: Option<Pid>,
+    sampled_at: Instant,
+    sample: Sample,
+}
+
+impl DebugOverlay {
+    pub fn new(system: SharedSystem) -> Self {
+        // the counts left from a previous overlay would skew the first sample
+        if let Ok(mut counters) = COUNTERS.lock() {
+            *counters = Counters::new();
+        }
+
+        Self {
+            system,
+            pid: sysinfo::get_current_pid().ok(),
+            sampled_at: Instant::now(),
+            sample: Sample::default(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Sample => {
+                let elapsed = self.sampled_at.elapsed().as_secs_f32().max(f32::EPSILON);
+                self.sampled_at = Instant::now();
+
+                let Ok(counters) = COUNTERS
+                    .lock()
+                    .map(|mut counters| std::mem::replace(&mut *counters, Counters::new()))
+                else {
+                    return;
+                };
+                let mut updates = counters
+                    .updates
+                    .into_iter()
+                    .map(|(source, count)| (source, count as f32 / elapsed))
+                    .collect::<Vec<_>>();
+                updates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+                self.sample = Sample {
+                    updates,
+                    wakeups: counters.wakeups as f32 / elapsed,
+                    views: counters.views as f32 / elapsed,
+                    view_avg: counters
+                        .view_time
+                        .checked_div(counters.views as u32)
+                        .unwrap_or_default(),
+                    view_max: counters.view_max,
+                    ..self.sample
+                };
+
+                if let Some(pid) = self.pid
+                    && let Ok(mut system) = self.system.lock()
+                {
+                    system.refresh_processes_specifics(
+                        ProcessesToUpdate::Some(&[pid]),
+                        true,
+                        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+                    );
+                    if let Some(process) = system.process(pid) {
+                        self.sample.memory = process.memory();
+                        self.sample.cpu = process.cpu_usage();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn view<'a, M: 'a>(&self, theme: &AshellTheme) -> Element<'a, M> {
+        let sample = &self.sample;
+        let busiest = sample
+            .updates
+            .first()
+            .map(|(source, rate)| format!(" · {source} {rate:.1}/s"))
+            .unwrap_or_default();
+
+        container(
+            row!(
+                icon(StaticIcon::Cpu),
+                text(format!(
+                    "{:.1}% · {} MB · view {:.1}/s {:.1}ms (max {:.1}ms) · {:.1} upd/s · {:.1} wake/s{busiest}",
+                    sample.cpu,
+                    sample.memory / 1024 / 1024,
+                    sample.views,
+                    sample.view_avg.as_secs_f32() * 1000.,
+                    sample.view_max.as_secs_f32() * 1000.,
+                    sample.updates_per_sec(),
+                    sample.wakeups,
+                ))
+                .size(theme.font_size.sm)
+                .wrapping(text::Wrapping::None),
+            )
+            .align_y(Alignment::Center)
+            .spacing(theme.space.xxs),
+        )
+        .padding([2, theme.space.xs])
+        .height(Length::Fill)
+        .align_y(Alignment::Center)
+        .style(|theme: &Theme| container::Style {
+            background: Some(theme.extended_palette().background.weak.color.into()),
+            text_color: Some(theme.extended_palette().background.weak.text),
+            ..Default::default()
+        })
+        .into()
+    }
+
+    /// The last sample with the rate of every part of the bar, for the
+    /// `diagnostics` IPC command.
+    pub fn diagnostics(&self) -> serde_json::Value {
+        let sample = &self.sample;
+
+        json!({
+            "cpu_percent": sample.cpu,
+            "memory_bytes": sample.memory,
+            "views_per_sec": sample.views,
+            "view_avg_ms": sample.view_avg.as_secs_f32() * 1000.,
+            "view_max_ms": sample.view_max.as_secs_f32() * 1000.,
+            "wakeups_per_sec": sample.wakeups,
+            "updates_per_sec": sample
+                .updates
+                .iter()
+                .map(|(source, rate)| (source.to_string(), json!(rate)))
+                .collect::<serde_json::Map<_, _>>(),
+        })
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        every(INTERVAL).map(|_| Message::Sample)
+    }
+}
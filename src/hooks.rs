@@ -0,0 +1,125 @@
+use crate::{app::App, config::HooksConfig, ipc::menu_name, modules::workspaces::Workspaces};
+use log::{debug, warn};
+use tokio::process::Command;
+
+/// Runs a hook command with the details of the event in its environment.
+fn run(command: &Option<String>, env: Vec<(&'static str, String)>) {
+    let Some(command) = command.clone() else {
+        return;
+    };
+
+    debug!("Running hook {command} with {env:?}");
+
+    tokio::spawn(async move {
+        match Command::new("bash")
+            .arg("-c")
+            .arg(&command)
+            .envs(env)
+            .status()
+            .await
+        {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("Hook {command} exited with {status}"),
+            Err(e) => warn!("Failed to run hook {command}: {e}"),
+        }
+    });
+}
+
+/// State compared before and after each update to find the events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookSnapshot {
+    menus: Vec<(String, String)>,
+    workspace: Option<String>,
+}
+
+impl App {
+    /// Returns `None` when there isn't any hook to run.
+    pub fn hook_snapshot(&self) -> Option<HookSnapshot> {
+        let hooks = &self.general_config.hooks;
+
+        if hooks.on_menu_open.is_none()
+            && hooks.on_menu_close.is_none()
+            && hooks.on_workspace_change.is_none()
+        {
+            return None;
+        }
+
+        Some(HookSnapshot {
+            menus: self
+                .outputs
+                .bars()
+                .filter_map(|(output, menu)| menu.map(|menu| (output.to_owned(), menu_name(menu))))
+                .collect(),
+            workspace: self
+                .workspaces
+                .as_ref()
+                .and_then(Workspaces::active_workspace)
+                .map(str::to_owned),
+        })
+    }
+
+    /// Runs the hooks of the changes happened since the snapshot was taken.
+    pub fn run_hooks(&self, before: Option<HookSnapshot>) {
+        let Some(before) = before else {
+            return;
+        };
+        let Some(after) = self.hook_snapshot() else {
+            return;
+        };
+        if before == after {
+            return;
+        }
+
+        let hooks = &self.general_config.hooks;
+
+        for (output, menu) in before.menus.iter().filter(|m| !after.menus.contains(m)) {
+            run(
+                &hooks.on_menu_close,
+                vec![
+                    ("ASHELL_MENU", menu.clone()),
+                    ("ASHELL_OUTPUT", output.clone()),
+                ],
+            );
+        }
+        for (output, menu) in after.menus.iter().filter(|m| !before.menus.contains(m)) {
+            run(
+                &hooks.on_menu_open,
+                vec![
+                    ("ASHELL_MENU", menu.clone()),
+                    ("ASHELL_OUTPUT", output.clone()),
+                ],
+            );
+        }
+
+        if let Some(workspace) = after.workspace
+            && before.workspace.as_ref() != Some(&workspace)
+        {
+            run(
+                &hooks.on_workspace_change,
+                vec![
+                    ("ASHELL_WORKSPACE", workspace),
+                    (
+                        "ASHELL_PREVIOUS_WORKSPACE",
+                        before.workspace.unwrap_or_default(),
+                    ),
+                ],
+            );
+        }
+    }
+}
+
+pub fn output_added(hooks: &HooksConfig, output: &str) {
+    run(
+        &hooks.on_output_added,
+        vec![("ASHELL_OUTPUT", output.to_owned())],
+    );
+}
+
+pub fn config_reloaded(hooks: &HooksConfig, path: &std::path::Path, error: Option<&str>) {
+    run(
+        &hooks.on_config_reload,
+        std::iter::once(("ASHELL_CONFIG_PATH", path.display().to_string()))
+            .chain(error.map(|error| ("ASHELL_CONFIG_ERROR", error.to_owned())))
+            .collect(),
+    );
+}
@@ -0,0 +1,204 @@
+//! Publish messages when the cursor enters and leaves the content.
+use crate::position_button::ButtonUIRef;
+use iced::{
+    Element, Length, Point, Rectangle, Size, Vector,
+    core::{
+        Clipboard, Layout, Shell, Widget,
+        event::{self, Event},
+        layout, mouse, overlay, renderer,
+        widget::{Operation, Tree, tree},
+    },
+};
+
+/// A container reporting the hover of its content, with the position
+/// of the content on the surface like the [`PositionButton`].
+///
+/// The events are always forwarded to the content.
+///
+/// [`PositionButton`]: crate::position_button::PositionButton
+#[allow(missing_debug_implementations)]
+pub struct HoverArea<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    on_enter: Box<dyn Fn(ButtonUIRef) -> Message + 'a>,
+    on_exit: Message,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    is_hovered: bool,
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for HoverArea<'_, Message, Theme, Renderer>
+where
+    Message: Clone,
+    Renderer: iced::core::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_mut(&mut self.content));
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        if let Event::Mouse(
+            mouse::Event::CursorMoved { .. }
+            | mouse::Event::CursorEntered
+            | mouse::Event::CursorLeft,
+        ) = event
+        {
+            let state = tree.state.downcast_mut::<State>();
+            let is_hovered = cursor.is_over(layout.bounds());
+
+            if is_hovered != state.is_hovered {
+                state.is_hovered = is_hovered;
+
+                shell.publish(if is_hovered {
+                    (self.on_enter)(ButtonUIRef {
+                        position: Point::new(
+                            layout.bounds().width / 2. + layout.position().x,
+                            layout.bounds().height / 2. + layout.position().y,
+                        ),
+                        viewport: (viewport.width, viewport.height),
+                    })
+                } else {
+                    self.on_exit.clone()
+                });
+            }
+        }
+
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer, translation)
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<HoverArea<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: 'a,
+    Renderer: iced::core::Renderer + 'a,
+{
+    fn from(hover_area: HoverArea<'a, Message, Theme, Renderer>) -> Self {
+        Self::new(hover_area)
+    }
+}
+
+/// Wraps the content to publish `on_enter` with its position when the cursor
+/// enters it, and `on_exit` when the cursor leaves it.
+pub fn hover_area<'a, Message, Theme, Renderer>(
+    content: impl Into<Element<'a, Message, Theme, Renderer>>,
+    on_enter: impl Fn(ButtonUIRef) -> Message + 'a,
+    on_exit: Message,
+) -> HoverArea<'a, Message, Theme, Renderer> {
+    HoverArea {
+        content: content.into(),
+        on_enter: Box::new(on_enter),
+        on_exit,
+    }
+}
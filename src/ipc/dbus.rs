@@ -0,0 +1,244 @@
+use super::{IpcCommand, IpcReply};
+use crate::{
+    app::{App, Message},
+    modules::{media_player::MediaPlayer, workspaces::Workspaces},
+};
+use iced::{
+    Subscription,
+    futures::{SinkExt, channel::mpsc::Sender},
+    stream::channel,
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    any::TypeId,
+    sync::{LazyLock, Mutex},
+};
+use tokio::sync::{oneshot, watch};
+use zbus::{fdo, interface, object_server::SignalEmitter};
+
+const NAME: &str = "org.ashell.Bar";
+const OBJECT_PATH: &str = "/org/ashell/Bar";
+
+/// State exposed as properties of the interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BarState {
+    pub visible: bool,
+    pub active_workspace: String,
+    pub media_playing: String,
+    pub updates_count: u32,
+}
+
+static STATE: LazyLock<watch::Sender<BarState>> =
+    LazyLock::new(|| watch::channel(BarState::default()).0);
+
+/// Status of the connection to the session bus.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", content = "error", rename_all = "snake_case")]
+pub enum Health {
+    Connecting,
+    Connected,
+    Failed(String),
+}
+
+static HEALTH: Mutex<Health> = Mutex::new(Health::Connecting);
+
+fn set_health(health: Health) {
+    if let Ok(mut current) = HEALTH.lock() {
+        *current = health;
+    }
+}
+
+pub fn health() -> Health {
+    HEALTH
+        .lock()
+        .map_or(Health::Connecting, |health| health.clone())
+}
+
+/// Updates the properties, the change signals are emitted only for the
+/// values that are different from the published ones.
+pub fn publish(state: BarState) {
+    STATE.send_if_modified(|current| {
+        if *current == state {
+            false
+        } else {
+            *current = state;
+            true
+        }
+    });
+}
+
+impl App {
+    pub fn bar_state(&self) -> BarState {
+        BarState {
+            visible: !self.outputs.is_hidden(),
+            active_workspace: self
+                .workspaces
+                .as_ref()
+                .and_then(Workspaces::active_workspace)
+                .unwrap_or_default()
+                .to_owned(),
+            media_playing: self
+                .media_player
+                .as_ref()
+                .and_then(MediaPlayer::now_playing)
+                .unwrap_or_default(),
+            updates_count: self
+                .updates
+                .as_ref()
+                .map_or(0, |updates| updates.count() as u32),
+        }
+    }
+}
+
+struct Bar {
+    output: Sender<Message>,
+    state: BarState,
+}
+
+impl Bar {
+    /// Forwards the command to the bar like a socket request.
+    async fn request(&self, command: IpcCommand) -> fdo::Result<()> {
+        let (sender, receiver) = oneshot::channel();
+
+        self.output
+            .clone()
+            .send(Message::Ipc(command, IpcReply::new(sender)))
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        let response = receiver
+            .await
+            .map_err(|_| fdo::Error::Failed("the request was dropped".to_owned()))?;
+
+        match response.error {
+            Some(error) => Err(fdo::Error::Failed(error)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An empty output targets the first output with a bar.
+fn target_output(output: String) -> Option<String> {
+    (!output.is_empty()).then_some(output)
+}
+
+#[interface(name = "org.ashell.Bar")]
+impl Bar {
+    async fn open_menu(&self, menu: String, output: String) -> fdo::Result<()> {
+        self.request(IpcCommand::OpenMenu {
+            menu,
+            output: target_output(output),
+        })
+        .await
+    }
+
+    async fn toggle_menu(&self, menu: String, output: String) -> fdo::Result<()> {
+        self.request(IpcCommand::ToggleMenu {
+            menu,
+            output: target_output(output),
+        })
+        .await
+    }
+
+    async fn close_menus(&self) -> fdo::Result<()> {
+        self.request(IpcCommand::CloseMenu { output: None }).await
+    }
+
+    async fn reload(&self) -> fdo::Result<()> {
+        self.request(IpcCommand::ReloadConfig).await
+    }
+
+    async fn set_visible(&self, visible: bool) -> fdo::Result<()> {
+        self.request(if visible {
+            IpcCommand::Show
+        } else {
+            IpcCommand::Hide
+        })
+        .await
+    }
+
+    async fn set_module_visible(&self, module: String, visible: bool) -> fdo::Result<()> {
+        self.request(if visible {
+            IpcCommand::ShowModule { module }
+        } else {
+            IpcCommand::HideModule { module }
+        })
+        .await
+    }
+
+    #[zbus(property)]
+    fn visible(&self) -> bool {
+        self.state.visible
+    }
+
+    #[zbus(property)]
+    fn active_workspace(&self) -> String {
+        self.state.active_workspace.clone()
+    }
+
+    #[zbus(property)]
+    fn media_playing(&self) -> String {
+        self.state.media_playing.clone()
+    }
+
+    #[zbus(property)]
+    fn updates_count(&self) -> u32 {
+        self.state.updates_count
+    }
+}
+
+async fn serve(output: Sender<Message>) -> anyhow::Result<()> {
+    let mut state = STATE.subscribe();
+    let bar = Bar {
+        output,
+        state: state.borrow_and_update().clone(),
+    };
+
+    let connection = zbus::connection::Builder::session()?
+        .name(NAME)?
+        .serve_at(OBJECT_PATH, bar)?
+        .build()
+        .await?;
+    let interface = connection
+        .object_server()
+        .interface::<_, Bar>(OBJECT_PATH)
+        .await?;
+    let emitter = SignalEmitter::new(&connection, OBJECT_PATH)?;
+
+    info!("D-Bus interface {NAME} published");
+    set_health(Health::Connected);
+
+    while state.changed().await.is_ok() {
+        let new_state = state.borrow_and_update().clone();
+        let mut bar = interface.get_mut().await;
+        let old_state = std::mem::replace(&mut bar.state, new_state);
+
+        if old_state.visible != bar.state.visible {
+            bar.visible_changed(&emitter).await?;
+        }
+        if old_state.active_workspace != bar.state.active_workspace {
+            bar.active_workspace_changed(&emitter).await?;
+        }
+        if old_state.media_playing != bar.state.media_playing {
+            bar.media_playing_changed(&emitter).await?;
+        }
+        if old_state.updates_count != bar.state.updates_count {
+            bar.updates_count_changed(&emitter).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn subscription() -> Subscription<Message> {
+    Subscription::run_with_id(
+        TypeId::of::<Bar>(),
+        channel(10, async move |output| {
+            if let Err(e) = serve(output).await {
+                warn!("Failed to publish the D-Bus interface {NAME}: {e}");
+                set_health(Health::Failed(e.to_string()));
+            }
+        }),
+    )
+}
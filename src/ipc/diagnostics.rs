@@ -0,0 +1,86 @@
+use crate::app::App;
+use flexi_logger::{DeferredNow, writers::LogWriter};
+use log::Record;
+use serde::Serialize;
+use serde_json::json;
+use std::{collections::VecDeque, io, sync::Mutex};
+
+/// Number of warnings and errors kept for the diagnostics.
+const MAX_ENTRIES: usize = 20;
+
+static RECENT: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Serialize)]
+struct LogEntry {
+    time: String,
+    level: String,
+    target: String,
+    message: String,
+}
+
+/// Log writer keeping the last warnings and errors in memory.
+pub struct RecentErrors;
+
+impl LogWriter for RecentErrors {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        if record.level() > log::Level::Warn {
+            return Ok(());
+        }
+
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == MAX_ENTRIES {
+                recent.pop_front();
+            }
+
+            recent.push_back(LogEntry {
+                time: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+                level: record.level().to_string(),
+                target: record.target().to_owned(),
+                message: record.args().to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl App {
+    /// Runtime state meant to be attached to bug reports.
+    pub fn diagnostics(&self) -> serde_json::Value {
+        let recent = RECENT
+            .lock()
+            .map(|recent| recent.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "git_hash": env!("GIT_HASH"),
+            "visible": !self.outputs.is_hidden(),
+            "ready": self.outputs.is_ready(),
+            "outputs": self.outputs.diagnostics(),
+            "modules": self
+                .layout_modules()
+                .into_iter()
+                .map(|module| json!({
+                    "name": module.name(),
+                    "hidden": self.hidden_modules.contains(&module),
+                    "subscribed": self.get_module_subscription(&module).is_some(),
+                }))
+                .collect::<Vec<_>>(),
+            "external_modules": self
+                .providers
+                .widgets()
+                .map(|widget| widget.name.as_str())
+                .collect::<Vec<_>>(),
+            "dbus": super::dbus::health(),
+            "http_port": self.general_config.http_port,
+            "config_error": self.config_error,
+            "recent_errors": recent,
+            "debug_overlay": self.debug_overlay.as_ref().map(|debug_overlay| debug_overlay.diagnostics()),
+        })
+    }
+}
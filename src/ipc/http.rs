@@ -0,0 +1,327 @@
+use crate::app::Message;
+use iced::{
+    Subscription,
+    futures::{SinkExt, StreamExt},
+    stream::channel,
+};
+use log::{debug, error, info, warn};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    time::interval,
+};
+use tokio_tungstenite::{
+    WebSocketStream,
+    tungstenite::{Message as WsMessage, handshake::derive_accept_key, protocol::Role},
+};
+
+/// Minimum time between two rebuilds of the served state.
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(250);
+
+static STATE: LazyLock<watch::Sender<serde_json::Value>> =
+    LazyLock::new(|| watch::channel(serde_json::Value::Null).0);
+
+struct Throttle {
+    published_at: Option<Instant>,
+    // an update was skipped since the last publish
+    pending: bool,
+}
+
+static THROTTLE: Mutex<Throttle> = Mutex::new(Throttle {
+    published_at: None,
+    pending: false,
+});
+
+/// Updates the state served by the endpoint, the WebSocket clients
+/// are notified only when it changes.
+///
+/// The state is built at most once every [`PUBLISH_INTERVAL`], a skipped
+/// update is caught up by the endpoint subscription.
+pub fn publish(state: impl FnOnce() -> serde_json::Value) {
+    if let Ok(mut throttle) = THROTTLE.lock() {
+        if throttle
+            .published_at
+            .is_some_and(|published_at| published_at.elapsed() < PUBLISH_INTERVAL)
+        {
+            throttle.pending = true;
+            return;
+        }
+        throttle.published_at = Some(Instant::now());
+        throttle.pending = false;
+    }
+
+    let state = state();
+    STATE.send_if_modified(|current| {
+        if *current == state {
+            false
+        } else {
+            *current = state;
+            true
+        }
+    });
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+/// Longest request or header line read, a longer one rejects the request.
+const MAX_LINE_LENGTH: u64 = 8 * 1024;
+
+/// Most header lines read for a request.
+const MAX_HEADERS: usize = 64;
+
+/// Reads a line of at most [`MAX_LINE_LENGTH`] bytes, `None` when it's longer.
+async fn read_line(
+    reader: &mut BufReader<TcpStream>,
+    line: &mut String,
+) -> std::io::Result<Option<usize>> {
+    line.clear();
+    let read = (&mut *reader).take(MAX_LINE_LENGTH).read_line(line).await?;
+
+    if read as u64 == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        Ok(None)
+    } else {
+        Ok(Some(read))
+    }
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>) -> anyhow::Result<Request> {
+    let mut line = String::new();
+    if read_line(reader, &mut line).await?.is_none() {
+        respond(
+            reader.get_mut(),
+            "400 Bad Request",
+            None,
+            r#"{"error":"request line too long"}"#,
+        )
+        .await?;
+        anyhow::bail!("request line too long");
+    }
+
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let path = parts.next().unwrap_or_default().to_owned();
+
+    let mut headers = HashMap::new();
+    let mut count = 0;
+    loop {
+        let read = read_line(reader, &mut line).await?;
+        if matches!(read, Some(read) if read == 0 || line.trim().is_empty()) {
+            break;
+        }
+
+        count += 1;
+        if read.is_none() || count > MAX_HEADERS {
+            respond(
+                reader.get_mut(),
+                "431 Request Header Fields Too Large",
+                None,
+                r#"{"error":"headers too large"}"#,
+            )
+            .await?;
+            anyhow::bail!("headers too large");
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_owned());
+        }
+    }
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+    })
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    origin: Option<&str>,
+    body: &str,
+) -> std::io::Result<()> {
+    // only the allowed origins are echoed back, never a wildcard
+    let cors = origin
+        .map(|origin| format!("Access-Control-Allow-Origin: {origin}\r\nVary: Origin\r\n"))
+        .unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{cors}Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await
+}
+
+async fn stream_state(mut stream: TcpStream, key: &str) -> anyhow::Result<()> {
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(response.as_bytes()).await?;
+
+    let mut websocket = WebSocketStream::from_raw_socket(stream, Role::Server, None).await;
+    let mut state = STATE.subscribe();
+
+    loop {
+        let message = state.borrow_and_update().to_string();
+        websocket.send(WsMessage::text(message)).await?;
+
+        // the clients can't send anything but pings and the close frame
+        loop {
+            tokio::select! {
+                changed = state.changed() => {
+                    changed?;
+                    break;
+                }
+                message = websocket.next() => match message {
+                    Some(Ok(WsMessage::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                }
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    port: u16,
+    allowed_origins: &[String],
+) -> anyhow::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = read_request(&mut reader).await?;
+    let mut stream = reader.into_inner();
+
+    // a DNS rebinding page is same origin, so it sends no `Origin`, but its
+    // `Host` is still the name it was loaded from
+    let host = request.headers.get("host").map(String::as_str);
+    if !host.is_some_and(|host| {
+        host == format!("127.0.0.1:{port}") || host == format!("localhost:{port}")
+    }) {
+        debug!("Status endpoint request for host {host:?} rejected");
+
+        return Ok(respond(
+            &mut stream,
+            "403 Forbidden",
+            None,
+            r#"{"error":"host not allowed"}"#,
+        )
+        .await?);
+    }
+
+    // browsers always send the origin of the page, other clients don't
+    let origin = request.headers.get("origin").map(String::as_str);
+    if let Some(origin) = origin
+        && !allowed_origins.iter().any(|allowed| allowed == origin)
+    {
+        debug!("Status endpoint request from {origin} rejected");
+
+        return Ok(respond(
+            &mut stream,
+            "403 Forbidden",
+            None,
+            r#"{"error":"origin not allowed"}"#,
+        )
+        .await?);
+    }
+
+    if request.path != "/state" {
+        return Ok(respond(
+            &mut stream,
+            "404 Not Found",
+            origin,
+            r#"{"error":"not found"}"#,
+        )
+        .await?);
+    }
+    if request.method != "GET" {
+        return Ok(respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            origin,
+            r#"{"error":"method not allowed"}"#,
+        )
+        .await?);
+    }
+
+    let upgrade = request
+        .headers
+        .get("upgrade")
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+
+    match request.headers.get("sec-websocket-key") {
+        Some(key) if upgrade => stream_state(stream, key).await,
+        _ => {
+            let state = STATE.borrow().to_string();
+
+            Ok(respond(&mut stream, "200 OK", origin, &state).await?)
+        }
+    }
+}
+
+struct StatusEndpoint;
+
+/// Serves the state on `127.0.0.1:<port>/state`, as JSON or as a WebSocket.
+///
+/// Requests are accepted only for the local host names, and the ones sent
+/// by a browser only from `allowed_origins`.
+pub fn subscription(port: u16, allowed_origins: Vec<String>) -> Subscription<Message> {
+    Subscription::run_with_id(
+        (
+            TypeId::of::<StatusEndpoint>(),
+            port,
+            allowed_origins.clone(),
+        ),
+        channel(1, async move |mut output| {
+            let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Failed to bind the status endpoint on port {port}: {e}");
+                    return;
+                }
+            };
+
+            info!("Serving the bar state on http://127.0.0.1:{port}/state");
+
+            let allowed_origins = Arc::new(allowed_origins);
+            let mut catch_up = interval(PUBLISH_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            let allowed_origins = allowed_origins.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = handle_connection(stream, port, &allowed_origins).await {
+                                    debug!("Status endpoint connection closed: {e}");
+                                }
+                            });
+                        }
+                        Err(e) => {
+                            warn!("Failed to accept a status endpoint connection: {e}");
+                        }
+                    },
+                    _ = catch_up.tick() => {
+                        let pending = THROTTLE
+                            .lock()
+                            .is_ok_and(|mut throttle| std::mem::take(&mut throttle.pending));
+                        // any message publishes the state once the interval is over
+                        if pending {
+                            let _ = output.send(Message::None).await;
+                        }
+                    }
+                }
+            }
+        }),
+    )
+}
@@ -0,0 +1,978 @@
+use crate::{
+    app::{App, Message},
+    chooser::Chooser,
+    config::{CustomModuleDef, DEFAULT_PROFILE, ModuleDef, ModuleName},
+    menu::MenuType,
+    modules::{
+        ModuleSection,
+        custom_module::{self, CustomListenData},
+        media_player::MediaPlayer,
+        provider::ProviderWidget,
+        workspaces::Workspaces,
+    },
+    position_button::ButtonUIRef,
+    toasts,
+};
+use anyhow::Context;
+use clap::{Subcommand, ValueEnum};
+use iced::{
+    Point, Subscription, Task,
+    futures::{SinkExt, channel::mpsc::Sender},
+    stream::channel,
+    window::Id,
+};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    any::TypeId,
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::oneshot,
+    time::sleep,
+};
+
+pub mod dbus;
+pub mod diagnostics;
+pub mod http;
+
+/// Path of the control socket, `$XDG_RUNTIME_DIR/ashell.sock`.
+///
+/// There's no fallback without `XDG_RUNTIME_DIR`, a shared directory would
+/// let another user take the socket over.
+pub fn socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("ashell.sock"))
+}
+
+/// Request sent over the socket, one JSON object per line.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcCommand {
+    /// Opens the menu or closes it if it's already open
+    ToggleMenu {
+        menu: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    OpenMenu {
+        menu: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    /// Closes the menu of the output, or every menu
+    CloseMenu {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    Show,
+    Hide,
+    ToggleVisibility,
+    /// Shows a module hidden at runtime
+    ShowModule {
+        module: String,
+    },
+    /// Hides a module of the layout until it's shown again
+    HideModule {
+        module: String,
+    },
+    ToggleModule {
+        module: String,
+    },
+    ReloadConfig,
+    /// Selects a config profile, `default` disables the profile overrides
+    SwitchProfile {
+        name: String,
+    },
+    /// Replaces the output of a custom module, like a line of its `listen_cmd`
+    SetCustomText {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        #[serde(default)]
+        alt: String,
+    },
+    /// Adds a module, or replaces its content, until it's unregistered
+    /// or the connection that registered it is closed
+    RegisterModule(ProviderWidget),
+    UnregisterModule {
+        name: String,
+    },
+    /// Adds a custom module to the layout without touching the config,
+    /// it's removed on restart
+    AddModule {
+        section: ModuleSection,
+        /// Position in the section, after the other modules when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+        module: CustomModuleDef,
+    },
+    /// Removes a module added with `add_module`
+    RemoveModule {
+        name: String,
+    },
+    /// Shows the options in a menu, the reply holds the picked one
+    Choose {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prompt: Option<String>,
+        options: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    /// Shows a transient toast, the first line of the text is its title
+    Toast {
+        text: String,
+        /// Icon name, path or `file://` URI
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+        /// Milliseconds on screen, `0` until dismissed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u64>,
+    },
+    GetState,
+    /// Returns the outputs, the surfaces, the modules and the last errors
+    Diagnostics,
+    /// Reports whether the bars are up
+    Ping,
+}
+
+/// Query of `ashell msg get`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum MsgQuery {
+    /// State of the bars and of the modules
+    State,
+    /// Runtime details to attach to bug reports
+    Diagnostics,
+}
+
+/// Commands of the `ashell msg` client.
+#[derive(Subcommand, Debug)]
+pub enum MsgCommand {
+    /// Open the menu or close it if it's already open
+    ToggleMenu {
+        /// Name of the menu, like `settings` or `tray:<application name>`
+        menu: String,
+        /// Target the first output whose name contains this value
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Open the menu
+    OpenMenu {
+        /// Name of the menu, like `settings` or `tray:<application name>`
+        menu: String,
+        /// Target the first output whose name contains this value
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Close the menu of the output, or every open menu
+    CloseMenu {
+        /// Target the first output whose name contains this value
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Show the bars
+    Show,
+    /// Hide the bars
+    Hide,
+    /// Hide the bars or show them again
+    ToggleVisibility,
+    /// Show a module hidden at runtime
+    ShowModule {
+        /// Name of the module in the layout, like `Tray` or the name of a custom module
+        module: String,
+    },
+    /// Hide a module of the layout, it stays hidden across restarts
+    HideModule {
+        /// Name of the module in the layout, like `Tray` or the name of a custom module
+        module: String,
+    },
+    /// Hide the module or show it again
+    ToggleModule {
+        /// Name of the module in the layout, like `Tray` or the name of a custom module
+        module: String,
+    },
+    /// Reload the config file
+    ReloadConfig,
+    /// Switch to the config profile, it's remembered across restarts
+    SwitchProfile {
+        /// Name of the profile, `default` disables the profile overrides
+        name: String,
+    },
+    /// Replace the output of a custom module
+    SetCustomText {
+        /// Name of the custom module
+        name: String,
+        /// Text to show, the module falls back to its icon without it
+        text: Option<String>,
+        /// Key of the `icons` and `alert` regexes of the module
+        #[arg(long, default_value = "")]
+        alt: String,
+    },
+    /// Add a custom module to the layout until it's removed or ashell restarts
+    AddModule {
+        #[arg(value_enum)]
+        section: ModuleSection,
+        /// Definition of the module as JSON, with the fields of a `[[CustomModule]]`
+        #[arg(value_parser = parse_module_definition)]
+        module: CustomModuleDef,
+        /// Position in the section, after the other modules when missing
+        #[arg(long)]
+        index: Option<usize>,
+    },
+    /// Remove a module added with `add-module`
+    RemoveModule {
+        /// Name of the custom module
+        name: String,
+    },
+    /// Show a transient toast, the first line of the text is its title
+    Toast {
+        text: String,
+        /// Icon name, path or `file://` URI
+        #[arg(long)]
+        icon: Option<String>,
+        /// Milliseconds on screen, `0` keeps it until it's dismissed
+        #[arg(long)]
+        timeout: Option<u64>,
+    },
+    /// Check whether the bars are up, exits with a non-zero code when they aren't
+    Ping,
+    /// Query the running instance
+    Get {
+        #[arg(value_enum)]
+        query: MsgQuery,
+    },
+}
+
+fn parse_module_definition(value: &str) -> Result<CustomModuleDef, String> {
+    serde_json::from_str(value).map_err(|e| e.to_string())
+}
+
+impl From<MsgCommand> for IpcCommand {
+    fn from(command: MsgCommand) -> Self {
+        match command {
+            MsgCommand::ToggleMenu { menu, output } => IpcCommand::ToggleMenu { menu, output },
+            MsgCommand::OpenMenu { menu, output } => IpcCommand::OpenMenu { menu, output },
+            MsgCommand::CloseMenu { output } => IpcCommand::CloseMenu { output },
+            MsgCommand::Show => IpcCommand::Show,
+            MsgCommand::Hide => IpcCommand::Hide,
+            MsgCommand::ToggleVisibility => IpcCommand::ToggleVisibility,
+            MsgCommand::ShowModule { module } => IpcCommand::ShowModule { module },
+            MsgCommand::HideModule { module } => IpcCommand::HideModule { module },
+            MsgCommand::ToggleModule { module } => IpcCommand::ToggleModule { module },
+            MsgCommand::ReloadConfig => IpcCommand::ReloadConfig,
+            MsgCommand::SwitchProfile { name } => IpcCommand::SwitchProfile { name },
+            MsgCommand::SetCustomText { name, text, alt } => {
+                IpcCommand::SetCustomText { name, text, alt }
+            }
+            MsgCommand::AddModule {
+                section,
+                module,
+                index,
+            } => IpcCommand::AddModule {
+                section,
+                index,
+                module,
+            },
+            MsgCommand::RemoveModule { name } => IpcCommand::RemoveModule { name },
+            MsgCommand::Toast {
+                text,
+                icon,
+                timeout,
+            } => IpcCommand::Toast {
+                text,
+                icon,
+                timeout,
+            },
+            MsgCommand::Ping => IpcCommand::Ping,
+            MsgCommand::Get {
+                query: MsgQuery::State,
+            } => IpcCommand::GetState,
+            MsgCommand::Get {
+                query: MsgQuery::Diagnostics,
+            } => IpcCommand::Diagnostics,
+        }
+    }
+}
+
+/// Reply to a request, `data` holds the result of the queries.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IpcResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcResponse {
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            data: None,
+            error: None,
+        }
+    }
+
+    pub fn data(data: serde_json::Value) -> Self {
+        Self {
+            ok: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Sends the response back to the connection the request came from.
+#[derive(Clone)]
+pub struct IpcReply(Arc<Mutex<Option<oneshot::Sender<IpcResponse>>>>);
+
+impl IpcReply {
+    fn new(sender: oneshot::Sender<IpcResponse>) -> Self {
+        Self(Arc::new(Mutex::new(Some(sender))))
+    }
+
+    pub fn send(&self, response: IpcResponse) {
+        let sender = self.0.lock().ok().and_then(|mut sender| sender.take());
+
+        if let Some(sender) = sender {
+            let _ = sender.send(response);
+        }
+    }
+}
+
+impl fmt::Debug for IpcReply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IpcReply")
+    }
+}
+
+fn parse_menu(name: &str) -> Option<MenuType> {
+    Some(match name {
+        "updates" => MenuType::Updates,
+        "settings" => MenuType::Settings,
+        "media_player" => MenuType::MediaPlayer,
+        "system_info" => MenuType::SystemInfo,
+        "feeds" => MenuType::Feeds,
+        "git_notifications" => MenuType::GitNotifications,
+        "privacy" => MenuType::Privacy,
+        "peripherals" => MenuType::Peripherals,
+        "notifications" => MenuType::Notifications,
+        "recording" => MenuType::Recording,
+        "keyboard_layout" => MenuType::KeyboardLayout,
+        "tasks" => MenuType::Tasks,
+        "windows" => MenuType::Windows,
+        name => match name.split_once(':')? {
+            ("tray", name) => MenuType::Tray(name.to_owned()),
+            ("provider", name) => MenuType::Provider(name.to_owned()),
+            ("custom", name) => MenuType::Custom(name.to_owned()),
+            _ => return None,
+        },
+    })
+}
+
+pub fn menu_name(menu_type: &MenuType) -> String {
+    match menu_type {
+        MenuType::Updates => "updates".to_owned(),
+        MenuType::Settings => "settings".to_owned(),
+        MenuType::Tray(name) => format!("tray:{name}"),
+        MenuType::MediaPlayer => "media_player".to_owned(),
+        MenuType::SystemInfo => "system_info".to_owned(),
+        MenuType::Feeds => "feeds".to_owned(),
+        MenuType::GitNotifications => "git_notifications".to_owned(),
+        MenuType::Privacy => "privacy".to_owned(),
+        MenuType::Peripherals => "peripherals".to_owned(),
+        MenuType::Notifications => "notifications".to_owned(),
+        MenuType::Recording => "recording".to_owned(),
+        MenuType::KeyboardLayout => "keyboard_layout".to_owned(),
+        MenuType::Tasks => "tasks".to_owned(),
+        MenuType::Chooser => "chooser".to_owned(),
+        MenuType::Windows => "windows".to_owned(),
+        MenuType::Provider(name) => format!("provider:{name}"),
+        MenuType::Custom(name) => format!("custom:{name}"),
+        MenuType::Tooltip(..) => "tooltip".to_owned(),
+    }
+}
+
+/// Module the menu belongs to, if any.
+pub fn menu_module(menu_type: &MenuType) -> Option<ModuleName> {
+    Some(match menu_type {
+        MenuType::Updates => ModuleName::Updates,
+        MenuType::Settings => ModuleName::Settings,
+        MenuType::Tray(_) => ModuleName::Tray,
+        MenuType::MediaPlayer => ModuleName::MediaPlayer,
+        MenuType::SystemInfo => ModuleName::SystemInfo,
+        MenuType::Feeds => ModuleName::Feeds,
+        MenuType::GitNotifications => ModuleName::GitNotifications,
+        MenuType::Privacy => ModuleName::Privacy,
+        MenuType::Peripherals => ModuleName::Peripherals,
+        MenuType::Notifications => ModuleName::Notifications,
+        MenuType::Recording => ModuleName::Recording,
+        MenuType::KeyboardLayout => ModuleName::KeyboardLayout,
+        MenuType::Tasks => ModuleName::Tasks,
+        MenuType::Provider(name) | MenuType::Custom(name) => ModuleName::Custom(name.clone()),
+        MenuType::Tooltip(module, _) => module.clone(),
+        MenuType::Chooser | MenuType::Windows => return None,
+    })
+}
+
+impl App {
+    /// Position of the menu opened without clicking its module, under
+    /// the section of the bar holding the module.
+    pub(crate) fn menu_anchor(&self, id: Id, menu_type: &MenuType) -> ButtonUIRef {
+        let vertical = self.bar_position(id).is_vertical();
+        let (bar_height, _) = self.outputs.height(id);
+        // assume a common resolution if the compositor didn't report the size
+        let length = self
+            .outputs
+            .bar_length(id)
+            .unwrap_or(if vertical { 1080. } else { 1920. });
+        let module = menu_module(menu_type);
+        let modules = self
+            .bar_config(id)
+            .map_or(&self.general_config.modules, |bar| &bar.modules);
+        let contains = |section: &[ModuleDef]| {
+            module.as_ref().is_some_and(|module| {
+                section.iter().any(|def| match def {
+                    ModuleDef::Single(name) => name == module,
+                    ModuleDef::Group(group) => group.contains(module),
+                })
+            })
+        };
+
+        let offset = if contains(&modules.left) {
+            0.
+        } else if contains(&modules.right) {
+            length
+        } else {
+            length / 2.
+        };
+
+        if vertical {
+            ButtonUIRef {
+                position: Point::new(bar_height / 2., offset),
+                viewport: (bar_height, length),
+            }
+        } else {
+            ButtonUIRef {
+                position: Point::new(offset, bar_height / 2.),
+                viewport: (length, bar_height),
+            }
+        }
+    }
+
+    /// Aggregated state of the bar and of the modules.
+    pub fn state(&self) -> serde_json::Value {
+        json!({
+            "visible": !self.outputs.is_hidden(),
+            "outputs": self
+                .outputs
+                .bars()
+                .map(|(name, menu)| json!({
+                    "name": name,
+                    "menu": menu.map(menu_name),
+                }))
+                .collect::<Vec<_>>(),
+            "config_error": self.config_error,
+            "hidden_modules": self.hidden_modules,
+            "active_workspace": self
+                .workspaces
+                .as_ref()
+                .and_then(Workspaces::active_workspace),
+            "media_playing": self
+                .media_player
+                .as_ref()
+                .and_then(MediaPlayer::now_playing),
+            "updates": self.updates.as_ref().map(|updates| updates.count()),
+            "external_modules": self
+                .providers
+                .widgets()
+                .map(|widget| widget.name.as_str())
+                .collect::<Vec<_>>(),
+            "added_modules": self
+                .injected_modules
+                .iter()
+                .map(|module| json!({
+                    "name": module.config.name,
+                    "section": module.section,
+                    "index": module.index,
+                }))
+                .collect::<Vec<_>>(),
+            "custom_modules": self
+                .custom
+                .iter()
+                .map(|(name, custom)| (name.clone(), json!(custom.data())))
+                .collect::<serde_json::Map<_, _>>(),
+        })
+    }
+
+    /// Opens the chooser with the options, the reply is sent once one
+    /// of them is picked or the menu is closed.
+    pub fn choose(
+        &mut self,
+        prompt: Option<String>,
+        options: Vec<String>,
+        output: Option<&str>,
+        reply: IpcReply,
+    ) -> Task<Message> {
+        let Some(id) = self.outputs.main_id(output) else {
+            reply.send(IpcResponse::error("no bar on the output"));
+            return Task::none();
+        };
+
+        // a pending chooser is cancelled when it's replaced
+        self.chooser = Some(Chooser::new(prompt, options, reply));
+
+        let name = self.outputs.get_monitor_name(id);
+        let already_open = self
+            .outputs
+            .bars()
+            .any(|(output, open)| Some(output) == name && open == Some(&MenuType::Chooser));
+
+        if already_open {
+            Chooser::focus()
+        } else {
+            let anchor = self.menu_anchor(id, &MenuType::Chooser);
+
+            Task::batch(vec![
+                self.outputs
+                    .toggle_menu(id, MenuType::Chooser, anchor, true),
+                Chooser::focus(),
+            ])
+        }
+    }
+
+    /// Cancels the chooser once its menu has been closed.
+    pub fn drop_closed_chooser(&mut self) {
+        if self.chooser.is_some()
+            && !self
+                .outputs
+                .bars()
+                .any(|(_, menu)| menu == Some(&MenuType::Chooser))
+        {
+            self.chooser = None;
+        }
+    }
+
+    pub fn handle_ipc(&mut self, command: IpcCommand) -> (IpcResponse, Task<Message>) {
+        debug!("IPC command {command:?}");
+
+        match command {
+            IpcCommand::ToggleMenu { menu, output } => {
+                let Some(id) = self.outputs.main_id(output.as_deref()) else {
+                    return (IpcResponse::error("no bar on the output"), Task::none());
+                };
+                let Some(menu_type) = parse_menu(&menu) else {
+                    return (
+                        IpcResponse::error(format!("unknown menu `{menu}`")),
+                        Task::none(),
+                    );
+                };
+                let anchor = self.menu_anchor(id, &menu_type);
+
+                (
+                    IpcResponse::ok(),
+                    self.update(Message::ToggleMenu(menu_type, id, anchor)),
+                )
+            }
+            IpcCommand::OpenMenu { menu, output } => {
+                let Some(id) = self.outputs.main_id(output.as_deref()) else {
+                    return (IpcResponse::error("no bar on the output"), Task::none());
+                };
+                let Some(menu_type) = parse_menu(&menu) else {
+                    return (
+                        IpcResponse::error(format!("unknown menu `{menu}`")),
+                        Task::none(),
+                    );
+                };
+                let name = self.outputs.get_monitor_name(id);
+                let already_open = self
+                    .outputs
+                    .bars()
+                    .any(|(output, open)| Some(output) == name && open == Some(&menu_type));
+
+                if already_open {
+                    (IpcResponse::ok(), Task::none())
+                } else {
+                    let anchor = self.menu_anchor(id, &menu_type);
+
+                    (
+                        IpcResponse::ok(),
+                        self.update(Message::ToggleMenu(menu_type, id, anchor)),
+                    )
+                }
+            }
+            IpcCommand::CloseMenu { output: None } => {
+                (IpcResponse::ok(), self.update(Message::CloseAllMenus))
+            }
+            IpcCommand::CloseMenu {
+                output: Some(output),
+            } => match self.outputs.main_id(Some(&output)) {
+                Some(id) => (IpcResponse::ok(), self.update(Message::CloseMenu(id))),
+                None => (IpcResponse::error("no bar on the output"), Task::none()),
+            },
+            IpcCommand::Show if self.outputs.is_hidden() => {
+                (IpcResponse::ok(), self.update(Message::ToggleVisibility))
+            }
+            IpcCommand::Hide if !self.outputs.is_hidden() => {
+                (IpcResponse::ok(), self.update(Message::ToggleVisibility))
+            }
+            IpcCommand::Show | IpcCommand::Hide => (IpcResponse::ok(), Task::none()),
+            IpcCommand::ToggleVisibility => {
+                (IpcResponse::ok(), self.update(Message::ToggleVisibility))
+            }
+            IpcCommand::ShowModule { module }
+            | IpcCommand::HideModule { module }
+            | IpcCommand::ToggleModule { module }
+                if !self
+                    .layout_modules()
+                    .contains(&ModuleName::from(module.as_str())) =>
+            {
+                (
+                    IpcResponse::error(format!("module `{module}` is not in the layout")),
+                    Task::none(),
+                )
+            }
+            IpcCommand::ShowModule { module } => {
+                self.set_module_visible(ModuleName::from(module.as_str()), true);
+
+                (IpcResponse::ok(), Task::none())
+            }
+            IpcCommand::HideModule { module } => {
+                self.set_module_visible(ModuleName::from(module.as_str()), false);
+
+                (IpcResponse::ok(), Task::none())
+            }
+            IpcCommand::ToggleModule { module } => {
+                self.toggle_module(ModuleName::from(module.as_str()));
+
+                (IpcResponse::ok(), Task::none())
+            }
+            IpcCommand::ReloadConfig => (IpcResponse::ok(), self.update(Message::ReloadConfig)),
+            IpcCommand::SwitchProfile { name }
+                if name != DEFAULT_PROFILE
+                    && !self.general_config.settings.profiles.contains(&name) =>
+            {
+                (
+                    IpcResponse::error(format!("unknown profile `{name}`")),
+                    Task::none(),
+                )
+            }
+            IpcCommand::SwitchProfile { name } => (IpcResponse::ok(), self.switch_profile(&name)),
+            IpcCommand::SetCustomText { name, text, alt } => match self.custom.get_mut(&name) {
+                Some(custom) => {
+                    let tooltip = custom.data().tooltip.clone();
+                    custom.update(custom_module::Message::Update(CustomListenData {
+                        alt,
+                        text,
+                        tooltip,
+                    }));
+
+                    (IpcResponse::ok(), Task::none())
+                }
+                None => (
+                    IpcResponse::error(format!("unknown custom module `{name}`")),
+                    Task::none(),
+                ),
+            },
+            IpcCommand::RegisterModule(widget) => {
+                self.providers.register(widget);
+
+                (IpcResponse::ok(), Task::none())
+            }
+            IpcCommand::UnregisterModule { name } => {
+                if self.providers.unregister(&name) {
+                    // the menu of the module can't be shown anymore
+                    let task = self.outputs.close_all_menu_if(
+                        MenuType::Provider(name),
+                        self.general_config.enable_esc_key,
+                    );
+
+                    (IpcResponse::ok(), task)
+                } else {
+                    (
+                        IpcResponse::error(format!("external module `{name}` is not registered")),
+                        Task::none(),
+                    )
+                }
+            }
+            IpcCommand::AddModule {
+                section,
+                index,
+                module,
+            } => match self.inject_module(section, index, module) {
+                Ok(()) => (IpcResponse::ok(), Task::none()),
+                Err(e) => (IpcResponse::error(e), Task::none()),
+            },
+            IpcCommand::RemoveModule { name } => match self.remove_injected_module(&name) {
+                Ok(()) => (IpcResponse::ok(), Task::none()),
+                Err(e) => (IpcResponse::error(e), Task::none()),
+            },
+            // replied once an option is picked, see `App::choose`
+            IpcCommand::Choose { .. } => (
+                IpcResponse::error("the chooser is opened with the reply of its request"),
+                Task::none(),
+            ),
+            IpcCommand::Toast {
+                text,
+                icon,
+                timeout,
+            } => (
+                IpcResponse::ok(),
+                self.update(Message::Toasts(toasts::Message::Toast {
+                    text,
+                    icon,
+                    timeout,
+                })),
+            ),
+            IpcCommand::GetState => (IpcResponse::data(self.state()), Task::none()),
+            IpcCommand::Diagnostics => (IpcResponse::data(self.diagnostics()), Task::none()),
+            IpcCommand::Ping => (
+                IpcResponse::data(json!({
+                    "ready": self.outputs.is_ready(),
+                    "version": env!("CARGO_PKG_VERSION"),
+                })),
+                Task::none(),
+            ),
+        }
+    }
+}
+
+/// Binds the socket, replacing the one left behind by a previous instance.
+fn bind(path: &Path) -> io::Result<UnixListener> {
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "another instance is listening on the socket",
+            ));
+        }
+
+        std::fs::remove_file(path)?;
+    }
+
+    UnixListener::bind(path)
+}
+
+async fn handle_connection(stream: UnixStream, mut output: Sender<Message>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut registered = Vec::new();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<IpcCommand>(&line) {
+            Ok(command) => {
+                if let IpcCommand::RegisterModule(widget) = &command
+                    && !registered.contains(&widget.name)
+                {
+                    registered.push(widget.name.clone());
+                }
+
+                let (sender, receiver) = oneshot::channel();
+
+                if output
+                    .send(Message::Ipc(command, IpcReply::new(sender)))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+
+                receiver
+                    .await
+                    .unwrap_or_else(|_| IpcResponse::error("the request was dropped"))
+            }
+            Err(e) => IpcResponse::error(format!("invalid request: {e}")),
+        };
+
+        let mut response = serde_json::to_string(&response).unwrap_or_default();
+        response.push('\n');
+
+        if let Err(e) = writer.write_all(response.as_bytes()).await {
+            debug!("IPC client disconnected: {e}");
+            break;
+        }
+    }
+
+    // the modules of a provider go away with it
+    for name in registered {
+        let (sender, _) = oneshot::channel();
+        let _ = output
+            .send(Message::Ipc(
+                IpcCommand::UnregisterModule { name },
+                IpcReply::new(sender),
+            ))
+            .await;
+    }
+}
+
+pub fn subscription() -> Subscription<Message> {
+    let id = TypeId::of::<IpcCommand>();
+
+    Subscription::run_with_id(
+        id,
+        channel(100, async move |output| {
+            let Some(path) = socket_path() else {
+                error!("XDG_RUNTIME_DIR isn't set, the IPC socket is disabled");
+                return;
+            };
+
+            let listener = match bind(&path) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Failed to bind the IPC socket {path:?}: {e}");
+                    return;
+                }
+            };
+
+            info!("Listening for IPC commands on {path:?}");
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(stream, output.clone()));
+                    }
+                    Err(e) => {
+                        warn!("Failed to accept an IPC connection: {e}");
+                    }
+                }
+            }
+        }),
+    )
+}
+
+/// Sends a single request to the running instance and waits for the reply.
+pub async fn request(command: &IpcCommand) -> anyhow::Result<IpcResponse> {
+    let path = socket_path().context("XDG_RUNTIME_DIR isn't set, can't locate the socket")?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("can't connect to {}, is ashell running?", path.display()))?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut request = serde_json::to_string(command)?;
+    request.push('\n');
+    writer.write_all(request.as_bytes()).await?;
+
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .context("the connection was closed without a reply")?;
+
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Runs `ashell menu`, the options are read from stdin and the picked one
+/// is printed on stdout. Returns whether an option was picked.
+pub async fn choose(prompt: Option<String>, output: Option<String>) -> bool {
+    let mut options = Vec::new();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if !line.is_empty() {
+            options.push(line);
+        }
+    }
+
+    let response = request(&IpcCommand::Choose {
+        prompt,
+        options,
+        output,
+    })
+    .await;
+
+    match response {
+        Ok(IpcResponse {
+            data: Some(serde_json::Value::String(picked)),
+            ..
+        }) => {
+            println!("{picked}");
+            true
+        }
+        Ok(response) => {
+            if let Some(error) = &response.error {
+                eprintln!("error: {error}");
+            }
+            false
+        }
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            false
+        }
+    }
+}
+
+/// Runs `ashell msg`, returns whether the command succeeded.
+pub async fn send_message(command: MsgCommand, json: bool) -> bool {
+    let ping = matches!(command, MsgCommand::Ping);
+    let response = match request(&command.into()).await {
+        Ok(response) => response,
+        Err(e) => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&IpcResponse::error(e.to_string())).unwrap_or_default()
+                );
+            } else {
+                eprintln!("error: {e:#}");
+            }
+
+            return false;
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string(&response).unwrap_or_default());
+    } else if let Some(error) = &response.error {
+        eprintln!("error: {error}");
+    } else if let Some(data) = &response.data {
+        println!("{}", serde_json::to_string_pretty(data).unwrap_or_default());
+    }
+
+    response.ok && (!ping || is_ready(&response))
+}
+
+fn is_ready(response: &IpcResponse) -> bool {
+    response
+        .data
+        .as_ref()
+        .and_then(|data| data.get("ready"))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Polls the running instance until its bars are up, returns `false` on timeout.
+pub async fn wait_ready(timeout: Duration) -> bool {
+    let start = Instant::now();
+
+    loop {
+        // the socket doesn't exist until ashell has started
+        if let Ok(response) = request(&IpcCommand::Ping).await
+            && is_ready(&response)
+        {
+            return true;
+        }
+
+        if start.elapsed() >= timeout {
+            eprintln!("error: ashell wasn't ready after {}s", timeout.as_secs());
+            return false;
+        }
+
+        sleep(Duration::from_millis(100)).await;
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    components::icons::{StaticIcon, icon},
+    config::{KeybindsConfig, OsdPosition},
+    services::{
+        ReadOnlyService, ServiceEvent,
+        compositor::{CompositorChoice, CompositorService},
+        registry::Consumer,
+    },
+    theme::AshellTheme,
+    utils::truncate_text,
+};
+use iced::{
+    Alignment, Border, Element, Length, Task, Theme,
+    platform_specific::shell::commands::layer_surface::{
+        Anchor, KeyboardInteractivity, Layer, destroy_layer_surface, get_layer_surface,
+    },
+    runtime::platform_specific::wayland::layer_surface::{
+        IcedMargin, IcedOutput, SctkLayerSurfaceSettings,
+    },
+    widget::{Column, column, container, row, text},
+    window::Id,
+};
+use log::warn;
+use serde::Deserialize;
+use std::process::Command;
+use wayland_client::protocol::wl_output::WlOutput;
+
+/// Distance from the top or bottom edge of the output
+const MARGIN: i32 = 64;
+/// Bindings listed at most, the others are left out
+const MAX_BINDS: usize = 20;
+const ROW_HEIGHT: u32 = 24;
+/// Height of the title and of the padding around the list
+const HEADER_HEIGHT: u32 = 56;
+
+/// A binding as listed by `hyprctl binds -j`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct HyprBind {
+    modmask: u32,
+    submap: String,
+    key: String,
+    description: String,
+    dispatcher: String,
+    arg: String,
+}
+
+/// Names of the modifiers of the `modmask` bits.
+const MODIFIERS: [(u32, &str); 5] = [
+    (64, "Super"),
+    (4, "Ctrl"),
+    (8, "Alt"),
+    (1, "Shift"),
+    (128, "Mod5"),
+];
+
+#[derive(Debug, Clone)]
+pub struct Keybind {
+    keys: String,
+    action: String,
+}
+
+impl From<HyprBind> for Keybind {
+    fn from(bind: HyprBind) -> Self {
+        let keys = MODIFIERS
+            .iter()
+            .filter(|(mask, _)| bind.modmask & mask != 0)
+            .map(|(_, name)| *name)
+            .chain(std::iter::once(bind.key.as_str()))
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let action = if bind.description.is_empty() {
+            format!("{} {}", bind.dispatcher, bind.arg)
+                .trim()
+                .to_owned()
+        } else {
+            bind.description
+        };
+
+        Self { keys, action }
+    }
+}
+
+/// Bindings of the submap, read from `hyprctl`.
+async fn fetch_binds(submap: String) -> Vec<Keybind> {
+    let binds = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<Keybind>> {
+        let output = Command::new("hyprctl").args(["binds", "-j"]).output()?;
+        let binds = serde_json::from_slice::<Vec<HyprBind>>(&output.stdout)?;
+
+        Ok(binds
+            .into_iter()
+            .filter(|bind| bind.submap == submap)
+            .map(Keybind::from)
+            .collect())
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|binds| binds);
+
+    binds.unwrap_or_else(|err| {
+        warn!("Failed to read the bindings of the submap: {err}");
+        Vec::new()
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Compositor(ServiceEvent<CompositorService>),
+    Binds(String, Vec<Keybind>),
+    ConfigReloaded(KeybindsConfig),
+}
+
+/// Bindings of the active Hyprland submap, shown on their own layer
+/// surface while the submap is active so that the modal bindings can be
+/// discovered.
+pub struct Keybinds {
+    config: KeybindsConfig,
+    service: Option<CompositorService>,
+    /// Active submap, with its bindings once they are read
+    submap: Option<(String, Option<Vec<Keybind>>)>,
+    /// Layer surface of the overlay, with its position and height
+    surface: Option<(Id, OsdPosition, u32)>,
+}
+
+impl Keybinds {
+    pub fn new(config: KeybindsConfig) -> Self {
+        Self {
+            config,
+            service: None,
+            submap: None,
+            surface: None,
+        }
+    }
+
+    /// The layer surface of the overlay.
+    pub fn surface(&self) -> Option<Id> {
+        self.surface.map(|(id, ..)| id)
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        self.config.output.as_deref()
+    }
+
+    /// Follows the submap of the compositor, its bindings are read when
+    /// it is entered.
+    fn sync_submap(&mut self) -> Task<Message> {
+        let submap = self
+            .service
+            .as_ref()
+            .filter(|service| matches!(service.backend, CompositorChoice::Hyprland))
+            .and_then(|service| service.submap.clone())
+            .filter(|submap| !submap.is_empty());
+
+        if submap.as_ref() == self.submap.as_ref().map(|(name, _)| name) {
+            return Task::none();
+        }
+
+        match submap {
+            Some(submap) => {
+                self.submap = Some((submap.clone(), None));
+
+                Task::perform(fetch_binds(submap.clone()), move |binds| {
+                    Message::Binds(submap.clone(), binds)
+                })
+            }
+            None => {
+                self.submap = None;
+
+                Task::none()
+            }
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Compositor(event) => {
+                match event {
+                    ServiceEvent::Init(service) => self.service = Some(service),
+                    ServiceEvent::Update(event) => {
+                        if let Some(service) = self.service.as_mut() {
+                            service.update(event);
+                        }
+                    }
+                    ServiceEvent::Error(_) => self.service = None,
+                }
+
+                self.sync_submap()
+            }
+            Message::Binds(submap, binds) => {
+                // the submap may have been left while they were read
+                if let Some((current, current_binds)) = self.submap.as_mut()
+                    && *current == submap
+                {
+                    *current_binds = Some(binds);
+                }
+
+                Task::none()
+            }
+            Message::ConfigReloaded(config) => {
+                if !config.enabled {
+                    self.service = None;
+                    self.submap = None;
+                }
+                self.config = config;
+
+                Task::none()
+            }
+        }
+    }
+
+    /// Bindings on screen, only once they are known and if there are some.
+    fn shown(&self) -> Option<(&str, &[Keybind])> {
+        match self.submap.as_ref()? {
+            (submap, Some(binds)) if !binds.is_empty() => Some((submap, binds)),
+            _ => None,
+        }
+    }
+
+    /// Creates or destroys the layer surface while a submap is active.
+    pub fn sync_surface<M: 'static>(&mut self, output: Option<WlOutput>) -> Task<M> {
+        let position = self.config.position;
+        let height = self
+            .shown()
+            .map(|(_, binds)| HEADER_HEIGHT + binds.len().min(MAX_BINDS) as u32 * ROW_HEIGHT);
+
+        match self.surface {
+            Some((id, current_position, current_height))
+                if Some(current_height) != height || current_position != position =>
+            {
+                self.surface = None;
+
+                Task::batch(vec![destroy_layer_surface(id), self.sync_surface(output)])
+            }
+            Some(_) => Task::none(),
+            None => match height {
+                Some(height) => {
+                    let id = Id::unique();
+                    self.surface = Some((id, position, height));
+
+                    get_layer_surface(SctkLayerSurfaceSettings {
+                        id,
+                        namespace: "ashell-keybinds".to_string(),
+                        size: Some((Some(self.config.width), Some(height))),
+                        layer: Layer::Overlay,
+                        keyboard_interactivity: KeyboardInteractivity::None,
+                        margin: IcedMargin {
+                            top: MARGIN,
+                            right: 0,
+                            bottom: MARGIN,
+                            left: 0,
+                        },
+                        output: output.map_or(IcedOutput::Active, IcedOutput::Output),
+                        // without anchors the surface is centered on the output
+                        anchor: match position {
+                            OsdPosition::Top => Anchor::TOP,
+                            OsdPosition::Center => Anchor::empty(),
+                            OsdPosition::Bottom => Anchor::BOTTOM,
+                        },
+                        ..Default::default()
+                    })
+                }
+                None => Task::none(),
+            },
+        }
+    }
+
+    pub fn view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let (submap, binds) = self.shown().unwrap_or_default();
+
+        container(
+            column!(
+                row!(
+                    icon(StaticIcon::Keyboard).size(theme.font_size.lg),
+                    text(submap).size(theme.font_size.lg),
+                )
+                .spacing(theme.space.sm)
+                .align_y(Alignment::Center),
+                Column::with_children(binds.iter().take(MAX_BINDS).map(|bind| {
+                    row!(
+                        text(bind.keys.as_str())
+                            .width(Length::FillPortion(2))
+                            .wrapping(text::Wrapping::None),
+                        text(truncate_text(&bind.action, 48))
+                            .width(Length::FillPortion(3))
+                            .wrapping(text::Wrapping::None),
+                    )
+                    .height(Length::Fixed(ROW_HEIGHT as f32))
+                    .spacing(theme.space.sm)
+                    .align_y(Alignment::Center)
+                    .into()
+                })),
+            )
+            .spacing(theme.space.xs),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .clip(true)
+        .padding([theme.space.sm, theme.space.md])
+        .style(move |iced_theme: &Theme| container::Style {
+            background: Some(
+                iced_theme
+                    .palette()
+                    .background
+                    .scale_alpha(theme.menu.opacity)
+                    .into(),
+            ),
+            border: Border {
+                color: iced_theme.extended_palette().secondary.base.color,
+                width: 1.,
+                radius: theme.radius.lg.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+    }
+
+    /// The compositor is watched for the submap while the overlay is enabled.
+    pub fn consumers(&self) -> Vec<Consumer> {
+        if self.config.enabled {
+            vec![Consumer::Keybinds]
+        } else {
+            Vec::new()
+        }
+    }
+}
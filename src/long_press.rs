@@ -0,0 +1,240 @@
+//! Publish a message when a finger is held on the content.
+use iced::{
+    Element, Length, Point, Rectangle, Size, Vector,
+    core::{
+        Clipboard, Layout, Shell, Widget,
+        event::{self, Event},
+        layout, mouse, overlay, renderer, touch,
+        widget::{Operation, Tree, tree},
+        window,
+    },
+};
+use std::time::{Duration, Instant};
+
+/// Time a finger has to be held to trigger the long press.
+const LONG_PRESS: Duration = Duration::from_millis(500);
+/// Distance a finger can move before the press is considered a drag.
+const TOUCH_SLOP: f32 = 10.;
+
+/// A container publishing a message on a long press, the touch
+/// counterpart of the right click.
+///
+/// The press is not forwarded to the content once the long press fired.
+#[allow(missing_debug_implementations)]
+pub struct LongPress<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    on_long_press: Message,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    pressed: Option<(touch::Finger, Point, Instant)>,
+    fired: bool,
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for LongPress<'_, Message, Theme, Renderer>
+where
+    Message: Clone,
+    Renderer: iced::core::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_mut(&mut self.content));
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        let state = tree.state.downcast_mut::<State>();
+        let mut captured = false;
+
+        let event = match event {
+            Event::Touch(touch::Event::FingerPressed { id, position })
+                if layout.bounds().contains(position) =>
+            {
+                let now = Instant::now();
+                *state = State {
+                    pressed: Some((id, position, now)),
+                    fired: false,
+                };
+                shell.request_redraw(window::RedrawRequest::At(now + LONG_PRESS));
+
+                event
+            }
+            Event::Touch(touch::Event::FingerMoved { id, position }) => {
+                if state.pressed.is_some_and(|(finger, origin, _)| {
+                    finger == id && origin.distance(position) > TOUCH_SLOP
+                }) {
+                    state.pressed = None;
+                }
+
+                event
+            }
+            Event::Window(window::Event::RedrawRequested(now)) => {
+                if let Some((_, _, since)) = state.pressed
+                    && !state.fired
+                    && now.saturating_duration_since(since) >= LONG_PRESS
+                {
+                    state.fired = true;
+                    shell.publish(self.on_long_press.clone());
+                }
+
+                event
+            }
+            Event::Touch(touch::Event::FingerLifted { id, position })
+                if state
+                    .pressed
+                    .is_some_and(|(finger, _, _)| finger == id && state.fired) =>
+            {
+                *state = State::default();
+                captured = true;
+
+                // cancel the press of the content, and of the parents, instead of activating it
+                Event::Touch(touch::Event::FingerLost { id, position })
+            }
+            Event::Touch(touch::Event::FingerLifted { .. } | touch::Event::FingerLost { .. }) => {
+                *state = State::default();
+
+                event
+            }
+            _ => event,
+        };
+
+        let status = self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        if captured {
+            event::Status::Captured
+        } else {
+            status
+        }
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer, translation)
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<LongPress<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: 'a,
+    Renderer: iced::core::Renderer + 'a,
+{
+    fn from(long_press: LongPress<'a, Message, Theme, Renderer>) -> Self {
+        Self::new(long_press)
+    }
+}
+
+/// Wraps the content to publish `on_long_press` when a finger is held on it.
+pub fn long_press<'a, Message, Theme, Renderer>(
+    content: impl Into<Element<'a, Message, Theme, Renderer>>,
+    on_long_press: Message,
+) -> LongPress<'a, Message, Theme, Renderer> {
+    LongPress {
+        content: content.into(),
+        on_long_press,
+    }
+}
@@ -1,33 +1,47 @@
 use crate::config::get_config;
 use app::App;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use flexi_logger::{
     Age, Cleanup, Criterion, FileSpec, LogSpecBuilder, LogSpecification, Logger, Naming,
 };
-use iced::Font;
+use iced::{Font, Task};
 use log::{debug, error, warn};
 use std::panic;
 use std::path::PathBuf;
 use std::{backtrace::Backtrace, borrow::Cow};
 
+mod animation;
 mod app;
 mod centerbox;
+mod chooser;
+mod color_scheme;
 mod components;
 mod config;
+mod debug_overlay;
+mod hooks;
+mod hover_area;
+mod ipc;
+mod keybinds;
+mod long_press;
 mod menu;
 mod modules;
+mod osd;
 mod outputs;
 mod password_dialog;
 mod position_button;
+mod scheduler;
 mod services;
+mod signals;
 mod theme;
+mod toasts;
 mod utils;
+mod watchdog;
+mod window_switcher;
 
 const NERD_FONT: &[u8] = include_bytes!("../target/generated/SymbolsNerdFont-Regular-Subset.ttf");
 const NERD_FONT_MONO: &[u8] =
     include_bytes!("../target/generated/SymbolsNerdFontMono-Regular-Subset.ttf");
 const CUSTOM_FONT: &[u8] = include_bytes!("../assets/AshellCustomIcon-Regular.otf");
-const HEIGHT: f64 = 34.;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -35,8 +49,50 @@ const HEIGHT: f64 = 34.;
     about = env!("CARGO_PKG_DESCRIPTION")
 )]
 struct Args {
-    #[arg(short, long, value_parser = clap::value_parser!(PathBuf))]
+    #[arg(short, long, global = true, value_parser = clap::value_parser!(PathBuf))]
     config_path: Option<PathBuf>,
+    /// Switch to the given config profile, `default` disables the profile overrides
+    #[arg(short, long)]
+    profile: Option<String>,
+    /// Wait until the running instance has its bars up, then exit, gives up after SECONDS
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "30")]
+    wait_ready: Option<u64>,
+    /// Show the frame times, the update counts and the memory usage of the bar
+    #[arg(long)]
+    debug_overlay: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Validate the config file and exit, with a non-zero code if it's invalid
+    CheckConfig,
+    /// Print the default config, with every field documented
+    DumpDefaultConfig {
+        /// Write the config to this file instead of stdout
+        output: Option<PathBuf>,
+        /// Print the JSON schema of the config instead
+        #[arg(long)]
+        schema: bool,
+    },
+    /// Show the lines read from stdin in a menu and print the picked one, like dmenu
+    Menu {
+        /// Placeholder of the filter
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Open the menu on the first output whose name contains this value
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Send a command to the running instance through the IPC socket
+    Msg {
+        /// Print the raw JSON reply
+        #[arg(long, global = true)]
+        json: bool,
+        #[command(subcommand)]
+        command: ipc::MsgCommand,
+    },
 }
 
 fn get_log_spec(log_level: &str) -> LogSpecification {
@@ -57,12 +113,69 @@ async fn main() -> iced::Result {
     let args = Args::parse();
     debug!("args: {args:?}");
 
+    if let Some(timeout) = args.wait_ready {
+        std::process::exit(
+            if ipc::wait_ready(std::time::Duration::from_secs(timeout)).await {
+                0
+            } else {
+                1
+            },
+        );
+    }
+
+    match args.command {
+        Some(Command::CheckConfig) => {
+            std::process::exit(if config::check_config(args.config_path) {
+                0
+            } else {
+                1
+            });
+        }
+        Some(Command::DumpDefaultConfig { output, schema }) => {
+            let content = if schema {
+                config::json_schema()
+            } else {
+                config::default_config()
+            };
+
+            match output {
+                Some(path) => {
+                    if let Err(e) = std::fs::write(&path, content) {
+                        eprintln!("Failed to write {}: {e}", path.display());
+                        std::process::exit(1);
+                    }
+                }
+                None => print!("{content}"),
+            }
+
+            std::process::exit(0);
+        }
+        Some(Command::Menu { prompt, output }) => {
+            std::process::exit(if ipc::choose(prompt, output).await {
+                0
+            } else {
+                1
+            });
+        }
+        Some(Command::Msg { json, command }) => {
+            std::process::exit(if ipc::send_message(command, json).await {
+                0
+            } else {
+                1
+            });
+        }
+        None => {}
+    }
+
     let logger = Logger::with(
         LogSpecBuilder::new()
             .default(log::LevelFilter::Info)
             .build(),
     )
-    .log_to_file(FileSpec::default().directory("/tmp/ashell"))
+    .log_to_file_and_writer(
+        FileSpec::default().directory("/tmp/ashell"),
+        Box::new(ipc::diagnostics::RecentErrors),
+    )
     .duplicate_to_stdout(flexi_logger::Duplicate::All)
     .rotate(
         Criterion::Age(Age::Day),
@@ -78,8 +191,17 @@ async fn main() -> iced::Result {
     panic::set_hook(Box::new(|info| {
         let b = Backtrace::capture();
         error!("Panic: {info} \n {b}");
+        watchdog::record_panic();
     }));
 
+    if args.debug_overlay {
+        debug_overlay::force();
+    }
+
+    if let Some(profile) = &args.profile {
+        config::set_active_profile(profile);
+    }
+
     let (config, config_path) = get_config(args.config_path).unwrap_or_else(|err| {
         error!("Failed to read config: {err}");
 
@@ -89,19 +211,54 @@ async fn main() -> iced::Result {
     logger.set_new_spec(get_log_spec(&config.log_level));
 
     let font = if let Some(font_name) = &config.appearance.font_name {
-        Font::with_name(Box::leak(font_name.clone().into_boxed_str()))
+        Font::with_name(theme::font_name(font_name))
     } else {
         Font::DEFAULT
     };
 
-    iced::daemon(App::title, App::update, App::view)
-        .subscription(App::subscription)
+    if let Some(icon_font_name) = &config.appearance.icon_font_name {
+        components::icons::set_icon_font(theme::font_name(icon_font_name));
+    }
+
+    let update = |app: &mut App, message: app::Message| {
+        debug_overlay::record_update(message.source());
+        let snapshot = app.hook_snapshot();
+        let switcher_open = app.window_switcher_open();
+        let task = app.update(message);
+        app.run_hooks(snapshot);
+        app.drop_closed_chooser();
+        let task = Task::batch(vec![task, app.sync_window_switcher(switcher_open)]);
+        ipc::dbus::publish(app.bar_state());
+        if app.general_config.http_port.is_some() {
+            ipc::http::publish(|| app.state());
+        }
+
+        task
+    };
+
+    let mut daemon = iced::daemon(App::title, update, App::view)
+        .subscription(|app| App::subscription(app).map(debug_overlay::record_wakeup))
         .theme(App::theme)
         .style(App::style)
         .scale_factor(App::scale_factor)
         .font(Cow::from(NERD_FONT))
         .font(Cow::from(NERD_FONT_MONO))
-        .font(Cow::from(CUSTOM_FONT))
+        .font(Cow::from(CUSTOM_FONT));
+
+    for font_file in &config.appearance.font_files {
+        let path = shellexpand::tilde(font_file);
+
+        match std::fs::read(path.as_ref()) {
+            Ok(bytes) => {
+                daemon = daemon.font(Cow::Owned(bytes));
+            }
+            Err(e) => {
+                warn!("Failed to load font file {path}: {e}");
+            }
+        }
+    }
+
+    daemon
         .default_font(font)
         .run_with(App::new((logger, config, config_path)))
 }
@@ -1,5 +1,8 @@
+use crate::animation::{self, lerp};
 use crate::app::{self, App};
-use crate::config::{AppearanceStyle, Position};
+use crate::config::{AppearanceStyle, ModuleName, Position};
+use crate::ipc::menu_name;
+use crate::outputs::HasOutput;
 use crate::position_button::ButtonUIRef;
 use crate::theme::backdrop_color;
 use iced::alignment::{Horizontal, Vertical};
@@ -7,10 +10,11 @@ use iced::platform_specific::shell::commands::layer_surface::{
     KeyboardInteractivity, Layer, set_keyboard_interactivity, set_layer,
 };
 use iced::widget::container::Style;
-use iced::widget::mouse_area;
+use iced::widget::{mouse_area, scrollable};
 use iced::window::Id;
 use iced::{self, Element, Task, Theme, widget::container};
 use iced::{Border, Length, Padding};
+use std::time::Instant;
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum MenuType {
@@ -19,12 +23,30 @@ pub enum MenuType {
     Tray(String),
     MediaPlayer,
     SystemInfo,
+    Feeds,
+    GitNotifications,
+    Privacy,
+    Peripherals,
+    Notifications,
+    Recording,
+    KeyboardLayout,
+    Tasks,
+    /// Options piped by `ashell menu`.
+    Chooser,
+    /// Open windows, to switch between them.
+    Windows,
+    Provider(String),
+    /// Menu printed by the `menu_cmd` of a custom module.
+    Custom(String),
+    /// Text shown while hovering a module.
+    Tooltip(ModuleName, String),
 }
 
 #[derive(Clone, Debug)]
 pub struct Menu {
     pub id: Id,
     pub menu_info: Option<(MenuType, ButtonUIRef)>,
+    pub opened_at: Option<Instant>,
 }
 
 impl Menu {
@@ -32,6 +54,7 @@ impl Menu {
         Self {
             id,
             menu_info: None,
+            opened_at: None,
         }
     }
 
@@ -42,6 +65,7 @@ impl Menu {
         request_keyboard: bool,
     ) -> Task<Message> {
         self.menu_info.replace((menu_type, button_ui_ref));
+        self.opened_at = Some(Instant::now());
 
         let mut tasks = vec![set_layer(self.id, Layer::Overlay)];
 
@@ -58,6 +82,7 @@ impl Menu {
     pub fn close<Message: 'static>(&mut self) -> Task<Message> {
         if self.menu_info.is_some() {
             self.menu_info.take();
+            self.opened_at = None;
 
             let mut tasks = vec![set_layer(self.id, Layer::Background)];
 
@@ -84,6 +109,7 @@ impl Menu {
             Some((current_type, current_button_ui_ref)) => {
                 *current_type = menu_type;
                 *current_button_ui_ref = button_ui_ref;
+                self.opened_at = Some(Instant::now());
                 Task::none()
             }
         }
@@ -136,20 +162,63 @@ impl App {
         menu_size: MenuSize,
         button_ui_ref: ButtonUIRef,
     ) -> Element<'a, app::Message> {
+        let position = self.bar_position(id);
+        let button_ui_ref = self.outputs.to_output_coordinates(id, button_ui_ref);
+        let (viewport_width, viewport_height) = button_ui_ref.viewport;
+
+        // the menu never gets wider than the output
+        let size = f32::min(menu_size.size(), viewport_width - 16.).max(1.);
+        let menu_type = match self.outputs.has(id) {
+            Some(HasOutput::Menu(Some((menu_type, _)))) => Some(menu_type),
+            _ => None,
+        };
+        let max_height = menu_type.and_then(|menu_type| {
+            self.theme
+                .menu
+                .max_height
+                .get(&menu_name(menu_type))
+                .map(|max_height| *max_height as f32)
+        });
+        // a tooltip doesn't take the focus from the rest of the screen
+        let is_tooltip = matches!(menu_type, Some(MenuType::Tooltip(..)));
+        let backdrop = if is_tooltip {
+            0.
+        } else {
+            self.theme.menu.backdrop
+        };
+        // the menu opens toward the larger side of the button
+        let flipped = if position.is_vertical() {
+            button_ui_ref.position.y > viewport_height / 2.
+        } else {
+            button_ui_ref.position.x > viewport_width / 2.
+        };
+
+        // the menu fades in while sliding from the bar
+        let progress =
+            animation::progress(self.theme.animations.menu, self.outputs.menu_opened_at(id));
+        let slide = lerp(self.theme.space.md as f32, 0., progress);
+
         mouse_area(
             container(
                 mouse_area(
-                    container(content)
+                    container(scrollable(content))
                         .height(Length::Shrink)
                         .width(Length::Shrink)
-                        .max_width(menu_size.size())
-                        .padding(self.theme.space.md)
+                        .max_width(size)
+                        .max_height(max_height.unwrap_or(f32::INFINITY))
+                        .padding(if is_tooltip {
+                            self.theme.space.xs
+                        } else {
+                            self.theme.space.md
+                        })
                         .style(move |theme: &Theme| Style {
+                            text_color: (progress < 1.0)
+                                .then(|| theme.palette().text.scale_alpha(progress)),
                             background: Some(
                                 theme
                                     .palette()
                                     .background
-                                    .scale_alpha(self.theme.menu.opacity)
+                                    .scale_alpha(self.theme.menu.opacity * progress)
                                     .into(),
                             ),
                             border: Border {
@@ -158,7 +227,7 @@ impl App {
                                     .secondary
                                     .base
                                     .color
-                                    .scale_alpha(self.theme.menu.opacity),
+                                    .scale_alpha(self.theme.menu.opacity * progress),
                                 width: 1.,
                                 radius: self.theme.radius.lg.into(),
                             },
@@ -167,39 +236,65 @@ impl App {
                 )
                 .on_release(app::Message::None),
             )
-            .align_y(match self.theme.bar_position {
+            .align_y(match position {
                 Position::Top => Vertical::Top,
                 Position::Bottom => Vertical::Bottom,
+                Position::Left | Position::Right if flipped => Vertical::Bottom,
+                Position::Left | Position::Right => Vertical::Top,
+            })
+            .align_x(match position {
+                Position::Right => Horizontal::Right,
+                Position::Top | Position::Bottom if flipped => Horizontal::Right,
+                _ => Horizontal::Left,
             })
-            .align_x(Horizontal::Left)
             .padding({
-                let size = menu_size.size();
-
                 let v_padding = match self.theme.bar_style {
                     AppearanceStyle::Solid | AppearanceStyle::Gradient => 2,
                     AppearanceStyle::Islands => 0,
                 };
+                let offset = v_padding as f32 + slide;
+
+                match position {
+                    Position::Top | Position::Bottom => {
+                        let padding = if position == Position::Top {
+                            Padding::new(0.).top(offset)
+                        } else {
+                            Padding::new(0.).bottom(offset)
+                        };
+                        // centered on the button, but kept inside the output
+                        let clamp = |distance: f32| {
+                            f32::min(distance - size / 2., viewport_width - size - 8.).max(8.)
+                        };
+
+                        if flipped {
+                            padding.right(clamp(viewport_width - button_ui_ref.position.x))
+                        } else {
+                            padding.left(clamp(button_ui_ref.position.x))
+                        }
+                    }
+                    Position::Left | Position::Right => {
+                        let padding = if position == Position::Left {
+                            Padding::new(0.).left(offset)
+                        } else {
+                            Padding::new(0.).right(offset)
+                        };
+                        let half_button = viewport_width / 2.;
 
-                Padding::new(0.)
-                    .top(if self.theme.bar_position == Position::Top {
-                        v_padding
-                    } else {
-                        0
-                    })
-                    .bottom(if self.theme.bar_position == Position::Bottom {
-                        v_padding
-                    } else {
-                        0
-                    })
-                    .left(f32::min(
-                        f32::max(button_ui_ref.position.x - size / 2., 8.),
-                        button_ui_ref.viewport.0 - size - 8.,
-                    ))
+                        if flipped {
+                            padding.bottom(f32::max(
+                                viewport_height - button_ui_ref.position.y - half_button,
+                                8.,
+                            ))
+                        } else {
+                            padding.top(f32::max(button_ui_ref.position.y - half_button, 8.))
+                        }
+                    }
+                }
             })
             .width(Length::Fill)
             .height(Length::Fill)
             .style(move |_| Style {
-                background: Some(backdrop_color(self.theme.menu.backdrop).into()),
+                background: Some(backdrop_color(backdrop * progress).into()),
                 ..Default::default()
             }),
         )
@@ -1,7 +1,13 @@
 use crate::{config::ClockModuleConfig, theme::AshellTheme};
 use chrono::{DateTime, Local};
-use iced::{Element, Subscription, time::every, widget::text};
-use std::time::Duration;
+use iced::{Element, Subscription, futures::SinkExt, stream::channel, widget::text};
+use itertools::Itertools;
+use std::{any::TypeId, time::Duration};
+use tokio::time::sleep;
+
+/// Interval of the checks of the time, the bar is only updated when one of
+/// the shown texts changes.
+const INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -11,13 +17,18 @@ pub enum Message {
 pub struct Clock {
     config: ClockModuleConfig,
     date: DateTime<Local>,
+    /// The date in the configured format, formatted once per update
+    text: String,
 }
 
 impl Clock {
     pub fn new(config: ClockModuleConfig) -> Self {
+        let date = Local::now();
+
         Self {
+            text: date.format(&config.format).to_string(),
             config,
-            date: Local::now(),
+            date,
         }
     }
 
@@ -25,32 +36,57 @@ impl Clock {
         match message {
             Message::Update => {
                 self.date = Local::now();
+                self.text = self.date.format(&self.config.format).to_string();
             }
         }
     }
 
-    pub fn view(&'_ self, _: &AshellTheme) -> Element<'_, Message> {
-        text(self.date.format(&self.config.format).to_string()).into()
+    pub fn view(
+        &'_ self,
+        theme: &AshellTheme,
+        config: Option<&ClockModuleConfig>,
+    ) -> Element<'_, Message> {
+        let text = match config {
+            Some(config) if config.format != self.config.format => {
+                text(self.date.format(&config.format).to_string())
+            }
+            _ => text(self.text.as_str()),
+        };
+
+        theme.module_text("Clock", text).into()
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        let second_specifiers = [
-            "%S",  // Seconds (00-60)
-            "%T",  // Hour:Minute:Second
-            "%X",  // Locale time representation with seconds
-            "%r",  // 12-hour clock time with seconds
-            "%:z", // UTC offset with seconds
-            "%s",  // Unix timestamp (seconds since epoch)
-        ];
-        let interval = if second_specifiers
-            .iter()
-            .any(|&spec| self.config.format.contains(spec))
-        {
-            Duration::from_secs(1)
-        } else {
-            Duration::from_secs(5)
-        };
+    /// Checks the formats of the clock and of the bars overriding it, and
+    /// only asks for an update when one of them shows a different text.
+    pub fn subscription<'a>(
+        &self,
+        bars: impl Iterator<Item = &'a ClockModuleConfig>,
+    ) -> Subscription<Message> {
+        let formats = std::iter::once(&self.config)
+            .chain(bars)
+            .map(|config| config.format.clone())
+            .unique()
+            .collect::<Vec<_>>();
+
+        Subscription::run_with_id(
+            (TypeId::of::<Self>(), formats.clone()),
+            channel(1, async move |mut output| {
+                let mut current = Vec::new();
+
+                loop {
+                    let now = Local::now();
+                    let texts = formats
+                        .iter()
+                        .map(|format| now.format(format).to_string())
+                        .collect::<Vec<_>>();
+                    if texts != current {
+                        current = texts;
+                        let _ = output.send(Message::Update).await;
+                    }
 
-        every(interval).map(|_| Message::Update)
+                    sleep(INTERVAL).await;
+                }
+            }),
+        )
     }
 }
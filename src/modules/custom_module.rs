@@ -1,46 +1,120 @@
 use crate::{
     components::icons::{DynamicIcon, StaticIcon, icon},
-    config::CustomModuleDef,
+    config::{CustomModuleDef, CustomModuleMode, CustomModuleOnError, StyleRuleColor},
+    services::tray::{TrayIcon, get_icon_from_name, image_from_bytes, tray_icon_from_path},
     theme::AshellTheme,
-    utils::launcher::execute_command,
+    utils::launcher::execute_command_with_env,
 };
+use base64::prelude::*;
 use iced::widget::canvas;
 use iced::{
-    Element, Length, Subscription, Theme,
+    Alignment, Element, Font, Length, Padding, Subscription, Theme,
+    futures::channel::mpsc::Sender,
     stream::channel,
-    widget::{Stack, row, text},
+    widget::{
+        Column, Image, MouseArea, Row, Stack, Svg, button, column, horizontal_rule, row, text,
+    },
 };
 use iced::{
-    mouse::Cursor,
+    mouse::{Cursor, ScrollDelta},
     widget::{
         canvas::{Cache, Geometry, Path, Program},
         container,
     },
 };
-use log::{error, info};
-use serde::Deserialize;
-use std::{any::TypeId, process::Stdio};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{any::TypeId, collections::HashSet, path::PathBuf, process::Stdio, time::Duration};
 use tokio::{
     io::{AsyncBufReadExt, BufReader},
     process::Command,
+    time::sleep,
 };
 
+/// Delay before restarting a stream command that exited, or a failed command.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest delay between the restarts of a stream command that keeps failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Text shown by the `Text` error behavior without an `error_text`.
+const DEFAULT_ERROR_TEXT: &str = "Error";
+
+/// Pixels scrolled on a touchpad to run the scroll command once.
+const SCROLL_STEP: f32 = 20.;
+
+/// Height of the pictures previewed in the menu entries.
+const THUMBNAIL_HEIGHT: f32 = 96.;
+/// Lines of a code snippet previewed in the menu entries.
+const CODE_PREVIEW_LINES: usize = 8;
+/// Words colored as keywords in the code snippets, common to most languages.
+const CODE_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "class", "const", "continue", "def", "else", "enum",
+    "export", "false", "fn", "for", "from", "func", "function", "if", "impl", "import", "in",
+    "let", "match", "mut", "None", "null", "pub", "return", "self", "static", "struct", "switch",
+    "this", "true", "type", "use", "var", "while",
+];
+
 #[derive(Debug, Clone)]
 pub struct Custom {
     config: CustomModuleDef,
     data: CustomListenData,
+    scroll_accumulator: f32,
+    image: Option<TrayIcon>,
+    menu: Option<Vec<CustomMenuEntry>>,
+    expanded_groups: HashSet<Vec<usize>>,
+    failed: bool,
+}
+
+/// Entry of the menu printed by the `menu_cmd`, a separator or a group
+/// of nested entries.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct CustomMenuEntry {
+    pub label: String,
+    pub icon: Option<String>,
+    pub command: Option<String>,
+    pub separator: bool,
+    pub entries: Vec<CustomMenuEntry>,
+    /// Path of a picture previewed under the label
+    pub image: Option<String>,
+    /// Base64 encoded PNG or JPEG picture previewed under the label
+    pub image_data: Option<String>,
+    /// Files listed under the label, as paths or `file://` uris
+    pub files: Vec<String>,
+    /// Snippet of code previewed under the label
+    pub code: Option<String>,
+    #[serde(skip)]
+    thumbnail: Option<TrayIcon>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct CustomListenData {
     pub alt: String,
     pub text: Option<String>,
+    pub tooltip: Option<String>,
+    /// Path of a picture replacing the icon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Base64 encoded PNG or JPEG picture replacing the icon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_data: Option<String>,
+    /// Name of a freedesktop icon replacing the icon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     LaunchCommand,
+    ClickRight,
+    ClickMiddle,
+    Scroll(ScrollDelta),
     Update(CustomListenData),
+    /// The listen command exited, successfully or not
+    Exited(bool),
+    MenuLoaded(Vec<CustomMenuEntry>),
+    MenuEntry(String),
+    ToggleGroup(Vec<usize>),
 }
 
 // Define a struct for the canvas program
@@ -75,54 +149,295 @@ impl Custom {
         Self {
             config,
             data: CustomListenData::default(),
+            scroll_accumulator: 0.,
+            image: None,
+            menu: None,
+            expanded_groups: HashSet::new(),
+            failed: false,
         }
     }
 
+    pub fn config(&self) -> &CustomModuleDef {
+        &self.config
+    }
+
     pub fn module_type(&self) -> crate::config::CustomModuleType {
         self.config.r#type
     }
 
+    pub fn data(&self) -> &CustomListenData {
+        &self.data
+    }
+
+    /// Text shown while hovering the module, without running the `tooltip_cmd`.
+    pub fn tooltip(&self) -> Option<String> {
+        self.data
+            .tooltip
+            .clone()
+            .filter(|tooltip| !tooltip.is_empty())
+            .or_else(|| self.config.tooltip.clone())
+    }
+
+    pub fn tooltip_cmd(&self) -> Option<&String> {
+        self.config.tooltip_cmd.as_ref()
+    }
+
+    pub fn menu_cmd(&self) -> Option<&String> {
+        self.config.menu_cmd.as_ref()
+    }
+
+    /// Hidden after the listen command failed, with the `Hide` error behavior.
+    pub fn is_hidden(&self) -> bool {
+        self.failed && self.config.on_error == CustomModuleOnError::Hide
+    }
+
+    /// Text of the output, or the error text after the listen command failed.
+    fn text(&self) -> Option<&str> {
+        if self.failed && self.config.on_error == CustomModuleOnError::Text {
+            Some(
+                self.config
+                    .error_text
+                    .as_deref()
+                    .unwrap_or(DEFAULT_ERROR_TEXT),
+            )
+        } else {
+            self.data.text.as_deref()
+        }
+    }
+
+    pub fn has_tooltip(&self) -> bool {
+        self.tooltip_cmd().is_some() || self.tooltip().is_some()
+    }
+
+    /// Runs one of the commands of the module, the current output
+    /// is available in the `ASHELL_TEXT` and `ASHELL_ALT` variables.
+    fn run(&self, command: Option<&String>) {
+        if let Some(command) = command {
+            execute_command_with_env(
+                command.clone(),
+                vec![
+                    ("ASHELL_TEXT", self.data.text.clone().unwrap_or_default()),
+                    ("ASHELL_ALT", self.data.alt.clone()),
+                ],
+            );
+        }
+    }
+
     pub fn update(&mut self, msg: Message) {
         match msg {
-            Message::LaunchCommand => {
-                if let Some(cmd) = &self.config.command {
-                    execute_command(cmd.clone());
+            Message::LaunchCommand => self.run(self.config.command.as_ref()),
+            Message::ClickRight => self.run(self.config.on_click_right.as_ref()),
+            Message::ClickMiddle => self.run(self.config.on_click_middle.as_ref()),
+            Message::Scroll(delta) => {
+                let y = match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    // a touchpad scrolls by small steps
+                    ScrollDelta::Pixels { y, .. } => {
+                        self.scroll_accumulator += y;
+                        if self.scroll_accumulator.abs() < SCROLL_STEP {
+                            return;
+                        }
+
+                        std::mem::take(&mut self.scroll_accumulator)
+                    }
+                };
+
+                if y > 0. {
+                    self.run(self.config.on_scroll_up.as_ref());
+                } else if y < 0. {
+                    self.run(self.config.on_scroll_down.as_ref());
                 }
             }
             Message::Update(data) => {
+                self.image = load_image(&self.config.name, &data);
                 self.data = data;
+                self.failed = false;
+            }
+            Message::Exited(success) => self.failed = !success,
+            Message::MenuLoaded(entries) => {
+                self.menu = Some(entries);
+                self.expanded_groups.clear();
+            }
+            Message::MenuEntry(command) => self.run(Some(&command)),
+            Message::ToggleGroup(path) => {
+                if !self.expanded_groups.remove(&path) {
+                    self.expanded_groups.insert(path);
+                }
             }
         }
     }
 
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        match &self.menu {
+            None => text("Loading...").into(),
+            Some(entries) if entries.is_empty() => text("No entries").into(),
+            Some(entries) => self.entries_view(theme, entries, Vec::new()),
+        }
+    }
+
+    fn entries_view<'a>(
+        &'a self,
+        theme: &'a AshellTheme,
+        entries: &'a [CustomMenuEntry],
+        path: Vec<usize>,
+    ) -> Element<'a, Message> {
+        Column::with_children(entries.iter().enumerate().map(|(index, entry)| {
+            if entry.separator {
+                return horizontal_rule(1).into();
+            }
+
+            let mut entry_path = path.clone();
+            entry_path.push(index);
+
+            let is_group = !entry.entries.is_empty();
+            let is_expanded = is_group && self.expanded_groups.contains(&entry_path);
+
+            let entry_button = button(
+                row!(entry_content(theme, entry))
+                    .push_maybe(is_group.then(|| {
+                        icon(if is_expanded {
+                            StaticIcon::Close
+                        } else {
+                            StaticIcon::RightChevron
+                        })
+                    }))
+                    .align_y(Alignment::Center)
+                    .spacing(theme.space.xs),
+            )
+            .style(theme.ghost_button_style())
+            .padding(theme.space.xs)
+            .width(Length::Fill);
+            let entry_button = if is_group {
+                entry_button.on_press(Message::ToggleGroup(entry_path.clone()))
+            } else {
+                entry_button.on_press_maybe(entry.command.clone().map(Message::MenuEntry))
+            };
+
+            column!()
+                .push(
+                    row!()
+                        .push_maybe(
+                            entry
+                                .icon
+                                .as_ref()
+                                .map(|value| icon(DynamicIcon(value.clone()))),
+                        )
+                        .push(entry_button)
+                        .align_y(Alignment::Center)
+                        .spacing(theme.space.xs),
+                )
+                .push_maybe(is_expanded.then(|| {
+                    container(self.entries_view(theme, &entry.entries, entry_path))
+                        .padding(Padding::ZERO.left(theme.space.md))
+                }))
+                .into()
+        }))
+        .spacing(theme.space.xxs)
+        .into()
+    }
+
     pub fn view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
-        match self.config.r#type {
-            crate::config::CustomModuleType::Text => self
-                .data
-                .text
-                .as_ref()
-                .and_then(|text_content| {
-                    if !text_content.is_empty() {
-                        Some(text(text_content.clone()).into())
-                    } else {
-                        None
-                    }
+        let content = self.styled(self.content(theme));
+        let config = &self.config;
+
+        if config.on_click_right.is_none()
+            && config.on_click_middle.is_none()
+            && config.on_scroll_up.is_none()
+            && config.on_scroll_down.is_none()
+        {
+            return content;
+        }
+
+        let mut area = MouseArea::new(content);
+        if config.on_click_right.is_some() {
+            area = area.on_right_press(Message::ClickRight);
+        }
+        if config.on_click_middle.is_some() {
+            area = area.on_middle_press(Message::ClickMiddle);
+        }
+        if config.on_scroll_up.is_some() || config.on_scroll_down.is_some() {
+            area = area.on_scroll(Message::Scroll);
+        }
+
+        area.into()
+    }
+
+    /// Colors the module with the danger color after the listen command failed,
+    /// or with the first style rule matching its text.
+    fn styled<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        let color = if self.failed
+            && matches!(
+                self.config.on_error,
+                CustomModuleOnError::Text | CustomModuleOnError::Style
+            ) {
+            Some(StyleRuleColor::Danger)
+        } else {
+            let text = self.data.text.as_deref().unwrap_or_default();
+            self.config
+                .style_rules
+                .iter()
+                .find(|rule| rule.pattern.is_match(text))
+                .map(|rule| rule.color)
+        };
+
+        match color {
+            Some(color) => container(content)
+                .style(move |theme: &Theme| container::Style {
+                    text_color: Some(color.color(theme)),
+                    ..Default::default()
                 })
-                .unwrap_or_else(|| text("").into()),
+                .into(),
+            None => content,
+        }
+    }
+
+    fn content(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
+        match self.config.r#type {
+            crate::config::CustomModuleType::Text => {
+                let text_element = self
+                    .text()
+                    .and_then(|text_content| {
+                        if !text_content.is_empty() {
+                            Some(
+                                theme
+                                    .module_text(&self.config.name, text(text_content.to_owned()))
+                                    .into(),
+                            )
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or_else(|| text("").into());
+
+                match &self.image {
+                    Some(image) => row![image_view(image, theme), text_element]
+                        .align_y(Alignment::Center)
+                        .spacing(theme.space.xs)
+                        .into(),
+                    None => text_element,
+                }
+            }
             crate::config::CustomModuleType::Button => {
-                let mut icon_element = self.config.icon.as_ref().map_or_else(
-                    || icon(StaticIcon::None),
-                    |text| icon(DynamicIcon(text.clone())),
-                );
-
-                if let Some(icons_map) = &self.config.icons {
-                    for (re, icon_str) in icons_map {
-                        if re.is_match(&self.data.alt) {
-                            icon_element = icon(DynamicIcon(icon_str.clone()));
-                            break; // Use the first match
+                let icon_element: Element<'_, Message> = match &self.image {
+                    Some(image) => image_view(image, theme),
+                    None => {
+                        let mut icon_element = self.config.icon.as_ref().map_or_else(
+                            || icon(StaticIcon::None),
+                            |text| icon(DynamicIcon(text.clone())),
+                        );
+
+                        if let Some(icons_map) = &self.config.icons {
+                            for (re, icon_str) in icons_map {
+                                if re.is_match(&self.data.alt) {
+                                    icon_element = icon(DynamicIcon(icon_str.clone()));
+                                    break; // Use the first match
+                                }
+                            }
                         }
+
+                        icon_element.into()
                     }
-                }
+                };
 
                 // Wrap the icon in a container to apply padding
                 let padded_icon_container = container(icon_element).padding([0, 1]);
@@ -155,9 +470,9 @@ impl Custom {
                     padded_icon_container.into() // No alert, just the padded icon
                 };
 
-                let maybe_text_element = self.data.text.as_ref().and_then(|text_content| {
+                let maybe_text_element = self.text().and_then(|text_content| {
                     if !text_content.is_empty() {
-                        Some(text(text_content.clone()))
+                        Some(theme.module_text(&self.config.name, text(text_content.to_owned())))
                     } else {
                         None
                     }
@@ -177,57 +492,34 @@ impl Custom {
     pub fn subscription(&self) -> Subscription<(String, Message)> {
         let id = TypeId::of::<Self>();
         let name = self.config.name.clone();
+        let mode = self.config.mode;
+        let max_retries = self.config.retries;
         if let Some(listen_cmd) = self.config.listen_cmd.clone() {
             Subscription::run_with_id(
-                (id, name.clone(), listen_cmd.clone()),
+                (id, name.clone(), listen_cmd.clone(), mode, max_retries),
                 channel(10, async move |mut output| {
-                    let command = Command::new("bash")
-                        .arg("-c")
-                        .arg(&listen_cmd)
-                        .stdout(Stdio::piped())
-                        .spawn();
-
-                    match command {
-                        Ok(mut child) => {
-                            if let Some(stdout) = child.stdout.take() {
-                                let mut reader = BufReader::new(stdout).lines();
-
-                                // Ensure the child process is spawned in the runtime so it can
-                                // make progress on its own while we await for any output.
-                                tokio::spawn(async move {
-                                    let status = child
-                                        .wait()
-                                        .await
-                                        .expect("child process encountered an error");
-
-                                    info!("child status was: {status}");
-                                });
-
-                                while let Some(line) = reader.next_line().await.ok().flatten() {
-                                    match serde_json::from_str(&line) {
-                                        Ok(event) => {
-                                            if let Err(e) = output
-                                                .try_send((name.clone(), Message::Update(event)))
-                                            {
-                                                error!(
-                                                    "Failed to send update for custom module '{name}': {e}"
-                                                );
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!(
-                                                "Failed to parse JSON for custom module '{name}': {e} (payload: {line})"
-                                            );
-                                        }
-                                    }
-                                }
-                            } else {
-                                error!("Failed to capture stdout for command: {listen_cmd}");
+                    let mut backoff = MIN_BACKOFF;
+                    let mut retries = 0;
+
+                    loop {
+                        let (updated, success) = listen(&name, &listen_cmd, &mut output).await;
+
+                        if mode != CustomModuleMode::Stream {
+                            if success || retries >= max_retries {
+                                break;
                             }
+                            retries += 1;
                         }
-                        Err(error) => {
-                            error!("Failed to execute command: {error}");
+
+                        // a command that worked for a while is restarted right away
+                        if updated {
+                            backoff = MIN_BACKOFF;
                         }
+                        warn!(
+                            "Listen command of custom module '{name}' exited, restarting in {backoff:?}"
+                        );
+                        sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
                     }
                 }),
             )
@@ -236,3 +528,317 @@ impl Custom {
         }
     }
 }
+
+/// Runs the listen command until it exits, publishing an update for each line of its output
+/// and its exit status. Returns `(updated, success)`: whether the command produced any update,
+/// and whether it exited successfully.
+async fn listen(
+    name: &str,
+    listen_cmd: &str,
+    output: &mut Sender<(String, Message)>,
+) -> (bool, bool) {
+    let command = Command::new("bash")
+        .arg("-c")
+        .arg(listen_cmd)
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+
+    let mut child = match command {
+        Ok(child) => child,
+        Err(error) => {
+            error!("Failed to execute command: {error}");
+            send_exited(name, output, false);
+            return (false, false);
+        }
+    };
+
+    let mut updated = false;
+
+    if let Some(stdout) = child.stdout.take() {
+        let mut reader = BufReader::new(stdout).lines();
+
+        while let Some(line) = reader.next_line().await.ok().flatten() {
+            match parse_line(&line) {
+                Ok(event) => {
+                    updated = true;
+                    if let Err(e) = output.try_send((name.to_owned(), Message::Update(event))) {
+                        error!("Failed to send update for custom module '{name}': {e}");
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to parse JSON for custom module '{name}': {e} (payload: {line})"
+                    );
+                }
+            }
+        }
+    } else {
+        error!("Failed to capture stdout for command: {listen_cmd}");
+    }
+
+    let success = match child.wait().await {
+        Ok(status) => {
+            info!("child status was: {status}");
+            status.success()
+        }
+        Err(e) => {
+            error!("Failed to wait for the command of custom module '{name}': {e}");
+            false
+        }
+    };
+    send_exited(name, output, success);
+
+    (updated, success)
+}
+
+fn send_exited(name: &str, output: &mut Sender<(String, Message)>, success: bool) {
+    if let Err(e) = output.try_send((name.to_owned(), Message::Exited(success))) {
+        error!("Failed to send exit status for custom module '{name}': {e}");
+    }
+}
+
+/// Picture of the module, as tall as the icons of the bar.
+fn image_view<'a>(image: &TrayIcon, theme: &AshellTheme) -> Element<'a, Message> {
+    let size = theme.font_size.md as f32 + 2.;
+
+    match image {
+        TrayIcon::Image(handle) => Image::new(handle.clone())
+            .height(Length::Fixed(size))
+            .into(),
+        TrayIcon::Svg(handle) => Svg::new(handle.clone())
+            .height(Length::Fixed(size))
+            .width(Length::Fixed(size))
+            .into(),
+    }
+}
+
+/// Picture of the module from the output of the listen command, the first of
+/// the encoded picture, the picture file and the freedesktop icon.
+fn load_image(name: &str, data: &CustomListenData) -> Option<TrayIcon> {
+    if let Some(encoded) = &data.image_data {
+        match BASE64_STANDARD.decode(encoded.trim()) {
+            Ok(bytes) => return Some(image_from_bytes(bytes)),
+            Err(e) => warn!("Invalid image_data for custom module '{name}': {e}"),
+        }
+    }
+
+    if let Some(path) = &data.image {
+        return tray_icon_from_path(PathBuf::from(path));
+    }
+
+    data.icon.as_deref().and_then(|icon_name| {
+        let icon = get_icon_from_name(icon_name);
+        if icon.is_none() {
+            warn!("Icon {icon_name} of custom module '{name}' not found");
+        }
+
+        icon
+    })
+}
+
+/// Picture previewed in a menu entry.
+fn thumbnail_view<'a>(thumbnail: &TrayIcon) -> Element<'a, Message> {
+    match thumbnail {
+        TrayIcon::Image(handle) => Image::new(handle.clone())
+            .height(Length::Fixed(THUMBNAIL_HEIGHT))
+            .into(),
+        TrayIcon::Svg(handle) => Svg::new(handle.clone())
+            .height(Length::Fixed(THUMBNAIL_HEIGHT))
+            .width(Length::Shrink)
+            .into(),
+    }
+}
+
+/// Label of a menu entry followed by the preview of its content.
+fn entry_content<'a>(theme: &'a AshellTheme, entry: &'a CustomMenuEntry) -> Element<'a, Message> {
+    column!(text(entry.label.clone()))
+        .push_maybe(entry.thumbnail.as_ref().map(thumbnail_view))
+        .push_maybe((!entry.files.is_empty()).then(|| {
+            Column::with_children(entry.files.iter().map(|file| {
+                let path = file.strip_prefix("file://").unwrap_or(file);
+                let name = path
+                    .rsplit('/')
+                    .find(|part| !part.is_empty())
+                    .unwrap_or(path);
+
+                row!(
+                    icon(StaticIcon::File).size(theme.font_size.sm),
+                    text(name.to_owned()).size(theme.font_size.sm)
+                )
+                .spacing(theme.space.xxs)
+                .align_y(Alignment::Center)
+                .into()
+            }))
+        }))
+        .push_maybe(entry.code.as_ref().map(|code| code_view(theme, code)))
+        .spacing(theme.space.xxs)
+        .width(Length::Fill)
+        .into()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodeToken {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+/// Splits a line of code in colored tokens, a rough highlighting that
+/// works for most languages without knowing which one it is.
+fn highlight(line: &str) -> Vec<(&str, CodeToken)> {
+    let mut tokens: Vec<(usize, usize, CodeToken)> = Vec::new();
+    let mut push = |start: usize, end: usize, kind: CodeToken| match tokens.last_mut() {
+        // merge the plain tokens to keep the number of widgets low
+        Some((_, last_end, CodeToken::Plain)) if kind == CodeToken::Plain => *last_end = end,
+        _ => tokens.push((start, end, kind)),
+    };
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if line[start..].starts_with("//") || (c == '#' && line[..start].trim().is_empty()) {
+            push(start, line.len(), CodeToken::Comment);
+            break;
+        }
+
+        if c == '"' || c == '\'' || c == '`' {
+            let mut escaped = false;
+            let end = chars
+                .by_ref()
+                .find(|(_, next)| {
+                    let closed = !escaped && *next == c;
+                    escaped = !escaped && *next == '\\';
+                    closed
+                })
+                .map_or(line.len(), |(index, next)| index + next.len_utf8());
+            push(start, end, CodeToken::String);
+        } else if c.is_alphanumeric() || c == '_' {
+            let number = c.is_ascii_digit();
+            while chars
+                .next_if(|(_, next)| {
+                    next.is_alphanumeric() || *next == '_' || (number && *next == '.')
+                })
+                .is_some()
+            {}
+            let end = chars.peek().map_or(line.len(), |(index, _)| *index);
+            let kind = if number {
+                CodeToken::Number
+            } else if CODE_KEYWORDS.contains(&&line[start..end]) {
+                CodeToken::Keyword
+            } else {
+                CodeToken::Plain
+            };
+            push(start, end, kind);
+        } else {
+            push(start, start + c.len_utf8(), CodeToken::Plain);
+        }
+    }
+
+    tokens
+        .into_iter()
+        .map(|(start, end, kind)| (&line[start..end], kind))
+        .collect()
+}
+
+/// First lines of a code snippet in a monospaced font, colored by token.
+fn code_view<'a>(theme: &'a AshellTheme, code: &'a str) -> Element<'a, Message> {
+    let palette = theme.get_theme().palette();
+
+    Column::with_children(code.lines().take(CODE_PREVIEW_LINES).map(|line| {
+        Row::with_children(highlight(line).into_iter().map(|(token, kind)| {
+            let color = match kind {
+                CodeToken::Plain => palette.text,
+                CodeToken::Keyword => palette.primary,
+                CodeToken::String => palette.success,
+                CodeToken::Number => palette.danger,
+                CodeToken::Comment => palette.text.scale_alpha(0.5),
+            };
+
+            text(token)
+                .font(Font::MONOSPACE)
+                .size(theme.font_size.sm)
+                .color(color)
+                .into()
+        }))
+        .into()
+    }))
+    .into()
+}
+
+/// Loads the pictures previewed in the entries and in the nested ones.
+fn load_thumbnails(entries: &mut [CustomMenuEntry]) {
+    for entry in entries {
+        entry.thumbnail = entry
+            .image_data
+            .as_ref()
+            .and_then(|encoded| match BASE64_STANDARD.decode(encoded.trim()) {
+                Ok(bytes) => Some(image_from_bytes(bytes)),
+                Err(e) => {
+                    warn!("Invalid image_data for menu entry '{}': {e}", entry.label);
+                    None
+                }
+            })
+            .or_else(|| {
+                entry
+                    .image
+                    .as_ref()
+                    .and_then(|path| tray_icon_from_path(PathBuf::from(path)))
+            });
+
+        load_thumbnails(&mut entry.entries);
+    }
+}
+
+/// Entries printed by the menu command as a JSON list, the errors are logged
+/// and shown as an empty menu.
+pub async fn menu_output(menu_cmd: String) -> Vec<CustomMenuEntry> {
+    match Command::new("bash").arg("-c").arg(&menu_cmd).output().await {
+        Ok(output) => match serde_json::from_slice::<Vec<CustomMenuEntry>>(&output.stdout) {
+            Ok(mut entries) => {
+                load_thumbnails(&mut entries);
+                entries
+            }
+            Err(e) => {
+                error!("Failed to parse the output of menu command {menu_cmd}: {e}");
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to execute menu command {menu_cmd}: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Output of the tooltip command, the errors are logged and shown as an empty tooltip.
+pub async fn tooltip_output(tooltip_cmd: String) -> String {
+    match Command::new("bash")
+        .arg("-c")
+        .arg(&tooltip_cmd)
+        .output()
+        .await
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_owned(),
+        Err(e) => {
+            error!("Failed to execute tooltip command {tooltip_cmd}: {e}");
+            String::new()
+        }
+    }
+}
+
+/// Reads a line of the listen command, a line that isn't a JSON object is used as the text.
+fn parse_line(line: &str) -> Result<CustomListenData, serde_json::Error> {
+    if line.trim_start().starts_with('{') {
+        serde_json::from_str(line)
+    } else {
+        Ok(CustomListenData {
+            alt: line.to_owned(),
+            text: Some(line.to_owned()),
+            ..CustomListenData::default()
+        })
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    components::icons::{StaticIcon, icon},
+    config::{FeedConfig, FeedsFormat, FeedsModuleConfig},
+    theme::AshellTheme,
+    utils::{
+        http,
+        launcher::open_url,
+        state::{read_state, write_state},
+        truncate_text,
+    },
+};
+use chrono::{DateTime, FixedOffset};
+use iced::{
+    Alignment, Element, Length, Subscription,
+    stream::channel,
+    time::every,
+    widget::{Column, button, column, container, horizontal_rule, row, scrollable, text},
+};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{any::TypeId, collections::HashSet, time::Duration};
+use tokio::time::sleep;
+
+const STATE_FILE: &str = "feeds.json";
+
+#[derive(Debug, Clone)]
+pub struct FeedItem {
+    /// Unique key, namespaced by the feed url
+    pub key: String,
+    pub feed: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub published: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct FeedsState {
+    pub read: HashSet<String>,
+}
+
+pub(crate) fn item_key(url: &str, id: &str) -> String {
+    format!("{url}#{id}")
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(|child| child.text())
+        .map(|value| value.trim().to_owned())
+}
+
+fn parse_feed(feed: &FeedConfig, content: &str) -> anyhow::Result<Vec<FeedItem>> {
+    let document = roxmltree::Document::parse(content)?;
+    let root = document.root_element();
+
+    let (feed_title, items) = match root.tag_name().name() {
+        // RSS 2.0 and RSS 1.0 (RDF)
+        "rss" | "RDF" => {
+            let rss_channel = root
+                .descendants()
+                .find(|node| node.tag_name().name() == "channel");
+            let items = root
+                .descendants()
+                .filter(|node| node.tag_name().name() == "item")
+                .map(|node| {
+                    let link = child_text(node, "link");
+                    let id = child_text(node, "guid").or_else(|| link.clone());
+                    let published = child_text(node, "pubDate")
+                        .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+                        .or_else(|| {
+                            child_text(node, "date")
+                                .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                        });
+
+                    (id, child_text(node, "title"), link, published)
+                })
+                .collect::<Vec<_>>();
+
+            (
+                rss_channel.and_then(|node| child_text(node, "title")),
+                items,
+            )
+        }
+        "feed" => {
+            let items = root
+                .children()
+                .filter(|node| node.tag_name().name() == "entry")
+                .map(|node| {
+                    let link = node
+                        .children()
+                        .filter(|child| child.tag_name().name() == "link")
+                        .find(|child| child.attribute("rel").is_none_or(|rel| rel == "alternate"))
+                        .and_then(|child| child.attribute("href"))
+                        .map(str::to_owned);
+                    let published = child_text(node, "updated")
+                        .or_else(|| child_text(node, "published"))
+                        .and_then(|date| DateTime::parse_from_rfc3339(&date).ok());
+
+                    (
+                        child_text(node, "id"),
+                        child_text(node, "title"),
+                        link,
+                        published,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            (child_text(root, "title"), items)
+        }
+        other => anyhow::bail!("unsupported feed root element <{other}>"),
+    };
+
+    let feed_name = feed
+        .name
+        .clone()
+        .or(feed_title)
+        .unwrap_or_else(|| feed.url.clone());
+
+    Ok(items
+        .into_iter()
+        .filter_map(|(id, title, link, published)| {
+            let title = title.unwrap_or_default();
+            let id = id.or_else(|| (!title.is_empty()).then(|| title.clone()))?;
+
+            Some(FeedItem {
+                key: item_key(&feed.url, &id),
+                feed: feed_name.clone(),
+                title,
+                link,
+                published,
+            })
+        })
+        .collect())
+}
+
+/// Fetches the feed, returns `None` when the server reports it as unchanged.
+pub(crate) async fn fetch_feed(
+    feed: &FeedConfig,
+    etag: &mut Option<String>,
+) -> anyhow::Result<Option<Vec<FeedItem>>> {
+    let headers = etag
+        .iter()
+        .map(|etag| ("If-None-Match", etag.clone()))
+        .collect::<Vec<_>>();
+    let response = http::get(&feed.url, &headers).await?;
+
+    match response.status {
+        304 => Ok(None),
+        200 => {
+            *etag = response.header("ETag").map(str::to_owned);
+
+            parse_feed(feed, &response.body).map(Some)
+        }
+        status => anyhow::bail!("unexpected status {status}"),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FeedFetched(String, Vec<FeedItem>),
+    RotateHeadline,
+    Open(String),
+    MarkAllRead,
+}
+
+pub struct Feeds {
+    config: FeedsModuleConfig,
+    items: Vec<FeedItem>,
+    read: HashSet<String>,
+    headline: usize,
+}
+
+impl Feeds {
+    pub fn new(config: FeedsModuleConfig) -> Self {
+        let state: FeedsState = read_state(STATE_FILE);
+
+        Self {
+            config,
+            items: Vec::new(),
+            read: state.read,
+            headline: 0,
+        }
+    }
+
+    fn save_state(&self) {
+        write_state(
+            STATE_FILE,
+            &FeedsState {
+                read: self.read.clone(),
+            },
+        );
+    }
+
+    /// The newest entries across all feeds, the others are kept so that
+    /// they come back when newer ones are removed from their feed.
+    fn shown(&self) -> impl Iterator<Item = &FeedItem> {
+        self.items.iter().take(self.config.max_items)
+    }
+
+    fn unread(&self) -> impl Iterator<Item = &FeedItem> {
+        self.shown().filter(|item| !self.read.contains(&item.key))
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::FeedFetched(url, items) => {
+                let prefix = item_key(&url, "");
+
+                // forget the read state of entries that dropped out of the feed
+                let before = self.read.len();
+                self.read.retain(|key| {
+                    !key.starts_with(&prefix) || items.iter().any(|item| item.key == *key)
+                });
+                if self.read.len() != before {
+                    self.save_state();
+                }
+
+                self.items.retain(|item| !item.key.starts_with(&prefix));
+                self.items.extend(items);
+                self.items.sort_by(|a, b| b.published.cmp(&a.published));
+            }
+            Message::RotateHeadline => {
+                self.headline = self.headline.wrapping_add(1);
+            }
+            Message::Open(key) => {
+                if let Some(link) = self
+                    .items
+                    .iter()
+                    .find(|item| item.key == key)
+                    .and_then(|item| item.link.clone())
+                {
+                    open_url(link);
+                }
+
+                if self.read.insert(key) {
+                    self.save_state();
+                }
+            }
+            Message::MarkAllRead => {
+                let keys = self
+                    .unread()
+                    .map(|item| item.key.clone())
+                    .collect::<Vec<_>>();
+
+                if !keys.is_empty() {
+                    self.read.extend(keys);
+                    self.save_state();
+                }
+            }
+        }
+    }
+
+    pub fn view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
+        let unread = self.unread().collect::<Vec<_>>();
+
+        let label = match self.config.format {
+            FeedsFormat::UnreadCount => (!unread.is_empty()).then(|| unread.len().to_string()),
+            FeedsFormat::Headline => unread
+                .get(self.headline % unread.len().max(1))
+                .map(|item| truncate_text(&item.title, self.config.max_headline_length)),
+        };
+
+        row!(icon(StaticIcon::Rss))
+            .push_maybe(label.map(|label| {
+                text(label)
+                    .size(theme.font_size.sm)
+                    .wrapping(text::Wrapping::None)
+            }))
+            .align_y(Alignment::Center)
+            .spacing(theme.space.xxs)
+            .into()
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let unread = self.unread().count();
+
+        column!(
+            row!(
+                text(format!("{unread} unread"))
+                    .size(theme.font_size.lg)
+                    .width(Length::Fill),
+                button(text("Mark all read").size(theme.font_size.sm))
+                    .style(theme.ghost_button_style())
+                    .padding(theme.space.xs)
+                    .on_press_maybe((unread > 0).then_some(Message::MarkAllRead)),
+            )
+            .align_y(Alignment::Center),
+            horizontal_rule(1),
+            if self.items.is_empty() {
+                Into::<Element<'_, _>>::into(container(text("No entries")).padding(theme.space.xs))
+            } else {
+                container(scrollable(
+                    Column::with_children(
+                        self.shown()
+                            .map(|item| {
+                                let entry = button(
+                                    column!(
+                                        text(item.title.clone())
+                                            .wrapping(text::Wrapping::WordOrGlyph)
+                                            .width(Length::Fill),
+                                        text(match item.published {
+                                            Some(date) => format!(
+                                                "{} · {}",
+                                                item.feed,
+                                                date.format("%d %b %R")
+                                            ),
+                                            None => item.feed.clone(),
+                                        })
+                                        .size(theme.font_size.xs),
+                                    )
+                                    .spacing(theme.space.xxs),
+                                )
+                                .padding(theme.space.xs)
+                                .width(Length::Fill)
+                                .on_press(Message::Open(item.key.clone()));
+
+                                // the two styles are different closures
+                                if self.read.contains(&item.key) {
+                                    entry.style(theme.ghost_button_style()).into()
+                                } else {
+                                    entry.style(theme.outline_button_style()).into()
+                                }
+                            })
+                            .collect::<Vec<Element<'_, _>>>(),
+                    )
+                    .spacing(theme.space.xxs)
+                    .padding([0, theme.space.md, 0, 0]),
+                ))
+                .max_height(400)
+                .into()
+            }
+        )
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let id = TypeId::of::<Self>();
+
+        let mut subscriptions = self
+            .config
+            .feeds
+            .iter()
+            .cloned()
+            .map(|feed| {
+                Subscription::run_with_id(
+                    (id, feed.url.clone(), feed.interval),
+                    channel(10, async move |mut output| {
+                        let interval = Duration::from_secs(feed.interval.max(60));
+                        let mut etag = None;
+
+                        loop {
+                            match fetch_feed(&feed, &mut etag).await {
+                                Ok(None) => {
+                                    debug!("Feed {} not modified", feed.url);
+                                }
+                                Ok(Some(items)) => {
+                                    debug!("Fetched {} entries from {}", items.len(), feed.url);
+                                    let _ = output
+                                        .try_send(Message::FeedFetched(feed.url.clone(), items));
+                                }
+                                Err(e) => {
+                                    warn!("Failed to fetch feed {}: {e}", feed.url);
+                                }
+                            }
+
+                            sleep(interval).await;
+                        }
+                    }),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if self.config.format == FeedsFormat::Headline {
+            subscriptions.push(
+                every(Duration::from_secs(self.config.headline_interval.max(1)))
+                    .map(|_| Message::RotateHeadline),
+            );
+        }
+
+        Subscription::batch(subscriptions)
+    }
+}
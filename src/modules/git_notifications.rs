@@ -0,0 +1,562 @@
+use crate::{
+    components::icons::{StaticIcon, icon},
+    config::{GitForgeConfig, GitNotificationsModuleConfig},
+    theme::AshellTheme,
+    utils::{http, launcher::open_url},
+};
+use anyhow::{Context, bail};
+use chrono::{DateTime, FixedOffset};
+use iced::{
+    Alignment, Element, Length, Subscription,
+    stream::channel,
+    widget::{Column, button, column, container, horizontal_rule, row, scrollable, text},
+};
+use log::{debug, warn};
+use serde::Deserialize;
+use std::{any::TypeId, process::Stdio, time::Duration};
+use tokio::{process::Command, time::sleep};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+}
+
+impl Forge {
+    fn name(self) -> &'static str {
+        match self {
+            Forge::GitHub => "GitHub",
+            Forge::GitLab => "GitLab",
+        }
+    }
+
+    fn api_url(self, config: &GitForgeConfig) -> String {
+        let url = match (self, config.url.as_deref()) {
+            (_, Some(url)) => url,
+            (Forge::GitHub, None) => "https://api.github.com",
+            (Forge::GitLab, None) => "https://gitlab.com",
+        };
+
+        url.trim_end_matches('/').to_owned()
+    }
+
+    fn auth_header(self, token: String) -> (&'static str, String) {
+        match self {
+            Forge::GitHub => ("Authorization", format!("Bearer {token}")),
+            Forge::GitLab => ("PRIVATE-TOKEN", token),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: String,
+    pub forge: Forge,
+    pub repository: String,
+    pub title: String,
+    pub reason: String,
+    pub url: String,
+    pub updated_at: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Deserialize)]
+struct GitHubThread {
+    id: String,
+    reason: String,
+    updated_at: String,
+    subject: GitHubSubject,
+    repository: GitHubRepository,
+}
+
+#[derive(Deserialize)]
+struct GitHubSubject {
+    title: String,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubRepository {
+    full_name: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabTodo {
+    id: u64,
+    action_name: String,
+    target_url: String,
+    body: String,
+    updated_at: String,
+    project: Option<GitLabProject>,
+    target: Option<GitLabTarget>,
+}
+
+#[derive(Deserialize)]
+struct GitLabProject {
+    path_with_namespace: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabTarget {
+    title: Option<String>,
+}
+
+/// Maps the API url of a notification subject to its web page.
+///
+/// Only issues, pull requests and commits can be mapped reliably,
+/// everything else falls back to the repository page.
+fn github_html_url(subject_url: Option<&str>, repository: &GitHubRepository) -> String {
+    let path = subject_url
+        .and_then(|url| url.split_once(&format!("/repos/{}/", repository.full_name)))
+        .map(|(_, path)| path);
+
+    match path.and_then(|path| path.split_once('/')) {
+        Some(("issues", number)) => format!("{}/issues/{number}", repository.html_url),
+        Some(("pulls", number)) => format!("{}/pull/{number}", repository.html_url),
+        Some(("commits", sha)) => format!("{}/commit/{sha}", repository.html_url),
+        _ => repository.html_url.clone(),
+    }
+}
+
+async fn resolve_token(config: &GitForgeConfig) -> anyhow::Result<String> {
+    if let Some(token) = &config.token {
+        return Ok(token.clone());
+    }
+
+    let Some(cmd) = &config.token_cmd else {
+        bail!("neither token nor token_cmd is configured");
+    };
+
+    let output = Command::new("bash")
+        .arg("-c")
+        .arg(cmd)
+        .stdout(Stdio::piped())
+        .output()
+        .await
+        .context("failed to run token_cmd")?;
+
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if !output.status.success() || token.is_empty() {
+        bail!("token_cmd did not return a token");
+    }
+
+    Ok(token)
+}
+
+/// Most pages of notifications fetched by a poll.
+const MAX_PAGES: usize = 20;
+
+/// Url of the next page, from the `Link` header of GitHub or the
+/// `X-Next-Page` header of GitLab.
+fn next_page(forge: Forge, api_url: &str, response: &http::Response) -> Option<String> {
+    match forge {
+        Forge::GitHub => response.header("Link")?.split(',').find_map(|link| {
+            let (url, rel) = link.split_once(';')?;
+
+            (rel.trim() == r#"rel="next""#).then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_owned()
+            })
+        }),
+        Forge::GitLab => response
+            .header("X-Next-Page")
+            .filter(|page| !page.is_empty())
+            .map(|page| format!("{api_url}/api/v4/todos?state=pending&per_page=100&page={page}")),
+    }
+}
+
+/// Keeps track of the token and of the conditional request headers for a
+/// single forge.
+struct Poller {
+    forge: Forge,
+    config: GitForgeConfig,
+    // read once, `token_cmd` can prompt or unlock a keyring
+    token: Option<String>,
+    last_modified: Option<String>,
+    etag: Option<String>,
+    interval: Duration,
+    // set while the forge rate limits the requests
+    retry_after: Option<Duration>,
+}
+
+impl Poller {
+    fn new(forge: Forge, config: GitForgeConfig) -> Self {
+        let interval = Duration::from_secs(config.interval.max(30));
+
+        Self {
+            forge,
+            config,
+            token: None,
+            last_modified: None,
+            etag: None,
+            interval,
+            retry_after: None,
+        }
+    }
+
+    /// Requests a page, the first one is conditional.
+    async fn get(&mut self, url: &str, first: bool) -> anyhow::Result<http::Response> {
+        let token = match self.token.clone() {
+            Some(token) => token,
+            None => self
+                .token
+                .insert(resolve_token(&self.config).await?)
+                .clone(),
+        };
+
+        let mut headers = vec![self.forge.auth_header(token)];
+        if first {
+            if let Some(last_modified) = &self.last_modified {
+                headers.push(("If-Modified-Since", last_modified.clone()));
+            }
+            if let Some(etag) = &self.etag {
+                headers.push(("If-None-Match", etag.clone()));
+            }
+        }
+        if self.forge == Forge::GitHub {
+            headers.push(("Accept", "application/vnd.github+json".to_owned()));
+        }
+
+        let response = http::get(url, &headers).await?;
+
+        // GitHub asks clients to not poll more often than this
+        if let Some(poll_interval) = response
+            .header("X-Poll-Interval")
+            .and_then(|value| value.parse::<u64>().ok())
+        {
+            self.interval = Duration::from_secs(self.config.interval.max(poll_interval));
+        }
+
+        if let Some(retry_after) = response.retry_after() {
+            self.retry_after = Some(retry_after.max(self.interval));
+            bail!("rate limited, retrying in {}s", retry_after.as_secs());
+        }
+
+        match response.status {
+            200 | 304 => Ok(response),
+            401 => {
+                // the token expired or was revoked, it's read again next time
+                self.token = None;
+                bail!("the token was rejected")
+            }
+            status => bail!("unexpected status {status}"),
+        }
+    }
+
+    /// Returns `None` when nothing changed since the last poll.
+    async fn poll(&mut self) -> anyhow::Result<Option<Vec<Notification>>> {
+        let api_url = self.forge.api_url(&self.config);
+        let mut url = match self.forge {
+            Forge::GitHub => format!("{api_url}/notifications?per_page=50"),
+            Forge::GitLab => format!("{api_url}/api/v4/todos?state=pending&per_page=100"),
+        };
+        let mut notifications = Vec::new();
+
+        for page in 1..=MAX_PAGES {
+            let response = self.get(&url, page == 1).await?;
+
+            if page == 1 {
+                if response.status == 304 {
+                    return Ok(None);
+                }
+
+                self.last_modified = response.header("Last-Modified").map(str::to_owned);
+                self.etag = response.header("ETag").map(str::to_owned);
+            }
+
+            notifications.extend(self.parse(&response.body)?);
+
+            match next_page(self.forge, &api_url, &response) {
+                Some(next) => url = next,
+                None => return Ok(Some(notifications)),
+            }
+        }
+
+        warn!(
+            "More than {MAX_PAGES} pages of {} notifications, only the first ones are shown",
+            self.forge.name()
+        );
+
+        Ok(Some(notifications))
+    }
+
+    fn parse(&self, body: &str) -> anyhow::Result<Vec<Notification>> {
+        Ok(match self.forge {
+            Forge::GitHub => serde_json::from_str::<Vec<GitHubThread>>(body)?
+                .into_iter()
+                .map(|thread| Notification {
+                    url: github_html_url(thread.subject.url.as_deref(), &thread.repository),
+                    id: thread.id,
+                    forge: Forge::GitHub,
+                    repository: thread.repository.full_name,
+                    title: thread.subject.title,
+                    reason: thread.reason.replace('_', " "),
+                    updated_at: DateTime::parse_from_rfc3339(&thread.updated_at).ok(),
+                })
+                .collect(),
+            Forge::GitLab => serde_json::from_str::<Vec<GitLabTodo>>(body)?
+                .into_iter()
+                .map(|todo| Notification {
+                    id: todo.id.to_string(),
+                    forge: Forge::GitLab,
+                    repository: todo
+                        .project
+                        .map(|project| project.path_with_namespace)
+                        .unwrap_or_default(),
+                    title: todo
+                        .target
+                        .and_then(|target| target.title)
+                        .unwrap_or(todo.body),
+                    reason: todo.action_name.replace('_', " "),
+                    url: todo.target_url,
+                    updated_at: DateTime::parse_from_rfc3339(&todo.updated_at).ok(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Marks a notification, or all of them when `id` is `None`, as read.
+async fn mark_read(forge: Forge, config: GitForgeConfig, id: Option<String>) -> anyhow::Result<()> {
+    let token = resolve_token(&config).await?;
+    let api_url = forge.api_url(&config);
+    let headers = [forge.auth_header(token)];
+
+    let response = match (forge, id) {
+        (Forge::GitHub, Some(id)) => {
+            http::request(
+                "PATCH",
+                &format!("{api_url}/notifications/threads/{id}"),
+                &headers,
+            )
+            .await?
+        }
+        (Forge::GitHub, None) => {
+            http::request("PUT", &format!("{api_url}/notifications"), &headers).await?
+        }
+        (Forge::GitLab, Some(id)) => {
+            http::request(
+                "POST",
+                &format!("{api_url}/api/v4/todos/{id}/mark_as_done"),
+                &headers,
+            )
+            .await?
+        }
+        (Forge::GitLab, None) => {
+            http::request(
+                "POST",
+                &format!("{api_url}/api/v4/todos/mark_as_done"),
+                &headers,
+            )
+            .await?
+        }
+    };
+
+    if !(200..300).contains(&response.status) {
+        bail!("unexpected status {}", response.status);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Fetched(Forge, Vec<Notification>),
+    Open(Forge, String),
+    MarkRead(Forge, String),
+    MarkAllRead,
+}
+
+pub struct GitNotifications {
+    config: GitNotificationsModuleConfig,
+    notifications: Vec<Notification>,
+}
+
+impl GitNotifications {
+    pub fn new(config: GitNotificationsModuleConfig) -> Self {
+        Self {
+            config,
+            notifications: Vec::new(),
+        }
+    }
+
+    fn forge_config(&self, forge: Forge) -> Option<&GitForgeConfig> {
+        match forge {
+            Forge::GitHub => self.config.github.as_ref(),
+            Forge::GitLab => self.config.gitlab.as_ref(),
+        }
+    }
+
+    fn mark_read(&mut self, forge: Forge, id: Option<String>) {
+        self.notifications.retain(|notification| {
+            notification.forge != forge || id.as_ref().is_some_and(|id| notification.id != *id)
+        });
+
+        if let Some(config) = self.forge_config(forge).cloned() {
+            tokio::spawn(async move {
+                if let Err(e) = mark_read(forge, config, id).await {
+                    warn!("Failed to mark {} notifications as read: {e}", forge.name());
+                }
+            });
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Fetched(forge, notifications) => {
+                self.notifications
+                    .retain(|notification| notification.forge != forge);
+                self.notifications.extend(notifications);
+                self.notifications
+                    .sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+            }
+            Message::Open(forge, id) => {
+                if let Some(notification) = self
+                    .notifications
+                    .iter()
+                    .find(|notification| notification.forge == forge && notification.id == id)
+                {
+                    open_url(notification.url.clone());
+                }
+
+                self.mark_read(forge, Some(id));
+            }
+            Message::MarkRead(forge, id) => {
+                self.mark_read(forge, Some(id));
+            }
+            Message::MarkAllRead => {
+                for forge in [Forge::GitHub, Forge::GitLab] {
+                    if self
+                        .notifications
+                        .iter()
+                        .any(|notification| notification.forge == forge)
+                    {
+                        self.mark_read(forge, None);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
+        row!(icon(StaticIcon::Git))
+            .push_maybe(
+                (!self.notifications.is_empty())
+                    .then(|| text(self.notifications.len()).size(theme.font_size.sm)),
+            )
+            .align_y(Alignment::Center)
+            .spacing(theme.space.xxs)
+            .into()
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let unread = self.notifications.len();
+
+        column!(
+            row!(
+                text(format!("{unread} notifications"))
+                    .size(theme.font_size.lg)
+                    .width(Length::Fill),
+                button(text("Mark all read").size(theme.font_size.sm))
+                    .style(theme.ghost_button_style())
+                    .padding(theme.space.xs)
+                    .on_press_maybe((unread > 0).then_some(Message::MarkAllRead)),
+            )
+            .align_y(Alignment::Center),
+            horizontal_rule(1),
+            if self.notifications.is_empty() {
+                Into::<Element<'_, _>>::into(
+                    container(text("All caught up")).padding(theme.space.xs),
+                )
+            } else {
+                container(scrollable(
+                    Column::with_children(
+                        self.notifications
+                            .iter()
+                            .map(|notification| {
+                                row!(
+                                    button(
+                                        column!(
+                                            text(notification.title.clone())
+                                                .wrapping(text::Wrapping::WordOrGlyph)
+                                                .width(Length::Fill),
+                                            text(format!(
+                                                "{} · {} · {}",
+                                                notification.forge.name(),
+                                                notification.repository,
+                                                notification.reason
+                                            ))
+                                            .size(theme.font_size.xs),
+                                        )
+                                        .spacing(theme.space.xxs),
+                                    )
+                                    .style(theme.ghost_button_style())
+                                    .padding(theme.space.xs)
+                                    .width(Length::Fill)
+                                    .on_press(Message::Open(
+                                        notification.forge,
+                                        notification.id.clone(),
+                                    )),
+                                    button(icon(StaticIcon::Close))
+                                        .style(theme.ghost_button_style())
+                                        .padding(theme.space.xs)
+                                        .on_press(Message::MarkRead(
+                                            notification.forge,
+                                            notification.id.clone(),
+                                        )),
+                                )
+                                .align_y(Alignment::Center)
+                                .into()
+                            })
+                            .collect::<Vec<Element<'_, _>>>(),
+                    )
+                    .spacing(theme.space.xxs)
+                    .padding([0, theme.space.md, 0, 0]),
+                ))
+                .max_height(400)
+                .into()
+            }
+        )
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::batch(
+            [
+                (Forge::GitHub, self.config.github.clone()),
+                (Forge::GitLab, self.config.gitlab.clone()),
+            ]
+            .into_iter()
+            .filter_map(|(forge, config)| config.map(|config| (forge, config)))
+            .map(|(forge, config)| {
+                Subscription::run_with_id(
+                    (id, forge, config.clone()),
+                    channel(10, async move |mut output| {
+                        let mut poller = Poller::new(forge, config);
+
+                        loop {
+                            match poller.poll().await {
+                                Ok(None) => {
+                                    debug!("No new {} notifications", forge.name());
+                                }
+                                Ok(Some(notifications)) => {
+                                    let _ = output.try_send(Message::Fetched(forge, notifications));
+                                }
+                                Err(e) => {
+                                    warn!("Failed to fetch {} notifications: {e}", forge.name());
+                                }
+                            }
+
+                            sleep(poller.retry_after.take().unwrap_or(poller.interval)).await;
+                        }
+                    }),
+                )
+            }),
+        )
+    }
+}
@@ -1,23 +1,59 @@
 use crate::{
-    config::KeyboardLayoutModuleConfig,
+    config::{KeyboardLayoutFormat, KeyboardLayoutModuleConfig},
     services::{
         ReadOnlyService, Service, ServiceEvent,
-        compositor::{CompositorCommand, CompositorService},
+        compositor::{CompositorCommand, CompositorService, LayoutSwitch},
     },
     theme::AshellTheme,
+    utils::xkb,
 };
-use iced::{Element, Subscription, Task, widget::text};
+use iced::{
+    Element, Length, Task, Theme,
+    mouse::ScrollDelta,
+    widget::{Column, MouseArea, button, column, container, horizontal_rule, text},
+};
+use std::collections::HashMap;
+
+/// Pixels scrolled on a touchpad to switch the layout.
+const SCROLL_STEP: f32 = 20.;
+
+/// Flag of a two letters layout code, most of them are country codes.
+fn flag(code: &str) -> Option<String> {
+    (code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic())).then(|| {
+        code.to_ascii_uppercase()
+            .chars()
+            .filter_map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)))
+            .collect()
+    })
+}
+
+fn format_layout(layout: &str, format: KeyboardLayoutFormat) -> String {
+    let code = xkb::layout_code(layout);
+
+    match (format, code) {
+        (KeyboardLayoutFormat::Full, _) | (_, None) => layout.to_owned(),
+        (KeyboardLayoutFormat::Code, Some(code)) => code.to_ascii_uppercase(),
+        (KeyboardLayoutFormat::Flag, Some(code)) => {
+            flag(code).unwrap_or_else(|| code.to_ascii_uppercase())
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ServiceEvent(ServiceEvent<CompositorService>),
     ChangeLayout,
+    Scroll(ScrollDelta),
+    SelectLayout(usize),
     ConfigReloaded(KeyboardLayoutModuleConfig),
 }
 
 pub struct KeyboardLayout {
     config: KeyboardLayoutModuleConfig,
     service: Option<CompositorService>,
+    scroll_accumulator: f32,
+    /// Layout of each window by address, used with `per_window`
+    window_layouts: HashMap<String, String>,
 }
 
 impl KeyboardLayout {
@@ -25,6 +61,69 @@ impl KeyboardLayout {
         Self {
             config,
             service: None,
+            scroll_accumulator: 0.,
+            window_layouts: HashMap::new(),
+        }
+    }
+
+    fn focused_window(&self) -> Option<String> {
+        self.service
+            .as_ref()?
+            .active_window
+            .as_ref()
+            .map(|window| window.address.clone())
+    }
+
+    /// Records the layout of the focused window, or restores it when the
+    /// focus moved to a window whose layout is known.
+    fn sync_window_layout(&mut self, previous_window: Option<String>) -> Task<Message> {
+        let Some(service) = self.service.as_ref() else {
+            return Task::none();
+        };
+        let Some(window) = self.focused_window() else {
+            return Task::none();
+        };
+
+        if previous_window.as_ref() != Some(&window)
+            && let Some(layout) = self.window_layouts.get(&window)
+            && layout != &service.keyboard_layout
+            && let Some(index) = service.keyboard_layouts.iter().position(|l| l == layout)
+            && let Ok(index) = u8::try_from(index)
+        {
+            return self.switch_layout(LayoutSwitch::Index(index));
+        }
+
+        self.window_layouts
+            .insert(window, service.keyboard_layout.clone());
+
+        Task::none()
+    }
+
+    /// Layout shown on the bar, the one of the focused window with `per_window`
+    /// so it doesn't lag behind while the layout is being restored.
+    fn active_layout(&self) -> Option<&str> {
+        let service = self.service.as_ref()?;
+
+        if self.config.per_window
+            && let Some(layout) = self
+                .focused_window()
+                .and_then(|window| self.window_layouts.get(&window))
+        {
+            Some(layout)
+        } else {
+            Some(&service.keyboard_layout)
+        }
+    }
+
+    fn switch_layout(&mut self, switch: LayoutSwitch) -> Task<Message> {
+        match self.service.as_mut() {
+            Some(service) => service
+                .command(CompositorCommand::SwitchLayout(
+                    self.config.device.clone(),
+                    switch,
+                ))
+                .map(Message::ServiceEvent),
+            None => Task::none(),
         }
     }
 
@@ -34,45 +133,116 @@ impl KeyboardLayout {
                 match event {
                     ServiceEvent::Init(s) => self.service = Some(s),
                     ServiceEvent::Update(e) => {
+                        let previous_window = self.focused_window();
                         if let Some(service) = &mut self.service {
                             service.update(e);
                         }
+
+                        if self.config.per_window {
+                            return self.sync_window_layout(previous_window);
+                        }
                     }
                     _ => {}
                 }
                 Task::none()
             }
-            Message::ChangeLayout => {
-                if let Some(service) = &mut self.service {
-                    return service
-                        .command(CompositorCommand::NextLayout)
-                        .map(Message::ServiceEvent);
+            Message::ChangeLayout => self.switch_layout(LayoutSwitch::Next),
+            Message::Scroll(delta) => {
+                let y = match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    // a touchpad scrolls by small steps
+                    ScrollDelta::Pixels { y, .. } => {
+                        self.scroll_accumulator += y;
+                        if self.scroll_accumulator.abs() < SCROLL_STEP {
+                            return Task::none();
+                        }
+
+                        std::mem::take(&mut self.scroll_accumulator)
+                    }
+                };
+
+                if y > 0. {
+                    self.switch_layout(LayoutSwitch::Previous)
+                } else if y < 0. {
+                    self.switch_layout(LayoutSwitch::Next)
+                } else {
+                    Task::none()
                 }
-                Task::none()
             }
+            Message::SelectLayout(index) => match u8::try_from(index) {
+                Ok(index) => self.switch_layout(LayoutSwitch::Index(index)),
+                Err(_) => Task::none(),
+            },
             Message::ConfigReloaded(new_config) => {
+                if !new_config.per_window {
+                    self.window_layouts.clear();
+                }
                 self.config = new_config;
                 Task::none()
             }
         }
     }
 
-    pub fn view(&self, _: &AshellTheme) -> Option<Element<'_, Message>> {
-        let service = self.service.as_ref()?;
-        let active_layout = &service.keyboard_layout;
+    pub fn view(
+        &self,
+        theme: &AshellTheme,
+        config: Option<&KeyboardLayoutModuleConfig>,
+    ) -> Option<Element<'_, Message>> {
+        let active_layout = self.active_layout()?;
+        let config = config.unwrap_or(&self.config);
 
-        // Fallback to displaying the layout ID/Name if no label config exists
-        let label = match self.config.labels.get(active_layout) {
+        // Fallback to displaying the layout in the configured format if no label config exists
+        let label = match config.labels.get(active_layout) {
             Some(value) => value.to_string(),
-            None => active_layout.clone(),
+            None => format_layout(active_layout, config.format),
         };
 
-        // Returns plain text matching original implementation style.
-        // (Assuming parent container or mouse area handles interactions if any)
-        Some(text(label).into())
+        // clicks are handled by the module button, scrolling cycles the layouts
+        Some(
+            MouseArea::new(theme.module_text("KeyboardLayout", text(label)))
+                .on_scroll(Message::Scroll)
+                .into(),
+        )
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        CompositorService::subscribe().map(Message::ServiceEvent)
+    /// Configured layouts, the active one is highlighted and a click selects one.
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let layouts = self
+            .service
+            .as_ref()
+            .map(|service| service.keyboard_layouts.as_slice())
+            .unwrap_or_default();
+        let active_layout = self.active_layout();
+
+        column!(
+            text("Keyboard layouts").size(theme.font_size.lg),
+            horizontal_rule(1),
+            Column::with_children(layouts.iter().enumerate().map(|(index, layout)| {
+                let label = match self.config.labels.get(layout) {
+                    Some(label) => format!("{label}  {layout}"),
+                    None => layout.clone(),
+                };
+
+                if active_layout == Some(layout.as_str()) {
+                    container(text(label))
+                        .padding([theme.space.xxs, theme.space.sm])
+                        .style(|theme: &Theme| container::Style {
+                            text_color: Some(theme.palette().success),
+                            ..Default::default()
+                        })
+                        .into()
+                } else {
+                    button(text(label))
+                        .on_press(Message::SelectLayout(index))
+                        .padding([theme.space.xxs, theme.space.sm])
+                        .width(Length::Fill)
+                        .style(theme.ghost_button_style())
+                        .into()
+                }
+            }))
+            .spacing(theme.space.xxs),
+        )
+        .spacing(theme.space.xs)
+        .into()
     }
 }
@@ -1,22 +1,32 @@
 use crate::{
+    components::icons::{DynamicIcon, icon},
+    config::KeyboardSubmapModuleConfig,
     services::{ReadOnlyService, ServiceEvent, compositor::CompositorService},
     theme::AshellTheme,
 };
-use iced::{Element, Subscription, widget::text};
+use iced::{
+    Alignment, Element, Theme,
+    widget::{Row, container, text},
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ServiceEvent(ServiceEvent<CompositorService>),
+    ConfigReloaded(KeyboardSubmapModuleConfig),
 }
 
 #[derive(Debug, Clone)]
 pub struct KeyboardSubmap {
+    config: KeyboardSubmapModuleConfig,
     service: Option<CompositorService>,
 }
 
 impl KeyboardSubmap {
-    pub fn default() -> Self {
-        Self { service: None }
+    pub fn new(config: KeyboardSubmapModuleConfig) -> Self {
+        Self {
+            config,
+            service: None,
+        }
     }
 
     pub fn update(&mut self, message: Message) {
@@ -30,20 +40,50 @@ impl KeyboardSubmap {
                 }
                 _ => {}
             },
+            Message::ConfigReloaded(config) => self.config = config,
         }
     }
 
-    pub fn view(&self, _: &AshellTheme) -> Option<Element<'_, Message>> {
-        let submap = self.service.as_ref()?.submap.as_ref()?;
+    pub fn view(&self, theme: &AshellTheme) -> Option<Element<'_, Message>> {
+        let submap = self
+            .service
+            .as_ref()?
+            .submap
+            .as_deref()
+            .filter(|submap| !submap.is_empty());
 
-        if !submap.is_empty() {
-            Some(text(submap).into())
-        } else {
-            None
-        }
-    }
+        let Some(submap) = submap else {
+            // without an inactive label the module takes no space on the bar
+            return self
+                .config
+                .inactive_label
+                .as_ref()
+                .map(|label| theme.module_text("KeyboardSubmap", text(label)).into());
+        };
+
+        let style = self.config.submaps.get(submap);
+        let label = style
+            .and_then(|style| style.label.as_deref())
+            .unwrap_or(submap);
+        let color = style.and_then(|style| style.color);
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        CompositorService::subscribe().map(Message::ServiceEvent)
+        Some(
+            container(
+                Row::new()
+                    .push_maybe(
+                        style
+                            .and_then(|style| style.icon.clone())
+                            .map(|value| icon(DynamicIcon(value))),
+                    )
+                    .push(theme.module_text("KeyboardSubmap", text(label)))
+                    .align_y(Alignment::Center)
+                    .spacing(theme.space.xxs),
+            )
+            .style(move |theme: &Theme| container::Style {
+                text_color: color.map(|color| color.color(theme)),
+                ..Default::default()
+            })
+            .into(),
+        )
     }
 }
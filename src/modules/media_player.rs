@@ -11,7 +11,7 @@ use crate::{
     utils::truncate_text,
 };
 use iced::{
-    Background, Border, Element, Length, Subscription, Task, Theme,
+    Background, Border, Element, Length, Task, Theme,
     alignment::Vertical,
     widget::{Column, column, container, horizontal_rule, row, slider, text},
 };
@@ -44,6 +44,15 @@ impl MediaPlayer {
         }
     }
 
+    /// Title of the first playing track.
+    pub fn now_playing(&self) -> Option<String> {
+        self.service.as_ref()?.iter().find_map(|player| {
+            (player.state == PlaybackStatus::Playing)
+                .then(|| player.metadata.as_ref().map(ToString::to_string))
+                .flatten()
+        })
+    }
+
     pub fn update(&mut self, message: Message) -> Action {
         match message {
             Message::Prev(s) => Action::Command(self.handle_command(s, PlayerCommand::Prev)),
@@ -65,7 +74,10 @@ impl MediaPlayer {
                     }
                     Action::None
                 }
-                ServiceEvent::Error(_) => Action::None,
+                ServiceEvent::Error(_) => {
+                    self.service = None;
+                    Action::None
+                }
             },
             Message::ConfigReloaded(c) => {
                 self.config = c;
@@ -81,7 +93,7 @@ impl MediaPlayer {
                 text("Players").size(theme.font_size.lg),
                 horizontal_rule(1),
                 column(s.iter().map(|d| {
-                    let title = text(self.get_title(d))
+                    let title = text(Self::get_title(&self.config, d))
                         .wrapping(text::Wrapping::WordOrGlyph)
                         .width(Length::Fill);
 
@@ -156,22 +168,31 @@ impl MediaPlayer {
         }
     }
 
-    fn get_title(&self, d: &MprisPlayerData) -> String {
+    fn get_title(config: &MediaPlayerModuleConfig, d: &MprisPlayerData) -> String {
         match &d.metadata {
-            Some(m) => truncate_text(&m.to_string(), self.config.max_title_length),
+            Some(m) => truncate_text(&m.to_string(), config.max_title_length),
             None => "No Title".to_string(),
         }
     }
 
-    pub fn view(&'_ self, theme: &AshellTheme) -> Option<Element<'_, Message>> {
+    pub fn view(
+        &'_ self,
+        theme: &AshellTheme,
+        config: Option<&MediaPlayerModuleConfig>,
+    ) -> Option<Element<'_, Message>> {
+        let config = config.unwrap_or(&self.config);
+
         self.service.as_ref().and_then(|s| {
             s.first().map(|player| {
                 let title =
-                    (self.config.indicator_format == MediaPlayerFormat::IconAndTitle).then(|| {
+                    (config.indicator_format == MediaPlayerFormat::IconAndTitle).then(|| {
                         container(
-                            text(self.get_title(player))
-                                .wrapping(text::Wrapping::None)
-                                .size(theme.font_size.sm),
+                            theme.module_text(
+                                "MediaPlayer",
+                                text(Self::get_title(config, player))
+                                    .wrapping(text::Wrapping::None)
+                                    .size(theme.font_size.sm),
+                            ),
                         )
                         .clip(true)
                     });
@@ -184,8 +205,4 @@ impl MediaPlayer {
             })
         })
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        MprisPlayerService::subscribe().map(Message::Event)
-    }
 }
@@ -1,24 +1,49 @@
 use crate::{
     app::{App, Message},
-    config::{AppearanceStyle, ModuleDef, ModuleName},
+    components::icons::{StaticIcon, icon},
+    config::{
+        AppearanceStyle, CustomModuleDef, ModuleDef, ModuleName, Modules, PrivacyClickAction,
+    },
+    hover_area::hover_area,
     menu::MenuType,
-    position_button::position_button,
+    modules::{
+        custom_module::Custom, peripherals::Peripherals, privacy::Privacy, updates::Updates,
+    },
+    position_button::{PositionButton, position_button},
     theme::AshellTheme,
+    utils::{
+        state::{read_state, write_state},
+        truncate_text,
+    },
+    watchdog::Guarded,
 };
 use iced::{
-    Alignment, Border, Color, Element, Length, Subscription,
-    widget::{Row, container, row},
+    Alignment, Border, Color, Element, Length, Subscription, Theme,
+    widget::{Column, Container, Row, container, row, text},
     window::Id,
 };
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+};
 
 pub mod clock;
 pub mod custom_module;
+pub mod feeds;
+pub mod git_notifications;
 pub mod keyboard_layout;
 pub mod keyboard_submap;
 pub mod media_player;
+pub mod peripherals;
 pub mod privacy;
+pub mod provider;
+pub mod recording;
 pub mod settings;
 pub mod system_info;
+pub mod taskbar;
+pub mod tasks;
 pub mod tray;
 pub mod updates;
 pub mod window_title;
@@ -28,51 +53,325 @@ pub mod workspaces;
 pub enum OnModulePress {
     Action(Box<Message>),
     ToggleMenu(MenuType),
+    /// Runs the action on a click and toggles the menu on a right click
+    ActionWithMenu(Box<Message>, MenuType),
+}
+
+const HIDDEN_MODULES_STATE_FILE: &str = "hidden_modules.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HiddenModulesState {
+    hidden: Vec<ModuleName>,
+}
+
+/// Section of the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleSection {
+    Left,
+    Center,
+    Right,
+}
+
+/// Custom module added at runtime through the IPC socket.
+#[derive(Debug, Clone)]
+pub struct InjectedModule {
+    pub section: ModuleSection,
+    /// Position in the section, after the modules of the config when missing
+    pub index: Option<usize>,
+    pub def: ModuleDef,
+    pub config: CustomModuleDef,
+}
+
+/// Modules hidden at runtime, they are kept hidden across restarts.
+pub fn hidden_modules() -> Vec<ModuleName> {
+    read_state::<HiddenModulesState>(HIDDEN_MODULES_STATE_FILE).hidden
+}
+
+/// Modules of the layouts, without duplicates.
+pub fn layout_modules<'a>(
+    layouts: impl Iterator<Item = &'a Modules>,
+    injected: &[InjectedModule],
+) -> Vec<ModuleName> {
+    let mut modules = Vec::new();
+
+    for layout in layouts {
+        for module_def in layout
+            .left
+            .iter()
+            .chain(&layout.center)
+            .chain(&layout.right)
+            .chain(injected.iter().map(|module| &module.def))
+        {
+            let names = match module_def {
+                ModuleDef::Single(module) => std::slice::from_ref(module),
+                ModuleDef::Group(group) => group.as_slice(),
+            };
+
+            for name in names {
+                if !modules.contains(name) {
+                    modules.push(name.clone());
+                }
+            }
+        }
+    }
+
+    modules
 }
 
 impl App {
+    /// Modules of the layout of every bar, without duplicates.
+    pub fn layout_modules(&self) -> Vec<ModuleName> {
+        layout_modules(
+            std::iter::once(&self.general_config.modules).chain(
+                self.general_config
+                    .bars
+                    .values()
+                    .flatten()
+                    .map(|bar| &bar.modules),
+            ),
+            &self.injected_modules,
+        )
+    }
+
+    /// Adds a custom module to the layout of every bar, until it's removed.
+    pub fn inject_module(
+        &mut self,
+        section: ModuleSection,
+        index: Option<usize>,
+        config: CustomModuleDef,
+    ) -> Result<(), String> {
+        if self.custom.contains_key(&config.name) {
+            return Err(format!("module `{}` already exists", config.name));
+        }
+
+        info!("Adding module {} to the {section:?} section", config.name);
+
+        self.custom
+            .insert(config.name.clone(), Custom::new(config.clone()));
+        self.injected_modules.push(InjectedModule {
+            section,
+            index,
+            def: ModuleDef::Single(ModuleName::Custom(config.name.clone())),
+            config,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_injected_module(&mut self, name: &str) -> Result<(), String> {
+        let Some(position) = self
+            .injected_modules
+            .iter()
+            .position(|module| module.config.name == name)
+        else {
+            return Err(format!("module `{name}` wasn't added at runtime"));
+        };
+
+        info!("Removing module {name}");
+
+        self.injected_modules.remove(position);
+        self.custom.remove(name);
+
+        Ok(())
+    }
+
+    pub fn set_module_visible(&mut self, module: ModuleName, visible: bool) {
+        let hidden = self.hidden_modules.contains(&module);
+
+        if visible && hidden {
+            info!("Showing module {}", module.name());
+            self.hidden_modules.retain(|name| *name != module);
+        } else if !visible && !hidden {
+            info!("Hiding module {}", module.name());
+            self.hidden_modules.push(module);
+        } else {
+            return;
+        }
+
+        write_state(
+            HIDDEN_MODULES_STATE_FILE,
+            &HiddenModulesState {
+                hidden: self.hidden_modules.clone(),
+            },
+        );
+    }
+
+    /// Runs the update or the view of a module, a panic disables the module
+    /// instead of taking the whole bar down.
+    pub fn catch_module<T>(&self, module: &ModuleName, f: impl FnOnce() -> T) -> Option<T> {
+        if self.crash_message(module).is_some() {
+            return None;
+        }
+
+        panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(|payload| self.record_crash(module, payload))
+            .ok()
+    }
+
+    /// Disables a module after a panic, until it is restarted.
+    pub fn record_crash(&self, module: &ModuleName, payload: Box<dyn Any + Send>) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned());
+        error!(
+            "Module {} crashed and was disabled: {message}",
+            module.name()
+        );
+
+        self.crashed_modules
+            .borrow_mut()
+            .push((module.clone(), message));
+    }
+
+    pub fn crash_message(&self, module: &ModuleName) -> Option<String> {
+        self.crashed_modules
+            .borrow()
+            .iter()
+            .find(|(name, _)| name == module)
+            .map(|(_, message)| message.clone())
+    }
+
+    /// Enables a crashed module again, with the state it had.
+    pub fn restart_module(&mut self, module: &ModuleName) {
+        info!("Restarting module {}", module.name());
+        self.crashed_modules
+            .get_mut()
+            .retain(|(name, _)| name != module);
+    }
+
+    /// Chip shown in place of a crashed module, a click restarts it.
+    fn crash_view<'a>(&self, module: &ModuleName, message: String) -> Element<'a, Message> {
+        let radius = self.theme.radius.sm;
+
+        container(
+            row!(
+                icon(StaticIcon::Alert),
+                text(truncate_text(
+                    &format!("{} crashed: {message}", module.name()),
+                    60
+                ))
+                .size(self.theme.font_size.sm)
+                .wrapping(text::Wrapping::None),
+            )
+            .align_y(Alignment::Center)
+            .spacing(self.theme.space.xxs),
+        )
+        .padding([2, self.theme.space.xs])
+        .style(move |theme: &Theme| container::Style {
+            background: Some(theme.extended_palette().danger.base.color.into()),
+            text_color: Some(theme.extended_palette().danger.base.text),
+            border: Border::default().rounded(radius),
+            ..Default::default()
+        })
+        .into()
+    }
+
+    pub fn toggle_module(&mut self, module: ModuleName) {
+        let visible = self.hidden_modules.contains(&module);
+
+        self.set_module_visible(module, visible);
+    }
+
     pub fn modules_section<'a>(
         &'a self,
         id: Id,
         theme: &'a AshellTheme,
     ) -> [Element<'a, Message>; 3] {
+        let modules = self
+            .bar_config(id)
+            .map_or(&self.general_config.modules, |bar| &bar.modules);
+
         [
-            &self.general_config.modules.left,
-            &self.general_config.modules.center,
-            &self.general_config.modules.right,
+            (ModuleSection::Left, &modules.left),
+            (ModuleSection::Center, &modules.center),
+            (ModuleSection::Right, &modules.right),
         ]
-        .map(|modules_def| {
-            let mut row = row!()
-                .height(Length::Shrink)
-                .align_y(Alignment::Center)
-                .spacing(self.theme.space.xxs);
+        .map(|(section, modules_def)| {
+            let mut modules_def = modules_def.iter().collect::<Vec<_>>();
+            for module in self
+                .injected_modules
+                .iter()
+                .filter(|module| module.section == section)
+            {
+                let index = module
+                    .index
+                    .map_or(modules_def.len(), |index| index.min(modules_def.len()));
+                modules_def.insert(index, &module.def);
+            }
 
-            for module_def in modules_def {
-                row = row.push_maybe(match module_def {
+            let modules = modules_def
+                .into_iter()
+                .filter_map(|module_def| match module_def {
                     // life parsing of string to module
                     ModuleDef::Single(module) => self.single_module_wrapper(id, theme, module),
                     ModuleDef::Group(group) => self.group_module_wrapper(id, theme, group),
-                });
-            }
+                })
+                .collect();
 
-            row.into()
+            self.modules_stack(id, modules, self.theme.space.xxs)
         })
     }
 
-    pub fn modules_subscriptions(&self, modules_def: &[ModuleDef]) -> Vec<Subscription<Message>> {
-        modules_def
-            .iter()
-            .flat_map(|module_def| match module_def {
-                ModuleDef::Single(module) => {
-                    vec![self.get_module_subscription(module)]
-                }
-                ModuleDef::Group(group) => group
-                    .iter()
-                    .map(|module| self.get_module_subscription(module))
-                    .collect(),
-            })
-            .flatten()
-            .collect()
+    /// Lays out the modules along the bar, from top to bottom on a vertical bar.
+    fn modules_stack<'a>(
+        &self,
+        id: Id,
+        modules: Vec<Element<'a, Message>>,
+        spacing: u16,
+    ) -> Element<'a, Message> {
+        if self.bar_position(id).is_vertical() {
+            Column::with_children(modules)
+                .width(Length::Shrink)
+                .align_x(Alignment::Center)
+                .spacing(spacing)
+                .into()
+        } else {
+            Row::with_children(modules)
+                .height(Length::Shrink)
+                .align_y(Alignment::Center)
+                .spacing(spacing)
+                .into()
+        }
+    }
+
+    /// Content of a module, filling the thickness of the bar.
+    ///
+    /// Modules are clipped to the bar thickness, so on a vertical bar
+    /// only their icon is visible when their text is too long.
+    fn module_content<'a>(&self, id: Id, content: Element<'a, Message>) -> Container<'a, Message> {
+        let content = container(content).clip(true);
+
+        if self.bar_position(id).is_vertical() {
+            content.width(Length::Fill).align_x(Alignment::Center)
+        } else {
+            content.height(Length::Fill).align_y(Alignment::Center)
+        }
+    }
+
+    fn module_padding(&self, id: Id) -> [u16; 2] {
+        if self.bar_position(id).is_vertical() {
+            [self.theme.space.xs, 2]
+        } else {
+            [2, self.theme.space.xs]
+        }
+    }
+
+    fn module_button<'a>(
+        &self,
+        id: Id,
+        content: Element<'a, Message>,
+    ) -> PositionButton<'a, Message> {
+        let button =
+            position_button(self.module_content(id, content)).padding(self.module_padding(id));
+
+        if self.bar_position(id).is_vertical() {
+            button.width(Length::Fill)
+        } else {
+            button.height(Length::Fill)
+        }
     }
 
     fn single_module_wrapper<'a>(
@@ -83,57 +382,57 @@ impl App {
     ) -> Option<Element<'a, Message>> {
         let module = self.get_module_view(id, module_name);
 
-        module.map(|(content, action)| match action {
-            Some(action) => {
-                let button = position_button(
-                    container(content)
-                        .align_y(Alignment::Center)
-                        .height(Length::Fill)
-                        .clip(true),
-                )
-                .padding([2, self.theme.space.xs])
-                .height(Length::Fill)
-                .style(theme.module_button_style(false));
-
-                match action {
-                    OnModulePress::Action(action) => button.on_press(*action),
-                    OnModulePress::ToggleMenu(menu_type) => {
-                        button.on_press_with_position(move |button_ui_ref| {
-                            Message::ToggleMenu(menu_type.clone(), id, button_ui_ref)
-                        })
+        module
+            .map(|(content, action)| match action {
+                Some(action) => {
+                    let button = self
+                        .module_button(id, content)
+                        .hover_transition(theme.animations.hover)
+                        .style(theme.module_button_style(false));
+
+                    match action {
+                        OnModulePress::Action(action) => button.on_press(*action),
+                        OnModulePress::ToggleMenu(menu_type) => {
+                            button.on_press_with_position(move |button_ui_ref| {
+                                Message::ToggleMenu(menu_type.clone(), id, button_ui_ref)
+                            })
+                        }
+                        OnModulePress::ActionWithMenu(action, menu_type) => button
+                            .on_press(*action)
+                            .on_right_press_with_position(move |button_ui_ref| {
+                                Message::ToggleMenu(menu_type.clone(), id, button_ui_ref)
+                            }),
                     }
+                    .into()
                 }
-                .into()
-            }
-            _ => {
-                let container = container(content)
-                    .padding([2, self.theme.space.xs])
-                    .height(Length::Fill)
-                    .align_y(Alignment::Center)
-                    .clip(true);
+                _ => {
+                    let container = self
+                        .module_content(id, content)
+                        .padding(self.module_padding(id));
 
-                match self.theme.bar_style {
-                    AppearanceStyle::Solid | AppearanceStyle::Gradient => container.into(),
-                    AppearanceStyle::Islands => container
-                        .style(|theme| container::Style {
-                            background: Some(
-                                theme
-                                    .palette()
-                                    .background
-                                    .scale_alpha(self.theme.opacity)
-                                    .into(),
-                            ),
-                            border: Border {
-                                width: 0.0,
-                                radius: self.theme.radius.lg.into(),
-                                color: Color::TRANSPARENT,
-                            },
-                            ..container::Style::default()
-                        })
-                        .into(),
+                    match self.theme.bar_style {
+                        AppearanceStyle::Solid | AppearanceStyle::Gradient => container.into(),
+                        AppearanceStyle::Islands => container
+                            .style(|theme| container::Style {
+                                background: Some(
+                                    theme
+                                        .palette()
+                                        .background
+                                        .scale_alpha(self.theme.opacity)
+                                        .into(),
+                                ),
+                                border: Border {
+                                    width: 0.0,
+                                    radius: self.theme.radius.lg.into(),
+                                    color: Color::TRANSPARENT,
+                                },
+                                ..container::Style::default()
+                            })
+                            .into(),
+                    }
                 }
-            }
-        })
+            })
+            .map(|module| self.with_tooltip(id, module_name, module))
     }
 
     fn group_module_wrapper<'a>(
@@ -144,49 +443,60 @@ impl App {
     ) -> Option<Element<'a, Message>> {
         let modules = group
             .iter()
-            .filter_map(|module| self.get_module_view(id, module))
+            .filter_map(|module| {
+                self.get_module_view(id, module)
+                    .map(|(content, action)| (module, content, action))
+            })
             .collect::<Vec<_>>();
 
         if modules.is_empty() {
             None
         } else {
             Some({
-                let group = Row::with_children(
+                let group = self.modules_stack(
+                    id,
                     modules
                         .into_iter()
-                        .map(|(content, action)| match action {
-                            Some(action) => {
-                                let button = position_button(
-                                    container(content)
-                                        .align_y(Alignment::Center)
-                                        .height(Length::Fill)
-                                        .clip(true),
-                                )
-                                .padding([2, self.theme.space.xs])
-                                .height(Length::Fill)
-                                .style(theme.module_button_style(true));
-
-                                match action {
-                                    OnModulePress::Action(action) => button.on_press(*action),
-                                    OnModulePress::ToggleMenu(menu_type) => button
-                                        .on_press_with_position(move |button_ui_ref| {
-                                            Message::ToggleMenu(
-                                                menu_type.clone(),
-                                                id,
-                                                button_ui_ref,
-                                            )
-                                        }),
+                        .map(|(module_name, content, action)| {
+                            let module = match action {
+                                Some(action) => {
+                                    let button = self
+                                        .module_button(id, content)
+                                        .hover_transition(theme.animations.hover)
+                                        .style(theme.module_button_style(true));
+
+                                    match action {
+                                        OnModulePress::Action(action) => button.on_press(*action),
+                                        OnModulePress::ToggleMenu(menu_type) => button
+                                            .on_press_with_position(move |button_ui_ref| {
+                                                Message::ToggleMenu(
+                                                    menu_type.clone(),
+                                                    id,
+                                                    button_ui_ref,
+                                                )
+                                            }),
+                                        OnModulePress::ActionWithMenu(action, menu_type) => button
+                                            .on_press(*action)
+                                            .on_right_press_with_position(move |button_ui_ref| {
+                                                Message::ToggleMenu(
+                                                    menu_type.clone(),
+                                                    id,
+                                                    button_ui_ref,
+                                                )
+                                            }),
+                                    }
+                                    .into()
                                 }
-                                .into()
-                            }
-                            _ => container(content)
-                                .padding([2, self.theme.space.xs])
-                                .height(Length::Fill)
-                                .align_y(Alignment::Center)
-                                .clip(true)
-                                .into(),
+                                _ => self
+                                    .module_content(id, content)
+                                    .padding(self.module_padding(id))
+                                    .into(),
+                            };
+
+                            self.with_tooltip(id, module_name, module)
                         })
                         .collect::<Vec<_>>(),
+                    0,
                 );
 
                 match self.theme.bar_style {
@@ -213,88 +523,319 @@ impl App {
         }
     }
 
+    /// Text shown while hovering the module, the `tooltip_cmd`
+    /// of the custom modules is run by the caller.
+    pub fn module_tooltip(&self, module_name: &ModuleName) -> Option<String> {
+        match module_name {
+            ModuleName::Updates => self.updates.as_ref().map(Updates::tooltip),
+            ModuleName::Privacy => self.privacy.as_ref().and_then(Privacy::tooltip),
+            ModuleName::Peripherals => self.peripherals.as_ref().and_then(Peripherals::tooltip),
+            ModuleName::Custom(name) => match self.custom.get(name) {
+                Some(custom) => custom.tooltip(),
+                None => self.providers.get(name)?.tooltip.clone(),
+            },
+            _ => None,
+        }
+    }
+
+    fn has_tooltip(&self, module_name: &ModuleName) -> bool {
+        match module_name {
+            ModuleName::Updates => self.updates.is_some(),
+            ModuleName::Privacy => self.privacy.is_some(),
+            ModuleName::Peripherals => self.peripherals.is_some(),
+            ModuleName::Custom(name) => match self.custom.get(name) {
+                Some(custom) => custom.has_tooltip(),
+                None => self
+                    .providers
+                    .get(name)
+                    .is_some_and(|widget| widget.tooltip.is_some()),
+            },
+            _ => false,
+        }
+    }
+
+    /// Reports the hover of the modules with a tooltip.
+    fn with_tooltip<'a>(
+        &self,
+        id: Id,
+        module_name: &ModuleName,
+        module: Element<'a, Message>,
+    ) -> Element<'a, Message> {
+        if !self.theme.tooltip.enabled || !self.has_tooltip(module_name) {
+            return module;
+        }
+
+        let module_name = module_name.clone();
+        hover_area(
+            module,
+            move |button_ui_ref| Message::TooltipHover(id, module_name.clone(), button_ui_ref),
+            Message::TooltipLeave(id),
+        )
+        .into()
+    }
+
     fn get_module_view<'a>(
         &'a self,
         id: Id,
         module_name: &'a ModuleName,
     ) -> Option<(Element<'a, Message>, Option<OnModulePress>)> {
+        if self.hidden_modules.contains(module_name) {
+            return None;
+        }
+
+        // custom modules hidden while their listen command is failing
+        if let ModuleName::Custom(name) = module_name
+            && self.custom.get(name).is_some_and(Custom::is_hidden)
+        {
+            return None;
+        }
+
+        if let Some(message) = self.crash_message(module_name) {
+            return Some((
+                self.crash_view(module_name, message),
+                Some(OnModulePress::Action(Box::new(Message::RestartModule(
+                    module_name.clone(),
+                )))),
+            ));
+        }
+
+        self.catch_module(module_name, || self.module_view(id, module_name))
+            .flatten()
+    }
+
+    fn module_view<'a>(
+        &'a self,
+        id: Id,
+        module_name: &'a ModuleName,
+    ) -> Option<(Element<'a, Message>, Option<OnModulePress>)> {
+        let bar_config = self.bar_config(id);
+
         match module_name {
-            ModuleName::Custom(name) => self.custom.get(name).map(|custom| {
-                let action = match custom.module_type() {
-                    crate::config::CustomModuleType::Text => None,
-                    crate::config::CustomModuleType::Button => {
-                        Some(OnModulePress::Action(Box::new(Message::Custom(
-                            name.clone(),
-                            custom_module::Message::LaunchCommand,
-                        ))))
-                    }
-                };
-                (
-                    custom
-                        .view(&self.theme)
-                        .map(|msg| Message::Custom(name.clone(), msg)),
-                    action,
-                )
-            }),
+            ModuleName::Custom(name) => self
+                .custom
+                .get(name)
+                .map(|custom| {
+                    let action = match custom.module_type() {
+                        _ if custom.menu_cmd().is_some() => {
+                            Some(OnModulePress::ToggleMenu(MenuType::Custom(name.clone())))
+                        }
+                        crate::config::CustomModuleType::Text => None,
+                        crate::config::CustomModuleType::Button => {
+                            Some(OnModulePress::Action(Box::new(Message::Custom(
+                                name.clone(),
+                                custom_module::Message::LaunchCommand,
+                            ))))
+                        }
+                    };
+                    (
+                        custom
+                            .view(&self.theme)
+                            .map(|msg| Message::Custom(name.clone(), msg)),
+                        action,
+                    )
+                })
+                .or_else(|| {
+                    // modules registered by external programs through the IPC socket
+                    let widget = self.providers.get(name)?;
+                    let action = if !widget.menu.is_empty() {
+                        Some(OnModulePress::ToggleMenu(MenuType::Provider(name.clone())))
+                    } else {
+                        widget.on_click.as_ref().map(|_| {
+                            OnModulePress::Action(Box::new(Message::Provider(
+                                provider::Message::Click(name.clone()),
+                            )))
+                        })
+                    };
+
+                    self.providers
+                        .view(&self.theme, name)
+                        .map(|view| (view.map(Message::Provider), action))
+                }),
             ModuleName::Updates => self.updates.as_ref().map(|updates| {
                 (
                     updates.view(&self.theme).map(Message::Updates),
                     Some(OnModulePress::ToggleMenu(MenuType::Updates)),
                 )
             }),
-            ModuleName::Workspaces => Some((
-                self.workspaces
-                    .view(id, &self.theme, &self.outputs)
-                    .map(Message::Workspaces),
-                None,
-            )),
-            ModuleName::WindowTitle => self.window_title.get_value().map(|title| {
+            ModuleName::Feeds => self.feeds.as_ref().map(|feeds| {
                 (
-                    self.window_title
-                        .view(&self.theme, title)
-                        .map(Message::WindowTitle),
+                    feeds.view(&self.theme).map(Message::Feeds),
+                    Some(OnModulePress::ToggleMenu(MenuType::Feeds)),
+                )
+            }),
+            ModuleName::GitNotifications => {
+                self.git_notifications.as_ref().map(|git_notifications| {
+                    (
+                        git_notifications
+                            .view(&self.theme)
+                            .map(Message::GitNotifications),
+                        Some(OnModulePress::ToggleMenu(MenuType::GitNotifications)),
+                    )
+                })
+            }
+            ModuleName::Tasks => self.tasks.as_ref().map(|tasks| {
+                (
+                    tasks.view(&self.theme).map(Message::Tasks),
+                    Some(OnModulePress::ToggleMenu(MenuType::Tasks)),
+                )
+            }),
+            ModuleName::Workspaces => self.workspaces.as_ref().map(|workspaces| {
+                (
+                    workspaces
+                        .view(id, &self.theme, &self.outputs)
+                        .map(Message::Workspaces),
                     None,
                 )
             }),
-            ModuleName::SystemInfo => Some((
-                self.system_info.view(&self.theme).map(Message::SystemInfo),
-                Some(OnModulePress::ToggleMenu(MenuType::SystemInfo)),
-            )),
-            ModuleName::KeyboardLayout => self.keyboard_layout.view(&self.theme).map(|view| {
+            ModuleName::WindowTitle => self.window_title.as_ref().and_then(|window_title| {
+                window_title
+                    .get_value(bar_config.map(|bar| &bar.window_title))
+                    .map(|title| {
+                        (
+                            window_title
+                                .view(&self.theme, title)
+                                .map(Message::WindowTitle),
+                            None,
+                        )
+                    })
+            }),
+            ModuleName::Taskbar => self.taskbar.as_ref().map(|taskbar| {
                 (
-                    view.map(Message::KeyboardLayout),
-                    Some(OnModulePress::Action(Box::new(Message::KeyboardLayout(
-                        keyboard_layout::Message::ChangeLayout,
-                    )))),
+                    taskbar
+                        .view(&self.theme, self.outputs.get_monitor_name(id))
+                        .map(Message::Taskbar),
+                    None,
                 )
             }),
+            ModuleName::SystemInfo => self.system_info.as_ref().map(|system_info| {
+                (
+                    system_info
+                        .view(&self.theme, bar_config.map(|bar| &bar.system_info))
+                        .map(Message::SystemInfo),
+                    Some(OnModulePress::ToggleMenu(MenuType::SystemInfo)),
+                )
+            }),
+            ModuleName::KeyboardLayout => self
+                .keyboard_layout
+                .as_ref()
+                .and_then(|keyboard_layout| {
+                    keyboard_layout.view(&self.theme, bar_config.map(|bar| &bar.keyboard_layout))
+                })
+                .map(|view| {
+                    (
+                        view.map(Message::KeyboardLayout),
+                        Some(OnModulePress::ActionWithMenu(
+                            Box::new(Message::KeyboardLayout(
+                                keyboard_layout::Message::ChangeLayout,
+                            )),
+                            MenuType::KeyboardLayout,
+                        )),
+                    )
+                }),
             ModuleName::KeyboardSubmap => self
                 .keyboard_submap
-                .view(&self.theme)
+                .as_ref()
+                .and_then(|keyboard_submap| keyboard_submap.view(&self.theme))
                 .map(|view| (view.map(Message::KeyboardSubmap), None)),
             ModuleName::Tray => self
                 .tray
-                .view(id, &self.theme)
+                .as_ref()
+                .and_then(|tray| tray.view(id, &self.theme))
                 .map(|view| (view.map(Message::Tray), None)),
-            ModuleName::Clock => Some((self.clock.view(&self.theme).map(Message::Clock), None)),
-            ModuleName::Privacy => self
-                .privacy
-                .view(&self.theme)
-                .map(|view| (view.map(Message::Privacy), None)),
-            ModuleName::MediaPlayer => self.media_player.view(&self.theme).map(|view| {
+            ModuleName::Clock => self.clock.as_ref().map(|clock| {
                 (
-                    view.map(Message::MediaPlayer),
-                    Some(OnModulePress::ToggleMenu(MenuType::MediaPlayer)),
+                    clock
+                        .view(&self.theme, bar_config.map(|bar| &bar.clock))
+                        .map(Message::Clock),
+                    None,
                 )
             }),
-            ModuleName::Settings => Some((
-                self.settings.view(&self.theme).map(Message::Settings),
-                Some(OnModulePress::ToggleMenu(MenuType::Settings)),
+            ModuleName::Privacy => self.privacy.as_ref().and_then(|privacy| {
+                privacy.view(&self.theme).map(|view| {
+                    (
+                        view.map(Message::Privacy),
+                        Some(match privacy.config.click_action {
+                            PrivacyClickAction::Menu => {
+                                OnModulePress::ToggleMenu(MenuType::Privacy)
+                            }
+                            PrivacyClickAction::ToggleMic => OnModulePress::Action(Box::new(
+                                Message::Privacy(privacy::Message::ToggleDefaultMute),
+                            )),
+                        }),
+                    )
+                })
+            }),
+            ModuleName::Peripherals => self
+                .peripherals
+                .as_ref()
+                .and_then(|peripherals| peripherals.view(&self.theme))
+                .map(|view| {
+                    (
+                        view.map(Message::Peripherals),
+                        Some(OnModulePress::ToggleMenu(MenuType::Peripherals)),
+                    )
+                }),
+            ModuleName::Recording => self
+                .recording
+                .as_ref()
+                .and_then(|recording| recording.view(&self.theme))
+                .map(|view| {
+                    (
+                        view.map(Message::Recording),
+                        Some(OnModulePress::ToggleMenu(MenuType::Recording)),
+                    )
+                }),
+            ModuleName::Notifications => Some((
+                self.toasts.module_view(&self.theme).map(Message::Toasts),
+                Some(OnModulePress::ToggleMenu(MenuType::Notifications)),
             )),
+            ModuleName::MediaPlayer => self
+                .media_player
+                .as_ref()
+                .and_then(|media_player| {
+                    media_player.view(&self.theme, bar_config.map(|bar| &bar.media_player))
+                })
+                .map(|view| {
+                    (
+                        view.map(Message::MediaPlayer),
+                        Some(OnModulePress::ToggleMenu(MenuType::MediaPlayer)),
+                    )
+                }),
+            ModuleName::Settings => self.settings.as_ref().map(|settings| {
+                (
+                    settings.view(&self.theme).map(Message::Settings),
+                    Some(OnModulePress::ToggleMenu(MenuType::Settings)),
+                )
+            }),
         }
     }
 
-    fn get_module_subscription(&self, module_name: &ModuleName) -> Option<Subscription<Message>> {
+    pub fn modules_subscriptions<'a>(
+        &self,
+        modules_def: impl IntoIterator<Item = &'a ModuleDef>,
+    ) -> Vec<Subscription<Message>> {
+        modules_def
+            .into_iter()
+            .flat_map(|module_def| match module_def {
+                ModuleDef::Single(module) => vec![module],
+                ModuleDef::Group(group) => group.iter().collect(),
+            })
+            .filter_map(|module| {
+                self.get_module_subscription(module)
+                    .map(|subscription| self.guard(Guarded::Module(module.clone()), subscription))
+            })
+            .collect()
+    }
+
+    pub fn get_module_subscription(
+        &self,
+        module_name: &ModuleName,
+    ) -> Option<Subscription<Message>> {
+        // restarted with the module
+        if self.crash_message(module_name).is_some() {
+            return None;
+        }
+
         match module_name {
             ModuleName::Custom(name) => self.custom.get(name).map(|custom| {
                 custom
@@ -305,30 +846,85 @@ impl App {
                 .updates
                 .as_ref()
                 .map(|updates| updates.subscription().map(Message::Updates)),
-            ModuleName::Workspaces => Some(self.workspaces.subscription().map(Message::Workspaces)),
-            ModuleName::WindowTitle => {
-                Some(self.window_title.subscription().map(Message::WindowTitle))
-            }
-            ModuleName::SystemInfo => {
-                Some(self.system_info.subscription().map(Message::SystemInfo))
-            }
-            ModuleName::KeyboardLayout => Some(
-                self.keyboard_layout
-                    .subscription()
-                    .map(Message::KeyboardLayout),
-            ),
-            ModuleName::KeyboardSubmap => Some(
-                self.keyboard_submap
-                    .subscription()
-                    .map(Message::KeyboardSubmap),
-            ),
-            ModuleName::Tray => Some(self.tray.subscription().map(Message::Tray)),
-            ModuleName::Clock => Some(self.clock.subscription().map(Message::Clock)),
-            ModuleName::Privacy => Some(self.privacy.subscription().map(Message::Privacy)),
-            ModuleName::MediaPlayer => {
-                Some(self.media_player.subscription().map(Message::MediaPlayer))
+            ModuleName::Feeds => self
+                .feeds
+                .as_ref()
+                .map(|feeds| feeds.subscription().map(Message::Feeds)),
+            ModuleName::GitNotifications => {
+                self.git_notifications.as_ref().map(|git_notifications| {
+                    git_notifications
+                        .subscription()
+                        .map(Message::GitNotifications)
+                })
             }
-            ModuleName::Settings => Some(self.settings.subscription().map(Message::Settings)),
+            ModuleName::Tasks => self
+                .tasks
+                .as_ref()
+                .map(|tasks| tasks.subscription().map(Message::Tasks)),
+            ModuleName::SystemInfo => self
+                .system_info
+                .as_ref()
+                .filter(|_| !self.scheduler.paused())
+                .map(|system_info| {
+                    let menu_open = self
+                        .outputs
+                        .bars()
+                        .any(|(_, menu)| menu == Some(&MenuType::SystemInfo));
+                    let bars = self
+                        .general_config
+                        .bars
+                        .values()
+                        .flatten()
+                        .map(|bar| &bar.system_info);
+
+                    system_info
+                        .subscription(menu_open, bars)
+                        .map(Message::SystemInfo)
+                }),
+            ModuleName::Tray => self
+                .tray
+                .as_ref()
+                .map(|tray| tray.subscription().map(Message::Tray)),
+            ModuleName::Clock => self
+                .clock
+                .as_ref()
+                .filter(|_| !self.scheduler.paused())
+                .map(|clock| {
+                    let bars = self
+                        .general_config
+                        .bars
+                        .values()
+                        .flatten()
+                        .map(|bar| &bar.clock);
+
+                    clock.subscription(bars).map(Message::Clock)
+                }),
+            ModuleName::Privacy => self.privacy.as_ref().map(|privacy| {
+                let menu_open = self
+                    .outputs
+                    .bars()
+                    .any(|(_, menu)| menu == Some(&MenuType::Privacy));
+
+                privacy.subscription(menu_open).map(Message::Privacy)
+            }),
+            ModuleName::Recording => self
+                .recording
+                .as_ref()
+                .map(|recording| recording.subscription().map(Message::Recording)),
+            // the daemon runs without the module as well
+            ModuleName::Notifications => None,
+            // fed by the shared backends of the app
+            ModuleName::Workspaces
+            | ModuleName::WindowTitle
+            | ModuleName::Taskbar
+            | ModuleName::KeyboardLayout
+            | ModuleName::KeyboardSubmap
+            | ModuleName::Peripherals
+            | ModuleName::MediaPlayer => None,
+            ModuleName::Settings => self
+                .settings
+                .as_ref()
+                .map(|settings| settings.subscription().map(Message::Settings)),
         }
     }
 }
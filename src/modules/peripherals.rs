@@ -0,0 +1,185 @@
+use crate::{
+    components::icons::icon,
+    config::PeripheralsModuleConfig,
+    services::{
+        ReadOnlyService, ServiceEvent,
+        upower::{BatteryStatus, Peripheral, UPowerService},
+    },
+    theme::AshellTheme,
+    utils::{launcher::execute_command_with_env, notification::send_notification},
+};
+use iced::{
+    Alignment, Element, Length, Theme,
+    widget::{Column, Row, column, container, horizontal_rule, row, text},
+};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(ServiceEvent<UPowerService>),
+    ConfigReloaded(PeripheralsModuleConfig),
+}
+
+pub struct Peripherals {
+    config: PeripheralsModuleConfig,
+    service: Option<UPowerService>,
+    /// Peripherals already reported as low, until they are charged again
+    low: HashSet<String>,
+}
+
+fn is_charging(peripheral: &Peripheral) -> bool {
+    matches!(peripheral.data.status, BatteryStatus::Charging(_))
+}
+
+impl Peripherals {
+    pub fn new(config: PeripheralsModuleConfig) -> Self {
+        Self {
+            config,
+            service: None,
+            low: HashSet::new(),
+        }
+    }
+
+    fn is_low(&self, peripheral: &Peripheral) -> bool {
+        !is_charging(peripheral) && peripheral.data.capacity <= self.config.low_threshold
+    }
+
+    fn peripherals(&self) -> &[Peripheral] {
+        self.service
+            .as_ref()
+            .map(|service| service.peripherals.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Runs the low battery hooks once for each peripheral going low.
+    fn check_low_batteries(&mut self) {
+        let low = self
+            .peripherals()
+            .iter()
+            .filter(|peripheral| self.is_low(peripheral))
+            .map(|peripheral| (peripheral.name.clone(), peripheral.data.capacity))
+            .collect::<Vec<_>>();
+
+        self.low
+            .retain(|name| low.iter().any(|(low, _)| low == name));
+
+        for (name, capacity) in low {
+            if !self.low.insert(name.clone()) {
+                continue;
+            }
+
+            if self.config.notify {
+                send_notification(
+                    format!("{name} battery low"),
+                    format!("{capacity}% remaining"),
+                    "battery-low",
+                );
+            }
+            if let Some(cmd) = self.config.low_battery_cmd.as_ref() {
+                execute_command_with_env(
+                    cmd.clone(),
+                    vec![
+                        ("ASHELL_DEVICE", name),
+                        ("ASHELL_CAPACITY", capacity.to_string()),
+                    ],
+                );
+            }
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Event(event) => match event {
+                ServiceEvent::Init(service) => {
+                    self.service = Some(service);
+                    self.check_low_batteries();
+                }
+                ServiceEvent::Update(data) => {
+                    if let Some(service) = self.service.as_mut() {
+                        service.update(data);
+                    }
+                    self.check_low_batteries();
+                }
+                ServiceEvent::Error(_) => self.service = None,
+            },
+            Message::ConfigReloaded(config) => {
+                self.config = config;
+            }
+        }
+    }
+
+    fn battery_view<'a>(&self, peripheral: &Peripheral) -> Element<'a, Message> {
+        let charging = is_charging(peripheral);
+        let low = self.is_low(peripheral);
+
+        container(
+            row!(
+                icon(peripheral.get_icon_state()),
+                text(format!("{}%", peripheral.data.capacity))
+            )
+            .spacing(4)
+            .align_y(Alignment::Center),
+        )
+        .style(move |theme: &Theme| container::Style {
+            text_color: Some(if charging {
+                theme.palette().success
+            } else if low {
+                theme.palette().danger
+            } else {
+                theme.palette().text
+            }),
+            ..Default::default()
+        })
+        .into()
+    }
+
+    /// The peripheral with the lowest battery, hidden without peripherals.
+    pub fn view(&'_ self, theme: &AshellTheme) -> Option<Element<'_, Message>> {
+        let lowest = self
+            .peripherals()
+            .iter()
+            .min_by_key(|peripheral| peripheral.data.capacity)?;
+
+        Some(
+            row!(icon(lowest.kind.get_icon()), self.battery_view(lowest))
+                .spacing(theme.space.xxs)
+                .align_y(Alignment::Center)
+                .into(),
+        )
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        let lines = self
+            .peripherals()
+            .iter()
+            .map(|peripheral| format!("{}: {}%", peripheral.name, peripheral.data.capacity))
+            .collect::<Vec<_>>();
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        column!(
+            text("Peripherals").size(theme.font_size.lg),
+            horizontal_rule(1),
+            Column::with_children(self.peripherals().iter().map(|peripheral| {
+                Row::new()
+                    .push(icon(peripheral.kind.get_icon()))
+                    .push(
+                        column!(
+                            text(peripheral.name.clone()),
+                            text(peripheral.kind.to_string()).size(theme.font_size.xs),
+                        )
+                        .width(Length::Fill),
+                    )
+                    .push(self.battery_view(peripheral))
+                    .align_y(Alignment::Center)
+                    .spacing(theme.space.sm)
+                    .into()
+            }))
+            .spacing(theme.space.xs),
+        )
+        .spacing(theme.space.xs)
+        .into()
+    }
+}
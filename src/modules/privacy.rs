@@ -1,24 +1,240 @@
 use crate::{
     components::icons::{StaticIcon, icon},
-    services::{ReadOnlyService, ServiceEvent, privacy::PrivacyService},
+    config::{PrivacyIndicator, PrivacyModuleConfig},
+    services::{
+        ReadOnlyService, ServiceEvent,
+        privacy::{ApplicationNode, DeviceUser, Media, PrivacyEvent, PrivacyService, Screencast},
+    },
     theme::AshellTheme,
 };
+use chrono::{DateTime, Local};
 use iced::{
-    Alignment, Element, Subscription,
-    widget::{Row, container},
+    Alignment, Element, Length, Subscription,
+    time::every,
+    widget::{Column, Row, button, column, container, horizontal_rule, row, text},
 };
+use log::warn;
+use std::{
+    collections::VecDeque,
+    fs::OpenOptions,
+    io::Write,
+    time::{Duration, Instant},
+};
+use tokio::process::Command;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Event(ServiceEvent<PrivacyService>),
+    Tick,
+    ToggleMute(u32),
+    Disconnect(u32),
+    ToggleDefaultMute,
+    ConfigReloaded(PrivacyModuleConfig),
+}
+
+/// A capture started or stopped, kept in the log of the menu.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    time: DateTime<Local>,
+    text: String,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Privacy {
+    pub config: PrivacyModuleConfig,
     pub service: Option<PrivacyService>,
+    log: VecDeque<LogEntry>,
+    webcam_since: Option<Instant>,
+    location_since: Option<Instant>,
+}
+
+fn run_pipewire_command(program: &'static str, args: Vec<String>) {
+    tokio::spawn(async move {
+        match Command::new(program).args(&args).status().await {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("{program} {} exited with {status}", args.join(" ")),
+            Err(e) => warn!("Failed to run {program}: {e}"),
+        }
+    });
+}
+
+fn media_indicator(media: Media) -> PrivacyIndicator {
+    match media {
+        Media::Video => PrivacyIndicator::ScreenShare,
+        Media::Audio => PrivacyIndicator::Microphone,
+    }
+}
+
+fn media_label(media: Media) -> &'static str {
+    match media {
+        Media::Video => "Screen sharing",
+        Media::Audio => "Microphone",
+    }
+}
+
+pub fn format_elapsed(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
 }
 
 impl Privacy {
+    pub fn new(config: PrivacyModuleConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the indicator is enabled and the application isn't ignored.
+    fn is_shown(&self, indicator: PrivacyIndicator, name: &str) -> bool {
+        self.config.indicators.contains(&indicator)
+            && !self
+                .config
+                .ignore
+                .iter()
+                .any(|regex| regex.0.is_match(name))
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &ApplicationNode> {
+        self.service
+            .iter()
+            .flat_map(|service| service.nodes())
+            .filter(|node| self.is_shown(media_indicator(node.media), &node.name))
+    }
+
+    fn screencasts(&self) -> impl Iterator<Item = &Screencast> {
+        self.service
+            .iter()
+            .flat_map(|service| service.screencasts())
+            .filter(|screencast| self.is_shown(PrivacyIndicator::ScreenShare, &screencast.target))
+    }
+
+    fn webcam_users(&self) -> impl Iterator<Item = &DeviceUser> {
+        self.service
+            .iter()
+            .flat_map(|service| service.webcam_users())
+            .filter(|user| self.is_shown(PrivacyIndicator::Webcam, &user.name))
+    }
+
+    fn location_users(&self) -> impl Iterator<Item = &String> {
+        self.service
+            .iter()
+            .flat_map(|service| service.location_users())
+            .filter(|user| self.is_shown(PrivacyIndicator::Location, user))
+    }
+
+    fn webcam_access(&self) -> bool {
+        self.config.indicators.contains(&PrivacyIndicator::Webcam)
+            && self.webcam_users().next().is_some()
+    }
+
+    fn push_log(&mut self, text: String) {
+        let entry = LogEntry {
+            time: Local::now(),
+            text,
+        };
+
+        if let Some(path) = self.config.log_file.as_ref() {
+            let path = shellexpand::tilde(path);
+            let res = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path.as_ref())
+                .and_then(|mut file| {
+                    writeln!(
+                        file,
+                        "{} {}",
+                        entry.time.format("%Y-%m-%d %H:%M:%S"),
+                        entry.text
+                    )
+                });
+            if let Err(e) = res {
+                warn!("Failed to write the privacy log {path}: {e}");
+            }
+        }
+
+        self.log.push_front(entry);
+        self.log.truncate(self.config.log_length);
+    }
+
+    /// Log entries of an event, computed before it's applied to the service.
+    fn event_log(&mut self, event: &PrivacyEvent) -> Vec<String> {
+        if self.service.is_none() {
+            return Vec::new();
+        }
+
+        match event {
+            PrivacyEvent::AddNode(node)
+                if self.is_shown(media_indicator(node.media), &node.name) =>
+            {
+                vec![format!(
+                    "{} started: {}",
+                    media_label(node.media),
+                    node.name
+                )]
+            }
+            PrivacyEvent::AddScreencast(screencast)
+                if self.is_shown(PrivacyIndicator::ScreenShare, &screencast.target) =>
+            {
+                vec![format!("Screencast started: {}", screencast.target)]
+            }
+            PrivacyEvent::AddNode(_) | PrivacyEvent::AddScreencast(_) => Vec::new(),
+            PrivacyEvent::RemoveNode(id) => self
+                .nodes()
+                .filter(|node| node.id == *id)
+                .map(|node| {
+                    format!(
+                        "{} stopped: {} after {}",
+                        media_label(node.media),
+                        node.name,
+                        format_elapsed(node.since.elapsed())
+                    )
+                })
+                .chain(
+                    self.screencasts()
+                        .filter(|screencast| screencast.id == *id)
+                        .map(|screencast| {
+                            format!(
+                                "Screencast stopped: {} after {}",
+                                screencast.target,
+                                format_elapsed(screencast.since.elapsed())
+                            )
+                        }),
+                )
+                .collect(),
+            PrivacyEvent::WebcamOpen | PrivacyEvent::WebcamClose => Vec::new(),
+            PrivacyEvent::Location(users) => {
+                let users = users
+                    .iter()
+                    .filter(|user| self.is_shown(PrivacyIndicator::Location, user))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                match (self.location_since, users.is_empty()) {
+                    (None, false) => {
+                        self.location_since = Some(Instant::now());
+                        vec![format!("Location started: {}", users.join(", "))]
+                    }
+                    (Some(since), true) => {
+                        let users = self.location_users().cloned().collect::<Vec<_>>();
+                        self.location_since = None;
+                        vec![format!(
+                            "Location stopped: {} after {}",
+                            users.join(", "),
+                            format_elapsed(since.elapsed())
+                        )]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+
     pub fn update(&mut self, message: Message) {
         match message {
             Message::Event(event) => match event {
@@ -26,44 +242,285 @@ impl Privacy {
                     self.service = Some(service);
                 }
                 ServiceEvent::Update(data) => {
+                    for text in self.event_log(&data) {
+                        self.push_log(text);
+                    }
+
+                    let was_webcam_open = self.webcam_access();
                     if let Some(privacy) = self.service.as_mut() {
                         privacy.update(data);
                     }
+
+                    // the users of the webcam are known once the event is applied
+                    if self.service.is_some() {
+                        let users = self
+                            .webcam_users()
+                            .map(|user| user.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        match (was_webcam_open, self.webcam_access()) {
+                            (false, true) => {
+                                self.webcam_since = Some(Instant::now());
+                                self.push_log(format!("Webcam started: {users}"));
+                            }
+                            (true, false) => {
+                                let elapsed = self
+                                    .webcam_since
+                                    .take()
+                                    .map(|since| {
+                                        format!(" after {}", format_elapsed(since.elapsed()))
+                                    })
+                                    .unwrap_or_default();
+                                self.push_log(format!("Webcam stopped{elapsed}"));
+                            }
+                            _ => {}
+                        }
+                    }
                 }
-                ServiceEvent::Error(_) => {}
+                ServiceEvent::Error(_) => self.service = None,
             },
+            // only used to refresh the capture durations
+            Message::Tick => {}
+            Message::ToggleMute(id) => {
+                run_pipewire_command(
+                    "wpctl",
+                    vec!["set-mute".to_owned(), id.to_string(), "toggle".to_owned()],
+                );
+            }
+            Message::Disconnect(id) => {
+                run_pipewire_command("pw-cli", vec!["destroy".to_owned(), id.to_string()]);
+            }
+            Message::ToggleDefaultMute => {
+                run_pipewire_command(
+                    "wpctl",
+                    vec![
+                        "set-mute".to_owned(),
+                        "@DEFAULT_AUDIO_SOURCE@".to_owned(),
+                        "toggle".to_owned(),
+                    ],
+                );
+            }
+            Message::ConfigReloaded(config) => {
+                self.log.truncate(config.log_length);
+                self.config = config;
+            }
         }
     }
 
+    fn indicator_view<'a>(&self, indicator: PrivacyIndicator) -> Element<'a, Message> {
+        let color = self.config.colors.get(indicator);
+
+        container(icon(match indicator {
+            PrivacyIndicator::Microphone => StaticIcon::Mic1,
+            PrivacyIndicator::Webcam => StaticIcon::Webcam,
+            PrivacyIndicator::ScreenShare => StaticIcon::ScreenShare,
+            PrivacyIndicator::Location => StaticIcon::Location,
+        }))
+        .style(move |theme| container::Style {
+            text_color: Some(match color {
+                Some(color) => color.color(theme),
+                None => theme.extended_palette().danger.weak.color,
+            }),
+            ..Default::default()
+        })
+        .into()
+    }
+
     pub fn view(&'_ self, theme: &AshellTheme) -> Option<Element<'_, Message>> {
-        if let Some(service) = self.service.as_ref()
-            && !service.no_access()
-        {
-            Some(
-                container(
-                    Row::new()
-                        .push_maybe(
-                            service
-                                .screenshare_access()
-                                .then(|| icon(StaticIcon::ScreenShare)),
-                        )
-                        .push_maybe(service.webcam_access().then(|| icon(StaticIcon::Webcam)))
-                        .push_maybe(service.microphone_access().then(|| icon(StaticIcon::Mic1)))
-                        .align_y(Alignment::Center)
-                        .spacing(theme.space.xs),
-                )
-                .style(|theme| container::Style {
-                    text_color: Some(theme.extended_palette().danger.weak.color),
-                    ..Default::default()
+        let screenshare = self.screencasts().next().is_some()
+            || self.nodes().any(|node| node.media == Media::Video);
+        let webcam = self.webcam_access();
+        let microphone = self.nodes().any(|node| node.media == Media::Audio);
+        let location = self.location_users().next().is_some();
+
+        (screenshare || webcam || microphone || location).then(|| {
+            Row::new()
+                .push_maybe(screenshare.then(|| self.indicator_view(PrivacyIndicator::ScreenShare)))
+                .push_maybe(webcam.then(|| self.indicator_view(PrivacyIndicator::Webcam)))
+                .push_maybe(microphone.then(|| self.indicator_view(PrivacyIndicator::Microphone)))
+                .push_maybe(location.then(|| self.indicator_view(PrivacyIndicator::Location)))
+                .align_y(Alignment::Center)
+                .spacing(theme.space.xs)
+                .into()
+        })
+    }
+
+    /// Applications using the microphone, the webcam or sharing the screen,
+    /// shown while hovering the module.
+    pub fn tooltip(&self) -> Option<String> {
+        let lines = self
+            .nodes()
+            .map(|node| match node.media {
+                Media::Video => format!("Screen sharing: {}", node.name),
+                Media::Audio => format!("Microphone: {}", node.name),
+            })
+            .chain(
+                self.screencasts()
+                    .map(|screencast| format!("Sharing: {}", screencast.target)),
+            )
+            .chain(
+                self.webcam_users()
+                    .map(|user| format!("Webcam: {}", user.name)),
+            )
+            .chain(
+                self.location_users()
+                    .map(|user| format!("Location: {user}")),
+            )
+            .collect::<Vec<_>>();
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn node_view<'a>(
+        &'a self,
+        node: &'a ApplicationNode,
+        theme: &'a AshellTheme,
+    ) -> Element<'a, Message> {
+        row!(
+            icon(match node.media {
+                Media::Video => StaticIcon::ScreenShare,
+                Media::Audio => StaticIcon::Mic1,
+            }),
+            column!(
+                text(node.name.clone()).width(Length::Fill),
+                text(match node.pid {
+                    Some(pid) => format!(
+                        "{} · pid {pid} · {}",
+                        node.media_class,
+                        format_elapsed(node.since.elapsed())
+                    ),
+                    None => format!(
+                        "{} · {}",
+                        node.media_class,
+                        format_elapsed(node.since.elapsed())
+                    ),
                 })
-                .into(),
+                .size(theme.font_size.xs),
             )
-        } else {
-            None
+            .width(Length::Fill),
+        )
+        .push_maybe((node.media == Media::Audio).then(|| {
+            button(text("Mute").size(theme.font_size.sm))
+                .style(theme.ghost_button_style())
+                .padding(theme.space.xs)
+                .on_press(Message::ToggleMute(node.id))
+        }))
+        .push(
+            button(text("Disconnect").size(theme.font_size.sm))
+                .style(theme.ghost_button_style())
+                .padding(theme.space.xs)
+                .on_press(Message::Disconnect(node.id)),
+        )
+        .align_y(Alignment::Center)
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    fn screencast_view<'a>(
+        &'a self,
+        screencast: &'a Screencast,
+        theme: &'a AshellTheme,
+    ) -> Element<'a, Message> {
+        row!(
+            icon(StaticIcon::ScreenShare),
+            column!(
+                text(screencast.target.clone()).width(Length::Fill),
+                text(format!(
+                    "Screencast · {}",
+                    format_elapsed(screencast.since.elapsed())
+                ))
+                .size(theme.font_size.xs),
+            )
+            .width(Length::Fill),
+            button(text("Stop sharing").size(theme.font_size.sm))
+                .style(theme.ghost_button_style())
+                .padding(theme.space.xs)
+                .on_press(Message::Disconnect(screencast.id)),
+        )
+        .align_y(Alignment::Center)
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        if self.service.is_none() {
+            return Row::new().into();
         }
+
+        column!(
+            row!(
+                text("Capture devices in use")
+                    .size(theme.font_size.lg)
+                    .width(Length::Fill),
+                button(text("Mute/unmute mic").size(theme.font_size.sm))
+                    .style(theme.ghost_button_style())
+                    .padding(theme.space.xs)
+                    .on_press(Message::ToggleDefaultMute),
+            )
+            .align_y(Alignment::Center),
+            horizontal_rule(1),
+            Column::with_children(
+                self.screencasts()
+                    .map(|screencast| self.screencast_view(screencast, theme))
+                    .chain(self.nodes().map(|node| self.node_view(node, theme)))
+                    .chain(self.webcam_users().map(|user| {
+                        row!(
+                            icon(StaticIcon::Webcam),
+                            column!(
+                                text(user.name.clone()),
+                                text(format!("Webcam · pid {}", user.pid)).size(theme.font_size.xs),
+                            )
+                        )
+                        .align_y(Alignment::Center)
+                        .spacing(theme.space.xs)
+                        .into()
+                    }))
+                    .chain(self.location_users().map(|user| {
+                        row!(
+                            icon(StaticIcon::Location),
+                            column!(
+                                text(user.clone()),
+                                text("Location").size(theme.font_size.xs),
+                            )
+                        )
+                        .align_y(Alignment::Center)
+                        .spacing(theme.space.xs)
+                        .into()
+                    })),
+            )
+            .spacing(theme.space.xs),
+        )
+        .push_maybe((!self.log.is_empty()).then(|| {
+            column!(
+                horizontal_rule(1),
+                text("Recent activity").size(theme.font_size.md),
+                Column::with_children(self.log.iter().map(|entry| {
+                    row!(
+                        text(entry.time.format("%H:%M:%S").to_string()).size(theme.font_size.xs),
+                        text(entry.text.clone())
+                            .size(theme.font_size.xs)
+                            .width(Length::Fill),
+                    )
+                    .spacing(theme.space.xs)
+                    .into()
+                })),
+            )
+            .spacing(theme.space.xs)
+        }))
+        .spacing(theme.space.xs)
+        .into()
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        PrivacyService::subscribe().map(Message::Event)
+    /// Refreshes the capture durations, only shown by the open menu.
+    pub fn subscription(&self, menu_open: bool) -> Subscription<Message> {
+        let capturing = self.service.as_ref().is_some_and(|service| {
+            !service.nodes().is_empty() || !service.screencasts().is_empty()
+        });
+
+        if menu_open && capturing {
+            every(Duration::from_secs(1)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        }
     }
 }
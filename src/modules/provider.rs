@@ -0,0 +1,151 @@
+use crate::{
+    components::icons::{DynamicIcon, icon},
+    theme::AshellTheme,
+    utils::launcher::execute_command,
+};
+use iced::{
+    Alignment, Element, Length,
+    widget::{Column, Row, button, column, horizontal_rule, row, text},
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Entry of the menu of an external module.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderMenuEntry {
+    pub label: String,
+    pub command: String,
+}
+
+/// Content of a module registered by an external program.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderWidget {
+    /// Name used to place the module in the layout
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+    /// Command executed when the module is clicked, ignored when it has a menu
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_click: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub menu: Vec<ProviderMenuEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Click(String),
+    MenuEntry(String),
+}
+
+/// Modules registered at runtime through the IPC socket.
+#[derive(Debug, Default)]
+pub struct Providers {
+    widgets: HashMap<String, ProviderWidget>,
+}
+
+impl Providers {
+    /// Adds the module or replaces its content if it's already registered.
+    pub fn register(&mut self, widget: ProviderWidget) {
+        if !self.widgets.contains_key(&widget.name) {
+            info!("External module {} registered", widget.name);
+        }
+
+        self.widgets.insert(widget.name.clone(), widget);
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let removed = self.widgets.remove(name).is_some();
+
+        if removed {
+            info!("External module {name} unregistered");
+        }
+
+        removed
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProviderWidget> {
+        self.widgets.get(name)
+    }
+
+    pub fn widgets(&self) -> impl Iterator<Item = &ProviderWidget> {
+        self.widgets.values()
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Click(name) => {
+                if let Some(command) = self
+                    .widgets
+                    .get(&name)
+                    .and_then(|widget| widget.on_click.clone())
+                {
+                    execute_command(command);
+                }
+            }
+            Message::MenuEntry(command) => {
+                execute_command(command);
+            }
+        }
+    }
+
+    pub fn view<'a>(&'a self, theme: &'a AshellTheme, name: &str) -> Option<Element<'a, Message>> {
+        self.widgets.get(name).map(|widget| {
+            Row::new()
+                .push_maybe(
+                    widget
+                        .icon
+                        .as_ref()
+                        .map(|value| icon(DynamicIcon(value.clone()))),
+                )
+                .push_maybe(
+                    widget
+                        .text
+                        .as_ref()
+                        .filter(|value| !value.is_empty())
+                        .map(|value| theme.module_text(&widget.name, text(value.clone()))),
+                )
+                .align_y(Alignment::Center)
+                .spacing(theme.space.xs)
+                .into()
+        })
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme, name: &str) -> Element<'a, Message> {
+        let Some(widget) = self.widgets.get(name) else {
+            return Row::new().into();
+        };
+
+        column!()
+            .push_maybe(widget.tooltip.as_ref().map(|tooltip| {
+                column!(
+                    text(tooltip.clone()).size(theme.font_size.sm),
+                    horizontal_rule(1)
+                )
+                .spacing(theme.space.xs)
+            }))
+            .push(
+                Column::with_children(
+                    widget
+                        .menu
+                        .iter()
+                        .map(|entry| {
+                            button(row!(text(entry.label.clone()).width(Length::Fill)))
+                                .style(theme.ghost_button_style())
+                                .padding(theme.space.xs)
+                                .on_press(Message::MenuEntry(entry.command.clone()))
+                                .width(Length::Fill)
+                                .into()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .spacing(theme.space.xxs),
+            )
+            .spacing(theme.space.xs)
+            .into()
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{
+    components::icons::{StaticIcon, icon},
+    config::RecordingModuleConfig,
+    modules::privacy::format_elapsed,
+    services::{
+        ReadOnlyService, ServiceEvent,
+        privacy::{PrivacyService, Screencast},
+        registry::SharedSystem,
+    },
+    theme::AshellTheme,
+};
+use iced::{
+    Alignment, Border, Element, Length, Subscription, Theme,
+    futures::SinkExt,
+    stream::channel,
+    time::every,
+    widget::{Column, button, column, container, horizontal_rule, row, text},
+};
+use log::warn;
+use std::{
+    any::TypeId,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tokio::process::Command;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Recorders(Vec<Recorder>),
+    Privacy(ServiceEvent<PrivacyService>),
+    Tick,
+    StopRecorder(u32),
+    StopScreencast(u32),
+    ConfigReloaded(RecordingModuleConfig),
+}
+
+/// A running screen recorder process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recorder {
+    pid: u32,
+    name: String,
+    /// File written by the recorder, when it can be told from its arguments
+    file: Option<PathBuf>,
+    started: SystemTime,
+}
+
+/// Options naming the output file of a recorder, and the file written without them.
+fn file_options(recorder: &str) -> (&'static [&'static str], Option<&'static str>) {
+    match recorder {
+        "wf-recorder" => (&["-f", "--file"], Some("recording.mp4")),
+        "wl-screenrec" => (&["-f", "--filename"], Some("screenrecord.mp4")),
+        "gpu-screen-recorder" => (&["-o"], None),
+        _ => (&["-f", "--file", "--filename"], None),
+    }
+}
+
+/// The output file from the arguments of the recorder, relative to its working directory.
+fn output_file(recorder: &str, args: &[String], cwd: Option<&Path>) -> Option<PathBuf> {
+    let (options, default) = file_options(recorder);
+    let mut args = args.iter();
+    let mut file = None;
+
+    while let Some(arg) = args.next() {
+        for option in options {
+            if arg == option {
+                file = args.next().cloned();
+            } else if let Some(value) = arg.strip_prefix(&format!("{option}=")) {
+                file = Some(value.to_owned());
+            } else if !option.starts_with("--")
+                && let Some(value) = arg.strip_prefix(option).filter(|value| !value.is_empty())
+            {
+                // short options can be glued to their value, e.g. `-fvideo.mp4`
+                file = Some(value.to_owned());
+            }
+        }
+    }
+
+    let file = PathBuf::from(file.or(default.map(str::to_owned))?);
+
+    Some(match cwd {
+        Some(cwd) if file.is_relative() => cwd.join(file),
+        _ => file,
+    })
+}
+
+fn recorders(system: &mut System, names: &[String]) -> Vec<Recorder> {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_cwd(UpdateKind::OnlyIfNotSet),
+    );
+
+    let mut recorders = system
+        .processes()
+        .values()
+        .filter_map(|process| {
+            let cmd = process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            // the process name is truncated by the kernel, the command isn't
+            let name = cmd
+                .first()
+                .and_then(|program| Path::new(program).file_name())
+                .map_or_else(
+                    || process.name().to_string_lossy().into_owned(),
+                    |program| program.to_string_lossy().into_owned(),
+                );
+            if !names.contains(&name) {
+                return None;
+            }
+
+            Some(Recorder {
+                pid: process.pid().as_u32(),
+                file: output_file(&name, cmd.get(1..).unwrap_or_default(), process.cwd()),
+                name,
+                started: UNIX_EPOCH + Duration::from_secs(process.start_time()),
+            })
+        })
+        .collect::<Vec<_>>();
+    recorders.sort_by_key(|recorder| recorder.pid);
+
+    recorders
+}
+
+fn stop(program: &'static str, args: Vec<String>) {
+    tokio::spawn(async move {
+        match Command::new(program).args(&args).status().await {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("{program} {} exited with {status}", args.join(" ")),
+            Err(e) => warn!("Failed to run {program}: {e}"),
+        }
+    });
+}
+
+fn elapsed(started: SystemTime) -> String {
+    format_elapsed(started.elapsed().unwrap_or_default())
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Recording {
+    config: RecordingModuleConfig,
+    recorders: Vec<Recorder>,
+    privacy: Option<PrivacyService>,
+    system: SharedSystem,
+}
+
+impl Recording {
+    pub fn new(config: RecordingModuleConfig, system: SharedSystem) -> Self {
+        Self {
+            config,
+            system,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the screencasts are shown, along with the recorders.
+    pub fn screencasts_enabled(&self) -> bool {
+        self.config.screencast
+    }
+
+    fn screencasts(&self) -> &[Screencast] {
+        match self.privacy.as_ref() {
+            Some(service) if self.config.screencast => service.screencasts(),
+            _ => &[],
+        }
+    }
+
+    fn is_recording(&self) -> bool {
+        !self.recorders.is_empty() || !self.screencasts().is_empty()
+    }
+
+    /// Start of the oldest recording.
+    fn started(&self) -> Option<SystemTime> {
+        self.recorders
+            .iter()
+            .map(|recorder| recorder.started)
+            .chain(
+                self.screencasts().iter().filter_map(|screencast| {
+                    SystemTime::now().checked_sub(screencast.since.elapsed())
+                }),
+            )
+            .min()
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Recorders(recorders) => self.recorders = recorders,
+            Message::Privacy(event) => match event {
+                ServiceEvent::Init(service) => self.privacy = Some(service),
+                ServiceEvent::Update(event) => {
+                    if let Some(service) = self.privacy.as_mut() {
+                        service.update(event);
+                    }
+                }
+                ServiceEvent::Error(_) => self.privacy = None,
+            },
+            // only used to refresh the elapsed time
+            Message::Tick => {}
+            Message::StopRecorder(pid) => {
+                // the recorders finish writing the file on SIGINT, like on Ctrl+C
+                stop("kill", vec!["-INT".to_owned(), pid.to_string()]);
+            }
+            Message::StopScreencast(id) => {
+                stop("pw-cli", vec!["destroy".to_owned(), id.to_string()]);
+            }
+            Message::ConfigReloaded(config) => {
+                if !config.screencast {
+                    self.privacy = None;
+                }
+                self.recorders
+                    .retain(|recorder| config.recorders.contains(&recorder.name));
+                self.config = config;
+            }
+        }
+    }
+
+    pub fn view<'a>(&self, theme: &AshellTheme) -> Option<Element<'a, Message>> {
+        let started = self.started()?;
+        let color = self.config.color;
+        let radius = theme.radius.lg;
+
+        Some(
+            container(
+                row!(
+                    icon(StaticIcon::Record),
+                    text(elapsed(started)).size(theme.font_size.sm),
+                )
+                .spacing(theme.space.xxs)
+                .align_y(Alignment::Center),
+            )
+            .padding([0, theme.space.xs])
+            .style(move |iced_theme: &Theme| {
+                let background =
+                    color.map_or(iced_theme.palette().danger, |color| color.color(iced_theme));
+
+                container::Style {
+                    background: Some(background.into()),
+                    text_color: Some(iced_theme.extended_palette().danger.base.text),
+                    border: Border {
+                        radius: radius.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            })
+            .into(),
+        )
+    }
+
+    fn session_view<'a>(
+        title: String,
+        details: String,
+        stop: Message,
+        theme: &'a AshellTheme,
+    ) -> Element<'a, Message> {
+        row!(
+            icon(StaticIcon::Record),
+            column!(
+                text(title).width(Length::Fill),
+                text(details).size(theme.font_size.xs),
+            )
+            .width(Length::Fill),
+            button(text("Stop").size(theme.font_size.sm))
+                .style(theme.outline_button_style())
+                .padding([theme.space.xxs, theme.space.sm])
+                .on_press(stop),
+        )
+        .align_y(Alignment::Center)
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let sessions = self
+            .recorders
+            .iter()
+            .map(|recorder| {
+                Self::session_view(
+                    recorder.name.clone(),
+                    match recorder.file.as_ref() {
+                        Some(file) => {
+                            format!("{} · {}", file.display(), elapsed(recorder.started))
+                        }
+                        None => format!("pid {} · {}", recorder.pid, elapsed(recorder.started)),
+                    },
+                    Message::StopRecorder(recorder.pid),
+                    theme,
+                )
+            })
+            .chain(self.screencasts().iter().map(|screencast| {
+                Self::session_view(
+                    screencast.target.clone(),
+                    format!(
+                        "Screencast · {}",
+                        format_elapsed(screencast.since.elapsed())
+                    ),
+                    Message::StopScreencast(screencast.id),
+                    theme,
+                )
+            }))
+            .collect::<Vec<_>>();
+        let idle = sessions.is_empty();
+
+        column!(
+            text("Screen recording").size(theme.font_size.lg),
+            horizontal_rule(1),
+            Column::with_children(sessions).spacing(theme.space.xs),
+        )
+        .push_maybe(idle.then(|| text("Nothing is being recorded").size(theme.font_size.sm)))
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let names = self.config.recorders.clone();
+        let system = self.system.clone();
+
+        Subscription::batch([
+            Subscription::run_with_id(
+                (TypeId::of::<Recorder>(), names.clone()),
+                channel(10, async move |mut output| {
+                    let mut current = Vec::new();
+
+                    loop {
+                        let recorders = system
+                            .lock()
+                            .map(|mut system| recorders(&mut system, &names))
+                            .unwrap_or_default();
+                        if recorders != current {
+                            current = recorders.clone();
+                            let _ = output.send(recorders).await;
+                        }
+
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                }),
+            )
+            .map(Message::Recorders),
+            if self.is_recording() {
+                every(Duration::from_secs(1)).map(|_| Message::Tick)
+            } else {
+                Subscription::none()
+            },
+        ])
+    }
+}
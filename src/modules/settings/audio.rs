@@ -1,6 +1,7 @@
 use super::SubMenu;
 use crate::{
     components::icons::{StaticIcon, icon, icon_button},
+    long_press::long_press,
     services::{
         ReadOnlyService, Service, ServiceEvent,
         audio::{AudioCommand, AudioService, DeviceType, Sinks},
@@ -8,7 +9,7 @@ use crate::{
     theme::AshellTheme,
 };
 use iced::{
-    Alignment, Element, Length, Subscription, Theme,
+    Alignment, Element, Length, Theme,
     widget::{
         Column, MouseArea, Row, button, column, container, horizontal_rule, row, slider, text,
     },
@@ -179,22 +180,25 @@ impl AudioSettings {
             .map(|service| {
                 let icon_type = service.sinks.get_icon(&service.server_info.default_sink);
                 let icon = icon(icon_type);
-                MouseArea::new(icon)
-                    .on_right_press(Message::OpenMore)
-                    .on_scroll(|delta| {
-                        let cur_vol = service.cur_sink_volume;
-                        let delta = match delta {
-                            iced::mouse::ScrollDelta::Lines { y, .. } => y,
-                            iced::mouse::ScrollDelta::Pixels { y, .. } => y,
-                        };
-                        let new_volume = if delta > 0.0 {
-                            (cur_vol + 5).min(100)
-                        } else {
-                            (cur_vol - 5).max(0)
-                        };
-                        Message::SinkVolumeChanged(new_volume)
-                    })
-                    .into()
+                long_press(
+                    MouseArea::new(icon)
+                        .on_right_press(Message::OpenMore)
+                        .on_scroll(|delta| {
+                            let cur_vol = service.cur_sink_volume;
+                            let delta = match delta {
+                                iced::mouse::ScrollDelta::Lines { y, .. } => y,
+                                iced::mouse::ScrollDelta::Pixels { y, .. } => y,
+                            };
+                            let new_volume = if delta > 0.0 {
+                                (cur_vol + 5).min(100)
+                            } else {
+                                (cur_vol - 5).max(0)
+                            };
+                            Message::SinkVolumeChanged(new_volume)
+                        }),
+                    Message::OpenMore,
+                )
+                .into()
             })
     }
 
@@ -324,7 +328,7 @@ impl AudioSettings {
         with_submenu: Option<(Option<SubMenu>, Message)>,
     ) -> Element<'a, Message> {
         Row::new()
-            .push(
+            .push(long_press(
                 MouseArea::new(
                     icon_button(
                         theme,
@@ -343,7 +347,8 @@ impl AudioSettings {
                     .on_press(toggle_mute),
                 )
                 .on_right_press(Message::OpenMore),
-            )
+                Message::OpenMore,
+            ))
             .push(
                 MouseArea::new(
                     slider(0..=100, volume, volume_changed)
@@ -434,8 +439,4 @@ impl AudioSettings {
             _ => entries,
         }
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        AudioService::subscribe().map(Message::Event)
-    }
 }
@@ -1,6 +1,7 @@
 use super::{SubMenu, quick_setting_button};
 use crate::{
     components::icons::{IconButtonSize, StaticIcon, icon, icon_button},
+    long_press::long_press,
     services::{
         ReadOnlyService, Service, ServiceEvent,
         bluetooth::{BluetoothCommand, BluetoothDevice, BluetoothService, BluetoothState},
@@ -8,7 +9,7 @@ use crate::{
     theme::AshellTheme,
 };
 use iced::{
-    Element, Length, Subscription, Task, Theme,
+    Element, Length, Task, Theme,
     alignment::{Horizontal, Vertical},
     widget::{
         Column, MouseArea, Row, button, column, container, horizontal_rule, row, scrollable, text,
@@ -79,7 +80,10 @@ impl BluetoothSettings {
                     }
                     Action::None
                 }
-                _ => Action::None,
+                ServiceEvent::Error(_) => {
+                    self.service = None;
+                    Action::None
+                }
             },
             Message::Toggle => match self.service.as_mut() {
                 Some(service) => Action::CloseSubMenu(
@@ -420,15 +424,13 @@ impl BluetoothSettings {
             };
 
             return Some(
-                MouseArea::new(icon(icon_type))
-                    .on_right_press(Message::OpenMore)
-                    .into(),
+                long_press(
+                    MouseArea::new(icon(icon_type)).on_right_press(Message::OpenMore),
+                    Message::OpenMore,
+                )
+                .into(),
             );
         }
         None
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        BluetoothService::subscribe().map(Message::Event)
-    }
 }
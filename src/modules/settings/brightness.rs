@@ -140,19 +140,16 @@ impl BrightnessSettings {
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        Subscription::batch([
-            BrightnessService::subscribe().map(Message::Event),
-            if self.reset_timer_active {
-                Subscription::run_with_id(
-                    0,
-                    stream::once(async {
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        Message::ResetUserAdjusting
-                    }),
-                )
-            } else {
-                Subscription::none()
-            },
-        ])
+        if self.reset_timer_active {
+            Subscription::run_with_id(
+                0,
+                stream::once(async {
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    Message::ResetUserAdjusting
+                }),
+            )
+        } else {
+            Subscription::none()
+        }
     }
 }
@@ -0,0 +1,241 @@
+use super::{SubMenu, quick_setting_button};
+use crate::{
+    components::icons::StaticIcon,
+    services::{
+        ReadOnlyService, Service, ServiceEvent,
+        displays::{Display, DisplayMode, DisplayTransform, DisplaysCommand, DisplaysService},
+    },
+    theme::AshellTheme,
+};
+use iced::{
+    Alignment, Element, Length, Subscription, Task,
+    widget::{
+        Column, column, container, horizontal_rule, pick_list, row, scrollable, text, toggler,
+    },
+};
+use std::fmt;
+
+const SCALES: [f64; 8] = [0.75, 1., 1.25, 1.5, 1.75, 2., 2.5, 3.];
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(ServiceEvent<DisplaysService>),
+    ToggleSubMenu,
+    SetEnabled(String, bool),
+    SetMode(String, DisplayMode),
+    SetScale(String, Scale),
+    SetTransform(String, DisplayTransform),
+    ConfigReloaded(DisplaysSettingsConfig),
+}
+
+pub enum Action {
+    None,
+    ToggleDisplaysMenu,
+    CloseSubMenu,
+    Command(Task<Message>),
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplaysSettingsConfig {
+    pub remove_displays_btn: bool,
+}
+
+impl DisplaysSettingsConfig {
+    pub fn new(remove_displays_btn: bool) -> Self {
+        Self {
+            remove_displays_btn,
+        }
+    }
+}
+
+/// Scale of a display, shown as a percentage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale(f64);
+
+impl fmt::Display for Scale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0}%", self.0 * 100.)
+    }
+}
+
+pub struct DisplaysSettings {
+    config: DisplaysSettingsConfig,
+    service: Option<DisplaysService>,
+}
+
+impl DisplaysSettings {
+    pub fn new(config: DisplaysSettingsConfig) -> Self {
+        Self {
+            config,
+            service: None,
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Event(event) => match event {
+                ServiceEvent::Init(service) => {
+                    self.service = Some(service);
+                    Action::None
+                }
+                ServiceEvent::Update(data) => {
+                    if let Some(service) = self.service.as_mut() {
+                        service.update(data);
+                    }
+                    Action::None
+                }
+                ServiceEvent::Error(_) => {
+                    self.service = None;
+                    Action::CloseSubMenu
+                }
+            },
+            Message::ToggleSubMenu => Action::ToggleDisplaysMenu,
+            Message::SetEnabled(name, enabled) => {
+                self.command(DisplaysCommand::SetEnabled(name, enabled))
+            }
+            Message::SetMode(name, mode) => self.command(DisplaysCommand::SetMode(name, mode)),
+            Message::SetScale(name, scale) => {
+                self.command(DisplaysCommand::SetScale(name, scale.0))
+            }
+            Message::SetTransform(name, transform) => {
+                self.command(DisplaysCommand::SetTransform(name, transform))
+            }
+            Message::ConfigReloaded(config) => {
+                self.config = config;
+                if self.config.remove_displays_btn {
+                    self.service = None;
+                }
+                Action::None
+            }
+        }
+    }
+
+    fn command(&mut self, command: DisplaysCommand) -> Action {
+        match self.service.as_mut() {
+            Some(service) => Action::Command(service.command(command).map(Message::Event)),
+            _ => Action::None,
+        }
+    }
+
+    pub fn quick_setting_button<'a>(
+        &'a self,
+        theme: &'a AshellTheme,
+        sub_menu: Option<SubMenu>,
+    ) -> Option<(Element<'a, Message>, Option<Element<'a, Message>>)> {
+        let service = self
+            .service
+            .as_ref()
+            .filter(|service| !service.is_empty())?;
+
+        let enabled = service
+            .iter()
+            .filter(|display| display.enabled)
+            .collect::<Vec<_>>();
+        let subtitle = match enabled.as_slice() {
+            [display] => display.current_mode().map(|mode| mode.to_string()),
+            displays => Some(format!("{} displays", displays.len())),
+        };
+
+        Some((
+            quick_setting_button(
+                theme,
+                StaticIcon::Monitor,
+                "Displays".to_owned(),
+                subtitle,
+                false,
+                Message::ToggleSubMenu,
+                None,
+                Some((SubMenu::Displays, sub_menu, Message::ToggleSubMenu)),
+            ),
+            sub_menu
+                .filter(|menu_type| *menu_type == SubMenu::Displays)
+                .map(|_| displays_menu(theme, service)),
+        ))
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.config.remove_displays_btn {
+            Subscription::none()
+        } else {
+            DisplaysService::subscribe().map(Message::Event)
+        }
+    }
+}
+
+fn displays_menu<'a>(theme: &'a AshellTheme, service: &'a DisplaysService) -> Element<'a, Message> {
+    container(scrollable(
+        Column::with_children(service.iter().map(|display| display_entry(theme, display)))
+            .spacing(theme.space.md)
+            .padding([0, theme.space.md, 0, theme.space.xs]),
+    ))
+    .height(Length::Shrink)
+    .max_height(400)
+    .into()
+}
+
+fn display_entry<'a>(theme: &'a AshellTheme, display: &'a Display) -> Element<'a, Message> {
+    let name = display.name.clone();
+    let header = row!(
+        column!(
+            text(display.name.clone()),
+            text(display.description.clone())
+                .size(theme.font_size.xs)
+                .wrapping(text::Wrapping::None),
+        )
+        .width(Length::Fill)
+        .clip(true),
+        toggler(display.enabled)
+            .on_toggle(move |enabled| Message::SetEnabled(name.clone(), enabled))
+            .width(Length::Shrink),
+    )
+    .align_y(Alignment::Center)
+    .spacing(theme.space.xs);
+
+    if !display.enabled {
+        return header.into();
+    }
+
+    let setting = |label: &'static str, element: Element<'a, Message>| {
+        row!(
+            text(label).size(theme.font_size.sm).width(Length::Fill),
+            element
+        )
+        .align_y(Alignment::Center)
+        .spacing(theme.space.xs)
+    };
+
+    let name = display.name.clone();
+    let mode = pick_list(display.modes.clone(), display.current_mode(), move |mode| {
+        Message::SetMode(name.clone(), mode)
+    })
+    .text_size(theme.font_size.sm)
+    .padding([theme.space.xxs, theme.space.xs]);
+
+    let name = display.name.clone();
+    let scale = pick_list(
+        SCALES.map(Scale),
+        Some(Scale(display.scale)),
+        move |scale| Message::SetScale(name.clone(), scale),
+    )
+    .text_size(theme.font_size.sm)
+    .padding([theme.space.xxs, theme.space.xs]);
+
+    let name = display.name.clone();
+    let transform = pick_list(
+        DisplayTransform::ALL,
+        Some(display.transform),
+        move |transform| Message::SetTransform(name.clone(), transform),
+    )
+    .text_size(theme.font_size.sm)
+    .padding([theme.space.xxs, theme.space.xs]);
+
+    column!(
+        header,
+        horizontal_rule(1),
+        setting("Resolution", mode.into()),
+        setting("Scale", scale.into()),
+        setting("Rotation", transform.into()),
+    )
+    .spacing(theme.space.xs)
+    .into()
+}
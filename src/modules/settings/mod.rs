@@ -8,31 +8,39 @@ use tokio::time::timeout;
 
 use crate::{
     components::icons::{DynamicIcon, Icon, IconButtonSize, StaticIcon, icon, icon_button},
-    config::{Position, SettingsCustomButton, SettingsIndicator, SettingsModuleConfig},
+    config::{
+        DEFAULT_PROFILE, ModuleName, Position, SettingsCustomButton, SettingsIndicator,
+        SettingsModuleConfig,
+    },
+    long_press::long_press,
     modules::settings::{
         audio::{AudioSettings, AudioSettingsConfig},
         bluetooth::{BluetoothSettings, BluetoothSettingsConfig},
         brightness::BrightnessSettings,
+        displays::{DisplaysSettings, DisplaysSettingsConfig},
         network::{NetworkSettings, NetworkSettingsConfig},
         power::{PowerSettings, PowerSettingsConfig},
     },
     password_dialog,
     services::idle_inhibitor::IdleInhibitorManager,
     theme::AshellTheme,
+    toasts::DoNotDisturb,
 };
 use iced::{
     Alignment, Background, Border, Element, Length, Padding, Subscription, Task, Theme,
     widget::{
-        Column, MouseArea, Row, Space, button, column, container, horizontal_space, row, text,
+        Column, MouseArea, Row, Space, button, column, container, horizontal_space, pick_list, row,
+        scrollable, text, toggler,
     },
     window::Id,
 };
 
-mod audio;
-mod bluetooth;
-mod brightness;
-mod network;
-mod power;
+pub mod audio;
+pub mod bluetooth;
+pub mod brightness;
+mod displays;
+pub mod network;
+pub mod power;
 
 pub struct Settings {
     lock_cmd: Option<String>,
@@ -41,12 +49,15 @@ pub struct Settings {
     brightness: BrightnessSettings,
     network: NetworkSettings,
     bluetooth: BluetoothSettings,
+    displays: DisplaysSettings,
     idle_inhibitor: Option<IdleInhibitorManager>,
     sub_menu: Option<SubMenu>,
     password_dialog: Option<(String, String)>,
     indicators: Vec<SettingsIndicator>,
     custom_buttons: Vec<SettingsCustomButton>,
     custom_buttons_status: HashMap<String, Option<bool>>,
+    profiles: Vec<String>,
+    active_profile: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -55,6 +66,7 @@ pub enum Message {
     Bluetooth(bluetooth::Message),
     Audio(audio::Message),
     Brightness(brightness::Message),
+    Displays(displays::Message),
     ToggleInhibitIdle,
     Lock,
     Power(power::Message),
@@ -63,6 +75,9 @@ pub enum Message {
     CustomButton(String),
     CustomButtonsStatus(Vec<(String, Option<bool>)>),
     MenuOpened,
+    SelectProfile(String),
+    ToggleModule(ModuleName),
+    ToggleDoNotDisturb,
     ConfigReloaded(SettingsModuleConfig),
 }
 
@@ -73,6 +88,9 @@ pub enum Action {
     RequestKeyboard(Id),
     ReleaseKeyboard(Id),
     ReleaseKeyboardWithCommand(Id, Task<Message>),
+    SwitchProfile(String),
+    ToggleModule(ModuleName),
+    ToggleDoNotDisturb,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -84,6 +102,8 @@ pub enum SubMenu {
     Wifi,
     Vpn,
     Bluetooth,
+    Displays,
+    Modules,
 }
 
 impl Settings {
@@ -113,6 +133,9 @@ impl Settings {
             bluetooth: BluetoothSettings::new(BluetoothSettingsConfig::new(
                 config.bluetooth_more_cmd,
             )),
+            displays: DisplaysSettings::new(DisplaysSettingsConfig::new(
+                config.remove_displays_btn,
+            )),
             idle_inhibitor: if config.remove_idle_btn {
                 None
             } else {
@@ -123,6 +146,8 @@ impl Settings {
             indicators: config.indicators,
             custom_buttons: config.custom_buttons,
             custom_buttons_status: HashMap::new(),
+            profiles: config.profiles,
+            active_profile: config.active_profile,
         }
     }
 
@@ -228,6 +253,24 @@ impl Settings {
                 brightness::Action::None => Action::None,
                 brightness::Action::Command(task) => Action::Command(task.map(Message::Brightness)),
             },
+            Message::Displays(msg) => match self.displays.update(msg) {
+                displays::Action::None => Action::None,
+                displays::Action::ToggleDisplaysMenu => {
+                    if self.sub_menu == Some(SubMenu::Displays) {
+                        self.sub_menu.take();
+                    } else {
+                        self.sub_menu.replace(SubMenu::Displays);
+                    }
+                    Action::None
+                }
+                displays::Action::CloseSubMenu => {
+                    if self.sub_menu == Some(SubMenu::Displays) {
+                        self.sub_menu.take();
+                    }
+                    Action::None
+                }
+                displays::Action::Command(task) => Action::Command(task.map(Message::Displays)),
+            },
             Message::ToggleSubMenu(menu_type) => {
                 if self.sub_menu == Some(menu_type) {
                     self.sub_menu.take();
@@ -371,6 +414,9 @@ impl Settings {
 
                 Action::Command(Task::batch([custom_buttons_task, brightness_task]))
             }
+            Message::SelectProfile(name) => Action::SwitchProfile(name),
+            Message::ToggleModule(module) => Action::ToggleModule(module),
+            Message::ToggleDoNotDisturb => Action::ToggleDoNotDisturb,
             Message::ConfigReloaded(config) => {
                 self.lock_cmd = config.lock_cmd;
                 self.power
@@ -399,12 +445,17 @@ impl Settings {
                 self.bluetooth.update(bluetooth::Message::ConfigReloaded(
                     BluetoothSettingsConfig::new(config.bluetooth_more_cmd),
                 ));
+                self.displays.update(displays::Message::ConfigReloaded(
+                    DisplaysSettingsConfig::new(config.remove_displays_btn),
+                ));
                 if config.remove_idle_btn {
                     self.idle_inhibitor = None;
                 } else if self.idle_inhibitor.is_none() {
                     self.idle_inhibitor = IdleInhibitorManager::new();
                 }
                 self.indicators = config.indicators;
+                self.profiles = config.profiles;
+                self.active_profile = config.active_profile;
                 Action::None
             }
         }
@@ -415,6 +466,8 @@ impl Settings {
         id: Id,
         theme: &'a AshellTheme,
         position: Position,
+        modules: Vec<(ModuleName, bool)>,
+        do_not_disturb: Option<DoNotDisturb>,
     ) -> Element<'a, Message> {
         if let Some((ssid, current_password)) = &self.password_dialog {
             password_dialog::view(id, theme, ssid, current_password).map(Message::PasswordDialog)
@@ -480,6 +533,14 @@ impl Settings {
                                 submenu.map(|e| e.map(Message::Network)),
                             )
                         }),
+                    self.displays
+                        .quick_setting_button(theme, self.sub_menu)
+                        .map(|(button, submenu)| {
+                            (
+                                button.map(Message::Displays),
+                                submenu.map(|e| e.map(Message::Displays)),
+                            )
+                        }),
                     self.network
                         .airplane_mode_quick_setting_button(theme)
                         .map(|(button, _)| (button.map(Message::Network), None)),
@@ -510,6 +571,27 @@ impl Settings {
                                 submenu.map(|e| e.map(Message::Power)),
                             )
                         }),
+                    self.modules_quick_setting_button(theme, modules),
+                    do_not_disturb.map(|do_not_disturb| {
+                        (
+                            quick_setting_button(
+                                theme,
+                                if do_not_disturb == DoNotDisturb::Off {
+                                    StaticIcon::Bell
+                                } else {
+                                    StaticIcon::BellOff
+                                },
+                                "Do Not Disturb".to_owned(),
+                                (do_not_disturb == DoNotDisturb::Automatic)
+                                    .then(|| "Scheduled".to_owned()),
+                                do_not_disturb == DoNotDisturb::Manual,
+                                Message::ToggleDoNotDisturb,
+                                None,
+                                None,
+                            ),
+                            None,
+                        )
+                    }),
                 ]
                 .into_iter()
                 .flatten()
@@ -537,12 +619,16 @@ impl Settings {
             );
 
             let (top_sink_slider, bottom_sink_slider) = match position {
-                Position::Top => (sink_slider.map(|e| e.map(Message::Audio)), None),
                 Position::Bottom => (None, sink_slider.map(|e| e.map(Message::Audio))),
+                Position::Top | Position::Left | Position::Right => {
+                    (sink_slider.map(|e| e.map(Message::Audio)), None)
+                }
             };
             let (top_source_slider, bottom_source_slider) = match position {
-                Position::Top => (source_slider.map(|e| e.map(Message::Audio)), None),
                 Position::Bottom => (None, source_slider.map(|e| e.map(Message::Audio))),
+                Position::Top | Position::Left | Position::Right => {
+                    (source_slider.map(|e| e.map(Message::Audio)), None)
+                }
             };
 
             Column::new()
@@ -591,11 +677,98 @@ impl Settings {
                         .map(|e| e.map(Message::Brightness)),
                 )
                 .push(quick_settings)
+                .push_maybe(self.profile_selector(theme))
                 .spacing(theme.space.md)
                 .into()
         }
     }
 
+    fn modules_quick_setting_button<'a>(
+        &self,
+        theme: &'a AshellTheme,
+        modules: Vec<(ModuleName, bool)>,
+    ) -> Option<(Element<'a, Message>, Option<Element<'a, Message>>)> {
+        if modules.is_empty() {
+            return None;
+        }
+
+        let hidden = modules.iter().filter(|(_, visible)| !visible).count();
+
+        Some((
+            quick_setting_button(
+                theme,
+                if hidden > 0 {
+                    StaticIcon::EyeClosed
+                } else {
+                    StaticIcon::EyeOpened
+                },
+                "Modules".to_owned(),
+                (hidden > 0).then(|| format!("{hidden} hidden")),
+                hidden > 0,
+                Message::ToggleSubMenu(SubMenu::Modules),
+                None,
+                Some((
+                    SubMenu::Modules,
+                    self.sub_menu,
+                    Message::ToggleSubMenu(SubMenu::Modules),
+                )),
+            ),
+            self.sub_menu
+                .filter(|menu_type| *menu_type == SubMenu::Modules)
+                .map(|_| {
+                    container(scrollable(
+                        Column::with_children(
+                            modules
+                                .into_iter()
+                                .map(|(module, visible)| {
+                                    row!(
+                                        text(module.name().to_owned()).width(Length::Fill),
+                                        toggler(visible)
+                                            .on_toggle(move |_| {
+                                                Message::ToggleModule(module.clone())
+                                            })
+                                            .width(Length::Shrink),
+                                    )
+                                    .into()
+                                })
+                                .collect::<Vec<Element<'a, Message>>>(),
+                        )
+                        .spacing(theme.space.xs)
+                        .padding([0, theme.space.md, 0, theme.space.xs]),
+                    ))
+                    .height(Length::Shrink)
+                    .max_height(300)
+                    .into()
+                }),
+        ))
+    }
+
+    fn profile_selector<'a>(&'a self, theme: &'a AshellTheme) -> Option<Element<'a, Message>> {
+        if self.profiles.is_empty() {
+            return None;
+        }
+
+        let options = std::iter::once(DEFAULT_PROFILE.to_owned())
+            .chain(self.profiles.iter().cloned())
+            .collect::<Vec<_>>();
+        let selected = self
+            .active_profile
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_owned());
+
+        Some(
+            row!(
+                text("Profile").width(Length::Fill),
+                pick_list(options, Some(selected), Message::SelectProfile)
+                    .text_size(theme.font_size.sm)
+                    .padding([theme.space.xxs, theme.space.xs]),
+            )
+            .align_y(Alignment::Center)
+            .spacing(theme.space.xs)
+            .into(),
+        )
+    }
+
     pub fn view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
         let mut row = Row::new();
 
@@ -687,11 +860,8 @@ impl Settings {
 
     pub fn subscription(&self) -> Subscription<Message> {
         Subscription::batch(vec![
-            self.power.subscription().map(Message::Power),
-            self.audio.subscription().map(Message::Audio),
             self.brightness.subscription().map(Message::Brightness),
-            self.network.subscription().map(Message::Network),
-            self.bluetooth.subscription().map(Message::Bluetooth),
+            self.displays.subscription().map(Message::Displays),
         ])
     }
 }
@@ -824,7 +994,11 @@ fn quick_setting_button<'a, Msg: Clone + 'static, I: Icon>(
     .height(Length::Fixed(50.));
 
     if let Some(on_right_press) = on_right_press {
-        MouseArea::new(btn).on_right_press(on_right_press).into()
+        long_press(
+            MouseArea::new(btn).on_right_press(on_right_press.clone()),
+            on_right_press,
+        )
+        .into()
     } else {
         btn.into()
     }
@@ -1,6 +1,7 @@
 use super::{SubMenu, quick_setting_button};
 use crate::{
     components::icons::{StaticIcon, icon, icon_button},
+    long_press::long_press,
     services::{
         ReadOnlyService, Service, ServiceEvent,
         network::{
@@ -12,7 +13,7 @@ use crate::{
     utils::IndicatorState,
 };
 use iced::{
-    Alignment, Element, Length, Subscription, Task, Theme,
+    Alignment, Element, Length, Task, Theme,
     widget::{
         Column, MouseArea, button, column, container, horizontal_rule, row, scrollable, text,
         toggler,
@@ -183,7 +184,10 @@ impl NetworkSettings {
                     }
                     Action::None
                 }
-                _ => Action::None,
+                ServiceEvent::Error(_) => {
+                    self.service = None;
+                    Action::None
+                }
             },
             Message::ToggleAirplaneMode => match self.service.as_mut() {
                 Some(service) => Action::CloseSubMenu(
@@ -313,9 +317,11 @@ impl NetworkSettings {
                     );
 
                 Some(
-                    MouseArea::new(content)
-                        .on_right_press(Message::OpenMore)
-                        .into(),
+                    long_press(
+                        MouseArea::new(content).on_right_press(Message::OpenMore),
+                        Message::OpenMore,
+                    )
+                    .into(),
                 )
             }
         })
@@ -659,8 +665,4 @@ impl NetworkSettings {
             main.into()
         }
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        NetworkService::subscribe().map(Message::Event)
-    }
 }
@@ -15,7 +15,7 @@ use crate::{
     utils::{self, IndicatorState, format_duration},
 };
 use iced::{
-    Alignment, Element, Length, Subscription, Task, Theme,
+    Alignment, Element, Length, Task, Theme,
     alignment::Vertical,
     widget::{Column, Row, button, column, container, horizontal_rule, row, text},
 };
@@ -124,7 +124,10 @@ impl PowerSettings {
                     }
                     Action::None
                 }
-                ServiceEvent::Error(_) => Action::None,
+                ServiceEvent::Error(_) => {
+                    self.service = None;
+                    Action::None
+                }
             },
             Message::TogglePeripheralMenu => Action::TogglePeripheralMenu,
             Message::TogglePowerProfile => match self.service.as_mut() {
@@ -493,8 +496,4 @@ impl PowerSettings {
             }
         })
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        UPowerService::subscribe().map(Message::Event)
-    }
 }
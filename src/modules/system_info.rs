@@ -1,25 +1,35 @@
 use crate::{
     components::icons::{StaticIcon, icon},
     config::{SystemInfoIndicator, SystemInfoModuleConfig},
+    services::registry::SharedSystem,
     theme::AshellTheme,
 };
 use iced::{
     Alignment, Element, Length, Subscription, Theme,
-    time::every,
+    futures::SinkExt,
+    stream::channel,
     widget::{Column, Row, column, container, horizontal_rule, row, text},
 };
 use itertools::Itertools;
-use std::time::{Duration, Instant};
-use sysinfo::{Components, Disks, Networks, System};
+use std::{
+    any::TypeId,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use sysinfo::{Components, Disks, Networks};
+use tokio::time::sleep;
+
+const INTERVAL: Duration = Duration::from_secs(5);
 
-struct NetworkData {
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkData {
     ip: String,
     download_speed: u32,
     upload_speed: u32,
-    last_check: Instant,
 }
 
-struct SystemInfoData {
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemInfoData {
     pub cpu_usage: u32,
     pub memory_usage: u32,
     pub memory_swap_usage: u32,
@@ -28,172 +38,218 @@ struct SystemInfoData {
     pub network: Option<NetworkData>,
 }
 
-fn get_system_info(
-    system: &mut System,
-    components: &mut Components,
-    disks: &mut Disks,
-    (networks, last_check): (&mut Networks, Option<Instant>),
-    temperature_sensor: &str,
-) -> SystemInfoData {
-    system.refresh_memory();
-    system.refresh_cpu_specifics(sysinfo::CpuRefreshKind::everything());
-
-    components.refresh(true);
-    disks.refresh(true);
-    networks.refresh(true);
-
-    let cpu_usage = system.global_cpu_usage().floor() as u32;
-    let memory_usage = ((system.total_memory() - system.available_memory()) as f32
-        / system.total_memory() as f32
-        * 100.) as u32;
-
-    let memory_swap_usage = ((system.total_swap() - system.free_swap()) as f32
-        / system.total_swap() as f32
-        * 100.) as u32;
-
-    let temperature = components
-        .iter()
-        .find(|c| c.label() == temperature_sensor)
-        .and_then(|c| c.temperature().map(|t| t as i32));
-
-    let disks = disks
-        .into_iter()
-        .filter(|d| !d.is_removable() && d.total_space() != 0)
-        .map(|d| {
-            (
-                d.mount_point().to_string_lossy().to_string(),
-                (((d.total_space() - d.available_space()) as f32) / d.total_space() as f32 * 100.)
-                    as u32,
-            )
-        })
-        .sorted_by(|a, b| a.0.cmp(&b.0))
-        .collect::<Vec<_>>();
-
-    let elapsed = last_check.map(|v| v.elapsed().as_secs());
-
-    let network = networks
-        .iter()
-        .filter(|(name, _)| {
-            name.contains("en")
-                || name.contains("eth")
-                || name.contains("wl")
-                || name.contains("wlan")
-        })
-        .sorted_by_key(|(name, _)| {
-            if name.contains("en") {
-                return 0;
-            }
+/// Sources of the system info refreshed by the samples, the closed menu
+/// only needs the ones of the indicators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+struct Sources {
+    cpu: bool,
+    memory: bool,
+    temperature: bool,
+    disks: bool,
+    network: bool,
+}
 
-            if name.contains("eth") {
-                return 1;
-            }
+impl Sources {
+    const ALL: Self = Self {
+        cpu: true,
+        memory: true,
+        temperature: true,
+        disks: true,
+        network: true,
+    };
+
+    fn of<'a>(indicators: impl IntoIterator<Item = &'a SystemInfoIndicator>) -> Self {
+        indicators
+            .into_iter()
+            .fold(Self::default(), |mut sources, indicator| {
+                match indicator {
+                    SystemInfoIndicator::Cpu => sources.cpu = true,
+                    SystemInfoIndicator::Memory | SystemInfoIndicator::MemorySwap => {
+                        sources.memory = true
+                    }
+                    SystemInfoIndicator::Temperature => sources.temperature = true,
+                    SystemInfoIndicator::Disk(_) => sources.disks = true,
+                    SystemInfoIndicator::IpAddress
+                    | SystemInfoIndicator::DownloadSpeed
+                    | SystemInfoIndicator::UploadSpeed => sources.network = true,
+                }
+                sources
+            })
+    }
+}
+
+/// Keeps the sources between the samples, the usage of the cpu and the
+/// network speeds are measured since the previous refresh.
+struct Sampler {
+    system: SharedSystem,
+    components: Components,
+    disks: Disks,
+    networks: Networks,
+    last_check: Option<Instant>,
+    data: SystemInfoData,
+}
 
-            if name.contains("wl") {
-                return 2;
+impl Sampler {
+    fn new(system: SharedSystem) -> Self {
+        Self {
+            system,
+            components: Components::new_with_refreshed_list(),
+            disks: Disks::new_with_refreshed_list(),
+            networks: Networks::new_with_refreshed_list(),
+            last_check: None,
+            data: SystemInfoData::default(),
+        }
+    }
+
+    /// Refreshes the given sources, the others keep their last values.
+    fn sample(&mut self, sources: Sources, temperature_sensor: &str) -> SystemInfoData {
+        if let Ok(mut system) = self.system.lock() {
+            if sources.cpu {
+                system.refresh_cpu_specifics(sysinfo::CpuRefreshKind::everything());
+                self.data.cpu_usage = system.global_cpu_usage().floor() as u32;
             }
 
-            if name.contains("wlan") {
-                return 3;
+            if sources.memory {
+                system.refresh_memory();
+                self.data.memory_usage = ((system.total_memory() - system.available_memory())
+                    as f32
+                    / system.total_memory() as f32
+                    * 100.) as u32;
+                self.data.memory_swap_usage = ((system.total_swap() - system.free_swap()) as f32
+                    / system.total_swap() as f32
+                    * 100.) as u32;
             }
+        }
 
-            99
-        })
-        .fold(
-            (None, 0, 0),
-            |(first_ip, total_received, total_transmitted), (_, data)| {
-                let ip = first_ip.or_else(|| {
-                    data.ip_networks()
-                        .iter()
-                        .sorted_by(|a, b| a.addr.cmp(&b.addr))
-                        .next()
-                        .map(|ip| ip.addr)
-                });
-
-                let received = data.received();
-                let transmitted = data.transmitted();
-
-                (
-                    first_ip.or(ip),
-                    total_received + received,
-                    total_transmitted + transmitted,
-                )
-            },
-        );
+        if sources.temperature {
+            self.components.refresh(true);
+            self.data.temperature = self
+                .components
+                .iter()
+                .find(|c| c.label() == temperature_sensor)
+                .and_then(|c| c.temperature().map(|t| t as i32));
+        }
 
-    let network_speed = |value: u64| {
-        match elapsed {
-            None | Some(0) => 0, // avoid division by zero
-            Some(elapsed) => (value / 1000) as u32 / elapsed as u32,
+        if sources.disks {
+            self.disks.refresh(true);
+            self.data.disks = self
+                .disks
+                .into_iter()
+                .filter(|d| !d.is_removable() && d.total_space() != 0)
+                .map(|d| {
+                    (
+                        d.mount_point().to_string_lossy().to_string(),
+                        (((d.total_space() - d.available_space()) as f32) / d.total_space() as f32
+                            * 100.) as u32,
+                    )
+                })
+                .sorted_by(|a, b| a.0.cmp(&b.0))
+                .collect::<Vec<_>>();
         }
-    };
 
-    SystemInfoData {
-        cpu_usage,
-        memory_usage,
-        memory_swap_usage,
-        temperature,
-        disks,
-        network: network.0.map(|ip| NetworkData {
+        if sources.network {
+            self.networks.refresh(true);
+            self.data.network = self.network();
+            self.last_check = Some(Instant::now());
+        }
+
+        self.data.clone()
+    }
+
+    fn network(&self) -> Option<NetworkData> {
+        let elapsed = self.last_check.map(|v| v.elapsed().as_secs());
+
+        let network = self
+            .networks
+            .iter()
+            .filter(|(name, _)| {
+                name.contains("en")
+                    || name.contains("eth")
+                    || name.contains("wl")
+                    || name.contains("wlan")
+            })
+            .sorted_by_key(|(name, _)| {
+                if name.contains("en") {
+                    return 0;
+                }
+
+                if name.contains("eth") {
+                    return 1;
+                }
+
+                if name.contains("wl") {
+                    return 2;
+                }
+
+                if name.contains("wlan") {
+                    return 3;
+                }
+
+                99
+            })
+            .fold(
+                (None, 0, 0),
+                |(first_ip, total_received, total_transmitted), (_, data)| {
+                    let ip = first_ip.or_else(|| {
+                        data.ip_networks()
+                            .iter()
+                            .sorted_by(|a, b| a.addr.cmp(&b.addr))
+                            .next()
+                            .map(|ip| ip.addr)
+                    });
+
+                    let received = data.received();
+                    let transmitted = data.transmitted();
+
+                    (
+                        first_ip.or(ip),
+                        total_received + received,
+                        total_transmitted + transmitted,
+                    )
+                },
+            );
+
+        let network_speed = |value: u64| {
+            match elapsed {
+                None | Some(0) => 0, // avoid division by zero
+                Some(elapsed) => (value / 1000) as u32 / elapsed as u32,
+            }
+        };
+
+        network.0.map(|ip| NetworkData {
             ip: ip.to_string(),
             download_speed: network_speed(network.1),
             upload_speed: network_speed(network.2),
-            last_check: Instant::now(),
-        }),
+        })
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    Update,
+    Update(SystemInfoData),
 }
 
 pub struct SystemInfo {
     config: SystemInfoModuleConfig,
-    system: System,
-    components: Components,
-    disks: Disks,
-    networks: Networks,
-    data: SystemInfoData,
+    /// Built by the first sample, the scan of the sources is slow
+    sampler: Arc<Mutex<Option<Sampler>>>,
+    system: SharedSystem,
+    data: Option<SystemInfoData>,
 }
 
 impl SystemInfo {
-    pub fn new(config: SystemInfoModuleConfig) -> Self {
-        let mut system = System::new();
-        let mut components = Components::new_with_refreshed_list();
-        let mut disks = Disks::new_with_refreshed_list();
-        let mut networks = Networks::new_with_refreshed_list();
-        let data = get_system_info(
-            &mut system,
-            &mut components,
-            &mut disks,
-            (&mut networks, None),
-            &config.temperature.sensor,
-        );
-
+    pub fn new(config: SystemInfoModuleConfig, system: SharedSystem) -> Self {
         Self {
             config,
+            sampler: Arc::default(),
             system,
-            components,
-            disks,
-            data,
-            networks,
+            data: None,
         }
     }
 
     pub fn update(&mut self, message: Message) {
         match message {
-            Message::Update => {
-                self.data = get_system_info(
-                    &mut self.system,
-                    &mut self.components,
-                    &mut self.disks,
-                    (
-                        &mut self.networks,
-                        self.data.network.as_ref().map(|n| n.last_check),
-                    ),
-                    &self.config.temperature.sensor,
-                );
-            }
+            Message::Update(data) => self.data = Some(data),
         }
     }
 
@@ -253,6 +309,16 @@ impl SystemInfo {
     }
 
     pub fn menu_view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
+        let Some(data) = self.data.as_ref() else {
+            return column!(
+                text("System Info").size(theme.font_size.lg),
+                horizontal_rule(1),
+                text("Loading...")
+            )
+            .spacing(theme.space.xs)
+            .into();
+        };
+
         column!(
             text("System Info").size(theme.font_size.lg),
             horizontal_rule(1),
@@ -261,21 +327,21 @@ impl SystemInfo {
                     theme,
                     StaticIcon::Cpu,
                     "CPU Usage".to_string(),
-                    format!("{}%", self.data.cpu_usage),
+                    format!("{}%", data.cpu_usage),
                 ))
                 .push(Self::info_element(
                     theme,
                     StaticIcon::Mem,
                     "Memory Usage".to_string(),
-                    format!("{}%", self.data.memory_usage),
+                    format!("{}%", data.memory_usage),
                 ))
                 .push(Self::info_element(
                     theme,
                     StaticIcon::Mem,
                     "Swap memory Usage".to_string(),
-                    format!("{}%", self.data.memory_swap_usage),
+                    format!("{}%", data.memory_swap_usage),
                 ))
-                .push_maybe(self.data.temperature.map(|temp| {
+                .push_maybe(data.temperature.map(|temp| {
                     Self::info_element(
                         theme,
                         StaticIcon::Temp,
@@ -285,8 +351,7 @@ impl SystemInfo {
                 }))
                 .push(
                     Column::with_children(
-                        self.data
-                            .disks
+                        data.disks
                             .iter()
                             .map(|(mount_point, usage)| {
                                 Self::info_element(
@@ -300,7 +365,7 @@ impl SystemInfo {
                     )
                     .spacing(theme.space.xxs),
                 )
-                .push_maybe(self.data.network.as_ref().map(|network| {
+                .push_maybe(data.network.as_ref().map(|network| {
                     Column::with_children(vec![
                         Self::info_element(
                             theme,
@@ -337,74 +402,71 @@ impl SystemInfo {
         .into()
     }
 
-    pub fn view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
-        let indicators = self.config.indicators.iter().filter_map(|i| match i {
+    pub fn view<'a>(
+        &'a self,
+        theme: &AshellTheme,
+        config: Option<&'a SystemInfoModuleConfig>,
+    ) -> Element<'a, Message> {
+        let config = config.unwrap_or(&self.config);
+        // shown until the first sample is taken
+        let Some(data) = self.data.as_ref() else {
+            return icon(StaticIcon::Cpu).into();
+        };
+        let indicators = config.indicators.iter().filter_map(|i| match i {
             SystemInfoIndicator::Cpu => Some(Self::indicator_info_element(
                 theme,
                 StaticIcon::Cpu,
-                self.data.cpu_usage,
+                data.cpu_usage,
                 "%",
-                Some((
-                    self.config.cpu.warn_threshold,
-                    self.config.cpu.alert_threshold,
-                )),
+                Some((config.cpu.warn_threshold, config.cpu.alert_threshold)),
                 None,
             )),
             SystemInfoIndicator::Memory => Some(Self::indicator_info_element(
                 theme,
                 StaticIcon::Mem,
-                self.data.memory_usage,
+                data.memory_usage,
                 "%",
-                Some((
-                    self.config.memory.warn_threshold,
-                    self.config.memory.alert_threshold,
-                )),
+                Some((config.memory.warn_threshold, config.memory.alert_threshold)),
                 None,
             )),
             SystemInfoIndicator::MemorySwap => Some(Self::indicator_info_element(
                 theme,
                 StaticIcon::Mem,
-                self.data.memory_swap_usage,
+                data.memory_swap_usage,
                 "%",
-                Some((
-                    self.config.memory.warn_threshold,
-                    self.config.memory.alert_threshold,
-                )),
+                Some((config.memory.warn_threshold, config.memory.alert_threshold)),
                 Some("swap"),
             )),
-            SystemInfoIndicator::Temperature => self.data.temperature.map(|temperature| {
+            SystemInfoIndicator::Temperature => data.temperature.map(|temperature| {
                 Self::indicator_info_element(
                     theme,
                     StaticIcon::Temp,
                     temperature,
                     "°C",
                     Some((
-                        self.config.temperature.warn_threshold,
-                        self.config.temperature.alert_threshold,
+                        config.temperature.warn_threshold,
+                        config.temperature.alert_threshold,
                     )),
                     None,
                 )
             }),
-            SystemInfoIndicator::Disk(config) => {
-                self.data.disks.iter().find_map(|(disk_mount, disk)| {
-                    if disk_mount == &config.path {
+            SystemInfoIndicator::Disk(disk_config) => {
+                data.disks.iter().find_map(|(disk_mount, disk)| {
+                    if disk_mount == &disk_config.path {
                         Some(Self::indicator_info_element(
                             theme,
                             StaticIcon::Drive,
                             *disk,
                             "%",
-                            Some((
-                                self.config.disk.warn_threshold,
-                                self.config.disk.alert_threshold,
-                            )),
-                            Some(config.name.as_deref().unwrap_or(disk_mount)),
+                            Some((config.disk.warn_threshold, config.disk.alert_threshold)),
+                            Some(disk_config.name.as_deref().unwrap_or(disk_mount)),
                         ))
                     } else {
                         None
                     }
                 })
             }
-            SystemInfoIndicator::IpAddress => self.data.network.as_ref().map(|network| {
+            SystemInfoIndicator::IpAddress => data.network.as_ref().map(|network| {
                 Self::indicator_info_element(
                     theme,
                     StaticIcon::IpAddress,
@@ -414,7 +476,7 @@ impl SystemInfo {
                     None,
                 )
             }),
-            SystemInfoIndicator::DownloadSpeed => self.data.network.as_ref().map(|network| {
+            SystemInfoIndicator::DownloadSpeed => data.network.as_ref().map(|network| {
                 Self::indicator_info_element(
                     theme,
                     StaticIcon::DownloadSpeed,
@@ -432,7 +494,7 @@ impl SystemInfo {
                     None,
                 )
             }),
-            SystemInfoIndicator::UploadSpeed => self.data.network.as_ref().map(|network| {
+            SystemInfoIndicator::UploadSpeed => data.network.as_ref().map(|network| {
                 Self::indicator_info_element(
                     theme,
                     StaticIcon::UploadSpeed,
@@ -458,7 +520,56 @@ impl SystemInfo {
             .into()
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        every(Duration::from_secs(5)).map(|_| Message::Update)
+    /// Samples the sources needed by the indicators of the bars, or all of
+    /// them while the menu is open, and only reports the changed values.
+    pub fn subscription<'a>(
+        &self,
+        menu_open: bool,
+        bars: impl Iterator<Item = &'a SystemInfoModuleConfig>,
+    ) -> Subscription<Message> {
+        let sources = if menu_open {
+            Sources::ALL
+        } else {
+            Sources::of(
+                self.config
+                    .indicators
+                    .iter()
+                    .chain(bars.flat_map(|bar| &bar.indicators)),
+            )
+        };
+        let sampler = self.sampler.clone();
+        let system = self.system.clone();
+        let sensor = self.config.temperature.sensor.clone();
+
+        Subscription::run_with_id(
+            (TypeId::of::<Self>(), sources, sensor.clone()),
+            channel(1, async move |mut output| {
+                let mut current = None;
+
+                loop {
+                    let sampler = sampler.clone();
+                    let system = system.clone();
+                    let sensor = sensor.clone();
+                    // sysinfo blocks, the first sample even scans all the sources
+                    let data = tokio::task::spawn_blocking(move || {
+                        sampler.lock().ok().map(|mut sampler| {
+                            sampler
+                                .get_or_insert_with(|| Sampler::new(system))
+                                .sample(sources, &sensor)
+                        })
+                    })
+                    .await;
+                    let Ok(Some(data)) = data else {
+                        return;
+                    };
+                    if current.as_ref() != Some(&data) {
+                        current = Some(data.clone());
+                        let _ = output.send(Message::Update(data)).await;
+                    }
+
+                    sleep(INTERVAL).await;
+                }
+            }),
+        )
     }
 }
@@ -0,0 +1,311 @@
+use crate::{
+    config::TaskbarModuleConfig,
+    services::{
+        ReadOnlyService, Service, ServiceEvent,
+        compositor::{CompositorCommand, CompositorService, CompositorWindow},
+        tray::{TrayIcon, get_icon_from_name},
+    },
+    theme::AshellTheme,
+};
+use iced::{
+    Alignment, Border, Element, Length, Task, Theme,
+    mouse::Interaction,
+    widget::{Image, Row, Svg, container, mouse_area, text},
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ServiceEvent(ServiceEvent<CompositorService>),
+    ConfigReloaded(TaskbarModuleConfig),
+    /// The button went down on a group, a drag or a click starts
+    Press(String),
+    /// The cursor entered a group, the dragged one takes its place
+    Enter(String),
+    /// The button went up on a group, a click when nothing was dragged
+    Release(String),
+    Close(String),
+    /// The cursor left the taskbar
+    Leave,
+}
+
+/// Group of windows shown as a single icon, the windows of an application
+/// or a single window when they are not grouped.
+struct Group<'a> {
+    key: String,
+    class: &'a str,
+    windows: Vec<&'a CompositorWindow>,
+}
+
+/// Open windows of the monitor of the bar, focused on click and closed with
+/// a middle click.
+pub struct Taskbar {
+    config: TaskbarModuleConfig,
+    service: Option<CompositorService>,
+    /// Keys of the groups in the order they are shown, changed by dragging
+    order: Vec<String>,
+    /// Group being dragged, and whether it has moved
+    drag: Option<(String, bool)>,
+    /// Icons of the application classes, looked up once
+    icons: HashMap<String, Option<TrayIcon>>,
+}
+
+impl Taskbar {
+    pub fn new(config: TaskbarModuleConfig) -> Self {
+        Self {
+            config,
+            service: None,
+            order: Vec::new(),
+            drag: None,
+            icons: HashMap::new(),
+        }
+    }
+
+    fn key(&self, window: &CompositorWindow) -> String {
+        if self.config.group_by_app {
+            window.class.clone()
+        } else {
+            window.id.clone()
+        }
+    }
+
+    /// Follows the open windows, the new groups are added at the end.
+    fn sync(&mut self) {
+        let Some(service) = self.service.as_ref() else {
+            return;
+        };
+        let keys = service
+            .windows
+            .iter()
+            .map(|window| self.key(window))
+            .collect::<Vec<_>>();
+
+        self.order.retain(|key| keys.contains(key));
+        for key in keys {
+            if !self.order.contains(&key) {
+                self.order.push(key);
+            }
+        }
+
+        // the icons of the closed applications are dropped
+        self.icons
+            .retain(|class, _| service.windows.iter().any(|window| &window.class == class));
+        for window in &service.windows {
+            self.icons
+                .entry(window.class.clone())
+                .or_insert_with(|| get_icon_from_name(&window.class.to_lowercase()));
+        }
+    }
+
+    /// Groups of the windows shown on the given monitor, in their order.
+    fn groups(&self, monitor: Option<&str>) -> Vec<Group<'_>> {
+        let Some(service) = self.service.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut groups = Vec::<Group>::new();
+        for window in service.windows.iter().filter(|window| {
+            self.config.all_monitors
+                || match (monitor, window.monitor.as_deref()) {
+                    (Some(monitor), Some(window_monitor)) => monitor.contains(window_monitor),
+                    // shown everywhere when the monitor is not known
+                    _ => true,
+                }
+        }) {
+            let key = self.key(window);
+            match groups.iter_mut().find(|group| group.key == key) {
+                Some(group) => group.windows.push(window),
+                None => groups.push(Group {
+                    key,
+                    class: &window.class,
+                    windows: vec![window],
+                }),
+            }
+        }
+        groups.sort_by_key(|group| {
+            self.order
+                .iter()
+                .position(|key| key == &group.key)
+                .unwrap_or(usize::MAX)
+        });
+
+        groups
+    }
+
+    fn active_window(&self) -> Option<&str> {
+        self.service
+            .as_ref()?
+            .active_window
+            .as_ref()
+            .map(|window| window.address.as_str())
+    }
+
+    /// Window of the group a click or a middle click applies to, the one
+    /// after the focused window so that clicks cycle through the group.
+    fn target(&self, key: &str, next: bool) -> Option<String> {
+        let windows = self
+            .service
+            .as_ref()?
+            .windows
+            .iter()
+            .filter(|window| self.key(window) == key)
+            .collect::<Vec<_>>();
+        let active = windows
+            .iter()
+            .position(|window| Some(window.id.as_str()) == self.active_window());
+
+        let index = match active {
+            Some(index) if next => (index + 1) % windows.len(),
+            Some(index) => index,
+            None => 0,
+        };
+
+        windows.get(index).map(|window| window.id.clone())
+    }
+
+    fn command(&mut self, command: CompositorCommand) -> Task<Message> {
+        match self.service.as_mut() {
+            Some(service) => service.command(command).map(Message::ServiceEvent),
+            None => Task::none(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ServiceEvent(event) => {
+                match event {
+                    ServiceEvent::Init(service) => self.service = Some(service),
+                    ServiceEvent::Update(event) => {
+                        if let Some(service) = self.service.as_mut() {
+                            service.update(event);
+                        }
+                    }
+                    ServiceEvent::Error(_) => {}
+                }
+                self.sync();
+
+                Task::none()
+            }
+            Message::ConfigReloaded(config) => {
+                if config.group_by_app != self.config.group_by_app {
+                    self.order.clear();
+                }
+                self.config = config;
+                self.sync();
+
+                Task::none()
+            }
+            Message::Press(key) => {
+                self.drag = Some((key, false));
+
+                Task::none()
+            }
+            Message::Enter(key) => {
+                if let Some((dragged, moved)) = self.drag.as_mut()
+                    && *dragged != key
+                    && let Some(from) = self.order.iter().position(|k| k == dragged)
+                    && let Some(to) = self.order.iter().position(|k| k == &key)
+                {
+                    let dragged = self.order.remove(from);
+                    self.order.insert(to, dragged);
+                    *moved = true;
+                }
+
+                Task::none()
+            }
+            Message::Release(key) => match self.drag.take() {
+                Some((_, true)) => Task::none(),
+                _ => match self.target(&key, true) {
+                    Some(id) => self.command(CompositorCommand::FocusWindow(id)),
+                    None => Task::none(),
+                },
+            },
+            Message::Close(key) => match self.target(&key, false) {
+                Some(id) => self.command(CompositorCommand::CloseWindow(id)),
+                None => Task::none(),
+            },
+            Message::Leave => {
+                self.drag = None;
+
+                Task::none()
+            }
+        }
+    }
+
+    fn icon_view<'a>(&self, class: &str, theme: &AshellTheme) -> Element<'a, Message> {
+        let size = Length::Fixed(theme.font_size.lg as f32);
+
+        match self.icons.get(class).and_then(Option::as_ref) {
+            Some(TrayIcon::Image(handle)) => {
+                Image::new(handle.clone()).width(size).height(size).into()
+            }
+            Some(TrayIcon::Svg(handle)) => Svg::new(handle.clone()).width(size).height(size).into(),
+            // without an icon the class stands in for it
+            None => text(class.chars().take(2).collect::<String>())
+                .size(theme.font_size.sm)
+                .into(),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        theme: &'a AshellTheme,
+        monitor: Option<&str>,
+    ) -> Element<'a, Message> {
+        let active = self.active_window();
+        let dragged = self.drag.as_ref().map(|(key, _)| key.as_str());
+        let radius = theme.radius.sm;
+
+        mouse_area(
+            Row::with_children(self.groups(monitor).into_iter().map(|group| {
+                let is_active = group
+                    .windows
+                    .iter()
+                    .any(|window| Some(window.id.as_str()) == active);
+                let is_dragged = dragged == Some(group.key.as_str());
+                let count = group.windows.len();
+
+                let content = Row::new()
+                    .push(self.icon_view(group.class, theme))
+                    .push_maybe(
+                        (count > 1).then(|| text(count.to_string()).size(theme.font_size.xs)),
+                    )
+                    .spacing(theme.space.xxs)
+                    .align_y(Alignment::Center);
+
+                mouse_area(container(content).padding([2, theme.space.xxs]).style(
+                    move |theme: &Theme| {
+                        let palette = theme.extended_palette();
+                        container::Style {
+                            background: if is_active || is_dragged {
+                                Some(palette.background.weak.color.into())
+                            } else {
+                                None
+                            },
+                            border: Border {
+                                width: if is_active { 1.0 } else { 0.0 },
+                                color: palette.primary.base.color,
+                                radius: radius.into(),
+                            },
+                            ..Default::default()
+                        }
+                    },
+                ))
+                .on_press(Message::Press(group.key.clone()))
+                .on_enter(Message::Enter(group.key.clone()))
+                .on_release(Message::Release(group.key.clone()))
+                .on_middle_press(Message::Close(group.key))
+                .interaction(if dragged.is_some() {
+                    Interaction::Grabbing
+                } else {
+                    Interaction::Pointer
+                })
+                .into()
+            }))
+            .spacing(theme.space.xxs)
+            .align_y(Alignment::Center),
+        )
+        .on_exit(Message::Leave)
+        .into()
+    }
+}
@@ -0,0 +1,391 @@
+use crate::{
+    components::icons::{StaticIcon, icon},
+    config::{TasksBackend, TasksModuleConfig},
+    theme::AshellTheme,
+};
+use anyhow::{Context, bail};
+use chrono::{Days, Local, NaiveDate, NaiveDateTime};
+use iced::{
+    Alignment, Element, Length, Subscription, Task,
+    stream::channel,
+    widget::{Column, button, column, container, horizontal_rule, row, scrollable, text},
+};
+use log::warn;
+use serde::Deserialize;
+use std::{any::TypeId, process::Stdio, time::Duration};
+use tokio::{fs, process::Command, time::sleep};
+
+#[derive(Debug, Clone)]
+pub struct TaskItem {
+    /// Taskwarrior uuid or the todo.txt line
+    pub id: String,
+    pub description: String,
+    pub project: Option<String>,
+    pub due: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+struct TaskwarriorTask {
+    uuid: String,
+    description: String,
+    project: Option<String>,
+    due: Option<String>,
+}
+
+async fn run(cmd: String) -> anyhow::Result<String> {
+    let output = Command::new("bash")
+        .arg("-c")
+        .arg(&cmd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .with_context(|| format!("failed to run {cmd}"))?;
+
+    if !output.status.success() {
+        bail!(
+            "{cmd} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn todo_file(config: &TasksModuleConfig) -> String {
+    shellexpand::tilde(&config.todo_file).into_owned()
+}
+
+/// Reads the `due:YYYY-MM-DD` tag of a todo.txt line.
+fn todo_due(line: &str) -> Option<NaiveDate> {
+    line.split_whitespace()
+        .find_map(|word| word.strip_prefix("due:"))
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+}
+
+fn parse_todo_line(line: &str) -> Option<TaskItem> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with("x ") {
+        return None;
+    }
+
+    let description = trimmed
+        .split_whitespace()
+        // drop `key:value` tags and projects, keep urls
+        .filter(|word| !(word.contains(':') && !word.contains("://")) && !word.starts_with('+'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(TaskItem {
+        id: line.to_owned(),
+        description,
+        project: trimmed
+            .split_whitespace()
+            .find_map(|word| word.strip_prefix('+'))
+            .map(str::to_owned),
+        due: todo_due(trimmed),
+    })
+}
+
+async fn load_tasks(config: &TasksModuleConfig) -> anyhow::Result<Vec<TaskItem>> {
+    let mut tasks = match config.backend {
+        TasksBackend::Taskwarrior => {
+            let export = run(format!("{} status:pending export", config.task_cmd)).await?;
+
+            serde_json::from_str::<Vec<TaskwarriorTask>>(&export)?
+                .into_iter()
+                .map(|task| TaskItem {
+                    id: task.uuid,
+                    description: task.description,
+                    project: task.project,
+                    due: task.due.and_then(|due| {
+                        NaiveDateTime::parse_from_str(&due, "%Y%m%dT%H%M%SZ")
+                            .ok()
+                            .map(|due| due.and_utc().with_timezone(&Local).date_naive())
+                    }),
+                })
+                .collect::<Vec<_>>()
+        }
+        TasksBackend::TodoTxt => fs::read_to_string(todo_file(config))
+            .await?
+            .lines()
+            .filter_map(parse_todo_line)
+            .collect(),
+    };
+
+    // tasks without a due date go last
+    tasks.sort_by_key(|task| (task.due.is_none(), task.due));
+
+    Ok(tasks)
+}
+
+/// Replaces the first occurrence of `line` in the todo.txt file with the result of `edit`.
+async fn edit_todo_line(
+    config: &TasksModuleConfig,
+    line: &str,
+    edit: impl FnOnce(&str) -> String,
+) -> anyhow::Result<()> {
+    let path = todo_file(config);
+    let content = fs::read_to_string(&path).await?;
+
+    let Some(position) = content.lines().position(|current| current == line) else {
+        bail!("task not found in {path}");
+    };
+
+    let mut lines = content.lines().map(str::to_owned).collect::<Vec<_>>();
+    lines[position] = edit(line);
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(&path, content).await?;
+
+    Ok(())
+}
+
+async fn complete_task(config: TasksModuleConfig, id: String) -> anyhow::Result<()> {
+    match config.backend {
+        TasksBackend::Taskwarrior => {
+            run(format!("{} rc.confirmation=off {id} done", config.task_cmd)).await?;
+        }
+        TasksBackend::TodoTxt => {
+            let today = Local::now().date_naive();
+            edit_todo_line(&config, &id, |line| {
+                // the priority is dropped when completing a task
+                let line = match line.as_bytes() {
+                    [b'(', b'A'..=b'Z', b')', b' ', ..] => &line[4..],
+                    _ => line,
+                };
+
+                format!("x {} {line}", today.format("%Y-%m-%d"))
+            })
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn defer_task(config: TasksModuleConfig, id: String) -> anyhow::Result<()> {
+    match config.backend {
+        TasksBackend::Taskwarrior => {
+            run(format!(
+                "{} rc.confirmation=off {id} modify due:{}",
+                config.task_cmd, config.defer
+            ))
+            .await?;
+        }
+        TasksBackend::TodoTxt => {
+            let today = Local::now().date_naive();
+            let tomorrow = today.checked_add_days(Days::new(1)).unwrap_or(today);
+            let due = format!("due:{}", tomorrow.format("%Y-%m-%d"));
+
+            edit_todo_line(&config, &id, |line| {
+                if todo_due(line).is_some() {
+                    line.split_whitespace()
+                        .map(|word| {
+                            if word.starts_with("due:") {
+                                due.as_str()
+                            } else {
+                                word
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                } else {
+                    format!("{line} {due}")
+                }
+            })
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Vec<TaskItem>),
+    Complete(String),
+    Defer(String),
+    Refresh,
+}
+
+pub struct Tasks {
+    config: TasksModuleConfig,
+    tasks: Vec<TaskItem>,
+}
+
+impl Tasks {
+    pub fn new(config: TasksModuleConfig) -> Self {
+        Self {
+            config,
+            tasks: Vec::new(),
+        }
+    }
+
+    fn due_today(&self) -> usize {
+        let today = Local::now().date_naive();
+
+        self.tasks
+            .iter()
+            .filter(|task| task.due.is_some_and(|due| due <= today))
+            .count()
+    }
+
+    fn refresh(&self) -> Task<Message> {
+        let config = self.config.clone();
+
+        Task::perform(
+            async move {
+                load_tasks(&config).await.unwrap_or_else(|e| {
+                    warn!("Failed to load tasks: {e}");
+                    Vec::new()
+                })
+            },
+            Message::Loaded,
+        )
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Loaded(tasks) => {
+                self.tasks = tasks;
+
+                Task::none()
+            }
+            Message::Complete(id) => {
+                let config = self.config.clone();
+
+                Task::perform(
+                    async move {
+                        if let Err(e) = complete_task(config, id).await {
+                            warn!("Failed to complete task: {e}");
+                        }
+                    },
+                    |_| Message::Refresh,
+                )
+            }
+            Message::Defer(id) => {
+                let config = self.config.clone();
+
+                Task::perform(
+                    async move {
+                        if let Err(e) = defer_task(config, id).await {
+                            warn!("Failed to defer task: {e}");
+                        }
+                    },
+                    |_| Message::Refresh,
+                )
+            }
+            Message::Refresh => self.refresh(),
+        }
+    }
+
+    pub fn view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
+        let due_today = self.due_today();
+
+        row!(icon(StaticIcon::Tasks))
+            .push_maybe((due_today > 0).then(|| text(due_today).size(theme.font_size.sm)))
+            .align_y(Alignment::Center)
+            .spacing(theme.space.xxs)
+            .into()
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let today = Local::now().date_naive();
+
+        column!(
+            text(format!(
+                "{} due today, {} pending",
+                self.due_today(),
+                self.tasks.len()
+            ))
+            .size(theme.font_size.lg),
+            horizontal_rule(1),
+            if self.tasks.is_empty() {
+                Into::<Element<'_, _>>::into(
+                    container(text("Nothing to do")).padding(theme.space.xs),
+                )
+            } else {
+                container(scrollable(
+                    Column::with_children(
+                        self.tasks
+                            .iter()
+                            .map(|task| {
+                                let details = [
+                                    task.project.clone(),
+                                    task.due.map(|due| match due {
+                                        due if due < today => {
+                                            format!("overdue {}", due.format("%d %b"))
+                                        }
+                                        due if due == today => "today".to_owned(),
+                                        due => due.format("%d %b").to_string(),
+                                    }),
+                                ]
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>()
+                                .join(" · ");
+
+                                row!(
+                                    column!(
+                                        text(task.description.clone())
+                                            .wrapping(text::Wrapping::WordOrGlyph)
+                                            .width(Length::Fill),
+                                    )
+                                    .push_maybe(
+                                        (!details.is_empty())
+                                            .then(|| text(details).size(theme.font_size.xs)),
+                                    )
+                                    .width(Length::Fill),
+                                    button(text("Defer").size(theme.font_size.sm))
+                                        .style(theme.ghost_button_style())
+                                        .padding(theme.space.xs)
+                                        .on_press(Message::Defer(task.id.clone())),
+                                    button(text("Done").size(theme.font_size.sm))
+                                        .style(theme.ghost_button_style())
+                                        .padding(theme.space.xs)
+                                        .on_press(Message::Complete(task.id.clone())),
+                                )
+                                .align_y(Alignment::Center)
+                                .spacing(theme.space.xs)
+                                .into()
+                            })
+                            .collect::<Vec<Element<'_, _>>>(),
+                    )
+                    .spacing(theme.space.xs)
+                    .padding([0, theme.space.md, 0, 0]),
+                ))
+                .max_height(400)
+                .into()
+            }
+        )
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let config = self.config.clone();
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            (id, config.clone()),
+            channel(10, async move |mut output| {
+                let interval = Duration::from_secs(config.interval.max(10));
+
+                loop {
+                    match load_tasks(&config).await {
+                        Ok(tasks) => {
+                            let _ = output.try_send(Message::Loaded(tasks));
+                        }
+                        Err(e) => {
+                            warn!("Failed to load tasks: {e}");
+                        }
+                    }
+
+                    sleep(interval).await;
+                }
+            }),
+        )
+    }
+}
@@ -60,7 +60,10 @@ impl TrayModule {
 
                     action
                 }
-                ServiceEvent::Error(_) => Action::None,
+                ServiceEvent::Error(_) => {
+                    self.service = None;
+                    Action::None
+                }
             },
             Message::ToggleMenu(menu_type, id, button_ui_ref) => {
                 Action::ToggleMenu(menu_type, id, button_ui_ref)
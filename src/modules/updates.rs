@@ -1,18 +1,45 @@
 use crate::{
     components::icons::{StaticIcon, icon},
-    config::UpdatesModuleConfig,
+    config::{FeedConfig, PackageManager, UpdatesBackend, UpdatesModuleConfig},
+    modules::feeds::{FeedItem, FeedsState, fetch_feed, item_key},
+    scheduler,
+    services::{
+        fwupd,
+        logind::Login1ManagerProxy,
+        network::dbus::{ConnectivityState, NetworkDbus},
+    },
     theme::AshellTheme,
+    utils::{
+        self,
+        launcher::open_url,
+        notification::send_notification,
+        state::{read_state, write_state},
+    },
 };
+use chrono::{Days, Local, NaiveTime};
 use iced::{
     Alignment, Element, Length, Subscription, Task,
     alignment::Horizontal,
+    futures::{
+        StreamExt,
+        future::join_all,
+        stream::{self, BoxStream},
+        stream_select,
+    },
     stream::channel,
     widget::{Column, button, column, container, horizontal_rule, row, scrollable, text},
     window::Id,
 };
-use log::error;
+use log::{debug, error, warn};
 use serde::Deserialize;
-use std::{any::TypeId, convert, process::Stdio, time::Duration};
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+    convert,
+    path::Path,
+    process::Stdio,
+    time::{Duration, Instant},
+};
 use tokio::{process, time::sleep};
 
 #[derive(Deserialize, Debug, Clone)]
@@ -22,6 +49,427 @@ pub struct Update {
     pub to: String,
 }
 
+/// A source of updates, a package manager or the `check_cmd` of the config.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Backend {
+    name: String,
+    check: Check,
+    update_cmd: Option<String>,
+    changelog_cmd: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Check {
+    /// Command printing the updates as `package from -> to`
+    Command(String),
+    /// Firmware updates from the fwupd daemon
+    Fwupd,
+    /// Inputs of the flake in the directory newer than in its lock file
+    Flake(String),
+}
+
+const NEWS_STATE_FILE: &str = "updates_news.json";
+
+const ARCH_NEWS_URL: &str = "https://archlinux.org/feeds/news/";
+
+/// Most recent news kept from all the feeds.
+const MAX_NEWS: usize = 20;
+
+/// Terminal running the updates of the package managers without a `terminal_cmd`.
+const DEFAULT_TERMINAL_CMD: &str = "xdg-terminal-exec {}";
+
+/// Directory of the flake of the `Nix` backend without a `flake`.
+const DEFAULT_FLAKE: &str = "/etc/nixos";
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// How the updates of the package manager are checked.
+fn default_check(manager: PackageManager, flake: &str) -> Check {
+    Check::Command(match manager {
+        PackageManager::Fwupd => return Check::Fwupd,
+        PackageManager::Nix => return Check::Flake(flake.to_owned()),
+        PackageManager::Pacman => "checkupdates".to_owned(),
+        PackageManager::Paru => "paru -Qua".to_owned(),
+        PackageManager::Yay => "yay -Qua".to_owned(),
+        PackageManager::Apt => r#"apt list --upgradable 2>/dev/null | awk '/upgradable from/ {split($1, name, "/"); from = $NF; sub(/]$/, "", from); print name[1], from, "->", $2}'"#.to_owned(),
+        PackageManager::Dnf => r#"dnf check-update -q | awk 'NF == 3 {print $1, $2}' | while read -r package version; do echo "$package $(rpm -q --qf '%{VERSION}-%{RELEASE}' "${package%.*}") -> $version"; done"#.to_owned(),
+        PackageManager::Zypper => r#"zypper -q list-updates | awk -F ' *[|] *' '$1 == "v" {print $3, $4, "->", $5}'"#.to_owned(),
+        PackageManager::Xbps => r#"xbps-install -Mun | while read -r pkgver _; do name=$(xbps-uhelper getpkgname "$pkgver"); echo "$name $(xbps-uhelper getpkgversion "$(xbps-query -p pkgver "$name")") -> $(xbps-uhelper getpkgversion "$pkgver")"; done"#.to_owned(),
+        PackageManager::Flatpak => r#"flatpak remote-ls --updates --columns=application,version | while read -r app version; do from=$(flatpak info "$app" 2>/dev/null | awk '/Version:/ {print $2; exit}'); echo "$app ${from:--} -> ${version:--}"; done"#.to_owned(),
+    })
+}
+
+/// Command installing the updates of the package manager, run in a terminal.
+fn default_update_cmd(manager: PackageManager, flake: &str) -> String {
+    match manager {
+        PackageManager::Pacman => "sudo pacman -Syu".to_owned(),
+        PackageManager::Paru => "paru -Sua".to_owned(),
+        PackageManager::Yay => "yay -Sua".to_owned(),
+        PackageManager::Apt => "sudo apt update && sudo apt upgrade".to_owned(),
+        PackageManager::Dnf => "sudo dnf upgrade".to_owned(),
+        PackageManager::Zypper => "sudo zypper update".to_owned(),
+        PackageManager::Xbps => "sudo xbps-install -Su".to_owned(),
+        PackageManager::Flatpak => "flatpak update".to_owned(),
+        // the lock file of the system flake belongs to root
+        PackageManager::Nix => format!(
+            "sudo nixos-rebuild switch --flake {} --recreate-lock-file",
+            quote(flake)
+        ),
+        PackageManager::Fwupd => "fwupdmgr update".to_owned(),
+    }
+}
+
+/// Command printing the changelog of the package in `ASHELL_PACKAGE`.
+fn default_changelog_cmd(manager: PackageManager) -> Option<String> {
+    match manager {
+        PackageManager::Pacman => Some(r#"pacman -Qc "$ASHELL_PACKAGE""#.to_owned()),
+        PackageManager::Apt => {
+            Some(r#"apt-get changelog "$ASHELL_PACKAGE" 2>/dev/null"#.to_owned())
+        }
+        PackageManager::Dnf => {
+            Some(r#"dnf changelog --upgrades "${ASHELL_PACKAGE%.*}""#.to_owned())
+        }
+        PackageManager::Zypper => Some(r#"rpm -q --changelog "$ASHELL_PACKAGE""#.to_owned()),
+        _ => None,
+    }
+}
+
+fn backend_name(manager: PackageManager) -> String {
+    match manager {
+        PackageManager::Fwupd => "Firmware".to_owned(),
+        manager => format!("{manager:?}"),
+    }
+}
+
+/// Runs the update command in the terminal, in place of the `{}` of the
+/// template or after it.
+fn in_terminal(terminal_cmd: &str, command: &str) -> String {
+    let command = format!(
+        "bash -c {}",
+        quote(&format!("{command}; echo Done - Press enter to exit; read"))
+    );
+
+    if terminal_cmd.contains("{}") {
+        terminal_cmd.replace("{}", &command)
+    } else {
+        format!("{terminal_cmd} {command}")
+    }
+}
+
+/// The `check_cmd` of the config followed by the package managers.
+fn backends(config: &UpdatesModuleConfig) -> Vec<Backend> {
+    let terminal_cmd = config
+        .terminal_cmd
+        .as_deref()
+        .unwrap_or(DEFAULT_TERMINAL_CMD);
+
+    config
+        .check_cmd
+        .iter()
+        .map(|check_cmd| Backend {
+            name: "System".to_owned(),
+            check: Check::Command(check_cmd.clone()),
+            // a complete command without a terminal_cmd, like before the backends
+            update_cmd: config
+                .update_cmd
+                .as_ref()
+                .map(|update_cmd| match &config.terminal_cmd {
+                    Some(terminal_cmd) => in_terminal(terminal_cmd, update_cmd),
+                    None => update_cmd.clone(),
+                }),
+            changelog_cmd: config.changelog_cmd.clone(),
+        })
+        .chain(config.backends.iter().map(|backend| match backend {
+            UpdatesBackend::Simple(manager) => Backend {
+                name: backend_name(*manager),
+                check: default_check(*manager, DEFAULT_FLAKE),
+                update_cmd: Some(in_terminal(
+                    terminal_cmd,
+                    &default_update_cmd(*manager, DEFAULT_FLAKE),
+                )),
+                changelog_cmd: default_changelog_cmd(*manager),
+            },
+            UpdatesBackend::Complete {
+                manager,
+                check_cmd,
+                update_cmd,
+                changelog_cmd,
+                flake,
+            } => {
+                let flake = flake.as_deref().unwrap_or(DEFAULT_FLAKE);
+
+                Backend {
+                    name: backend_name(*manager),
+                    check: check_cmd
+                        .clone()
+                        .map_or_else(|| default_check(*manager, flake), Check::Command),
+                    update_cmd: Some(update_cmd.clone().unwrap_or_else(|| {
+                        in_terminal(terminal_cmd, &default_update_cmd(*manager, flake))
+                    })),
+                    changelog_cmd: changelog_cmd
+                        .clone()
+                        .or_else(|| default_changelog_cmd(*manager)),
+                }
+            }
+        }))
+        .collect()
+}
+
+/// Packages needing a reboot once updated, with their variants like `linux-lts`.
+const REBOOT_PACKAGES: [&str; 5] = ["linux", "kernel", "systemd", "glibc", "libc6"];
+
+fn needs_reboot(package: &str) -> bool {
+    REBOOT_PACKAGES.iter().any(|name| {
+        package == *name
+            || package
+                .strip_prefix(name)
+                .is_some_and(|variant| variant.starts_with('-'))
+    })
+}
+
+/// Whether the system asks for a reboot, or the modules of the running kernel
+/// were removed by an update.
+fn system_requires_reboot() -> bool {
+    if Path::new("/var/run/reboot-required").exists() {
+        return true;
+    }
+
+    let modules = Path::new("/usr/lib/modules");
+    modules.is_dir()
+        && std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| !modules.join(release.trim()).exists())
+}
+
+/// Reasons to check for updates again.
+enum Trigger {
+    Scheduled,
+    Resumed,
+    /// The network went online or offline
+    Connectivity(bool),
+}
+
+/// Delay until the next of the `at` times, or the `interval` without them.
+fn next_check(at: &[NaiveTime], interval: Duration) -> Duration {
+    let now = Local::now().naive_local();
+
+    at.iter()
+        .map(|time| {
+            let today = now.date().and_time(*time);
+            let next = if today > now {
+                today
+            } else {
+                today.checked_add_days(Days::new(1)).unwrap_or(today)
+            };
+
+            (next - now).to_std().unwrap_or_default()
+        })
+        .min()
+        .unwrap_or(interval)
+}
+
+async fn resume_events() -> anyhow::Result<BoxStream<'static, Trigger>> {
+    let connection = zbus::Connection::system().await?;
+    let proxy = Login1ManagerProxy::new(&connection).await?;
+
+    Ok(proxy
+        .receive_prepare_for_sleep()
+        .await?
+        .filter_map(|signal| async move {
+            signal
+                .args()
+                .is_ok_and(|args| !args.starting)
+                .then_some(Trigger::Resumed)
+        })
+        .boxed())
+}
+
+/// Whether the network is online, and its changes from NetworkManager.
+async fn connectivity_events() -> anyhow::Result<(bool, BoxStream<'static, Trigger>)> {
+    fn is_online(state: ConnectivityState) -> bool {
+        // without connectivity checks NetworkManager reports an unknown state
+        !matches!(
+            state,
+            ConnectivityState::None | ConnectivityState::Portal | ConnectivityState::Loss
+        )
+    }
+
+    let connection = zbus::Connection::system().await?;
+    let nm = NetworkDbus::new(&connection).await?;
+    let online = is_online(nm.connectivity().await?);
+
+    let events = nm
+        .receive_connectivity_changed()
+        .await
+        .then(|value| async move {
+            let state = ConnectivityState::from(value.get().await.unwrap_or_default());
+
+            Trigger::Connectivity(is_online(state))
+        })
+        .boxed();
+
+    Ok((online, events))
+}
+
+/// Checks all the backends at once, the updates are listed by backend.
+async fn check_all(checks: Vec<Check>) -> Vec<Vec<Update>> {
+    join_all(checks.iter().map(|check| async move {
+        match check {
+            Check::Command(check_cmd) => check_update_now(check_cmd).await,
+            Check::Fwupd => check_firmware().await,
+            Check::Flake(flake) => check_flake(flake).await,
+        }
+    }))
+    .await
+}
+
+async fn check_firmware() -> Vec<Update> {
+    match fwupd::upgrades().await {
+        Ok(upgrades) => upgrades
+            .into_iter()
+            .map(|upgrade| Update {
+                package: upgrade.device,
+                from: upgrade.from,
+                to: upgrade.to,
+            })
+            .collect(),
+        Err(e) => {
+            error!("Failed to check the firmware updates: {e}");
+            vec![]
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FlakeLock {
+    root: String,
+    nodes: HashMap<String, FlakeNode>,
+}
+
+#[derive(Deserialize)]
+struct FlakeNode {
+    // an input following another one is a path of names
+    #[serde(default)]
+    inputs: HashMap<String, serde_json::Value>,
+    locked: Option<FlakeRef>,
+    original: Option<FlakeRef>,
+}
+
+#[derive(Deserialize)]
+struct FlakeRef {
+    #[serde(rename = "type")]
+    kind: String,
+    owner: Option<String>,
+    repo: Option<String>,
+    host: Option<String>,
+    #[serde(rename = "ref")]
+    reference: Option<String>,
+    rev: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitLabCommit {
+    id: String,
+}
+
+/// Latest revision of the branch of a GitHub or GitLab input, from their
+/// API rather than by fetching the input.
+async fn latest_rev(original: &FlakeRef) -> anyhow::Result<Option<String>> {
+    let (Some(owner), Some(repo)) = (&original.owner, &original.repo) else {
+        return Ok(None);
+    };
+    let reference = original.reference.as_deref().unwrap_or("HEAD");
+
+    match original.kind.as_str() {
+        "github" => {
+            let api = original.host.as_ref().map_or_else(
+                || "https://api.github.com".to_owned(),
+                |host| format!("https://{host}/api/v3"),
+            );
+            let response = utils::http::get(
+                &format!("{api}/repos/{owner}/{repo}/commits/{reference}"),
+                &[("Accept", "application/vnd.github.sha".to_owned())],
+            )
+            .await?;
+            if response.status != 200 {
+                anyhow::bail!("unexpected status {}", response.status);
+            }
+
+            Ok(Some(response.body.trim().to_owned()))
+        }
+        "gitlab" => {
+            let host = original.host.as_deref().unwrap_or("gitlab.com");
+            let project = format!("{owner}/{repo}").replace('/', "%2F");
+            let response = utils::http::get(
+                &format!("https://{host}/api/v4/projects/{project}/repository/commits/{reference}"),
+                &[],
+            )
+            .await?;
+            if response.status != 200 {
+                anyhow::bail!("unexpected status {}", response.status);
+            }
+
+            Ok(Some(
+                serde_json::from_str::<GitLabCommit>(&response.body)?.id,
+            ))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Compares the inputs of the flake locked at a branch with the head of
+/// the branch, only the GitHub and GitLab inputs are checked.
+async fn check_flake(flake: &str) -> Vec<Update> {
+    let lock = match std::fs::read_to_string(Path::new(flake).join("flake.lock")) {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("Failed to read the lock file of the flake {flake}: {e}");
+            return vec![];
+        }
+    };
+    let lock = match serde_json::from_str::<FlakeLock>(&lock) {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("Failed to parse the lock file of the flake {flake}: {e}");
+            return vec![];
+        }
+    };
+
+    let inputs = lock
+        .nodes
+        .get(&lock.root)
+        .into_iter()
+        .flat_map(|root| &root.inputs)
+        .filter_map(|(name, node)| Some((name, lock.nodes.get(node.as_str()?)?)))
+        .filter_map(|(name, node)| {
+            let original = node.original.as_ref()?;
+            let rev = node.locked.as_ref()?.rev.as_ref()?;
+
+            // a pinned input never changes
+            original.rev.is_none().then_some((name, original, rev))
+        });
+
+    join_all(inputs.map(|(name, original, rev)| async move {
+        match latest_rev(original).await {
+            Ok(Some(latest)) if latest != *rev => Some(Update {
+                package: name.clone(),
+                from: rev.chars().take(7).collect(),
+                to: latest.chars().take(7).collect(),
+            }),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Failed to check the flake input {name}: {e}");
+                None
+            }
+        }
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
 async fn check_update_now(check_cmd: &str) -> Vec<Update> {
     let check_update_cmd = process::Command::new("bash")
         .arg("-c")
@@ -59,7 +507,47 @@ async fn check_update_now(check_cmd: &str) -> Vec<Update> {
     }
 }
 
-async fn update(update_cmd: &str) {
+/// Runs the update commands one after the other.
+async fn update(update_cmds: Vec<String>) {
+    for update_cmd in update_cmds {
+        update_now(&update_cmd).await;
+    }
+}
+
+/// Longest changelog shown in the menu, in lines.
+const MAX_CHANGELOG_LINES: usize = 40;
+
+/// Output of the changelog command for the package, in `ASHELL_PACKAGE`.
+async fn changelog(changelog_cmd: &str, package: &str) -> String {
+    match process::Command::new("bash")
+        .arg("-c")
+        .arg(changelog_cmd)
+        .env("ASHELL_PACKAGE", package)
+        .output()
+        .await
+    {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let changelog = stdout
+                .lines()
+                .take(MAX_CHANGELOG_LINES)
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            if changelog.trim().is_empty() {
+                "No changelog available".to_owned()
+            } else {
+                changelog
+            }
+        }
+        Err(e) => {
+            error!("Failed to run the changelog command: {e}");
+            "No changelog available".to_owned()
+        }
+    }
+}
+
+async fn update_now(update_cmd: &str) {
     let _ = process::Command::new("bash")
         .arg("-c")
         .arg(update_cmd)
@@ -69,17 +557,28 @@ async fn update(update_cmd: &str) {
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    UpdatesCheckCompleted(Vec<Update>),
+    UpdatesCheckCompleted(Vec<Vec<Update>>),
     UpdateFinished,
     MenuOpened,
     ToggleUpdatesList,
+    ToggleBackend(usize),
+    ToggleChangelog(usize, String),
+    ChangelogLoaded(usize, String, String),
     CheckNow,
     Update(Id),
+    UpdateBackend(Id, usize),
+    NewsFetched(String, Vec<FeedItem>),
+    OpenNews(String),
+    MarkNewsRead,
+    ConfirmReboot,
+    CancelReboot,
+    Reboot,
 }
 
 pub enum Action {
     None,
     CheckForUpdates(Task<Message>),
+    Command(Task<Message>),
     CloseMenu(Id, Task<Message>),
 }
 
@@ -93,40 +592,190 @@ enum State {
 #[derive(Debug, Clone)]
 pub struct Updates {
     config: UpdatesModuleConfig,
+    backends: Vec<Backend>,
     state: State,
-    updates: Vec<Update>,
+    /// Updates of each backend
+    updates: Vec<Vec<Update>>,
     is_updates_list_open: bool,
+    /// Backends with an expanded section in the menu
+    open_backends: HashSet<usize>,
+    /// Backend and package with an expanded changelog, until it's loaded
+    changelog: Option<(usize, String, Option<String>)>,
+    last_notification: Option<Instant>,
+    reboot_cmd: String,
+    /// Pending packages needing a reboot when the update started
+    updating: Vec<String>,
+    reboot_required: bool,
+    is_reboot_confirm_open: bool,
+    news: Vec<FeedItem>,
+    news_read: HashSet<String>,
 }
 
 impl Updates {
-    pub fn new(config: UpdatesModuleConfig) -> Self {
+    pub fn new(config: UpdatesModuleConfig, reboot_cmd: String) -> Self {
         Self {
+            backends: backends(&config),
             config,
             state: State::default(),
             updates: Vec::new(),
             is_updates_list_open: false,
+            open_backends: HashSet::new(),
+            changelog: None,
+            last_notification: None,
+            reboot_cmd,
+            updating: Vec::new(),
+            reboot_required: system_requires_reboot(),
+            is_reboot_confirm_open: false,
+            news: Vec::new(),
+            news_read: read_state::<FeedsState>(NEWS_STATE_FILE).read,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.updates.iter().map(Vec::len).sum()
+    }
+
+    /// Notifies when the count of updates grew past the threshold,
+    /// at most once per interval.
+    fn notify(&mut self, previous: usize) {
+        let Some(notification) = &self.config.notification else {
+            return;
+        };
+        let count = self.count();
+
+        if count <= previous
+            || count < notification.threshold
+            || self
+                .last_notification
+                .is_some_and(|last| last.elapsed() < Duration::from_secs(notification.interval))
+        {
+            return;
         }
+
+        self.last_notification = Some(Instant::now());
+        // the packages of the tooltip, without its title
+        let body = self
+            .tooltip()
+            .lines()
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join("\n");
+        send_notification(
+            format!("{count} Updates available"),
+            body,
+            "software-update-available",
+        );
+    }
+
+    fn is_pinned(&self, update: &Update) -> bool {
+        self.config
+            .pin
+            .iter()
+            .any(|pattern| pattern.is_match(&update.package))
+    }
+
+    fn news_feeds(&self) -> Vec<FeedConfig> {
+        let arch_news = self.config.arch_news.then(|| FeedConfig {
+            url: ARCH_NEWS_URL.to_owned(),
+            name: Some("Arch Linux".to_owned()),
+            interval: 3600,
+        });
+
+        self.config.news.iter().cloned().chain(arch_news).collect()
+    }
+
+    fn unread_news(&self) -> impl Iterator<Item = &FeedItem> {
+        self.news
+            .iter()
+            .filter(|item| !self.news_read.contains(&item.key))
+    }
+
+    fn save_news_state(&self) {
+        write_state(
+            NEWS_STATE_FILE,
+            &FeedsState {
+                read: self.news_read.clone(),
+            },
+        );
+    }
+
+    fn backend_count(&self, index: usize) -> usize {
+        self.updates.get(index).map_or(0, Vec::len)
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        self.backends
+            .iter()
+            .map(|backend| backend.check.clone())
+            .collect()
+    }
+
+    fn run_update(&mut self, id: Id, update_cmds: Vec<String>) -> Action {
+        self.updating = self
+            .updates
+            .iter()
+            .flatten()
+            .filter(|update| needs_reboot(&update.package))
+            .map(|update| update.package.clone())
+            .collect();
+
+        Action::CloseMenu(
+            id,
+            Task::perform(
+                async move {
+                    update(update_cmds).await; // Wait for real completion
+                },
+                move |_| Message::UpdateFinished,
+            ),
+        )
     }
 
     pub fn update(&mut self, message: Message) -> Action {
         match message {
-            Message::UpdatesCheckCompleted(updates) => {
+            Message::UpdatesCheckCompleted(mut updates) => {
+                let previous = self.count();
+                for updates in &mut updates {
+                    updates.retain(|update| {
+                        !self
+                            .config
+                            .ignore
+                            .iter()
+                            .any(|pattern| pattern.is_match(&update.package))
+                    });
+                    // the pinned packages first, in their order
+                    updates.sort_by_key(|update| !self.is_pinned(update));
+                }
                 self.updates = updates;
                 self.state = State::Ready;
+                self.notify(previous);
+
+                // a package needing a reboot was installed since the update started
+                let updated = self.updating.iter().any(|package| {
+                    !self
+                        .updates
+                        .iter()
+                        .flatten()
+                        .any(|update| update.package == *package)
+                });
+                if updated || system_requires_reboot() {
+                    self.reboot_required = true;
+                    self.updating.clear();
+                }
 
                 Action::None
             }
             Message::UpdateFinished => {
                 // Re-check updates to verify they were actually applied
-                let check_command = self.config.check_cmd.clone();
-
                 Action::CheckForUpdates(Task::perform(
-                    async move { check_update_now(&check_command).await },
+                    check_all(self.checks()),
                     Message::UpdatesCheckCompleted,
                 ))
             }
             Message::MenuOpened => {
                 self.is_updates_list_open = false;
+                self.open_backends.clear();
+                self.changelog = None;
+                self.is_reboot_confirm_open = false;
 
                 Action::None
             }
@@ -135,160 +784,650 @@ impl Updates {
 
                 Action::None
             }
+            Message::NewsFetched(url, items) => {
+                let prefix = item_key(&url, "");
+
+                // forget the read state of the news that dropped out of the feed
+                let before = self.news_read.len();
+                self.news_read.retain(|key| {
+                    !key.starts_with(&prefix) || items.iter().any(|item| item.key == *key)
+                });
+                if self.news_read.len() != before {
+                    self.save_news_state();
+                }
+
+                self.news.retain(|item| !item.key.starts_with(&prefix));
+                self.news.extend(items);
+                self.news.sort_by(|a, b| b.published.cmp(&a.published));
+                self.news.truncate(MAX_NEWS);
+
+                Action::None
+            }
+            Message::OpenNews(key) => {
+                if let Some(link) = self
+                    .news
+                    .iter()
+                    .find(|item| item.key == key)
+                    .and_then(|item| item.link.clone())
+                {
+                    open_url(link);
+                }
+
+                if self.news_read.insert(key) {
+                    self.save_news_state();
+                }
+
+                Action::None
+            }
+            Message::MarkNewsRead => {
+                let keys = self
+                    .unread_news()
+                    .map(|item| item.key.clone())
+                    .collect::<Vec<_>>();
+
+                if !keys.is_empty() {
+                    self.news_read.extend(keys);
+                    self.save_news_state();
+                }
+
+                Action::None
+            }
+            Message::ConfirmReboot => {
+                self.is_reboot_confirm_open = true;
+
+                Action::None
+            }
+            Message::CancelReboot => {
+                self.is_reboot_confirm_open = false;
+
+                Action::None
+            }
+            Message::Reboot => {
+                utils::launcher::reboot(self.reboot_cmd.clone());
+
+                Action::None
+            }
+            Message::ToggleChangelog(index, package) => {
+                if self
+                    .changelog
+                    .as_ref()
+                    .is_some_and(|(backend, open, _)| *backend == index && *open == package)
+                {
+                    self.changelog = None;
+
+                    return Action::None;
+                }
+
+                let Some(changelog_cmd) = self
+                    .backends
+                    .get(index)
+                    .and_then(|backend| backend.changelog_cmd.clone())
+                else {
+                    return Action::None;
+                };
+                self.changelog = Some((index, package.clone(), None));
+
+                Action::Command(Task::perform(
+                    async move {
+                        let changelog = changelog(&changelog_cmd, &package).await;
+
+                        (package, changelog)
+                    },
+                    move |(package, changelog)| Message::ChangelogLoaded(index, package, changelog),
+                ))
+            }
+            Message::ChangelogLoaded(index, package, changelog) => {
+                // the row may have been closed or replaced while loading
+                if let Some((backend, open, loaded)) = &mut self.changelog
+                    && *backend == index
+                    && *open == package
+                {
+                    *loaded = Some(changelog);
+                }
+
+                Action::None
+            }
+            Message::ToggleBackend(index) => {
+                if !self.open_backends.remove(&index) {
+                    self.open_backends.insert(index);
+                }
+
+                Action::None
+            }
             Message::CheckNow => {
                 self.state = State::Checking;
-                let check_command = self.config.check_cmd.clone();
 
                 Action::CheckForUpdates(Task::perform(
-                    async move { check_update_now(&check_command).await },
+                    check_all(self.checks()),
                     Message::UpdatesCheckCompleted,
                 ))
             }
             Message::Update(id) => {
-                let update_command = self.config.update_cmd.clone();
-
-                Action::CloseMenu(
-                    id,
-                    Task::perform(
-                        async move {
-                            update(&update_command).await; // Wait for real completion
-                        },
-                        move |_| Message::UpdateFinished,
-                    ),
-                )
+                // only the backends with updates, unless there are none
+                let pending = self
+                    .backends
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| self.backend_count(*index) > 0)
+                    .filter_map(|(_, backend)| backend.update_cmd.clone())
+                    .collect::<Vec<_>>();
+                let update_cmds = if pending.is_empty() {
+                    self.backends
+                        .iter()
+                        .filter_map(|backend| backend.update_cmd.clone())
+                        .collect()
+                } else {
+                    pending
+                };
+
+                self.run_update(id, update_cmds)
             }
+            Message::UpdateBackend(id, index) => self.run_update(
+                id,
+                self.backends
+                    .get(index)
+                    .and_then(|backend| backend.update_cmd.clone())
+                    .into_iter()
+                    .collect(),
+            ),
         }
     }
 
     pub fn view(&'_ self, theme: &AshellTheme) -> Element<'_, Message> {
         let mut content = row!(container(icon(match self.state {
             State::Checking => StaticIcon::Refresh,
-            State::Ready if self.updates.is_empty() => StaticIcon::NoUpdatesAvailable,
+            State::Ready if self.count() == 0 => StaticIcon::NoUpdatesAvailable,
             _ => StaticIcon::UpdatesAvailable,
         })))
         .align_y(Alignment::Center)
         .spacing(theme.space.xxs);
 
-        if !self.updates.is_empty() {
-            content = content.push(text(self.updates.len()));
+        if self.count() > 0 {
+            content = content.push(text(self.count()));
+        }
+
+        if self.reboot_required {
+            content = content.push(icon(StaticIcon::Reboot));
+        }
+
+        if self.config.backend_counts && self.backends.len() > 1 {
+            for (index, backend) in self.backends.iter().enumerate() {
+                let count = self.backend_count(index);
+                if count > 0 {
+                    content = content
+                        .push(text(format!("{} {count}", backend.name)).size(theme.font_size.xs));
+                }
+            }
         }
 
         content.into()
     }
 
-    pub fn menu_view<'a>(&'a self, id: Id, theme: &'a AshellTheme) -> Element<'a, Message> {
-        column!(
-            if self.updates.is_empty() {
-                convert::Into::<Element<'_, _, _>>::into(
-                    container(text("Up to date ;)")).padding(theme.space.xs),
-                )
-            } else {
-                let mut elements = column!(
-                    button(row!(
-                        text(format!("{} Updates available", self.updates.len()))
-                            .width(Length::Fill),
-                        icon(if self.is_updates_list_open {
-                            StaticIcon::MenuClosed
-                        } else {
-                            StaticIcon::MenuOpen
-                        })
-                    ))
-                    .style(theme.ghost_button_style())
-                    .padding(theme.space.xs)
-                    .on_press(Message::ToggleUpdatesList)
-                    .width(Length::Fill),
-                )
-                .spacing(theme.space.xs);
-
-                if self.is_updates_list_open {
-                    elements = elements.push(
-                        container(scrollable(
-                            Column::with_children(
-                                self.updates
-                                    .iter()
-                                    .map(|update| {
-                                        column!(
-                                            text(update.package.clone())
-                                                .size(theme.font_size.xs)
-                                                .width(Length::Fill),
-                                            text(format!(
-                                                "{} -> {}",
-                                                {
-                                                    let mut res = update.from.clone();
-                                                    res.truncate(18);
-
-                                                    res
-                                                },
-                                                {
-                                                    let mut res = update.to.clone();
-                                                    res.truncate(18);
-
-                                                    res
-                                                },
-                                            ))
-                                            .width(Length::Fill)
-                                            .align_x(Horizontal::Right)
-                                            .size(theme.font_size.xs)
-                                        )
-                                        .into()
-                                    })
-                                    .collect::<Vec<Element<'_, _, _>>>(),
-                            )
-                            .spacing(theme.space.xs)
-                            .padding([
-                                0,
-                                theme.space.md,
-                                0,
-                                theme.space.xs,
-                            ]),
-                        ))
-                        .max_height(300),
+    /// Number of updates and the first packages, shown while hovering the module.
+    pub fn tooltip(&self) -> String {
+        const MAX_PACKAGES: usize = 10;
+
+        let tooltip = match self.state {
+            State::Checking => "Checking for updates".to_owned(),
+            State::Ready if self.count() == 0 => "Up to date".to_owned(),
+            State::Ready => {
+                let mut lines = vec![format!("{} Updates available", self.count())];
+                if self.backends.len() > 1 {
+                    lines.extend(
+                        self.backends
+                            .iter()
+                            .enumerate()
+                            .filter(|(index, _)| self.backend_count(*index) > 0)
+                            .map(|(index, backend)| {
+                                format!("{}: {}", backend.name, self.backend_count(index))
+                            }),
                     );
                 }
-                elements.into()
-            },
-            horizontal_rule(1),
-            column!(
-                button("Update")
-                    .style(theme.ghost_button_style())
-                    .padding(theme.space.xs)
-                    .on_press(Message::Update(id))
+                lines.extend(
+                    self.updates
+                        .iter()
+                        .flatten()
+                        .take(MAX_PACKAGES)
+                        .map(|update| {
+                            format!("{} {} → {}", update.package, update.from, update.to)
+                        }),
+                );
+                if self.count() > MAX_PACKAGES {
+                    lines.push(format!("and {} more", self.count() - MAX_PACKAGES));
+                }
+
+                lines.join("\n")
+            }
+        };
+
+        let unread_news = self.unread_news().count();
+        let mut lines = vec![tooltip];
+        if unread_news > 0 {
+            lines.push(format!("{unread_news} unread news"));
+        }
+        if self.reboot_required {
+            lines.push("Reboot required".to_owned());
+        }
+
+        lines.join("\n")
+    }
+
+    /// Package with its versions, a click shows its changelog when the backend has a `changelog_cmd`.
+    fn update_view<'a>(
+        &'a self,
+        index: usize,
+        update: &'a Update,
+        theme: &'a AshellTheme,
+    ) -> Element<'a, Message> {
+        let mut package = text(update.package.clone())
+            .size(theme.font_size.xs)
+            .width(Length::Fill);
+        if self.is_pinned(update) {
+            package = package.color(theme.get_theme().palette().primary);
+        }
+
+        let versions = column!(
+            package,
+            text(format!(
+                "{} -> {}",
+                {
+                    let mut res = update.from.clone();
+                    res.truncate(18);
+
+                    res
+                },
+                {
+                    let mut res = update.to.clone();
+                    res.truncate(18);
+
+                    res
+                },
+            ))
+            .width(Length::Fill)
+            .align_x(Horizontal::Right)
+            .size(theme.font_size.xs)
+        );
+
+        if self.backends[index].changelog_cmd.is_none() {
+            return versions.into();
+        }
+
+        let mut content = column!(
+            button(versions)
+                .style(theme.ghost_button_style())
+                .padding(0)
+                .on_press(Message::ToggleChangelog(index, update.package.clone()))
+                .width(Length::Fill)
+        )
+        .spacing(theme.space.xxs);
+
+        if let Some((backend, package, changelog)) = &self.changelog
+            && *backend == index
+            && *package == update.package
+        {
+            content = content.push(
+                text(changelog.as_deref().unwrap_or("Loading..."))
+                    .size(theme.font_size.xs)
                     .width(Length::Fill),
-                button({
-                    let mut content = row!(text("Check now").width(Length::Fill),);
+            );
+        }
 
-                    if self.state == State::Checking {
-                        content = content.push(icon(StaticIcon::Refresh));
-                    }
+        content.into()
+    }
 
-                    content
+    fn updates_view<'a>(
+        &'a self,
+        updates: impl Iterator<Item = (usize, &'a Update)>,
+        theme: &'a AshellTheme,
+    ) -> Column<'a, Message> {
+        Column::with_children(updates.map(|(index, update)| self.update_view(index, update, theme)))
+            .spacing(theme.space.xs)
+    }
+
+    /// Name and count of the backend, toggling its section, and its update button.
+    fn backend_header<'a>(
+        &'a self,
+        id: Id,
+        index: usize,
+        theme: &'a AshellTheme,
+    ) -> Element<'a, Message> {
+        row!(
+            button(row!(
+                text(format!(
+                    "{} ({})",
+                    self.backends[index].name,
+                    self.backend_count(index)
+                ))
+                .width(Length::Fill),
+                icon(if self.open_backends.contains(&index) {
+                    StaticIcon::MenuClosed
+                } else {
+                    StaticIcon::MenuOpen
                 })
+            ))
+            .style(theme.ghost_button_style())
+            .padding(theme.space.xs)
+            .on_press(Message::ToggleBackend(index))
+            .width(Length::Fill),
+            button(text("Update").size(theme.font_size.xs))
                 .style(theme.ghost_button_style())
-                .padding(theme.space.xs)
-                .on_press(Message::CheckNow)
-                .width(Length::Fill)
-            ),
+                .padding([theme.space.xxs, theme.space.xs])
+                .on_press(Message::UpdateBackend(id, index)),
         )
-        .spacing(theme.space.xs)
+        .align_y(Alignment::Center)
         .into()
     }
 
+    pub fn menu_view<'a>(&'a self, id: Id, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let menu =
+            column!(
+                if self.count() == 0 {
+                    convert::Into::<Element<'_, _, _>>::into(
+                        container(text("Up to date ;)")).padding(theme.space.xs),
+                    )
+                } else if self.backends.len() > 1 {
+                    // a collapsible section for each backend with updates
+                    container(scrollable(
+                        Column::with_children(
+                            self.updates
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, updates)| !updates.is_empty())
+                                .map(|(index, updates)| {
+                                    let mut section =
+                                        column!(self.backend_header(id, index, theme))
+                                            .spacing(theme.space.xs);
+
+                                    if self.open_backends.contains(&index) {
+                                        section =
+                                            section.push(
+                                                self.updates_view(
+                                                    updates.iter().map(|update| (index, update)),
+                                                    theme,
+                                                )
+                                                .padding([0, theme.space.md, 0, theme.space.xs]),
+                                            );
+                                    }
+
+                                    section.into()
+                                })
+                                .collect::<Vec<Element<'_, _, _>>>(),
+                        )
+                        .spacing(theme.space.xs),
+                    ))
+                    .max_height(300)
+                    .into()
+                } else {
+                    let mut elements = column!(
+                        button(row!(
+                            text(format!("{} Updates available", self.count())).width(Length::Fill),
+                            icon(if self.is_updates_list_open {
+                                StaticIcon::MenuClosed
+                            } else {
+                                StaticIcon::MenuOpen
+                            })
+                        ))
+                        .style(theme.ghost_button_style())
+                        .padding(theme.space.xs)
+                        .on_press(Message::ToggleUpdatesList)
+                        .width(Length::Fill),
+                    )
+                    .spacing(theme.space.xs);
+
+                    if self.is_updates_list_open {
+                        elements = elements.push(
+                            container(scrollable(
+                                self.updates_view(
+                                    self.updates
+                                        .iter()
+                                        .enumerate()
+                                        .flat_map(|(index, updates)| {
+                                            updates.iter().map(move |update| (index, update))
+                                        }),
+                                    theme,
+                                )
+                                .padding([
+                                    0,
+                                    theme.space.md,
+                                    0,
+                                    theme.space.xs,
+                                ]),
+                            ))
+                            .max_height(300),
+                        );
+                    }
+                    elements.into()
+                },
+                horizontal_rule(1),
+                column!(
+                    button("Update")
+                        .style(theme.ghost_button_style())
+                        .padding(theme.space.xs)
+                        .on_press(Message::Update(id))
+                        .width(Length::Fill),
+                    button({
+                        let mut content = row!(text("Check now").width(Length::Fill),);
+
+                        if self.state == State::Checking {
+                            content = content.push(icon(StaticIcon::Refresh));
+                        }
+
+                        content
+                    })
+                    .style(theme.ghost_button_style())
+                    .padding(theme.space.xs)
+                    .on_press(Message::CheckNow)
+                    .width(Length::Fill)
+                )
+                .push_maybe(self.reboot_required.then(|| self.reboot_view(theme))),
+            )
+            .spacing(theme.space.xs);
+
+        Column::new()
+            .push_maybe(self.news_view(theme))
+            .push(menu)
+            .spacing(theme.space.xs)
+            .into()
+    }
+
+    /// Unread news, shown before the updates to read them before updating.
+    fn news_view<'a>(&'a self, theme: &'a AshellTheme) -> Option<Element<'a, Message>> {
+        let unread = self.unread_news().collect::<Vec<_>>();
+        if unread.is_empty() {
+            return None;
+        }
+
+        Some(
+            column!(
+                row!(
+                    text(format!("{} unread news", unread.len())).width(Length::Fill),
+                    button(text("Mark all read").size(theme.font_size.xs))
+                        .style(theme.ghost_button_style())
+                        .padding([theme.space.xxs, theme.space.xs])
+                        .on_press(Message::MarkNewsRead),
+                )
+                .align_y(Alignment::Center)
+                .padding([0, 0, 0, theme.space.xs]),
+                container(scrollable(
+                    Column::with_children(
+                        unread
+                            .into_iter()
+                            .map(|item| {
+                                button(
+                                    column!(
+                                        text(item.title.clone())
+                                            .wrapping(text::Wrapping::WordOrGlyph)
+                                            .width(Length::Fill),
+                                        text(match item.published {
+                                            Some(date) => format!(
+                                                "{} · {}",
+                                                item.feed,
+                                                date.format("%d %b %R")
+                                            ),
+                                            None => item.feed.clone(),
+                                        })
+                                        .size(theme.font_size.xs),
+                                    )
+                                    .spacing(theme.space.xxs),
+                                )
+                                .style(theme.outline_button_style())
+                                .padding(theme.space.xs)
+                                .width(Length::Fill)
+                                .on_press(Message::OpenNews(item.key.clone()))
+                                .into()
+                            })
+                            .collect::<Vec<Element<'_, _>>>(),
+                    )
+                    .spacing(theme.space.xxs)
+                    .padding([0, theme.space.md, 0, 0]),
+                ))
+                .max_height(200),
+                horizontal_rule(1),
+            )
+            .spacing(theme.space.xs)
+            .into(),
+        )
+    }
+
+    /// Reboot button, asking for a confirmation first.
+    fn reboot_view<'a>(&self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        if self.is_reboot_confirm_open {
+            row!(
+                text("Reboot now?").width(Length::Fill),
+                button("Cancel")
+                    .style(theme.ghost_button_style())
+                    .padding([theme.space.xxs, theme.space.xs])
+                    .on_press(Message::CancelReboot),
+                button("Reboot")
+                    .style(theme.confirm_button_style())
+                    .padding([theme.space.xxs, theme.space.xs])
+                    .on_press(Message::Reboot),
+            )
+            .align_y(Alignment::Center)
+            .spacing(theme.space.xs)
+            .padding([0, theme.space.xs])
+            .into()
+        } else {
+            button(row!(
+                text("Reboot required").width(Length::Fill),
+                icon(StaticIcon::Reboot)
+            ))
+            .style(theme.ghost_button_style())
+            .padding(theme.space.xs)
+            .on_press(Message::ConfirmReboot)
+            .width(Length::Fill)
+            .into()
+        }
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
-        let check_cmd = self.config.check_cmd.clone();
+        let checks = self.checks();
         let interval = Duration::from_secs(self.config.interval.max(60));
+        let at = self
+            .config
+            .at
+            .iter()
+            .filter_map(|time| {
+                NaiveTime::parse_from_str(time, "%H:%M")
+                    .inspect_err(|e| warn!("Invalid time '{time}' of the update checks: {e}"))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        let check_on_resume = self.config.check_on_resume;
+        let check_on_network = self.config.check_on_network;
         let id = TypeId::of::<Self>();
 
-        Subscription::run_with_id(
-            (id, check_cmd.clone()),
+        let news = self.news_feeds().into_iter().map(|feed| {
+            Subscription::run_with_id(
+                (id, feed.url.clone(), feed.interval),
+                channel(10, async move |mut output| {
+                    let interval = Duration::from_secs(feed.interval.max(60));
+                    let mut etag = None;
+
+                    loop {
+                        scheduler::active().await;
+
+                        match fetch_feed(&feed, &mut etag).await {
+                            Ok(None) => debug!("News {} not modified", feed.url),
+                            Ok(Some(items)) => {
+                                let _ =
+                                    output.try_send(Message::NewsFetched(feed.url.clone(), items));
+                            }
+                            Err(e) => warn!("Failed to fetch the news {}: {e}", feed.url),
+                        }
+
+                        sleep(interval).await;
+                    }
+                }),
+            )
+        });
+
+        let check_updates = Subscription::run_with_id(
+            (
+                id,
+                checks.clone(),
+                at.clone(),
+                interval,
+                check_on_resume,
+                check_on_network,
+            ),
             channel(10, async move |mut output| {
+                let scheduled = stream::unfold(at, move |at| async move {
+                    sleep(next_check(&at, interval)).await;
+
+                    Some((Trigger::Scheduled, at))
+                })
+                .boxed();
+
+                let resumed = if check_on_resume {
+                    resume_events().await.unwrap_or_else(|e| {
+                        warn!("Failed to listen to the resume from suspend: {e}");
+                        stream::pending().boxed()
+                    })
+                } else {
+                    stream::pending().boxed()
+                };
+
+                let (mut online, connectivity) = if check_on_network {
+                    connectivity_events().await.unwrap_or_else(|e| {
+                        warn!("Failed to listen to the network connectivity: {e}");
+                        (true, stream::pending().boxed())
+                    })
+                } else {
+                    (true, stream::pending().boxed())
+                };
+
+                let mut triggers = stream_select!(scheduled, resumed, connectivity);
+                let mut check = true;
+
                 loop {
-                    let updates = check_update_now(&check_cmd).await;
+                    if check && online {
+                        // a check due while nobody looks at the bar runs on wake
+                        scheduler::active().await;
+
+                        let updates = check_all(checks.clone()).await;
 
-                    let _ = output.try_send(Message::UpdatesCheckCompleted(updates));
+                        let _ = output.try_send(Message::UpdatesCheckCompleted(updates));
+                    } else if check {
+                        debug!("Offline, skipping the check for updates");
+                    }
+
+                    let Some(trigger) = triggers.next().await else {
+                        break;
+                    };
+                    check = match trigger {
+                        Trigger::Scheduled | Trigger::Resumed => true,
+                        Trigger::Connectivity(connected) => {
+                            let back_online = connected && !online;
+                            online = connected;
 
-                    sleep(interval).await;
+                            back_online
+                        }
+                    };
                 }
             }),
-        )
+        );
+
+        Subscription::batch(std::iter::once(check_updates).chain(news))
     }
 }
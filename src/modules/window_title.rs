@@ -5,7 +5,7 @@ use crate::{
     utils::truncate_text,
 };
 use iced::{
-    Element, Subscription,
+    Element,
     widget::{container, text},
 };
 
@@ -52,38 +52,48 @@ impl WindowTitle {
         }
     }
 
+    fn format_title(config: &WindowTitleConfig, service: &CompositorService) -> Option<String> {
+        service.active_window.as_ref().map(|w| {
+            let raw_title = match config.mode {
+                WindowTitleMode::Title => &w.title,
+                WindowTitleMode::Class => &w.class,
+            };
+
+            if config.truncate_title_after_length > 0 {
+                truncate_text(raw_title, config.truncate_title_after_length)
+            } else {
+                raw_title.clone()
+            }
+        })
+    }
+
     fn recalculate_value(&mut self) {
         if let Some(service) = &self.service {
-            self.value = service.active_window.as_ref().map(|w| {
-                let raw_title = match self.config.mode {
-                    WindowTitleMode::Title => &w.title,
-                    WindowTitleMode::Class => &w.class,
-                };
-
-                if self.config.truncate_title_after_length > 0 {
-                    truncate_text(raw_title, self.config.truncate_title_after_length)
-                } else {
-                    raw_title.clone()
-                }
-            });
+            self.value = Self::format_title(&self.config, service);
         }
     }
 
-    pub fn get_value(&self) -> Option<String> {
-        self.value.clone()
+    /// Returns the title formatted with the given config, or with the module one.
+    pub fn get_value(&self, config: Option<&WindowTitleConfig>) -> Option<String> {
+        match config {
+            Some(config) => self
+                .service
+                .as_ref()
+                .and_then(|service| Self::format_title(config, service)),
+            None => self.value.clone(),
+        }
     }
 
     pub fn view(&'_ self, theme: &AshellTheme, title: String) -> Element<'_, Message> {
         container(
-            text(title)
-                .size(theme.font_size.sm)
-                .wrapping(text::Wrapping::None),
+            theme.module_text(
+                "WindowTitle",
+                text(title)
+                    .size(theme.font_size.sm)
+                    .wrapping(text::Wrapping::None),
+            ),
         )
         .clip(true)
         .into()
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        CompositorService::subscribe().map(Message::ServiceEvent)
-    }
 }
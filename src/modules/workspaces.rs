@@ -1,4 +1,5 @@
 use crate::{
+    animation::{self, lerp},
     config::{WorkspaceVisibilityMode, WorkspacesModuleConfig},
     outputs::Outputs,
     services::{
@@ -8,12 +9,12 @@ use crate::{
     theme::AshellTheme,
 };
 use iced::{
-    Element, Length, Subscription, alignment,
+    Element, Length, alignment,
     widget::{MouseArea, Row, button, container, text},
     window::Id,
 };
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Instant};
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Displayed {
@@ -54,6 +55,17 @@ pub struct Workspaces {
     service: Option<CompositorService>,
     ui_workspaces: Vec<UiWorkspace>,
     scroll_accumulator: f32,
+    // displayed state of the workspaces before the last change, to animate their width
+    previous_displayed: HashMap<i32, Displayed>,
+    changed_at: Option<Instant>,
+}
+
+fn workspace_width(theme: &AshellTheme, displayed: &Displayed) -> f32 {
+    match displayed {
+        Displayed::Active => theme.space.xl as f32,
+        Displayed::Visible => theme.space.lg as f32,
+        Displayed::Hidden => theme.space.md as f32,
+    }
 }
 
 fn calculate_ui_workspaces(
@@ -247,9 +259,19 @@ impl Workspaces {
             service: None,
             ui_workspaces: Vec::new(),
             scroll_accumulator: 0.,
+            previous_displayed: HashMap::new(),
+            changed_at: None,
         }
     }
 
+    /// Name of the focused workspace.
+    pub fn active_workspace(&self) -> Option<&str> {
+        self.ui_workspaces
+            .iter()
+            .find(|w| w.id > 0 && w.displayed == Displayed::Active)
+            .map(|w| w.name.as_str())
+    }
+
     pub fn update(&mut self, message: Message) -> iced::Task<Message> {
         match message {
             Message::ServiceEvent(event) => {
@@ -363,10 +385,32 @@ impl Workspaces {
 
     fn recalculate_ui_workspaces(&mut self) {
         if let Some(service) = &self.service {
-            self.ui_workspaces = calculate_ui_workspaces(&self.config, service);
+            let ui_workspaces = calculate_ui_workspaces(&self.config, service);
+
+            let changed = ui_workspaces.iter().any(|new| {
+                self.ui_workspaces
+                    .iter()
+                    .find(|old| old.id == new.id)
+                    .is_some_and(|old| old.displayed != new.displayed)
+            });
+            if changed {
+                self.previous_displayed = self
+                    .ui_workspaces
+                    .iter()
+                    .map(|w| (w.id, w.displayed.clone()))
+                    .collect();
+                self.changed_at = Some(Instant::now());
+            }
+
+            self.ui_workspaces = ui_workspaces;
         }
     }
 
+    /// Start of the running workspace change transition.
+    pub fn changed_at(&self) -> Option<Instant> {
+        self.changed_at
+    }
+
     pub fn view<'a>(
         &'a self,
         id: Id,
@@ -374,6 +418,7 @@ impl Workspaces {
         outputs: &Outputs,
     ) -> Element<'a, Message> {
         let monitor_name = outputs.get_monitor_name(id);
+        let progress = animation::progress(theme.animations.workspace, self.changed_at);
 
         MouseArea::new(
             Row::with_children(
@@ -435,11 +480,17 @@ impl Workspaces {
                                 } else {
                                     Message::ToggleSpecialWorkspace(w.id)
                                 })
-                                .width(match (w.id < 0, &w.displayed) {
-                                    (true, _) => Length::Shrink,
-                                    (_, Displayed::Active) => Length::Fixed(theme.space.xl as f32),
-                                    (_, Displayed::Visible) => Length::Fixed(theme.space.lg as f32),
-                                    (_, Displayed::Hidden) => Length::Fixed(theme.space.md as f32),
+                                .width(if w.id < 0 {
+                                    Length::Shrink
+                                } else {
+                                    let width = workspace_width(theme, &w.displayed);
+
+                                    Length::Fixed(match self.previous_displayed.get(&w.id) {
+                                        Some(previous) if progress < 1.0 => {
+                                            lerp(workspace_width(theme, previous), width, progress)
+                                        }
+                                        _ => width,
+                                    })
                                 })
                                 .height(theme.space.md)
                                 .into(),
@@ -474,8 +525,4 @@ impl Workspaces {
         })
         .into()
     }
-
-    pub fn subscription(&self) -> Subscription<Message> {
-        CompositorService::subscribe().map(Message::ServiceEvent)
-    }
 }
@@ -0,0 +1,368 @@
+use crate::{
+    components::{
+        icons::{StaticIcon, icon},
+        level_bar::level_bar,
+    },
+    config::{OsdConfig, OsdPosition},
+    services::{
+        ReadOnlyService, ServiceEvent,
+        audio::{AudioService, Sinks},
+        brightness::BrightnessService,
+        registry::Consumer,
+    },
+    theme::AshellTheme,
+};
+use iced::{
+    Alignment, Border, Element, Length, Subscription, Task, Theme,
+    futures::SinkExt,
+    platform_specific::shell::commands::layer_surface::{
+        Anchor, KeyboardInteractivity, Layer, destroy_layer_surface, get_layer_surface,
+    },
+    runtime::platform_specific::wayland::layer_surface::{
+        IcedMargin, IcedOutput, SctkLayerSurfaceSettings,
+    },
+    stream::channel,
+    time::every,
+    widget::{Space, container, row, text},
+    window::Id,
+};
+use std::{
+    any::TypeId,
+    fs,
+    time::{Duration, Instant},
+};
+use wayland_client::protocol::wl_output::WlOutput;
+
+const HEIGHT: u32 = 56;
+/// Distance from the top or bottom edge of the output
+const MARGIN: i32 = 64;
+const TICK: Duration = Duration::from_millis(100);
+const CAPS_LOCK_POLL: Duration = Duration::from_millis(200);
+const LEDS_PATH: &str = "/sys/class/leds";
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Audio(ServiceEvent<AudioService>),
+    Brightness(ServiceEvent<BrightnessService>),
+    CapsLock(bool),
+    Tick(Instant),
+    ConfigReloaded(OsdConfig),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Indicator {
+    Volume,
+    Brightness,
+    CapsLock,
+}
+
+/// Whether the LED of a caps lock key is on, `None` without such LED.
+fn caps_lock_led() -> Option<bool> {
+    let leds = fs::read_dir(LEDS_PATH).ok()?;
+    let mut found = false;
+
+    for led in leds.flatten() {
+        if !led.file_name().to_string_lossy().ends_with("capslock") {
+            continue;
+        }
+        found = true;
+
+        if fs::read_to_string(led.path().join("brightness"))
+            .is_ok_and(|brightness| brightness.trim() != "0")
+        {
+            return Some(true);
+        }
+    }
+
+    found.then_some(false)
+}
+
+/// Transient display of the volume and brightness levels, and of the caps
+/// lock state, shown on its own layer surface when they change.
+pub struct Osd {
+    config: OsdConfig,
+    audio: Option<AudioService>,
+    /// Volume and mute state of the default output device, once it's known
+    volume: Option<(i32, bool)>,
+    brightness: Option<BrightnessService>,
+    caps_lock: Option<bool>,
+    /// What is on screen, and until when
+    shown: Option<(Indicator, Instant)>,
+    /// Layer surface of the display, with its position
+    surface: Option<(Id, OsdPosition)>,
+}
+
+impl Osd {
+    pub fn new(config: OsdConfig) -> Self {
+        Self {
+            config,
+            audio: None,
+            volume: None,
+            brightness: None,
+            caps_lock: None,
+            shown: None,
+            surface: None,
+        }
+    }
+
+    /// The layer surface of the display.
+    pub fn surface(&self) -> Option<Id> {
+        self.surface.map(|(id, _)| id)
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        self.config.output.as_deref()
+    }
+
+    fn show(&mut self, indicator: Indicator) {
+        self.shown = Some((
+            indicator,
+            Instant::now() + Duration::from_millis(self.config.timeout),
+        ));
+    }
+
+    fn volume(&self) -> Option<(i32, bool)> {
+        self.audio
+            .as_ref()
+            .filter(|service| !service.server_info.default_sink.is_empty())
+            .map(|service| {
+                let muted = service
+                    .sinks
+                    .iter()
+                    .any(|sink| sink.name == service.server_info.default_sink && sink.is_mute);
+
+                (service.cur_sink_volume, muted)
+            })
+    }
+
+    fn brightness(&self) -> Option<u32> {
+        self.brightness
+            .as_ref()
+            .filter(|service| service.max > 0)
+            .map(|service| service.current * 100 / service.max)
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Audio(event) => match event {
+                ServiceEvent::Init(service) => {
+                    self.audio = Some(service);
+                    self.volume = self.volume();
+                }
+                ServiceEvent::Update(event) => {
+                    if let Some(service) = self.audio.as_mut() {
+                        service.update(event);
+                    }
+
+                    let volume = self.volume();
+                    // the first known volume is not a change
+                    if self.volume.is_some() && volume != self.volume {
+                        self.show(Indicator::Volume);
+                    }
+                    self.volume = volume;
+                }
+                ServiceEvent::Error(_) => {}
+            },
+            Message::Brightness(event) => match event {
+                ServiceEvent::Init(service) => self.brightness = Some(service),
+                ServiceEvent::Update(event) => {
+                    let before = self.brightness();
+
+                    if let Some(service) = self.brightness.as_mut() {
+                        service.update(event);
+                    }
+                    if before != self.brightness() {
+                        self.show(Indicator::Brightness);
+                    }
+                }
+                ServiceEvent::Error(_) => {}
+            },
+            Message::CapsLock(on) => {
+                if self.caps_lock.is_some_and(|current| current != on) {
+                    self.show(Indicator::CapsLock);
+                }
+                self.caps_lock = Some(on);
+            }
+            Message::Tick(now) => {
+                if self.shown.is_some_and(|(_, until)| until <= now) {
+                    self.shown = None;
+                }
+            }
+            Message::ConfigReloaded(config) => {
+                if !config.enabled {
+                    self.shown = None;
+                }
+                if !config.volume {
+                    self.audio = None;
+                    self.volume = None;
+                }
+                if !config.brightness {
+                    self.brightness = None;
+                }
+                if !config.caps_lock {
+                    self.caps_lock = None;
+                }
+                self.config = config;
+            }
+        }
+    }
+
+    /// Creates or destroys the layer surface while something is shown.
+    pub fn sync_surface<M: 'static>(&mut self, output: Option<WlOutput>) -> Task<M> {
+        let position = self.config.position;
+
+        match self.surface {
+            Some((id, current)) if self.shown.is_none() || current != position => {
+                self.surface = None;
+
+                Task::batch(vec![destroy_layer_surface(id), self.sync_surface(output)])
+            }
+            Some(_) => Task::none(),
+            None if self.shown.is_none() => Task::none(),
+            None => {
+                let id = Id::unique();
+                self.surface = Some((id, position));
+
+                get_layer_surface(SctkLayerSurfaceSettings {
+                    id,
+                    namespace: "ashell-osd".to_string(),
+                    size: Some((Some(self.config.width), Some(HEIGHT))),
+                    layer: Layer::Overlay,
+                    keyboard_interactivity: KeyboardInteractivity::None,
+                    margin: IcedMargin {
+                        top: MARGIN,
+                        right: 0,
+                        bottom: MARGIN,
+                        left: 0,
+                    },
+                    output: output.map_or(IcedOutput::Active, IcedOutput::Output),
+                    // without anchors the surface is centered on the output
+                    anchor: match position {
+                        OsdPosition::Top => Anchor::TOP,
+                        OsdPosition::Center => Anchor::empty(),
+                        OsdPosition::Bottom => Anchor::BOTTOM,
+                    },
+                    ..Default::default()
+                })
+            }
+        }
+    }
+
+    pub fn view<'a>(&self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let content: Element<'a, Message> = match self.shown.map(|(indicator, _)| indicator) {
+            Some(Indicator::Volume) => {
+                let volume = self.volume.map_or(0, |(volume, _)| volume.max(0) as u32);
+                let speaker = self.audio.as_ref().map_or(StaticIcon::Speaker0, |service| {
+                    service.sinks.get_icon(&service.server_info.default_sink)
+                });
+
+                row!(
+                    icon(speaker).size(theme.font_size.lg),
+                    level_bar(volume, self.config.color, 6., theme),
+                    text(format!("{volume}%")).width(Length::Fixed(40.)),
+                )
+                .spacing(theme.space.md)
+                .align_y(Alignment::Center)
+                .into()
+            }
+            Some(Indicator::Brightness) => {
+                let brightness = self.brightness().unwrap_or_default();
+
+                row!(
+                    icon(StaticIcon::Brightness).size(theme.font_size.lg),
+                    level_bar(brightness, self.config.color, 6., theme),
+                    text(format!("{brightness}%")).width(Length::Fixed(40.)),
+                )
+                .spacing(theme.space.md)
+                .align_y(Alignment::Center)
+                .into()
+            }
+            Some(Indicator::CapsLock) => row!(
+                icon(StaticIcon::Keyboard).size(theme.font_size.lg),
+                text(if self.caps_lock == Some(true) {
+                    "Caps Lock on"
+                } else {
+                    "Caps Lock off"
+                }),
+            )
+            .spacing(theme.space.md)
+            .align_y(Alignment::Center)
+            .into(),
+            None => Space::new(Length::Shrink, Length::Shrink).into(),
+        };
+
+        container(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_y(Alignment::Center)
+            .padding([theme.space.sm, theme.space.md])
+            .style(move |iced_theme: &Theme| container::Style {
+                background: Some(
+                    iced_theme
+                        .palette()
+                        .background
+                        .scale_alpha(theme.menu.opacity)
+                        .into(),
+                ),
+                border: Border {
+                    color: iced_theme.extended_palette().secondary.base.color,
+                    width: 1.,
+                    radius: theme.radius.lg.into(),
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+
+    /// The shared services watched for the levels to show.
+    pub fn consumers(&self) -> Vec<Consumer> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        [
+            (self.config.volume, Consumer::OsdVolume),
+            (self.config.brightness, Consumer::OsdBrightness),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, consumer)| enabled.then_some(consumer))
+        .collect()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if !self.config.enabled {
+            return Subscription::none();
+        }
+
+        Subscription::batch(vec![
+            if self.config.caps_lock {
+                Subscription::run_with_id(
+                    TypeId::of::<Indicator>(),
+                    channel(10, async move |mut output| {
+                        let mut current = None;
+
+                        loop {
+                            let on = caps_lock_led();
+                            if on != current
+                                && let Some(on) = on
+                            {
+                                let _ = output.send(on).await;
+                            }
+                            current = on;
+
+                            tokio::time::sleep(CAPS_LOCK_POLL).await;
+                        }
+                    }),
+                )
+                .map(Message::CapsLock)
+            } else {
+                Subscription::none()
+            },
+            if self.shown.is_some() {
+                every(TICK).map(Message::Tick)
+            } else {
+                Subscription::none()
+            },
+        ])
+    }
+}
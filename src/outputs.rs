@@ -1,34 +1,422 @@
 use iced::{
-    Task,
+    Point, Task,
     platform_specific::shell::commands::layer_surface::{
         Anchor, KeyboardInteractivity, Layer, destroy_layer_surface, get_layer_surface, set_anchor,
-        set_exclusive_zone, set_keyboard_interactivity, set_size,
+        set_exclusive_zone, set_keyboard_interactivity, set_margin, set_size,
+    },
+    runtime::platform_specific::wayland::layer_surface::{
+        IcedMargin, IcedOutput, SctkLayerSurfaceSettings,
     },
-    runtime::platform_specific::wayland::layer_surface::{IcedOutput, SctkLayerSurfaceSettings},
     window::Id,
 };
+use indexmap::IndexMap;
 use log::debug;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 use wayland_client::protocol::wl_output::WlOutput;
 
 use crate::{
-    HEIGHT,
-    config::{self, AppearanceStyle, Position},
+    config::{
+        self, AppearanceStyle, AutoHideConfig, BarAlign, BarWidth, Config, MarginConfig, Position,
+    },
     menu::{Menu, MenuType},
     position_button::ButtonUIRef,
 };
 
+/// Time after which a bar surface is considered rendered.
+const READY_DELAY: Duration = Duration::from_millis(100);
+
+/// Settings of the bar surface of an output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceConfig {
+    pub style: AppearanceStyle,
+    pub position: Position,
+    pub layer: config::Layer,
+    pub scale_factor: f64,
+    pub auto_hide: AutoHideConfig,
+    pub hide_on_fullscreen: bool,
+    pub margin: MarginConfig,
+    pub exclusive: bool,
+    pub height: u32,
+    pub width: BarWidth,
+    pub align: BarAlign,
+}
+
+impl SurfaceConfig {
+    fn new(config: &Config) -> Self {
+        Self {
+            style: config.appearance.style,
+            position: config.position,
+            layer: config.layer,
+            scale_factor: config.appearance.scale_factor,
+            auto_hide: config.auto_hide,
+            hide_on_fullscreen: config.hide_on_fullscreen,
+            margin: config.margin,
+            exclusive: config.exclusive,
+            height: config.height,
+            width: config.width,
+            align: config.align,
+        }
+    }
+
+    /// Logical height of the content of the bar, the solid styles leave a gap for the menus.
+    fn content_height(&self) -> f64 {
+        let gap = match self.style {
+            AppearanceStyle::Solid | AppearanceStyle::Gradient => 8.,
+            AppearanceStyle::Islands => 0.,
+        };
+
+        (self.height as f64 - gap).max(1.)
+    }
+
+    fn scaled_height(&self) -> f64 {
+        self.content_height() * self.scale_factor
+    }
+
+    /// Height of the surface, only the strip on the screen edge
+    /// is left while an auto-hidden bar is collapsed.
+    fn surface_height(&self, revealed: bool) -> u32 {
+        if self.auto_hide.enabled && !revealed {
+            self.auto_hide.strip_size.max(1)
+        } else {
+            self.scaled_height() as u32
+        }
+    }
+
+    /// Length of the bar along the screen edge, `None` when it spans the whole edge.
+    ///
+    /// A percentage of the output spans the whole edge until the size of the output is known.
+    fn length(&self, output_size: Option<(f32, f32)>) -> Option<u32> {
+        let edge = output_size.map(|(width, height)| {
+            if self.position.is_vertical() {
+                height
+            } else {
+                width
+            }
+        });
+
+        match self.width {
+            BarWidth::Full => None,
+            BarWidth::Percent(percent) => {
+                edge.map(|edge| (edge * percent.clamp(1, 100) as f32 / 100.) as u32)
+            }
+            BarWidth::Fixed(length) => Some((length as f64 * self.scale_factor) as u32),
+        }
+    }
+
+    /// Size of the surface with the given thickness, along the screen edge.
+    fn size(&self, thickness: u32, length: Option<u32>) -> (Option<u32>, Option<u32>) {
+        if self.position.is_vertical() {
+            (Some(thickness), length)
+        } else {
+            (length, Some(thickness))
+        }
+    }
+
+    fn exclusive_zone(&self) -> i32 {
+        if self.auto_hide.enabled || !self.exclusive {
+            0
+        } else {
+            self.scaled_height() as i32
+        }
+    }
+
+    fn anchor(&self, length: Option<u32>) -> Anchor {
+        let (edge, start, end) = match self.position {
+            Position::Top => (Anchor::TOP, Anchor::LEFT, Anchor::RIGHT),
+            Position::Bottom => (Anchor::BOTTOM, Anchor::LEFT, Anchor::RIGHT),
+            Position::Left => (Anchor::LEFT, Anchor::TOP, Anchor::BOTTOM),
+            Position::Right => (Anchor::RIGHT, Anchor::TOP, Anchor::BOTTOM),
+        };
+
+        // a shorter bar is centered by the compositor when it's anchored to neither end
+        match (length, self.align) {
+            (None, _) => edge | start | end,
+            (Some(_), BarAlign::Start) => edge | start,
+            (Some(_), BarAlign::Center) => edge,
+            (Some(_), BarAlign::End) => edge | end,
+        }
+    }
+
+    /// Offset of the bar from the start of the screen edge, in surface pixels.
+    fn origin(&self, output_size: Option<(f32, f32)>) -> f32 {
+        let (margin_start, margin_end) = if self.position.is_vertical() {
+            (self.margin.top, self.margin.bottom)
+        } else {
+            (self.margin.left, self.margin.right)
+        };
+        let edge = output_size.map_or(0., |(width, height)| {
+            if self.position.is_vertical() {
+                height
+            } else {
+                width
+            }
+        });
+
+        match (self.length(output_size), self.align) {
+            (None, _) | (Some(_), BarAlign::Start) => margin_start as f32,
+            (Some(length), BarAlign::Center) => {
+                (edge - length as f32 + (margin_start - margin_end) as f32) / 2.
+            }
+            (Some(length), BarAlign::End) => edge - length as f32 - margin_end as f32,
+        }
+    }
+
+    fn margin(&self) -> IcedMargin {
+        IcedMargin {
+            top: self.margin.top,
+            right: self.margin.right,
+            bottom: self.margin.bottom,
+            left: self.margin.left,
+        }
+    }
+
+    fn layer(&self) -> Layer {
+        match self.layer {
+            // without an exclusive zone the bar would be covered by the windows
+            config::Layer::Bottom if self.exclusive_zone() == 0 => Layer::Top,
+            config::Layer::Bottom => Layer::Bottom,
+            config::Layer::Overlay => Layer::Overlay,
+        }
+    }
+}
+
+impl Default for SurfaceConfig {
+    fn default() -> Self {
+        Self::new(&Config::default())
+    }
+}
+
+/// Surface settings of every output, with the `[[Bar]]` overrides applied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputsConfig {
+    targets: config::Outputs,
+    default: SurfaceConfig,
+    bars: HashMap<String, Vec<SurfaceConfig>>,
+}
+
+impl OutputsConfig {
+    pub fn new(config: &Config) -> Self {
+        let default = SurfaceConfig::new(config);
+
+        Self {
+            targets: config.outputs.clone(),
+            default,
+            // the bars are drawn with the global theme, so only the
+            // settings that don't change how they look can be overridden
+            bars: config
+                .bar_configs
+                .iter()
+                .map(|(output, bars)| {
+                    (
+                        output.clone(),
+                        bars.iter()
+                            .map(|bar| SurfaceConfig {
+                                position: bar.position,
+                                layer: bar.layer,
+                                auto_hide: bar.auto_hide,
+                                hide_on_fullscreen: bar.hide_on_fullscreen,
+                                margin: bar.margin,
+                                exclusive: bar.exclusive,
+                                height: bar.height,
+                                width: bar.width,
+                                align: bar.align,
+                                ..default
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Settings of the bars of an output, in the order of their `[[Bar]]` entries.
+    fn get(&self, name: &str) -> Vec<SurfaceConfig> {
+        self.bars
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| vec![self.default])
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ShellInfo {
     id: Id,
-    position: Position,
-    layer: config::Layer,
-    style: AppearanceStyle,
+    config: SurfaceConfig,
+    // logical size of the output, when the compositor reported it
+    output_size: Option<(f32, f32)>,
     menu: Menu,
-    scale_factor: f64,
+    created_at: Instant,
+    // auto-hide state, when the bar was revealed or `None` while it's collapsed
+    revealed_at: Option<Instant>,
+    hovered: bool,
+    // when the revealed bar stopped being used
+    idle_since: Option<Instant>,
+    // the output shows a fullscreen window, the bar is hidden
+    fullscreen: bool,
+}
+
+impl ShellInfo {
+    fn new(id: Id, menu_id: Id, config: SurfaceConfig, output_size: Option<(f32, f32)>) -> Self {
+        Self {
+            id,
+            config,
+            output_size,
+            menu: Menu::new(menu_id),
+            created_at: Instant::now(),
+            revealed_at: None,
+            hovered: false,
+            idle_since: None,
+            fullscreen: false,
+        }
+    }
+
+    /// Whether the given surface is the bar or its menu.
+    fn owns(&self, id: Id) -> bool {
+        self.id == id || self.menu.id == id
+    }
+
+    fn surfaces(&self) -> [(Id, SurfaceRole); 2] {
+        [
+            (self.id, SurfaceRole::Main),
+            (self.menu.id, SurfaceRole::Menu),
+        ]
+    }
+
+    fn is_revealed(&self) -> bool {
+        !self.config.auto_hide.enabled || self.revealed_at.is_some()
+    }
+
+    /// Applies new settings, or a new output size, to the surface,
+    /// `None` when the surface has to be created again to apply them.
+    fn set_config<Message: 'static>(
+        &mut self,
+        config: SurfaceConfig,
+        output_size: Option<(f32, f32)>,
+    ) -> Option<Task<Message>> {
+        if self.config.layer() != config.layer()
+            || self.config.auto_hide.enabled != config.auto_hide.enabled
+        {
+            return None;
+        }
+
+        debug!("Updating bar: {:?}, new settings {:?}", self.id, config);
+
+        let mut tasks = Vec::new();
+        let anchor = config.anchor(config.length(output_size));
+        if self.config.anchor(self.config.length(self.output_size)) != anchor {
+            tasks.push(set_anchor(self.id, anchor));
+        }
+        if self.config.margin != config.margin {
+            let margin = config.margin;
+            tasks.push(set_margin(
+                self.id,
+                margin.top,
+                margin.right,
+                margin.bottom,
+                margin.left,
+            ));
+        }
+        self.config = config;
+        self.output_size = output_size;
+        self.fullscreen &= config.hide_on_fullscreen;
+        tasks.push(self.resize());
+
+        Some(Task::batch(tasks))
+    }
+
+    fn height(&self) -> u32 {
+        if self.fullscreen {
+            1
+        } else {
+            self.config.surface_height(self.is_revealed())
+        }
+    }
+
+    fn exclusive_zone(&self) -> i32 {
+        if self.fullscreen {
+            0
+        } else {
+            self.config.exclusive_zone()
+        }
+    }
+
+    fn set_surface_size<Message: 'static>(&self) -> Task<Message> {
+        let (width, height) = self
+            .config
+            .size(self.height(), self.config.length(self.output_size));
+
+        set_size(self.id, width, height)
+    }
+
+    fn resize<Message: 'static>(&self) -> Task<Message> {
+        Task::batch(vec![
+            self.set_surface_size(),
+            set_exclusive_zone(self.id, self.exclusive_zone()),
+        ])
+    }
+
+    fn reveal<Message: 'static>(&mut self) -> Task<Message> {
+        self.idle_since = None;
+
+        if self.is_revealed() {
+            return Task::none();
+        }
+
+        debug!("Revealing the bar {:?}", self.id);
+        self.revealed_at = Some(Instant::now());
+
+        self.set_surface_size()
+    }
+
+    fn collapse<Message: 'static>(&mut self) -> Task<Message> {
+        self.idle_since = None;
+
+        if self.revealed_at.take().is_none() {
+            return Task::none();
+        }
+
+        debug!("Collapsing the bar {:?}", self.id);
+
+        self.set_surface_size()
+    }
+}
+
+/// Stable key of a known output.
+///
+/// Keys survive layer surface re-creation (layer changes, output
+/// disconnect/reconnect with the same name), unlike surface [`Id`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutputKey(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceRole {
+    Main,
+    Menu,
 }
 
 #[derive(Debug, Clone)]
-pub struct Outputs(Vec<(String, Option<ShellInfo>, Option<WlOutput>)>);
+struct OutputEntry {
+    name: String,
+    // bars of the output, in the order of their settings
+    bars: Vec<ShellInfo>,
+    wl_output: Option<WlOutput>,
+    logical_size: Option<(f32, f32)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Outputs {
+    config: OutputsConfig,
+    // in insertion order, so that scans like `find_key` are deterministic
+    entries: IndexMap<OutputKey, OutputEntry>,
+    // surface id (main or menu) of every bar -> owning output, used by every view/update lookup
+    surfaces: HashMap<Id, (OutputKey, SurfaceRole)>,
+    next_key: u64,
+    // the bars are hidden, outputs are tracked without creating surfaces
+    hidden: bool,
+}
 
 pub enum HasOutput<'a> {
     Main,
@@ -36,71 +424,36 @@ pub enum HasOutput<'a> {
 }
 
 impl Outputs {
-    pub fn new<Message: 'static>(
-        style: AppearanceStyle,
-        position: Position,
-        layer: config::Layer,
-        scale_factor: f64,
-    ) -> (Self, Task<Message>) {
-        let (id, menu_id, task) =
-            Self::create_output_layers(style, None, position, layer, scale_factor);
-
-        (
-            Self(vec![(
-                "Fallback".to_string(),
-                Some(ShellInfo {
-                    id,
-                    menu: Menu::new(menu_id),
-                    position,
-                    layer,
-                    style,
-                    scale_factor,
-                }),
-                None,
-            )]),
-            task,
-        )
-    }
+    pub fn new<Message: 'static>(config: OutputsConfig) -> (Self, Task<Message>) {
+        let mut outputs = Self {
+            config,
+            ..Self::default()
+        };
+        let task = outputs.add_fallback();
 
-    fn get_height(style: AppearanceStyle, scale_factor: f64) -> f64 {
-        (HEIGHT
-            - match style {
-                AppearanceStyle::Solid | AppearanceStyle::Gradient => 8.,
-                AppearanceStyle::Islands => 0.,
-            })
-            * scale_factor
+        (outputs, task)
     }
 
     fn create_output_layers<Message: 'static>(
-        style: AppearanceStyle,
+        config: SurfaceConfig,
+        output_size: Option<(f32, f32)>,
         wl_output: Option<WlOutput>,
-        position: Position,
-        layer: config::Layer,
-        scale_factor: f64,
     ) -> (Id, Id, Task<Message>) {
         let id = Id::unique();
-        let height = Self::get_height(style, scale_factor);
-
-        let iced_layer = match layer {
-            config::Layer::Bottom => Layer::Bottom,
-            config::Layer::Overlay => Layer::Overlay,
-        };
+        let length = config.length(output_size);
 
         let task = get_layer_surface(SctkLayerSurfaceSettings {
             id,
             namespace: "ashell-main-layer".to_string(),
-            size: Some((None, Some(height as u32))),
-            layer: iced_layer,
+            size: Some(config.size(config.surface_height(false), length)),
+            layer: config.layer(),
             keyboard_interactivity: KeyboardInteractivity::None,
-            exclusive_zone: height as i32,
+            exclusive_zone: config.exclusive_zone(),
+            margin: config.margin(),
             output: wl_output.clone().map_or(IcedOutput::Active, |wl_output| {
                 IcedOutput::Output(wl_output)
             }),
-            anchor: match position {
-                Position::Top => Anchor::TOP,
-                Position::Bottom => Anchor::BOTTOM,
-            } | Anchor::LEFT
-                | Anchor::RIGHT,
+            anchor: config.anchor(length),
             ..Default::default()
         });
 
@@ -121,6 +474,13 @@ impl Outputs {
         (id, menu_id, Task::batch(vec![task, menu_task]))
     }
 
+    fn destroy_output_layers<Message: 'static>(shell_info: &ShellInfo) -> Task<Message> {
+        Task::batch(vec![
+            destroy_layer_surface(shell_info.id),
+            destroy_layer_surface(shell_info.menu.id),
+        ])
+    }
+
     fn name_in_config(name: &str, outputs: &config::Outputs) -> bool {
         match outputs {
             config::Outputs::All => true,
@@ -131,173 +491,259 @@ impl Outputs {
         }
     }
 
+    fn insert(&mut self, entry: OutputEntry) -> OutputKey {
+        let key = OutputKey(self.next_key);
+        self.next_key += 1;
+
+        for shell_info in &entry.bars {
+            for (id, role) in shell_info.surfaces() {
+                self.surfaces.insert(id, (key, role));
+            }
+        }
+        self.entries.insert(key, entry);
+
+        key
+    }
+
+    fn take(&mut self, key: OutputKey) -> Option<OutputEntry> {
+        let entry = self.entries.shift_remove(&key)?;
+
+        for shell_info in &entry.bars {
+            for (id, _) in shell_info.surfaces() {
+                self.surfaces.remove(&id);
+            }
+        }
+
+        Some(entry)
+    }
+
+    fn attach(&mut self, key: OutputKey, shell_info: ShellInfo) {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            for (id, role) in shell_info.surfaces() {
+                self.surfaces.insert(id, (key, role));
+            }
+            entry.bars.push(shell_info);
+        }
+    }
+
+    /// Takes the bars of an output out, their surfaces still have to be destroyed.
+    fn detach(&mut self, key: OutputKey) -> Vec<ShellInfo> {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return Vec::new();
+        };
+        let bars = std::mem::take(&mut entry.bars);
+
+        for shell_info in &bars {
+            for (id, _) in shell_info.surfaces() {
+                self.surfaces.remove(&id);
+            }
+        }
+
+        bars
+    }
+
+    /// Creates the bars of an output, one for each of its settings.
+    fn attach_bars<Message: 'static>(
+        &mut self,
+        key: OutputKey,
+        configs: Vec<SurfaceConfig>,
+        wl_output: Option<WlOutput>,
+    ) -> Task<Message> {
+        let output_size = self.entries.get(&key).and_then(|entry| entry.logical_size);
+
+        Task::batch(
+            configs
+                .into_iter()
+                .map(|config| {
+                    let (id, menu_id, task) =
+                        Self::create_output_layers(config, output_size, wl_output.clone());
+                    self.attach(key, ShellInfo::new(id, menu_id, config, output_size));
+
+                    task
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn destroy_bars<Message: 'static>(bars: &[ShellInfo]) -> Task<Message> {
+        Task::batch(
+            bars.iter()
+                .map(Self::destroy_output_layers)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn find_key(&self, predicate: impl Fn(&OutputEntry) -> bool) -> Option<OutputKey> {
+        self.entries
+            .iter()
+            .find_map(|(key, entry)| predicate(entry).then_some(*key))
+    }
+
+    fn shell_info(&self, id: Id) -> Option<&ShellInfo> {
+        let (key, _) = *self.surfaces.get(&id)?;
+
+        self.entries
+            .get(&key)?
+            .bars
+            .iter()
+            .find(|shell_info| shell_info.owns(id))
+    }
+
+    fn shell_info_mut(&mut self, id: Id) -> Option<&mut ShellInfo> {
+        let (key, _) = *self.surfaces.get(&id)?;
+
+        self.entries
+            .get_mut(&key)?
+            .bars
+            .iter_mut()
+            .find(|shell_info| shell_info.owns(id))
+    }
+
+    fn all_bars(&self) -> impl Iterator<Item = &ShellInfo> {
+        self.entries.values().flat_map(|entry| &entry.bars)
+    }
+
+    fn all_bars_mut(&mut self) -> impl Iterator<Item = &mut ShellInfo> {
+        self.entries.values_mut().flat_map(|entry| &mut entry.bars)
+    }
+
+    fn add_fallback<Message: 'static>(&mut self) -> Task<Message> {
+        let key = self.insert(OutputEntry {
+            name: "Fallback".to_string(),
+            bars: Vec::new(),
+            wl_output: None,
+            logical_size: None,
+        });
+
+        self.attach_bars(key, vec![self.config.default], None)
+    }
+
     pub fn has(&'_ self, id: Id) -> Option<HasOutput<'_>> {
-        self.0.iter().find_map(|(_, info, _)| {
-            info.as_ref().and_then(|info| {
-                if info.id == id {
-                    Some(HasOutput::Main)
-                } else if info.menu.id == id {
-                    Some(HasOutput::Menu(info.menu.menu_info.as_ref()))
-                } else {
-                    None
-                }
-            })
+        let (_, role) = *self.surfaces.get(&id)?;
+        let shell_info = self.shell_info(id)?;
+
+        Some(match role {
+            SurfaceRole::Main => HasOutput::Main,
+            SurfaceRole::Menu => HasOutput::Menu(shell_info.menu.menu_info.as_ref()),
         })
     }
 
+    /// Name of the output owning the given surface (bar or menu)
     pub fn get_monitor_name(&self, id: Id) -> Option<&str> {
-        self.0.iter().find_map(|(name, info, _)| {
-            info.as_ref().and_then(|info| {
-                if info.id == id {
-                    Some(name.as_str())
-                } else {
-                    None
-                }
-            })
-        })
+        let (key, _) = self.surfaces.get(&id)?;
+
+        self.entries.get(key).map(|entry| entry.name.as_str())
     }
 
-    pub fn has_name(&self, name: &str) -> bool {
-        self.0
+    /// Output name of the given surface and position of its bar between the bars of the output.
+    pub fn bar_index(&self, id: Id) -> Option<(&str, usize)> {
+        let (key, _) = self.surfaces.get(&id)?;
+        let entry = self.entries.get(key)?;
+        let index = entry
+            .bars
             .iter()
-            .any(|(n, info, _)| info.is_some() && n.as_str().contains(name))
+            .position(|shell_info| shell_info.owns(id))?;
+
+        Some((entry.name.as_str(), index))
+    }
+
+    /// Edge of the screen of the bar owning the given surface.
+    pub fn position(&self, id: Id) -> Option<Position> {
+        self.shell_info(id)
+            .map(|shell_info| shell_info.config.position)
+    }
+
+    /// Scale of the bar owning the given surface, and of its menu.
+    pub fn scale_factor(&self, id: Id) -> Option<f64> {
+        self.shell_info(id)
+            .map(|shell_info| shell_info.config.scale_factor)
+    }
+
+    /// Logical height of the bar owning the given surface,
+    /// and of the content drawn on it.
+    pub fn height(&self, id: Id) -> (f32, f32) {
+        let config = self
+            .shell_info(id)
+            .map_or(&self.config.default, |shell_info| &shell_info.config);
+
+        (config.height as f32, config.content_height() as f32)
+    }
+
+    /// Wayland output whose name contains the given one.
+    pub fn wl_output(&self, name: &str) -> Option<WlOutput> {
+        self.entries
+            .values()
+            .find(|entry| entry.name.contains(name))
+            .and_then(|entry| entry.wl_output.clone())
+    }
+
+    pub fn has_name(&self, name: &str) -> bool {
+        self.entries
+            .values()
+            .any(|entry| !entry.bars.is_empty() && entry.name.as_str().contains(name))
     }
 
-    #[allow(clippy::too_many_arguments)]
     pub fn add<Message: 'static>(
         &mut self,
-        style: AppearanceStyle,
-        request_outputs: &config::Outputs,
-        position: Position,
-        layer: config::Layer,
         name: &str,
         wl_output: WlOutput,
-        scale_factor: f64,
+        logical_size: Option<(i32, i32)>,
     ) -> Task<Message> {
-        let target = Self::name_in_config(name, request_outputs);
-
-        if target {
-            debug!("Found target output, creating a new layer surface");
-
-            let (id, menu_id, task) = Self::create_output_layers(
-                style,
-                Some(wl_output.clone()),
-                position,
-                layer,
-                scale_factor,
-            );
-
-            let destroy_task = match self.0.iter().position(|(key, _, _)| key.as_str() == name) {
-                Some(index) => {
-                    let old_output = self.0.swap_remove(index);
+        let target = !self.hidden && Self::name_in_config(name, &self.config.targets);
+
+        let key = match self.find_key(|entry| entry.name == name) {
+            Some(key) => key,
+            None => self.insert(OutputEntry {
+                name: name.to_owned(),
+                bars: Vec::new(),
+                wl_output: None,
+                logical_size: None,
+            }),
+        };
 
-                    match old_output.1 {
-                        Some(shell_info) => {
-                            let destroy_main_task = destroy_layer_surface(shell_info.id);
-                            let destroy_menu_task = destroy_layer_surface(shell_info.menu.id);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.wl_output = Some(wl_output.clone());
+            if let Some((width, height)) = logical_size {
+                entry.logical_size = Some((width as f32, height as f32));
+            }
+        }
 
-                            Task::batch(vec![destroy_main_task, destroy_menu_task])
-                        }
-                        _ => Task::none(),
-                    }
-                }
-                _ => Task::none(),
-            };
+        if target {
+            debug!("Found target output, creating new layer surfaces");
 
-            self.0.push((
-                name.to_owned(),
-                Some(ShellInfo {
-                    id,
-                    menu: Menu::new(menu_id),
-                    position,
-                    layer,
-                    style,
-                    scale_factor,
-                }),
-                Some(wl_output),
-            ));
+            let destroy_task = Self::destroy_bars(&self.detach(key));
+            let task = self.attach_bars(key, self.config.get(name), Some(wl_output));
 
             // remove fallback layer surface
-            let destroy_fallback_task =
-                match self.0.iter().position(|(_, _, output)| output.is_none()) {
-                    Some(index) => {
-                        let old_output = self.0.swap_remove(index);
-
-                        match old_output.1 {
-                            Some(shell_info) => {
-                                let destroy_fallback_main_task =
-                                    destroy_layer_surface(shell_info.id);
-                                let destroy_fallback_menu_task =
-                                    destroy_layer_surface(shell_info.menu.id);
-
-                                Task::batch(vec![
-                                    destroy_fallback_main_task,
-                                    destroy_fallback_menu_task,
-                                ])
-                            }
-                            _ => Task::none(),
-                        }
-                    }
-                    _ => Task::none(),
-                };
+            let destroy_fallback_task = self
+                .find_key(|entry| entry.wl_output.is_none())
+                .and_then(|key| self.take(key))
+                .map_or_else(Task::none, |entry| Self::destroy_bars(&entry.bars));
 
             Task::batch(vec![destroy_task, destroy_fallback_task, task])
         } else {
-            self.0.push((name.to_owned(), None, Some(wl_output)));
-
             Task::none()
         }
     }
 
-    pub fn remove<Message: 'static>(
-        &mut self,
-        style: AppearanceStyle,
-        position: Position,
-        layer: config::Layer,
-        wl_output: WlOutput,
-        scale_factor: f64,
-    ) -> Task<Message> {
-        match self.0.iter().position(|(_, _, assigned_wl_output)| {
-            assigned_wl_output
+    pub fn remove<Message: 'static>(&mut self, wl_output: WlOutput) -> Task<Message> {
+        match self.find_key(|entry| {
+            entry
+                .wl_output
                 .as_ref()
                 .is_some_and(|assigned_wl_output| *assigned_wl_output == wl_output)
         }) {
-            Some(index_to_remove) => {
-                debug!("Removing layer surface for output");
-
-                let (name, shell_info, wl_output) = self.0.swap_remove(index_to_remove);
+            Some(key) => {
+                debug!("Removing layer surfaces for output");
 
-                let destroy_task = if let Some(shell_info) = shell_info {
-                    let destroy_main_task = destroy_layer_surface(shell_info.id);
-                    let destroy_menu_task = destroy_layer_surface(shell_info.menu.id);
+                let destroy_task = Self::destroy_bars(&self.detach(key));
 
-                    Task::batch(vec![destroy_main_task, destroy_menu_task])
-                } else {
-                    Task::none()
-                };
-
-                self.0.push((name, None, wl_output));
-
-                if self.0.iter().any(|(_, shell_info, _)| shell_info.is_some()) {
-                    Task::batch(vec![destroy_task])
+                if self.hidden || self.all_bars().next().is_some() {
+                    destroy_task
                 } else {
                     debug!("No outputs left, creating a fallback layer surface");
 
-                    let (id, menu_id, task) =
-                        Self::create_output_layers(style, None, position, layer, scale_factor);
-
-                    self.0.push((
-                        "Fallback".to_string(),
-                        Some(ShellInfo {
-                            id,
-                            menu: Menu::new(menu_id),
-                            position,
-                            layer,
-                            style,
-                            scale_factor,
-                        }),
-                        None,
-                    ));
+                    let task = self.add_fallback();
 
                     Task::batch(vec![destroy_task, task])
                 }
@@ -306,36 +752,46 @@ impl Outputs {
         }
     }
 
-    pub fn sync<Message: 'static>(
-        &mut self,
-        style: AppearanceStyle,
-        request_outputs: &config::Outputs,
-        position: Position,
-        layer: config::Layer,
-        scale_factor: f64,
-    ) -> Task<Message> {
-        debug!("Syncing outputs: {self:?}, request_outputs: {request_outputs:?}");
+    /// Applies new settings to the bars, after a config change.
+    pub fn set_config<Message: 'static>(&mut self, config: OutputsConfig) -> Task<Message> {
+        if self.config == config {
+            return Task::none();
+        }
+
+        self.config = config;
+        self.sync()
+    }
+
+    pub fn sync<Message: 'static>(&mut self) -> Task<Message> {
+        debug!("Syncing outputs: {self:?}");
+
+        if self.hidden {
+            // the surfaces are created with the new settings once the bars are shown again
+            return Task::none();
+        }
 
         let to_remove = self
-            .0
-            .iter()
-            .filter_map(|(name, shell_info, wl_output)| {
-                if !Self::name_in_config(name, request_outputs) && shell_info.is_some() {
-                    Some(wl_output.clone())
+            .entries
+            .values()
+            .filter_map(|entry| {
+                if !Self::name_in_config(&entry.name, &self.config.targets)
+                    && !entry.bars.is_empty()
+                {
+                    entry.wl_output.clone()
                 } else {
                     None
                 }
             })
-            .flatten()
             .collect::<Vec<_>>();
         debug!("Removing outputs: {to_remove:?}");
 
         let to_add = self
-            .0
-            .iter()
-            .filter_map(|(name, shell_info, wl_output)| {
-                if Self::name_in_config(name, request_outputs) && shell_info.is_none() {
-                    Some((name.clone(), wl_output.clone()))
+            .entries
+            .values()
+            .filter_map(|entry| {
+                if Self::name_in_config(&entry.name, &self.config.targets) && entry.bars.is_empty()
+                {
+                    Some((entry.name.clone(), entry.wl_output.clone()))
                 } else {
                     None
                 }
@@ -347,111 +803,410 @@ impl Outputs {
 
         for (name, wl_output) in to_add {
             if let Some(wl_output) = wl_output {
-                tasks.push(self.add(
-                    style,
-                    request_outputs,
-                    position,
-                    layer,
-                    name.as_str(),
-                    wl_output,
-                    scale_factor,
-                ));
+                tasks.push(self.add(name.as_str(), wl_output, None));
             }
         }
 
         for wl_output in to_remove {
-            tasks.push(self.remove(style, position, layer, wl_output, scale_factor));
+            tasks.push(self.remove(wl_output));
         }
 
-        for shell_info in self.0.iter_mut().filter_map(|(_, shell_info, _)| {
-            if let Some(shell_info) = shell_info
-                && shell_info.position != position
-            {
-                Some(shell_info)
-            } else {
-                None
+        // bars are added or removed when the number of bars of an output changed,
+        // and recreated only when their settings can't be changed in place
+        let keys = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.bars.is_empty())
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+
+        for key in keys {
+            let Some((configs, output_size, wl_output)) = self.entries.get(&key).map(|entry| {
+                (
+                    self.config.get(&entry.name),
+                    entry.logical_size,
+                    entry.wl_output.clone(),
+                )
+            }) else {
+                continue;
+            };
+
+            let mut old_bars = self.detach(key).into_iter();
+            let mut kept = 0;
+
+            for config in &configs {
+                let Some(mut shell_info) = old_bars.next() else {
+                    break;
+                };
+
+                if shell_info.config != *config || shell_info.output_size != output_size {
+                    match shell_info.set_config(*config, output_size) {
+                        Some(task) => tasks.push(task),
+                        None => {
+                            // the bars after it are created again as well, to keep their order
+                            tasks.push(Self::destroy_output_layers(&shell_info));
+                            break;
+                        }
+                    }
+                }
+
+                self.attach(key, shell_info);
+                kept += 1;
             }
-        }) {
+
+            let old_bars = old_bars.collect::<Vec<_>>();
             debug!(
-                "Repositioning output: {:?}, new position {:?}",
-                shell_info.id, position
+                "Output bars: {kept} kept, {} destroyed, {} created",
+                old_bars.len(),
+                configs.len() - kept
             );
-            shell_info.position = position;
-            tasks.push(set_anchor(
-                shell_info.id,
-                match position {
-                    Position::Top => Anchor::TOP,
-                    Position::Bottom => Anchor::BOTTOM,
-                } | Anchor::LEFT
-                    | Anchor::RIGHT,
-            ));
+
+            tasks.push(Self::destroy_bars(&old_bars));
+            tasks.push(self.attach_bars(key, configs[kept..].to_vec(), wl_output));
         }
 
-        // Handle layer changes - only recreate surfaces when layer actually changes
-        for (_name, shell_info, wl_output) in &mut self.0 {
-            if let Some(shell_info) = shell_info
-                && shell_info.layer != layer
-            {
-                let destroy_main_task = destroy_layer_surface(shell_info.id);
-                let destroy_menu_task = destroy_layer_surface(shell_info.menu.id);
-
-                let (id, menu_id, task) = Self::create_output_layers(
-                    style,
-                    wl_output.clone(),
-                    position,
-                    layer,
-                    scale_factor,
-                );
-
-                shell_info.id = id;
-                shell_info.menu = Menu::new(menu_id);
-                shell_info.position = position;
-                shell_info.layer = layer;
-                shell_info.style = style;
-                shell_info.scale_factor = scale_factor;
-
-                tasks.push(Task::batch(vec![
-                    destroy_main_task,
-                    destroy_menu_task,
-                    task,
-                ]));
+        Task::batch(tasks)
+    }
+
+    /// Hides the bars by destroying their surfaces, or creates them again.
+    pub fn toggle_visibility<Message: 'static>(&mut self) -> Task<Message> {
+        self.hidden = !self.hidden;
+
+        if self.hidden {
+            debug!("Hiding the bars");
+
+            let keys = self.entries.keys().copied().collect::<Vec<_>>();
+            let tasks = keys
+                .into_iter()
+                .map(|key| {
+                    let is_fallback = self
+                        .entries
+                        .get(&key)
+                        .is_some_and(|entry| entry.wl_output.is_none());
+
+                    let bars = if is_fallback {
+                        self.take(key).map(|entry| entry.bars).unwrap_or_default()
+                    } else {
+                        self.detach(key)
+                    };
+
+                    Self::destroy_bars(&bars)
+                })
+                .collect::<Vec<_>>();
+
+            Task::batch(tasks)
+        } else {
+            debug!("Showing the bars");
+
+            let task = self.sync();
+
+            if self.all_bars().next().is_some() {
+                task
+            } else {
+                Task::batch(vec![task, self.add_fallback()])
             }
         }
+    }
 
-        for shell_info in self.0.iter_mut().filter_map(|(_, shell_info, _)| {
-            if let Some(shell_info) = shell_info
-                && (shell_info.style != style || shell_info.scale_factor != scale_factor)
-            {
-                Some(shell_info)
-            } else {
-                None
+    /// Tracks the pointer on the bars, an auto-hidden bar is revealed
+    /// as soon as the pointer reaches it.
+    pub fn set_hovered<Message: 'static>(&mut self, id: Id, hovered: bool) -> Task<Message> {
+        if !matches!(self.surfaces.get(&id), Some((_, SurfaceRole::Main))) {
+            return Task::none();
+        }
+        let Some(shell_info) = self.shell_info_mut(id) else {
+            return Task::none();
+        };
+
+        shell_info.hovered = hovered;
+
+        if !shell_info.config.auto_hide.enabled {
+            Task::none()
+        } else if hovered {
+            shell_info.reveal()
+        } else {
+            shell_info.idle_since = Some(Instant::now());
+
+            Task::none()
+        }
+    }
+
+    /// Whether an auto-hidden bar has to be hidden or revealed.
+    pub fn auto_hide_pending(&self) -> bool {
+        self.all_bars()
+            .filter(|shell_info| shell_info.config.auto_hide.enabled)
+            .any(|shell_info| {
+                let menu_open = shell_info.menu.menu_info.is_some();
+
+                if shell_info.revealed_at.is_some() {
+                    !shell_info.hovered && !menu_open
+                } else {
+                    menu_open
+                }
+            })
+    }
+
+    /// Reveals the auto-hidden bars with an open menu and collapses the ones
+    /// that haven't been used for their delay.
+    pub fn update_auto_hide<Message: 'static>(&mut self) -> Task<Message> {
+        let now = Instant::now();
+
+        Task::batch(
+            self.all_bars_mut()
+                .filter(|shell_info| shell_info.config.auto_hide.enabled)
+                .map(|shell_info| {
+                    if shell_info.hovered || shell_info.menu.menu_info.is_some() {
+                        return shell_info.reveal();
+                    }
+
+                    let idle_since = *shell_info.idle_since.get_or_insert(now);
+                    let delay = Duration::from_millis(shell_info.config.auto_hide.delay);
+
+                    if now.saturating_duration_since(idle_since) >= delay {
+                        shell_info.collapse()
+                    } else {
+                        Task::none()
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Whether the bars hidden by fullscreen windows have to be tracked.
+    pub fn hides_on_fullscreen(&self) -> bool {
+        !self.hidden
+            && self
+                .all_bars()
+                .any(|shell_info| shell_info.config.hide_on_fullscreen)
+    }
+
+    /// Hides the bars of the outputs showing a fullscreen window, releasing
+    /// their exclusive zone, and shows the others again.
+    pub fn set_fullscreen<Message: 'static>(&mut self, monitors: &[&str]) -> Task<Message> {
+        Task::batch(
+            self.entries
+                .values_mut()
+                .flat_map(|entry| {
+                    let name = entry.name.as_str();
+                    let fullscreen = monitors.iter().any(|monitor| name.contains(monitor));
+
+                    entry.bars.iter_mut().filter_map(move |shell_info| {
+                        let fullscreen = fullscreen && shell_info.config.hide_on_fullscreen;
+
+                        (shell_info.fullscreen != fullscreen).then(|| {
+                            debug!("Fullscreen window on {name}: {fullscreen}");
+                            shell_info.fullscreen = fullscreen;
+
+                            shell_info.resize()
+                        })
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Whether the content of the bar is hidden, because it's auto-hidden
+    /// and collapsed to the strip on the screen edge or because of a fullscreen window.
+    pub fn is_collapsed(&self, id: Id) -> bool {
+        self.shell_info(id)
+            .is_some_and(|shell_info| shell_info.fullscreen || !shell_info.is_revealed())
+    }
+
+    /// When the auto-hidden bar was revealed.
+    pub fn revealed_at(&self, id: Id) -> Option<Instant> {
+        self.shell_info(id)?.revealed_at
+    }
+
+    /// Reveal times of the auto-hidden bars.
+    pub fn bars_revealed_at(&self) -> impl Iterator<Item = Instant> + '_ {
+        self.all_bars()
+            .filter_map(|shell_info| shell_info.revealed_at)
+    }
+
+    /// When the menu of the given surface was opened.
+    pub fn menu_opened_at(&self, id: Id) -> Option<Instant> {
+        self.shell_info(id)?.menu.opened_at
+    }
+
+    /// Opening times of the open menus.
+    pub fn menus_opened_at(&self) -> impl Iterator<Item = Instant> + '_ {
+        self.all_bars()
+            .filter_map(|shell_info| shell_info.menu.opened_at)
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Whether every bar surface has been around for a few frames, so that
+    /// it has been configured by the compositor and rendered at least once.
+    pub fn is_ready(&self) -> bool {
+        let mut bars = self.all_bars().peekable();
+
+        self.hidden
+            || bars.peek().is_some() && bars.all(|info| info.created_at.elapsed() >= READY_DELAY)
+    }
+
+    /// Tracks the size of an output, the bars shorter than
+    /// the screen edge are resized to follow it.
+    pub fn set_logical_size<Message: 'static>(
+        &mut self,
+        name: &str,
+        size: Option<(i32, i32)>,
+    ) -> Task<Message> {
+        let Some(entry) = self
+            .find_key(|entry| entry.name == name)
+            .and_then(|key| self.entries.get_mut(&key))
+        else {
+            return Task::none();
+        };
+        let size = size.map(|(width, height)| (width as f32, height as f32));
+        entry.logical_size = size;
+
+        Task::batch(
+            entry
+                .bars
+                .iter_mut()
+                .filter(|shell_info| shell_info.output_size != size)
+                .filter_map(|shell_info| shell_info.set_config(shell_info.config, size))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Moves the position of a button of the bar owning the given surface to the
+    /// coordinates of the menu surface, that covers the whole output while the bar
+    /// can be shorter than the screen edge or be away from it.
+    pub fn to_output_coordinates(&self, id: Id, button_ui_ref: ButtonUIRef) -> ButtonUIRef {
+        let Some(shell_info) = self.shell_info(id) else {
+            return button_ui_ref;
+        };
+        let config = shell_info.config;
+        let scale_factor = config.scale_factor as f32;
+        let origin = config.origin(shell_info.output_size) / scale_factor;
+        let (width, height) = shell_info
+            .output_size
+            .map_or(button_ui_ref.viewport, |(width, height)| {
+                (width / scale_factor, height / scale_factor)
+            });
+
+        if config.position.is_vertical() {
+            ButtonUIRef {
+                position: Point::new(button_ui_ref.position.x, button_ui_ref.position.y + origin),
+                viewport: (button_ui_ref.viewport.0, height),
+            }
+        } else {
+            ButtonUIRef {
+                position: Point::new(button_ui_ref.position.x + origin, button_ui_ref.position.y),
+                viewport: (width, button_ui_ref.viewport.1),
             }
-        }) {
-            debug!(
-                "Change style or scale_factor for output: {:?}, new style {:?}, new scale_factor {:?}",
-                shell_info.id, style, scale_factor
-            );
-            shell_info.style = style;
-            shell_info.scale_factor = scale_factor;
-            let height = Self::get_height(style, scale_factor);
-            tasks.push(Task::batch(vec![
-                set_size(shell_info.id, None, Some(height as u32)),
-                set_exclusive_zone(shell_info.id, height as i32),
-            ]));
         }
+    }
 
-        Task::batch(tasks)
+    /// Length of the bar owning the given surface along
+    /// the screen edge in logical pixels, when it's known.
+    pub fn bar_length(&self, id: Id) -> Option<f32> {
+        let shell_info = self.shell_info(id)?;
+        let scale_factor = shell_info.config.scale_factor as f32;
+
+        match shell_info.config.length(shell_info.output_size) {
+            Some(length) => Some(length as f32 / scale_factor),
+            None => shell_info.output_size.map(|(width, height)| {
+                let edge = if shell_info.config.position.is_vertical() {
+                    height - (shell_info.config.margin.top + shell_info.config.margin.bottom) as f32
+                } else {
+                    width - (shell_info.config.margin.left + shell_info.config.margin.right) as f32
+                };
+
+                edge / scale_factor
+            }),
+        }
+    }
+
+    /// First bar surface of the first output whose name contains `name`,
+    /// or of the first output with a bar.
+    pub fn main_id(&self, name: Option<&str>) -> Option<Id> {
+        self.entries
+            .values()
+            .filter(|entry| name.is_none_or(|name| entry.name.contains(name)))
+            .find_map(|entry| entry.bars.first().map(|info| info.id))
+    }
+
+    /// Output names of the bars and their open menu.
+    pub fn bars(&self) -> impl Iterator<Item = (&str, Option<&MenuType>)> {
+        self.entries.values().flat_map(|entry| {
+            entry.bars.iter().map(|shell_info| {
+                (
+                    entry.name.as_str(),
+                    shell_info
+                        .menu
+                        .menu_info
+                        .as_ref()
+                        .map(|(menu_type, _)| menu_type),
+                )
+            })
+        })
     }
 
+    /// Outputs and their surfaces, as reported by the `diagnostics` IPC command.
+    pub fn diagnostics(&self) -> Vec<serde_json::Value> {
+        self.entries
+            .values()
+            .map(|entry| {
+                serde_json::json!({
+                    "name": entry.name,
+                    "connected": entry.wl_output.is_some(),
+                    "logical_size": entry.logical_size,
+                    "bars": entry.bars.iter().map(|shell_info| serde_json::json!({
+                        "surface": format!("{:?}", shell_info.id),
+                        "menu_surface": format!("{:?}", shell_info.menu.id),
+                        "height": shell_info.height(),
+                        "exclusive_zone": shell_info.exclusive_zone(),
+                        "length": shell_info.config.length(shell_info.output_size),
+                        "margin": shell_info.config.margin,
+                        "position": shell_info.config.position,
+                        "layer": shell_info.config.layer,
+                        "style": shell_info.config.style,
+                        "scale_factor": shell_info.config.scale_factor,
+                        "revealed": shell_info.is_revealed(),
+                        "fullscreen": shell_info.fullscreen,
+                        "age_ms": shell_info.created_at.elapsed().as_millis() as u64,
+                        "ready": shell_info.created_at.elapsed() >= READY_DELAY,
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether a menu is open, the tooltips aren't considered menus.
     pub fn menu_is_open(&self) -> bool {
-        self.0.iter().any(|(_, shell_info, _)| {
+        self.all_bars().any(|shell_info| {
             shell_info
+                .menu
+                .menu_info
                 .as_ref()
-                .map(|shell_info| shell_info.menu.menu_info.is_some())
-                .unwrap_or_default()
+                .is_some_and(|(menu_type, _)| !matches!(menu_type, MenuType::Tooltip(..)))
         })
     }
 
+    /// Whether the bar owning the given surface reserves its space on the screen,
+    /// otherwise the menu surface covers the bar.
+    pub fn is_exclusive(&self, id: Id) -> bool {
+        self.shell_info(id)
+            .is_some_and(|shell_info| shell_info.config.exclusive_zone() > 0)
+    }
+
+    /// Closes the tooltip of the bar owning the given surface, leaving its menu open.
+    pub fn close_tooltip<Message: 'static>(&mut self, id: Id) -> Task<Message> {
+        match self.shell_info_mut(id) {
+            Some(shell_info)
+                if matches!(shell_info.menu.menu_info, Some((MenuType::Tooltip(..), _))) =>
+            {
+                shell_info.menu.close()
+            }
+            _ => Task::none(),
+        }
+    }
+
     pub fn toggle_menu<Message: 'static>(
         &mut self,
         id: Id,
@@ -459,29 +1214,17 @@ impl Outputs {
         button_ui_ref: ButtonUIRef,
         request_keyboard: bool,
     ) -> Task<Message> {
-        let task = match self.0.iter_mut().find(|(_, shell_info, _)| {
-            shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
-                || shell_info.as_ref().map(|shell_info| shell_info.menu.id) == Some(id)
-        }) {
-            Some((_, Some(shell_info), _)) => {
+        let task = match self.shell_info_mut(id) {
+            Some(shell_info) => {
+                let bar_id = shell_info.id;
                 let toggle_task =
                     shell_info
                         .menu
                         .toggle(menu_type, button_ui_ref, request_keyboard);
                 let mut tasks = self
-                    .0
-                    .iter_mut()
-                    .filter_map(|(_, shell_info, _)| {
-                        if let Some(shell_info) = shell_info {
-                            if shell_info.id != id && shell_info.menu.id != id {
-                                Some(shell_info.menu.close())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
+                    .all_bars_mut()
+                    .filter(|shell_info| shell_info.id != bar_id)
+                    .map(|shell_info| shell_info.menu.close())
                     .collect::<Vec<_>>();
                 tasks.push(toggle_task);
 
@@ -512,13 +1255,9 @@ impl Outputs {
         id: Id,
         esc_button_enabled: bool,
     ) -> Task<Message> {
-        let task = match self.0.iter_mut().find(|(_, shell_info, _)| {
-            shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
-                || shell_info.as_ref().map(|shell_info| shell_info.menu.id) == Some(id)
-        }) {
-            Some((_, Some(shell_info), _)) => shell_info.menu.close(),
-            _ => Task::none(),
-        };
+        let task = self
+            .shell_info_mut(id)
+            .map_or_else(Task::none, |shell_info| shell_info.menu.close());
 
         if esc_button_enabled && !self.menu_is_open() {
             Task::batch(vec![
@@ -536,13 +1275,9 @@ impl Outputs {
         menu_type: MenuType,
         esc_button_enabled: bool,
     ) -> Task<Message> {
-        let task = match self.0.iter_mut().find(|(_, shell_info, _)| {
-            shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
-                || shell_info.as_ref().map(|shell_info| shell_info.menu.id) == Some(id)
-        }) {
-            Some((_, Some(shell_info), _)) => shell_info.menu.close_if(menu_type),
-            _ => Task::none(),
-        };
+        let task = self
+            .shell_info_mut(id)
+            .map_or_else(Task::none, |shell_info| shell_info.menu.close_if(menu_type));
 
         if esc_button_enabled && !self.menu_is_open() {
             Task::batch(vec![
@@ -554,35 +1289,29 @@ impl Outputs {
         }
     }
 
+    fn release_all_keyboards<Message: 'static>(&self) -> Task<Message> {
+        Task::batch(
+            self.all_bars()
+                .map(|shell_info| {
+                    set_keyboard_interactivity(shell_info.id, KeyboardInteractivity::None)
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
     pub fn close_all_menu_if<Message: 'static>(
         &mut self,
         menu_type: MenuType,
         esc_button_enabled: bool,
     ) -> Task<Message> {
         let task = Task::batch(
-            self.0
-                .iter_mut()
-                .map(|(_, shell_info, _)| {
-                    if let Some(shell_info) = shell_info {
-                        shell_info.menu.close_if(menu_type.clone())
-                    } else {
-                        Task::none()
-                    }
-                })
+            self.all_bars_mut()
+                .map(|shell_info| shell_info.menu.close_if(menu_type.clone()))
                 .collect::<Vec<_>>(),
         );
 
         if esc_button_enabled && !self.menu_is_open() {
-            let keyboard_tasks = self
-                .0
-                .iter()
-                .map(|(_, shell_info, _)| {
-                    shell_info.as_ref().map_or_else(Task::none, |shell_info| {
-                        set_keyboard_interactivity(shell_info.id, KeyboardInteractivity::None)
-                    })
-                })
-                .collect::<Vec<_>>();
-            Task::batch(vec![task, Task::batch(keyboard_tasks)])
+            Task::batch(vec![task, self.release_all_keyboards()])
         } else {
             task
         }
@@ -590,55 +1319,26 @@ impl Outputs {
 
     pub fn close_all_menus<Message: 'static>(&mut self, esc_button_enabled: bool) -> Task<Message> {
         let task = Task::batch(
-            self.0
-                .iter_mut()
-                .map(|(_, shell_info, _)| {
-                    if let Some(shell_info) = shell_info {
-                        if shell_info.menu.menu_info.is_some() {
-                            shell_info.menu.close()
-                        } else {
-                            Task::none()
-                        }
-                    } else {
-                        Task::none()
-                    }
-                })
+            self.all_bars_mut()
+                .filter(|shell_info| shell_info.menu.menu_info.is_some())
+                .map(|shell_info| shell_info.menu.close())
                 .collect::<Vec<_>>(),
         );
 
         if esc_button_enabled && !self.menu_is_open() {
-            let keyboard_tasks = self
-                .0
-                .iter()
-                .map(|(_, shell_info, _)| {
-                    shell_info.as_ref().map_or_else(Task::none, |shell_info| {
-                        set_keyboard_interactivity(shell_info.id, KeyboardInteractivity::None)
-                    })
-                })
-                .collect::<Vec<_>>();
-            Task::batch(vec![task, Task::batch(keyboard_tasks)])
+            Task::batch(vec![task, self.release_all_keyboards()])
         } else {
             task
         }
     }
 
     pub fn request_keyboard<Message: 'static>(&self, id: Id) -> Task<Message> {
-        match self.0.iter().find(|(_, shell_info, _)| {
-            shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
-                || shell_info.as_ref().map(|shell_info| shell_info.menu.id) == Some(id)
-        }) {
-            Some((_, Some(shell_info), _)) => shell_info.menu.request_keyboard(),
-            _ => Task::none(),
-        }
+        self.shell_info(id)
+            .map_or_else(Task::none, |shell_info| shell_info.menu.request_keyboard())
     }
 
     pub fn release_keyboard<Message: 'static>(&self, id: Id) -> Task<Message> {
-        match self.0.iter().find(|(_, shell_info, _)| {
-            shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
-                || shell_info.as_ref().map(|shell_info| shell_info.menu.id) == Some(id)
-        }) {
-            Some((_, Some(shell_info), _)) => shell_info.menu.release_keyboard(),
-            _ => Task::none(),
-        }
+        self.shell_info(id)
+            .map_or_else(Task::none, |shell_info| shell_info.menu.release_keyboard())
     }
 }
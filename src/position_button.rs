@@ -1,14 +1,17 @@
+use crate::animation::{Transition, mix_background, mix_color};
 use iced::{
-    Background, Color, Element, Length, Padding, Point, Rectangle, Size, Vector,
+    Background, Border, Color, Element, Length, Padding, Point, Rectangle, Size, Vector,
     core::{
         Clipboard, Layout, Shell, Widget,
         event::{self, Event},
         keyboard, layout, mouse, overlay, renderer, touch,
         widget::{Operation, Tree, tree},
+        window,
     },
     id::Id,
     widget::button::{Catalog, Status, Style, StyleFn},
 };
+use std::time::Instant;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ButtonUIRef {
@@ -28,11 +31,13 @@ where
 {
     content: Element<'a, Message, Theme, Renderer>,
     on_press: Option<OnPress<'a, Message>>,
+    on_right_press: Option<Box<dyn Fn(ButtonUIRef) -> Message + 'a>>,
     id: Id,
     width: Length,
     height: Length,
     padding: Padding,
     clip: bool,
+    hover_transition: Option<Transition>,
     class: Theme::Class<'a>,
 }
 
@@ -49,10 +54,12 @@ where
             content,
             id: Id::unique(),
             on_press: None,
+            on_right_press: None,
             width: size.width.fluid(),
             height: size.height.fluid(),
             padding: DEFAULT_PADDING,
             clip: false,
+            hover_transition: None,
             class: Theme::default(),
         }
     }
@@ -91,6 +98,15 @@ where
         self
     }
 
+    /// Sets the message produced when the [`Button`] is pressed with the right button.
+    pub fn on_right_press_with_position(
+        mut self,
+        on_right_press: impl Fn(ButtonUIRef) -> Message + 'a,
+    ) -> Self {
+        self.on_right_press = Some(Box::new(on_right_press));
+        self
+    }
+
     /// Sets whether the contents of the [`Button`] should be clipped on
     /// overflow.
     pub fn clip(mut self, clip: bool) -> Self {
@@ -98,6 +114,12 @@ where
         self
     }
 
+    /// Sets the transition between the active and the hovered style.
+    pub fn hover_transition(mut self, transition: Option<Transition>) -> Self {
+        self.hover_transition = transition;
+        self
+    }
+
     /// Sets the style of the [`Button`].
     #[must_use]
     pub fn style(mut self, style: impl Fn(&Theme, Status) -> Style + 'a) -> Self
@@ -119,7 +141,22 @@ where
 struct State {
     is_hovered: bool,
     is_pressed: bool,
+    is_right_pressed: bool,
     is_focused: bool,
+    hover_changed_at: Option<Instant>,
+}
+
+/// Blends the colors of two button styles, used while the hover transition runs.
+fn mix_style(from: Style, to: Style, progress: f32) -> Style {
+    Style {
+        background: mix_background(from.background, to.background, progress),
+        text_color: mix_color(from.text_color, to.text_color, progress),
+        border: Border {
+            color: mix_color(from.border.color, to.border.color, progress),
+            ..to.border
+        },
+        ..to
+    }
 }
 
 impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
@@ -193,6 +230,27 @@ where
         shell: &mut Shell<'_, Message>,
         viewport: &Rectangle,
     ) -> event::Status {
+        if let Some(transition) = self.hover_transition {
+            let state = tree.state.downcast_mut::<State>();
+
+            if let Event::Window(window::Event::RedrawRequested(now)) = event {
+                if state
+                    .hover_changed_at
+                    .is_some_and(|since| transition.is_running(since, now))
+                {
+                    shell.request_redraw(window::RedrawRequest::NextFrame);
+                }
+            } else {
+                let is_hovered = cursor.is_over(layout.bounds());
+
+                if is_hovered != state.is_hovered {
+                    state.is_hovered = is_hovered;
+                    state.hover_changed_at = Some(Instant::now());
+                    shell.request_redraw(window::RedrawRequest::NextFrame);
+                }
+            }
+        }
+
         if let event::Status::Captured = self.content.as_widget_mut().on_event(
             &mut tree.children[0],
             event.clone(),
@@ -206,13 +264,20 @@ where
             return event::Status::Captured;
         }
 
+        // a touch has no cursor, its position comes with the event
+        let is_over = match event {
+            Event::Touch(
+                touch::Event::FingerPressed { position, .. }
+                | touch::Event::FingerLifted { position, .. },
+            ) => layout.bounds().contains(position),
+            _ => cursor.is_over(layout.bounds()),
+        };
+
         match event {
             Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
             | Event::Touch(touch::Event::FingerPressed { .. }) => {
                 if self.on_press.is_some() {
-                    let bounds = layout.bounds();
-
-                    if cursor.is_over(bounds) {
+                    if is_over {
                         let state = tree.state.downcast_mut::<State>();
 
                         state.is_pressed = true;
@@ -229,9 +294,7 @@ where
                     if state.is_pressed {
                         state.is_pressed = false;
 
-                        let bounds = layout.bounds();
-
-                        if cursor.is_over(bounds) {
+                        if is_over {
                             match on_press {
                                 OnPress::Message(message) => {
                                     shell.publish(message.clone());
@@ -253,6 +316,36 @@ where
                     }
                 }
             }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                if self.on_right_press.is_some() && is_over {
+                    let state = tree.state.downcast_mut::<State>();
+
+                    state.is_right_pressed = true;
+
+                    return event::Status::Captured;
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Right)) => {
+                if let Some(on_right_press) = self.on_right_press.as_ref() {
+                    let state = tree.state.downcast_mut::<State>();
+
+                    if state.is_right_pressed {
+                        state.is_right_pressed = false;
+
+                        if is_over {
+                            shell.publish(on_right_press(ButtonUIRef {
+                                position: Point::new(
+                                    layout.bounds().width / 2. + layout.position().x,
+                                    layout.bounds().height / 2. + layout.position().y,
+                                ),
+                                viewport: (viewport.width, viewport.height),
+                            }));
+                        }
+
+                        return event::Status::Captured;
+                    }
+                }
+            }
             Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => {
                 if let Some(on_press) = self.on_press.as_ref() {
                     let state = tree.state.downcast_mut::<State>();
@@ -284,6 +377,7 @@ where
                 let state = tree.state.downcast_mut::<State>();
                 state.is_hovered = false;
                 state.is_pressed = false;
+                state.is_right_pressed = false;
             }
             _ => {}
         }
@@ -319,7 +413,23 @@ where
             Status::Active
         };
 
-        let style = theme.style(&self.class, status);
+        let state = tree.state.downcast_ref::<State>();
+        let style = match (self.hover_transition, state.hover_changed_at, status) {
+            (Some(transition), Some(since), Status::Active | Status::Hovered) => {
+                let (from, to) = if status == Status::Hovered {
+                    (Status::Active, Status::Hovered)
+                } else {
+                    (Status::Hovered, Status::Active)
+                };
+
+                mix_style(
+                    theme.style(&self.class, from),
+                    theme.style(&self.class, to),
+                    transition.progress(since, Instant::now()),
+                )
+            }
+            _ => theme.style(&self.class, status),
+        };
 
         if style.background.is_some() || style.border.width > 0.0 || style.shadow.color.a > 0.0 {
             renderer.fill_quad(
@@ -0,0 +1,182 @@
+use crate::services::logind::{Login1ManagerProxy, Login1SessionProxy};
+use iced::{
+    Subscription,
+    futures::{SinkExt, StreamExt, stream::BoxStream},
+    stream::channel,
+};
+use log::{debug, warn};
+use std::{any::TypeId, fs, sync::LazyLock, time::Duration};
+use tokio::sync::watch;
+
+const DRM_PATH: &str = "/sys/class/drm";
+const DISPLAYS_POLL: Duration = Duration::from_secs(2);
+
+/// Whether the polling of the modules is paused, read by the long running
+/// streams that schedule their own work.
+static PAUSED: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::channel(false).0);
+
+/// Waits until somebody can see the bar again, returns at once when the
+/// polling isn't paused.
+pub async fn active() {
+    let _ = PAUSED.subscribe().wait_for(|paused| !paused).await;
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Locked(bool),
+    DisplaysOff(bool),
+}
+
+/// Whether the displays of the connected outputs are all turned off, `None`
+/// when the kernel doesn't tell.
+fn displays_off() -> Option<bool> {
+    let mut found = false;
+
+    for connector in fs::read_dir(DRM_PATH).ok()?.flatten() {
+        let path = connector.path();
+        let connected = fs::read_to_string(path.join("status"))
+            .is_ok_and(|status| status.trim() == "connected");
+        if !connected {
+            continue;
+        }
+        found = true;
+
+        if fs::read_to_string(path.join("dpms")).is_ok_and(|dpms| dpms.trim() == "On") {
+            return Some(false);
+        }
+    }
+
+    found.then_some(true)
+}
+
+/// Whether the session is locked, and its changes from logind.
+///
+/// Only the locked hint set by the locker is followed, the `Lock` signal
+/// asks for a lock but no `Unlock` comes once the user unlocks the session.
+async fn lock_events() -> anyhow::Result<(bool, BoxStream<'static, bool>)> {
+    let connection = zbus::Connection::system().await?;
+    let manager = Login1ManagerProxy::new(&connection).await?;
+    let path = manager.get_session("auto").await?;
+    let session = Login1SessionProxy::builder(&connection)
+        .path(path)?
+        .build()
+        .await?;
+
+    let locked = session.locked_hint().await.unwrap_or_default();
+    let events = session
+        .receive_locked_hint_changed()
+        .await
+        .filter_map(|change| async move { change.get().await.ok() })
+        .boxed();
+
+    Ok((locked, events))
+}
+
+/// Pauses the polling of the modules while nobody can see the bar: the
+/// session is locked, the displays are off or the bars are hidden.
+///
+/// The polled modules sample again as soon as their subscriptions restart,
+/// so nothing has to be refreshed when the polling resumes.
+#[derive(Debug)]
+pub struct Scheduler {
+    enabled: bool,
+    locked: bool,
+    displays_off: bool,
+    hidden: bool,
+}
+
+impl Scheduler {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            locked: false,
+            displays_off: false,
+            hidden: false,
+        }
+    }
+
+    pub fn paused(&self) -> bool {
+        self.enabled && (self.locked || self.displays_off || self.hidden)
+    }
+
+    fn publish(&self, was_paused: bool) {
+        let paused = self.paused();
+        if paused != was_paused {
+            debug!(
+                "Polling of the modules {}",
+                if paused { "paused" } else { "resumed" }
+            );
+        }
+        PAUSED.send_if_modified(|current| std::mem::replace(current, paused) != paused);
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        let was_paused = self.paused();
+        self.enabled = enabled;
+        if !enabled {
+            self.locked = false;
+            self.displays_off = false;
+        }
+
+        self.publish(was_paused)
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        let was_paused = self.paused();
+        self.hidden = hidden;
+
+        self.publish(was_paused)
+    }
+
+    pub fn update(&mut self, message: Message) {
+        let was_paused = self.paused();
+        match message {
+            Message::Locked(locked) => self.locked = locked,
+            Message::DisplaysOff(off) => self.displays_off = off,
+        }
+
+        self.publish(was_paused)
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if !self.enabled {
+            return Subscription::none();
+        }
+
+        Subscription::batch([
+            Subscription::run_with_id(
+                TypeId::of::<Self>(),
+                channel(10, async move |mut output| match lock_events().await {
+                    Ok((locked, mut events)) => {
+                        let _ = output.send(locked).await;
+
+                        while let Some(locked) = events.next().await {
+                            let _ = output.send(locked).await;
+                        }
+                    }
+                    Err(e) => warn!("Failed to watch the lock of the session: {e}"),
+                }),
+            )
+            .map(Message::Locked),
+            Subscription::run_with_id(
+                (TypeId::of::<Self>(), DRM_PATH),
+                channel(10, async move |mut output| {
+                    let mut current = None;
+
+                    loop {
+                        let off = displays_off();
+                        if off != current
+                            && let Some(off) = off
+                        {
+                            let _ = output.send(off).await;
+                        }
+                        current = off;
+
+                        tokio::time::sleep(DISPLAYS_POLL).await;
+                    }
+                }),
+            )
+            .map(Message::DisplaysOff),
+        ])
+    }
+}
@@ -0,0 +1,137 @@
+use super::{ReadOnlyService, ServiceEvent};
+use iced::{
+    Subscription,
+    futures::{SinkExt, StreamExt},
+    stream::channel,
+};
+use log::{debug, warn};
+use std::any::TypeId;
+use zbus::{
+    Connection,
+    zvariant::{OwnedValue, Value},
+};
+
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorSchemePreference {
+    #[default]
+    NoPreference,
+    Dark,
+    Light,
+}
+
+impl From<&Value<'_>> for ColorSchemePreference {
+    fn from(value: &Value<'_>) -> Self {
+        match value {
+            Value::U32(1) => ColorSchemePreference::Dark,
+            Value::U32(2) => ColorSchemePreference::Light,
+            // the deprecated `Read` method wraps the value in another variant
+            Value::Value(value) => ColorSchemePreference::from(value.as_ref()),
+            _ => ColorSchemePreference::NoPreference,
+        }
+    }
+}
+
+/// System wide appearance preferences, read from the XDG desktop portal.
+#[derive(Debug, Clone)]
+pub struct AppearanceService {
+    pub color_scheme: ColorSchemePreference,
+}
+
+impl AppearanceService {
+    async fn read_color_scheme(proxy: &PortalSettingsProxy<'_>) -> ColorSchemePreference {
+        let value = match proxy.read_one(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY).await {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                debug!("ReadOne not available, falling back to Read: {e}");
+                proxy.read(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY).await
+            }
+        };
+
+        match value {
+            Ok(value) => ColorSchemePreference::from(&*value),
+            Err(e) => {
+                warn!("Failed to read the color scheme preference: {e}");
+                ColorSchemePreference::NoPreference
+            }
+        }
+    }
+}
+
+impl ReadOnlyService for AppearanceService {
+    type UpdateEvent = ColorSchemePreference;
+    type Error = String;
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        self.color_scheme = event;
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            id,
+            channel(10, async move |mut output| {
+                let connection = match Connection::session().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        let err = format!("Failed to connect to session bus: {e}");
+                        let _ = output.send(ServiceEvent::Error(err)).await;
+                        return;
+                    }
+                };
+
+                let proxy = match PortalSettingsProxy::new(&connection).await {
+                    Ok(p) => p,
+                    Err(e) => {
+                        let err = format!("Failed to create portal settings proxy: {e}");
+                        let _ = output.send(ServiceEvent::Error(err)).await;
+                        return;
+                    }
+                };
+
+                let mut stream = match proxy.receive_setting_changed().await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        let err = format!("Failed to subscribe to SettingChanged: {e}");
+                        let _ = output.send(ServiceEvent::Error(err)).await;
+                        return;
+                    }
+                };
+
+                let color_scheme = Self::read_color_scheme(&proxy).await;
+                let _ = output
+                    .send(ServiceEvent::Init(AppearanceService { color_scheme }))
+                    .await;
+
+                while let Some(signal) = stream.next().await {
+                    if let Ok(args) = signal.args()
+                        && args.namespace == APPEARANCE_NAMESPACE
+                        && args.key == COLOR_SCHEME_KEY
+                    {
+                        let color_scheme = ColorSchemePreference::from(&args.value);
+                        debug!("Color scheme preference changed: {color_scheme:?}");
+
+                        let _ = output.send(ServiceEvent::Update(color_scheme)).await;
+                    }
+                }
+            }),
+        )
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Settings",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait PortalSettings {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+    fn read(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+    #[zbus(signal)]
+    fn setting_changed(&self, namespace: &str, key: &str, value: Value<'_>) -> zbus::Result<()>;
+}
@@ -1,12 +1,9 @@
-use super::{ReadOnlyService, Service, ServiceEvent};
+use super::{Backoff, ReadOnlyService, Service, ServiceEvent, restarted};
 use dbus::{BatteryProxy, BluetoothDbus};
 use iced::{
     Subscription, Task,
     futures::{
-        SinkExt, Stream, StreamExt,
-        channel::mpsc::Sender,
-        stream::{pending, select_all},
-        stream_select,
+        SinkExt, Stream, StreamExt, channel::mpsc::Sender, stream::select_all, stream_select,
     },
     stream::channel,
 };
@@ -18,6 +15,8 @@ use zbus::zvariant::OwnedObjectPath;
 
 mod dbus;
 
+const BLUEZ_SERVICE: &str = "org.bluez";
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum BluetoothState {
     Unavailable,
@@ -142,7 +141,11 @@ impl BluetoothService {
         Ok(combined)
     }
 
-    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+    async fn start_listening(
+        state: State,
+        output: &mut Sender<ServiceEvent<Self>>,
+        backoff: &mut Backoff,
+    ) -> State {
         match state {
             State::Init => match zbus::Connection::system().await {
                 Ok(conn) => {
@@ -178,14 +181,18 @@ impl BluetoothService {
                 info!("Listening for bluetooth events");
 
                 match BluetoothService::events(&conn).await {
-                    Ok(mut events) => {
+                    Ok(events) => {
+                        let mut events =
+                            events.take_until(Box::pin(restarted(conn.clone(), BLUEZ_SERVICE)));
+
                         while events.next().await.is_some() {
                             if let Ok(data) = BluetoothService::initialize_data(&conn).await {
                                 let _ = output.send(ServiceEvent::Update(data)).await;
                             }
                         }
 
-                        State::Active(conn)
+                        // the connection was lost or BlueZ restarted
+                        State::Error
                     }
                     Err(err) => {
                         error!("Failed to listen for bluetooth events: {err}");
@@ -194,10 +201,9 @@ impl BluetoothService {
                 }
             }
             State::Error => {
-                error!("Bluetooth service error");
+                backoff.failed(output, ()).await;
 
-                let _ = pending::<u8>().next().await;
-                State::Error
+                State::Init
             }
         }
     }
@@ -247,9 +253,11 @@ impl ReadOnlyService for BluetoothService {
             id,
             channel(100, async |mut output| {
                 let mut state = State::Init;
+                let mut backoff = Backoff::default();
 
                 loop {
-                    state = BluetoothService::start_listening(state, &mut output).await;
+                    state =
+                        BluetoothService::start_listening(state, &mut output, &mut backoff).await;
                 }
             }),
         )
@@ -0,0 +1,400 @@
+use super::types::{
+    ActiveWindow, CompositorCommand, CompositorEvent, CompositorMonitor, CompositorService,
+    CompositorState, CompositorWindow,
+};
+use crate::services::ServiceEvent;
+use anyhow::{Context, Result, anyhow};
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::broadcast;
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle, delegate_noop, event_created_child,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{
+        wl_output::{self, WlOutput},
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+    },
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+/// Handles of the open windows with the connection they belong to, the
+/// commands are sent from outside of the listener task.
+static CONTROL: Mutex<Option<Control>> = Mutex::new(None);
+
+struct Control {
+    connection: Connection,
+    seat: WlSeat,
+    handles: Vec<ZwlrForeignToplevelHandleV1>,
+}
+
+pub async fn execute_command(cmd: CompositorCommand) -> Result<()> {
+    match cmd {
+        CompositorCommand::FocusWindow(id) => with_handle(&id, |handle, seat| {
+            handle.activate(seat);
+        }),
+        CompositorCommand::CloseWindow(id) => with_handle(&id, |handle, _| handle.close()),
+        CompositorCommand::FocusWorkspace(_)
+        | CompositorCommand::FocusSpecialWorkspace(_)
+        | CompositorCommand::ToggleSpecialWorkspace(_)
+        | CompositorCommand::ScrollWorkspace(_) => Err(anyhow!(
+            "Workspaces not supported in foreign toplevel backend"
+        )),
+        CompositorCommand::FocusMonitor(_) => Err(anyhow!(
+            "FocusMonitor by ID not supported in foreign toplevel backend"
+        )),
+        CompositorCommand::SwitchLayout(..) => Err(anyhow!(
+            "Switching the layout not supported in foreign toplevel backend"
+        )),
+        CompositorCommand::CustomDispatch(action, _) => {
+            Err(anyhow!("Unknown custom dispatch: {}", action))
+        }
+    }
+}
+
+/// Sends a request to the window with the given id.
+fn with_handle(id: &str, f: impl FnOnce(&ZwlrForeignToplevelHandleV1, &WlSeat)) -> Result<()> {
+    let control = CONTROL
+        .lock()
+        .map_err(|_| anyhow!("Foreign toplevel listener panicked"))?;
+    let control = control
+        .as_ref()
+        .context("Foreign toplevel listener not running")?;
+    let handle = control
+        .handles
+        .iter()
+        .find(|handle| window_id(handle) == id)
+        .with_context(|| format!("No window with id {id}"))?;
+
+    f(handle, &control.seat);
+    control.connection.flush()?;
+
+    Ok(())
+}
+
+fn window_id(handle: &ZwlrForeignToplevelHandleV1) -> String {
+    handle.id().protocol_id().to_string()
+}
+
+/// Fallback for the compositors without a backend of their own, as long as
+/// they list their windows through wlr-foreign-toplevel-management.
+pub fn is_available() -> bool {
+    static AVAILABLE: LazyLock<bool> =
+        LazyLock::new(|| super::has_wayland_global(ZwlrForeignToplevelManagerV1::interface().name));
+
+    *AVAILABLE
+}
+
+pub async fn run_listener(tx: &broadcast::Sender<ServiceEvent<CompositorService>>) -> Result<()> {
+    let result = listen(tx).await;
+    if let Ok(mut control) = CONTROL.lock() {
+        *control = None;
+    }
+
+    result
+}
+
+async fn listen(tx: &broadcast::Sender<ServiceEvent<CompositorService>>) -> Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<Listener>(&connection)?;
+    let handle = queue.handle();
+
+    let _manager: ZwlrForeignToplevelManagerV1 = globals
+        .bind(&handle, 1..=3, ())
+        .context("Foreign toplevel protocol not supported")?;
+    let seat: WlSeat = globals.bind(&handle, 1..=1, ())?;
+
+    let mut listener = Listener {
+        tx: tx.clone(),
+        outputs: Vec::new(),
+        toplevels: Vec::new(),
+        changed: false,
+    };
+    globals.contents().with_list(|list| {
+        for global in list {
+            if global.interface == WlOutput::interface().name {
+                listener.add_output(globals.registry(), global.name, global.version, &handle);
+            }
+        }
+    });
+
+    if let Ok(mut control) = CONTROL.lock() {
+        *control = Some(Control {
+            connection: connection.clone(),
+            seat,
+            handles: Vec::new(),
+        });
+    }
+
+    super::dispatch_until_closed(&connection, &mut queue, &mut listener, tx, |listener| {
+        if !std::mem::take(&mut listener.changed) {
+            return;
+        }
+
+        if let Ok(mut control) = CONTROL.lock()
+            && let Some(control) = control.as_mut()
+        {
+            control.handles = listener
+                .toplevels
+                .iter()
+                .map(|toplevel| toplevel.handle.clone())
+                .collect();
+        }
+
+        let _ = listener
+            .tx
+            .send(ServiceEvent::Update(CompositorEvent::StateChanged(
+                listener.state(),
+            )));
+    })
+    .await
+}
+
+struct Output {
+    /// Name of the global, identifies the output in the events
+    global: u32,
+    output: WlOutput,
+    name: String,
+}
+
+#[derive(Clone, Default)]
+struct Properties {
+    title: String,
+    app_id: String,
+    /// Globals of the outputs showing the window
+    outputs: Vec<u32>,
+    activated: bool,
+    fullscreen: bool,
+}
+
+struct Toplevel {
+    handle: ZwlrForeignToplevelHandleV1,
+    /// Properties applied by the last `done` event
+    current: Properties,
+    pending: Properties,
+    /// Listed once its first `done` event is received
+    ready: bool,
+}
+
+struct Listener {
+    tx: broadcast::Sender<ServiceEvent<CompositorService>>,
+    outputs: Vec<Output>,
+    toplevels: Vec<Toplevel>,
+    changed: bool,
+}
+
+impl Listener {
+    fn add_output(
+        &mut self,
+        registry: &WlRegistry,
+        global: u32,
+        version: u32,
+        handle: &QueueHandle<Self>,
+    ) {
+        self.outputs.push(Output {
+            global,
+            output: registry.bind(global, version.min(4), handle, global),
+            name: String::new(),
+        });
+    }
+
+    fn toplevel_mut(&mut self, handle: &ZwlrForeignToplevelHandleV1) -> Option<&mut Toplevel> {
+        self.toplevels
+            .iter_mut()
+            .find(|toplevel| &toplevel.handle == handle)
+    }
+
+    fn state(&self) -> CompositorState {
+        let toplevels = self
+            .toplevels
+            .iter()
+            .filter(|toplevel| toplevel.ready)
+            .map(|toplevel| (window_id(&toplevel.handle), &toplevel.current))
+            .collect::<Vec<_>>();
+
+        let monitors = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(id, output)| CompositorMonitor {
+                id: id as i128,
+                name: output.name.clone(),
+                active_workspace_id: -1,
+                special_workspace_id: -1,
+                fullscreen: toplevels.iter().any(|(_, properties)| {
+                    properties.fullscreen && properties.outputs.contains(&output.global)
+                }),
+            })
+            .collect();
+
+        CompositorState {
+            // the protocol knows nothing of the workspaces
+            workspaces: Vec::new(),
+            monitors,
+            active_workspace_id: None,
+            active_window: toplevels
+                .iter()
+                .find(|(_, properties)| properties.activated)
+                .map(|(id, properties)| ActiveWindow {
+                    title: properties.title.clone(),
+                    class: properties.app_id.clone(),
+                    address: id.clone(),
+                }),
+            windows: toplevels
+                .iter()
+                .map(|(id, properties)| CompositorWindow {
+                    id: id.clone(),
+                    title: properties.title.clone(),
+                    class: properties.app_id.clone(),
+                    workspace_id: None,
+                    monitor: properties.outputs.first().and_then(|global| {
+                        self.outputs
+                            .iter()
+                            .find(|output| output.global == *global)
+                            .map(|output| output.name.clone())
+                    }),
+                })
+                .collect(),
+            keyboard_layout: "Unknown".to_string(),
+            keyboard_layouts: Vec::new(),
+            submap: None,
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Listener {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == WlOutput::interface().name => {
+                state.add_output(registry, name, version, handle);
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(index) = state
+                    .outputs
+                    .iter()
+                    .position(|output| output.global == name)
+                {
+                    let output = state.outputs.remove(index);
+                    if output.output.version() >= 3 {
+                        output.output.release();
+                    }
+                    state.changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &WlOutput,
+        event: wl_output::Event,
+        global: &u32,
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event
+            && let Some(output) = state
+                .outputs
+                .iter_mut()
+                .find(|output| output.global == *global)
+        {
+            output.name = name;
+            state.changed = true;
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            state.toplevels.push(Toplevel {
+                handle: toplevel,
+                current: Properties::default(),
+                pending: Properties::default(),
+                ready: false,
+            });
+        }
+    }
+
+    event_created_child!(Listener, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_handle_v1::Event::Closed = event {
+            state.toplevels.retain(|toplevel| &toplevel.handle != proxy);
+            proxy.destroy();
+            state.changed = true;
+
+            return;
+        }
+
+        let Some(toplevel) = state.toplevel_mut(proxy) else {
+            return;
+        };
+        let pending = &mut toplevel.pending;
+
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => pending.title = title,
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => pending.app_id = app_id,
+            zwlr_foreign_toplevel_handle_v1::Event::OutputEnter { output } => {
+                pending.outputs.extend(output.data::<u32>());
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::OutputLeave { output } => {
+                pending
+                    .outputs
+                    .retain(|global| Some(global) != output.data::<u32>());
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: flags } => {
+                let flags = flags
+                    .chunks_exact(4)
+                    .map(|flag| u32::from_ne_bytes([flag[0], flag[1], flag[2], flag[3]]))
+                    .collect::<Vec<_>>();
+                pending.activated =
+                    flags.contains(&(zwlr_foreign_toplevel_handle_v1::State::Activated as u32));
+                pending.fullscreen =
+                    flags.contains(&(zwlr_foreign_toplevel_handle_v1::State::Fullscreen as u32));
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                toplevel.current = pending.clone();
+                toplevel.ready = true;
+                state.changed = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(Listener: ignore WlSeat);
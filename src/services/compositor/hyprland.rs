@@ -1,12 +1,17 @@
 use super::types::{
     ActiveWindow, CompositorCommand, CompositorEvent, CompositorMonitor, CompositorState,
-    CompositorWorkspace,
+    CompositorWindow, CompositorWorkspace, LayoutSwitch,
+};
+use crate::{
+    services::{ServiceEvent, compositor::CompositorService},
+    utils::xkb,
 };
-use crate::services::{ServiceEvent, compositor::CompositorService};
 use anyhow::Result;
 use hyprland::{
-    data::{Client, Devices, Monitors, Workspace, Workspaces},
-    dispatch::{Dispatch, DispatchType, MonitorIdentifier, WorkspaceIdentifierWithSpecial},
+    data::{Client, Clients, Devices, Monitors, Workspace, Workspaces},
+    dispatch::{
+        Dispatch, DispatchType, MonitorIdentifier, WindowIdentifier, WorkspaceIdentifierWithSpecial,
+    },
     event_listener::AsyncEventListener,
     prelude::*,
 };
@@ -38,15 +43,31 @@ pub async fn execute_command(cmd: CompositorCommand) -> Result<()> {
                 WorkspaceIdentifierWithSpecial::Relative(d.to_string().parse()?),
             ))?;
         }
-        CompositorCommand::NextLayout => {
+        CompositorCommand::SwitchLayout(device, switch) => {
+            use hyprland::ctl::switch_xkb_layout::SwitchXKBLayoutCmdTypes;
+
             hyprland::ctl::switch_xkb_layout::call(
-                "all",
-                hyprland::ctl::switch_xkb_layout::SwitchXKBLayoutCmdTypes::Next,
+                &device,
+                match switch {
+                    LayoutSwitch::Next => SwitchXKBLayoutCmdTypes::Next,
+                    LayoutSwitch::Previous => SwitchXKBLayoutCmdTypes::Previous,
+                    LayoutSwitch::Index(index) => SwitchXKBLayoutCmdTypes::Id(index),
+                },
             )?;
         }
         CompositorCommand::CustomDispatch(dispatcher, args) => {
             Dispatch::call(DispatchType::Custom(&dispatcher, &args))?;
         }
+        CompositorCommand::FocusWindow(address) => {
+            Dispatch::call(DispatchType::FocusWindow(WindowIdentifier::Address(
+                hyprland::shared::Address::new(address),
+            )))?;
+        }
+        CompositorCommand::CloseWindow(address) => {
+            Dispatch::call(DispatchType::CloseWindow(WindowIdentifier::Address(
+                hyprland::shared::Address::new(address),
+            )))?;
+        }
     }
     Ok(())
 }
@@ -116,6 +137,8 @@ pub async fn run_listener(tx: &broadcast::Sender<ServiceEvent<CompositorService>
     add_refresh_handler!(add_window_opened_handler);
     add_refresh_handler!(add_window_moved_handler);
     add_refresh_handler!(add_active_window_changed_handler);
+    add_refresh_handler!(add_window_title_changed_handler);
+    add_refresh_handler!(add_fullscreen_state_changed_handler);
 
     add_refresh_handler!(add_layout_changed_handler);
 
@@ -146,6 +169,16 @@ fn fetch_full_state(internal_state: &HyprInternalState) -> Result<CompositorStat
     let workspaces = Workspaces::get()?
         .into_iter()
         .sorted_by_key(|w| w.id)
+        .collect::<Vec<_>>();
+
+    let fullscreen_workspaces = workspaces
+        .iter()
+        .filter(|w| w.fullscreen)
+        .map(|w| w.id)
+        .collect::<Vec<_>>();
+
+    let workspaces: Vec<CompositorWorkspace> = workspaces
+        .into_iter()
         .map(|w| CompositorWorkspace {
             id: w.id,
             index: w.id,
@@ -164,6 +197,7 @@ fn fetch_full_state(internal_state: &HyprInternalState) -> Result<CompositorStat
             name: m.name,
             active_workspace_id: m.active_workspace.id,
             special_workspace_id: m.special_workspace.id,
+            fullscreen: fullscreen_workspaces.contains(&m.active_workspace.id),
         })
         .collect();
 
@@ -175,14 +209,45 @@ fn fetch_full_state(internal_state: &HyprInternalState) -> Result<CompositorStat
         address: w.address.to_string(),
     });
 
-    let keyboard_layout = Devices::get()
+    let windows = Clients::get()?
+        .into_iter()
+        .filter(|c| c.mapped)
+        .map(|c| CompositorWindow {
+            id: c.address.to_string(),
+            title: c.title,
+            class: c.class,
+            monitor: workspaces
+                .iter()
+                .find(|w| w.id == c.workspace.id)
+                .map(|w| w.monitor.clone()),
+            workspace_id: Some(c.workspace.id),
+        })
+        .collect();
+
+    let main_keyboard = Devices::get()
         .ok()
-        .and_then(|d| {
-            d.keyboards
-                .into_iter()
-                .find(|k| k.main)
-                .map(|k| k.active_keymap)
+        .and_then(|d| d.keyboards.into_iter().find(|k| k.main));
+
+    // hyprland lists the codes of the layouts and variants, e.g. `us,de` and `intl,`
+    let keyboard_layouts = main_keyboard
+        .as_ref()
+        .map(|k| {
+            let variants = k.variant.split(',').collect::<Vec<_>>();
+            k.layout
+                .split(',')
+                .enumerate()
+                .map(|(i, layout)| {
+                    let variant = variants.get(i).copied().unwrap_or_default();
+                    xkb::layout_name(layout, variant)
+                        .or_else(|| xkb::layout_name(layout, ""))
+                        .map_or_else(|| layout.to_owned(), str::to_owned)
+                })
+                .collect()
         })
+        .unwrap_or_default();
+
+    let keyboard_layout = main_keyboard
+        .map(|k| k.active_keymap)
         .unwrap_or_else(|| "Unknown".to_string());
 
     Ok(CompositorState {
@@ -190,7 +255,9 @@ fn fetch_full_state(internal_state: &HyprInternalState) -> Result<CompositorStat
         monitors,
         active_workspace_id,
         active_window,
+        windows,
         keyboard_layout,
+        keyboard_layouts,
         submap: if internal_state.submap.is_empty() {
             None
         } else {
@@ -0,0 +1,574 @@
+use self::protocol::{
+    virtual_desktop::{
+        org_kde_plasma_virtual_desktop::{self, OrgKdePlasmaVirtualDesktop},
+        org_kde_plasma_virtual_desktop_management::{self, OrgKdePlasmaVirtualDesktopManagement},
+    },
+    window_management::{
+        org_kde_plasma_window::{self, OrgKdePlasmaWindow},
+        org_kde_plasma_window_management::{self, OrgKdePlasmaWindowManagement, State},
+    },
+};
+use super::types::{
+    ActiveWindow, CompositorCommand, CompositorEvent, CompositorMonitor, CompositorService,
+    CompositorState, CompositorWindow, CompositorWorkspace,
+};
+use crate::services::ServiceEvent;
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::broadcast;
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{
+        wl_output::{self, WlOutput},
+        wl_registry::{self, WlRegistry},
+    },
+};
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    unused_imports,
+    clippy::all
+)]
+mod protocol {
+    pub mod virtual_desktop {
+        use wayland_client;
+        use wayland_client::protocol::*;
+
+        pub mod __interfaces {
+            use wayland_client::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!("protocols/org-kde-plasma-virtual-desktop.xml");
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_client_code!("protocols/org-kde-plasma-virtual-desktop.xml");
+    }
+
+    pub mod window_management {
+        use wayland_client;
+        use wayland_client::protocol::*;
+
+        pub mod __interfaces {
+            use wayland_client::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!("protocols/plasma-window-management.xml");
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_client_code!("protocols/plasma-window-management.xml");
+    }
+}
+
+const ACTIVE: u32 = State::Active as u32;
+const MINIMIZED: u32 = State::Minimized as u32;
+const SKIP_TASKBAR: u32 = State::Skiptaskbar as u32;
+
+/// Windows and virtual desktops with the connection they belong to, the
+/// commands are sent from outside of the listener task.
+static CONTROL: Mutex<Option<Control>> = Mutex::new(None);
+
+struct Control {
+    connection: Connection,
+    windows: Vec<(String, OrgKdePlasmaWindow)>,
+    /// Virtual desktops in their order, the workspace ids are their
+    /// positions numbered from 1
+    desktops: Vec<OrgKdePlasmaVirtualDesktop>,
+    active_desktop: Option<usize>,
+}
+
+pub async fn execute_command(cmd: CompositorCommand) -> Result<()> {
+    match cmd {
+        CompositorCommand::FocusWorkspace(id) => with_control(|control| {
+            let desktop = usize::try_from(id - 1)
+                .ok()
+                .and_then(|index| control.desktops.get(index))
+                .with_context(|| format!("No virtual desktop {id}"))?;
+            desktop.request_activate();
+
+            Ok(())
+        }),
+        CompositorCommand::ScrollWorkspace(dir) => with_control(|control| {
+            let active = control
+                .active_desktop
+                .context("No active virtual desktop")?;
+            let index = if dir > 0 {
+                (active + 1).min(control.desktops.len().saturating_sub(1))
+            } else {
+                active.saturating_sub(1)
+            };
+            if let Some(desktop) = control.desktops.get(index) {
+                desktop.request_activate();
+            }
+
+            Ok(())
+        }),
+        CompositorCommand::FocusWindow(id) => with_window(&id, |window| {
+            // a minimized window is restored as well
+            window.set_state(ACTIVE | MINIMIZED, ACTIVE);
+        }),
+        CompositorCommand::CloseWindow(id) => with_window(&id, |window| window.close()),
+        CompositorCommand::FocusSpecialWorkspace(_)
+        | CompositorCommand::ToggleSpecialWorkspace(_) => {
+            Err(anyhow!("Special workspaces not supported in KDE backend"))
+        }
+        CompositorCommand::FocusMonitor(_) => {
+            Err(anyhow!("FocusMonitor by ID not supported in KDE backend"))
+        }
+        CompositorCommand::SwitchLayout(..) => {
+            Err(anyhow!("Switching the layout not supported in KDE backend"))
+        }
+        CompositorCommand::CustomDispatch(action, _) => {
+            Err(anyhow!("Unknown custom dispatch: {}", action))
+        }
+    }
+}
+
+fn with_control(f: impl FnOnce(&Control) -> Result<()>) -> Result<()> {
+    let control = CONTROL
+        .lock()
+        .map_err(|_| anyhow!("KDE listener panicked"))?;
+    let control = control.as_ref().context("KDE listener not running")?;
+
+    f(control)?;
+    control.connection.flush()?;
+
+    Ok(())
+}
+
+/// Sends a request to the window with the given id.
+fn with_window(id: &str, f: impl FnOnce(&OrgKdePlasmaWindow)) -> Result<()> {
+    with_control(|control| {
+        let (_, window) = control
+            .windows
+            .iter()
+            .find(|(window_id, _)| window_id == id)
+            .with_context(|| format!("No window with id {id}"))?;
+        f(window);
+
+        Ok(())
+    })
+}
+
+/// KWin restricts the window management protocol to the clients allowed
+/// by their desktop file, it is looked up on the Wayland registry.
+pub fn is_available() -> bool {
+    static AVAILABLE: LazyLock<bool> =
+        LazyLock::new(|| super::has_wayland_global(OrgKdePlasmaWindowManagement::interface().name));
+
+    *AVAILABLE
+}
+
+pub async fn run_listener(tx: &broadcast::Sender<ServiceEvent<CompositorService>>) -> Result<()> {
+    let result = listen(tx).await;
+    if let Ok(mut control) = CONTROL.lock() {
+        *control = None;
+    }
+
+    result
+}
+
+async fn listen(tx: &broadcast::Sender<ServiceEvent<CompositorService>>) -> Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<Listener>(&connection)?;
+    let handle = queue.handle();
+
+    let _window_management: OrgKdePlasmaWindowManagement = globals
+        .bind(&handle, 8..=13, ())
+        .context("Plasma window management protocol not supported")?;
+    // without it the windows are still listed
+    let _desktop_management: Option<OrgKdePlasmaVirtualDesktopManagement> = globals
+        .bind(&handle, 1..=1, ())
+        .inspect_err(|e| warn!("Plasma virtual desktop protocol not supported: {e}"))
+        .ok();
+
+    let mut listener = Listener {
+        tx: tx.clone(),
+        outputs: Vec::new(),
+        desktops: Vec::new(),
+        windows: Vec::new(),
+        changed: false,
+    };
+    globals.contents().with_list(|list| {
+        for global in list {
+            if global.interface == WlOutput::interface().name {
+                listener.add_output(globals.registry(), global.name, global.version, &handle);
+            }
+        }
+    });
+
+    if let Ok(mut control) = CONTROL.lock() {
+        *control = Some(Control {
+            connection: connection.clone(),
+            windows: Vec::new(),
+            desktops: Vec::new(),
+            active_desktop: None,
+        });
+    }
+
+    super::dispatch_until_closed(&connection, &mut queue, &mut listener, tx, |listener| {
+        if !std::mem::take(&mut listener.changed) {
+            return;
+        }
+
+        if let Ok(mut control) = CONTROL.lock()
+            && let Some(control) = control.as_mut()
+        {
+            control.windows = listener
+                .windows
+                .iter()
+                .map(|window| (window.id.clone(), window.proxy.clone()))
+                .collect();
+            control.desktops = listener
+                .desktops
+                .iter()
+                .map(|desktop| desktop.proxy.clone())
+                .collect();
+            control.active_desktop = listener.active_desktop();
+        }
+
+        let _ = listener
+            .tx
+            .send(ServiceEvent::Update(CompositorEvent::StateChanged(
+                listener.state(),
+            )));
+    })
+    .await
+}
+
+struct Output {
+    /// Name of the global, identifies the output in the events
+    global: u32,
+    output: WlOutput,
+    name: String,
+}
+
+struct Desktop {
+    proxy: OrgKdePlasmaVirtualDesktop,
+    id: String,
+    name: String,
+    active: bool,
+}
+
+struct Window {
+    proxy: OrgKdePlasmaWindow,
+    /// Uuid of the window, or its internal id before version 13
+    id: String,
+    title: String,
+    app_id: String,
+    state: u32,
+    /// Ids of the virtual desktops of the window, none when it is on all
+    /// of them
+    desktops: Vec<String>,
+    /// Listed once its initial state is received
+    ready: bool,
+}
+
+struct Listener {
+    tx: broadcast::Sender<ServiceEvent<CompositorService>>,
+    outputs: Vec<Output>,
+    desktops: Vec<Desktop>,
+    windows: Vec<Window>,
+    changed: bool,
+}
+
+impl Listener {
+    fn add_output(
+        &mut self,
+        registry: &WlRegistry,
+        global: u32,
+        version: u32,
+        handle: &QueueHandle<Self>,
+    ) {
+        self.outputs.push(Output {
+            global,
+            output: registry.bind(global, version.min(4), handle, global),
+            name: String::new(),
+        });
+    }
+
+    fn add_window(&mut self, proxy: OrgKdePlasmaWindow, id: String) {
+        self.windows.push(Window {
+            proxy,
+            id,
+            title: String::new(),
+            app_id: String::new(),
+            state: 0,
+            desktops: Vec::new(),
+            ready: false,
+        });
+    }
+
+    fn active_desktop(&self) -> Option<usize> {
+        self.desktops.iter().position(|desktop| desktop.active)
+    }
+
+    /// Workspace of the window, its first virtual desktop.
+    fn workspace_id(&self, window: &Window) -> Option<i32> {
+        window.desktops.iter().find_map(|id| {
+            self.desktops
+                .iter()
+                .position(|desktop| &desktop.id == id)
+                .map(|index| index as i32 + 1)
+        })
+    }
+
+    fn state(&self) -> CompositorState {
+        // the panels and the desktop of Plasma skip the taskbar
+        let windows = self
+            .windows
+            .iter()
+            .filter(|window| window.ready && window.state & SKIP_TASKBAR == 0)
+            .collect::<Vec<_>>();
+        let active_window = windows.iter().find(|window| window.state & ACTIVE != 0);
+        let active_workspace_id = self.active_desktop().map(|index| index as i32 + 1);
+
+        let workspaces = self
+            .desktops
+            .iter()
+            .enumerate()
+            .map(|(index, desktop)| CompositorWorkspace {
+                id: index as i32 + 1,
+                index: index as i32 + 1,
+                name: desktop.name.clone(),
+                // the virtual desktops span every output
+                monitor: String::new(),
+                monitor_id: None,
+                windows: windows
+                    .iter()
+                    .filter(|window| window.desktops.contains(&desktop.id))
+                    .count() as u16,
+                is_special: false,
+            })
+            .collect();
+
+        let monitors = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(id, output)| CompositorMonitor {
+                id: id as i128,
+                name: output.name.clone(),
+                active_workspace_id: active_workspace_id.unwrap_or(-1),
+                special_workspace_id: -1,
+                // the protocol doesn't tell the output of a window
+                fullscreen: false,
+            })
+            .collect();
+
+        CompositorState {
+            workspaces,
+            monitors,
+            active_workspace_id,
+            active_window: active_window.map(|window| ActiveWindow {
+                title: window.title.clone(),
+                class: window.app_id.clone(),
+                address: window.id.clone(),
+            }),
+            windows: windows
+                .iter()
+                .map(|window| CompositorWindow {
+                    id: window.id.clone(),
+                    title: window.title.clone(),
+                    class: window.app_id.clone(),
+                    workspace_id: self.workspace_id(window),
+                    monitor: None,
+                })
+                .collect(),
+            keyboard_layout: "Unknown".to_string(),
+            keyboard_layouts: Vec::new(),
+            submap: None,
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Listener {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == WlOutput::interface().name => {
+                state.add_output(registry, name, version, handle);
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(index) = state
+                    .outputs
+                    .iter()
+                    .position(|output| output.global == name)
+                {
+                    let output = state.outputs.remove(index);
+                    if output.output.version() >= 3 {
+                        output.output.release();
+                    }
+                    state.changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &WlOutput,
+        event: wl_output::Event,
+        global: &u32,
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event
+            && let Some(output) = state
+                .outputs
+                .iter_mut()
+                .find(|output| output.global == *global)
+        {
+            output.name = name;
+            state.changed = true;
+        }
+    }
+}
+
+impl Dispatch<OrgKdePlasmaVirtualDesktopManagement, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &OrgKdePlasmaVirtualDesktopManagement,
+        event: org_kde_plasma_virtual_desktop_management::Event,
+        _data: &(),
+        _conn: &Connection,
+        handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            org_kde_plasma_virtual_desktop_management::Event::DesktopCreated {
+                desktop_id,
+                position,
+            } => {
+                let desktop = Desktop {
+                    proxy: proxy.get_virtual_desktop(desktop_id.clone(), handle, ()),
+                    id: desktop_id,
+                    name: String::new(),
+                    active: false,
+                };
+                let position = (position as usize).min(state.desktops.len());
+                state.desktops.insert(position, desktop);
+            }
+            org_kde_plasma_virtual_desktop_management::Event::DesktopRemoved { desktop_id } => {
+                state.desktops.retain(|desktop| desktop.id != desktop_id);
+            }
+            org_kde_plasma_virtual_desktop_management::Event::Done => state.changed = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<OrgKdePlasmaVirtualDesktop, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &OrgKdePlasmaVirtualDesktop,
+        event: org_kde_plasma_virtual_desktop::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let org_kde_plasma_virtual_desktop::Event::Removed = event {
+            state.desktops.retain(|desktop| &desktop.proxy != proxy);
+            state.changed = true;
+
+            return;
+        }
+
+        let Some(desktop) = state
+            .desktops
+            .iter_mut()
+            .find(|desktop| &desktop.proxy == proxy)
+        else {
+            return;
+        };
+
+        match event {
+            org_kde_plasma_virtual_desktop::Event::Name { name } => desktop.name = name,
+            org_kde_plasma_virtual_desktop::Event::Activated => desktop.active = true,
+            org_kde_plasma_virtual_desktop::Event::Deactivated => desktop.active = false,
+            org_kde_plasma_virtual_desktop::Event::Done => state.changed = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<OrgKdePlasmaWindowManagement, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &OrgKdePlasmaWindowManagement,
+        event: org_kde_plasma_window_management::Event,
+        _data: &(),
+        _conn: &Connection,
+        handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            org_kde_plasma_window_management::Event::Window { id } => {
+                let window = proxy.get_window(id, handle, ());
+                state.add_window(window, id.to_string());
+            }
+            org_kde_plasma_window_management::Event::WindowWithUuid { uuid, .. } => {
+                let window = proxy.get_window_by_uuid(uuid.clone(), handle, ());
+                state.add_window(window, uuid);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<OrgKdePlasmaWindow, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &OrgKdePlasmaWindow,
+        event: org_kde_plasma_window::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let org_kde_plasma_window::Event::Unmapped = event {
+            state.windows.retain(|window| &window.proxy != proxy);
+            proxy.destroy();
+            state.changed = true;
+
+            return;
+        }
+
+        let Some(window) = state
+            .windows
+            .iter_mut()
+            .find(|window| &window.proxy == proxy)
+        else {
+            return;
+        };
+
+        match event {
+            org_kde_plasma_window::Event::TitleChanged { title } => window.title = title,
+            org_kde_plasma_window::Event::AppIdChanged { app_id } => window.app_id = app_id,
+            org_kde_plasma_window::Event::StateChanged { flags } => window.state = flags,
+            org_kde_plasma_window::Event::VirtualDesktopEntered { id } => {
+                window.desktops.push(id);
+            }
+            org_kde_plasma_window::Event::VirtualDesktopLeft { is } => {
+                window.desktops.retain(|id| *id != is);
+            }
+            org_kde_plasma_window::Event::InitialState => window.ready = true,
+            _ => return,
+        }
+
+        if window.ready {
+            state.changed = true;
+        }
+    }
+}
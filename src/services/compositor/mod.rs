@@ -1,32 +1,51 @@
+pub mod foreign_toplevel;
 pub mod hyprland;
+pub mod kde;
 pub mod niri;
+pub mod river;
 pub mod types;
 
 pub use self::types::{
     CompositorChoice, CompositorCommand, CompositorEvent, CompositorService, CompositorState,
+    CompositorWindow, LayoutSwitch,
 };
 
 use crate::services::{ReadOnlyService, Service, ServiceEvent};
 use iced::futures::SinkExt;
 use iced::{Subscription, Task, stream::channel};
-use std::{any::TypeId, ops::Deref};
-use tokio::sync::{OnceCell, broadcast};
+use std::{any::TypeId, io::ErrorKind, ops::Deref, os::fd::AsFd};
+use tokio::{
+    io::{Interest, unix::AsyncFd},
+    sync::{Mutex, broadcast},
+};
+use wayland_client::{
+    Connection, Dispatch, EventQueue, QueueHandle,
+    backend::WaylandError,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::wl_registry::{self, WlRegistry},
+};
 
 const BROADCAST_CAPACITY: usize = 64;
 
-static BROADCASTER: OnceCell<broadcast::Sender<ServiceEvent<CompositorService>>> =
-    OnceCell::const_new();
+/// Sender of the running event loop, the loop stops and forgets it once
+/// the last subscriber is gone.
+static BROADCASTER: Mutex<Option<broadcast::Sender<ServiceEvent<CompositorService>>>> =
+    Mutex::const_new(None);
 
-/// Subscribe to compositor events.  Initializes the broadcaster on first call.
+/// Subscribe to compositor events.  Starts the event loop when it isn't running.
 async fn broadcaster_subscribe() -> broadcast::Receiver<ServiceEvent<CompositorService>> {
-    BROADCASTER
-        .get_or_init(|| async {
-            let (tx, _) = broadcast::channel(BROADCAST_CAPACITY);
+    let mut broadcaster = BROADCASTER.lock().await;
+
+    match broadcaster.as_ref() {
+        Some(tx) => tx.subscribe(),
+        None => {
+            let (tx, rx) = broadcast::channel(BROADCAST_CAPACITY);
             tokio::spawn(broadcaster_event_loop(tx.clone()));
-            tx
-        })
-        .await
-        .subscribe()
+            *broadcaster = Some(tx);
+
+            rx
+        }
+    }
 }
 
 async fn broadcaster_event_loop(tx: broadcast::Sender<ServiceEvent<CompositorService>>) {
@@ -35,19 +54,80 @@ async fn broadcaster_event_loop(tx: broadcast::Sender<ServiceEvent<CompositorSer
         let _ = tx.send(ServiceEvent::Error(
             "No supported compositor backend found".into(),
         ));
+        *BROADCASTER.lock().await = None;
         return;
     };
 
-    log::info!("Starting compositor event loop with {:?} backend", backend);
+    loop {
+        log::info!("Starting compositor event loop with {:?} backend", backend);
 
-    let result = match backend {
-        CompositorChoice::Hyprland => hyprland::run_listener(&tx).await,
-        CompositorChoice::Niri => niri::run_listener(&tx).await,
-    };
+        // the Wayland listeners stop by themselves, clearing their state
+        let result = match backend {
+            CompositorChoice::Hyprland => until_closed(&tx, hyprland::run_listener(&tx)).await,
+            CompositorChoice::Niri => until_closed(&tx, niri::run_listener(&tx)).await,
+            CompositorChoice::River => river::run_listener(&tx).await,
+            CompositorChoice::Kde => kde::run_listener(&tx).await,
+            CompositorChoice::ForeignToplevel => foreign_toplevel::run_listener(&tx).await,
+        };
+
+        if let Err(e) = &result {
+            log::error!("Compositor event loop failed: {}", e);
+            let _ = tx.send(ServiceEvent::Error(e.to_string()));
+        }
+
+        let mut broadcaster = BROADCASTER.lock().await;
+        // a subscriber may have come while the listener was stopping
+        if result.is_err() || tx.receiver_count() == 0 {
+            log::info!("Compositor event loop stopped");
+            *broadcaster = None;
+            return;
+        }
+    }
+}
+
+/// Runs a listener until the last subscriber is gone.
+async fn until_closed(
+    tx: &broadcast::Sender<ServiceEvent<CompositorService>>,
+    listener: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    tokio::select! {
+        result = listener => result,
+        () = tx.closed() => Ok(()),
+    }
+}
+
+/// Dispatches the events of a Wayland listener as they come in, until the
+/// last subscriber is gone.
+async fn dispatch_until_closed<State>(
+    connection: &Connection,
+    queue: &mut EventQueue<State>,
+    state: &mut State,
+    tx: &broadcast::Sender<ServiceEvent<CompositorService>>,
+    mut dispatched: impl FnMut(&mut State),
+) -> anyhow::Result<()> {
+    let fd = AsyncFd::with_interest(connection.as_fd(), Interest::READABLE)?;
+
+    loop {
+        queue.dispatch_pending(state)?;
+        dispatched(state);
+        queue.flush()?;
+
+        tokio::select! {
+            readable = fd.readable() => {
+                let mut readable = readable?;
 
-    if let Err(e) = result {
-        log::error!("Compositor event loop failed: {}", e);
-        let _ = tx.send(ServiceEvent::Error(e.to_string()));
+                if let Some(guard) = queue.prepare_read() {
+                    match guard.read() {
+                        Ok(_) => {}
+                        Err(WaylandError::Io(e)) if e.kind() == ErrorKind::WouldBlock => {
+                            readable.clear_ready();
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+            () = tx.closed() => return Ok(()),
+        }
     }
 }
 
@@ -56,11 +136,44 @@ fn detect_backend() -> Option<CompositorChoice> {
         Some(CompositorChoice::Hyprland)
     } else if niri::is_available() {
         Some(CompositorChoice::Niri)
+    } else if river::is_available() {
+        Some(CompositorChoice::River)
+    } else if kde::is_available() {
+        Some(CompositorChoice::Kde)
+    } else if foreign_toplevel::is_available() {
+        Some(CompositorChoice::ForeignToplevel)
     } else {
         None
     }
 }
 
+/// Whether the Wayland compositor advertises the global, for the backends
+/// detected by their protocol rather than by an environment variable.
+fn has_wayland_global(interface: &str) -> bool {
+    struct Registry;
+
+    impl Dispatch<WlRegistry, GlobalListContents> for Registry {
+        fn event(
+            _state: &mut Self,
+            _registry: &WlRegistry,
+            _event: wl_registry::Event,
+            _data: &GlobalListContents,
+            _conn: &Connection,
+            _handle: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    Connection::connect_to_env()
+        .ok()
+        .and_then(|connection| registry_queue_init::<Registry>(&connection).ok())
+        .is_some_and(|(globals, _)| {
+            globals
+                .contents()
+                .with_list(|list| list.iter().any(|global| global.interface == interface))
+        })
+}
+
 impl Deref for CompositorService {
     type Target = CompositorState;
     fn deref(&self) -> &Self::Target {
@@ -144,6 +257,9 @@ async fn execute_command(
     match backend {
         CompositorChoice::Hyprland => hyprland::execute_command(command).await,
         CompositorChoice::Niri => niri::execute_command(command).await,
+        CompositorChoice::River => river::execute_command(command).await,
+        CompositorChoice::Kde => kde::execute_command(command).await,
+        CompositorChoice::ForeignToplevel => foreign_toplevel::execute_command(command).await,
     }
     .map_err(|e| e.to_string())
 }
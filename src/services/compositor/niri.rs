@@ -1,6 +1,6 @@
 use super::types::{
     ActiveWindow, CompositorCommand, CompositorEvent, CompositorMonitor, CompositorService,
-    CompositorState, CompositorWorkspace,
+    CompositorState, CompositorWindow, CompositorWorkspace, LayoutSwitch,
 };
 use crate::services::ServiceEvent;
 use anyhow::{Context, Result, anyhow};
@@ -47,8 +47,23 @@ pub async fn execute_command(cmd: CompositorCommand) -> Result<()> {
                 Action::FocusWorkspaceDown {}
             }
         }
-        CompositorCommand::NextLayout => Action::SwitchLayout {
-            layout: niri_ipc::LayoutSwitchTarget::Next,
+        CompositorCommand::SwitchLayout(_, switch) => Action::SwitchLayout {
+            layout: match switch {
+                LayoutSwitch::Next => niri_ipc::LayoutSwitchTarget::Next,
+                LayoutSwitch::Previous => niri_ipc::LayoutSwitchTarget::Prev,
+                LayoutSwitch::Index(index) => niri_ipc::LayoutSwitchTarget::Index(index),
+            },
+        },
+        CompositorCommand::FocusWindow(id) => Action::FocusWindow {
+            id: id
+                .parse()
+                .with_context(|| format!("Invalid window id {id} for Niri backend"))?,
+        },
+        CompositorCommand::CloseWindow(id) => Action::CloseWindow {
+            id: Some(
+                id.parse()
+                    .with_context(|| format!("Invalid window id {id} for Niri backend"))?,
+            ),
         },
         CompositorCommand::CustomDispatch(action, args) => {
             if action == "spawn" {
@@ -227,6 +242,8 @@ fn map_state(niri: &EventStreamState) -> CompositorState {
             name: name.clone(),
             active_workspace_id: *active_ws_id,
             special_workspace_id: -1,
+            // not reported by the niri IPC
+            fullscreen: false,
         });
     }
 
@@ -248,6 +265,23 @@ fn map_state(niri: &EventStreamState) -> CompositorState {
             address: w.id.to_string(),
         });
 
+    let windows = niri
+        .windows
+        .windows
+        .values()
+        .sorted_by_key(|w| w.id)
+        .map(|w| CompositorWindow {
+            id: w.id.to_string(),
+            title: w.title.clone().unwrap_or_default(),
+            class: w.app_id.clone().unwrap_or_default(),
+            workspace_id: w.workspace_id.map(|id| id as i32),
+            monitor: w
+                .workspace_id
+                .and_then(|id| niri.workspaces.workspaces.get(&id))
+                .and_then(|ws| ws.output.clone()),
+        })
+        .collect();
+
     let keyboard_layout = niri.keyboard_layouts.keyboard_layouts.as_ref().map_or_else(
         || "Unknown".to_string(),
         |k| {
@@ -258,12 +292,21 @@ fn map_state(niri: &EventStreamState) -> CompositorState {
         },
     );
 
+    let keyboard_layouts = niri
+        .keyboard_layouts
+        .keyboard_layouts
+        .as_ref()
+        .map(|k| k.names.clone())
+        .unwrap_or_default();
+
     CompositorState {
         workspaces,
         monitors,
         active_workspace_id,
         active_window,
+        windows,
         keyboard_layout,
+        keyboard_layouts,
         submap: None,
     }
 }
@@ -0,0 +1,489 @@
+use self::protocol::{
+    control::{
+        zriver_command_callback_v1::{self, ZriverCommandCallbackV1},
+        zriver_control_v1::ZriverControlV1,
+    },
+    status::{
+        zriver_output_status_v1::{self, ZriverOutputStatusV1},
+        zriver_seat_status_v1::{self, ZriverSeatStatusV1},
+        zriver_status_manager_v1::ZriverStatusManagerV1,
+    },
+};
+use super::types::{
+    ActiveWindow, CompositorCommand, CompositorEvent, CompositorMonitor, CompositorService,
+    CompositorState, CompositorWorkspace,
+};
+use crate::services::ServiceEvent;
+use anyhow::{Context, Result, anyhow};
+use std::sync::{
+    LazyLock,
+    atomic::{AtomicU32, Ordering},
+};
+use tokio::sync::broadcast;
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle, delegate_noop,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{
+        wl_output::{self, WlOutput},
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+    },
+};
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    unused_imports,
+    clippy::all
+)]
+mod protocol {
+    pub mod status {
+        use wayland_client;
+        use wayland_client::protocol::*;
+
+        pub mod __interfaces {
+            use wayland_client::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!("protocols/river-status-unstable-v1.xml");
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_client_code!("protocols/river-status-unstable-v1.xml");
+    }
+
+    pub mod control {
+        use wayland_client;
+        use wayland_client::protocol::*;
+
+        pub mod __interfaces {
+            use wayland_client::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!("protocols/river-control-unstable-v1.xml");
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_client_code!("protocols/river-control-unstable-v1.xml");
+    }
+}
+
+/// River has 32 tags, shown as the workspaces numbered from 1.
+const TAGS: i32 = 32;
+
+/// Tags focused on the focused output, scrolled by `ScrollWorkspace`.
+static FOCUSED_TAGS: AtomicU32 = AtomicU32::new(1);
+
+pub async fn execute_command(cmd: CompositorCommand) -> Result<()> {
+    let args = match cmd {
+        CompositorCommand::FocusWorkspace(id) => {
+            vec!["set-focused-tags".to_owned(), tag_mask(id)?.to_string()]
+        }
+        CompositorCommand::FocusSpecialWorkspace(_)
+        | CompositorCommand::ToggleSpecialWorkspace(_) => {
+            return Err(anyhow!("Special workspaces not supported in River backend"));
+        }
+        CompositorCommand::FocusMonitor(_) => {
+            return Err(anyhow!("FocusMonitor by ID not supported in River backend"));
+        }
+        CompositorCommand::ScrollWorkspace(dir) => {
+            let tags = FOCUSED_TAGS.load(Ordering::Relaxed).max(1);
+            let tags = if dir > 0 {
+                tags.rotate_left(1)
+            } else {
+                tags.rotate_right(1)
+            };
+
+            vec!["set-focused-tags".to_owned(), tags.to_string()]
+        }
+        CompositorCommand::FocusWindow(_) | CompositorCommand::CloseWindow(_) => {
+            return Err(anyhow!("Windows not supported in River backend"));
+        }
+        CompositorCommand::SwitchLayout(..) => {
+            return Err(anyhow!(
+                "Switching the layout not supported in River backend"
+            ));
+        }
+        // spawn takes the whole shell command as a single argument
+        CompositorCommand::CustomDispatch(action, args) if action == "spawn" => vec![action, args],
+        CompositorCommand::CustomDispatch(action, args) => std::iter::once(action)
+            .chain(args.split_whitespace().map(str::to_owned))
+            .collect(),
+    };
+
+    tokio::task::spawn_blocking(move || run_command(&args)).await?
+}
+
+fn tag_mask(id: i32) -> Result<u32> {
+    if (1..=TAGS).contains(&id) {
+        Ok(1 << (id - 1))
+    } else {
+        Err(anyhow!("Tag {} is out of range for River backend", id))
+    }
+}
+
+/// First tag of a bitfield, numbered from 1.
+fn first_tag(tags: u32) -> Option<i32> {
+    (tags != 0).then(|| tags.trailing_zeros() as i32 + 1)
+}
+
+/// River sets no environment variable of its own, the status protocol is
+/// looked up on the Wayland registry instead.
+pub fn is_available() -> bool {
+    static AVAILABLE: LazyLock<bool> =
+        LazyLock::new(|| super::has_wayland_global(ZriverStatusManagerV1::interface().name));
+
+    *AVAILABLE
+}
+
+pub async fn run_listener(tx: &broadcast::Sender<ServiceEvent<CompositorService>>) -> Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<Listener>(&connection)?;
+    let handle = queue.handle();
+
+    let manager: ZriverStatusManagerV1 = globals
+        .bind(&handle, 1..=4, ())
+        .context("River status protocol not supported")?;
+    let seat: WlSeat = globals.bind(&handle, 1..=1, ())?;
+    manager.get_river_seat_status(&seat, &handle, ());
+
+    let mut listener = Listener {
+        tx: tx.clone(),
+        manager,
+        outputs: Vec::new(),
+        focused_output: None,
+        title: String::new(),
+        mode: String::new(),
+        changed: false,
+    };
+    globals.contents().with_list(|list| {
+        for global in list {
+            if global.interface == WlOutput::interface().name {
+                listener.add_output(globals.registry(), global.name, global.version, &handle);
+            }
+        }
+    });
+
+    super::dispatch_until_closed(&connection, &mut queue, &mut listener, tx, |listener| {
+        if !std::mem::take(&mut listener.changed) {
+            return;
+        }
+
+        if let Some(output) = listener.focused() {
+            FOCUSED_TAGS.store(output.focused_tags, Ordering::Relaxed);
+        }
+
+        let _ = listener
+            .tx
+            .send(ServiceEvent::Update(CompositorEvent::StateChanged(
+                listener.state(),
+            )));
+    })
+    .await
+}
+
+fn run_command(args: &[String]) -> Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<Command>(&connection)?;
+    let handle = queue.handle();
+
+    let control: ZriverControlV1 = globals
+        .bind(&handle, 1..=1, ())
+        .context("River control protocol not supported")?;
+    let seat: WlSeat = globals.bind(&handle, 1..=1, ())?;
+
+    for arg in args {
+        control.add_argument(arg.clone());
+    }
+    control.run_command(&seat, &handle, ());
+
+    let mut command = Command::default();
+    loop {
+        queue.blocking_dispatch(&mut command)?;
+
+        if let Some(result) = command.result.take() {
+            control.destroy();
+
+            return result.map_err(|e| anyhow!("River error: {}", e));
+        }
+    }
+}
+
+struct Output {
+    /// Name of the global, identifies the output in the events
+    global: u32,
+    output: WlOutput,
+    status: ZriverOutputStatusV1,
+    name: String,
+    focused_tags: u32,
+    /// Tags of every window of the output
+    view_tags: Vec<u32>,
+}
+
+struct Listener {
+    tx: broadcast::Sender<ServiceEvent<CompositorService>>,
+    manager: ZriverStatusManagerV1,
+    outputs: Vec<Output>,
+    focused_output: Option<u32>,
+    /// Title of the focused window, empty when none is focused
+    title: String,
+    mode: String,
+    changed: bool,
+}
+
+impl Listener {
+    fn add_output(
+        &mut self,
+        registry: &WlRegistry,
+        global: u32,
+        version: u32,
+        handle: &QueueHandle<Self>,
+    ) {
+        let output: WlOutput = registry.bind(global, version.min(4), handle, global);
+        let status = self
+            .manager
+            .get_river_output_status(&output, handle, global);
+
+        self.outputs.push(Output {
+            global,
+            output,
+            status,
+            name: String::new(),
+            focused_tags: 0,
+            view_tags: Vec::new(),
+        });
+    }
+
+    fn output_mut(&mut self, global: u32) -> Option<&mut Output> {
+        self.outputs
+            .iter_mut()
+            .find(|output| output.global == global)
+    }
+
+    fn focused(&self) -> Option<&Output> {
+        self.focused_output
+            .and_then(|global| self.outputs.iter().find(|output| output.global == global))
+            .or_else(|| self.outputs.first())
+    }
+
+    fn state(&self) -> CompositorState {
+        let workspaces = (1..=TAGS)
+            .filter_map(|id| {
+                let mask = tag_mask(id).ok()?;
+                let windows = self
+                    .outputs
+                    .iter()
+                    .flat_map(|output| &output.view_tags)
+                    .filter(|tags| *tags & mask != 0)
+                    .count();
+                // a tag can be focused on several outputs, the first one wins
+                let monitor_id = self
+                    .outputs
+                    .iter()
+                    .position(|output| output.focused_tags & mask != 0)
+                    .or_else(|| {
+                        self.outputs
+                            .iter()
+                            .position(|output| output.view_tags.iter().any(|tags| tags & mask != 0))
+                    })?;
+
+                Some(CompositorWorkspace {
+                    id,
+                    index: id,
+                    name: id.to_string(),
+                    monitor: self.outputs[monitor_id].name.clone(),
+                    monitor_id: Some(monitor_id as i128),
+                    windows: windows as u16,
+                    is_special: false,
+                })
+            })
+            .collect();
+
+        let monitors = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(id, output)| CompositorMonitor {
+                id: id as i128,
+                name: output.name.clone(),
+                active_workspace_id: first_tag(output.focused_tags).unwrap_or(-1),
+                special_workspace_id: -1,
+                // not reported by the status protocol
+                fullscreen: false,
+            })
+            .collect();
+
+        CompositorState {
+            workspaces,
+            monitors,
+            active_workspace_id: self
+                .focused()
+                .and_then(|output| first_tag(output.focused_tags)),
+            // River only reports the title of the focused window
+            active_window: (!self.title.is_empty()).then(|| ActiveWindow {
+                title: self.title.clone(),
+                ..ActiveWindow::default()
+            }),
+            windows: Vec::new(),
+            keyboard_layout: "Unknown".to_string(),
+            keyboard_layouts: Vec::new(),
+            // the modes of River play the part of the submaps of Hyprland
+            submap: (!self.mode.is_empty() && self.mode != "normal").then(|| self.mode.clone()),
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Listener {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == WlOutput::interface().name => {
+                state.add_output(registry, name, version, handle);
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(index) = state
+                    .outputs
+                    .iter()
+                    .position(|output| output.global == name)
+                {
+                    let output = state.outputs.remove(index);
+                    output.status.destroy();
+                    if output.output.version() >= 3 {
+                        output.output.release();
+                    }
+                    state.changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &WlOutput,
+        event: wl_output::Event,
+        global: &u32,
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event
+            && let Some(output) = state.output_mut(*global)
+        {
+            output.name = name;
+            state.changed = true;
+        }
+    }
+}
+
+impl Dispatch<ZriverOutputStatusV1, u32> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZriverOutputStatusV1,
+        event: zriver_output_status_v1::Event,
+        global: &u32,
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.output_mut(*global) else {
+            return;
+        };
+
+        match event {
+            zriver_output_status_v1::Event::FocusedTags { tags } => {
+                output.focused_tags = tags;
+            }
+            zriver_output_status_v1::Event::ViewTags { tags } => {
+                output.view_tags = tags
+                    .chunks_exact(4)
+                    .map(|tags| u32::from_ne_bytes([tags[0], tags[1], tags[2], tags[3]]))
+                    .collect();
+            }
+            _ => return,
+        }
+        state.changed = true;
+    }
+}
+
+impl Dispatch<ZriverSeatStatusV1, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZriverSeatStatusV1,
+        event: zriver_seat_status_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zriver_seat_status_v1::Event::FocusedOutput { output } => {
+                state.focused_output = output.data::<u32>().copied();
+            }
+            zriver_seat_status_v1::Event::UnfocusedOutput { output } => {
+                if state.focused_output == output.data::<u32>().copied() {
+                    state.focused_output = None;
+                }
+            }
+            zriver_seat_status_v1::Event::FocusedView { title } => {
+                state.title = title;
+            }
+            zriver_seat_status_v1::Event::Mode { name } => {
+                state.mode = name;
+            }
+            _ => return,
+        }
+        state.changed = true;
+    }
+}
+
+delegate_noop!(Listener: ZriverStatusManagerV1);
+delegate_noop!(Listener: ignore WlSeat);
+
+/// State of a command sent through the control protocol.
+#[derive(Default)]
+struct Command {
+    result: Option<Result<(), String>>,
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Command {
+    fn event(
+        _state: &mut Self,
+        _registry: &WlRegistry,
+        _event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZriverCommandCallbackV1, ()> for Command {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZriverCommandCallbackV1,
+        event: zriver_command_callback_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zriver_command_callback_v1::Event::Success { .. } => {
+                state.result = Some(Ok(()));
+            }
+            zriver_command_callback_v1::Event::Failure { failure_message } => {
+                state.result = Some(Err(failure_message));
+            }
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(Command: ZriverControlV1);
+delegate_noop!(Command: ignore WlSeat);
@@ -15,6 +15,8 @@ pub struct CompositorMonitor {
     pub name: String,
     pub active_workspace_id: i32,
     pub special_workspace_id: i32,
+    /// The active workspace of the monitor has a fullscreen window
+    pub fullscreen: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -24,13 +26,27 @@ pub struct ActiveWindow {
     pub address: String,
 }
 
+/// Open window, the id is the one used to focus it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositorWindow {
+    pub id: String,
+    pub title: String,
+    pub class: String,
+    pub workspace_id: Option<i32>,
+    /// Name of the output showing the window
+    pub monitor: Option<String>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct CompositorState {
     pub workspaces: Vec<CompositorWorkspace>,
     pub monitors: Vec<CompositorMonitor>,
     pub active_workspace_id: Option<i32>,
     pub active_window: Option<ActiveWindow>,
+    pub windows: Vec<CompositorWindow>,
     pub keyboard_layout: String,
+    /// Names of the configured keyboard layouts, in switching order
+    pub keyboard_layouts: Vec<String>,
     pub submap: Option<String>,
 }
 
@@ -38,6 +54,13 @@ pub struct CompositorState {
 pub enum CompositorChoice {
     Hyprland,
     Niri,
+    River,
+    /// KWin, through the Plasma window management and virtual desktop
+    /// protocols
+    Kde,
+    /// Any compositor implementing wlr-foreign-toplevel-management, only
+    /// the windows are known
+    ForeignToplevel,
 }
 
 #[derive(Debug, Clone)]
@@ -62,5 +85,17 @@ pub enum CompositorCommand {
     ToggleSpecialWorkspace(String),
     ScrollWorkspace(i32),           // +1 or -1
     CustomDispatch(String, String), // For "vdesk"
-    NextLayout,
+    /// Focuses the window with the given id
+    FocusWindow(String),
+    /// Asks the window with the given id to close
+    CloseWindow(String),
+    /// Switches the layout of the keyboard device, `all` for every keyboard
+    SwitchLayout(String, LayoutSwitch),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LayoutSwitch {
+    Next,
+    Previous,
+    Index(u8),
 }
@@ -0,0 +1,605 @@
+use super::{Backoff, ReadOnlyService, Service, ServiceEvent};
+use anyhow::{Context, Result, anyhow, bail};
+use iced::{
+    Subscription, Task,
+    futures::{SinkExt, channel::mpsc::Sender, future::pending},
+    stream::channel,
+};
+use log::{debug, error, info, warn};
+use std::{any::TypeId, fmt, ops::Deref, sync::Mutex};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle, WEnum, delegate_noop, event_created_child,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{wl_output::Transform, wl_registry::WlRegistry},
+};
+use wayland_protocols_wlr::output_management::v1::client::{
+    zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1,
+    zwlr_output_configuration_v1::{self, ZwlrOutputConfigurationV1},
+    zwlr_output_head_v1::{self, ZwlrOutputHeadV1},
+    zwlr_output_manager_v1::{self, ZwlrOutputManagerV1},
+    zwlr_output_mode_v1::{self, ZwlrOutputModeV1},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayMode {
+    pub width: i32,
+    pub height: i32,
+    /// Refresh rate in mHz, 0 when unknown
+    pub refresh: i32,
+    pub preferred: bool,
+}
+
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}×{}", self.width, self.height)?;
+        if self.refresh > 0 {
+            write!(f, " @ {:.2} Hz", self.refresh as f64 / 1000.)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTransform {
+    #[default]
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Flipped,
+    Flipped90,
+    Flipped180,
+    Flipped270,
+}
+
+impl DisplayTransform {
+    pub const ALL: [DisplayTransform; 8] = [
+        DisplayTransform::Normal,
+        DisplayTransform::Rotate90,
+        DisplayTransform::Rotate180,
+        DisplayTransform::Rotate270,
+        DisplayTransform::Flipped,
+        DisplayTransform::Flipped90,
+        DisplayTransform::Flipped180,
+        DisplayTransform::Flipped270,
+    ];
+}
+
+impl fmt::Display for DisplayTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DisplayTransform::Normal => "Normal",
+            DisplayTransform::Rotate90 => "90°",
+            DisplayTransform::Rotate180 => "180°",
+            DisplayTransform::Rotate270 => "270°",
+            DisplayTransform::Flipped => "Flipped",
+            DisplayTransform::Flipped90 => "Flipped 90°",
+            DisplayTransform::Flipped180 => "Flipped 180°",
+            DisplayTransform::Flipped270 => "Flipped 270°",
+        })
+    }
+}
+
+impl From<Transform> for DisplayTransform {
+    fn from(transform: Transform) -> Self {
+        match transform {
+            Transform::_90 => DisplayTransform::Rotate90,
+            Transform::_180 => DisplayTransform::Rotate180,
+            Transform::_270 => DisplayTransform::Rotate270,
+            Transform::Flipped => DisplayTransform::Flipped,
+            Transform::Flipped90 => DisplayTransform::Flipped90,
+            Transform::Flipped180 => DisplayTransform::Flipped180,
+            Transform::Flipped270 => DisplayTransform::Flipped270,
+            _ => DisplayTransform::Normal,
+        }
+    }
+}
+
+impl From<DisplayTransform> for Transform {
+    fn from(transform: DisplayTransform) -> Self {
+        match transform {
+            DisplayTransform::Normal => Transform::Normal,
+            DisplayTransform::Rotate90 => Transform::_90,
+            DisplayTransform::Rotate180 => Transform::_180,
+            DisplayTransform::Rotate270 => Transform::_270,
+            DisplayTransform::Flipped => Transform::Flipped,
+            DisplayTransform::Flipped90 => Transform::Flipped90,
+            DisplayTransform::Flipped180 => Transform::Flipped180,
+            DisplayTransform::Flipped270 => Transform::Flipped270,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Display {
+    /// Connector name, like `DP-1`
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    pub modes: Vec<DisplayMode>,
+    /// Index of the current mode in `modes`
+    pub current_mode: Option<usize>,
+    pub scale: f64,
+    pub transform: DisplayTransform,
+}
+
+impl Display {
+    pub fn current_mode(&self) -> Option<DisplayMode> {
+        self.current_mode
+            .and_then(|index| self.modes.get(index))
+            .copied()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplaysService {
+    displays: Vec<Display>,
+}
+
+impl Deref for DisplaysService {
+    type Target = Vec<Display>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.displays
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplaysEvent(Vec<Display>);
+
+impl ReadOnlyService for DisplaysService {
+    type UpdateEvent = DisplaysEvent;
+    type Error = ();
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        self.displays = event.0;
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            id,
+            channel(100, async |mut output| {
+                let mut backoff = Backoff::default();
+
+                loop {
+                    if !DisplaysService::listen(&mut output).await {
+                        // without the protocol there is nothing to wait for
+                        pending::<()>().await;
+                    }
+
+                    let _ = output.send(ServiceEvent::Error(())).await;
+                    backoff.wait().await;
+                }
+            }),
+        )
+    }
+}
+
+impl DisplaysService {
+    /// Forwards the state of the outputs until the listener stops, returns
+    /// whether the compositor supports the protocol.
+    async fn listen(output: &mut Sender<ServiceEvent<Self>>) -> bool {
+        let (tx, mut rx) = unbounded_channel();
+
+        // the Wayland event queue blocks, it gets a thread of its own
+        let listener = tokio::task::spawn_blocking(move || listen(tx));
+
+        let mut initialized = false;
+        while let Some(displays) = rx.recv().await {
+            let event = if initialized {
+                ServiceEvent::Update(DisplaysEvent(displays))
+            } else {
+                ServiceEvent::Init(DisplaysService { displays })
+            };
+            initialized = true;
+
+            let _ = output.send(event).await;
+        }
+
+        if let Ok(mut control) = CONTROL.lock() {
+            *control = None;
+        }
+
+        match listener.await {
+            Ok(Err(err)) if !initialized => {
+                info!("Output management is not available: {err}");
+
+                false
+            }
+            Ok(Err(err)) => {
+                error!("Output management listener failed: {err}");
+
+                true
+            }
+            Ok(Ok(())) => true,
+            Err(err) => {
+                error!("Output management listener panicked: {err}");
+
+                true
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DisplaysCommand {
+    SetEnabled(String, bool),
+    SetMode(String, DisplayMode),
+    SetScale(String, f64),
+    SetTransform(String, DisplayTransform),
+}
+
+impl Service for DisplaysService {
+    type Command = DisplaysCommand;
+
+    fn command(&mut self, command: Self::Command) -> Task<ServiceEvent<Self>> {
+        debug!("Display command: {command:?}");
+
+        // the new state is received once the compositor applied it
+        let result = match command {
+            DisplaysCommand::SetEnabled(name, enabled) => configure(&name, |display| {
+                display.enabled = enabled;
+                if enabled && display.current_mode.is_none() {
+                    display.current_mode = display
+                        .modes
+                        .iter()
+                        .position(|mode| mode.preferred)
+                        .or((!display.modes.is_empty()).then_some(0));
+                }
+            }),
+            DisplaysCommand::SetMode(name, mode) => configure(&name, |display| {
+                if let Some(index) = display.modes.iter().position(|m| *m == mode) {
+                    display.current_mode = Some(index);
+                }
+            }),
+            DisplaysCommand::SetScale(name, scale) => {
+                configure(&name, |display| display.scale = scale)
+            }
+            DisplaysCommand::SetTransform(name, transform) => {
+                configure(&name, |display| display.transform = transform)
+            }
+        };
+
+        if let Err(err) = result {
+            warn!("Failed to configure the displays: {err}");
+        }
+
+        Task::none()
+    }
+}
+
+/// Heads of the outputs with the connection they belong to, the
+/// configurations are sent from outside of the listener thread.
+static CONTROL: Mutex<Option<Control>> = Mutex::new(None);
+
+struct Control {
+    connection: Connection,
+    handle: QueueHandle<Listener>,
+    manager: ZwlrOutputManagerV1,
+    /// Serial of the last `done` event, identifies the state a
+    /// configuration applies to
+    serial: u32,
+    /// Head and modes of each display
+    heads: Vec<(ZwlrOutputHeadV1, Vec<ZwlrOutputModeV1>)>,
+    displays: Vec<Display>,
+}
+
+/// Applies the current state of every display, with one of them changed.
+fn configure(name: &str, f: impl FnOnce(&mut Display)) -> Result<()> {
+    let control = CONTROL
+        .lock()
+        .map_err(|_| anyhow!("Output management listener panicked"))?;
+    let control = control
+        .as_ref()
+        .context("Output management listener not running")?;
+
+    let mut displays = control.displays.clone();
+    let display = displays
+        .iter_mut()
+        .find(|display| display.name == name)
+        .with_context(|| format!("No display named {name}"))?;
+    f(display);
+
+    if displays.iter().all(|display| !display.enabled) {
+        bail!("Refusing to disable the last enabled display");
+    }
+
+    let configuration = control
+        .manager
+        .create_configuration(control.serial, &control.handle, ());
+    for ((head, modes), display) in control.heads.iter().zip(&displays) {
+        if display.enabled {
+            let head = configuration.enable_head(head, &control.handle, ());
+            if let Some(mode) = display.current_mode.and_then(|index| modes.get(index)) {
+                head.set_mode(mode);
+            }
+            head.set_scale(display.scale);
+            head.set_transform(display.transform.into());
+        } else {
+            configuration.disable_head(head);
+        }
+    }
+    configuration.apply();
+    control.connection.flush()?;
+
+    Ok(())
+}
+
+fn listen(tx: UnboundedSender<Vec<Display>>) -> Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<Listener>(&connection)?;
+    let handle = queue.handle();
+
+    let manager: ZwlrOutputManagerV1 = globals
+        .bind(&handle, 1..=3, ())
+        .context("Output management protocol not supported")?;
+
+    if let Ok(mut control) = CONTROL.lock() {
+        *control = Some(Control {
+            connection,
+            handle: handle.clone(),
+            manager,
+            serial: 0,
+            heads: Vec::new(),
+            displays: Vec::new(),
+        });
+    }
+
+    let mut listener = Listener {
+        heads: Vec::new(),
+        done: None,
+        finished: false,
+    };
+
+    while !listener.finished {
+        queue.blocking_dispatch(&mut listener)?;
+
+        if let Some(serial) = listener.done.take() {
+            let displays = listener.displays();
+
+            if let Ok(mut control) = CONTROL.lock()
+                && let Some(control) = control.as_mut()
+            {
+                control.serial = serial;
+                control.heads = listener
+                    .heads
+                    .iter()
+                    .map(|head| {
+                        (
+                            head.proxy.clone(),
+                            head.modes.iter().map(|mode| mode.proxy.clone()).collect(),
+                        )
+                    })
+                    .collect();
+                control.displays = displays.clone();
+            }
+
+            if tx.send(displays).is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(anyhow!("Output manager finished"))
+}
+
+struct Mode {
+    proxy: ZwlrOutputModeV1,
+    mode: DisplayMode,
+}
+
+struct Head {
+    proxy: ZwlrOutputHeadV1,
+    name: String,
+    description: String,
+    enabled: bool,
+    modes: Vec<Mode>,
+    current_mode: Option<ZwlrOutputModeV1>,
+    scale: f64,
+    transform: DisplayTransform,
+}
+
+struct Listener {
+    heads: Vec<Head>,
+    /// Serial of a `done` event not forwarded yet
+    done: Option<u32>,
+    finished: bool,
+}
+
+impl Listener {
+    fn head_mut(&mut self, proxy: &ZwlrOutputHeadV1) -> Option<&mut Head> {
+        self.heads.iter_mut().find(|head| &head.proxy == proxy)
+    }
+
+    fn mode_mut(&mut self, proxy: &ZwlrOutputModeV1) -> Option<&mut Mode> {
+        self.heads
+            .iter_mut()
+            .flat_map(|head| head.modes.iter_mut())
+            .find(|mode| &mode.proxy == proxy)
+    }
+
+    fn displays(&self) -> Vec<Display> {
+        self.heads
+            .iter()
+            .map(|head| Display {
+                name: head.name.clone(),
+                description: head.description.clone(),
+                enabled: head.enabled,
+                modes: head.modes.iter().map(|mode| mode.mode).collect(),
+                current_mode: head
+                    .current_mode
+                    .as_ref()
+                    .and_then(|current| head.modes.iter().position(|mode| &mode.proxy == current)),
+                scale: head.scale,
+                transform: head.transform,
+            })
+            .collect()
+    }
+}
+
+impl Dispatch<ZwlrOutputManagerV1, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwlrOutputManagerV1,
+        event: zwlr_output_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_manager_v1::Event::Head { head } => state.heads.push(Head {
+                proxy: head,
+                name: String::new(),
+                description: String::new(),
+                enabled: false,
+                modes: Vec::new(),
+                current_mode: None,
+                scale: 1.,
+                transform: DisplayTransform::Normal,
+            }),
+            zwlr_output_manager_v1::Event::Done { serial } => state.done = Some(serial),
+            zwlr_output_manager_v1::Event::Finished => state.finished = true,
+            _ => {}
+        }
+    }
+
+    event_created_child!(Listener, ZwlrOutputManagerV1, [
+        zwlr_output_manager_v1::EVT_HEAD_OPCODE => (ZwlrOutputHeadV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrOutputHeadV1, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrOutputHeadV1,
+        event: zwlr_output_head_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let zwlr_output_head_v1::Event::Finished = event {
+            if let Some(index) = state.heads.iter().position(|head| &head.proxy == proxy) {
+                state.heads.remove(index);
+            }
+            if proxy.version() >= 3 {
+                proxy.release();
+            }
+
+            return;
+        }
+
+        let Some(head) = state.head_mut(proxy) else {
+            return;
+        };
+
+        match event {
+            zwlr_output_head_v1::Event::Name { name } => head.name = name,
+            zwlr_output_head_v1::Event::Description { description } => {
+                head.description = description;
+            }
+            zwlr_output_head_v1::Event::Mode { mode } => head.modes.push(Mode {
+                proxy: mode,
+                mode: DisplayMode {
+                    width: 0,
+                    height: 0,
+                    refresh: 0,
+                    preferred: false,
+                },
+            }),
+            zwlr_output_head_v1::Event::Enabled { enabled } => head.enabled = enabled != 0,
+            zwlr_output_head_v1::Event::CurrentMode { mode } => head.current_mode = Some(mode),
+            zwlr_output_head_v1::Event::Scale { scale } => head.scale = scale,
+            zwlr_output_head_v1::Event::Transform {
+                transform: WEnum::Value(transform),
+            } => head.transform = transform.into(),
+            _ => {}
+        }
+    }
+
+    event_created_child!(Listener, ZwlrOutputHeadV1, [
+        zwlr_output_head_v1::EVT_MODE_OPCODE => (ZwlrOutputModeV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrOutputModeV1, ()> for Listener {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrOutputModeV1,
+        event: zwlr_output_mode_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        if let zwlr_output_mode_v1::Event::Finished = event {
+            for head in &mut state.heads {
+                head.modes.retain(|mode| &mode.proxy != proxy);
+                if head.current_mode.as_ref() == Some(proxy) {
+                    head.current_mode = None;
+                }
+            }
+            if proxy.version() >= 3 {
+                proxy.release();
+            }
+
+            return;
+        }
+
+        let Some(mode) = state.mode_mut(proxy) else {
+            return;
+        };
+
+        match event {
+            zwlr_output_mode_v1::Event::Size { width, height } => {
+                mode.mode.width = width;
+                mode.mode.height = height;
+            }
+            zwlr_output_mode_v1::Event::Refresh { refresh } => mode.mode.refresh = refresh,
+            zwlr_output_mode_v1::Event::Preferred => mode.mode.preferred = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwlrOutputConfigurationV1, ()> for Listener {
+    fn event(
+        _state: &mut Self,
+        proxy: &ZwlrOutputConfigurationV1,
+        event: zwlr_output_configuration_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_configuration_v1::Event::Succeeded => {
+                debug!("Display configuration applied");
+            }
+            zwlr_output_configuration_v1::Event::Failed => {
+                warn!("The compositor rejected the display configuration");
+            }
+            zwlr_output_configuration_v1::Event::Cancelled => {
+                warn!("Display configuration cancelled, the displays changed meanwhile");
+            }
+            _ => return,
+        }
+
+        proxy.destroy();
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Listener {
+    fn event(
+        _state: &mut Self,
+        _registry: &WlRegistry,
+        _event: <WlRegistry as Proxy>::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _handle: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(Listener: ignore ZwlrOutputConfigurationHeadV1);
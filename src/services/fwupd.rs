@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use zbus::{Connection, proxy, zvariant::OwnedValue};
+
+/// The device can be updated while the system is running.
+const DEVICE_FLAG_UPDATABLE: u64 = 1 << 1;
+
+type Dict = HashMap<String, OwnedValue>;
+
+/// A firmware update of a device.
+#[derive(Debug, Clone)]
+pub struct FirmwareUpgrade {
+    pub device: String,
+    pub from: String,
+    pub to: String,
+}
+
+fn string(dict: &Dict, key: &str) -> Option<String> {
+    dict.get(key)
+        .and_then(|value| value.try_clone().ok()?.try_into().ok())
+}
+
+/// Newest firmware of each updatable device, from the metadata already
+/// downloaded by the fwupd daemon.
+pub async fn upgrades() -> anyhow::Result<Vec<FirmwareUpgrade>> {
+    let connection = Connection::system().await?;
+    let proxy = FwupdProxy::new(&connection).await?;
+
+    let mut upgrades = Vec::new();
+    for device in proxy.get_devices().await? {
+        let updatable = device
+            .get("Flags")
+            .and_then(|flags| u64::try_from(flags).ok())
+            .is_some_and(|flags| flags & DEVICE_FLAG_UPDATABLE != 0);
+        let Some(device_id) = string(&device, "DeviceId") else {
+            continue;
+        };
+        if !updatable {
+            continue;
+        }
+
+        // fwupd replies with an error when there is nothing to do
+        let Ok(releases) = proxy.get_upgrades(&device_id).await else {
+            continue;
+        };
+
+        if let Some(to) = releases
+            .first()
+            .and_then(|release| string(release, "Version"))
+        {
+            upgrades.push(FirmwareUpgrade {
+                device: string(&device, "Name").unwrap_or(device_id),
+                from: string(&device, "Version").unwrap_or_default(),
+                to,
+            });
+        }
+    }
+
+    Ok(upgrades)
+}
+
+#[proxy(
+    interface = "org.freedesktop.fwupd",
+    default_service = "org.freedesktop.fwupd",
+    default_path = "/"
+)]
+trait Fwupd {
+    fn get_devices(&self) -> zbus::Result<Vec<Dict>>;
+
+    fn get_upgrades(&self, device_id: &str) -> zbus::Result<Vec<Dict>>;
+}
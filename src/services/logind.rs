@@ -5,7 +5,7 @@ use iced::{
     stream::channel,
 };
 use std::any::TypeId;
-use zbus::Connection;
+use zbus::{Connection, zvariant::OwnedObjectPath};
 
 #[derive(Debug, Clone)]
 pub struct ResumeEvent;
@@ -71,7 +71,18 @@ impl ReadOnlyService for LogindService {
     default_service = "org.freedesktop.login1",
     default_path = "/org/freedesktop/login1"
 )]
-trait Login1Manager {
+pub(crate) trait Login1Manager {
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
     #[zbus(signal)]
     fn prepare_for_sleep(&self, starting: bool) -> ();
 }
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+pub(crate) trait Login1Session {
+    #[zbus(property)]
+    fn locked_hint(&self) -> zbus::Result<bool>;
+}
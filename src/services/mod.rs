@@ -1,14 +1,25 @@
-use iced::{Subscription, Task};
+use iced::{
+    Subscription, Task,
+    futures::{SinkExt, StreamExt, channel::mpsc::Sender, future::pending},
+};
+use log::{debug, warn};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
 
+pub mod appearance;
 pub mod audio;
 pub mod bluetooth;
 pub mod brightness;
 pub mod compositor;
+pub mod displays;
+pub mod fwupd;
 pub mod idle_inhibitor;
 pub mod logind;
 pub mod mpris;
 pub mod network;
+pub mod notifications;
 pub mod privacy;
+pub mod registry;
 mod throttle;
 pub mod tray;
 pub mod upower;
@@ -35,3 +46,75 @@ pub trait ReadOnlyService: Sized {
 
     fn subscribe() -> Subscription<ServiceEvent<Self>>;
 }
+
+/// Delay before connecting again to a backend that failed or went away,
+/// doubled after each failed attempt.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    delay: Duration,
+    failed_at: Option<Instant>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            delay: Self::MIN,
+            failed_at: None,
+        }
+    }
+}
+
+impl Backoff {
+    const MIN: Duration = Duration::from_secs(1);
+    const MAX: Duration = Duration::from_secs(60);
+
+    pub async fn wait(&mut self) {
+        // a backend that ran for a while starts over from the shortest delay
+        if self
+            .failed_at
+            .is_some_and(|failed_at| failed_at.elapsed() > self.delay + Self::MAX)
+        {
+            self.delay = Self::MIN;
+        }
+
+        debug!("Reconnecting in {:?}", self.delay);
+        sleep(self.delay).await;
+
+        self.delay = (self.delay * 2).min(Self::MAX);
+        self.failed_at = Some(Instant::now());
+    }
+
+    /// Reports the failure of the backend, so that its consumers drop the
+    /// stale state until it's back, then waits before connecting again.
+    pub async fn failed<S: ReadOnlyService>(
+        &mut self,
+        output: &mut Sender<ServiceEvent<S>>,
+        error: S::Error,
+    ) {
+        let _ = output.send(ServiceEvent::Error(error)).await;
+        self.wait().await;
+    }
+}
+
+/// Resolves when the service owning a bus name goes away or restarts, never
+/// when the owner can't be followed.
+pub async fn restarted(conn: zbus::Connection, name: &'static str) {
+    let changes = async {
+        zbus::fdo::DBusProxy::new(&conn)
+            .await?
+            .receive_name_owner_changed_with_args(&[(0, name)])
+            .await
+    };
+
+    match changes.await {
+        Ok(mut changes) => {
+            if changes.next().await.is_some() {
+                debug!("The owner of {name} changed");
+            }
+        }
+        Err(e) => {
+            warn!("Failed to follow the owner of {name}: {e}");
+            pending::<()>().await;
+        }
+    }
+}
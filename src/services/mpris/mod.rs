@@ -1,12 +1,9 @@
-use super::{ReadOnlyService, Service, ServiceEvent};
+use super::{Backoff, ReadOnlyService, Service, ServiceEvent};
 use dbus::MprisPlayerProxy;
 use iced::{
     Subscription,
     futures::{
-        SinkExt, Stream, StreamExt,
-        channel::mpsc::Sender,
-        future::join_all,
-        stream::{SelectAll, pending},
+        SinkExt, Stream, StreamExt, channel::mpsc::Sender, future::join_all, stream::SelectAll,
     },
     stream::channel,
 };
@@ -111,9 +108,10 @@ impl ReadOnlyService for MprisPlayerService {
             id,
             channel(10, async |mut output| {
                 let mut state = State::Init;
+                let mut backoff = Backoff::default();
 
                 loop {
-                    state = Self::start_listening(state, &mut output).await;
+                    state = Self::start_listening(state, &mut output, &mut backoff).await;
                 }
             }),
         )
@@ -275,7 +273,11 @@ impl MprisPlayerService {
         Ok(combined)
     }
 
-    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+    async fn start_listening(
+        state: State,
+        output: &mut Sender<ServiceEvent<Self>>,
+        backoff: &mut Backoff,
+    ) -> State {
         match state {
             State::Init => match zbus::Connection::session().await {
                 Ok(conn) => {
@@ -333,9 +335,9 @@ impl MprisPlayerService {
                 }
             },
             State::Error => {
-                let _ = pending::<u8>().next().await;
+                backoff.failed(output, ()).await;
 
-                State::Error
+                State::Init
             }
         }
     }
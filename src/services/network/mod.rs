@@ -1,9 +1,8 @@
-use super::{Service, ServiceEvent};
+use super::{Backoff, Service, ServiceEvent, restarted};
 use crate::services::ReadOnlyService;
 use dbus::ConnectivityState;
 use dbus::NetworkDbus;
 use iced::futures::TryFutureExt;
-use iced::futures::stream::pending;
 use iced::{
     Subscription, Task,
     futures::{SinkExt, StreamExt, channel::mpsc::Sender},
@@ -17,6 +16,9 @@ use zbus::zvariant::OwnedObjectPath;
 pub mod dbus;
 pub mod iwd_dbus;
 
+const NETWORK_MANAGER_SERVICE: &str = "org.freedesktop.NetworkManager";
+const IWD_SERVICE: &str = "net.connman.iwd";
+
 /// Trait defining the interface for a network backend.
 /// This allows abstracting the specific D-Bus implementation (like IWD or `NetworkManager`).
 pub trait NetworkBackend: Send + Sync {
@@ -229,9 +231,10 @@ impl ReadOnlyService for NetworkService {
             id,
             channel(50, async |mut output| {
                 let mut state = State::Init;
+                let mut backoff = Backoff::default();
 
                 loop {
-                    state = NetworkService::start_listening(state, &mut output).await;
+                    state = NetworkService::start_listening(state, &mut output, &mut backoff).await;
                 }
             }),
         )
@@ -360,7 +363,11 @@ impl NetworkBackend for BackendChoiceWithConnection {
 }
 
 impl NetworkService {
-    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+    async fn start_listening(
+        state: State,
+        output: &mut Sender<ServiceEvent<Self>>,
+        backoff: &mut Backoff,
+    ) -> State {
         match state {
             State::Init => match zbus::Connection::system().await {
                 Ok(conn) => {
@@ -439,7 +446,12 @@ impl NetworkService {
                         };
 
                         match nm.subscribe_events().await {
-                            Ok(mut events) => {
+                            Ok(events) => {
+                                let mut events = events.take_until(Box::pin(restarted(
+                                    conn.clone(),
+                                    NETWORK_MANAGER_SERVICE,
+                                )));
+
                                 while let Some(event) = events.next().await {
                                     let exit_loop =
                                         matches!(event, NetworkEvent::WirelessDevice { .. });
@@ -448,13 +460,14 @@ impl NetworkService {
                                     let _ = output.send(ServiceEvent::Update(event)).await;
 
                                     if exit_loop {
-                                        break;
+                                        debug!("Network service exit events stream");
+
+                                        return State::Active(conn, choice);
                                     }
                                 }
 
-                                debug!("Network service exit events stream");
-
-                                State::Active(conn, choice)
+                                // the connection was lost or NetworkManager restarted
+                                State::Error
                             }
                             Err(err) => {
                                 error!("Failed to listen for network events: {err}");
@@ -472,7 +485,10 @@ impl NetworkService {
                             }
                         };
                         match iwd.subscribe_events().await {
-                            Ok(mut event_s) => {
+                            Ok(event_s) => {
+                                let mut event_s = event_s
+                                    .take_until(Box::pin(restarted(conn.clone(), IWD_SERVICE)));
+
                                 while let Some(events) = event_s.next().await {
                                     for event in events {
                                         // TODO: network manager leaves with device - we can also
@@ -482,9 +498,8 @@ impl NetworkService {
                                     }
                                 }
 
-                                debug!("Network service exit events stream");
-
-                                State::Active(conn, choice)
+                                // the connection was lost or iwd restarted
+                                State::Error
                             }
                             Err(err) => {
                                 error!("Failed to listen for network events: {err}");
@@ -496,11 +511,9 @@ impl NetworkService {
                 }
             }
             State::Error => {
-                error!("Network service error");
-
-                let _ = pending::<u8>().next().await;
+                backoff.failed(output, ()).await;
 
-                State::Error
+                State::Init
             }
         }
     }
@@ -0,0 +1,303 @@
+use crate::services::tray::{TrayIcon, get_icon_from_name, image_from_rgba, tray_icon_from_path};
+use iced::{
+    Subscription,
+    futures::{SinkExt, channel::mpsc::Sender},
+    stream::channel,
+};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{any::TypeId, collections::HashMap, path::PathBuf, sync::Mutex, time::Duration};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use zbus::{
+    interface,
+    object_server::SignalEmitter,
+    zvariant::{OwnedValue, Value},
+};
+
+const NAME: &str = "org.freedesktop.Notifications";
+const OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+/// How long a notification stays on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    /// Chosen by the server
+    Default,
+    Never,
+    After(Duration),
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u32,
+    pub app_name: String,
+    pub summary: String,
+    pub body: String,
+    /// Key and label of each action, `default` is invoked by clicking the notification
+    pub actions: Vec<(String, String)>,
+    pub urgency: Urgency,
+    pub expiration: Expiration,
+    pub icon: Option<TrayIcon>,
+    /// Notifications of the application with the same tag replace each other,
+    /// e.g. the volume changes
+    pub stack_tag: Option<String>,
+    /// Percentage of the `value` hint, e.g. of a download
+    pub progress: Option<u8>,
+}
+
+impl Notification {
+    /// Whether this notification takes the place of the other one.
+    pub fn replaces(&self, other: &Notification) -> bool {
+        self.id == other.id
+            || (self.stack_tag.is_some()
+                && self.stack_tag == other.stack_tag
+                && self.app_name == other.app_name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    Notify(Notification),
+    Close(u32),
+}
+
+/// Why a notification has been closed, as sent in the `NotificationClosed` signal.
+#[derive(Debug, Clone, Copy)]
+pub enum CloseReason {
+    Expired = 1,
+    Dismissed = 2,
+    Closed = 3,
+}
+
+#[derive(Debug)]
+enum Signal {
+    Closed(u32, CloseReason),
+    ActionInvoked(u32, String),
+}
+
+static SIGNALS: Mutex<Option<UnboundedSender<Signal>>> = Mutex::new(None);
+
+fn emit(signal: Signal) {
+    if let Ok(signals) = SIGNALS.lock()
+        && let Some(signals) = signals.as_ref()
+    {
+        let _ = signals.send(signal);
+    }
+}
+
+/// Tells the application that its notification is gone.
+pub fn closed(id: u32, reason: CloseReason) {
+    emit(Signal::Closed(id, reason));
+}
+
+/// Tells the application that an action of its notification has been picked.
+pub fn invoke_action(id: u32, key: String) {
+    emit(Signal::ActionInvoked(id, key));
+}
+
+/// Raw image of the `image-data` hint: width, height, rowstride,
+/// has alpha, bits per sample, channels and the pixels.
+type ImageData = (i32, i32, i32, bool, i32, i32, Vec<u8>);
+
+fn image_data(value: &Value<'_>) -> Option<TrayIcon> {
+    let (width, height, rowstride, _, _, channels, data): ImageData =
+        value.try_clone().ok()?.try_into().ok()?;
+    let channels = usize::try_from(channels)
+        .ok()
+        .filter(|c| *c == 3 || *c == 4)?;
+    let width = usize::try_from(width).ok()?;
+    let rowstride = usize::try_from(rowstride).ok()?;
+
+    let pixels = data
+        .chunks(rowstride)
+        .take(usize::try_from(height).ok()?)
+        .flat_map(|row| row.chunks_exact(channels).take(width))
+        .flat_map(|pixel| {
+            [
+                pixel[0],
+                pixel[1],
+                pixel[2],
+                pixel.get(3).copied().unwrap_or(255),
+            ]
+        })
+        .collect();
+
+    Some(image_from_rgba(width as u32, height as u32, pixels))
+}
+
+/// An icon name, a path or a `file://` URI.
+pub fn icon(icon: &str) -> Option<TrayIcon> {
+    match icon.strip_prefix("file://") {
+        Some(path) => tray_icon_from_path(PathBuf::from(path)),
+        None if icon.starts_with('/') => tray_icon_from_path(PathBuf::from(icon)),
+        None if icon.is_empty() => None,
+        None => get_icon_from_name(icon),
+    }
+}
+
+struct Server {
+    output: Sender<NotificationEvent>,
+    last_id: u32,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl Server {
+    fn get_capabilities(&self) -> Vec<&'static str> {
+        vec!["actions", "body", "icon-static"]
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn notify(
+        &mut self,
+        app_name: String,
+        replaces_id: u32,
+        app_icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+        expire_timeout: i32,
+    ) -> u32 {
+        let id = if replaces_id == 0 {
+            self.last_id = self.last_id.wrapping_add(1).max(1);
+            self.last_id
+        } else {
+            replaces_id
+        };
+
+        // the image hints have precedence over the icon of the application
+        let icon = ["image-data", "image_data"]
+            .iter()
+            .find_map(|key| hints.get(*key).and_then(|value| image_data(value)))
+            .or_else(|| {
+                ["image-path", "image_path"].iter().find_map(|key| {
+                    hints
+                        .get(*key)
+                        .and_then(|value| String::try_from(value.try_clone().ok()?).ok())
+                        .and_then(|path| icon(&path))
+                })
+            })
+            .or_else(|| icon(&app_icon))
+            .or_else(|| hints.get("icon_data").and_then(|value| image_data(value)));
+        let stack_tag = ["x-canonical-private-synchronous", "x-dunst-stack-tag"]
+            .iter()
+            .find_map(|key| {
+                hints
+                    .get(*key)
+                    .and_then(|value| String::try_from(value.try_clone().ok()?).ok())
+            });
+        let progress = hints.get("value").and_then(|value| {
+            i32::try_from(value)
+                .ok()
+                .or_else(|| u32::try_from(value).ok().map(|value| value as i32))
+                .map(|value| value.clamp(0, 100) as u8)
+        });
+        let urgency = match hints
+            .get("urgency")
+            .and_then(|value| u8::try_from(value).ok())
+        {
+            Some(0) => Urgency::Low,
+            Some(2) => Urgency::Critical,
+            _ => Urgency::Normal,
+        };
+        let expiration = match expire_timeout {
+            0 => Expiration::Never,
+            timeout if timeout < 0 => Expiration::Default,
+            timeout => Expiration::After(Duration::from_millis(timeout as u64)),
+        };
+
+        let notification = Notification {
+            id,
+            app_name,
+            summary,
+            body,
+            actions: actions
+                .chunks_exact(2)
+                .map(|action| (action[0].clone(), action[1].clone()))
+                .collect(),
+            urgency,
+            expiration,
+            icon,
+            stack_tag,
+            progress,
+        };
+        if let Err(e) = self
+            .output
+            .clone()
+            .send(NotificationEvent::Notify(notification))
+            .await
+        {
+            warn!("Failed to show the notification {id}: {e}");
+        }
+
+        id
+    }
+
+    async fn close_notification(&self, id: u32) {
+        let _ = self.output.clone().send(NotificationEvent::Close(id)).await;
+    }
+
+    fn get_server_information(&self) -> (&'static str, &'static str, &'static str, &'static str) {
+        ("ashell", "ashell", env!("CARGO_PKG_VERSION"), "1.2")
+    }
+
+    #[zbus(signal)]
+    async fn notification_closed(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        reason: u32,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn action_invoked(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        action_key: &str,
+    ) -> zbus::Result<()>;
+}
+
+async fn serve(output: Sender<NotificationEvent>) -> anyhow::Result<()> {
+    let connection = zbus::connection::Builder::session()?
+        .name(NAME)?
+        .serve_at(OBJECT_PATH, Server { output, last_id: 0 })?
+        .build()
+        .await?;
+    let emitter = SignalEmitter::new(&connection, OBJECT_PATH)?;
+
+    let (sender, mut signals) = unbounded_channel();
+    if let Ok(mut current) = SIGNALS.lock() {
+        *current = Some(sender);
+    }
+
+    info!("Notification daemon {NAME} started");
+
+    while let Some(signal) = signals.recv().await {
+        match signal {
+            Signal::Closed(id, reason) => {
+                Server::notification_closed(&emitter, id, reason as u32).await?
+            }
+            Signal::ActionInvoked(id, key) => Server::action_invoked(&emitter, id, &key).await?,
+        }
+    }
+
+    Ok(())
+}
+
+pub fn subscription() -> Subscription<NotificationEvent> {
+    Subscription::run_with_id(
+        TypeId::of::<Server>(),
+        channel(10, async move |output| {
+            // the name is owned by the daemon already running, e.g. mako or dunst
+            if let Err(e) = serve(output).await {
+                warn!("Failed to start the notification daemon {NAME}: {e}");
+            }
+        }),
+    )
+}
@@ -1,4 +1,4 @@
-use super::{ReadOnlyService, ServiceEvent};
+use super::{Backoff, ReadOnlyService, ServiceEvent};
 use iced::{
     Subscription,
     futures::{
@@ -9,10 +9,12 @@ use iced::{
 use inotify::{EventMask, Inotify, WatchMask};
 use log::{debug, error, info, warn};
 use pipewire::{context::ContextBox, main_loop::MainLoopBox};
-use std::{any::TypeId, fs, ops::Deref, path::Path, thread};
+use std::{any::TypeId, fs, ops::Deref, path::Path, thread, time::Instant};
 use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+use zbus::{Connection, fdo::IntrospectableProxy, proxy};
 
 const WEBCAM_DEVICE_PATH: &str = "/dev/video0";
+const GEOCLUE_CLIENTS_PATH: &str = "/org/freedesktop/GeoClue2/Client";
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Media {
@@ -24,36 +26,64 @@ pub enum Media {
 pub struct ApplicationNode {
     pub id: u32,
     pub media: Media,
+    pub media_class: String,
+    pub name: String,
+    pub pid: Option<u32>,
+    pub since: Instant,
+}
+
+/// A screen or window shared through the screencast portal.
+///
+/// The portal backend publishes the shared content as a video source
+/// node, its description names the output or the window when the
+/// backend provides it.
+#[derive(Debug, Clone)]
+pub struct Screencast {
+    pub id: u32,
+    pub target: String,
+    pub since: Instant,
+}
+
+/// A process holding the webcam device open.
+#[derive(Debug, Clone)]
+pub struct DeviceUser {
+    pub pid: u32,
+    pub name: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct PrivacyData {
     nodes: Vec<ApplicationNode>,
-    webcam_access: i32,
+    screencasts: Vec<Screencast>,
+    webcam_users: Vec<DeviceUser>,
+    location_users: Vec<String>,
 }
 
 impl PrivacyData {
     fn new() -> Self {
         Self {
             nodes: Vec::new(),
-            webcam_access: is_device_in_use(WEBCAM_DEVICE_PATH),
+            screencasts: Vec::new(),
+            webcam_users: device_users(WEBCAM_DEVICE_PATH),
+            location_users: Vec::new(),
         }
     }
 
-    pub fn no_access(&self) -> bool {
-        self.nodes.is_empty() && self.webcam_access == 0
+    pub fn nodes(&self) -> &[ApplicationNode] {
+        &self.nodes
     }
 
-    pub fn microphone_access(&self) -> bool {
-        self.nodes.iter().any(|n| n.media == Media::Audio)
+    pub fn screencasts(&self) -> &[Screencast] {
+        &self.screencasts
     }
 
-    pub fn webcam_access(&self) -> bool {
-        self.webcam_access > 0
+    pub fn webcam_users(&self) -> &[DeviceUser] {
+        &self.webcam_users
     }
 
-    pub fn screenshare_access(&self) -> bool {
-        self.nodes.iter().any(|n| n.media == Media::Video)
+    /// Applications requesting the location through geoclue.
+    pub fn location_users(&self) -> &[String] {
+        &self.location_users
     }
 }
 
@@ -97,12 +127,39 @@ impl PrivacyService {
                 .global({
                     let tx = tx.clone();
                     move |global| {
+                        // video sources without a device behind them are the
+                        // streams published by the screencast portal backends
                         if let Some(props) = global.props
+                            && props.get("media.class") == Some("Video/Source")
+                            && props.get("device.api").is_none()
+                        {
+                            debug!("New screencast: {global:?}");
+                            let target =
+                                ["node.description", "media.name", "node.nick", "node.name"]
+                                    .into_iter()
+                                    .find_map(|key| props.get(key))
+                                    .unwrap_or("Screen")
+                                    .to_owned();
+                            let _ = tx.send(PrivacyEvent::AddScreencast(Screencast {
+                                id: global.id,
+                                target,
+                                since: Instant::now(),
+                            }));
+                        } else if let Some(props) = global.props
                             && let Some(media) = props.get("media.class").filter(|v| {
                                 v == &"Stream/Input/Video" || v == &"Stream/Input/Audio"
                             })
                         {
                             debug!("New global: {global:?}");
+                            let name = [
+                                "application.name",
+                                "application.process.binary",
+                                "node.name",
+                            ]
+                            .into_iter()
+                            .find_map(|key| props.get(key))
+                            .unwrap_or("Unknown")
+                            .to_owned();
                             let _ = tx.send(PrivacyEvent::AddNode(ApplicationNode {
                                 id: global.id,
                                 media: if media == "Stream/Input/Video" {
@@ -110,6 +167,12 @@ impl PrivacyService {
                                 } else {
                                     Media::Audio
                                 },
+                                media_class: media.to_owned(),
+                                name,
+                                pid: props
+                                    .get("application.process.id")
+                                    .and_then(|pid| pid.parse().ok()),
+                                since: Instant::now(),
                             }));
                         }
                     }
@@ -172,11 +235,45 @@ impl PrivacyService {
         ))
     }
 
-    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+    async fn location_listener()
+    -> anyhow::Result<Box<dyn Stream<Item = PrivacyEvent> + Unpin + Send>> {
+        let connection = Connection::system().await?;
+        let manager = GeoClueManagerProxy::new(&connection).await?;
+        let in_use = manager.receive_in_use_changed().await;
+
+        Ok(Box::new(
+            in_use
+                .then(move |change| {
+                    let connection = connection.clone();
+                    async move {
+                        let users = if change.get().await.unwrap_or_default() {
+                            location_users(&connection).await
+                        } else {
+                            Vec::new()
+                        };
+                        debug!("Location users: {users:?}");
+
+                        PrivacyEvent::Location(users)
+                    }
+                })
+                .boxed(),
+        ))
+    }
+
+    async fn start_listening(
+        state: State,
+        output: &mut Sender<ServiceEvent<Self>>,
+        backoff: &mut Backoff,
+    ) -> State {
         match state {
             State::Init => {
                 let pipewire = Self::create_pipewire_listener().await;
                 let webcam = Self::webcam_listener().await;
+                let location = Self::location_listener().await.unwrap_or_else(|e| {
+                    info!("Geoclue is not available: {e}");
+
+                    Box::new(pending::<PrivacyEvent>().boxed())
+                });
                 match (pipewire, webcam) {
                     (Ok(pipewire), Ok(webcam)) => {
                         let data = PrivacyData::new();
@@ -185,7 +282,7 @@ impl PrivacyService {
                             .send(ServiceEvent::Init(PrivacyService { data }))
                             .await;
 
-                        State::Active((pipewire, webcam))
+                        State::Active((pipewire, webcam, location))
                     }
                     (Err(pipewire_error), Ok(_)) => {
                         error!("Failed to connect to pipewire: {pipewire_error}");
@@ -195,7 +292,11 @@ impl PrivacyService {
                     (Ok(pipewire), Err(webcam_error)) => {
                         warn!("Failed to connect to webcam: {webcam_error}");
 
-                        State::Active((pipewire, Box::new(pending::<PrivacyEvent>().boxed())))
+                        State::Active((
+                            pipewire,
+                            Box::new(pending::<PrivacyEvent>().boxed()),
+                            location,
+                        ))
                     }
                     (Err(pipewire_error), Err(webcam_error)) => {
                         error!("Failed to connect to pipewire: {pipewire_error}");
@@ -205,7 +306,7 @@ impl PrivacyService {
                     }
                 }
             }
-            State::Active((mut pipewire, mut webcam)) => {
+            State::Active((mut pipewire, mut webcam, mut location)) => {
                 info!("Listening for privacy events");
 
                 select! {
@@ -216,6 +317,8 @@ impl PrivacyService {
                             }
                             None => {
                                 error!("Pipewire listener exited");
+
+                                return State::Error;
                             }
                         }
                     },
@@ -226,18 +329,31 @@ impl PrivacyService {
                             }
                             None => {
                                 error!("Webcam listener exited");
+
+                                return State::Error;
+                            }
+                        }
+                    },
+                    value = location.next().fuse() => {
+                        match value {
+                            Some(event) => {
+                                let _ = output.send(ServiceEvent::Update(event)).await;
+                            }
+                            None => {
+                                error!("Geoclue listener exited");
+
+                                return State::Error;
                             }
                         }
                     }
                 };
 
-                State::Active((pipewire, webcam))
+                State::Active((pipewire, webcam, location))
             }
             State::Error => {
-                error!("Privacy service error");
+                backoff.failed(output, ()).await;
 
-                let _ = pending::<u8>().next().await;
-                State::Error
+                State::Init
             }
         }
     }
@@ -249,6 +365,7 @@ enum State {
         (
             UnboundedReceiver<PrivacyEvent>,
             Box<dyn Stream<Item = PrivacyEvent> + Unpin + Send>,
+            Box<dyn Stream<Item = PrivacyEvent> + Unpin + Send>,
         ),
     ),
     Error,
@@ -257,9 +374,11 @@ enum State {
 #[derive(Debug, Clone)]
 pub enum PrivacyEvent {
     AddNode(ApplicationNode),
+    AddScreencast(Screencast),
     RemoveNode(u32),
     WebcamOpen,
     WebcamClose,
+    Location(Vec<String>),
 }
 
 impl ReadOnlyService for PrivacyService {
@@ -271,16 +390,25 @@ impl ReadOnlyService for PrivacyService {
             PrivacyEvent::AddNode(node) => {
                 self.data.nodes.push(node);
             }
+            PrivacyEvent::AddScreencast(screencast) => {
+                self.data.screencasts.push(screencast);
+            }
             PrivacyEvent::RemoveNode(id) => {
                 self.data.nodes.retain(|n| n.id != id);
+                self.data.screencasts.retain(|s| s.id != id);
             }
+            // a process can hold the device more than once, so the users
+            // are read again rather than counting the events
             PrivacyEvent::WebcamOpen => {
-                self.data.webcam_access += 1;
-                debug!("Webcam opened {}", self.data.webcam_access);
+                self.data.webcam_users = device_users(WEBCAM_DEVICE_PATH);
+                debug!("Webcam opened {}", self.data.webcam_users.len());
+            }
+            PrivacyEvent::Location(users) => {
+                self.data.location_users = users;
             }
             PrivacyEvent::WebcamClose => {
-                self.data.webcam_access = i32::max(self.data.webcam_access - 1, 0);
-                debug!("Webcam closed {}", self.data.webcam_access);
+                self.data.webcam_users = device_users(WEBCAM_DEVICE_PATH);
+                debug!("Webcam closed {}", self.data.webcam_users.len());
             }
         }
     }
@@ -292,25 +420,71 @@ impl ReadOnlyService for PrivacyService {
             id,
             channel(100, async |mut output| {
                 let mut state = State::Init;
+                let mut backoff = Backoff::default();
 
                 loop {
-                    state = PrivacyService::start_listening(state, &mut output).await;
+                    state = PrivacyService::start_listening(state, &mut output, &mut backoff).await;
                 }
             }),
         )
     }
 }
 
-fn is_device_in_use(target: &str) -> i32 {
-    let mut used_by = 0;
+/// Desktop ids of the active geoclue clients, an unknown application is
+/// reported when geoclue doesn't let us read them.
+async fn location_users(connection: &Connection) -> Vec<String> {
+    let clients = match IntrospectableProxy::new(
+        connection,
+        "org.freedesktop.GeoClue2",
+        GEOCLUE_CLIENTS_PATH,
+    )
+    .await
+    {
+        Ok(proxy) => proxy.introspect().await.unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+
+    let mut users = Vec::new();
+    for client in clients.split("<node name=\"").skip(1) {
+        let Some(client) = client.split('"').next() else {
+            continue;
+        };
+        let Ok(proxy) =
+            GeoClueClientProxy::new(connection, format!("{GEOCLUE_CLIENTS_PATH}/{client}")).await
+        else {
+            continue;
+        };
+
+        if proxy.active().await.unwrap_or_default()
+            && let Ok(desktop_id) = proxy.desktop_id().await
+            && !desktop_id.is_empty()
+            && !users.contains(&desktop_id)
+        {
+            users.push(desktop_id);
+        }
+    }
+
+    if users.is_empty() {
+        users.push("Unknown application".to_owned());
+    }
+
+    users
+}
+
+fn device_users(target: &str) -> Vec<DeviceUser> {
+    let mut used_by = Vec::new();
     if let Ok(entries) = fs::read_dir("/proc") {
         for entry in entries.flatten() {
             let pid_path = entry.path();
 
             // Skip non-numeric directories (not process folders)
-            if !pid_path.join("fd").exists() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u32>().ok())
+            else {
                 continue;
-            }
+            };
 
             // Check file descriptors in each process folder
             if let Ok(fd_entries) = fs::read_dir(pid_path.join("fd")) {
@@ -318,7 +492,11 @@ fn is_device_in_use(target: &str) -> i32 {
                     if let Ok(link_path) = fs::read_link(fd_entry.path())
                         && link_path == Path::new(target)
                     {
-                        used_by += 1;
+                        let name = fs::read_to_string(pid_path.join("comm"))
+                            .map(|comm| comm.trim().to_owned())
+                            .unwrap_or_else(|_| pid.to_string());
+                        used_by.push(DeviceUser { pid, name });
+                        break;
                     }
                 }
             }
@@ -327,3 +505,25 @@ fn is_device_in_use(target: &str) -> i32 {
 
     used_by
 }
+
+#[proxy(
+    interface = "org.freedesktop.GeoClue2.Manager",
+    default_service = "org.freedesktop.GeoClue2",
+    default_path = "/org/freedesktop/GeoClue2/Manager"
+)]
+trait GeoClueManager {
+    #[zbus(property)]
+    fn in_use(&self) -> zbus::Result<bool>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.GeoClue2.Client",
+    default_service = "org.freedesktop.GeoClue2"
+)]
+trait GeoClueClient {
+    #[zbus(property)]
+    fn active(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn desktop_id(&self) -> zbus::Result<String>;
+}
@@ -0,0 +1,287 @@
+use super::{
+    ReadOnlyService, ServiceEvent, audio::AudioService, bluetooth::BluetoothService,
+    brightness::BrightnessService, compositor::CompositorService, mpris::MprisPlayerService,
+    network::NetworkService, privacy::PrivacyService, upower::UPowerService,
+};
+use iced::Subscription;
+use std::sync::{Arc, Mutex, Weak};
+use sysinfo::System;
+
+/// The `sysinfo` state shared by the parts reading the processes and the
+/// usage of the machine, each of them refreshes what it needs.
+pub type SharedSystem = Arc<Mutex<System>>;
+
+/// Backend shared by several parts of the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Compositor,
+    Privacy,
+    Audio,
+    Brightness,
+    UPower,
+    /// NetworkManager or iwd
+    Network,
+    /// BlueZ
+    Bluetooth,
+    Mpris,
+}
+
+/// A part of the bar receiving the events of a backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Consumer {
+    Workspaces,
+    WindowTitle,
+    Taskbar,
+    KeyboardLayout,
+    KeyboardSubmap,
+    WindowSwitcher,
+    /// Bars hidden by the fullscreen windows
+    HideOnFullscreen,
+    /// Do not disturb mode of the notifications while a window is fullscreen
+    DndFullscreen,
+    /// Overlay of the bindings of the active submap
+    Keybinds,
+    Privacy,
+    Recording,
+    /// Do not disturb mode of the notifications while the screen is shared
+    DndScreenSharing,
+    SettingsAudio,
+    SettingsBrightness,
+    SettingsPower,
+    Peripherals,
+    OsdVolume,
+    OsdBrightness,
+    SettingsNetwork,
+    SettingsBluetooth,
+    MediaPlayer,
+}
+
+impl Consumer {
+    pub fn backend(self) -> Backend {
+        match self {
+            Consumer::Workspaces
+            | Consumer::WindowTitle
+            | Consumer::Taskbar
+            | Consumer::KeyboardLayout
+            | Consumer::KeyboardSubmap
+            | Consumer::WindowSwitcher
+            | Consumer::HideOnFullscreen
+            | Consumer::DndFullscreen
+            | Consumer::Keybinds => Backend::Compositor,
+            Consumer::Privacy | Consumer::Recording | Consumer::DndScreenSharing => {
+                Backend::Privacy
+            }
+            Consumer::SettingsAudio | Consumer::OsdVolume => Backend::Audio,
+            Consumer::SettingsBrightness | Consumer::OsdBrightness => Backend::Brightness,
+            Consumer::SettingsPower | Consumer::Peripherals => Backend::UPower,
+            Consumer::SettingsNetwork => Backend::Network,
+            Consumer::SettingsBluetooth => Backend::Bluetooth,
+            Consumer::MediaPlayer => Backend::Mpris,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ServiceMessage {
+    Compositor(ServiceEvent<CompositorService>),
+    Privacy(ServiceEvent<PrivacyService>),
+    Audio(ServiceEvent<AudioService>),
+    Brightness(ServiceEvent<BrightnessService>),
+    UPower(ServiceEvent<UPowerService>),
+    Network(ServiceEvent<NetworkService>),
+    Bluetooth(ServiceEvent<BluetoothService>),
+    Mpris(ServiceEvent<MprisPlayerService>),
+}
+
+impl ServiceMessage {
+    pub fn backend(&self) -> Backend {
+        match self {
+            ServiceMessage::Compositor(_) => Backend::Compositor,
+            ServiceMessage::Privacy(_) => Backend::Privacy,
+            ServiceMessage::Audio(_) => Backend::Audio,
+            ServiceMessage::Brightness(_) => Backend::Brightness,
+            ServiceMessage::UPower(_) => Backend::UPower,
+            ServiceMessage::Network(_) => Backend::Network,
+            ServiceMessage::Bluetooth(_) => Backend::Bluetooth,
+            ServiceMessage::Mpris(_) => Backend::Mpris,
+        }
+    }
+}
+
+fn apply<S>(cache: &mut Option<S>, event: &ServiceEvent<S>)
+where
+    S: ReadOnlyService + Clone,
+    S::UpdateEvent: Clone,
+{
+    match event {
+        ServiceEvent::Init(service) => *cache = Some(service.clone()),
+        ServiceEvent::Update(event) => {
+            if let Some(service) = cache.as_mut() {
+                service.update(event.clone());
+            }
+        }
+        ServiceEvent::Error(_) => *cache = None,
+    }
+}
+
+/// The shared backends run once, while at least one consumer needs them,
+/// and their events are handed to every consumer.
+///
+/// The state of each running backend is kept, so that the consumers added
+/// by a config reload start from it instead of waiting for a new backend.
+#[derive(Debug, Default)]
+pub struct Services {
+    compositor: Option<CompositorService>,
+    privacy: Option<PrivacyService>,
+    audio: Option<AudioService>,
+    brightness: Option<BrightnessService>,
+    upower: Option<UPowerService>,
+    network: Option<NetworkService>,
+    bluetooth: Option<BluetoothService>,
+    mpris: Option<MprisPlayerService>,
+    // held by its users rather than by a subscription
+    system: Weak<Mutex<System>>,
+}
+
+impl Services {
+    pub fn update(&mut self, message: &ServiceMessage) {
+        match message {
+            ServiceMessage::Compositor(event) => apply(&mut self.compositor, event),
+            ServiceMessage::Privacy(event) => apply(&mut self.privacy, event),
+            ServiceMessage::Audio(event) => apply(&mut self.audio, event),
+            ServiceMessage::Brightness(event) => apply(&mut self.brightness, event),
+            ServiceMessage::UPower(event) => apply(&mut self.upower, event),
+            ServiceMessage::Network(event) => apply(&mut self.network, event),
+            ServiceMessage::Bluetooth(event) => apply(&mut self.bluetooth, event),
+            ServiceMessage::Mpris(event) => apply(&mut self.mpris, event),
+        }
+    }
+
+    /// The shared `sysinfo` state, created again once its last user is gone.
+    pub fn system(&mut self) -> SharedSystem {
+        self.system.upgrade().unwrap_or_else(|| {
+            let system = SharedSystem::default();
+            self.system = Arc::downgrade(&system);
+
+            system
+        })
+    }
+
+    /// The current state of the backend, for a new consumer.
+    pub fn init(&self, backend: Backend) -> Option<ServiceMessage> {
+        match backend {
+            Backend::Compositor => self
+                .compositor
+                .clone()
+                .map(|service| ServiceMessage::Compositor(ServiceEvent::Init(service))),
+            Backend::Privacy => self
+                .privacy
+                .clone()
+                .map(|service| ServiceMessage::Privacy(ServiceEvent::Init(service))),
+            Backend::Audio => self
+                .audio
+                .clone()
+                .map(|service| ServiceMessage::Audio(ServiceEvent::Init(service))),
+            Backend::Brightness => self
+                .brightness
+                .clone()
+                .map(|service| ServiceMessage::Brightness(ServiceEvent::Init(service))),
+            Backend::UPower => self
+                .upower
+                .clone()
+                .map(|service| ServiceMessage::UPower(ServiceEvent::Init(service))),
+            Backend::Network => self
+                .network
+                .clone()
+                .map(|service| ServiceMessage::Network(ServiceEvent::Init(service))),
+            Backend::Bluetooth => self
+                .bluetooth
+                .clone()
+                .map(|service| ServiceMessage::Bluetooth(ServiceEvent::Init(service))),
+            Backend::Mpris => self
+                .mpris
+                .clone()
+                .map(|service| ServiceMessage::Mpris(ServiceEvent::Init(service))),
+        }
+    }
+
+    /// Forgets the state of the backends stopped for lack of consumers.
+    pub fn retain(&mut self, consumers: &[Consumer]) {
+        let needed = |backend| {
+            consumers
+                .iter()
+                .any(|consumer| consumer.backend() == backend)
+        };
+
+        if !needed(Backend::Compositor) {
+            self.compositor = None;
+        }
+        if !needed(Backend::Privacy) {
+            self.privacy = None;
+        }
+        if !needed(Backend::Audio) {
+            self.audio = None;
+        }
+        if !needed(Backend::Brightness) {
+            self.brightness = None;
+        }
+        if !needed(Backend::UPower) {
+            self.upower = None;
+        }
+        if !needed(Backend::Network) {
+            self.network = None;
+        }
+        if !needed(Backend::Bluetooth) {
+            self.bluetooth = None;
+        }
+        if !needed(Backend::Mpris) {
+            self.mpris = None;
+        }
+    }
+
+    /// Runs the backends needed by the consumers, `guard` wraps each of them.
+    pub fn subscription(
+        consumers: &[Consumer],
+        guard: impl Fn(Backend, Subscription<ServiceMessage>) -> Subscription<ServiceMessage>,
+    ) -> Subscription<ServiceMessage> {
+        let needed = |backend| {
+            consumers
+                .iter()
+                .any(|consumer| consumer.backend() == backend)
+        };
+        let run = |backend: Backend, subscribe: fn() -> Subscription<ServiceMessage>| {
+            if needed(backend) {
+                guard(backend, subscribe())
+            } else {
+                Subscription::none()
+            }
+        };
+
+        Subscription::batch([
+            run(Backend::Compositor, || {
+                CompositorService::subscribe().map(ServiceMessage::Compositor)
+            }),
+            run(Backend::Privacy, || {
+                PrivacyService::subscribe().map(ServiceMessage::Privacy)
+            }),
+            run(Backend::Audio, || {
+                AudioService::subscribe().map(ServiceMessage::Audio)
+            }),
+            run(Backend::Brightness, || {
+                BrightnessService::subscribe().map(ServiceMessage::Brightness)
+            }),
+            run(Backend::UPower, || {
+                UPowerService::subscribe().map(ServiceMessage::UPower)
+            }),
+            run(Backend::Network, || {
+                NetworkService::subscribe().map(ServiceMessage::Network)
+            }),
+            run(Backend::Bluetooth, || {
+                BluetoothService::subscribe().map(ServiceMessage::Bluetooth)
+            }),
+            run(Backend::Mpris, || {
+                MprisPlayerService::subscribe().map(ServiceMessage::Mpris)
+            }),
+        ])
+    }
+}
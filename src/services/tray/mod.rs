@@ -1,4 +1,5 @@
-use super::{ReadOnlyService, Service, ServiceEvent};
+use super::{Backoff, ReadOnlyService, Service, ServiceEvent};
+use crate::{config::CacheConfig, utils::lru::Lru};
 use dbus::{
     DBusMenuProxy, Layout, StatusNotifierItemProxy, StatusNotifierWatcher,
     StatusNotifierWatcherProxy,
@@ -7,10 +8,8 @@ use freedesktop_icons::lookup;
 use iced::{
     Subscription, Task,
     futures::{
-        SinkExt, Stream, StreamExt,
-        channel::mpsc::Sender,
-        stream::{pending, select_all},
-        stream_select,
+        SinkExt, Stream, StreamExt, channel::mpsc::Sender, future::try_join_all,
+        stream::select_all, stream_select,
     },
     stream::channel,
     widget::{image, svg},
@@ -22,8 +21,10 @@ use std::{
     any::TypeId,
     collections::BTreeSet,
     env, fs,
+    hash::{DefaultHasher, Hash, Hasher},
     ops::Deref,
     path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
 };
 
 pub mod dbus;
@@ -36,7 +37,74 @@ static SYSTEM_ICON_ENTRIES: Lazy<Vec<(String, String)>> = Lazy::new(|| {
         .collect()
 });
 
-fn get_icon_from_name(icon_name: &str) -> Option<TrayIcon> {
+/// Indexes the icons of the system in the background, the index is built on
+/// the first lookup of an icon otherwise and holds up the tray.
+pub fn preload_icons() {
+    std::thread::spawn(|| Lazy::force(&SYSTEM_ICON_ENTRIES));
+}
+
+/// Icons found by name, including the missing ones, the lookups go through
+/// the icon themes on disk.
+static ICON_CACHE: LazyLock<Mutex<Lru<String, Option<TrayIcon>>>> =
+    LazyLock::new(|| Mutex::new(Lru::new(CacheConfig::default().icons)));
+/// Pictures sent as raw data, by the hash of the data: a picture sent again
+/// reuses the handle, and so the texture, of the first one.
+static IMAGE_CACHE: LazyLock<Mutex<Lru<u64, image::Handle>>> =
+    LazyLock::new(|| Mutex::new(Lru::new(CacheConfig::default().images)));
+
+pub fn set_cache_limits(config: &CacheConfig) {
+    if let Ok(mut icons) = ICON_CACHE.lock() {
+        icons.set_capacity(config.icons);
+    }
+    if let Ok(mut images) = IMAGE_CACHE.lock() {
+        images.set_capacity(config.images);
+    }
+}
+
+fn image_key(data: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+fn cached_image(key: u64, handle: impl FnOnce() -> image::Handle) -> TrayIcon {
+    TrayIcon::Image(match IMAGE_CACHE.lock() {
+        Ok(mut images) => images.get_or_insert_with(key, handle),
+        Err(_) => handle(),
+    })
+}
+
+/// An encoded picture, PNG, JPEG and the other formats of the `image` crate.
+pub fn image_from_bytes(bytes: Vec<u8>) -> TrayIcon {
+    cached_image(image_key(&bytes), || image::Handle::from_bytes(bytes))
+}
+
+pub fn image_from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> TrayIcon {
+    cached_image(image_key((width, height, &pixels)), || {
+        image::Handle::from_rgba(width, height, pixels)
+    })
+}
+
+pub fn get_icon_from_name(icon_name: &str) -> Option<TrayIcon> {
+    let cached = ICON_CACHE
+        .lock()
+        .ok()
+        .and_then(|mut icons| icons.get(&icon_name.to_owned()));
+    if let Some(icon) = cached {
+        return icon;
+    }
+
+    // the lookup is slow, the cache isn't locked meanwhile
+    let icon = find_icon(icon_name);
+    if let Ok(mut icons) = ICON_CACHE.lock() {
+        icons.insert(icon_name.to_owned(), icon.clone());
+    }
+
+    icon
+}
+
+fn find_icon(icon_name: &str) -> Option<TrayIcon> {
     if let Some(path) = find_icon_path(icon_name) {
         return tray_icon_from_path(path);
     }
@@ -58,7 +126,7 @@ fn get_icon_from_name(icon_name: &str) -> Option<TrayIcon> {
     None
 }
 
-fn tray_icon_from_path(path: PathBuf) -> Option<TrayIcon> {
+pub fn tray_icon_from_path(path: PathBuf) -> Option<TrayIcon> {
     if path.extension().is_some_and(|ext| ext == "svg") {
         debug!("svg icon found. Path: {path:?}");
 
@@ -266,11 +334,7 @@ impl StatusNotifierItem {
                         for pixel in i.bytes.chunks_exact_mut(4) {
                             pixel.rotate_left(1);
                         }
-                        TrayIcon::Image(image::Handle::from_rgba(
-                            i.width as u32,
-                            i.height as u32,
-                            i.bytes,
-                        ))
+                        image_from_rgba(i.width as u32, i.height as u32, i.bytes)
                     })
             }
             Err(_) => item_proxy
@@ -337,11 +401,13 @@ impl TrayService {
 
         let items = proxy.registered_status_notifier_items().await?;
 
-        let mut status_items = Vec::with_capacity(items.len());
-        for item in items {
-            let item = StatusNotifierItem::new(conn, item).await?;
-            status_items.push(item);
-        }
+        // the items answer at their own pace, a slow one doesn't hold the others
+        let status_items = try_join_all(
+            items
+                .into_iter()
+                .map(|item| StatusNotifierItem::new(conn, item)),
+        )
+        .await?;
 
         Ok(TrayData(status_items))
     }
@@ -416,11 +482,11 @@ impl TrayService {
                                             }
                                             TrayEvent::IconChanged(
                                                 name.to_owned(),
-                                                TrayIcon::Image(image::Handle::from_rgba(
+                                                image_from_rgba(
                                                     i.width as u32,
                                                     i.height as u32,
                                                     i.bytes,
-                                                )),
+                                                ),
                                             )
                                         })
                                 })
@@ -488,7 +554,11 @@ impl TrayService {
         .boxed())
     }
 
-    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+    async fn start_listening(
+        state: State,
+        output: &mut Sender<ServiceEvent<Self>>,
+        backoff: &mut Backoff,
+    ) -> State {
         match state {
             State::Init => match StatusNotifierWatcher::start_server().await {
                 Ok(conn) => {
@@ -546,10 +616,9 @@ impl TrayService {
                 }
             }
             State::Error => {
-                error!("Tray service error");
+                backoff.failed(output, ()).await;
 
-                let _ = pending::<u8>().next().await;
-                State::Error
+                State::Init
             }
         }
     }
@@ -620,9 +689,10 @@ impl ReadOnlyService for TrayService {
             id,
             channel(100, async |mut output| {
                 let mut state = State::Init;
+                let mut backoff = Backoff::default();
 
                 loop {
-                    state = TrayService::start_listening(state, &mut output).await;
+                    state = TrayService::start_listening(state, &mut output, &mut backoff).await;
                 }
             }),
         )
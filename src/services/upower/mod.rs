@@ -1,4 +1,4 @@
-use super::{ReadOnlyService, Service, ServiceEvent};
+use super::{Backoff, ReadOnlyService, Service, ServiceEvent, restarted};
 use crate::{
     components::icons::StaticIcon, services::throttle::ThrottleExt, utils::IndicatorState,
 };
@@ -8,18 +8,24 @@ use iced::{
     futures::{
         SinkExt, Stream, StreamExt,
         channel::mpsc::Sender,
+        future::select,
         stream::{once, pending, select_all},
         stream_select,
     },
     stream::channel,
 };
 use log::{error, warn};
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::{any::TypeId, fmt, time::Duration};
 use zbus::zvariant::ObjectPath;
 
 mod dbus;
 
+const UPOWER_SERVICE: &str = "org.freedesktop.UPower";
+/// Name owned by power-profiles-daemon in all its versions
+const POWER_PROFILES_SERVICE: &str = "net.hadess.PowerProfiles";
+
 const KEYBOARD_BATTERY_ICONS: [StaticIcon; 5] = [
     StaticIcon::KeyboardBatteryCharging,
     StaticIcon::KeyboardBatteryFull,
@@ -152,7 +158,7 @@ impl Peripheral {
     }
 }
 
-#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum PeripheralDeviceKind {
     Keyboard,
     Mouse,
@@ -284,9 +290,10 @@ impl ReadOnlyService for UPowerService {
             id,
             channel(100, async |mut output| {
                 let mut state = State::Init;
+                let mut backoff = Backoff::default();
 
                 loop {
-                    state = UPowerService::start_listening(state, &mut output).await;
+                    state = UPowerService::start_listening(state, &mut output, &mut backoff).await;
                 }
             }),
         )
@@ -617,7 +624,11 @@ impl UPowerService {
         ))
     }
 
-    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+    async fn start_listening(
+        state: State,
+        output: &mut Sender<ServiceEvent<Self>>,
+        backoff: &mut Backoff,
+    ) -> State {
         match state {
             State::Init => match zbus::Connection::system().await {
                 Ok(conn) => match UPowerService::initialize_data(&conn).await {
@@ -650,12 +661,18 @@ impl UPowerService {
             },
             State::Active(conn, system_battery_paths, peripheral_paths) => {
                 match UPowerService::events(&conn, &system_battery_paths, &peripheral_paths).await {
-                    Ok(mut events) => {
+                    Ok(events) => {
+                        let mut events = events.take_until(select(
+                            Box::pin(restarted(conn.clone(), UPOWER_SERVICE)),
+                            Box::pin(restarted(conn.clone(), POWER_PROFILES_SERVICE)),
+                        ));
+
                         while let Some(event) = events.next().await {
                             let _ = output.send(ServiceEvent::Update(event)).await;
                         }
 
-                        State::Active(conn, system_battery_paths, peripheral_paths)
+                        // the connection was lost, UPower or the profiles daemon restarted
+                        State::Error
                     }
                     Err(err) => {
                         error!("Failed to listen for upower events: {err}");
@@ -665,9 +682,9 @@ impl UPowerService {
                 }
             }
             State::Error => {
-                let _ = pending::<u8>().next().await;
+                backoff.failed(output, ()).await;
 
-                State::Error
+                State::Init
             }
         }
     }
@@ -0,0 +1,42 @@
+use crate::app::Message;
+use iced::{Subscription, futures::SinkExt, stream::channel};
+use log::{error, info};
+use std::any::TypeId;
+use tokio::signal::unix::{SignalKind, signal};
+
+struct Signals;
+
+/// Reloads the config on `SIGUSR1` and toggles the bars visibility on `SIGUSR2`.
+pub fn subscription() -> Subscription<Message> {
+    Subscription::run_with_id(
+        TypeId::of::<Signals>(),
+        channel(10, async move |mut output| {
+            let (mut reload, mut toggle) = match (
+                signal(SignalKind::user_defined1()),
+                signal(SignalKind::user_defined2()),
+            ) {
+                (Ok(reload), Ok(toggle)) => (reload, toggle),
+                (Err(e), _) | (_, Err(e)) => {
+                    error!("Failed to register the signal handlers: {e}");
+                    return;
+                }
+            };
+
+            loop {
+                let message = tokio::select! {
+                    Some(()) = reload.recv() => {
+                        info!("SIGUSR1 received, reloading the config");
+                        Message::ReloadConfig
+                    }
+                    Some(()) = toggle.recv() => {
+                        info!("SIGUSR2 received, toggling the bars");
+                        Message::ToggleVisibility
+                    }
+                    else => break,
+                };
+
+                let _ = output.send(message).await;
+            }
+        }),
+    )
+}
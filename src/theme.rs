@@ -1,12 +1,61 @@
-use crate::config::{Appearance, AppearanceColor, AppearanceStyle, MenuAppearance, Position};
+use crate::{
+    animation::Animations,
+    color_scheme::ColorScheme,
+    config::{
+        Appearance, AppearanceColor, AppearanceStyle, FontWeight, GradientConfig, GradientStop,
+        MAX_GRADIENT_STOPS, MenuAppearance, Position, TooltipAppearance,
+    },
+};
 use iced::{
-    Background, Border, Color, Theme,
+    Background, Border, Color, Font, Gradient, Radians, Theme, font,
+    gradient::Linear,
     theme::{Palette, palette},
     widget::{
+        Text,
         button::{self, Status},
         text_input::{self},
     },
 };
+use std::{collections::HashMap, f32::consts::PI, sync::Mutex};
+
+/// Returns a `'static` copy of the font name, iced only accepts static font families.
+///
+/// Names are interned so reloading the config doesn't leak a new copy every time.
+pub fn font_name(name: &str) -> &'static str {
+    static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    match names.iter().find(|interned| **interned == name) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+            names.push(interned);
+            interned
+        }
+    }
+}
+
+impl From<FontWeight> for font::Weight {
+    fn from(weight: FontWeight) -> Self {
+        match weight {
+            FontWeight::Thin => font::Weight::Thin,
+            FontWeight::ExtraLight => font::Weight::ExtraLight,
+            FontWeight::Light => font::Weight::Light,
+            FontWeight::Normal => font::Weight::Normal,
+            FontWeight::Medium => font::Weight::Medium,
+            FontWeight::Semibold => font::Weight::Semibold,
+            FontWeight::Bold => font::Weight::Bold,
+            FontWeight::ExtraBold => font::Weight::ExtraBold,
+            FontWeight::Black => font::Weight::Black,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ModuleFont {
+    pub font: Font,
+    pub size: Option<u16>,
+}
 
 #[allow(unused)]
 #[derive(Debug, Copy, Clone)]
@@ -90,13 +139,26 @@ pub struct AshellTheme {
     pub bar_style: AppearanceStyle,
     pub opacity: f32,
     pub menu: MenuAppearance,
+    pub tooltip: TooltipAppearance,
+    pub gradient: GradientConfig,
     pub workspace_colors: Vec<AppearanceColor>,
     pub special_workspace_colors: Option<Vec<AppearanceColor>>,
     pub scale_factor: f64,
+    pub module_fonts: HashMap<String, ModuleFont>,
+    pub animations: Animations,
 }
 
 impl AshellTheme {
-    pub fn new(position: Position, appearance: &Appearance) -> Self {
+    pub fn new(
+        position: Position,
+        appearance: &Appearance,
+        color_scheme: Option<&ColorScheme>,
+    ) -> Self {
+        let appearance = &color_scheme.map_or_else(
+            || appearance.clone(),
+            |color_scheme| color_scheme.apply(appearance),
+        );
+
         AshellTheme {
             space: Space::default(),
             radius: Radius::default(),
@@ -104,10 +166,33 @@ impl AshellTheme {
             bar_position: position,
             bar_style: appearance.style,
             opacity: appearance.opacity,
-            menu: appearance.menu,
+            menu: appearance.menu.clone(),
+            tooltip: appearance.tooltip,
+            gradient: appearance.gradient.clone(),
             workspace_colors: appearance.workspace_colors.clone(),
             special_workspace_colors: appearance.special_workspace_colors.clone(),
             scale_factor: appearance.scale_factor,
+            animations: Animations::new(&appearance.animations),
+            module_fonts: appearance
+                .module_fonts
+                .iter()
+                .map(|(module, config)| {
+                    let family = config.family.as_deref().or(appearance.font_name.as_deref());
+
+                    (
+                        module.clone(),
+                        ModuleFont {
+                            font: Font {
+                                weight: config.weight.into(),
+                                ..family.map_or(Font::DEFAULT, |family| {
+                                    Font::with_name(font_name(family))
+                                })
+                            },
+                            size: config.size,
+                        },
+                    )
+                })
+                .collect(),
             iced_theme: Theme::custom_with_fn(
                 "local".to_string(),
                 Palette {
@@ -216,6 +301,64 @@ impl AshellTheme {
         &self.iced_theme
     }
 
+    /// Linear gradient of the `Gradient` style, darkened by `backdrop` while a menu is open.
+    pub fn gradient(
+        &self,
+        position: Position,
+        angle: Option<f32>,
+        stops: &[GradientStop],
+        background: Color,
+        backdrop: Option<f32>,
+    ) -> Gradient {
+        // by default the gradient fades away from the screen edge
+        let angle = angle.map_or(
+            match position {
+                Position::Top => PI,
+                Position::Bottom => 0.0,
+                Position::Left => PI / 2.,
+                Position::Right => PI * 1.5,
+            },
+            f32::to_radians,
+        );
+
+        let linear = stops.iter().take(MAX_GRADIENT_STOPS).fold(
+            Linear::new(Radians(angle)),
+            |linear, stop| {
+                let color = stop
+                    .color
+                    .map_or(background, |color| {
+                        Color::from_rgb8(color.r, color.g, color.b)
+                    })
+                    .scale_alpha(stop.opacity * self.opacity);
+
+                linear.add_stop(
+                    stop.offset.clamp(0.0, 1.0),
+                    match backdrop {
+                        Some(backdrop) => darken_color(color, backdrop),
+                        None => color,
+                    },
+                )
+            },
+        );
+
+        Gradient::Linear(linear)
+    }
+
+    /// Applies the font override configured for the module, if any.
+    pub fn module_text<'a>(&self, module: &str, text: Text<'a>) -> Text<'a> {
+        match self.module_fonts.get(module) {
+            Some(module_font) => {
+                let text = text.font(module_font.font);
+
+                match module_font.size {
+                    Some(size) => text.size(size),
+                    None => text,
+                }
+            }
+            None => text,
+        }
+    }
+
     pub fn ghost_button_style(&self) -> impl Fn(&Theme, Status) -> button::Style {
         move |theme, status| {
             let mut base = button::Style {
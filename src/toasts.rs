@@ -0,0 +1,956 @@
+use crate::{
+    components::{
+        icons::{StaticIcon, icon},
+        level_bar::level_bar,
+    },
+    config::{NotificationPosition, NotificationsConfig},
+    services::{
+        ReadOnlyService, ServiceEvent,
+        compositor::CompositorService,
+        notifications::{self, CloseReason, Expiration, Notification, NotificationEvent, Urgency},
+        privacy::{Media, PrivacyService},
+        registry::Consumer,
+        tray::TrayIcon,
+    },
+    theme::AshellTheme,
+    utils::state::{read_state, write_state},
+};
+use chrono::{DateTime, Local, NaiveTime};
+use iced::{
+    Alignment, Border, Element, Length, Subscription, Task, Theme,
+    platform_specific::shell::commands::layer_surface::{
+        Anchor, KeyboardInteractivity, Layer, destroy_layer_surface, get_layer_surface, set_size,
+    },
+    runtime::platform_specific::wayland::layer_surface::{
+        IcedMargin, IcedOutput, SctkLayerSurfaceSettings,
+    },
+    time::every,
+    widget::{
+        Column, Image, Row, Space, Svg, button, column, container, horizontal_rule, mouse_area,
+        row, text, text_input,
+    },
+    window::Id,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::process::Command;
+use wayland_client::protocol::wl_output::WlOutput;
+
+const WIDTH: u32 = 360;
+const TOAST_HEIGHT: u32 = 84;
+/// Extra height of the toasts with action buttons
+const ACTIONS_HEIGHT: u32 = 36;
+/// Extra height of the toasts with a progress bar
+const PROGRESS_HEIGHT: u32 = 12;
+const SPACING: u32 = 8;
+/// Distance from the edges of the output
+const MARGIN: i32 = 8;
+const TICK: Duration = Duration::from_millis(250);
+const HISTORY_STATE_FILE: &str = "notifications.json";
+/// Ids of the toasts sent over IPC, above the ones given by the daemon
+const SCRIPT_IDS: u32 = 1 << 31;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(NotificationEvent),
+    /// Toast sent by a script over IPC, the first line of the text is the title
+    Toast {
+        text: String,
+        icon: Option<String>,
+        timeout: Option<u64>,
+    },
+    Tick(Instant),
+    Dismiss(u32),
+    Action(u32, String),
+    ToggleGroup(String),
+    ClearGroup(String),
+    /// Clears the notifications matching the search
+    ClearHistory,
+    Delete(u64),
+    SearchChanged(String),
+    ToggleDoNotDisturb,
+    Compositor(ServiceEvent<CompositorService>),
+    Privacy(ServiceEvent<PrivacyService>),
+    ConfigReloaded(NotificationsConfig),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoNotDisturb {
+    Off,
+    /// Turned on by the user
+    Manual,
+    /// Turned on by the schedule, a fullscreen window or the screen sharing
+    Automatic,
+}
+
+struct Toast {
+    notification: Notification,
+    expires_at: Option<Instant>,
+    /// Older notifications of the same application, hidden under this one
+    stacked: Vec<u32>,
+}
+
+impl Toast {
+    /// Actions shown as buttons, `default` is invoked by clicking the toast.
+    fn buttons(&self) -> impl Iterator<Item = &(String, String)> {
+        self.notification
+            .actions
+            .iter()
+            .filter(|(key, _)| key != "default")
+    }
+
+    fn height(&self) -> u32 {
+        let mut height = TOAST_HEIGHT;
+
+        if self.buttons().next().is_some() {
+            height += ACTIONS_HEIGHT;
+        }
+        if self.notification.progress.is_some() {
+            height += PROGRESS_HEIGHT;
+        }
+
+        height
+    }
+}
+
+/// Notification kept in the history, saved across restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    /// Unique across restarts, unlike the ids of the notifications
+    key: u64,
+    id: u32,
+    app_name: String,
+    summary: String,
+    body: String,
+    stack_tag: Option<String>,
+    /// Unix timestamp
+    received_at: i64,
+    /// Received since the start, the notifications can only replace these
+    #[serde(skip)]
+    live: bool,
+}
+
+impl HistoryEntry {
+    fn is_replaced_by(&self, notification: &Notification) -> bool {
+        self.live
+            && (self.id == notification.id
+                || (notification.stack_tag.is_some()
+                    && notification.stack_tag == self.stack_tag
+                    && notification.app_name == self.app_name))
+    }
+
+    fn matches(&self, search: &str) -> bool {
+        [&self.app_name, &self.summary, &self.body]
+            .iter()
+            .any(|field| field.to_lowercase().contains(search))
+    }
+
+    fn received_at(&self) -> String {
+        let Some(date) = DateTime::from_timestamp(self.received_at, 0) else {
+            return String::new();
+        };
+        let date = date.with_timezone(&Local);
+
+        if date.date_naive() == Local::now().date_naive() {
+            date.format("%R").to_string()
+        } else {
+            date.format("%d %b %R").to_string()
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryState {
+    history: Vec<HistoryEntry>,
+}
+
+/// Number of notifications in a stack.
+fn badge<'a>(count: usize, theme: &AshellTheme) -> Element<'a, Message> {
+    let radius = theme.radius.lg;
+
+    container(text(count.to_string()).size(theme.font_size.xs))
+        .padding([0, theme.space.xs])
+        .style(move |iced_theme: &Theme| container::Style {
+            background: Some(iced_theme.extended_palette().secondary.base.color.into()),
+            text_color: Some(iced_theme.extended_palette().secondary.base.text),
+            border: Border {
+                radius: radius.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+/// Popups of the desktop notifications, shown on their own layer surface,
+/// and the history of the notifications.
+pub struct Toasts {
+    config: NotificationsConfig,
+    /// Newest first
+    toasts: Vec<Toast>,
+    /// Layer surface showing the toasts, with its height and corner
+    surface: Option<(Id, u32, NotificationPosition)>,
+    /// Notifications received, newest first
+    history: Vec<HistoryEntry>,
+    next_key: u64,
+    /// Lowercase text searched in the history
+    search: String,
+    /// Applications whose notifications are all listed in the history menu
+    expanded: HashSet<String>,
+    /// Notifications with an incomplete progress, newest first
+    in_progress: Vec<Notification>,
+    next_script_id: u32,
+    /// Do not disturb turned on by the user
+    dnd: bool,
+    compositor: Option<CompositorService>,
+    privacy: Option<PrivacyService>,
+}
+
+/// Whether the time is in one of the `HH:MM-HH:MM` ranges, they can span midnight.
+fn in_schedule(schedule: &[String], now: NaiveTime) -> bool {
+    schedule.iter().any(|range| {
+        let parsed = range.split_once('-').and_then(|(start, end)| {
+            Some((
+                NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+                NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+            ))
+        });
+
+        match parsed {
+            Some((start, end)) if start <= end => start <= now && now < end,
+            Some((start, end)) => now >= start || now < end,
+            None => {
+                warn!("Invalid do not disturb range '{range}', expected HH:MM-HH:MM");
+                false
+            }
+        }
+    })
+}
+
+/// Plays a sound file, or a sound of the theme by its name.
+fn play_sound(sound: String) {
+    tokio::spawn(async move {
+        let flag = if sound.contains('/') { "-f" } else { "-i" };
+
+        if let Err(e) = Command::new("canberra-gtk-play")
+            .arg(flag)
+            .arg(&sound)
+            .status()
+            .await
+        {
+            warn!("Failed to play the notification sound '{sound}': {e}");
+        }
+    });
+}
+
+impl Toasts {
+    pub fn new(config: NotificationsConfig) -> Self {
+        let mut history = read_state::<HistoryState>(HISTORY_STATE_FILE).history;
+        history.truncate(config.history_size);
+        let next_key = history.iter().map(|entry| entry.key + 1).max().unwrap_or(0);
+
+        Self {
+            config,
+            toasts: Vec::new(),
+            surface: None,
+            history,
+            next_key,
+            search: String::new(),
+            expanded: HashSet::new(),
+            in_progress: Vec::new(),
+            next_script_id: SCRIPT_IDS,
+            dnd: false,
+            compositor: None,
+            privacy: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn do_not_disturb(&self) -> DoNotDisturb {
+        let fullscreen = self.config.dnd_fullscreen
+            && self
+                .compositor
+                .as_ref()
+                .is_some_and(|service| service.monitors.iter().any(|monitor| monitor.fullscreen));
+        let screen_sharing = self.config.dnd_screen_sharing
+            && self.privacy.as_ref().is_some_and(|service| {
+                !service.screencasts().is_empty()
+                    || service
+                        .nodes()
+                        .iter()
+                        .any(|node| node.media == Media::Video)
+            });
+
+        if self.dnd {
+            DoNotDisturb::Manual
+        } else if fullscreen
+            || screen_sharing
+            || in_schedule(&self.config.dnd_schedule, Local::now().time())
+        {
+            DoNotDisturb::Automatic
+        } else {
+            DoNotDisturb::Off
+        }
+    }
+
+    /// The layer surface showing the toasts.
+    pub fn surface(&self) -> Option<Id> {
+        self.surface.map(|(id, _, _)| id)
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        self.config.output.as_deref()
+    }
+
+    fn visible(&self) -> &[Toast] {
+        &self.toasts[..self.toasts.len().min(self.config.max_visible)]
+    }
+
+    fn height(&self) -> u32 {
+        let visible = self.visible();
+
+        visible.iter().map(Toast::height).sum::<u32>()
+            + SPACING * (visible.len() as u32).saturating_sub(1)
+    }
+
+    /// The timeout of the rule of the application, or of the urgency level,
+    /// wins over the one chosen by the application.
+    fn expires_at(&self, notification: &Notification, timeout: Option<u64>) -> Option<Instant> {
+        let timeout = match (timeout, notification.expiration) {
+            (Some(0), _) | (None, Expiration::Never) => return None,
+            (Some(timeout), _) => Duration::from_millis(timeout),
+            (None, Expiration::After(timeout)) => timeout,
+            (None, Expiration::Default) => Duration::from_millis(self.config.timeout),
+        };
+
+        Some(Instant::now() + timeout)
+    }
+
+    /// Removes the toast of the notification, with the ones stacked under it.
+    fn remove(&mut self, id: u32, reason: CloseReason) {
+        for toast in &mut self.toasts {
+            toast.stacked.retain(|stacked| *stacked != id);
+        }
+
+        if let Some(index) = self
+            .toasts
+            .iter()
+            .position(|toast| toast.notification.id == id)
+        {
+            let toast = self.toasts.remove(index);
+
+            for id in toast.stacked.into_iter().chain([id]) {
+                if id < SCRIPT_IDS {
+                    notifications::closed(id, reason);
+                }
+            }
+        }
+    }
+
+    fn save_history(&self) {
+        write_state(
+            HISTORY_STATE_FILE,
+            &HistoryState {
+                history: self.history.clone(),
+            },
+        );
+    }
+
+    /// Adds the notification to the history, in place of the one it replaces.
+    fn record(&mut self, notification: &Notification) {
+        self.history
+            .retain(|entry| !entry.is_replaced_by(notification));
+        self.history.insert(
+            0,
+            HistoryEntry {
+                key: self.next_key,
+                id: notification.id,
+                app_name: notification.app_name.clone(),
+                summary: notification.summary.clone(),
+                body: notification.body.clone(),
+                stack_tag: notification.stack_tag.clone(),
+                received_at: Local::now().timestamp(),
+                live: true,
+            },
+        );
+        self.next_key += 1;
+        self.history.truncate(self.config.history_size);
+        self.save_history();
+    }
+
+    /// History entries matching the search.
+    fn found(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.history
+            .iter()
+            .filter(|entry| entry.matches(&self.search))
+    }
+
+    fn notify(&mut self, mut notification: Notification) {
+        let rule = self
+            .config
+            .rules
+            .iter()
+            .find(|rule| rule.app_name.eq_ignore_ascii_case(&notification.app_name));
+        if let Some(urgency) = rule.and_then(|rule| rule.urgency) {
+            notification.urgency = urgency;
+        }
+        let silenced = rule.is_some_and(|rule| rule.silence);
+        let urgency = self.config.urgency(notification.urgency);
+        let sound = rule
+            .and_then(|rule| rule.sound.clone())
+            .or_else(|| urgency.sound.clone());
+        let timeout = rule.and_then(|rule| rule.timeout).or(urgency.timeout);
+
+        self.record(&notification);
+
+        // the progress is followed in the bar even while the popups are silenced
+        self.in_progress
+            .retain(|current| !notification.replaces(current));
+        if notification.progress.is_some_and(|progress| progress < 100) {
+            self.in_progress.insert(0, notification.clone());
+        }
+
+        // the critical notifications get through the do not disturb mode
+        if silenced
+            || (notification.urgency != Urgency::Critical
+                && self.do_not_disturb() != DoNotDisturb::Off)
+        {
+            return;
+        }
+        if let Some(sound) = sound {
+            play_sound(sound);
+        }
+
+        let expires_at = self.expires_at(&notification, timeout);
+        self.show(notification, expires_at);
+    }
+
+    /// Shows the toast of the notification, stacked on the one of the same application.
+    fn show(&mut self, notification: Notification, expires_at: Option<Instant>) {
+        // a replaced notification keeps its place, and the progress
+        // notifications update in place instead of piling up
+        if let Some(current) = self
+            .toasts
+            .iter_mut()
+            .find(|current| notification.replaces(&current.notification))
+        {
+            let replaced = std::mem::replace(&mut current.notification, notification);
+            current.expires_at = expires_at;
+
+            if replaced.id != current.notification.id {
+                notifications::closed(replaced.id, CloseReason::Closed);
+            }
+            return;
+        }
+
+        for toast in &mut self.toasts {
+            toast.stacked.retain(|stacked| *stacked != notification.id);
+        }
+
+        match self.toasts.first_mut() {
+            Some(newest)
+                if self.config.group && newest.notification.app_name == notification.app_name =>
+            {
+                let previous = std::mem::replace(&mut newest.notification, notification);
+                newest.stacked.push(previous.id);
+                newest.expires_at = expires_at;
+            }
+            _ => self.toasts.insert(
+                0,
+                Toast {
+                    notification,
+                    expires_at,
+                    stacked: Vec::new(),
+                },
+            ),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Event(NotificationEvent::Notify(notification)) => self.notify(notification),
+            Message::Event(NotificationEvent::Close(id)) => {
+                self.in_progress.retain(|current| current.id != id);
+                self.remove(id, CloseReason::Closed);
+            }
+            // the scripts get their feedback on screen even without the daemon,
+            // it isn't kept in the history
+            Message::Toast {
+                text,
+                icon,
+                timeout,
+            } => {
+                let (summary, body) = text.split_once('\n').unwrap_or((&text, ""));
+                let notification = Notification {
+                    id: self.next_script_id,
+                    app_name: "ashell".to_owned(),
+                    summary: summary.to_owned(),
+                    body: body.to_owned(),
+                    actions: Vec::new(),
+                    urgency: Urgency::Normal,
+                    expiration: Expiration::Default,
+                    icon: icon.as_deref().and_then(notifications::icon),
+                    stack_tag: None,
+                    progress: None,
+                };
+                self.next_script_id = self.next_script_id.checked_add(1).unwrap_or(SCRIPT_IDS);
+
+                let expires_at = self.expires_at(&notification, timeout);
+                self.show(notification, expires_at);
+            }
+            Message::Tick(now) => {
+                let expired = self
+                    .toasts
+                    .iter()
+                    .filter(|toast| toast.expires_at.is_some_and(|expires_at| expires_at <= now))
+                    .map(|toast| toast.notification.id)
+                    .collect::<Vec<_>>();
+
+                for id in expired {
+                    self.remove(id, CloseReason::Expired);
+                }
+            }
+            Message::Dismiss(id) => {
+                self.in_progress.retain(|current| current.id != id);
+                self.remove(id, CloseReason::Dismissed);
+            }
+            Message::Action(id, key) => {
+                notifications::invoke_action(id, key);
+                self.in_progress.retain(|current| current.id != id);
+                self.remove(id, CloseReason::Dismissed);
+            }
+            Message::ToggleGroup(app_name) => {
+                if !self.expanded.remove(&app_name) {
+                    self.expanded.insert(app_name);
+                }
+            }
+            Message::ClearGroup(app_name) => {
+                self.history
+                    .retain(|entry| entry.app_name != app_name || !entry.matches(&self.search));
+                self.expanded.remove(&app_name);
+                self.save_history();
+            }
+            Message::ClearHistory => {
+                self.history.retain(|entry| !entry.matches(&self.search));
+                self.expanded.clear();
+                self.save_history();
+            }
+            Message::Delete(key) => {
+                self.history.retain(|entry| entry.key != key);
+                self.save_history();
+            }
+            Message::SearchChanged(search) => self.search = search.to_lowercase(),
+            Message::ToggleDoNotDisturb => self.dnd = !self.dnd,
+            Message::Compositor(event) => match event {
+                ServiceEvent::Init(service) => self.compositor = Some(service),
+                ServiceEvent::Update(event) => {
+                    if let Some(service) = self.compositor.as_mut() {
+                        service.update(event);
+                    }
+                }
+                ServiceEvent::Error(_) => {}
+            },
+            Message::Privacy(event) => match event {
+                ServiceEvent::Init(service) => self.privacy = Some(service),
+                ServiceEvent::Update(event) => {
+                    if let Some(service) = self.privacy.as_mut() {
+                        service.update(event);
+                    }
+                }
+                ServiceEvent::Error(_) => self.privacy = None,
+            },
+            Message::ConfigReloaded(config) => {
+                if !config.enabled {
+                    self.toasts.clear();
+                }
+                if !config.dnd_fullscreen {
+                    self.compositor = None;
+                }
+                if !config.dnd_screen_sharing {
+                    self.privacy = None;
+                }
+                if self.history.len() > config.history_size {
+                    self.history.truncate(config.history_size);
+                    self.save_history();
+                }
+                self.config = config;
+            }
+        }
+    }
+
+    /// Creates, resizes or destroys the layer surface to fit the toasts.
+    pub fn sync_surface<M: 'static>(&mut self, output: Option<WlOutput>) -> Task<M> {
+        let height = self.height();
+        let position = self.config.position;
+
+        match self.surface {
+            Some((id, _, current)) if height == 0 || current != position => {
+                self.surface = None;
+
+                Task::batch(vec![destroy_layer_surface(id), self.sync_surface(output)])
+            }
+            Some((id, current, _)) if current != height => {
+                self.surface = Some((id, height, position));
+
+                set_size(id, Some(WIDTH), Some(height))
+            }
+            Some(_) => Task::none(),
+            None if height == 0 => Task::none(),
+            None => {
+                let id = Id::unique();
+                self.surface = Some((id, height, position));
+
+                get_layer_surface(SctkLayerSurfaceSettings {
+                    id,
+                    namespace: "ashell-notifications".to_string(),
+                    size: Some((Some(WIDTH), Some(height))),
+                    layer: Layer::Overlay,
+                    keyboard_interactivity: KeyboardInteractivity::None,
+                    margin: IcedMargin {
+                        top: MARGIN,
+                        right: MARGIN,
+                        bottom: MARGIN,
+                        left: MARGIN,
+                    },
+                    output: output.map_or(IcedOutput::Active, IcedOutput::Output),
+                    anchor: match position {
+                        NotificationPosition::TopLeft => Anchor::TOP | Anchor::LEFT,
+                        NotificationPosition::TopRight => Anchor::TOP | Anchor::RIGHT,
+                        NotificationPosition::BottomLeft => Anchor::BOTTOM | Anchor::LEFT,
+                        NotificationPosition::BottomRight => Anchor::BOTTOM | Anchor::RIGHT,
+                    },
+                    ..Default::default()
+                })
+            }
+        }
+    }
+
+    fn icon_view<'a>(icon: &TrayIcon, theme: &AshellTheme) -> Element<'a, Message> {
+        let size = Length::Fixed(theme.font_size.xl as f32 * 2.);
+
+        match icon {
+            TrayIcon::Image(handle) => Image::new(handle.clone()).width(size).height(size).into(),
+            TrayIcon::Svg(handle) => Svg::new(handle.clone()).width(size).height(size).into(),
+        }
+    }
+
+    fn toast_view<'a>(&'a self, toast: &'a Toast, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let notification = &toast.notification;
+        let color = self.config.urgency(notification.urgency).color;
+        let title = if notification.summary.is_empty() {
+            notification.app_name.as_str()
+        } else {
+            notification.summary.as_str()
+        };
+        let buttons = toast.buttons().collect::<Vec<_>>();
+
+        let content = column!(
+            Row::new()
+                .push_maybe(
+                    notification
+                        .icon
+                        .as_ref()
+                        .map(|icon| Self::icon_view(icon, theme))
+                )
+                .push(
+                    column!(
+                        Row::new()
+                            .push(text(title).size(theme.font_size.md).style(
+                                move |iced_theme: &Theme| text::Style {
+                                    color: color.map(|color| color.color(iced_theme)),
+                                }
+                            ))
+                            .push_maybe(
+                                (!toast.stacked.is_empty())
+                                    .then(|| badge(toast.stacked.len() + 1, theme))
+                            )
+                            .spacing(theme.space.xs)
+                            .align_y(Alignment::Center),
+                        text(notification.body.as_str()).size(theme.font_size.sm),
+                    )
+                    .push_maybe(notification.progress.map(|progress| level_bar(
+                        progress.into(),
+                        color,
+                        6.,
+                        theme
+                    )))
+                    .spacing(theme.space.xxs)
+                    .width(Length::Fill),
+                )
+                .push(
+                    button(icon(StaticIcon::Close).size(theme.font_size.sm))
+                        .on_press(Message::Dismiss(notification.id))
+                        .padding(theme.space.xxs)
+                        .style(theme.ghost_button_style()),
+                )
+                .spacing(theme.space.sm)
+                .align_y(Alignment::Start)
+                .height(Length::Fill),
+        )
+        .push_maybe((!buttons.is_empty()).then(|| {
+            Row::with_children(buttons.into_iter().map(|(key, label)| {
+                button(text(label.as_str()).size(theme.font_size.sm))
+                    .on_press(Message::Action(notification.id, key.clone()))
+                    .padding([theme.space.xxs, theme.space.sm])
+                    .style(theme.outline_button_style())
+                    .into()
+            }))
+            .spacing(theme.space.xs)
+        }))
+        .spacing(theme.space.xs);
+
+        let has_default = notification.actions.iter().any(|(key, _)| key == "default");
+
+        mouse_area(
+            container(content)
+                .width(Length::Fill)
+                .height(toast.height() as f32)
+                .padding(theme.space.sm)
+                .clip(true)
+                .style(move |iced_theme: &Theme| container::Style {
+                    background: Some(
+                        iced_theme
+                            .palette()
+                            .background
+                            .scale_alpha(theme.menu.opacity)
+                            .into(),
+                    ),
+                    border: Border {
+                        color: color.map_or(
+                            iced_theme.extended_palette().secondary.base.color,
+                            |color| color.color(iced_theme),
+                        ),
+                        width: 1.,
+                        radius: theme.radius.lg.into(),
+                    },
+                    ..Default::default()
+                }),
+        )
+        .on_press(if has_default {
+            Message::Action(notification.id, "default".to_owned())
+        } else {
+            Message::Dismiss(notification.id)
+        })
+        .into()
+    }
+
+    pub fn view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let toasts = self
+            .visible()
+            .iter()
+            .map(|toast| self.toast_view(toast, theme));
+
+        // the newest toast is the nearest to the edge
+        let toasts: Vec<_> = match self.config.position {
+            NotificationPosition::TopLeft | NotificationPosition::TopRight => toasts.collect(),
+            NotificationPosition::BottomLeft | NotificationPosition::BottomRight => {
+                toasts.rev().collect()
+            }
+        };
+
+        Column::with_children(toasts).spacing(SPACING as f32).into()
+    }
+
+    fn bell(&self) -> StaticIcon {
+        if self.do_not_disturb() == DoNotDisturb::Off {
+            StaticIcon::Bell
+        } else {
+            StaticIcon::BellOff
+        }
+    }
+
+    /// A bell with the number of notifications in the history.
+    pub fn module_view<'a>(&self, theme: &AshellTheme) -> Element<'a, Message> {
+        let progress = self
+            .in_progress
+            .first()
+            .filter(|_| self.config.progress_in_bar)
+            .and_then(|notification| notification.progress);
+
+        Row::new()
+            .push(icon(self.bell()))
+            .push_maybe((!self.history.is_empty()).then(|| text(self.history.len().to_string())))
+            .push_maybe(progress.map(|progress| {
+                row!(
+                    container(level_bar(progress.into(), None, 4., theme))
+                        .width(Length::Fixed(48.)),
+                    text(format!("{progress}%")).size(theme.font_size.sm),
+                )
+                .spacing(theme.space.xxs)
+                .align_y(Alignment::Center)
+            }))
+            .spacing(theme.space.xxs)
+            .align_y(Alignment::Center)
+            .into()
+    }
+
+    fn history_entry_view<'a>(
+        entry: &'a HistoryEntry,
+        theme: &'a AshellTheme,
+    ) -> Element<'a, Message> {
+        row!(
+            column!(
+                text(entry.summary.as_str()),
+                text(entry.body.as_str()).size(theme.font_size.sm),
+            )
+            .width(Length::Fill),
+            text(entry.received_at()).size(theme.font_size.xs),
+            button(icon(StaticIcon::Close).size(theme.font_size.xs))
+                .on_press(Message::Delete(entry.key))
+                .padding(theme.space.xxs)
+                .style(theme.ghost_button_style()),
+        )
+        .spacing(theme.space.xs)
+        .align_y(Alignment::Start)
+        .into()
+    }
+
+    fn group_view<'a>(&'a self, app_name: &'a str, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let notifications = self
+            .found()
+            .filter(|entry| entry.app_name == app_name)
+            .collect::<Vec<_>>();
+        let count = notifications.len();
+        // every match is listed while searching
+        let expanded = self.expanded.contains(app_name) || !self.search.is_empty();
+
+        let header = Row::new()
+            .push(
+                text(if app_name.is_empty() {
+                    "Unknown application"
+                } else {
+                    app_name
+                })
+                .size(theme.font_size.sm),
+            )
+            .push_maybe((count > 1).then(|| badge(count, theme)))
+            .push(Space::with_width(Length::Fill))
+            .push_maybe((count > 1).then(|| {
+                button(icon(if expanded {
+                    StaticIcon::MenuOpen
+                } else {
+                    StaticIcon::MenuClosed
+                }))
+                .on_press(Message::ToggleGroup(app_name.to_owned()))
+                .padding([theme.space.xxs, theme.space.xs])
+                .style(theme.ghost_button_style())
+            }))
+            .push(
+                button(icon(StaticIcon::Close).size(theme.font_size.sm))
+                    .on_press(Message::ClearGroup(app_name.to_owned()))
+                    .padding([theme.space.xxs, theme.space.xs])
+                    .style(theme.ghost_button_style()),
+            )
+            .spacing(theme.space.xs)
+            .align_y(Alignment::Center);
+
+        // collapsed groups show their newest notification only
+        let shown = if expanded { count } else { 1 };
+
+        column!(
+            header,
+            Column::with_children(
+                notifications
+                    .into_iter()
+                    .take(shown)
+                    .map(|entry| Self::history_entry_view(entry, theme)),
+            )
+            .spacing(theme.space.xs)
+            .padding([0, theme.space.sm]),
+        )
+        .spacing(theme.space.xxs)
+        .into()
+    }
+
+    /// History of the notifications, grouped by application.
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let mut app_names = Vec::new();
+        for entry in self.found() {
+            if !app_names.contains(&entry.app_name.as_str()) {
+                app_names.push(entry.app_name.as_str());
+            }
+        }
+
+        let app_names_empty = app_names.is_empty();
+        let list: Element<'a, Message> = if app_names_empty {
+            text(if self.search.is_empty() {
+                "No notifications"
+            } else {
+                "No matching notifications"
+            })
+            .into()
+        } else {
+            Column::with_children(
+                app_names
+                    .into_iter()
+                    .map(|app_name| self.group_view(app_name, theme)),
+            )
+            .spacing(theme.space.sm)
+            .into()
+        };
+
+        column!(
+            row!(
+                text("Notifications")
+                    .size(theme.font_size.lg)
+                    .width(Length::Fill),
+                button(icon(self.bell()))
+                    .on_press(Message::ToggleDoNotDisturb)
+                    .padding([theme.space.xxs, theme.space.xs])
+                    .style(theme.ghost_button_style()),
+                button(text("Clear"))
+                    .on_press_maybe((!app_names_empty).then_some(Message::ClearHistory),)
+                    .padding([theme.space.xxs, theme.space.sm])
+                    .style(theme.outline_button_style()),
+            )
+            .spacing(theme.space.xs)
+            .align_y(Alignment::Center),
+            horizontal_rule(1),
+            text_input("Search notifications", &self.search)
+                .size(theme.font_size.md)
+                .padding([theme.space.xs, theme.space.md])
+                .style(theme.text_input_style())
+                .on_input(Message::SearchChanged),
+            list,
+        )
+        .spacing(theme.space.xs)
+        .into()
+    }
+
+    /// The shared services watched by the do not disturb rules.
+    pub fn consumers(&self) -> Vec<Consumer> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        [
+            (self.config.dnd_fullscreen, Consumer::DndFullscreen),
+            (self.config.dnd_screen_sharing, Consumer::DndScreenSharing),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, consumer)| enabled.then_some(consumer))
+        .collect()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch(vec![
+            if self.config.enabled {
+                notifications::subscription().map(Message::Event)
+            } else {
+                Subscription::none()
+            },
+            if self.toasts.iter().any(|toast| toast.expires_at.is_some()) {
+                every(TICK).map(Message::Tick)
+            } else {
+                Subscription::none()
+            },
+        ])
+    }
+}
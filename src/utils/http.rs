@@ -0,0 +1,160 @@
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use std::{collections::HashMap, io, process::Stdio, time::Duration};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Time to wait before the next request when the server rate limits the
+    /// client, from `Retry-After` or the reset time of the rate limit window.
+    pub fn retry_after(&self) -> Option<Duration> {
+        if !matches!(self.status, 403 | 429) {
+            return None;
+        }
+
+        if let Some(value) = self.header("Retry-After") {
+            return value
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .ok()
+                .or_else(|| {
+                    DateTime::parse_from_rfc2822(value)
+                        .ok()
+                        .map(|date| until(date.timestamp()))
+                });
+        }
+
+        // GitHub uses the `X-` prefix, GitLab doesn't, and both send the
+        // headers with every response, a 403 can also be a missing permission
+        let exhausted = self.status == 429
+            || self
+                .header("X-RateLimit-Remaining")
+                .or_else(|| self.header("RateLimit-Remaining"))
+                == Some("0");
+        if !exhausted {
+            return None;
+        }
+
+        self.header("X-RateLimit-Reset")
+            .or_else(|| self.header("RateLimit-Reset"))
+            .and_then(|reset| reset.parse::<i64>().ok())
+            .map(until)
+    }
+}
+
+fn until(timestamp: i64) -> Duration {
+    Duration::from_secs(u64::try_from(timestamp - Utc::now().timestamp()).unwrap_or_default())
+}
+
+/// Performs a GET request using the system `curl` binary.
+///
+/// Redirects are followed, only the headers of the final response are kept.
+pub async fn get(url: &str, headers: &[(&str, String)]) -> anyhow::Result<Response> {
+    request("GET", url, headers).await
+}
+
+/// Performs a request with the given method and an empty body.
+pub async fn request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, String)],
+) -> anyhow::Result<Response> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--location", "--include"])
+        .args(["--max-time", "30"])
+        .args(["--request", method])
+        // read from stdin to keep the tokens out of the process list
+        .args(["--header", "@-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if method != "GET" {
+        // some APIs reject body-less writes without a Content-Length
+        command.args(["--data", ""]);
+    }
+
+    let mut child = match command.arg("--").arg(url).spawn() {
+        Ok(child) => child,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            bail!("curl is not installed, it is needed to request {url}")
+        }
+        Err(e) => return Err(e).context("failed to run curl"),
+    };
+
+    // dropping stdin closes it, curl reads the headers until then
+    if let Some(mut stdin) = child.stdin.take() {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}\n"))
+            .collect::<String>();
+        stdin
+            .write_all(headers.as_bytes())
+            .await
+            .context("failed to pass the headers to curl")?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .context("failed to run curl")?;
+
+    if !output.status.success() {
+        bail!(
+            "curl failed for {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    parse_response(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_response(raw: &str) -> anyhow::Result<Response> {
+    let mut rest = raw;
+    let mut head = "";
+
+    // every redirect (and `100 Continue`) adds a header block
+    while rest.starts_with("HTTP/") {
+        let Some((block, body)) = rest
+            .split_once("\r\n\r\n")
+            .or_else(|| rest.split_once("\n\n"))
+        else {
+            head = rest;
+            rest = "";
+            break;
+        };
+        head = block;
+        rest = body;
+    }
+
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .context("malformed HTTP response")?;
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_owned()))
+        .collect();
+
+    Ok(Response {
+        status,
+        headers,
+        body: rest.to_owned(),
+    })
+}
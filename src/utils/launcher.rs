@@ -11,6 +11,33 @@ pub fn execute_command(command: String) {
     });
 }
 
+/// Runs the command with the given environment variables set.
+pub fn execute_command_with_env(command: String, envs: Vec<(&'static str, String)>) {
+    tokio::spawn(async move {
+        if let Err(e) = Command::new("bash")
+            .arg("-c")
+            .arg(&command)
+            .envs(envs)
+            .spawn()
+            .and_then(|mut child| child.wait())
+        {
+            log::warn!("Failed to execute command {command}: {e}");
+        }
+    });
+}
+
+pub fn open_url(url: String) {
+    tokio::spawn(async move {
+        if let Err(e) = Command::new("xdg-open")
+            .arg(&url)
+            .spawn()
+            .and_then(|mut child| child.wait())
+        {
+            log::warn!("Failed to open {url}: {e}");
+        }
+    });
+}
+
 pub fn suspend(cmd: String) {
     tokio::spawn(async move {
         let _ = Command::new("bash")
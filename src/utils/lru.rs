@@ -0,0 +1,71 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Map keeping at most `capacity` entries, the least recently used ones are
+/// evicted first.
+#[derive(Debug)]
+pub struct Lru<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    /// Incremented on each access, the entry with the lowest tick is the
+    /// least recently used
+    tick: u64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        self.entries.get_mut(key).map(|(value, used)| {
+            *used = tick;
+            value.clone()
+        })
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        self.entries.insert(key, (value, self.tick));
+        self.evict();
+    }
+
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> V {
+        match self.get(&key) {
+            Some(value) => value,
+            None => {
+                let value = f();
+                self.insert(key, value.clone());
+
+                value
+            }
+        }
+    }
+
+    /// Changes the capacity, a capacity of 0 disables the cache.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone())
+            else {
+                return;
+            };
+
+            self.entries.remove(&oldest);
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use std::time::Duration;
 
+pub mod http;
 pub mod launcher;
+pub mod lru;
+pub mod notification;
+pub mod state;
+pub mod xkb;
 
 #[derive(Debug, Clone, Copy)]
 pub enum IndicatorState {
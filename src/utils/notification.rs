@@ -0,0 +1,41 @@
+use log::warn;
+use std::collections::HashMap;
+use zbus::{Connection, proxy, zvariant::Value};
+
+/// Sends a desktop notification through the notification daemon of the session.
+pub fn send_notification(summary: String, body: String, icon: &'static str) {
+    tokio::spawn(async move {
+        if let Err(e) = notify(&summary, &body, icon).await {
+            warn!("Failed to send the notification '{summary}': {e}");
+        }
+    });
+}
+
+async fn notify(summary: &str, body: &str, icon: &str) -> zbus::Result<u32> {
+    let connection = Connection::session().await?;
+    let proxy = NotificationsProxy::new(&connection).await?;
+
+    proxy
+        .notify("ashell", 0, icon, summary, body, &[], HashMap::new(), -1)
+        .await
+}
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, &Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}
@@ -0,0 +1,44 @@
+use log::{debug, warn};
+use serde::{Serialize, de::DeserializeOwned};
+use std::path::PathBuf;
+
+/// Location of a runtime state file, `$XDG_STATE_HOME/ashell/<file_name>`
+pub fn state_file_path(file_name: &str) -> PathBuf {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| PathBuf::from(shellexpand::tilde("~/.local/state").as_ref()))
+        .join("ashell")
+        .join(file_name)
+}
+
+pub fn read_state<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let path = state_file_path(file_name);
+
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Failed to parse state file {path:?}: {e}");
+            T::default()
+        }),
+        Err(e) => {
+            debug!("No state file {path:?}: {e}");
+            T::default()
+        }
+    }
+}
+
+pub fn write_state<T: Serialize>(file_name: &str, value: &T) {
+    let path = state_file_path(file_name);
+
+    let res = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            let content = serde_json::to_string(value).map_err(std::io::Error::other)?;
+            std::fs::write(&path, content)
+        });
+
+    if let Err(e) = res {
+        warn!("Failed to write state file {path:?}: {e}");
+    }
+}
@@ -0,0 +1,77 @@
+use log::debug;
+use std::{collections::HashMap, path::PathBuf, sync::LazyLock};
+
+/// Layouts and variants listed in the xkb rules.
+#[derive(Debug, Default)]
+struct XkbLayouts {
+    /// Layout codes by the names of the layouts and variants
+    codes: HashMap<String, String>,
+    /// Names by layout code and variant, the variant is empty for the layouts
+    names: HashMap<(String, String), String>,
+}
+
+static LAYOUTS: LazyLock<XkbLayouts> = LazyLock::new(|| {
+    let path = std::env::var_os("XKB_CONFIG_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/usr/share/X11/xkb"))
+        .join("rules/evdev.lst");
+
+    match std::fs::read_to_string(&path) {
+        Ok(content) => parse_layouts(&content),
+        Err(e) => {
+            debug!("Failed to read the xkb layouts from {path:?}: {e}");
+            XkbLayouts::default()
+        }
+    }
+});
+
+/// Reads the xkb layouts in the background, before the first layout is shown.
+pub fn preload() {
+    std::thread::spawn(|| LazyLock::force(&LAYOUTS));
+}
+
+/// Parses the `layout` and `variant` sections of an xkb rules list,
+/// the variants are named `<variant> <layout>: <name>`.
+fn parse_layouts(content: &str) -> XkbLayouts {
+    let mut layouts = XkbLayouts::default();
+    let mut section = "";
+
+    for line in content.lines() {
+        if let Some(name) = line.strip_prefix('!') {
+            section = name.trim();
+            continue;
+        }
+        let Some((code, name)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+
+        let (layout, variant, name) = match section {
+            "layout" => (code, "", name.trim()),
+            "variant" => match name.trim().split_once(':') {
+                Some((layout, name)) => (layout, code, name.trim()),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        layouts.codes.insert(name.to_owned(), layout.to_owned());
+        layouts
+            .names
+            .insert((layout.to_owned(), variant.to_owned()), name.to_owned());
+    }
+
+    layouts
+}
+
+/// Code of the layout with this name, e.g. `us` for `English (US, intl., with dead keys)`.
+pub fn layout_code(name: &str) -> Option<&'static str> {
+    LAYOUTS.codes.get(name).map(String::as_str)
+}
+
+/// Name of a layout, or of one of its variants.
+pub fn layout_name(layout: &str, variant: &str) -> Option<&'static str> {
+    LAYOUTS
+        .names
+        .get(&(layout.to_owned(), variant.to_owned()))
+        .map(String::as_str)
+}
@@ -0,0 +1,116 @@
+use crate::{config::ModuleName, services::registry::Backend};
+use iced::{Subscription, time::every};
+use log::warn;
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+use tokio::task;
+
+/// Interval of the checks for the panics of the subscriptions.
+const INTERVAL: Duration = Duration::from_secs(10);
+
+/// Subscription restarted by the watchdog after a panic.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Guarded {
+    Module(ModuleName),
+    Backend(Backend),
+}
+
+/// Task running each guarded subscription, recorded from its messages so
+/// that the panic hook can tell which one died.
+static TASKS: Mutex<Vec<(Guarded, task::Id)>> = Mutex::new(Vec::new());
+
+/// Guarded subscriptions that panicked since the last check, the stream of
+/// a subscription that panicked is dropped by the runtime without anything
+/// restarting it.
+static PANICKED: Mutex<Vec<Guarded>> = Mutex::new(Vec::new());
+
+/// Called by the panic hook.
+pub fn record_panic() {
+    let Some(id) = task::try_id() else {
+        return;
+    };
+
+    let guarded = TASKS.lock().ok().and_then(|mut tasks| {
+        let index = tasks.iter().position(|(_, task)| *task == id)?;
+
+        Some(tasks.swap_remove(index).0)
+    });
+
+    if let Some(guarded) = guarded
+        && let Ok(mut panicked) = PANICKED.lock()
+    {
+        panicked.push(guarded);
+    }
+}
+
+fn record_task(guarded: &Guarded) {
+    let Some(id) = task::try_id() else {
+        return;
+    };
+
+    if let Ok(mut tasks) = TASKS.lock() {
+        match tasks.iter_mut().find(|(name, _)| name == guarded) {
+            Some((_, task)) => *task = id,
+            None => tasks.push((guarded.clone(), id)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Check,
+}
+
+/// Restarts the subscriptions of the modules and of the backends that
+/// panicked, by changing their identity.
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    generations: HashMap<Guarded, usize>,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        // the panics of a previous watchdog are already handled
+        if let Ok(mut panicked) = PANICKED.lock() {
+            panicked.clear();
+        }
+
+        Self::default()
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Check => {
+                let panicked = PANICKED
+                    .lock()
+                    .map(|mut panicked| std::mem::take(&mut *panicked))
+                    .unwrap_or_default();
+
+                for guarded in panicked {
+                    warn!("The subscription of {guarded:?} panicked, restarting it");
+                    *self.generations.entry(guarded).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// Ties the subscription to its generation, it is restarted when it
+    /// panics. Panics before its first message aren't detected.
+    pub fn guard<M: 'static>(
+        &self,
+        guarded: Guarded,
+        subscription: Subscription<M>,
+    ) -> Subscription<M> {
+        let generation = self.generations.get(&guarded).copied().unwrap_or_default();
+
+        subscription
+            .with((guarded, generation))
+            .map(|((guarded, _), message)| {
+                record_task(&guarded);
+                message
+            })
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        every(INTERVAL).map(|_| Message::Check)
+    }
+}
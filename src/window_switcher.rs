@@ -0,0 +1,209 @@
+use crate::{
+    services::{
+        ReadOnlyService, Service, ServiceEvent,
+        compositor::{CompositorCommand, CompositorService, CompositorWindow},
+        tray::{TrayIcon, get_icon_from_name},
+    },
+    theme::AshellTheme,
+};
+use iced::{
+    Alignment, Element, Length, Task,
+    widget::{Column, Image, Space, Svg, button, column, row, text, text_input},
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(ServiceEvent<CompositorService>),
+    FilterChanged(String),
+    Submit,
+    Focus(String),
+}
+
+pub enum Action {
+    None,
+    /// A window has been picked, the menu should close
+    Focus(Task<Message>),
+}
+
+/// Open windows of the compositor, filtered by typing and focused on click.
+#[derive(Default)]
+pub struct WindowSwitcher {
+    service: Option<CompositorService>,
+    filter: String,
+    /// Icons of the application classes, looked up once
+    icons: HashMap<String, Option<TrayIcon>>,
+}
+
+fn input_id() -> text_input::Id {
+    text_input::Id::new("window_switcher")
+}
+
+/// Whether the characters of the filter appear in order in the text.
+fn fuzzy_match(text: &str, filter: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|c| chars.any(|next| next == c))
+}
+
+impl WindowSwitcher {
+    /// Clears the filter and gives it the keyboard focus, when the menu opens.
+    pub fn open(&mut self) -> Task<Message> {
+        self.filter.clear();
+
+        text_input::focus(input_id())
+    }
+
+    fn workspace_name(&self, window: &CompositorWindow) -> Option<&str> {
+        let service = self.service.as_ref()?;
+        let id = window.workspace_id?;
+
+        service
+            .workspaces
+            .iter()
+            .find(|workspace| workspace.id == id)
+            .map(|workspace| workspace.name.as_str())
+    }
+
+    /// Windows matching the filter, the focused one last so that the
+    /// first entry switches to another window.
+    fn windows(&self) -> Vec<&CompositorWindow> {
+        let Some(service) = self.service.as_ref() else {
+            return Vec::new();
+        };
+        let focused = service
+            .active_window
+            .as_ref()
+            .map(|window| window.address.as_str());
+
+        let mut windows = service
+            .windows
+            .iter()
+            .filter(|window| {
+                let workspace = self.workspace_name(window).unwrap_or_default();
+
+                fuzzy_match(
+                    &format!("{} {} {workspace}", window.title, window.class),
+                    &self.filter,
+                )
+            })
+            .collect::<Vec<_>>();
+        windows.sort_by_key(|window| Some(window.id.as_str()) == focused);
+
+        windows
+    }
+
+    fn focus(&mut self, id: String) -> Action {
+        match self.service.as_mut() {
+            Some(service) => Action::Focus(
+                service
+                    .command(CompositorCommand::FocusWindow(id))
+                    .map(Message::Event),
+            ),
+            None => Action::None,
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Event(event) => {
+                match event {
+                    ServiceEvent::Init(service) => self.service = Some(service),
+                    ServiceEvent::Update(event) => {
+                        if let Some(service) = self.service.as_mut() {
+                            service.update(event);
+                        }
+                    }
+                    ServiceEvent::Error(_) => {}
+                }
+
+                if let Some(service) = self.service.as_ref() {
+                    // the icons of the closed applications are dropped
+                    self.icons.retain(|class, _| {
+                        service.windows.iter().any(|window| &window.class == class)
+                    });
+                    for window in &service.windows {
+                        self.icons
+                            .entry(window.class.clone())
+                            .or_insert_with(|| get_icon_from_name(&window.class.to_lowercase()));
+                    }
+                }
+
+                Action::None
+            }
+            Message::FilterChanged(filter) => {
+                self.filter = filter;
+
+                Action::None
+            }
+            Message::Submit => match self.windows().first().map(|window| window.id.clone()) {
+                Some(id) => self.focus(id),
+                None => Action::None,
+            },
+            Message::Focus(id) => self.focus(id),
+        }
+    }
+
+    fn icon_view<'a>(&self, class: &str, theme: &AshellTheme) -> Element<'a, Message> {
+        let size = Length::Fixed(theme.font_size.xl as f32);
+
+        match self.icons.get(class).and_then(Option::as_ref) {
+            Some(TrayIcon::Image(handle)) => {
+                Image::new(handle.clone()).width(size).height(size).into()
+            }
+            Some(TrayIcon::Svg(handle)) => Svg::new(handle.clone()).width(size).height(size).into(),
+            None => Space::new(size, size).into(),
+        }
+    }
+
+    pub fn menu_view<'a>(&'a self, theme: &'a AshellTheme) -> Element<'a, Message> {
+        let windows = self.windows();
+        let list: Element<'a, Message> = if windows.is_empty() {
+            text("No windows").into()
+        } else {
+            Column::with_children(windows.into_iter().map(|window| {
+                let details = match self.workspace_name(window) {
+                    Some(workspace) => format!("{} · workspace {workspace}", window.class),
+                    None => window.class.clone(),
+                };
+
+                button(
+                    row!(
+                        self.icon_view(&window.class, theme),
+                        column!(
+                            text(window.title.clone()),
+                            text(details).size(theme.font_size.xs),
+                        )
+                        .width(Length::Fill),
+                    )
+                    .spacing(theme.space.sm)
+                    .align_y(Alignment::Center),
+                )
+                .on_press(Message::Focus(window.id.clone()))
+                .padding([theme.space.xxs, theme.space.sm])
+                .width(Length::Fill)
+                .style(theme.ghost_button_style())
+                .into()
+            }))
+            .spacing(theme.space.xxs)
+            .into()
+        };
+
+        column!(
+            text_input("Search windows", &self.filter)
+                .id(input_id())
+                .size(theme.font_size.md)
+                .padding([theme.space.xs, theme.space.md])
+                .style(theme.text_input_style())
+                .on_input(Message::FilterChanged)
+                .on_submit(Message::Submit),
+            list,
+        )
+        .spacing(theme.space.sm)
+        .into()
+    }
+}